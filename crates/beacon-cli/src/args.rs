@@ -2,7 +2,11 @@ use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 
-use crate::cli::{PlanCommands, StepCommands};
+use crate::cli::{
+    BadgeArgs, BenchArgs, CompleteArgs, CompletionsArgs, ConfigCommands, DbCommands,
+    DedupeReportArgs, EventsArgs, MetricsArgs, PlanCommands, StatsArgs, StepCommands,
+    TemplateCommands,
+};
 
 /// Main command-line interface for Beacon task management tool
 ///
@@ -14,28 +18,55 @@ use crate::cli::{PlanCommands, StepCommands};
 #[derive(Parser)]
 #[command(version, about, name = "b")]
 pub struct Args {
-    /// Path to the SQLite database file. Defaults to
-    /// $XDG_DATA_HOME/beacon/beacon.db
+    /// Path to the SQLite database file. Defaults to BEACON_DATABASE_FILE,
+    /// then database_file in the configuration file, then
+    /// $XDG_DATA_HOME/beacon/beacon.db. `:memory:` uses a throwaway
+    /// in-memory database
     #[arg(long, global = true)]
     pub database_file: Option<PathBuf>,
 
-    /// Disable colored output and use plain text
+    /// Disable colored output and use plain text. Setting BEACON_NO_COLOR or
+    /// no_color in the configuration file has the same effect
     #[arg(long, global = true)]
     pub no_color: bool,
 
-    /// Disable pager for output
+    /// Disable pager for output. Setting BEACON_NO_PAGER or no_pager in the
+    /// configuration file has the same effect
     #[arg(long, global = true)]
     pub no_pager: bool,
 
+    /// Print how plan listings were computed to stderr: the database used,
+    /// the effective filter and the plans remaining after each stage
+    #[arg(long, global = true)]
+    pub explain: bool,
+
+    /// Hide the completion streak and weekly trends shown in plan listings.
+    /// Setting BEACON_NO_MOMENTUM to any value has the same effect
+    #[arg(long, global = true)]
+    pub no_momentum: bool,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
 
 /// Available commands for the Beacon CLI
 ///
-/// The CLI is organized into three main command categories:
+/// The CLI is organized into the following command categories:
 /// - `plan`: Operations for managing task plans (create, list, archive, etc.)
 /// - `step`: Operations for managing individual steps within plans
+/// - `open`: Resolve a `beacon://` URL and show the referenced entity
+/// - `metrics`: Show simple productivity metrics
+/// - `streak`: Show the completion streak and weekly trends
+/// - `badge`: Print a one-line summary of a directory's plans for prompts
+/// - `stats`: Report what happened to plans and steps, for reviews
+/// - `dedupe-report`: Report open steps tracked in more than one place
+/// - `events`: List the changes made to plans and steps
+/// - `template`: Define lists of steps to add to plans by name
+/// - `config`: Read and change the settings stored in the database, and show
+///   the CLI configuration
+/// - `where`: Show where the database is stored
+/// - `db`: Maintain the database file
+/// - `completions`: Print a shell completion script
 /// - `serve`: Start the MCP server for AI assistant integration
 #[derive(Subcommand)]
 pub enum Commands {
@@ -51,6 +82,93 @@ pub enum Commands {
         #[command(subcommand)]
         command: StepCommands,
     },
+    /// Open a beacon:// URL and show the referenced plan, step or plan list
+    ///
+    /// Intended as the target of an editor's custom URL handler, e.g.
+    /// `b open beacon://open/plan/12`. A URL qualified with a workspace is
+    /// only opened against that workspace's database, e.g. `work.db` for
+    /// `?workspace=work`.
+    Open {
+        /// URL of the form beacon://open/<plan|step>/<id>[?workspace=<name>]
+        /// or beacon://open/plans[?workspace=<name>]
+        url: String,
+    },
+    /// Show productivity metrics such as steps completed this week
+    #[command(alias = "m")]
+    Metrics(MetricsArgs),
+    /// Show how many days in a row steps were completed, and how each plan's
+    /// last week compares with the week before
+    Streak,
+    /// Print how many active plans a directory and its subdirectories hold
+    /// and how many of their steps are pending, for shell prompts
+    ///
+    /// Prints nothing outside planned work. It runs on every prompt, so it
+    /// reads the database without starting the planner whenever it can,
+    /// e.g. `PS1='$(b badge) \$ '`.
+    Badge(BadgeArgs),
+    /// Report plans and steps created, completed and archived, the
+    /// completion rate, the average time to done and the busiest
+    /// directories, for weekly reviews
+    Stats(StatsArgs),
+    /// Report open steps with the same title, ignoring case, whitespace and
+    /// punctuation, to find work tracked more than once
+    DedupeReport(DedupeReportArgs),
+    /// List the changes made to plans and steps, oldest first
+    Events(EventsArgs),
+    /// Define named lists of steps and add them to plans
+    #[command(alias = "t")]
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommands,
+    },
+    /// Read and change settings stored in the database, which apply to the
+    /// CLI and the MCP server alike, and show the CLI's own configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Show the resolved database path and whether the file exists
+    #[command(alias = "db-path")]
+    Where,
+    /// Maintain the database file
+    Db {
+        #[command(subcommand)]
+        command: DbCommands,
+    },
+    /// Measure how long common operations take, to report performance
+    ///
+    /// Runs against a throwaway database seeded with synthetic plans; the
+    /// database given with --database-file is never touched.
+    Bench(BenchArgs),
+    /// Print a completion script for bash, zsh or fish
+    ///
+    /// Besides commands and flags, it completes plan IDs for `plan show`,
+    /// `archive` and `delete`, and step IDs for `step show` and `update`.
+    /// Load it with e.g. `source <(b completions bash)`.
+    Completions(CompletionsArgs),
+    /// Print completion candidates for a partial command line, one per line
+    #[command(hide = true)]
+    Complete(CompleteArgs),
     /// Start the MCP server
     Serve,
 }
+
+impl Commands {
+    /// Whether the command uses the terminal itself, asking for
+    /// confirmation or redrawing its output, where the pager would get in
+    /// the way.
+    pub fn uses_terminal(&self) -> bool {
+        match self {
+            Commands::Plan {
+                command: PlanCommands::Watch(_),
+            } => true,
+            Commands::Plan {
+                command: PlanCommands::Delete(args),
+            } => !args.yes && !args.dry_run,
+            Commands::Step {
+                command: StepCommands::Remove(args),
+            } => !args.yes && !args.dry_run,
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,367 @@
+//! Benchmark of common operations, for reporting performance with numbers.
+//!
+//! Runs against a throwaway database in a temporary directory seeded with
+//! synthetic plans, never against the user's own database, and reports the
+//! median wall-clock time of each operation together with the environment.
+
+use std::{
+    env::{consts, temp_dir},
+    fs,
+    future::Future,
+    path::{Path, PathBuf},
+    process,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use beacon_core::{
+    CreateStepRequest, Database, Planner, PlannerBuilder, StepStatus, UpdateStepRequest,
+    params::{CreatePlan, Id, ListPlans, SearchPlans},
+};
+use rmcp::serde_json::json;
+
+/// Number of plan directories the seeded plans are spread over.
+const DIRECTORIES: usize = 10;
+/// Number of step updates timed together as one sample.
+const STATUS_UPDATES: usize = 100;
+/// Time after which an operation takes no further samples, so that a run
+/// stays short on slow disks. Every operation is timed at least once.
+const OPERATION_BUDGET: Duration = Duration::from_secs(1);
+
+/// Size of the seeded database and how often each operation is timed.
+#[derive(Debug, Clone, Copy)]
+pub struct BenchConfig {
+    pub plans: usize,
+    pub steps: usize,
+    pub samples: usize,
+}
+
+/// Timings of one operation.
+#[derive(Debug)]
+pub struct Timing {
+    pub operation: String,
+    pub samples: Vec<Duration>,
+}
+
+impl Timing {
+    /// Returns the median of the samples.
+    pub fn median(&self) -> Duration {
+        let mut samples = self.samples.clone();
+        samples.sort();
+        match samples.len() {
+            0 => Duration::ZERO,
+            len if len % 2 == 1 => samples[len / 2],
+            len => (samples[len / 2 - 1] + samples[len / 2]) / 2,
+        }
+    }
+}
+
+/// Outcome of a benchmark run.
+#[derive(Debug)]
+pub struct BenchReport {
+    pub config: BenchConfig,
+    pub sqlite_version: &'static str,
+    pub journal_mode: String,
+    /// Time taken to seed the database
+    pub seed: Duration,
+    pub timings: Vec<Timing>,
+}
+
+impl BenchReport {
+    /// Renders the report as markdown, with a table of median timings.
+    pub fn to_markdown(&self) -> String {
+        let mut output = format!(
+            "# Benchmark\n\n\
+             - **Beacon**: {}\n\
+             - **OS**: {} ({})\n\
+             - **SQLite**: {}\n\
+             - **Journal mode**: {}\n\
+             - **Seeded**: {} plans × {} steps in {}\n\n\
+             | Operation | Median | Samples |\n\
+             |---|---:|---:|\n",
+            env!("CARGO_PKG_VERSION"),
+            consts::OS,
+            consts::ARCH,
+            self.sqlite_version,
+            self.journal_mode,
+            self.config.plans,
+            self.config.steps,
+            format_duration(self.seed),
+        );
+        for timing in &self.timings {
+            output.push_str(&format!(
+                "| {} | {} | {} |\n",
+                timing.operation,
+                format_duration(timing.median()),
+                timing.samples.len()
+            ));
+        }
+        output
+    }
+
+    /// Renders the report as a single line of JSON, to paste into issues.
+    pub fn to_json(&self) -> String {
+        let operations: Vec<_> = self
+            .timings
+            .iter()
+            .map(|timing| {
+                json!({
+                    "operation": timing.operation,
+                    "median_ms": milliseconds(timing.median()),
+                    "samples": timing.samples.len(),
+                })
+            })
+            .collect();
+
+        json!({
+            "beacon_version": env!("CARGO_PKG_VERSION"),
+            "os": consts::OS,
+            "arch": consts::ARCH,
+            "sqlite_version": self.sqlite_version,
+            "journal_mode": self.journal_mode,
+            "wal": self.journal_mode.eq_ignore_ascii_case("wal"),
+            "plans": self.config.plans,
+            "steps": self.config.steps,
+            "seed_ms": milliseconds(self.seed),
+            "operations": operations,
+        })
+        .to_string()
+    }
+}
+
+/// Seeds a throwaway database and times the operation mix against it.
+pub async fn run(config: BenchConfig) -> Result<BenchReport> {
+    let scratch = ScratchDir::create()?;
+    let database_path = scratch.path().join("bench.db");
+    let planner = PlannerBuilder::new()
+        .with_database_path(Some(&database_path))
+        .infer_git_root(false)
+        .build()
+        .await
+        .context("Failed to create benchmark database")?;
+
+    let started = Instant::now();
+    let plan_ids = seed(&planner, scratch.path(), config).await?;
+    let seed = started.elapsed();
+
+    let timings = time_operations(&planner, scratch.path(), &plan_ids, config).await?;
+
+    let journal_mode = Database::new(&database_path)
+        .and_then(|db| db.journal_mode())
+        .context("Failed to read the benchmark database's journal mode")?;
+
+    Ok(BenchReport {
+        config,
+        sqlite_version: Database::sqlite_version(),
+        journal_mode,
+        seed,
+        timings,
+    })
+}
+
+/// Creates `config.plans` plans of `config.steps` steps each, spread over a
+/// few directories, returning their IDs.
+async fn seed(planner: &Planner, root: &Path, config: BenchConfig) -> Result<Vec<u64>> {
+    let mut plan_ids = Vec::with_capacity(config.plans);
+    for index in 0..config.plans {
+        let plan = create_plan(planner, root, index, config.steps).await?;
+        plan_ids.push(plan);
+    }
+    Ok(plan_ids)
+}
+
+/// Creates synthetic plan number `index` with `steps` steps.
+async fn create_plan(planner: &Planner, root: &Path, index: usize, steps: usize) -> Result<u64> {
+    let params = CreatePlan {
+        title: format!("Benchmark plan {index}"),
+        description: Some("Synthetic plan seeded by `b bench`".to_string()),
+        directory: Some(directory(root, index)),
+        infer_git_root: Some(false),
+        ..Default::default()
+    };
+    let steps = (0..steps)
+        .map(|step| CreateStepRequest {
+            title: format!("Step {step} of plan {index}"),
+            description: Some("Synthetic step".to_string()),
+            acceptance_criteria: None,
+            result: None,
+            references: Vec::new(),
+            status: StepStatus::Todo,
+        })
+        .collect();
+
+    let plan = planner
+        .create_plan_with_steps(&params, steps)
+        .await
+        .context("Failed to seed benchmark plan")?;
+    Ok(plan.id)
+}
+
+/// Returns the directory of synthetic plan number `index`.
+fn directory(root: &Path, index: usize) -> String {
+    root.join(format!("project-{}", index % DIRECTORIES))
+        .display()
+        .to_string()
+}
+
+/// Times each operation of the mix, up to `config.samples` times.
+async fn time_operations(
+    planner: &Planner,
+    root: &Path,
+    plan_ids: &[u64],
+    config: BenchConfig,
+) -> Result<Vec<Timing>> {
+    let samples = config.samples;
+    let first_plan = plan_ids.first().copied().unwrap_or_default();
+    let step_ids: Vec<u64> = match plan_ids.first() {
+        Some(&id) => planner
+            .get_plan(&Id { id })
+            .await?
+            .map(|plan| plan.steps.iter().map(|step| step.id).collect())
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+
+    let mut timings = Vec::new();
+    let mut created = config.plans;
+    timings.push(
+        time("create plan", samples, |_| {
+            created += 1;
+            create_plan(planner, root, created, config.steps)
+        })
+        .await?,
+    );
+    timings.push(
+        time("list summaries", samples, |_| async {
+            planner.list_plans_summary(&ListPlans::default()).await?;
+            Ok(())
+        })
+        .await?,
+    );
+    timings.push(
+        time("show largest plan", samples, |_| async move {
+            planner.get_plan(&Id { id: first_plan }).await?;
+            Ok(())
+        })
+        .await?,
+    );
+    timings.push(
+        time("search by directory", samples, |sample| async move {
+            let params = SearchPlans {
+                directory: directory(root, sample),
+                ..Default::default()
+            };
+            planner.search_plans_summary(&params).await?;
+            Ok(())
+        })
+        .await?,
+    );
+    let updates = format!("{STATUS_UPDATES} status updates");
+    timings.push(time(&updates, samples, |_| update_statuses(planner, &step_ids)).await?);
+    timings.push(
+        time("claim loop", samples, |sample| {
+            claim_all(planner, plan_ids.get(sample + 1).copied())
+        })
+        .await?,
+    );
+
+    Ok(timings)
+}
+
+/// Times `operation` up to `samples` times, passing it the sample number,
+/// until it has used up the [`OPERATION_BUDGET`].
+async fn time<F, Fut, T>(name: &str, samples: usize, mut operation: F) -> Result<Timing>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut timing = Timing {
+        operation: name.to_string(),
+        samples: Vec::with_capacity(samples),
+    };
+    let mut spent = Duration::ZERO;
+    for sample in 0..samples {
+        let started = Instant::now();
+        operation(sample)
+            .await
+            .with_context(|| format!("Benchmark operation '{name}' failed"))?;
+        let elapsed = started.elapsed();
+        timing.samples.push(elapsed);
+
+        spent += elapsed;
+        if spent >= OPERATION_BUDGET {
+            break;
+        }
+    }
+    Ok(timing)
+}
+
+/// Moves steps between statuses, starting each step and setting it back to
+/// todo right after, so that every update changes the step and each sample
+/// leaves the steps as it found them.
+async fn update_statuses(planner: &Planner, step_ids: &[u64]) -> Result<()> {
+    let updates = step_ids.iter().cycle().flat_map(|&step_id| {
+        [
+            (step_id, StepStatus::InProgress),
+            (step_id, StepStatus::Todo),
+        ]
+    });
+    for (step_id, status) in updates.take(STATUS_UPDATES) {
+        let request = UpdateStepRequest {
+            status: Some(status),
+            ..Default::default()
+        };
+        planner.update_step(step_id, request).await?;
+    }
+    Ok(())
+}
+
+/// Claims the steps of a plan one after another, as an agent working
+/// through it would, until none is left to claim.
+async fn claim_all(planner: &Planner, plan_id: Option<u64>) -> Result<()> {
+    let Some(id) = plan_id else {
+        return Ok(());
+    };
+    let Some(plan) = planner.get_plan(&Id { id }).await? else {
+        return Ok(());
+    };
+    for step in &plan.steps {
+        planner.claim_step(&Id { id: step.id }).await?;
+    }
+    Ok(())
+}
+
+/// Formats a duration in milliseconds.
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2} ms", milliseconds(duration))
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Temporary directory holding the benchmark database, removed on drop.
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn create() -> Result<Self> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default();
+        let path = temp_dir().join(format!("beacon-bench-{}-{nanos}", process::id()));
+        fs::create_dir_all(&path)
+            .with_context(|| format!("Failed to create '{}'", path.display()))?;
+        Ok(Self(path))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
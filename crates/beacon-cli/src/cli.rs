@@ -1,326 +1,1868 @@
-use std::str::FromStr;
+use std::{
+    collections::BTreeMap,
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use beacon_core::{
-    CreateResult, Id, OperationStatus, Planner, StepStatus, UpdateResult, params::*,
+    BeaconUri, ConflictPolicy, CreateResult, DeleteResult, DuplicateGroups, Events, Id,
+    ImportOutcome, Momentum, OperationStatus, Page, Plan, PlanFilter, PlanStatus, PlanSummaries,
+    PlanSummary, Planner, PlannerBuilder, PlannerError, PromptBadge, SettingValues, StatusLabels,
+    Step, StepOutlines, StepReference, StepStatus, Steps, TemplateStep, Templates, TrashedSteps,
+    UpdateResult, UriTarget,
+    display::{PlanAt, StepAt, StepsMode},
+    models::duplicates::normalize_title,
+    params::*,
+    quick, quickadd,
+};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use jiff::{Timestamp, ToSpan, Zoned};
+use log::info;
+use tokio::runtime::Runtime;
+
+use crate::{
+    args::Args,
+    bench::{self, BenchConfig},
+    completion::{self, Candidate, Completion, Shell},
+    config::CliConfig,
+    git_log::{self, LogQuery},
+    renderer::{Output, PlanProgress, TerminalRenderer},
 };
-use clap::{Parser, Subcommand, ValueEnum};
 
-use crate::renderer::TerminalRenderer;
+/// Handle where command
+///
+/// Prints the database path the planner would use, resolved exactly as when
+/// building it, and whether the file exists yet.
+pub fn show_database_path(builder: &PlannerBuilder) -> Result<Output> {
+    let path = builder
+        .resolved_path()
+        .context("Failed to resolve database path")?;
+    let exists = if path.exists() { "yes" } else { "no" };
+
+    Ok(Output::new(format!(
+        "- **Database**: `{}`\n- **Exists**: {exists}\n",
+        path.display()
+    )))
+}
+
+/// Handle config show command
+///
+/// Prints each CLI option with its effective value and source, resolving the
+/// default database path without opening the database.
+pub fn show_config(config: &CliConfig, builder: &PlannerBuilder) -> Result<Output> {
+    let file = match &config.path {
+        Some(path) if config.file_found => format!("`{}`", path.display()),
+        Some(path) => format!("`{}` (not found)", path.display()),
+        None => "none, the home directory is unknown".to_string(),
+    };
+    let database_file = builder
+        .resolved_path()
+        .context("Failed to resolve database path")?;
+
+    Ok(Output::new(format!(
+        "# Configuration
+
+- **Configuration file**: {file}
+
+- database_file = `{}` ({})
+- no_color = {} ({})
+- no_pager = {} ({})
+- pager = `{}` ({})
+- format = {} ({})
+",
+        database_file.display(),
+        config.database_file.source,
+        config.no_color.value,
+        config.no_color.source,
+        config.no_pager.value,
+        config.no_pager.source,
+        config.pager.value,
+        config.pager.source,
+        config.format.value,
+        config.format.source,
+    )))
+}
+
+/// Handle the default listing without a planner
+///
+/// Lists active plans like [`Cli::list_plans`] through the quick read path,
+/// which fails rather than creating or migrating the database, so that the
+/// caller can fall back to the planner.
+pub fn quick_list_plans(builder: &PlannerBuilder, momentum: bool) -> Result<Output> {
+    if builder.is_in_memory() {
+        bail!("An in-memory database is only reachable through the planner");
+    }
+    let path = builder
+        .resolved_path()
+        .context("Failed to resolve database path")?;
+    let params = ListPlans {
+        archived: false,
+        ..Default::default()
+    };
+
+    let mut summaries = Page::all(
+        quick::quick_summaries(&path, Some(&PlanFilter::try_from(&params)?))
+            .context("Failed to list plans")?,
+    );
+    let momentum = if momentum {
+        let (momentum, by_plan) =
+            quick::quick_momentum(&path, &Zoned::now()).context("Failed to compute momentum")?;
+        add_trends(&mut summaries.items, &by_plan);
+        Some(momentum)
+    } else {
+        None
+    };
+
+    Ok(plan_listing(&params, momentum, summaries))
+}
+
+/// Sets the weekly trend of each listed plan from its momentum.
+fn add_trends(summaries: &mut [PlanSummary], by_plan: &BTreeMap<u64, Momentum>) {
+    for summary in summaries {
+        summary.momentum = by_plan.get(&summary.id).map(|momentum| momentum.trend);
+    }
+}
+
+/// Shows a plan listing, headed by the momentum unless it is empty.
+fn plan_listing(
+    params: &ListPlans,
+    momentum: Option<Momentum>,
+    summaries: Page<PlanSummary>,
+) -> Output {
+    let title = match (params.archived, params.children_of) {
+        (true, Some(parent_id)) => format!("Archived Child Plans of Plan {parent_id}"),
+        (false, Some(parent_id)) => format!("Active Child Plans of Plan {parent_id}"),
+        (true, None) => "Archived Plans".to_string(),
+        (false, None) => "Active Plans".to_string(),
+    };
+
+    let momentum = momentum
+        .filter(|momentum| !momentum.is_empty())
+        .map(|momentum| format!("**Momentum**: {momentum}\n\n"))
+        .unwrap_or_default();
+
+    let progress = summaries
+        .items
+        .iter()
+        .map(|summary| {
+            let header = format!("## {} (ID: {})", summary.title, summary.id);
+            PlanProgress::new(header, summary)
+        })
+        .collect();
+    Output::new(format!("# {title}\n\n{momentum}"))
+        .then(summaries)
+        .with_progress(progress)
+}
+
+/// Shows `step` as of now with its plan's status `labels`.
+fn labeled_step(step: Step, labels: StatusLabels) -> Output {
+    let now = Timestamp::now();
+    Output::from_fn(move |f| {
+        let step = StepAt::new(&step, now).with_status_labels(&labels);
+        write!(f, "{step}")
+    })
+}
+
+/// Handle bench command
+///
+/// Times the operation mix against a throwaway database and prints a table
+/// of medians, followed by the same report as JSON for pasting into issues.
+pub async fn bench(args: &BenchArgs, renderer: &TerminalRenderer) -> Result<()> {
+    let report = bench::run(args.config())
+        .await
+        .context("Benchmark failed")?;
+
+    renderer.render(report.to_markdown());
+    println!("{}", report.to_json());
+    Ok(())
+}
+
+/// Asks on the terminal what to do with an imported plan that conflicts
+/// with `existing`, showing both sides. Anything but "d" or "m" skips it.
+fn ask_conflict_policy(existing: &Plan, imported: &Plan) -> Result<ConflictPolicy> {
+    let titles: Vec<String> = existing
+        .steps
+        .iter()
+        .map(|step| normalize_title(&step.title))
+        .collect();
+    let missing = imported
+        .steps
+        .iter()
+        .filter(|step| !titles.contains(&normalize_title(&step.title)))
+        .count();
+    let side = |plan: &Plan| {
+        let done = plan
+            .steps
+            .iter()
+            .filter(|step| step.status == StepStatus::Done)
+            .count();
+        format!(
+            "{} step(s), {done} done, last updated {}",
+            plan.steps.len(),
+            plan.updated_at.strftime("%Y-%m-%d")
+        )
+    };
+
+    eprintln!(
+        "Plan '{}' already exists in {}",
+        imported.title,
+        imported.directory.as_deref().unwrap_or("no directory")
+    );
+    eprintln!("  existing (ID: {}): {}", existing.id, side(existing));
+    eprintln!(
+        "  imported: {}, {missing} of them not in the existing plan",
+        side(imported)
+    );
+    eprint!("[s]kip, import as a [d]uplicate, or [m]erge the missing steps? [S/d/m] ");
+    io::stderr()
+        .flush()
+        .context("Failed to ask how to resolve the conflict")?;
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .context("Failed to read how to resolve the conflict")?;
+    Ok(match answer.trim().to_lowercase().as_str() {
+        "d" | "duplicate" => ConflictPolicy::DuplicateWithSuffix,
+        "m" | "merge" => ConflictPolicy::Merge,
+        _ => ConflictPolicy::Skip,
+    })
+}
+
+/// Handle complete command
+///
+/// Prints the candidates for the word under the cursor, one per line. It
+/// runs on every tab press, so plan and step IDs are read through a
+/// read-only planner, and when the database can't be read, e.g. because it
+/// doesn't exist yet, no IDs are offered and nothing is reported.
+pub fn complete(args: &CompleteArgs, builder: PlannerBuilder) -> Result<()> {
+    let builder = match completion::database_file(&args.words) {
+        Some(path) => builder.with_database_path(Some(path)),
+        None => builder,
+    };
+
+    let candidates = match completion::complete(&mut Args::command(), &args.words) {
+        Completion::Candidates(candidates) => candidates,
+        completion => {
+            let lookup = async {
+                let planner = builder.read_only(true).build().await?;
+                id_candidates(&planner, &completion).await
+            };
+            Runtime::new()
+                .context("Failed to create tokio runtime")?
+                .block_on(lookup)
+                .unwrap_or_else(|e| {
+                    info!("No IDs to complete: {e:#}");
+                    Vec::new()
+                })
+        }
+    };
+
+    let current = args.words.last().map_or("", String::as_str);
+    for candidate in candidates
+        .iter()
+        .filter(|candidate| candidate.value.starts_with(current))
+    {
+        println!("{candidate}");
+    }
+    Ok(())
+}
+
+/// Handle badge command
+///
+/// Prints the badge of the active plans in a directory, or nothing when
+/// there are none. It runs on every prompt, so it reads through the quick
+/// path, and only builds a planner when that fails, e.g. because the
+/// database doesn't exist yet or needs migrating.
+pub fn badge(args: &BadgeArgs, builder: PlannerBuilder) -> Result<()> {
+    let quick = if builder.is_in_memory() {
+        None
+    } else {
+        let path = builder
+            .resolved_path()
+            .context("Failed to resolve database path")?;
+        quick::quick_badge(&path, &args.directory)
+            .inspect_err(|e| info!("Quick badge unavailable, using the planner: {e:#}"))
+            .ok()
+    };
+    let badge = match quick {
+        Some(badge) => badge,
+        None => Runtime::new()
+            .context("Failed to create tokio runtime")?
+            .block_on(async {
+                let planner = builder
+                    .build()
+                    .await
+                    .context("Failed to initialize planner")?;
+                let params = SearchPlans {
+                    directory: args.directory.clone(),
+                    ..Default::default()
+                };
+                let summaries = planner
+                    .search_plans_summary(&params)
+                    .await
+                    .context("Failed to list plans")?;
+                anyhow::Ok(PromptBadge::from_summaries(summaries.iter()))
+            })?,
+    };
+
+    if !badge.is_empty() {
+        println!("{badge}");
+    }
+    Ok(())
+}
+
+/// Lists the active plans, or the steps of active plans, as `ID:title`.
+async fn id_candidates(
+    planner: &Planner,
+    completion: &Completion,
+) -> beacon_core::Result<Vec<Candidate>> {
+    let plans = planner.list_plans_summary(&ListPlans::default()).await?;
+    if *completion == Completion::PlanIds {
+        return Ok(plans
+            .items
+            .iter()
+            .map(|plan| Candidate::new(plan.id.to_string(), Some(&plan.title)))
+            .collect());
+    }
+
+    let mut candidates = Vec::new();
+    for plan in &plans.items {
+        let steps = planner.get_steps(&Id { id: plan.id }).await?;
+        candidates.extend(
+            steps
+                .iter()
+                .map(|step| Candidate::new(step.id.to_string(), Some(&step.title))),
+        );
+    }
+    Ok(candidates)
+}
+
+/// Asks on the terminal whether to go ahead with a destructive operation.
+///
+/// Only a typed "y" or "yes" confirms. Without a terminal to ask on, e.g. in
+/// scripts, nothing is asked and the operation is refused, so that nothing
+/// is deleted unattended unless --yes is passed.
+fn confirm(question: &str) -> Result<bool> {
+    let stdin = io::stdin();
+    if !stdin.is_terminal() {
+        bail!("{question} Pass --yes to confirm without a prompt");
+    }
+
+    eprint!("{question} [y/N] ");
+    io::stderr()
+        .flush()
+        .context("Failed to ask for confirmation")?;
+    let mut answer = String::new();
+    stdin
+        .read_line(&mut answer)
+        .context("Failed to read confirmation")?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
 
 /// Handler implementations for the CLI
+///
+/// Handlers return what they show as an [`Output`], which the caller renders
+/// with [`Cli::render`] once the command is done. Only watching a plan and
+/// importing plans render as they go.
 pub struct Cli {
     planner: Planner,
     renderer: TerminalRenderer,
+    /// Whether plan listings explain how they were computed on stderr
+    explain: bool,
+    /// Whether plan listings show completion streaks and weekly trends
+    momentum: bool,
 }
 
 impl Cli {
     pub fn new(planner: Planner, renderer: TerminalRenderer) -> Self {
-        Self { planner, renderer }
+        Self {
+            planner,
+            renderer,
+            explain: false,
+            momentum: true,
+        }
+    }
+
+    /// Sets whether plan listings explain how they were computed.
+    pub fn with_explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    /// Sets whether plan listings show completion streaks and weekly trends.
+    pub fn with_momentum(mut self, momentum: bool) -> Self {
+        self.momentum = momentum;
+        self
+    }
+
+    /// Renders what a command returned.
+    pub fn render(&self, output: Output) {
+        self.renderer.render_output(output);
     }
 
     /// Handle plan subcommands
-    pub(crate) async fn handle_plan_command(&self, command: PlanCommands) -> Result<()> {
+    pub(crate) async fn handle_plan_command(&self, command: PlanCommands) -> Result<Output> {
         use PlanCommands::*;
         match command {
-            Create(args) => self.create_plan(&args.into()).await,
+            Create(args) => self.create(args).await,
             List(args) => self.list_plans(&args.into()).await,
-            Show(args) => self.show_plan(&args.into()).await,
-            Archive(args) => self.archive_plan(&args.into()).await,
+            Show(args) => self.show(args).await,
+            Update(args) => self.update_plan(&args.into()).await,
+            Archive(args) => self.archive(args).await,
             Unarchive(args) => self.unarchive_plan(&args.into()).await,
-            Delete(args) => self.delete_plan(&args.into()).await,
+            Delete(args) => self.delete(args).await,
+            Clone(args) => self.clone_plan(&args.into()).await,
+            Merge(args) => self.merge_plans(&args.into()).await,
+            Export(args) => self.export_plans(&args).await,
+            Import(args) => self.import_plans(&args).await,
             Search(args) => self.search_plans(&args.into()).await,
+            MoveDir(args) => self.reassign_directory(&args.into()).await,
+            Note(args) => self.append_plan_note(&args.into()).await,
+            Handoff(args) => self.handoff_plan(&args.into()).await,
+            Outline(args) => self.outline_plan(&args.into()).await,
+            Changelog(args) => self.plan_changelog(&args.into()).await,
+            Report(args) => self.plan_report(&args.into()).await,
+            Repair(args) => self.repair_plan(&args.into()).await,
+            Reset(args) => self.reset_plan_steps(&args.into()).await,
+            Watch(args) => self.watch_plan(&args).await,
+        }
+    }
+
+    /// Handle config subcommands
+    pub(crate) async fn handle_config_command(&self, command: ConfigCommands) -> Result<Output> {
+        match command {
+            ConfigCommands::Get(args) => {
+                let setting = self
+                    .planner
+                    .get_setting(&args.key)
+                    .await
+                    .context("Failed to read setting")?;
+                Ok(Output::new(setting).then("\n"))
+            }
+            ConfigCommands::Set(args) => {
+                let setting = self
+                    .planner
+                    .set_setting(&args.key, &args.value)
+                    .await
+                    .context("Failed to change setting")?;
+                let message = format!("Set {} to {}", setting.key, setting.value);
+                Ok(Output::new(OperationStatus::success(message)))
+            }
+            ConfigCommands::List => {
+                let settings = self
+                    .planner
+                    .list_settings()
+                    .await
+                    .context("Failed to read settings")?;
+                Ok(Output::new("# Settings\n\n").then(SettingValues::owned(settings)))
+            }
+            ConfigCommands::Show => unreachable!("handled before the planner is built"),
+        }
+    }
+
+    /// Handle database subcommands
+    pub(crate) async fn handle_db_command(&self, command: DbCommands) -> Result<Output> {
+        match command {
+            DbCommands::Maintenance => {
+                let report = self
+                    .planner
+                    .maintenance()
+                    .await
+                    .context("Failed to run database maintenance")?;
+                Ok(Output::new("# Database maintenance\n\n").then(report))
+            }
+            DbCommands::Backup(args) => {
+                self.planner
+                    .backup_to(&args.file)
+                    .await
+                    .context("Failed to back up database")?;
+                let message = format!("Backed up database to {}", args.file.display());
+                Ok(Output::new(OperationStatus::success(message)))
+            }
+            DbCommands::Restore(args) => {
+                self.planner
+                    .restore_from(&args.file, args.yes)
+                    .await
+                    .with_context(|| {
+                        format!("Failed to restore database from {}", args.file.display())
+                    })?;
+                let message = format!("Restored database from {}", args.file.display());
+                Ok(Output::new(OperationStatus::success(message)))
+            }
+        }
+    }
+
+    /// Handle template subcommands
+    pub(crate) async fn handle_template_command(
+        &self,
+        command: TemplateCommands,
+    ) -> Result<Output> {
+        match command {
+            TemplateCommands::Create(args) => {
+                let template = self
+                    .planner
+                    .create_template(&args.into())
+                    .await
+                    .context("Failed to create template")?;
+                let message = format!(
+                    "Created template '{}' with {} step(s)",
+                    template.name,
+                    template.steps.len()
+                );
+                Ok(Output::new(OperationStatus::success(message)))
+            }
+            TemplateCommands::List => {
+                let templates = self
+                    .planner
+                    .list_templates()
+                    .await
+                    .context("Failed to list templates")?;
+                Ok(Output::new("# Templates\n\n").then(Templates(templates)))
+            }
+            TemplateCommands::Apply(args) => {
+                let params: ApplyTemplate = args.into();
+                let steps = self
+                    .planner
+                    .apply_template(&params)
+                    .await
+                    .context("Failed to apply template")?;
+                let message = format!(
+                    "Applied template '{}' to plan {}, adding {} step(s)",
+                    params.name.trim(),
+                    params.plan_id,
+                    steps.len()
+                );
+                Ok(Output::new(OperationStatus::success(message))
+                    .then("\n")
+                    .then(Steps::owned(steps).with_mode(self.renderer.checklist_mode())))
+            }
         }
     }
 
     /// Handle step subcommands
-    pub(crate) async fn handle_step_command(&self, command: StepCommands) -> Result<()> {
+    pub(crate) async fn handle_step_command(&self, command: StepCommands) -> Result<Output> {
         use StepCommands::*;
         match command {
-            Add(args) => self.add_step(&args.into()).await,
-            Insert(args) => self.insert_step(&args.into()).await,
+            Add(args) => {
+                let quick = args.quick;
+                self.add_step(&quick_add(args.into(), quick)?).await
+            }
+            Insert(args) => {
+                let quick = args.quick;
+                let mut params: InsertStep = args.into();
+                params.step = quick_add(params.step, quick)?;
+                self.insert_step(&params).await
+            }
             Update(args) => self.update_step(&args.into()).await,
+            Show(args) if args.url => self.show_step_url(&args.into()).await,
             Show(args) => self.show_step(&args.into()).await,
+            Find(args) => self.find_steps(&args.into()).await,
+            List(args) => self.list_steps_by_status(&args.into()).await,
+            Next(args) => self.next_step(&args.into()).await,
+            Release(args) => self.release_step(&args.into()).await,
+            Remove(args) if args.dry_run => self.preview_step_removal(&args.into()).await,
+            Remove(args) => {
+                let confirmed = args.yes;
+                self.remove_step(&args.into(), confirmed).await
+            }
+            Trash(args) => self.trashed_steps(&args.into()).await,
+            Restore(args) => self.restore_step(&args.into()).await,
+            Purge(args) => self.purge_trash(&args.into()).await,
             Swap(args) => self.swap_step(&args.into()).await,
+            Move(args) => self.move_step(&args.into()).await,
+            MoveToPlan(args) => self.move_step_to_plan(&args.into()).await,
+            Phase(args) => self.set_step_phase(&args.into()).await,
+            Verify(args) => self.verify_step(&args.into()).await,
         }
     }
 
     /// Handle plan list command  
-    pub async fn list_plans(&self, params: &ListPlans) -> Result<()> {
-        let plan_summaries = self
-            .planner
-            .list_plans_summary(params)
-            .await
-            .context("Failed to list plans")?;
-
-        let title = if params.archived {
-            "Archived Plans"
+    pub async fn list_plans(&self, params: &ListPlans) -> Result<Output> {
+        let mut plan_summaries = if self.explain {
+            let (summaries, explanation) = self
+                .planner
+                .explain_list_plans(params)
+                .await
+                .context("Failed to list plans")?;
+            eprintln!("{explanation}");
+            summaries
         } else {
-            "Active Plans"
+            self.planner
+                .list_plans_summary(params)
+                .await
+                .context("Failed to list plans")?
         };
 
-        self.renderer
-            .render(format!("# {title}\n\n{plan_summaries}"));
+        let momentum = self.momentum(&mut plan_summaries.items).await?;
+        Ok(plan_listing(params, momentum, plan_summaries))
+    }
+
+    /// Adds each listed plan's weekly trend to `summaries` and returns the
+    /// overall momentum, or None if momentum indicators are turned off.
+    async fn momentum(&self, summaries: &mut [PlanSummary]) -> Result<Option<Momentum>> {
+        if !self.momentum {
+            return Ok(None);
+        }
+
+        let now = Zoned::now();
+        let by_plan = self
+            .planner
+            .momentum_stats_by_plan(&now)
+            .await
+            .context("Failed to compute plan momentum")?;
+        add_trends(summaries, &by_plan);
 
-        Ok(())
+        let momentum = self
+            .planner
+            .momentum_stats(&now)
+            .await
+            .context("Failed to compute momentum")?;
+        Ok(Some(momentum))
     }
 
-    /// Handle plan create command
-    async fn create_plan(&self, params: &CreatePlan) -> Result<()> {
-        let plan = self
+    /// Handle streak command
+    ///
+    /// Shows the current streak of days with completed steps, followed by
+    /// each active plan with completions in the last two weeks.
+    pub async fn streak(&self) -> Result<Output> {
+        let now = Zoned::now();
+        let momentum = self
             .planner
-            .create_plan(params)
+            .momentum_stats(&now)
             .await
-            .context("Failed to create plan")?;
+            .context("Failed to compute momentum")?;
+        let by_plan = self
+            .planner
+            .momentum_stats_by_plan(&now)
+            .await
+            .context("Failed to compute plan momentum")?;
+        let plans = self
+            .planner
+            .list_plans_summary(&ListPlans::default())
+            .await
+            .context("Failed to list plans")?;
 
-        self.renderer.render(CreateResult::new(plan));
+        let mut output = format!("# Momentum\n\n{momentum}\n");
+        let trends: Vec<String> = plans
+            .items
+            .iter()
+            .filter_map(|plan| {
+                let trend = by_plan.get(&plan.id)?.trend;
+                (!trend.is_empty())
+                    .then(|| format!("- {} (ID: {}): {trend}\n", plan.title, plan.id))
+            })
+            .collect();
+        if !trends.is_empty() {
+            output.push_str("\n## Plans\n\n");
+            output.push_str(&trends.concat());
+        }
 
-        Ok(())
+        Ok(Output::new(output))
     }
 
-    /// Handle plan show command
-    async fn show_plan(&self, params: &Id) -> Result<()> {
-        let plan = self
+    /// Handle stats command
+    pub async fn stats(&self, params: &GetStats) -> Result<Output> {
+        let stats = self
             .planner
-            .get_plan(params)
+            .stats_since(params)
             .await
-            .context("Failed to get plan")?
-            .ok_or_else(|| anyhow::anyhow!("Plan with ID {} not found", params.id))?;
+            .context("Failed to compute statistics")?;
+        Ok(Output::new(stats))
+    }
 
-        self.renderer.render(&plan);
+    /// Handle events command
+    pub async fn events(&self, params: &ListEvents) -> Result<Output> {
+        let events = self
+            .planner
+            .events(params)
+            .await
+            .context("Failed to list events")?;
 
-        Ok(())
+        Ok(Output::new("# Events\n\n").then(Events::owned(events)))
     }
 
-    /// Handle plan archive command
-    async fn archive_plan(&self, params: &Id) -> Result<()> {
-        let plan = self
+    /// Handle dedupe-report command
+    pub async fn dedupe_report(&self, params: &DuplicateStepsReport) -> Result<Output> {
+        let groups = self
             .planner
-            .archive_plan(params)
+            .find_duplicate_steps(params.into())
             .await
-            .with_context(|| format!("Failed to archive plan {}", params.id))?
-            .ok_or_else(|| anyhow::anyhow!("Plan with ID {} not found", params.id))?;
+            .context("Failed to find duplicate steps")?;
 
-        let message = format!(
-            "Archived plan '{}' (ID: {}). Use 'beacon plan unarchive {}' to restore.",
-            plan.title, params.id, params.id
-        );
-        self.renderer.render(OperationStatus::success(message));
-        Ok(())
+        Ok(Output::new("# Duplicate Steps\n\n").then(DuplicateGroups::owned(groups)))
     }
 
-    /// Handle plan unarchive command
-    async fn unarchive_plan(&self, params: &Id) -> Result<()> {
-        let _plan = self
+    /// Handle plan create command
+    async fn create_plan(&self, params: &CreatePlan) -> Result<Output> {
+        let plan = self
             .planner
-            .unarchive_plan(params)
+            .create_plan(params)
             .await
-            .with_context(|| format!("Failed to unarchive plan {}", params.id))?;
+            .context("Failed to create plan")?;
 
-        let message = format!("Unarchived plan with ID: {}", params.id);
-        self.renderer.render(OperationStatus::success(message));
-        Ok(())
+        Ok(Output::new(CreateResult::new(plan)))
+    }
+
+    /// Handle plan create command, creating the plan given or, with
+    /// --from-git-log, one from the commits in its directory
+    async fn create(&self, args: CreatePlanArgs) -> Result<Output> {
+        if args.from_git_log {
+            self.create_plan_from_git_log(args).await
+        } else {
+            self.create_plan(&args.into()).await
+        }
     }
 
-    /// Handle plan delete command
-    async fn delete_plan(&self, args: &DeletePlan) -> Result<()> {
+    /// Handle plan create command with --from-git-log
+    ///
+    /// Creates the plan and one done step per commit in a single transaction,
+    /// oldest commit first.
+    async fn create_plan_from_git_log(&self, args: CreatePlanArgs) -> Result<Output> {
+        let dir = PathBuf::from(args.directory.as_deref().unwrap_or("."));
+        let query = LogQuery {
+            since: args.since.as_deref(),
+            max: args.max,
+            include_merges: args.include_merges,
+        };
+        let commits = git_log::read_commits(&dir, &query)?;
+        if commits.is_empty() {
+            return Err(anyhow::anyhow!("No commits found to import"));
+        }
+
+        let title = match &args.title {
+            Some(title) => title.clone(),
+            None => git_log::repo_name(&dir)?,
+        };
+        let params = CreatePlan {
+            title,
+            ..args.into()
+        };
+        let steps = commits.into_iter().map(Into::into).collect();
+
         let plan = self
             .planner
-            .delete_plan(args)
+            .create_plan_with_steps(&params, steps)
             .await
-            .with_context(|| format!("Failed to delete plan {}", &args.id))?
-            .ok_or_else(|| anyhow::anyhow!("Plan with ID {} not found", &args.id))?;
+            .context("Failed to create plan")?;
 
-        let message = format!(
-            "Permanently deleted plan '{}' (ID: {}). This action cannot be undone.",
-            plan.title, plan.id
-        );
-        self.renderer.render(OperationStatus::success(message));
-        Ok(())
+        Ok(Output::new(CreateResult::new(plan)))
     }
 
-    /// Handle plan search command
-    async fn search_plans(&self, params: &SearchPlans) -> Result<()> {
-        let plan_summaries = self
+    /// Handle plan show command
+    async fn show_plan(&self, params: &ShowPlan) -> Result<Output> {
+        let Some(plan) = self
+            .planner
+            .get_plan(&Id { id: params.id })
+            .await
+            .context("Failed to get plan")?
+        else {
+            return Err(self.planner.plan_not_found(params.id).await.into());
+        };
+
+        let counts = self
             .planner
-            .search_plans_summary(params)
+            .plan_progress(&Id { id: params.id })
             .await
-            .context("Failed to search plans")?;
+            .context("Failed to count steps")?;
 
-        let title = format!(
-            "{} plans in directory: {}",
-            if params.archived {
-                "ARCHIVED"
-            } else {
-                "ACTIVE"
-            },
-            params.directory
+        let mode = if params.compact {
+            self.renderer.checklist_mode()
+        } else {
+            StepsMode::Detailed
+        };
+        let progress = PlanProgress::new(
+            format!("# {}. {}", plan.id, plan.title),
+            &PlanSummary::from(&plan),
         );
+        let now = Timestamp::now();
+        Ok(Output::from_fn(move |f| {
+            let plan = PlanAt::new(&plan, now)
+                .with_status_counts(counts)
+                .with_steps_mode(mode);
+            write!(f, "{plan}")
+        })
+        .with_progress(vec![progress]))
+    }
 
-        self.renderer
-            .render(format!("# {title}\n\n{plan_summaries}"));
-        Ok(())
+    /// Handle plan show command, showing the plan or, with --url, its URL
+    async fn show(&self, args: ShowPlanArgs) -> Result<Output> {
+        if args.url {
+            self.show_plan_url(&args.into()).await
+        } else {
+            self.show_plan(&args.into()).await
+        }
     }
 
-    /// Handle step add command
-    async fn add_step(&self, params: &StepCreate) -> Result<()> {
-        let step = self
+    /// Handle plan show command with --url
+    async fn show_plan_url(&self, params: &Id) -> Result<Output> {
+        let Some(plan) = self
             .planner
-            .add_step(params)
+            .get_plan_summary(params)
             .await
-            .with_context(|| format!("Failed to add step to plan {}", params.plan_id))?;
-        self.renderer.render(CreateResult::new(step));
-        Ok(())
-    }
+            .context("Failed to get plan")?
+        else {
+            return Err(self.planner.plan_not_found(params.id).await.into());
+        };
 
-    /// Handle step insert command
-    async fn insert_step(&self, params: &InsertStep) -> Result<()> {
-        let step = self.planner.insert_step(params).await.with_context(|| {
-            format!(
-                "Failed to insert step into plan {} at position {}",
-                params.step.plan_id, params.position
-            )
-        })?;
+        // Printed as is rather than rendered, for scripts to read
+        println!("{}", BeaconUri::plan(plan.id));
 
-        self.renderer.render(CreateResult::new(step));
-        Ok(())
+        Ok(Output::default())
     }
 
-    /// Handle step update command
-    async fn update_step(&self, params: &UpdateStep) -> Result<()> {
-        // Check if we have anything to update
-        if params.status.is_none()
-            && params.title.is_none()
-            && params.description.is_none()
-            && params.acceptance_criteria.is_none()
-            && params.references.is_none()
-            && params.result.is_none()
-        {
-            return Err(anyhow::anyhow!(
-                "No updates specified. Use --status, --title, --description, --acceptance-criteria, --references, or --result"
-            ));
+    /// Handle open command by resolving a beacon:// URL
+    pub async fn open(&self, url: &str) -> Result<Output> {
+        let uri = url
+            .parse::<BeaconUri>()
+            .with_context(|| format!("Failed to open '{url}'"))?;
+        uri.check_workspace(self.planner.database_path())
+            .with_context(|| format!("Failed to open '{url}'"))?;
+
+        match uri.target {
+            UriTarget::Plan(id) => {
+                self.show_plan(&ShowPlan {
+                    id,
+                    ..Default::default()
+                })
+                .await
+            }
+            UriTarget::Step(id) => self.show_step(&Id { id }).await,
+            UriTarget::Plans => self.list_plans(&ListPlans::default()).await,
         }
+    }
 
-        let status =
-            StepStatus::from_str(params.status.as_ref().unwrap_or(&"".to_string())).unwrap();
-
-        // Validate result requirement for done status
-        if let StepStatus::Done = status
-            && params.result.is_none()
+    /// Handle plan update command
+    async fn update_plan(&self, params: &UpdatePlan) -> Result<Output> {
+        // Check if we have anything to update
+        if params.title.is_none()
+            && params.description.is_none()
+            && params.directory.is_none()
+            && params.parent_plan_id.is_none()
+            && !params.clear_parent
+            && params.auto_archive.is_none()
+            && params.status_labels.is_none()
+            && params.due_date.is_none()
+            && !params.clear_due_date
         {
             return Err(anyhow::anyhow!(
-                "Result description is required when marking a step as done. Use --result to describe what was accomplished."
+                "No updates specified. Use --title, --description, --directory, --parent, --no-parent, --auto-archive, --no-auto-archive, --status-label, --clear-status-labels, --due, or --no-due"
             ));
         }
 
         // Build list of changes made for display
         let mut changes = Vec::new();
-        if params.status.is_some() {
-            changes.push("status".to_string());
-        }
         if params.title.is_some() {
             changes.push("title".to_string());
         }
         if params.description.is_some() {
             changes.push("description".to_string());
         }
-        if params.acceptance_criteria.is_some() {
-            changes.push("acceptance criteria".to_string());
+        if params.directory.is_some() {
+            changes.push("directory".to_string());
+        }
+        if params.parent_plan_id.is_some() || params.clear_parent {
+            changes.push("parent".to_string());
+        }
+        if params.auto_archive.is_some() {
+            changes.push("auto-archive".to_string());
         }
-        if params.references.is_some() {
-            changes.push("references".to_string());
+        if params.status_labels.is_some() {
+            changes.push("status labels".to_string());
+        }
+        if params.due_date.is_some() || params.clear_due_date {
+            changes.push("due date".to_string());
         }
 
-        let updated_step = self
+        let plan = self
             .planner
-            .update_step_validated(params)
+            .update_plan(params)
             .await
-            .with_context(|| format!("Failed to update step {}", params.id))?
-            .ok_or_else(|| anyhow::anyhow!("Step with ID {} not found", params.id))?;
-
-        let result = UpdateResult::with_changes(updated_step, changes);
-        self.renderer.render(&result);
+            .with_context(|| format!("Failed to update plan {}", params.id))?;
 
-        Ok(())
+        Ok(Output::new(UpdateResult::with_changes(plan, changes)))
     }
 
-    /// Handle step show command
-    async fn show_step(&self, params: &Id) -> Result<()> {
-        let step = self
+    /// Handle metrics command
+    pub async fn metrics(&self, args: &MetricsArgs) -> Result<Output> {
+        let (start, end) = args
+            .window()
+            .context("Failed to determine the metrics window")?;
+        let completed = self
             .planner
-            .get_step(params)
+            .completions_in_range(start, end)
             .await
-            .context("Failed to get step")?
-            .ok_or_else(|| anyhow::anyhow!("Step with ID {} not found", params.id))?;
+            .context("Failed to count completed steps")?;
 
-        self.renderer.render(&step);
+        Ok(Output::new(format!(
+            "# Metrics\n\n- **Steps completed this week**: {completed}\n"
+        )))
+    }
 
-        Ok(())
+    /// Handle plan archive command, archiving the plan given or, with
+    /// --all-completed, every completed plan
+    async fn archive(&self, args: ArchivePlanArgs) -> Result<Output> {
+        if args.all_completed {
+            self.archive_completed_plans(args).await
+        } else {
+            self.archive_plan(&args.into()).await
+        }
     }
 
-    /// Handle step swap command
-    async fn swap_step(&self, params: &SwapSteps) -> Result<()> {
-        self.planner.swap_steps(params).await.with_context(|| {
-            format!(
-                "Failed to swap steps {} and {}",
-                params.step1_id, params.step2_id
-            )
-        })?;
+    /// Archive a single plan
+    async fn archive_plan(&self, params: &ArchivePlan) -> Result<Output> {
+        let Some(plan) = self
+            .planner
+            .archive_plan_checked(params, true)
+            .await
+            .with_context(|| format!("Failed to archive plan {}", params.id))?
+        else {
+            return Err(self.planner.plan_not_found(params.id).await.into());
+        };
 
-        let message = format!(
-            "Swapped order of steps {} and {}",
-            params.step1_id, params.step2_id
-        );
-        let status = OperationStatus::success(message);
-        self.renderer.render(&status);
+        let active_children = plan
+            .child_plans
+            .iter()
+            .filter(|child| child.status == PlanStatus::Active)
+            .count();
 
-        Ok(())
+        let mut message = format!(
+            "Archived plan '{}' (ID: {}). Use 'beacon plan unarchive {}' to restore.",
+            plan.title, params.id, params.id
+        );
+        if active_children > 0 {
+            message.push_str(&format!(
+                " Warning: {active_children} child plan(s) are still active."
+            ));
+        }
+        let pending: Vec<_> = plan
+            .steps
+            .iter()
+            .filter(|step| step.status != StepStatus::Done)
+            .collect();
+        if params.force && !pending.is_empty() {
+            message.push_str(&format!(
+                " Archived anyway with {} step(s) not done:",
+                pending.len()
+            ));
+            for step in pending {
+                message.push_str(&format!("\n- {} ({})", step.title, step.status));
+            }
+        }
+        Ok(Output::new(OperationStatus::success(message)))
     }
-}
-
-// ============================================================================
-// CLI Argument Wrapper Implementations
-// ============================================================================
-//
-// These structures implement the CLI side of the parameter wrapper pattern.
-// Each wrapper:
-// 1. Defines CLI-specific argument parsing with clap derives
-// 2. Provides conversion methods to core parameter types
-// 3. Isolates clap framework concerns from core domain logic
-//
-// The into_params() methods perform explicit type conversion, ensuring
-// compile-time verification of parameter mapping between CLI and core layers.
 
-/// Create a new plan
-///
-/// CLI wrapper for CreatePlan that adds clap-specific argument handling
-/// including short/long flags, help text generation, and input validation.
-#[derive(Parser)]
-pub struct CreatePlanArgs {
-    /// Title of the plan
-    pub title: String,
-    /// Optional description providing more context about the plan
-    #[arg(
-        short,
-        long,
-        help = "Optional description providing more context about the plan"
-    )]
-    pub description: Option<String>,
-    /// Working directory to associate with this plan
-    #[arg(long, help = "Working directory to associate with this plan")]
-    pub directory: Option<String>,
-}
+    /// Handle plan archive --all-completed command
+    async fn archive_completed_plans(&self, args: ArchivePlanArgs) -> Result<Output> {
+        let plans = self
+            .planner
+            .archive_completed_plans(args.directory)
+            .await
+            .context("Failed to archive completed plans")?;
 
-impl From<CreatePlanArgs> for CreatePlan {
-    /// Convert CLI arguments to core parameter structure
-    ///
-    /// This explicit conversion ensures type safety and makes the boundary
-    /// between CLI concerns and core logic clear and verifiable.
-    fn from(val: CreatePlanArgs) -> Self {
-        CreatePlan {
-            title: val.title,
-            description: val.description,
-            directory: val.directory,
+        if plans.is_empty() {
+            return Ok(Output::new(OperationStatus::success(
+                "No completed plans to archive".to_string(),
+            )));
+        }
+
+        Ok(
+            Output::new(format!("# Archived {} Completed Plan(s)\n\n", plans.len()))
+                .then(PlanSummaries::owned(plans)),
+        )
+    }
+
+    /// Handle plan unarchive command
+    async fn unarchive_plan(&self, params: &Id) -> Result<Output> {
+        let _plan = self
+            .planner
+            .unarchive_plan(params)
+            .await
+            .with_context(|| format!("Failed to unarchive plan {}", params.id))?;
+
+        let message = format!("Unarchived plan with ID: {}", params.id);
+        Ok(Output::new(OperationStatus::success(message)))
+    }
+
+    /// Handle plan delete command, previewing the deletion with --dry-run
+    async fn delete(&self, args: DeletePlanArgs) -> Result<Output> {
+        if args.dry_run {
+            self.preview_plan_deletion(&args.into()).await
+        } else {
+            self.delete_plan(&args.into()).await
+        }
+    }
+
+    /// Delete a plan
+    async fn delete_plan(&self, args: &DeletePlan) -> Result<Output> {
+        let mut args = args.clone();
+        if !args.confirmed {
+            let plan = self.plan_to_delete(args.id).await?;
+            let question = format!(
+                "Permanently delete plan '{}' (ID: {}) and its {} step(s)?",
+                plan.title,
+                plan.id,
+                plan.steps.len()
+            );
+            if !confirm(&question)? {
+                return Ok(Output::new(format!(
+                    "Cancelled; plan {} was not deleted\n",
+                    plan.id
+                )));
+            }
+            args.confirmed = true;
+        }
+
+        let Some(plan) = self
+            .planner
+            .delete_plan(&args)
+            .await
+            .with_context(|| format!("Failed to delete plan {}", &args.id))?
+        else {
+            return Err(self.planner.plan_not_found(args.id).await.into());
+        };
+
+        let message = format!(
+            "Permanently deleted plan '{}' (ID: {}). This action cannot be undone.",
+            plan.title, plan.id
+        );
+        Ok(Output::new(OperationStatus::success(message)))
+    }
+
+    /// Handle plan delete command with --dry-run
+    ///
+    /// Shows what would be deleted, and whether --force would be needed,
+    /// without touching the database.
+    async fn preview_plan_deletion(&self, args: &DeletePlan) -> Result<Output> {
+        let plan = self.plan_to_delete(args.id).await?;
+        let pending = plan
+            .steps
+            .iter()
+            .filter(|step| step.status != StepStatus::Done)
+            .count();
+
+        let mut output = Output::new(DeleteResult::dry_run(plan));
+        if pending > 0 && !args.force {
+            output = output.then(format!(
+                "\n{pending} step(s) are not done, so deleting it requires --force\n"
+            ));
+        }
+        Ok(output)
+    }
+
+    async fn plan_to_delete(&self, id: u64) -> Result<Plan> {
+        match self
+            .planner
+            .get_plan(&Id { id })
+            .await
+            .with_context(|| format!("Failed to delete plan {id}"))?
+        {
+            Some(plan) => Ok(plan),
+            None => Err(self.planner.plan_not_found(id).await.into()),
+        }
+    }
+
+    /// Handle plan clone command
+    async fn clone_plan(&self, args: &ClonePlan) -> Result<Output> {
+        let plan = self
+            .planner
+            .clone_plan(&Id { id: args.id }, args.title.clone())
+            .await
+            .with_context(|| format!("Failed to clone plan {}", args.id))?;
+
+        Ok(Output::new(CreateResult::new(plan)))
+    }
+
+    /// Handle plan merge command
+    async fn merge_plans(&self, args: &MergePlans) -> Result<Output> {
+        let plan = self
+            .planner
+            .merge_plans_confirmed(args)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to merge plan {} into plan {}",
+                    args.source_id, args.target_id
+                )
+            })?;
+
+        let message = format!(
+            "Merged plan {} into '{}' (ID: {}), which now has {} step(s). Plan {} was archived.",
+            args.source_id,
+            plan.title,
+            plan.id,
+            plan.steps.len(),
+            args.source_id
+        );
+        Ok(Output::new(OperationStatus::success(message)))
+    }
+
+    /// Handle plan export command
+    async fn export_plans(&self, args: &ExportPlansArgs) -> Result<Output> {
+        let ids = if args.ids.is_empty() {
+            self.planner
+                .list_plans_summary(&ListPlans::default())
+                .await
+                .context("Failed to list plans")?
+                .items
+                .into_iter()
+                .map(|summary| summary.id)
+                .collect()
+        } else {
+            args.ids.clone()
+        };
+
+        let mut plans = Vec::with_capacity(ids.len());
+        for id in ids {
+            let plan = self
+                .planner
+                .get_plan(&Id { id })
+                .await
+                .context("Failed to get plan")?;
+            match plan {
+                Some(plan) => plans.push(plan),
+                None => return Err(self.planner.plan_not_found(id).await.into()),
+            }
+        }
+
+        let json = serde_json::to_string_pretty(&plans).context("Failed to export plans")?;
+        match &args.output {
+            Some(path) => {
+                std::fs::write(path, format!("{json}\n"))
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                let message = format!("Exported {} plan(s) to {}", plans.len(), path.display());
+                Ok(Output::new(OperationStatus::success(message)))
+            }
+            None => {
+                // Printed as is rather than rendered, so it can be imported
+                println!("{json}");
+                Ok(Output::default())
+            }
+        }
+    }
+
+    /// Handle plan import command
+    ///
+    /// Without --on-conflict, each conflict is asked about on a terminal and
+    /// skipped otherwise, so that unattended imports never change existing
+    /// plans.
+    async fn import_plans(&self, args: &ImportPlansArgs) -> Result<Output> {
+        let text = std::fs::read_to_string(&args.file)
+            .with_context(|| format!("Failed to read {}", args.file.display()))?;
+        let plans: Vec<Plan> = serde_json::from_str(&text).with_context(|| {
+            format!(
+                "{} is not a plan export such as `b plan export` writes",
+                args.file.display()
+            )
+        })?;
+        let ask = args.on_conflict.is_none() && io::stdin().is_terminal();
+        let policy = args.on_conflict.map(Into::into).unwrap_or_default();
+
+        for plan in &plans {
+            let policy = match self.planner.find_import_conflict(plan).await {
+                Ok(Some(existing)) if ask => ask_conflict_policy(&existing, plan)?,
+                Ok(_) => policy,
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to import '{}'", plan.title));
+                }
+            };
+            let outcome = self
+                .planner
+                .import_plan(plan, policy)
+                .await
+                .with_context(|| format!("Failed to import '{}'", plan.title))?;
+
+            let message = match outcome {
+                ImportOutcome::Created(created) => {
+                    format!("Imported '{}' (ID: {})", created.title, created.id)
+                }
+                ImportOutcome::Duplicated(created) => format!(
+                    "Imported '{}' as '{}' (ID: {}) next to the existing plan",
+                    plan.title, created.title, created.id
+                ),
+                ImportOutcome::Merged {
+                    plan: merged,
+                    added,
+                    skipped,
+                } => format!(
+                    "Merged '{}' into plan {}, appending {} step(s) and skipping {} it already had",
+                    plan.title,
+                    merged.id,
+                    added.len(),
+                    skipped.len()
+                ),
+                ImportOutcome::Skipped { existing_id } => format!(
+                    "Skipped '{}': plan {existing_id} already has its title and directory",
+                    plan.title
+                ),
+            };
+            // Reported as each plan is imported, so that a later failure
+            // doesn't hide what was already imported
+            self.renderer.render(OperationStatus::success(message));
+        }
+        Ok(Output::default())
+    }
+
+    /// Handle plan search command
+    async fn search_plans(&self, params: &SearchPlans) -> Result<Output> {
+        let plan_summaries = if self.explain {
+            let (summaries, explanation) = self
+                .planner
+                .explain_search_plans(params)
+                .await
+                .context("Failed to search plans")?;
+            eprintln!("{explanation}");
+            summaries
+        } else {
+            self.planner
+                .search_plans_summary(params)
+                .await
+                .context("Failed to search plans")?
+        };
+
+        let title = format!(
+            "{} plans in directory: {}",
+            if params.archived {
+                "ARCHIVED"
+            } else {
+                "ACTIVE"
+            },
+            display_directory(&params.directory)
+        );
+
+        Ok(Output::new(format!("# {title}\n\n")).then(plan_summaries))
+    }
+
+    /// Handle plan changelog command
+    async fn plan_changelog(&self, params: &PlanChangelog) -> Result<Output> {
+        let changelog = self
+            .planner
+            .changelog(params)
+            .await
+            .with_context(|| format!("Failed to get changelog of plan {}", params.id))?;
+
+        Ok(Output::new(changelog))
+    }
+
+    /// Handle plan report command
+    async fn plan_report(&self, params: &GetPlanReport) -> Result<Output> {
+        let report = self
+            .planner
+            .report(params)
+            .await
+            .with_context(|| format!("Failed to get report of plan {}", params.id))?;
+
+        Ok(Output::new(report))
+    }
+
+    /// Handle plan repair command
+    async fn repair_plan(&self, params: &Id) -> Result<Output> {
+        let renumbered = self
+            .planner
+            .repair_plan(params)
+            .await
+            .with_context(|| format!("Failed to repair plan {}", params.id))?;
+
+        let message = format!(
+            "Renumbered {renumbered} step(s) of plan {} in their current order",
+            params.id
+        );
+        Ok(Output::new(OperationStatus::success(message)))
+    }
+
+    /// Handle plan reset command
+    async fn reset_plan_steps(&self, params: &ResetPlanSteps) -> Result<Output> {
+        let reset = self
+            .planner
+            .reset_plan_steps(&Id { id: params.id }, !params.keep_results)
+            .await
+            .with_context(|| format!("Failed to reset plan {}", params.id))?;
+
+        let message = format!("Reset {reset} step(s) of plan {} to todo", params.id);
+        Ok(Output::new(OperationStatus::success(message)))
+    }
+
+    /// Handle plan move-dir command
+    async fn reassign_directory(&self, params: &ReassignDirectory) -> Result<Output> {
+        let moved = self
+            .planner
+            .reassign_directory(&params.from, &params.to)
+            .await
+            .context("Failed to move plans")?;
+
+        let (from, to) = (
+            display_directory(&params.from),
+            display_directory(&params.to),
+        );
+        let message = if moved == 0 {
+            format!("No plans found in {from}")
+        } else {
+            format!("Moved {moved} plan(s) from {from} to {to}")
+        };
+        Ok(Output::new(OperationStatus::success(message)))
+    }
+
+    /// Handle plan note command
+    async fn append_plan_note(&self, params: &AppendPlanNote) -> Result<Output> {
+        self.planner
+            .append_plan_note(&Id { id: params.id }, &params.text)
+            .await
+            .with_context(|| format!("Failed to add note to plan {}", params.id))?;
+
+        Ok(Output::new(OperationStatus::success(format!(
+            "Added note to plan {}",
+            params.id
+        ))))
+    }
+
+    /// Handle plan watch command, showing the plan again on every change
+    /// until interrupted or the plan is deleted
+    async fn watch_plan(&self, args: &WatchPlanArgs) -> Result<Output> {
+        // Waits end now and then to check for Ctrl-C between them
+        const WAIT: Duration = Duration::from_secs(60);
+
+        let id = Id { id: args.id };
+        let Some(mut since) = self.show_watched_plan(&id, args.interval).await? else {
+            return Err(self.planner.plan_not_found(id.id).await.into());
+        };
+
+        let interrupted = tokio::signal::ctrl_c();
+        tokio::pin!(interrupted);
+        loop {
+            let change = tokio::select! {
+                _ = &mut interrupted => return Ok(Output::default()),
+                change = self.planner.wait_for_change_every(&id, since, WAIT, args.interval) => change,
+            };
+            let shown = match change {
+                Ok(Some(_)) => self.show_watched_plan(&id, args.interval).await?,
+                Ok(None) => continue,
+                Err(PlannerError::PlanNotFound { .. }) => None,
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to watch plan {}", id.id));
+                }
+            };
+            match shown {
+                Some(updated_at) => since = updated_at,
+                None => {
+                    return Ok(Output::new(format!(
+                        "\nPlan {} was deleted; stopped watching.\n",
+                        id.id
+                    )));
+                }
+            }
+        }
+    }
+
+    /// Clears the terminal and shows a watched plan, returning when it last
+    /// changed, or None if it no longer exists.
+    async fn show_watched_plan(&self, id: &Id, interval: Duration) -> Result<Option<Timestamp>> {
+        let Some(plan) = self
+            .planner
+            .get_plan(id)
+            .await
+            .context("Failed to get plan")?
+        else {
+            return Ok(None);
+        };
+
+        if io::stdout().is_terminal() {
+            print!("\x1b[2J\x1b[H");
+        }
+        self.renderer.render(format!(
+            "{}\n_Watching plan {}, checking every {:?}; press Ctrl-C to stop_\n",
+            PlanAt::new(&plan, Timestamp::now()),
+            plan.id,
+            interval
+        ));
+        io::stdout().flush().context("Failed to write plan")?;
+        Ok(Some(plan.updated_at))
+    }
+
+    /// Handle plan handoff command
+    async fn handoff_plan(&self, params: &Id) -> Result<Output> {
+        let brief = self
+            .planner
+            .handoff_summary(params)
+            .await
+            .with_context(|| format!("Failed to summarize plan {}", params.id))?;
+
+        Ok(Output::new(brief))
+    }
+
+    /// Handle plan outline command
+    async fn outline_plan(&self, params: &Id) -> Result<Output> {
+        let outline = self
+            .planner
+            .get_plan_outline(params.id)
+            .await
+            .with_context(|| format!("Failed to get outline of plan {}", params.id))?;
+        let summary = self
+            .planner
+            .get_plan_summary(params)
+            .await
+            .with_context(|| format!("Failed to get outline of plan {}", params.id))?;
+
+        let mut header = format!("# Outline of Plan {}", params.id);
+        if let Some(summary) = summary {
+            header.push_str(&format!(
+                ": {} ({}/{} done)",
+                summary.title, summary.completed_steps, summary.total_steps
+            ));
+        }
+        Ok(Output::new(format!("{header}\n\n")).then(StepOutlines::owned(outline)))
+    }
+
+    /// Handle step add command
+    async fn add_step(&self, params: &StepCreate) -> Result<Output> {
+        let step = self
+            .planner
+            .add_step(params)
+            .await
+            .with_context(|| format!("Failed to add step to plan {}", params.plan_id))?;
+        Ok(Output::new(CreateResult::new(step)))
+    }
+
+    /// Handle step insert command
+    async fn insert_step(&self, params: &InsertStep) -> Result<Output> {
+        let step = self.planner.insert_step(params).await.with_context(|| {
+            format!(
+                "Failed to insert step into plan {} at position {}",
+                params.step.plan_id, params.position
+            )
+        })?;
+
+        Ok(Output::new(CreateResult::new(step)))
+    }
+
+    /// Handle step update command
+    async fn update_step(&self, params: &UpdateStep) -> Result<Output> {
+        // Check if we have anything to update
+        if params.status.is_none()
+            && params.title.is_none()
+            && params.description.is_none()
+            && params.acceptance_criteria.is_none()
+            && params.expected_output.is_none()
+            && params.references.is_none()
+            && params.result.is_none()
+            && !params.clear_result
+            && params.blocked_reason.is_none()
+            && params.verification_required.is_none()
+        {
+            return Err(anyhow::anyhow!(
+                "No updates specified. Use --status, --title, --description, --acceptance-criteria, --expected-output, --references, --result, --clear-result, --blocked-reason, --require-verification, or --no-require-verification"
+            ));
+        }
+
+        let Some(result) = self
+            .planner
+            .update_step_with_changes(params)
+            .await
+            .with_context(|| format!("Failed to update step {}", params.id))?
+        else {
+            return Err(self.planner.step_not_found(params.id).await.into());
+        };
+
+        Ok(Output::new(result))
+    }
+
+    /// Handle step show command
+    async fn show_step(&self, params: &Id) -> Result<Output> {
+        let Some(step) = self
+            .planner
+            .get_step(params)
+            .await
+            .context("Failed to get step")?
+        else {
+            return Err(self.planner.step_not_found(params.id).await.into());
+        };
+        let labels = self
+            .planner
+            .get_step_status_labels(params)
+            .await
+            .context("Failed to get status labels")?;
+
+        Ok(labeled_step(step, labels))
+    }
+
+    /// Handle step find command
+    async fn find_steps(&self, params: &FindSteps) -> Result<Output> {
+        let steps = self
+            .planner
+            .find_steps_validated(params)
+            .await
+            .with_context(|| format!("Failed to find steps in plan {}", params.plan_id))?;
+
+        Ok(Output::new(format!(
+            "# Steps of Plan {} matching '{}'\n\n",
+            params.plan_id,
+            params.query.trim()
+        ))
+        .then(steps))
+    }
+
+    /// Handle step list command
+    async fn list_steps_by_status(&self, params: &ListStepsByStatus) -> Result<Output> {
+        let status = params.validate()?;
+        let steps = self
+            .planner
+            .list_steps_by_status_validated(params)
+            .await
+            .context("Failed to list steps")?;
+
+        Ok(Output::new(format!("# {} Steps\n\n", status.label())).then(steps))
+    }
+
+    /// Handle step next command
+    async fn next_step(&self, params: &Id) -> Result<Output> {
+        let Some(step) = self
+            .planner
+            .next_step(params)
+            .await
+            .context("Failed to find next step")?
+        else {
+            let summary = self
+                .planner
+                .get_plan_summary(params)
+                .await
+                .context("Failed to get plan")?;
+            let message = match summary {
+                Some(summary) if summary.is_complete() => format!(
+                    "Plan {} appears complete; all its steps are done. Consider archiving it with `b plan archive {}`",
+                    params.id, params.id
+                ),
+                Some(summary) if summary.total_steps == 0 => {
+                    format!("Plan {} has no steps yet", params.id)
+                }
+                _ => format!(
+                    "No step of plan {} is ready to start: the remaining steps are in progress, awaiting verification, or blocked by unfinished dependencies",
+                    params.id
+                ),
+            };
+            return Ok(Output::new(format!("{message}\n")));
+        };
+        let labels = self
+            .planner
+            .get_step_status_labels(&Id { id: step.id })
+            .await
+            .context("Failed to get status labels")?;
+
+        Ok(labeled_step(step, labels))
+    }
+
+    /// Handle step show command with --url
+    async fn show_step_url(&self, params: &Id) -> Result<Output> {
+        let Some(step) = self
+            .planner
+            .get_step(params)
+            .await
+            .context("Failed to get step")?
+        else {
+            return Err(self.planner.step_not_found(params.id).await.into());
+        };
+
+        // Printed as is rather than rendered, for scripts to read
+        println!("{}", BeaconUri::step(step.id));
+
+        Ok(Output::default())
+    }
+
+    /// Handle step release command
+    async fn release_step(&self, params: &Id) -> Result<Output> {
+        let released = self
+            .planner
+            .release_step(params)
+            .await
+            .with_context(|| format!("Failed to release step {}", params.id))?;
+        if !released {
+            return Err(anyhow::anyhow!(
+                "Step {} is not in progress, so there is nothing to release",
+                params.id
+            ));
+        }
+
+        let message = format!("Released step {}; it is back in todo", params.id);
+        Ok(Output::new(OperationStatus::success(message)))
+    }
+
+    /// Handle step remove command
+    async fn remove_step(&self, params: &Id, confirmed: bool) -> Result<Output> {
+        if !confirmed {
+            let step = self.step_to_remove(params.id).await?;
+            let question = format!("Move step '{}' (ID: {}) to the trash?", step.title, step.id);
+            if !confirm(&question)? {
+                return Ok(Output::new(format!(
+                    "Cancelled; step {} was not removed\n",
+                    step.id
+                )));
+            }
+        }
+
+        self.planner
+            .remove_step(params)
+            .await
+            .with_context(|| format!("Failed to remove step {}", params.id))?;
+
+        let message = format!(
+            "Moved step {} to the trash; restore it with `b step restore {}`",
+            params.id, params.id
+        );
+        Ok(Output::new(OperationStatus::success(message)))
+    }
+
+    /// Handle step remove command with --dry-run
+    async fn preview_step_removal(&self, params: &Id) -> Result<Output> {
+        let step = self.step_to_remove(params.id).await?;
+
+        let message = format!(
+            "Would move step '{}' (ID: {}) of plan {} to the trash",
+            step.title, step.id, step.plan_id
+        );
+        Ok(Output::new(format!("{message}\n")))
+    }
+
+    async fn step_to_remove(&self, id: u64) -> Result<Step> {
+        match self
+            .planner
+            .get_step(&Id { id })
+            .await
+            .with_context(|| format!("Failed to remove step {id}"))?
+        {
+            Some(step) => Ok(step),
+            None => Err(self.planner.step_not_found(id).await.into()),
+        }
+    }
+
+    /// Handle step trash command
+    async fn trashed_steps(&self, params: &Id) -> Result<Output> {
+        let steps = self
+            .planner
+            .get_trashed_steps(params)
+            .await
+            .with_context(|| format!("Failed to list the trash of plan {}", params.id))?;
+
+        Ok(Output::new(format!("# Trash of Plan {}\n\n", params.id))
+            .then(TrashedSteps::owned(steps)))
+    }
+
+    /// Handle step restore command
+    async fn restore_step(&self, params: &Id) -> Result<Output> {
+        let step = self
+            .planner
+            .restore_step(params)
+            .await
+            .with_context(|| format!("Failed to restore step {}", params.id))?;
+
+        let message = format!(
+            "Restored step {} '{}' to the end of plan {}",
+            step.id, step.title, step.plan_id
+        );
+        Ok(Output::new(OperationStatus::success(message)))
+    }
+
+    /// Handle step purge command
+    async fn purge_trash(&self, params: &PurgeTrash) -> Result<Output> {
+        let purged = self
+            .planner
+            .purge_trashed_steps(params)
+            .await
+            .context("Failed to purge the trash")?;
+
+        let message = format!(
+            "Permanently deleted {purged} step(s) trashed more than {} day(s) ago",
+            params.older_than_days
+        );
+        Ok(Output::new(OperationStatus::success(message)))
+    }
+
+    /// Handle step swap command
+    async fn swap_step(&self, params: &SwapSteps) -> Result<Output> {
+        self.planner.swap_steps(params).await.with_context(|| {
+            format!(
+                "Failed to swap steps {} and {}",
+                params.step1_id, params.step2_id
+            )
+        })?;
+
+        let message = format!(
+            "Swapped order of steps {} and {}",
+            params.step1_id, params.step2_id
+        );
+        let status = OperationStatus::success(message);
+        Ok(Output::new(status))
+    }
+
+    /// Handle step move command
+    async fn move_step(&self, params: &MoveStep) -> Result<Output> {
+        self.planner
+            .move_step(params)
+            .await
+            .with_context(|| format!("Failed to move step {}", params.step_id))?;
+
+        let message = format!(
+            "Moved step {} to position {}",
+            params.step_id, params.new_position
+        );
+        Ok(Output::new(OperationStatus::success(message)))
+    }
+
+    /// Handle step move-to-plan command
+    async fn move_step_to_plan(&self, params: &MoveStepToPlan) -> Result<Output> {
+        let step = self
+            .planner
+            .move_step_to_plan(params.step_id, params.plan_id, params.position)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to move step {} to plan {}",
+                    params.step_id, params.plan_id
+                )
+            })?;
+
+        let message = format!(
+            "Moved step {} to plan {} at position {}",
+            step.id, step.plan_id, step.order
+        );
+        Ok(Output::new(OperationStatus::success(message)))
+    }
+
+    /// Handle step verify command
+    async fn verify_step(&self, params: &VerifyStep) -> Result<Output> {
+        let step = self
+            .planner
+            .verify_step(params)
+            .await
+            .with_context(|| format!("Failed to verify step {}", params.id))?;
+
+        Ok(Output::new(step))
+    }
+
+    /// Handle step phase command
+    async fn set_step_phase(&self, params: &SetStepPhase) -> Result<Output> {
+        let Some(step) = self
+            .planner
+            .set_step_phase(params)
+            .await
+            .with_context(|| format!("Failed to set phase of step {}", params.id))?
+        else {
+            return Err(self.planner.step_not_found(params.id).await.into());
+        };
+
+        let message = match &step.phase {
+            Some(phase) => format!("Moved step {} to phase '{phase}'", step.id),
+            None => format!("Removed step {} from its phase", step.id),
+        };
+        Ok(Output::new(OperationStatus::success(message)))
+    }
+}
+
+// ============================================================================
+// CLI Argument Wrapper Implementations
+// ============================================================================
+//
+// These structures implement the CLI side of the parameter wrapper pattern.
+// Each wrapper:
+// 1. Defines CLI-specific argument parsing with clap derives
+// 2. Provides conversion methods to core parameter types
+// 3. Isolates clap framework concerns from core domain logic
+//
+// The into_params() methods perform explicit type conversion, ensuring
+// compile-time verification of parameter mapping between CLI and core layers.
+
+/// Create a new plan
+///
+/// CLI wrapper for CreatePlan that adds clap-specific argument handling
+/// including short/long flags, help text generation, and input validation.
+#[derive(Parser)]
+pub struct CreatePlanArgs {
+    /// Title of the plan
+    #[arg(required_unless_present = "from_git_log")]
+    pub title: Option<String>,
+    /// Optional description providing more context about the plan
+    #[arg(
+        short,
+        long,
+        help = "Optional description providing more context about the plan"
+    )]
+    pub description: Option<String>,
+    /// Working directory to associate with this plan
+    #[arg(long, help = "Working directory to associate with this plan")]
+    pub directory: Option<String>,
+    /// Store the enclosing git repository root as the plan directory
+    #[arg(
+        long,
+        help = "Store the enclosing git repository root instead of a subdirectory"
+    )]
+    pub git_root: bool,
+    /// ID of a parent plan to create this plan under
+    #[arg(long, help = "Create the plan as a child of the plan with this ID")]
+    pub parent: Option<u64>,
+    /// Archive the plan automatically once all of its steps are done
+    #[arg(
+        long,
+        conflicts_with = "no_auto_archive",
+        help = "Archive the plan automatically once all its steps are done (default: the auto_archive setting)"
+    )]
+    pub auto_archive: bool,
+    /// Keep the plan when all of its steps are done, whatever the
+    /// workspace's auto_archive setting
+    #[arg(
+        long,
+        help = "Don't archive the plan automatically, overriding the auto_archive setting"
+    )]
+    pub no_auto_archive: bool,
+    /// Date the plan should be done by
+    #[arg(
+        long,
+        value_name = "DATE",
+        help = "Date the plan should be done by, e.g. 2025-01-31"
+    )]
+    pub due: Option<String>,
+    /// Scaffold the plan from recent git history
+    #[arg(
+        long,
+        help = "Add one done step per recent commit of the git repository; the title defaults to the repository name"
+    )]
+    pub from_git_log: bool,
+    /// Only import commits newer than this age
+    #[arg(
+        long,
+        requires = "from_git_log",
+        value_name = "AGE",
+        help = "Only import commits newer than this, e.g. 2w, 3d, or a date"
+    )]
+    pub since: Option<String>,
+    /// Maximum number of commits to import
+    #[arg(
+        long,
+        requires = "from_git_log",
+        default_value_t = 20,
+        help = "Maximum number of commits to import"
+    )]
+    pub max: usize,
+    /// Import merge commits too
+    #[arg(long, requires = "from_git_log", help = "Also import merge commits")]
+    pub include_merges: bool,
+}
+
+impl From<CreatePlanArgs> for CreatePlan {
+    /// Convert CLI arguments to core parameter structure
+    ///
+    /// This explicit conversion ensures type safety and makes the boundary
+    /// between CLI concerns and core logic clear and verifiable.
+    fn from(val: CreatePlanArgs) -> Self {
+        CreatePlan {
+            // Only absent with --from-git-log, which supplies its own title
+            title: val.title.unwrap_or_default(),
+            description: val.description,
+            directory: val.directory,
+            infer_git_root: val.git_root.then_some(true),
+            parent_plan_id: val.parent,
+            auto_archive: val
+                .auto_archive
+                .then_some(true)
+                .or(val.no_auto_archive.then_some(false)),
+            due_date: val.due,
         }
     }
 }
@@ -339,12 +1881,40 @@ pub struct ListPlansArgs {
         help = "Show archived (completed/inactive) plans instead of active ones"
     )]
     pub archived: bool,
+    /// Only list the child plans of this plan
+    #[arg(long, help = "Only list the child plans of the plan with this ID")]
+    pub children_of: Option<u64>,
+    /// Only list plans with this completion status
+    #[arg(long, value_enum, help = "Only list plans with this completion status")]
+    pub completion: Option<CompletionArg>,
+    /// Only list plans due on or before this date
+    #[arg(
+        long,
+        value_name = "DATE",
+        help = "Only list plans due on or before this date, e.g. 2025-01-31"
+    )]
+    pub due_before: Option<String>,
+    /// Maximum number of plans to list
+    #[arg(long, value_name = "N", help = "List at most this many plans")]
+    pub limit: Option<u32>,
+    /// Number of plans to skip
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Skip this many plans before listing, e.g. with --limit to page through them"
+    )]
+    pub offset: Option<u32>,
 }
 
 impl From<ListPlansArgs> for ListPlans {
     fn from(val: ListPlansArgs) -> Self {
         ListPlans {
             archived: val.archived,
+            children_of: val.children_of,
+            completion: val.completion.map(|completion| completion.to_string()),
+            due_before: val.due_before,
+            limit: val.limit,
+            offset: val.offset,
         }
     }
 }
@@ -359,6 +1929,15 @@ pub struct ShowPlanArgs {
     /// ID of the plan to display
     #[arg(help = "Unique identifier of the plan to show details for")]
     pub id: u64,
+    /// Print the canonical beacon:// URL instead of the plan details
+    #[arg(long, help = "Print the canonical beacon:// URL for the plan")]
+    pub url: bool,
+    /// Show one line per step instead of every section
+    #[arg(
+        long,
+        help = "Show each step as one checklist line with its ID, title and status"
+    )]
+    pub compact: bool,
 }
 
 impl From<ShowPlanArgs> for Id {
@@ -367,22 +1946,177 @@ impl From<ShowPlanArgs> for Id {
     }
 }
 
+impl From<ShowPlanArgs> for ShowPlan {
+    fn from(val: ShowPlanArgs) -> Self {
+        ShowPlan {
+            id: val.id,
+            compact: val.compact,
+            ..Default::default()
+        }
+    }
+}
+
+/// Update a plan's details or parent
+///
+/// Change the title, description or directory of a plan, move it under a
+/// parent plan, or detach it from its parent. Plans nest only one level deep: a parent cannot
+/// itself have a parent.
+#[derive(Parser)]
+pub struct UpdatePlanArgs {
+    #[arg(help = "Unique identifier of the plan to update")]
+    pub id: u64,
+    #[arg(short, long, help = "Updated title for the plan")]
+    pub title: Option<String>,
+    #[arg(short, long, help = "Updated description for the plan")]
+    pub description: Option<String>,
+    #[arg(
+        long,
+        help = "Updated working directory for the plan (relative paths are made absolute)"
+    )]
+    pub directory: Option<String>,
+    #[arg(
+        long,
+        conflicts_with = "no_parent",
+        help = "Make the plan a child of the plan with this ID"
+    )]
+    pub parent: Option<u64>,
+    #[arg(long, help = "Detach the plan from its parent")]
+    pub no_parent: bool,
+    #[arg(
+        long,
+        conflicts_with = "no_auto_archive",
+        help = "Archive the plan automatically once all its steps are done"
+    )]
+    pub auto_archive: bool,
+    #[arg(long, help = "Stop archiving the plan automatically on completion")]
+    pub no_auto_archive: bool,
+    /// Display a step status under a custom name, e.g. `inprogress=doing`.
+    /// Replaces all existing labels; statuses not given show their default
+    /// names.
+    #[arg(
+        long = "status-label",
+        value_name = "STATUS=LABEL",
+        value_parser = parse_status_label,
+        conflicts_with = "clear_status_labels",
+        help = "Show a step status under a custom label (STATUS=LABEL, repeatable; replaces existing labels)"
+    )]
+    pub status_labels: Vec<(StepStatus, String)>,
+    #[arg(long, help = "Remove all custom status labels")]
+    pub clear_status_labels: bool,
+    #[arg(
+        long,
+        value_name = "DATE",
+        conflicts_with = "no_due",
+        help = "Date the plan should be done by, e.g. 2025-01-31"
+    )]
+    pub due: Option<String>,
+    #[arg(long, help = "Remove the plan's due date")]
+    pub no_due: bool,
+}
+
+/// A directory given on the command line as it is matched against plans:
+/// absolute, without `.` or `..` components and trailing separators. Falls
+/// back to the directory as given when the working directory is unknown.
+fn display_directory(directory: &str) -> String {
+    beacon_core::path::to_absolute(Some(Path::new(directory)))
+        .map_or_else(|_| directory.to_string(), |path| path.display().to_string())
+}
+
+/// Parses a `STATUS=LABEL` pair, accepting only canonical status names.
+fn parse_status_label(value: &str) -> std::result::Result<(StepStatus, String), String> {
+    let (status, label) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected STATUS=LABEL, got '{value}'"))?;
+    let status = match status.trim() {
+        "todo" => StepStatus::Todo,
+        "inprogress" | "in-progress" | "in_progress" => StepStatus::InProgress,
+        "done" => StepStatus::Done,
+        "blocked" => StepStatus::Blocked,
+        other => {
+            return Err(format!(
+                "unknown status '{other}', expected todo, in-progress, done or blocked"
+            ));
+        }
+    };
+    Ok((status, label.trim().to_string()))
+}
+
+impl From<UpdatePlanArgs> for UpdatePlan {
+    fn from(val: UpdatePlanArgs) -> Self {
+        UpdatePlan {
+            id: val.id,
+            title: val.title,
+            description: val.description,
+            directory: val.directory,
+            parent_plan_id: val.parent,
+            clear_parent: val.no_parent,
+            auto_archive: val
+                .auto_archive
+                .then_some(true)
+                .or(val.no_auto_archive.then_some(false)),
+            status_labels: if val.clear_status_labels {
+                Some(StatusLabels::default())
+            } else if val.status_labels.is_empty() {
+                None
+            } else {
+                let mut labels = StatusLabels::default();
+                for (status, label) in val.status_labels {
+                    labels.set(status, Some(label));
+                }
+                Some(labels)
+            },
+            due_date: val.due,
+            clear_due_date: val.no_due,
+        }
+    }
+}
+
 /// Archive a plan
 ///
 /// Move a plan to the archived state, hiding it from the default plan list.
 /// Archived plans are preserved and can be restored later with the unarchive
 /// command. Use this for completed projects or plans that are temporarily on
-/// hold.
+/// hold. A parent plan with active child plans is only archived with --force.
+///
+/// With --all-completed, every active plan whose steps are all done is
+/// archived at once instead.
 #[derive(Parser)]
 pub struct ArchivePlanArgs {
     /// ID of the plan to archive
-    #[arg(help = "Unique identifier of the plan to move to archived state")]
-    pub id: u64,
+    #[arg(
+        required_unless_present = "all_completed",
+        conflicts_with = "all_completed",
+        help = "Unique identifier of the plan to move to archived state"
+    )]
+    pub id: Option<u64>,
+    /// Archive even if steps are not done or child plans are still active
+    #[arg(
+        long,
+        conflicts_with = "all_completed",
+        help = "Archive even if steps are not done or child plans are still active"
+    )]
+    pub force: bool,
+    /// Archive every active plan whose steps are all done
+    #[arg(
+        long,
+        help = "Archive every active plan with at least one step whose steps are all done"
+    )]
+    pub all_completed: bool,
+    /// Only archive completed plans in this directory
+    #[arg(
+        long,
+        requires = "all_completed",
+        help = "Only archive completed plans in this directory or below it"
+    )]
+    pub directory: Option<String>,
 }
 
-impl From<ArchivePlanArgs> for Id {
+impl From<ArchivePlanArgs> for ArchivePlan {
     fn from(val: ArchivePlanArgs) -> Self {
-        Id { id: val.id }
+        ArchivePlan {
+            id: val.id.unwrap_or_default(),
+            force: val.force,
+        }
     }
 }
 
@@ -405,21 +2139,113 @@ impl From<UnarchivePlanArgs> for Id {
     }
 }
 
+/// Export plans with their steps as JSON
+///
+/// The export can be imported into another database with `b plan import`.
+/// Unlike `b db backup`, it holds only the chosen plans.
+#[derive(Parser)]
+pub struct ExportPlansArgs {
+    /// IDs of the plans to export; all active plans when none are given
+    pub ids: Vec<u64>,
+    /// File to write the export to instead of standard output
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Import plans exported with `b plan export`
+///
+/// A plan conflicts with an active plan that has the same title in the same
+/// directory. Without --on-conflict, each conflict is asked about on a
+/// terminal and skipped when there is no terminal to ask on.
+#[derive(Parser)]
+pub struct ImportPlansArgs {
+    /// File written by `b plan export`
+    pub file: PathBuf,
+    /// What to do with every plan that conflicts with an existing one
+    #[arg(long, value_enum)]
+    pub on_conflict: Option<ConflictArg>,
+}
+
 /// Delete a plan permanently
+///
+/// A plan with steps that are not done yet is only deleted with --force.
+/// Asks for confirmation on the terminal first unless --yes is given.
 #[derive(Parser)]
 pub struct DeletePlanArgs {
     /// ID of the plan to delete
     #[arg(help = "Unique identifier of the plan to permanently delete")]
     pub id: u64,
-    /// Confirm the deletion (required to prevent accidental deletion)
+    /// Delete without asking for confirmation, e.g. in scripts
+    #[arg(short, long, alias = "confirm", conflicts_with = "dry_run")]
+    pub yes: bool,
+    /// Show what would be deleted without deleting anything
     #[arg(long)]
-    pub confirm: bool,
+    pub dry_run: bool,
+    /// Delete even if steps are not done yet
+    #[arg(long, help = "Delete even if steps are not done yet")]
+    pub force: bool,
 }
 
 impl From<DeletePlanArgs> for DeletePlan {
     fn from(val: DeletePlanArgs) -> Self {
         DeletePlan {
             id: val.id,
+            confirmed: val.yes,
+            force: val.force,
+        }
+    }
+}
+
+/// Copy a plan as a template
+///
+/// Create a new active plan with the plan's description, directory and steps.
+/// The copied steps start out todo, without results.
+#[derive(Parser)]
+pub struct ClonePlanArgs {
+    /// ID of the plan to copy
+    #[arg(help = "Unique identifier of the plan to copy")]
+    pub id: u64,
+    /// Title of the copy
+    #[arg(long, help = "Title of the copy (defaults to the original's title)")]
+    pub title: Option<String>,
+}
+
+impl From<ClonePlanArgs> for ClonePlan {
+    fn from(val: ClonePlanArgs) -> Self {
+        ClonePlan {
+            id: val.id,
+            title: val.title,
+        }
+    }
+}
+
+/// Merge one plan into another
+///
+/// Append the source plan's steps to the end of the target plan, keeping their
+/// status, results and order, and concatenate the descriptions. The source
+/// plan is archived with a pointer to the target rather than deleted.
+#[derive(Parser)]
+pub struct MergePlanArgs {
+    /// ID of the plan to merge and archive
+    #[arg(help = "Unique identifier of the plan whose steps are moved")]
+    pub source: u64,
+    /// ID of the plan receiving the steps
+    #[arg(help = "Unique identifier of the plan to merge into")]
+    pub target: u64,
+    /// Prefix the moved steps' titles with the source plan's title
+    #[arg(long)]
+    pub prefix_titles: bool,
+    /// Confirm the merge (required to prevent accidental merges)
+    #[arg(long)]
+    pub confirm: bool,
+}
+
+impl From<MergePlanArgs> for MergePlans {
+    fn from(val: MergePlanArgs) -> Self {
+        MergePlans {
+            source_id: val.source,
+            target_id: val.target,
+            prefix_titles: val.prefix_titles,
             confirmed: val.confirm,
         }
     }
@@ -441,6 +2267,9 @@ pub struct SearchPlansArgs {
         help = "Include archived (completed/inactive) plans in search results"
     )]
     pub archived: bool,
+    /// Only plans in exactly this directory, not in its subdirectories
+    #[arg(long, help = "Only match plans in exactly this directory")]
+    pub exact: bool,
 }
 
 impl From<SearchPlansArgs> for SearchPlans {
@@ -448,14 +2277,244 @@ impl From<SearchPlansArgs> for SearchPlans {
         SearchPlans {
             directory: val.directory,
             archived: val.archived,
+            include_subdirectories: !val.exact,
+        }
+    }
+}
+
+/// Move plans to another directory
+///
+/// Every plan in the directory or below it, archived ones included, moves to
+/// the corresponding path under the new directory, so that plans follow a
+/// repository moved on disk. The old directory need not exist any more.
+#[derive(Parser)]
+pub struct MoveDirArgs {
+    /// Directory the plans are in now
+    #[arg(help = "Directory the plans are in now")]
+    pub from: String,
+    /// Directory to move the plans to
+    #[arg(help = "Directory to move the plans to, outside the old one")]
+    pub to: String,
+}
+
+impl From<MoveDirArgs> for ReassignDirectory {
+    fn from(val: MoveDirArgs) -> Self {
+        ReassignDirectory {
+            from: val.from,
+            to: val.to,
+        }
+    }
+}
+
+/// Append to a plan's working notes
+///
+/// Add text to the plan's notes under a heading with the current time. Notes
+/// are shown by `plan show` after the description.
+#[derive(Parser)]
+pub struct NotePlanArgs {
+    /// ID of the plan the note is about
+    #[arg(help = "Unique identifier of the plan")]
+    pub id: u64,
+    /// Text of the note
+    #[arg(help = "Text to append to the plan's notes")]
+    pub text: String,
+}
+
+impl From<NotePlanArgs> for AppendPlanNote {
+    fn from(val: NotePlanArgs) -> Self {
+        AppendPlanNote {
+            id: val.id,
+            text: val.text,
+        }
+    }
+}
+
+/// Summarize a plan for handoff
+///
+/// Print a concise markdown brief of the plan: its goal, completed steps with
+/// their results, steps in progress, and what is left. Suitable for pasting
+/// into a handoff message.
+#[derive(Parser)]
+pub struct HandoffPlanArgs {
+    /// ID of the plan to summarize
+    #[arg(help = "Unique identifier of the plan to summarize")]
+    pub id: u64,
+}
+
+impl From<HandoffPlanArgs> for Id {
+    fn from(val: HandoffPlanArgs) -> Self {
+        Id { id: val.id }
+    }
+}
+
+/// Show the outline of a plan
+///
+/// Print one line per step with its ID, title and status, leaving out
+/// descriptions, acceptance criteria, references and results.
+#[derive(Parser)]
+pub struct OutlinePlanArgs {
+    /// ID of the plan to outline
+    #[arg(help = "Unique identifier of the plan to outline")]
+    pub id: u64,
+}
+
+impl From<OutlinePlanArgs> for Id {
+    fn from(val: OutlinePlanArgs) -> Self {
+        Id { id: val.id }
+    }
+}
+
+/// Show a plan's changelog
+///
+/// Print the plan's changes, newest first and grouped by day in the local
+/// time zone: steps created, claimed, completed with the first line of their
+/// result, and plan-level changes.
+#[derive(Parser)]
+pub struct ChangelogPlanArgs {
+    /// ID of the plan
+    #[arg(help = "Unique identifier of the plan")]
+    pub id: u64,
+    /// Only show changes newer than this age or date
+    #[arg(
+        long,
+        value_name = "AGE",
+        help = "Only show changes newer than this, e.g. 30d, 2w, 12h, or a date"
+    )]
+    pub since: Option<String>,
+}
+
+impl From<ChangelogPlanArgs> for PlanChangelog {
+    fn from(val: ChangelogPlanArgs) -> Self {
+        PlanChangelog {
+            id: val.id,
+            since: val.since,
+        }
+    }
+}
+
+/// Report a plan's progress for a standup
+///
+/// Print a short markdown report of the steps completed since the cutoff
+/// with their results, the steps in progress, and the next todo steps.
+#[derive(Parser)]
+pub struct ReportPlanArgs {
+    /// ID of the plan
+    #[arg(help = "Unique identifier of the plan")]
+    pub id: u64,
+    /// Start of the reported period
+    #[arg(
+        long,
+        value_name = "AGE",
+        default_value = "24h",
+        help = "Report progress since this, e.g. 24h, 3d, or a date"
+    )]
+    pub since: String,
+}
+
+impl From<ReportPlanArgs> for GetPlanReport {
+    fn from(val: ReportPlanArgs) -> Self {
+        GetPlanReport {
+            id: val.id,
+            since: Some(val.since),
+        }
+    }
+}
+
+/// Repair the order of a plan's steps
+///
+/// Renumber the plan's steps in their current order, fixing steps that share
+/// a position, e.g. after a crash in the middle of an update.
+#[derive(Parser)]
+pub struct RepairPlanArgs {
+    /// ID of the plan to repair
+    #[arg(help = "Unique identifier of the plan whose steps to renumber")]
+    pub id: u64,
+}
+
+impl From<RepairPlanArgs> for Id {
+    fn from(val: RepairPlanArgs) -> Self {
+        Id { id: val.id }
+    }
+}
+
+/// Reset a plan's steps
+///
+/// Put every step of the plan back to todo in one go, e.g. to work through a
+/// recurring checklist again under the same plan ID. Step results are
+/// cleared unless --keep-results is given. Archived plans are left alone.
+#[derive(Parser)]
+pub struct ResetPlanArgs {
+    /// ID of the plan to reset
+    #[arg(help = "Unique identifier of the plan whose steps to reset")]
+    pub id: u64,
+    /// Keep the results of the steps that were done
+    #[arg(long)]
+    pub keep_results: bool,
+}
+
+impl From<ResetPlanArgs> for ResetPlanSteps {
+    fn from(val: ResetPlanArgs) -> Self {
+        ResetPlanSteps {
+            id: val.id,
+            keep_results: val.keep_results,
+        }
+    }
+}
+
+/// Watch a plan
+///
+/// Show the plan, then clear the screen and show it again whenever it or one
+/// of its steps changes, e.g. while an agent works through it in another
+/// terminal. Stop with Ctrl-C.
+#[derive(Parser)]
+pub struct WatchPlanArgs {
+    /// ID of the plan to watch
+    #[arg(help = "Unique identifier of the plan to watch")]
+    pub id: u64,
+    /// How often to check for changes
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value = "1",
+        value_parser = parse_interval,
+        help = "Seconds between checks for changes, e.g. 0.5"
+    )]
+    pub interval: Duration,
+}
+
+/// Parses a reference given as `URL` or `LABEL=URL`. Text before the first
+/// `=` is only a label if it has no `/`, `\`, `:` or `?`, so URLs and paths
+/// with `=` in them are taken whole.
+fn parse_reference(value: &str) -> std::result::Result<StepReference, String> {
+    if value.trim().is_empty() {
+        return Err("a reference cannot be empty".to_string());
+    }
+    match value.split_once('=') {
+        Some((label, url))
+            if !label.trim().is_empty() && !label.contains(['/', '\\', ':', '?']) =>
+        {
+            if url.trim().is_empty() {
+                return Err(format!("expected LABEL=URL, got '{value}' without a URL"));
+            }
+            Ok(StepReference::labelled(url.trim(), label.trim()))
         }
+        _ => Ok(value.into()),
     }
 }
 
+/// Parses a positive number of seconds, such as `2` or `0.5`.
+fn parse_interval(text: &str) -> std::result::Result<Duration, String> {
+    text.parse::<f64>()
+        .ok()
+        .filter(|seconds| *seconds > 0.0)
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("'{text}' is not a positive number of seconds"))
+}
+
 #[derive(Subcommand)]
 pub enum PlanCommands {
     /// Create a new plan
-    #[command(alias = "c")]
+    #[command(aliases = ["c", "new"])]
     Create(CreatePlanArgs),
     /// List all plans
     #[command(aliases = ["l", "ls"])]
@@ -463,6 +2522,9 @@ pub enum PlanCommands {
     /// Show details of a specific plan
     #[command(alias = "s")]
     Show(ShowPlanArgs),
+    /// Update a plan's details or parent
+    #[command(alias = "e")]
+    Update(UpdatePlanArgs),
     /// Archive a plan
     #[command(alias = "a")]
     Archive(ArchivePlanArgs),
@@ -472,22 +2534,64 @@ pub enum PlanCommands {
     /// Delete a plan permanently
     #[command(aliases = ["d", "rm"])]
     Delete(DeletePlanArgs),
+    /// Copy a plan as a template
+    Clone(ClonePlanArgs),
+    /// Merge one plan into another
+    #[command(alias = "m")]
+    Merge(MergePlanArgs),
+    /// Export plans with their steps as JSON
+    Export(ExportPlansArgs),
+    /// Import plans exported with `b plan export`
+    Import(ImportPlansArgs),
     /// Search for plans by directory
     #[command(alias = "f")]
     Search(SearchPlansArgs),
+    /// Move the plans in a directory to another one, e.g. after moving a
+    /// repository
+    MoveDir(MoveDirArgs),
+    /// Append to a plan's working notes
+    Note(NotePlanArgs),
+    /// Summarize a plan for handoff
+    #[command(alias = "h")]
+    Handoff(HandoffPlanArgs),
+    /// Show the outline of a plan
+    #[command(alias = "o")]
+    Outline(OutlinePlanArgs),
+    /// Show a plan's changes grouped by day
+    #[command(alias = "log")]
+    Changelog(ChangelogPlanArgs),
+    /// Report a plan's progress for a standup
+    Report(ReportPlanArgs),
+    /// Renumber a plan's steps, fixing steps that share a position
+    Repair(RepairPlanArgs),
+    /// Put every step of a plan back to todo, to work through it again
+    Reset(ResetPlanArgs),
+    /// Show a plan and show it again whenever it changes
+    Watch(WatchPlanArgs),
+}
+
+/// Applies quick-add syntax in the title of a new step when `quick` is set
+/// or the title contains quick-add tokens.
+fn quick_add(mut step: StepCreate, quick: bool) -> Result<StepCreate> {
+    if quick || quickadd::contains_tokens(&step.title) {
+        let line = std::mem::take(&mut step.title);
+        quickadd::parse(&line)?.apply_to(&mut step)?;
+    }
+    Ok(step)
 }
 
 /// Add a new step to a plan
 ///
 /// Example of wrapper pattern with more complex parameter mapping, showing
-/// how CLI-specific features (value_delimiter) can be added without affecting
-/// the core parameter structure.
+/// how CLI-specific features (parsing `LABEL=URL` references) can be added
+/// without affecting the core parameter structure.
 #[derive(Parser)]
 pub struct AddStepArgs {
     /// ID of the plan to add the step to
     #[arg(help = "Unique identifier of the plan to add this step to")]
     pub plan_id: u64,
-    /// Title of the step
+    /// Title of the step, optionally followed by quick-add tokens:
+    /// `!crit: CRITERIA`, `@ref:REFERENCE` and `~30m` for an estimate
     pub title: String,
     /// Optional detailed description of what needs to be done
     #[arg(
@@ -503,20 +2607,36 @@ pub struct AddStepArgs {
         help = "Optional acceptance criteria defining when the step is complete"
     )]
     pub acceptance_criteria: Option<String>,
-    /// References (file paths, URLs) - comma-separated list
+    /// Optional artifact the step is expected to produce
+    #[arg(
+        long,
+        help = "Optional artifact the step is expected to produce, shown next to the result once done"
+    )]
+    pub expected_output: Option<String>,
+    /// References (file paths, URLs), each given as URL or LABEL=URL
     #[arg(
         short,
         long,
-        value_delimiter = ',',
-        help = "References (file paths, URLs) as comma-separated list"
+        value_parser = parse_reference,
+        help = "Reference (file path, URL) as URL or LABEL=URL; repeat for more"
+    )]
+    pub references: Vec<StepReference>,
+    #[arg(
+        long,
+        help = "Require a second agent to verify the step once it is done"
+    )]
+    pub require_verification: bool,
+    #[arg(
+        long,
+        help = "Parse quick-add tokens in the title even if none are detected (applies escapes)"
     )]
-    pub references: Vec<String>,
+    pub quick: bool,
 }
 
 impl From<AddStepArgs> for StepCreate {
     /// Convert CLI arguments to core StepCreate
     ///
-    /// Note how CLI-specific features (value_delimiter) are handled
+    /// Note how CLI-specific features (reference parsing) are handled
     /// transparently by clap, while the core parameter structure remains
     /// simple and focused.
     fn from(val: AddStepArgs) -> Self {
@@ -525,7 +2645,10 @@ impl From<AddStepArgs> for StepCreate {
             title: val.title,
             description: val.description,
             acceptance_criteria: val.acceptance_criteria,
+            expected_output: val.expected_output,
             references: val.references,
+            verification_required: val.require_verification,
+            estimate_minutes: None,
         }
     }
 }
@@ -541,7 +2664,8 @@ pub struct InsertStepArgs {
     pub plan_id: u64,
     #[arg(help = "0-based position index where to insert the step (0 = first position)")]
     pub position: u32,
-    /// Title of the step
+    /// Title of the step, optionally followed by quick-add tokens:
+    /// `!crit: CRITERIA`, `@ref:REFERENCE` and `~30m` for an estimate
     pub title: String,
     #[arg(
         short,
@@ -555,13 +2679,29 @@ pub struct InsertStepArgs {
         help = "Optional acceptance criteria defining when the step is complete"
     )]
     pub acceptance_criteria: Option<String>,
+    /// Optional artifact the step is expected to produce
+    #[arg(
+        long,
+        help = "Optional artifact the step is expected to produce, shown next to the result once done"
+    )]
+    pub expected_output: Option<String>,
     #[arg(
         short,
         long,
-        value_delimiter = ',',
-        help = "References (file paths, URLs) as comma-separated list"
+        value_parser = parse_reference,
+        help = "Reference (file path, URL) as URL or LABEL=URL; repeat for more"
     )]
-    pub references: Vec<String>,
+    pub references: Vec<StepReference>,
+    #[arg(
+        long,
+        help = "Require a second agent to verify the step once it is done"
+    )]
+    pub require_verification: bool,
+    #[arg(
+        long,
+        help = "Parse quick-add tokens in the title even if none are detected (applies escapes)"
+    )]
+    pub quick: bool,
 }
 
 impl From<InsertStepArgs> for InsertStep {
@@ -572,7 +2712,10 @@ impl From<InsertStepArgs> for InsertStep {
                 title: val.title,
                 description: val.description,
                 acceptance_criteria: val.acceptance_criteria,
+                expected_output: val.expected_output,
                 references: val.references,
+                verification_required: val.require_verification,
+                estimate_minutes: None,
             },
             position: val.position,
         }
@@ -590,7 +2733,11 @@ impl From<InsertStepArgs> for InsertStep {
 pub struct UpdateStepArgs {
     #[arg(help = "Unique identifier of the step to update")]
     pub id: u64,
-    #[arg(short, long, help = "New status for the step (todo, inprogress, done)")]
+    #[arg(
+        short,
+        long,
+        help = "New status for the step (todo, inprogress, done, blocked)"
+    )]
     pub status: Option<StepStatusArg>,
     #[arg(short, long, help = "Updated title for the step")]
     pub title: Option<String>,
@@ -606,18 +2753,39 @@ pub struct UpdateStepArgs {
         help = "Updated acceptance criteria defining when the step is complete"
     )]
     pub acceptance_criteria: Option<String>,
+    #[arg(long, help = "Updated artifact the step is expected to produce")]
+    pub expected_output: Option<String>,
     #[arg(
         short,
         long,
-        value_delimiter = ',',
-        help = "Updated references (file paths, URLs) as comma-separated list"
+        value_parser = parse_reference,
+        help = "Updated reference (file path, URL) as URL or LABEL=URL; repeat for more"
     )]
-    pub references: Option<Vec<String>>,
+    pub references: Option<Vec<StepReference>>,
     #[arg(
         long,
         help = "Description of what was accomplished - required when changing status to 'done'"
     )]
     pub result: Option<String>,
+    #[arg(
+        long,
+        conflicts_with = "result",
+        help = "Drop the result a reopened step kept from when it was done"
+    )]
+    pub clear_result: bool,
+    #[arg(
+        long,
+        help = "Why the step is blocked - only accepted when changing status to 'blocked' or for a blocked step"
+    )]
+    pub blocked_reason: Option<String>,
+    #[arg(
+        long,
+        conflicts_with = "no_require_verification",
+        help = "Require a second agent to verify the step once it is done"
+    )]
+    pub require_verification: bool,
+    #[arg(long, help = "Stop requiring verification of the step")]
+    pub no_require_verification: bool,
 }
 
 impl From<UpdateStepArgs> for UpdateStep {
@@ -628,8 +2796,15 @@ impl From<UpdateStepArgs> for UpdateStep {
             title: val.title,
             description: val.description,
             acceptance_criteria: val.acceptance_criteria,
+            expected_output: val.expected_output,
             references: val.references,
             result: val.result,
+            clear_result: val.clear_result,
+            blocked_reason: val.blocked_reason,
+            verification_required: val
+                .require_verification
+                .then_some(true)
+                .or(val.no_require_verification.then_some(false)),
         }
     }
 }
@@ -644,6 +2819,9 @@ impl From<UpdateStepArgs> for UpdateStep {
 pub struct ShowStepArgs {
     #[arg(help = "Unique identifier of the step to show details for")]
     pub id: u64,
+    /// Print the canonical beacon:// URL instead of the step details
+    #[arg(long, help = "Print the canonical beacon:// URL for the step")]
+    pub url: bool,
 }
 
 impl From<ShowStepArgs> for Id {
@@ -652,6 +2830,165 @@ impl From<ShowStepArgs> for Id {
     }
 }
 
+/// Show the step to work on next in a plan
+///
+/// Shows the first step of the plan, in order, that is still todo and not
+/// blocked by an unfinished dependency. Steps in progress or done are
+/// skipped. When every step is done, suggests archiving the plan.
+#[derive(Parser)]
+pub struct NextStepArgs {
+    #[arg(help = "Unique identifier of the plan to find the next step in")]
+    pub plan_id: u64,
+}
+
+impl From<NextStepArgs> for Id {
+    fn from(val: NextStepArgs) -> Self {
+        Id { id: val.plan_id }
+    }
+}
+
+/// Put an in-progress step back to todo
+///
+/// Gives up a step claimed by an agent that crashed or could not finish it,
+/// so that it can be claimed again. Steps that are todo or done are left
+/// alone.
+#[derive(Parser)]
+pub struct ReleaseStepArgs {
+    #[arg(help = "Unique identifier of the in-progress step to release")]
+    pub id: u64,
+}
+
+impl From<ReleaseStepArgs> for Id {
+    fn from(val: ReleaseStepArgs) -> Self {
+        Id { id: val.id }
+    }
+}
+
+/// Move a step to the trash
+///
+/// Hides the step from its plan and its progress. Trashed steps can be listed
+/// with `step trash` and brought back with `step restore` until they are
+/// purged. Asks for confirmation on the terminal first unless --yes is given.
+#[derive(Parser)]
+pub struct RemoveStepArgs {
+    #[arg(help = "Unique identifier of the step to move to the trash")]
+    pub id: u64,
+    /// Remove without asking for confirmation, e.g. in scripts
+    #[arg(short, long, conflicts_with = "dry_run")]
+    pub yes: bool,
+    /// Show what would be removed without removing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl From<RemoveStepArgs> for Id {
+    fn from(val: RemoveStepArgs) -> Self {
+        Id { id: val.id }
+    }
+}
+
+/// Find the steps of a plan by their text
+///
+/// Lists the steps whose title or description contains the query, ignoring
+/// case, with their IDs, in plan order.
+#[derive(Parser)]
+pub struct FindStepsArgs {
+    #[arg(help = "Unique identifier of the plan whose steps to search")]
+    pub plan_id: u64,
+    #[arg(help = "Text to find in step titles and descriptions")]
+    pub query: String,
+    #[arg(short, long, help = "Only find steps with this status")]
+    pub status: Option<StepStatusArg>,
+}
+
+impl From<FindStepsArgs> for FindSteps {
+    fn from(val: FindStepsArgs) -> Self {
+        FindSteps {
+            plan_id: val.plan_id,
+            query: val.query,
+            status: val.status.map(|s| s.to_string()),
+        }
+    }
+}
+
+/// List the steps with a status across plans
+///
+/// Groups the steps under their plans, most recently updated first, so that
+/// the work in progress everywhere shows in one place. Steps of archived
+/// plans are left out unless --archived is given.
+#[derive(Parser)]
+pub struct ListStepsByStatusArgs {
+    #[arg(
+        short,
+        long,
+        default_value = "in-progress",
+        help = "Status of the steps to list"
+    )]
+    pub status: StepStatusArg,
+    #[arg(long, help = "Include the steps of archived plans")]
+    pub archived: bool,
+}
+
+impl From<ListStepsByStatusArgs> for ListStepsByStatus {
+    fn from(val: ListStepsByStatusArgs) -> Self {
+        ListStepsByStatus {
+            status: val.status.to_string(),
+            include_archived_plans: val.archived,
+        }
+    }
+}
+
+/// List the steps of a plan that are in the trash
+#[derive(Parser)]
+pub struct TrashArgs {
+    #[arg(help = "Unique identifier of the plan whose trash to list")]
+    pub plan_id: u64,
+}
+
+impl From<TrashArgs> for Id {
+    fn from(val: TrashArgs) -> Self {
+        Id { id: val.plan_id }
+    }
+}
+
+/// Restore a step from the trash
+///
+/// The step is placed at the end of its plan.
+#[derive(Parser)]
+pub struct RestoreStepArgs {
+    #[arg(help = "Unique identifier of the trashed step to restore")]
+    pub id: u64,
+}
+
+impl From<RestoreStepArgs> for Id {
+    fn from(val: RestoreStepArgs) -> Self {
+        Id { id: val.id }
+    }
+}
+
+/// Permanently delete steps from the trash
+///
+/// Purges the steps of every plan that were trashed more than the given
+/// number of days ago. This cannot be undone.
+#[derive(Parser)]
+pub struct PurgeTrashArgs {
+    /// Only purge steps trashed more than this many days ago
+    #[arg(
+        long,
+        default_value_t = 30,
+        help = "Only purge steps trashed more than this many days ago (0 purges all)"
+    )]
+    pub older_than_days: u32,
+}
+
+impl From<PurgeTrashArgs> for PurgeTrash {
+    fn from(val: PurgeTrashArgs) -> Self {
+        PurgeTrash {
+            older_than_days: val.older_than_days,
+        }
+    }
+}
+
 /// Swap the order of two steps within the same plan
 ///
 /// Reorders steps by swapping the positions of two existing steps. Both steps
@@ -675,6 +3012,111 @@ impl From<SwapStepsArgs> for SwapSteps {
     }
 }
 
+/// Move a step to another position in its plan
+///
+/// Position is 0-indexed (0 = first position). The steps between the old
+/// and new positions shift by one to make room, so a step can be moved to
+/// the front of a plan in one go rather than with repeated swaps.
+#[derive(Parser)]
+pub struct MoveStepArgs {
+    #[arg(help = "Unique identifier of the step to move")]
+    pub step_id: u64,
+    #[arg(help = "0-based position to move the step to (0 = first position)")]
+    pub new_position: u32,
+}
+
+impl From<MoveStepArgs> for MoveStep {
+    fn from(val: MoveStepArgs) -> Self {
+        MoveStep {
+            step_id: val.step_id,
+            new_position: val.new_position,
+        }
+    }
+}
+
+/// Move a step to another plan
+///
+/// The step keeps its status, result and references. It goes to the end of
+/// the target plan unless a 0-indexed position is given; the steps after it
+/// in its old plan move up to close the gap.
+#[derive(Parser)]
+pub struct MoveStepToPlanArgs {
+    #[arg(help = "Unique identifier of the step to move")]
+    pub step_id: u64,
+    #[arg(help = "Unique identifier of the plan to move the step to")]
+    pub plan_id: u64,
+    #[arg(
+        long,
+        help = "0-based position in the target plan (defaults to the end)"
+    )]
+    pub position: Option<u32>,
+}
+
+impl From<MoveStepToPlanArgs> for MoveStepToPlan {
+    fn from(val: MoveStepToPlanArgs) -> Self {
+        MoveStepToPlan {
+            step_id: val.step_id,
+            plan_id: val.plan_id,
+            position: val.position,
+        }
+    }
+}
+
+/// Group a step under a phase
+///
+/// Steps sharing a phase are shown together under a "Phase" header when the
+/// plan is displayed, e.g. "Design", "Implementation" and "Testing" in large
+/// plans. Phases are listed in the order their first step appears.
+#[derive(Parser)]
+pub struct SetStepPhaseArgs {
+    #[arg(help = "Unique identifier of the step to group")]
+    pub id: u64,
+    #[arg(
+        required_unless_present = "clear",
+        help = "Name of the phase, e.g. \"Design\""
+    )]
+    pub phase: Option<String>,
+    #[arg(
+        long,
+        conflicts_with = "phase",
+        help = "Remove the step from its phase"
+    )]
+    pub clear: bool,
+}
+
+impl From<SetStepPhaseArgs> for SetStepPhase {
+    fn from(val: SetStepPhaseArgs) -> Self {
+        SetStepPhase {
+            id: val.id,
+            phase: val.phase,
+        }
+    }
+}
+
+/// Verify the completion of a step
+///
+/// Steps created with --require-verification only count as complete once a
+/// second agent, other than the one that did the work, confirms the result.
+#[derive(Parser)]
+pub struct VerifyStepArgs {
+    #[arg(help = "Unique identifier of the step to verify")]
+    pub id: u64,
+    #[arg(long = "by", help = "Who is verifying the step")]
+    pub verifier: String,
+    #[arg(short = 'm', long = "message", help = "Notes on what was checked")]
+    pub comment: Option<String>,
+}
+
+impl From<VerifyStepArgs> for VerifyStep {
+    fn from(val: VerifyStepArgs) -> Self {
+        VerifyStep {
+            id: val.id,
+            verifier: val.verifier,
+            comment: val.comment,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 pub enum StepCommands {
     /// Add a new step to a plan
@@ -689,9 +3131,360 @@ pub enum StepCommands {
     /// Show details of a specific step
     #[command(alias = "s")]
     Show(ShowStepArgs),
+    /// Find the steps of a plan by words in their title or description
+    #[command(alias = "f")]
+    Find(FindStepsArgs),
+    /// List the steps with a status across all plans, such as the work in
+    /// progress
+    #[command(alias = "ls")]
+    List(ListStepsByStatusArgs),
+    /// Show the step to work on next in a plan
+    #[command(alias = "n")]
+    Next(NextStepArgs),
+    /// Put an in-progress step back to todo
+    Release(ReleaseStepArgs),
+    /// Move a step to the trash
+    #[command(alias = "rm")]
+    Remove(RemoveStepArgs),
+    /// List the steps of a plan that are in the trash
+    Trash(TrashArgs),
+    /// Restore a step from the trash
+    Restore(RestoreStepArgs),
+    /// Permanently delete steps from the trash
+    Purge(PurgeTrashArgs),
     /// Swap the order of two steps within the same plan
     #[command(alias = "sw")]
     Swap(SwapStepsArgs),
+    /// Move a step to another position in its plan
+    #[command(alias = "mv")]
+    Move(MoveStepArgs),
+    /// Move a step to another plan
+    MoveToPlan(MoveStepToPlanArgs),
+    /// Group a step under a phase
+    #[command(alias = "ph")]
+    Phase(SetStepPhaseArgs),
+    /// Verify the completion of a step that requires verification
+    #[command(alias = "v")]
+    Verify(VerifyStepArgs),
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Show the value of a setting and where it comes from
+    Get(GetConfigArgs),
+    /// Change a setting for everyone using this database
+    Set(SetConfigArgs),
+    /// List all settings with their values and what they control
+    #[command(alias = "ls")]
+    List,
+    /// Show the CLI options in effect, merged from the configuration file,
+    /// the environment and flags, and where each value comes from
+    ///
+    /// The configuration file is $XDG_CONFIG_HOME/beacon/config.toml, with
+    /// the keys database_file, no_color, no_pager, pager and format (rich or
+    /// plain). BEACON_DATABASE_FILE, BEACON_NO_COLOR, BEACON_NO_PAGER,
+    /// BEACON_PAGER and BEACON_FORMAT override it, and flags override both.
+    Show,
+}
+
+#[derive(Subcommand)]
+pub enum DbCommands {
+    /// Reclaim the space of deleted data, refresh query statistics and check
+    /// the database's integrity
+    ///
+    /// Waits for other connections, such as a running MCP server, to finish
+    /// writing, and fails if the database stays busy.
+    Maintenance,
+    /// Copy the database to a file, safely while it is in use
+    Backup(BackupArgs),
+    /// Replace the database's contents with a backup
+    Restore(RestoreArgs),
+}
+
+#[derive(Subcommand)]
+pub enum TemplateCommands {
+    /// Define a named list of steps to add to plans
+    Create(CreateTemplateArgs),
+    /// List the templates with their steps
+    #[command(alias = "ls")]
+    List,
+    /// Append a template's steps to the end of a plan
+    Apply(ApplyTemplateArgs),
+}
+
+/// Create a step template
+#[derive(Parser)]
+pub struct CreateTemplateArgs {
+    /// Name to apply the template by, unique regardless of case
+    pub name: String,
+    /// Title of a step, in order; repeat for each step
+    #[arg(long = "step", value_name = "TITLE", required = true)]
+    pub steps: Vec<String>,
+}
+
+impl From<CreateTemplateArgs> for CreateTemplate {
+    fn from(val: CreateTemplateArgs) -> Self {
+        CreateTemplate {
+            name: val.name,
+            steps: val
+                .steps
+                .into_iter()
+                .map(|title| TemplateStep {
+                    title,
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Apply a step template to a plan
+#[derive(Parser)]
+pub struct ApplyTemplateArgs {
+    /// ID of the plan to add the steps to
+    pub plan_id: u64,
+    /// Name of the template, ignoring case
+    pub name: String,
+}
+
+impl From<ApplyTemplateArgs> for ApplyTemplate {
+    fn from(val: ApplyTemplateArgs) -> Self {
+        ApplyTemplate {
+            plan_id: val.plan_id,
+            name: val.name,
+        }
+    }
+}
+
+/// Back up the database
+#[derive(Parser)]
+pub struct BackupArgs {
+    /// File to write the backup to; an existing file is overwritten
+    pub file: PathBuf,
+}
+
+/// Restore the database from a backup
+#[derive(Parser)]
+pub struct RestoreArgs {
+    /// Backup file to restore, such as one written by `b db backup`
+    pub file: PathBuf,
+    /// Restore even though steps are in progress, discarding their progress
+    #[arg(long, alias = "confirm")]
+    pub yes: bool,
+}
+
+/// Show a workspace setting
+#[derive(Parser)]
+pub struct GetConfigArgs {
+    /// Name of the setting, e.g. auto_archive
+    pub key: String,
+}
+
+/// Change a workspace setting
+#[derive(Parser)]
+pub struct SetConfigArgs {
+    /// Name of the setting, e.g. auto_archive
+    pub key: String,
+    /// New value, e.g. true or 36h
+    pub value: String,
+}
+
+/// Report open steps tracked more than once
+///
+/// Groups the open steps of active plans whose titles match once case,
+/// whitespace and punctuation are ignored, listing each step with its plan so
+/// that duplicates can be consolidated by hand.
+#[derive(Parser)]
+pub struct DedupeReportArgs {
+    /// Only compare steps of plans in this directory or below it
+    #[arg(
+        long,
+        help = "Only compare steps of plans in this directory (relative or absolute)"
+    )]
+    pub directory: Option<String>,
+}
+
+impl From<DedupeReportArgs> for DuplicateStepsReport {
+    fn from(val: DedupeReportArgs) -> Self {
+        DuplicateStepsReport {
+            directory: val.directory,
+        }
+    }
+}
+
+/// List the changes made to plans and steps
+///
+/// Every plan created, archived or deleted and every step added, updated,
+/// claimed, removed or swapped is recorded as an event, oldest first.
+#[derive(Parser)]
+pub struct EventsArgs {
+    /// Only show events newer than this age or date
+    #[arg(
+        long,
+        value_name = "AGE",
+        help = "Only show events newer than this, e.g. 30d, 2w, 12h, or a date"
+    )]
+    pub since: Option<String>,
+    /// Maximum number of events to show
+    #[arg(long, help = "Show at most this many events, oldest first")]
+    pub limit: Option<u32>,
+}
+
+/// Report statistics
+///
+/// Counts what happened to plans and steps from `--since` until now, or over
+/// all time without it.
+#[derive(Parser)]
+pub struct StatsArgs {
+    /// Only count what happened since this age or date
+    #[arg(
+        long,
+        value_name = "AGE",
+        help = "Only count what happened since this, e.g. 7d, 2w, 12h, or a date"
+    )]
+    pub since: Option<String>,
+}
+
+impl From<StatsArgs> for GetStats {
+    fn from(val: StatsArgs) -> Self {
+        GetStats { since: val.since }
+    }
+}
+
+impl From<EventsArgs> for ListEvents {
+    fn from(val: EventsArgs) -> Self {
+        ListEvents {
+            since: val.since,
+            limit: val.limit,
+        }
+    }
+}
+
+/// Show productivity metrics
+///
+/// Reports simple throughput numbers, such as how many steps were marked done
+/// during the current week (Monday to Sunday, local time).
+#[derive(Parser)]
+pub struct MetricsArgs {
+    /// Count steps completed during the current week
+    #[arg(
+        long,
+        required = true,
+        help = "Count steps completed this week (since Monday 00:00 local time)"
+    )]
+    pub week: bool,
+}
+
+impl MetricsArgs {
+    /// Returns the time window to report on.
+    ///
+    /// The window is the current week, from Monday 00:00 to the following
+    /// Monday 00:00 in the local time zone. `--week` is the only window so far
+    /// and clap requires it.
+    fn window(&self) -> Result<(Timestamp, Timestamp)> {
+        let today = Zoned::now().start_of_day()?;
+        let days_since_monday = i64::from(today.weekday().to_monday_zero_offset());
+        let start = today.checked_sub(days_since_monday.days())?;
+        let end = start.checked_add(1.week())?;
+        Ok((start.timestamp(), end.timestamp()))
+    }
+}
+
+/// Measure performance against a throwaway database
+///
+/// The defaults keep a run to a few seconds on typical hardware.
+#[derive(Parser)]
+pub struct BenchArgs {
+    /// Number of synthetic plans to seed
+    #[arg(long, default_value_t = 500)]
+    pub plans: usize,
+    /// Number of steps in each seeded plan
+    #[arg(long, default_value_t = 20)]
+    pub steps: usize,
+    /// How many times each operation is timed, at most
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u16).range(1..))]
+    pub samples: u16,
+}
+
+impl BenchArgs {
+    fn config(&self) -> BenchConfig {
+        BenchConfig {
+            plans: self.plans,
+            steps: self.steps,
+            samples: usize::from(self.samples),
+        }
+    }
+}
+
+/// Command-line argument representation of conflict policies
+///
+/// Used with the `--on-conflict` flag of `plan import`.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum ConflictArg {
+    /// Keep the existing plan and leave the imported one out
+    Skip,
+    /// Import the plan with " (imported YYYY-MM-DD)" appended to its title
+    Duplicate,
+    /// Append the imported steps the existing plan doesn't have yet to it
+    Merge,
+}
+
+impl From<ConflictArg> for ConflictPolicy {
+    fn from(val: ConflictArg) -> Self {
+        match val {
+            ConflictArg::Skip => ConflictPolicy::Skip,
+            ConflictArg::Duplicate => ConflictPolicy::DuplicateWithSuffix,
+            ConflictArg::Merge => ConflictPolicy::Merge,
+        }
+    }
+}
+
+/// Print a summary of a directory's plans for shell prompts
+#[derive(Parser)]
+pub struct BadgeArgs {
+    /// Directory whose plans are counted, with those of its subdirectories
+    #[arg(default_value = ".")]
+    pub directory: String,
+}
+
+/// Print a shell completion script
+#[derive(Parser)]
+pub struct CompletionsArgs {
+    /// Shell to print the completion script for
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+/// Print completion candidates for a partial command line
+#[derive(Parser)]
+pub struct CompleteArgs {
+    /// Words of the command line after `b`, the last one being the word
+    /// under the cursor
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub words: Vec<String>,
+}
+
+/// Command-line argument representation of plan completion filters
+///
+/// Used with the `--completion` flag of `plan list`.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum CompletionArg {
+    /// Plans whose steps are all done
+    Complete,
+    /// Plans with a step that is not done
+    Incomplete,
+    /// Plans without steps
+    Empty,
+}
+
+impl std::fmt::Display for CompletionArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompletionArg::Complete => write!(f, "complete"),
+            CompletionArg::Incomplete => write!(f, "incomplete"),
+            CompletionArg::Empty => write!(f, "empty"),
+        }
+    }
 }
 
 /// Command-line argument representation of step status values
@@ -704,9 +3497,12 @@ pub enum StepStatusArg {
     /// Mark step as todo
     Todo,
     /// Mark step as in progress
+    #[value(alias = "inprogress")]
     InProgress,
     /// Mark step as done
     Done,
+    /// Mark step as blocked
+    Blocked,
 }
 
 impl std::fmt::Display for StepStatusArg {
@@ -715,6 +3511,7 @@ impl std::fmt::Display for StepStatusArg {
             StepStatusArg::Todo => write!(f, "todo"),
             StepStatusArg::InProgress => write!(f, "inprogress"),
             StepStatusArg::Done => write!(f, "done"),
+            StepStatusArg::Blocked => write!(f, "blocked"),
         }
     }
 }
@@ -0,0 +1,276 @@
+//! Shell completion for `b`.
+//!
+//! The scripts printed by `b completions <shell>` are thin: on every tab
+//! press they call the hidden `b complete -- <words>` subcommand, which walks
+//! the clap command tree to offer subcommands and flags, and looks up plan
+//! and step IDs in the database where a command expects one. Keeping the
+//! logic here means the scripts never go stale as commands are added.
+
+use std::{fmt, path::PathBuf};
+
+use clap::{Arg, Command, ValueEnum};
+
+/// Shells that completion scripts can be generated for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    /// Returns the completion script, to be sourced by the shell.
+    pub fn script(self) -> &'static str {
+        match self {
+            Shell::Bash => BASH_SCRIPT,
+            Shell::Zsh => ZSH_SCRIPT,
+            Shell::Fish => FISH_SCRIPT,
+        }
+    }
+}
+
+/// Candidates are printed as `value:description`, so bash keeps the part
+/// before the first colon and fish turns the colon into a tab.
+const BASH_SCRIPT: &str = r#"_b() {
+    local IFS=$'\n'
+    COMPREPLY=($(b complete -- "${COMP_WORDS[@]:1:COMP_CWORD}" 2>/dev/null | cut -d: -f1))
+}
+complete -o default -F _b b
+"#;
+
+const ZSH_SCRIPT: &str = r#"#compdef b
+_b() {
+    local -a candidates
+    candidates=("${(@f)$(b complete -- "${(@)words[2,CURRENT]}" 2>/dev/null)}")
+    if [[ -n "${candidates[1]}" ]]; then
+        _describe 'b' candidates
+    else
+        _files
+    fi
+}
+if [[ "${funcstack[1]}" = "_b" ]]; then
+    _b "$@"
+else
+    compdef _b b
+fi
+"#;
+
+const FISH_SCRIPT: &str = r#"function __b_complete
+    set -l words (commandline -opc)[2..-1] (commandline -ct)
+    b complete -- $words 2>/dev/null | string replace ':' \t
+end
+complete -c b -f -a '(__b_complete)'
+"#;
+
+/// What can be typed at the cursor.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Completion {
+    /// Subcommand names or flags, known without the database.
+    Candidates(Vec<Candidate>),
+    /// The ID of a plan.
+    PlanIds,
+    /// The ID of a step.
+    StepIds,
+}
+
+/// A completion candidate with an optional description.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub value: String,
+    pub help: Option<String>,
+}
+
+impl Candidate {
+    pub fn new(value: impl Into<String>, help: Option<&str>) -> Self {
+        // Only the first line fits the one-candidate-per-line output
+        let help = help
+            .and_then(|help| help.lines().next())
+            .map(str::trim)
+            .filter(|help| !help.is_empty())
+            .map(String::from);
+        Self {
+            value: value.into(),
+            help,
+        }
+    }
+}
+
+impl fmt::Display for Candidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.help {
+            Some(help) => write!(f, "{}:{help}", self.value),
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
+/// Works out what can be typed at the cursor.
+///
+/// `words` are the words of the command line after the program name, the
+/// last being the one under the cursor, which may be empty.
+pub fn complete(command: &mut Command, words: &[String]) -> Completion {
+    command.build();
+    let typed = words.split_last().map_or(&[][..], |(_, typed)| typed);
+
+    let mut command = &*command;
+    let mut path = Vec::new();
+    let mut positionals = 0;
+    let mut expects_value = false;
+    for word in typed {
+        if expects_value {
+            expects_value = false;
+        } else if let Some(long) = word.strip_prefix("--") {
+            expects_value = !long.contains('=')
+                && find_flag(command, |arg| arg.get_long() == Some(long)).is_some_and(takes_value);
+        } else if let Some(short) = word.strip_prefix('-').filter(|short| !short.is_empty()) {
+            // Only a lone short flag can be followed by its value
+            let mut chars = short.chars();
+            expects_value = match (chars.next(), chars.next()) {
+                (Some(short), None) => find_flag(command, |arg| arg.get_short() == Some(short))
+                    .is_some_and(takes_value),
+                _ => false,
+            };
+        } else if let Some(subcommand) = command.find_subcommand(word) {
+            command = subcommand;
+            path.push(subcommand.get_name());
+            positionals = 0;
+        } else {
+            positionals += 1;
+        }
+    }
+
+    if expects_value {
+        return Completion::Candidates(Vec::new());
+    }
+    if words.last().is_some_and(|current| current.starts_with('-')) {
+        return Completion::Candidates(flags(command));
+    }
+    if command.has_subcommands() {
+        return Completion::Candidates(subcommands(command));
+    }
+    match (path.as_slice(), positionals) {
+        (["plan", "show" | "archive" | "delete"], 0) => Completion::PlanIds,
+        (["step", "show" | "update"], 0) => Completion::StepIds,
+        _ => Completion::Candidates(Vec::new()),
+    }
+}
+
+/// Returns the database file given on the command line being completed, so
+/// IDs are looked up where the command will run.
+pub fn database_file(words: &[String]) -> Option<PathBuf> {
+    words
+        .iter()
+        .enumerate()
+        .find_map(|(i, word)| match word.strip_prefix("--database-file") {
+            Some("") => words.get(i + 1).map(PathBuf::from),
+            Some(value) => value.strip_prefix('=').map(PathBuf::from),
+            None => None,
+        })
+}
+
+fn find_flag(command: &Command, matches: impl Fn(&Arg) -> bool) -> Option<&Arg> {
+    command
+        .get_arguments()
+        .find(|arg| !arg.is_positional() && matches(arg))
+}
+
+fn takes_value(arg: &Arg) -> bool {
+    arg.get_action().takes_values()
+}
+
+fn flags(command: &Command) -> Vec<Candidate> {
+    command
+        .get_arguments()
+        .filter(|arg| !arg.is_hide_set())
+        .filter_map(|arg| {
+            let long = arg.get_long()?;
+            let help = arg.get_help().map(ToString::to_string);
+            Some(Candidate::new(format!("--{long}"), help.as_deref()))
+        })
+        .collect()
+}
+
+fn subcommands(command: &Command) -> Vec<Candidate> {
+    command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+        .map(|subcommand| {
+            let about = subcommand.get_about().map(ToString::to_string);
+            Candidate::new(subcommand.get_name(), about.as_deref())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+    use crate::args::Args;
+
+    fn complete_line(line: &str) -> Completion {
+        let mut words: Vec<String> = line.split(' ').map(String::from).collect();
+        if line.is_empty() {
+            words.clear();
+        }
+        complete(&mut Args::command(), &words)
+    }
+
+    fn values(completion: Completion) -> Vec<String> {
+        match completion {
+            Completion::Candidates(candidates) => candidates.into_iter().map(|c| c.value).collect(),
+            other => panic!("expected candidates, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_complete_subcommands_and_flags() {
+        let top = values(complete_line(""));
+        assert!(top.contains(&"plan".to_string()), "{top:?}");
+        assert!(top.contains(&"completions".to_string()), "{top:?}");
+        assert!(!top.contains(&"complete".to_string()), "{top:?}");
+
+        let plan = values(complete_line("p "));
+        assert!(plan.contains(&"show".to_string()), "{plan:?}");
+
+        let flags = values(complete_line("plan show 1 --"));
+        assert!(flags.contains(&"--compact".to_string()), "{flags:?}");
+        assert!(flags.contains(&"--database-file".to_string()), "{flags:?}");
+    }
+
+    #[test]
+    fn test_complete_ids_where_commands_expect_them() {
+        assert_eq!(complete_line("plan show "), Completion::PlanIds);
+        assert_eq!(complete_line("p a 1"), Completion::PlanIds);
+        assert_eq!(
+            complete_line("--database-file x.db plan delete "),
+            Completion::PlanIds
+        );
+        assert_eq!(complete_line("step update "), Completion::StepIds);
+        assert!(values(complete_line("plan show 1 ")).is_empty());
+        assert!(values(complete_line("step update 1 --status ")).is_empty());
+    }
+
+    #[test]
+    fn test_database_file() {
+        let words = |line: &str| line.split(' ').map(String::from).collect::<Vec<_>>();
+        assert_eq!(
+            database_file(&words("--database-file a.db plan show ")),
+            Some(PathBuf::from("a.db"))
+        );
+        assert_eq!(
+            database_file(&words("plan show --database-file=b.db ")),
+            Some(PathBuf::from("b.db"))
+        );
+        assert_eq!(database_file(&words("plan show ")), None);
+    }
+
+    #[test]
+    fn test_candidate_display() {
+        assert_eq!(
+            Candidate::new("12", Some("Ship it\nmore")).to_string(),
+            "12:Ship it"
+        );
+        assert_eq!(Candidate::new("--all", None).to_string(), "--all");
+    }
+}
@@ -0,0 +1,381 @@
+//! CLI defaults read from a configuration file and the environment
+//!
+//! Options are read from `$XDG_CONFIG_HOME/beacon/config.toml`, then from
+//! `BEACON_*` environment variables, then from command-line flags, each
+//! overriding the one before:
+//!
+//! ```toml
+//! database_file = "/home/me/notes/beacon.db"
+//! no_color = false
+//! no_pager = false
+//! pager = "less -R"
+//! format = "plain"
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    env::var,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// Pager used when neither the configuration nor the environment name one.
+const DEFAULT_PAGER: &str = "less -FRX";
+
+/// How command output is printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Markdown styled for the terminal
+    #[default]
+    Rich,
+    /// Markdown as plain text
+    Plain,
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Rich => write!(f, "rich"),
+            OutputFormat::Plain => write!(f, "plain"),
+        }
+    }
+}
+
+/// Where the effective value of an option comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// Nothing set it
+    Default,
+    /// The configuration file
+    File,
+    /// The named environment variable
+    Environment(&'static str),
+    /// A command-line flag
+    Flag,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::File => write!(f, "configuration file"),
+            Source::Environment(name) => write!(f, "environment ({name})"),
+            Source::Flag => write!(f, "flag"),
+        }
+    }
+}
+
+/// The effective value of an option and where it comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T> Setting<T> {
+    fn new(value: T, source: Source) -> Self {
+        Self { value, source }
+    }
+}
+
+/// Options given on the command line, which override everything else.
+#[derive(Debug, Default)]
+pub struct Flags {
+    pub database_file: Option<PathBuf>,
+    pub no_color: bool,
+    pub no_pager: bool,
+}
+
+/// Contents of the configuration file. Every key is optional.
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    database_file: Option<PathBuf>,
+    no_color: Option<bool>,
+    no_pager: Option<bool>,
+    pager: Option<String>,
+    format: Option<OutputFormat>,
+    /// Keys beacon doesn't know, reported as warnings
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+impl ConfigFile {
+    /// Parses the configuration file at `path`, resolving a relative
+    /// database file against the file's directory.
+    fn parse(path: &Path, contents: &str) -> Result<Self> {
+        let mut file: Self = toml::from_str(contents)
+            .with_context(|| format!("Invalid configuration file {}", path.display()))?;
+        if let (Some(database_file), Some(directory)) = (&mut file.database_file, path.parent())
+            && database_file.is_relative()
+        {
+            *database_file = directory.join(&*database_file);
+        }
+        Ok(file)
+    }
+}
+
+/// CLI options merged from the configuration file, the environment and
+/// command-line flags.
+#[derive(Debug)]
+pub struct CliConfig {
+    /// Path of the configuration file, whether or not it exists
+    pub path: Option<PathBuf>,
+    /// Whether the configuration file was found and read
+    pub file_found: bool,
+    /// Database to use; `None` uses the planner's default location
+    pub database_file: Setting<Option<PathBuf>>,
+    pub no_color: Setting<bool>,
+    pub no_pager: Setting<bool>,
+    /// Command to page output through
+    pub pager: Setting<String>,
+    pub format: Setting<OutputFormat>,
+    /// Problems that didn't stop the configuration from loading, such as
+    /// unknown keys
+    pub warnings: Vec<String>,
+}
+
+impl CliConfig {
+    /// Loads the configuration file from `$XDG_CONFIG_HOME/beacon`, if
+    /// there is one, and merges it with the environment and `flags`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration file exists but cannot be read
+    /// or is not valid TOML with values of the right types.
+    pub fn load(flags: Flags) -> Result<Self> {
+        let path = xdg::BaseDirectories::with_prefix("beacon").get_config_file("config.toml");
+        let file = match &path {
+            Some(path) => match fs::read_to_string(path) {
+                Ok(contents) => Some(ConfigFile::parse(path, &contents)?),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!("Failed to read configuration file {}", path.display())
+                    });
+                }
+            },
+            None => None,
+        };
+
+        Ok(Self::merge(path, file, |name| var(name).ok(), flags))
+    }
+
+    /// Merges the configuration file with the environment, read through
+    /// `env`, and `flags`.
+    fn merge(
+        path: Option<PathBuf>,
+        file: Option<ConfigFile>,
+        env: impl Fn(&str) -> Option<String>,
+        flags: Flags,
+    ) -> Self {
+        let file_found = file.is_some();
+        let file = file.unwrap_or_default();
+        let mut warnings: Vec<String> = file
+            .unknown
+            .keys()
+            .map(|key| format!("Unknown key '{key}' in configuration file ignored"))
+            .collect();
+
+        let flag_or = |set: bool, name: &'static str, from_file: Option<bool>| {
+            if set {
+                Setting::new(true, Source::Flag)
+            } else if env(name).is_some() {
+                // Like BEACON_NO_MOMENTUM, any value turns the option on
+                Setting::new(true, Source::Environment(name))
+            } else if let Some(value) = from_file {
+                Setting::new(value, Source::File)
+            } else {
+                Setting::new(false, Source::Default)
+            }
+        };
+        let no_color = flag_or(flags.no_color, "BEACON_NO_COLOR", file.no_color);
+        let no_pager = flag_or(flags.no_pager, "BEACON_NO_PAGER", file.no_pager);
+
+        let database_file = if let Some(path) = flags.database_file {
+            Setting::new(Some(path), Source::Flag)
+        } else if let Some(path) = env("BEACON_DATABASE_FILE") {
+            Setting::new(
+                Some(path.into()),
+                Source::Environment("BEACON_DATABASE_FILE"),
+            )
+        } else if let Some(path) = file.database_file {
+            Setting::new(Some(path), Source::File)
+        } else {
+            Setting::new(None, Source::Default)
+        };
+
+        // Like git, a beacon-specific setting beats the generic PAGER
+        let pager = if let Some(pager) = env("BEACON_PAGER") {
+            Setting::new(pager, Source::Environment("BEACON_PAGER"))
+        } else if let Some(pager) = file.pager {
+            Setting::new(pager, Source::File)
+        } else if let Some(pager) = env("PAGER") {
+            Setting::new(pager, Source::Environment("PAGER"))
+        } else {
+            Setting::new(DEFAULT_PAGER.to_string(), Source::Default)
+        };
+
+        let format = match env("BEACON_FORMAT").as_deref() {
+            Some("rich") => Setting::new(OutputFormat::Rich, Source::Environment("BEACON_FORMAT")),
+            Some("plain") => {
+                Setting::new(OutputFormat::Plain, Source::Environment("BEACON_FORMAT"))
+            }
+            invalid => {
+                if let Some(format) = invalid {
+                    warnings.push(format!(
+                        "BEACON_FORMAT must be 'rich' or 'plain', ignoring '{format}'"
+                    ));
+                }
+                match file.format {
+                    Some(format) => Setting::new(format, Source::File),
+                    None => Setting::new(OutputFormat::default(), Source::Default),
+                }
+            }
+        };
+
+        Self {
+            path,
+            file_found,
+            database_file,
+            no_color,
+            no_pager,
+            pager,
+            format,
+            warnings,
+        }
+    }
+
+    /// Whether output is styled for the terminal: the format is rich and
+    /// color isn't turned off.
+    pub fn rich_output(&self) -> bool {
+        self.format.value == OutputFormat::Rich && !self.no_color.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn merge(file: &str, env: &[(&str, &str)], flags: Flags) -> CliConfig {
+        let path = Path::new("/home/me/.config/beacon/config.toml");
+        let file = ConfigFile::parse(path, file).unwrap();
+        let env: HashMap<_, _> = env.iter().copied().collect();
+        CliConfig::merge(
+            Some(path.to_path_buf()),
+            Some(file),
+            |name| env.get(name).map(|value| value.to_string()),
+            flags,
+        )
+    }
+
+    #[test]
+    fn test_defaults() {
+        let config = CliConfig::merge(None, None, |_| None, Flags::default());
+        assert!(!config.file_found);
+        assert_eq!(config.database_file, Setting::new(None, Source::Default));
+        assert_eq!(config.no_color, Setting::new(false, Source::Default));
+        assert_eq!(config.pager.value, DEFAULT_PAGER);
+        assert_eq!(config.format.value, OutputFormat::Rich);
+        assert!(config.rich_output());
+        assert!(config.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_flags_override_environment_and_file() {
+        let file = "database_file = \"beacon.db\"\nno_color = false\npager = \"more\"";
+        let config = merge(file, &[], Flags::default());
+        assert_eq!(
+            config.database_file,
+            Setting::new(
+                Some(PathBuf::from("/home/me/.config/beacon/beacon.db")),
+                Source::File
+            )
+        );
+        assert_eq!(config.no_color, Setting::new(false, Source::File));
+        assert_eq!(config.pager, Setting::new("more".to_string(), Source::File));
+
+        let env = [
+            ("BEACON_DATABASE_FILE", "/tmp/env.db"),
+            ("BEACON_NO_COLOR", "1"),
+            ("PAGER", "most"),
+        ];
+        let config = merge(file, &env, Flags::default());
+        assert_eq!(
+            config.database_file,
+            Setting::new(
+                Some(PathBuf::from("/tmp/env.db")),
+                Source::Environment("BEACON_DATABASE_FILE")
+            )
+        );
+        assert_eq!(
+            config.no_color,
+            Setting::new(true, Source::Environment("BEACON_NO_COLOR"))
+        );
+        // The file's pager beats the generic PAGER
+        assert_eq!(config.pager.source, Source::File);
+
+        let flags = Flags {
+            database_file: Some(PathBuf::from("/tmp/flag.db")),
+            no_color: true,
+            no_pager: true,
+        };
+        let config = merge(file, &env, flags);
+        assert_eq!(config.database_file.source, Source::Flag);
+        assert_eq!(config.no_color.source, Source::Flag);
+        assert_eq!(config.no_pager, Setting::new(true, Source::Flag));
+        assert!(!config.rich_output());
+    }
+
+    #[test]
+    fn test_format() {
+        let config = merge("format = \"plain\"", &[], Flags::default());
+        assert_eq!(
+            config.format,
+            Setting::new(OutputFormat::Plain, Source::File)
+        );
+        assert!(!config.rich_output());
+
+        let config = merge(
+            "format = \"plain\"",
+            &[("BEACON_FORMAT", "rich")],
+            Flags::default(),
+        );
+        assert_eq!(
+            config.format,
+            Setting::new(OutputFormat::Rich, Source::Environment("BEACON_FORMAT"))
+        );
+
+        let config = merge("", &[("BEACON_FORMAT", "html")], Flags::default());
+        assert_eq!(
+            config.format,
+            Setting::new(OutputFormat::Rich, Source::Default)
+        );
+        assert_eq!(config.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_unknown_keys_warn() {
+        let config = merge("colour = false\n[extra]\nkey = 1", &[], Flags::default());
+        assert_eq!(
+            config.warnings,
+            [
+                "Unknown key 'colour' in configuration file ignored",
+                "Unknown key 'extra' in configuration file ignored",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_values_fail() {
+        let path = Path::new("/config.toml");
+        assert!(ConfigFile::parse(path, "no_color = \"yes\"").is_err());
+        assert!(ConfigFile::parse(path, "format = \"html\"").is_err());
+        assert!(ConfigFile::parse(path, "no_color =").is_err());
+    }
+}
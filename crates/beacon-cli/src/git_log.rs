@@ -0,0 +1,189 @@
+//! Reading commits from `git log` to scaffold plans from existing history.
+//!
+//! The `git` binary is run as a subprocess in the target directory, so the
+//! user's own git installation and configuration apply.
+
+use std::{io::ErrorKind, path::Path, process::Command};
+
+use anyhow::{Context, Result, anyhow, bail};
+use beacon_core::{CreateStepRequest, StepStatus};
+
+/// Separates the fields of a commit in the log output.
+const FIELD_SEPARATOR: char = '\x1f';
+/// Terminates each commit in the log output.
+const RECORD_SEPARATOR: char = '\x1e';
+/// `git log` format producing `hash US subject US body RS` per commit.
+const LOG_FORMAT: &str = "--format=%H%x1f%s%x1f%b%x1e";
+
+/// A commit read from `git log`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub hash: String,
+    pub subject: String,
+    pub body: Option<String>,
+}
+
+impl From<Commit> for CreateStepRequest {
+    fn from(commit: Commit) -> Self {
+        CreateStepRequest {
+            title: commit.subject,
+            description: commit.body,
+            acceptance_criteria: None,
+            result: Some(format!("imported from commit {}", commit.hash)),
+            references: vec![commit.hash.into()],
+            status: StepStatus::Done,
+        }
+    }
+}
+
+/// Which commits to read from the log.
+#[derive(Debug, Clone)]
+pub struct LogQuery<'a> {
+    /// Only commits newer than this, as a compact age (`2w`, `3d`) or any
+    /// date git understands
+    pub since: Option<&'a str>,
+    /// Maximum number of commits to read
+    pub max: usize,
+    /// Whether merge commits are included
+    pub include_merges: bool,
+}
+
+/// Returns the name of the repository containing `dir`, taken from the last
+/// component of its top-level directory.
+pub fn repo_name(dir: &Path) -> Result<String> {
+    let toplevel = run_git(dir, &["rev-parse", "--show-toplevel"])?;
+    let toplevel = Path::new(toplevel.trim());
+
+    toplevel
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| {
+            anyhow!(
+                "Cannot derive a repository name from '{}'",
+                toplevel.display()
+            )
+        })
+}
+
+/// Reads commits of the repository containing `dir`, oldest first.
+pub fn read_commits(dir: &Path, query: &LogQuery<'_>) -> Result<Vec<Commit>> {
+    let mut args = vec![
+        "log".to_string(),
+        LOG_FORMAT.to_string(),
+        format!("--max-count={}", query.max),
+    ];
+    if !query.include_merges {
+        args.push("--no-merges".to_string());
+    }
+    if let Some(since) = query.since {
+        args.push(format!("--since={}", git_since(since)));
+    }
+
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    let output = run_git(dir, &args)?;
+
+    // git lists the newest commit first
+    let mut commits = parse_log(&output);
+    commits.reverse();
+    Ok(commits)
+}
+
+/// Runs git with `args` in `dir`, returning its standard output.
+fn run_git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => anyhow!("git is not installed or not on PATH"),
+            _ => anyhow!(e).context("Failed to run git"),
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "git failed in '{}' (is it a git repository?): {}",
+            dir.display(),
+            stderr.trim()
+        );
+    }
+
+    String::from_utf8(output.stdout).context("git produced output that is not valid UTF-8")
+}
+
+/// Expands compact ages like `2w` into the `2.weeks.ago` form git accepts.
+/// Anything else is passed through for git to interpret.
+fn git_since(since: &str) -> String {
+    let Some(suffix) = since.chars().last() else {
+        return since.to_string();
+    };
+    let count = &since[..since.len() - suffix.len_utf8()];
+    let unit = match suffix {
+        'h' => "hours",
+        'd' => "days",
+        'w' => "weeks",
+        'm' => "months",
+        'y' => "years",
+        _ => return since.to_string(),
+    };
+
+    if !count.is_empty() && count.chars().all(|c| c.is_ascii_digit()) {
+        format!("{count}.{unit}.ago")
+    } else {
+        since.to_string()
+    }
+}
+
+/// Parses output produced with [`LOG_FORMAT`].
+fn parse_log(output: &str) -> Vec<Commit> {
+    output
+        .split(RECORD_SEPARATOR)
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(3, FIELD_SEPARATOR);
+            let hash = fields.next()?.trim();
+            let subject = fields.next()?.trim();
+            let body = fields.next().map(str::trim).filter(|body| !body.is_empty());
+
+            (!hash.is_empty()).then(|| Commit {
+                hash: hash.to_string(),
+                subject: subject.to_string(),
+                body: body.map(String::from),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_git_since_expands_compact_ages() {
+        assert_eq!(git_since("2w"), "2.weeks.ago");
+        assert_eq!(git_since("36h"), "36.hours.ago");
+        assert_eq!(git_since("2024-01-01"), "2024-01-01");
+        assert_eq!(git_since("w"), "w");
+        assert_eq!(git_since(""), "");
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let output = "abc\x1fSecond\x1fBody line\n\nMore\n\x1e\ndef\x1fFirst\x1f\x1e\n";
+        assert_eq!(
+            parse_log(output),
+            vec![
+                Commit {
+                    hash: "abc".to_string(),
+                    subject: "Second".to_string(),
+                    body: Some("Body line\n\nMore".to_string()),
+                },
+                Commit {
+                    hash: "def".to_string(),
+                    subject: "First".to_string(),
+                    body: None,
+                },
+            ]
+        );
+    }
+}
@@ -4,79 +4,187 @@
 //! Following the m43 pattern for clean CLI implementation.
 
 mod args;
+mod bench;
 mod cli;
+mod completion;
+mod config;
+mod git_log;
 mod mcp;
 mod renderer;
 
-use std::env::var;
+use std::{env::var_os, process::ExitCode};
 
 use Commands::*;
 use anyhow::{Context, Result};
 use args::{Args, Commands};
-use beacon_core::{PlannerBuilder, params::ListPlans};
+use beacon_core::{PlannerBuilder, PlannerError, params::ListPlans};
 use clap::Parser;
-use cli::Cli;
+use cli::{Cli, ConfigCommands};
+use config::{CliConfig, Flags};
 use log::info;
 use mcp::{BeaconMcpServer, run_stdio_server};
 use pager::Pager;
 use renderer::TerminalRenderer;
 use tokio::runtime::Runtime;
 
-fn main() -> Result<()> {
+fn main() -> ExitCode {
+    // Honors RUST_LOG, and also shows the planner's tracing spans and
+    // events, which beacon-core forwards to `log`
     env_logger::init();
 
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            exit_code(&e)
+        }
+    }
+}
+
+/// Exit status for a failed command.
+///
+/// Errors the user can recover from by correcting the command or retrying,
+/// such as an unknown ID or a busy database, exit with 2; anything else
+/// exits with 1.
+fn exit_code(error: &anyhow::Error) -> ExitCode {
+    let recoverable = error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<PlannerError>())
+        .any(PlannerError::is_recoverable);
+    if recoverable {
+        ExitCode::from(2)
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Pipes stdout through the configured pager, unless it is turned off or
+/// `command` uses the terminal itself, and returns the renderer for the
+/// configured output.
+fn setup_output(config: &CliConfig, command: Option<&Commands>) -> TerminalRenderer {
+    if !config.no_pager.value && !command.is_some_and(Commands::uses_terminal) {
+        // Set up the pager before starting async runtime to avoid I/O conflicts
+        Pager::with_pager(&config.pager.value).setup();
+    }
+    TerminalRenderer::new(config.rich_output())
+}
+
+/// Loads the CLI configuration, the flags in `args` overriding the
+/// configuration file and the environment, and warns about anything in it
+/// that was ignored.
+fn load_config(args: &mut Args) -> Result<CliConfig> {
+    let config = CliConfig::load(Flags {
+        database_file: args.database_file.take(),
+        no_color: args.no_color,
+        no_pager: args.no_pager,
+    })?;
+    for warning in &config.warnings {
+        eprintln!("Warning: {warning}");
+    }
+    Ok(config)
+}
+
+fn run() -> Result<()> {
+    let mut args = Args::parse();
+    let config = load_config(&mut args)?;
     let Args {
-        database_file,
-        no_color,
-        no_pager,
+        explain,
+        no_momentum,
         command,
-    } = Args::parse();
+        ..
+    } = args;
+    let momentum = !no_momentum && var_os("BEACON_NO_MOMENTUM").is_none();
+    let builder = PlannerBuilder::new().with_database_path(config.database_file.value.clone());
 
-    if !no_pager {
-        // Set up the pager before starting async runtime to avoid I/O conflicts
-        Pager::with_pager(
-            &var("BEACON_PAGER")
-                .or_else(|_| var("PAGER"))
-                .unwrap_or_else(|_| "less -FRX".to_string()),
-        )
-        .setup();
+    // Read by the shell rather than a person, so never paged
+    match command {
+        Some(Completions(args)) => {
+            print!("{}", args.shell.script());
+            return Ok(());
+        }
+        Some(Complete(args)) => return cli::complete(&args, builder),
+        Some(Badge(args)) => return cli::badge(&args, builder),
+        _ => {}
     }
 
-    let renderer = TerminalRenderer::new(!no_color);
+    let renderer = setup_output(&config, command.as_ref());
+
+    // Resolved without opening the database, so they work before one exists
+    match command {
+        Some(Where) => {
+            renderer.render_output(cli::show_database_path(&builder)?);
+            return Ok(());
+        }
+        Some(Config {
+            command: ConfigCommands::Show,
+        }) => {
+            renderer.render_output(cli::show_config(&config, &builder)?);
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    // Prompt integrations run the default listing constantly, so it skips
+    // the runtime and schema work when the database is already up to date
+    if command.is_none() && !explain {
+        match cli::quick_list_plans(&builder, momentum) {
+            Ok(output) => {
+                renderer.render_output(output);
+                return Ok(());
+            }
+            Err(e) => info!("Quick listing unavailable, using the planner: {e:#}"),
+        }
+    }
 
     Runtime::new()
         .context("Failed to create tokio runtime")?
         .block_on(async move {
-            let planner = PlannerBuilder::new()
-                .with_database_path(database_file)
+            // Runs on a database of its own, so the user's is never opened
+            if let Some(Bench(args)) = &command {
+                return cli::bench(args, &renderer).await;
+            }
+
+            let planner = builder
                 .build()
                 .await
                 .context("Failed to initialize planner")?;
 
             info!("Beacon started");
 
-            match command {
-                Some(Plan { command }) => {
-                    Cli::new(planner, renderer)
-                        .handle_plan_command(command)
-                        .await
-                }
-                Some(Step { command }) => {
-                    Cli::new(planner, renderer)
-                        .handle_step_command(command)
-                        .await
-                }
-                Some(Serve) => {
-                    info!("Starting Beacon MCP server");
-                    run_stdio_server(BeaconMcpServer::new(planner))
-                        .await
-                        .context("MCP server failed")
+            if let Some(Serve) = command {
+                info!("Starting Beacon MCP server");
+                return run_stdio_server(BeaconMcpServer::new(planner))
+                    .await
+                    .context("MCP server failed");
+            }
+
+            let cli = Cli::new(planner, renderer)
+                .with_explain(explain)
+                .with_momentum(momentum);
+            let output = match command {
+                Some(Plan { command }) => cli.handle_plan_command(command).await,
+                Some(Step { command }) => cli.handle_step_command(command).await,
+                Some(Open { url }) => cli.open(&url).await,
+                Some(Metrics(args)) => cli.metrics(&args).await,
+                Some(Streak) => cli.streak().await,
+                Some(Stats(args)) => cli.stats(&args.into()).await,
+                Some(DedupeReport(args)) => cli.dedupe_report(&args.into()).await,
+                Some(Events(args)) => cli.events(&args.into()).await,
+                Some(Config { command }) => cli.handle_config_command(command).await,
+                Some(Template { command }) => cli.handle_template_command(command).await,
+                Some(Db { command }) => cli.handle_db_command(command).await,
+                Some(Where | Bench(_) | Badge(_) | Completions(_) | Complete(_) | Serve) => {
+                    unreachable!("handled before the planner is used")
                 }
                 None => {
-                    Cli::new(planner, renderer)
-                        .list_plans(&ListPlans { archived: false })
-                        .await
+                    cli.list_plans(&ListPlans {
+                        archived: false,
+                        ..Default::default()
+                    })
+                    .await
                 }
-            }
+            }?;
+            cli.render(output);
+            Ok(())
         })
 }
@@ -0,0 +1,235 @@
+//! Mapping of planner errors to MCP errors
+//!
+//! Agents decide how to react from the error code and the `data` payload
+//! rather than from the message. Every error's data carries a
+//! machine-readable `kind` and whether retrying the same call may succeed,
+//! which is only the case for `busy`, `timeout` and `unavailable`:
+//!
+//! | kind                    | code               | extra data                       |
+//! |-------------------------|--------------------|----------------------------------|
+//! | `not_found`             | resource not found | `entity`, `id`, optional `hint`  |
+//! | `invalid_input`         | invalid params     | `field`                          |
+//! | `plan_not_empty`        | invalid params     | `plan_id`, `pending_steps`       |
+//! | `plan_has_active_steps` | invalid params     | `plan_id`, `in_progress`, `todo` |
+//! | `conflict`              | invalid params     |                                  |
+//! | `read_only`             | invalid request    |                                  |
+//! | `busy`                  | internal error     |                                  |
+//! | `timeout`               | internal error     |                                  |
+//! | `unavailable`           | internal error     |                                  |
+//! | `internal`              | internal error     |                                  |
+
+use beacon_core::{IdHint, PlannerError};
+use rmcp::{ErrorData, serde_json};
+use serde_json::{Value, json};
+
+/// Helper to convert planner errors to MCP errors.
+///
+/// Missing plans or steps and rejected input are reported as client errors
+/// so agents can correct the request; only failures of the database or the
+/// environment are internal errors. The message includes the full chain of
+/// underlying causes. When a plan or step ID turns out to belong to the
+/// other kind of item, the message suggests the matching tool and the error
+/// data carries the hint.
+pub fn to_mcp_error(message: &str, error: &PlannerError) -> ErrorData {
+    let mut message = format!("{message}: {}", error.display_chain());
+
+    if error.is_not_found() {
+        let mut data = error_data("not_found", false);
+        if let Some((entity, id)) = missing_entity(error) {
+            data["entity"] = entity.into();
+            data["id"] = id;
+        }
+        if let Some(hint) = error.id_hint() {
+            let tool = match hint {
+                IdHint::StepExists { step_id, plan_id } => {
+                    data["step_id"] = step_id.into();
+                    data["plan_id"] = plan_id.into();
+                    "show_step"
+                }
+                IdHint::PlanExists { plan_id } => {
+                    data["plan_id"] = plan_id.into();
+                    "show_plan"
+                }
+            };
+            message.push_str(&format!(" — did you mean {tool}?"));
+            data["hint"] = hint.to_string().into();
+            data["suggested_tool"] = tool.into();
+        }
+        return ErrorData::resource_not_found(message, Some(data));
+    }
+
+    match error {
+        PlannerError::InvalidInput { field, .. } => {
+            let mut data = error_data("invalid_input", false);
+            data["field"] = field.as_str().into();
+            ErrorData::invalid_params(message, Some(data))
+        }
+        PlannerError::PlanNotEmpty { id, pending_steps } => {
+            let mut data = error_data("plan_not_empty", false);
+            data["plan_id"] = (*id).into();
+            data["pending_steps"] = (*pending_steps).into();
+            ErrorData::invalid_params(message, Some(data))
+        }
+        PlannerError::PlanHasActiveSteps {
+            id,
+            in_progress,
+            todo,
+        } => {
+            let mut data = error_data("plan_has_active_steps", false);
+            data["plan_id"] = (*id).into();
+            data["in_progress"] = (*in_progress).into();
+            data["todo"] = (*todo).into();
+            ErrorData::invalid_params(message, Some(data))
+        }
+        PlannerError::ReadOnly { .. } => {
+            ErrorData::invalid_request(message, Some(error_data("read_only", false)))
+        }
+        _ if error.is_conflict() => {
+            ErrorData::invalid_params(message, Some(error_data("conflict", false)))
+        }
+        _ if error.is_timeout() => {
+            message.push_str(" — the database is unresponsive; retry the call later");
+            ErrorData::internal_error(message, Some(error_data("timeout", true)))
+        }
+        _ if error.is_busy() => {
+            message.push_str(" — another process is writing to the database; retry the call");
+            ErrorData::internal_error(message, Some(error_data("busy", true)))
+        }
+        _ if error.is_unavailable() => {
+            message.push_str(" — retry the call once the database file is back");
+            ErrorData::internal_error(message, Some(error_data("unavailable", true)))
+        }
+        _ => ErrorData::internal_error(message, Some(error_data("internal", false))),
+    }
+}
+
+/// The data every error carries: its kind and whether retrying may help.
+fn error_data(kind: &str, retryable: bool) -> Value {
+    json!({ "kind": kind, "retryable": retryable })
+}
+
+/// The kind of item that was not found, and its ID or name.
+fn missing_entity(error: &PlannerError) -> Option<(&'static str, Value)> {
+    match error {
+        PlannerError::PlanNotFound { id } => Some(("plan", (*id).into())),
+        PlannerError::StepNotFound { id } => Some(("step", (*id).into())),
+        PlannerError::TemplateNotFound { name } => Some(("template", name.as_str().into())),
+        PlannerError::NotFoundWithHint { source, .. } => missing_entity(source),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rmcp::model::ErrorCode;
+
+    use super::*;
+
+    #[test]
+    fn test_not_found_carries_entity_and_hint() {
+        let error = to_mcp_error("Failed", &PlannerError::StepNotFound { id: 7 });
+        assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+        assert_eq!(
+            error.data,
+            Some(json!({ "kind": "not_found", "retryable": false, "entity": "step", "id": 7 }))
+        );
+
+        let error = PlannerError::NotFoundWithHint {
+            source: Box::new(PlannerError::PlanNotFound { id: 3 }),
+            hint: IdHint::StepExists {
+                step_id: 3,
+                plan_id: 1,
+            },
+        };
+        let error = to_mcp_error("Failed", &error);
+        let data = error.data.unwrap();
+        assert_eq!(data["entity"], "plan");
+        assert_eq!(data["id"], 3);
+        assert_eq!(data["suggested_tool"], "show_step");
+        assert_eq!(data["plan_id"], 1);
+        assert!(error.message.contains("did you mean show_step?"));
+    }
+
+    #[test]
+    fn test_client_errors() {
+        let error = to_mcp_error(
+            "Failed",
+            &PlannerError::invalid_input("title").with_reason("Too long"),
+        );
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert_eq!(
+            error.data,
+            Some(json!({ "kind": "invalid_input", "retryable": false, "field": "title" }))
+        );
+
+        let error = PlannerError::PlanNotEmpty {
+            id: 2,
+            pending_steps: 4,
+        };
+        let data = to_mcp_error("Failed", &error).data.unwrap();
+        assert_eq!(data["kind"], "plan_not_empty");
+        assert_eq!(data["pending_steps"], 4);
+
+        let error = PlannerError::PlanHasActiveSteps {
+            id: 2,
+            in_progress: 1,
+            todo: 3,
+        };
+        let error = to_mcp_error("Failed", &error);
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert_eq!(
+            error.data,
+            Some(json!({
+                "kind": "plan_has_active_steps",
+                "retryable": false,
+                "plan_id": 2,
+                "in_progress": 1,
+                "todo": 3,
+            }))
+        );
+
+        let error = PlannerError::ReadOnly {
+            operation: "create_plan".to_string(),
+        };
+        assert_eq!(
+            to_mcp_error("Failed", &error).code,
+            ErrorCode::INVALID_REQUEST
+        );
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        for (error, kind) in [
+            (
+                PlannerError::Busy {
+                    operation: "add_step".to_string(),
+                },
+                "busy",
+            ),
+            (
+                PlannerError::Timeout {
+                    operation: "add_step".to_string(),
+                },
+                "timeout",
+            ),
+            (
+                PlannerError::DatabaseUnavailable {
+                    path: "/tmp/beacon.db".into(),
+                },
+                "unavailable",
+            ),
+        ] {
+            let error = to_mcp_error("Failed", &error);
+            assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+            assert_eq!(error.data, Some(json!({ "kind": kind, "retryable": true })));
+            assert!(error.message.contains("retry"));
+        }
+
+        let error = PlannerError::Configuration {
+            message: "broken".to_string(),
+        };
+        let error = to_mcp_error("Failed", &error);
+        assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+        assert_eq!(error.data.unwrap()["retryable"], false);
+    }
+}
@@ -1,29 +1,38 @@
 //! MCP tool handlers implementation
 
-use std::sync::Arc;
+use std::{fmt, sync::Arc};
 
 use beacon_core::{
-    Planner,
-    display::{CreateResult, OperationStatus},
+    BeaconUri, ClaimOutcome, PlanStatus, PlanSummary, Planner, Step, StepStatus, UriTarget,
+    display::{
+        CreateResult, DuplicateGroups, ListedSteps, OperationStatus, PlanAt, PlanSummaries,
+        SettingValues, StepAt, StepOutlines, Steps, StepsMode, UpdateResult,
+    },
     params as core,
 };
+use jiff::Timestamp;
 use log::debug;
 use rmcp::{
     ErrorData, ErrorData as McpError, RoleServer,
     handler::server::tool::Parameters,
     model::{
-        CallToolResult, Content, GetPromptRequestParam, GetPromptResult, ListPromptsResult,
-        PaginatedRequestParam, Prompt, PromptArgument, PromptMessage, PromptMessageContent,
-        PromptMessageRole,
+        AnnotateAble, CallToolResult, Content, GetPromptRequestParam, GetPromptResult,
+        ListPromptsResult, ListResourcesResult, PaginatedRequestParam, Prompt, PromptArgument,
+        PromptMessage, PromptMessageContent, PromptMessageRole, RawResource,
+        ReadResourceRequestParam, ReadResourceResult, ResourceContents,
     },
+    serde_json,
     service::RequestContext,
 };
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 
 use super::{prompts::PROMPT_TEMPLATES, to_mcp_error};
 
+/// MIME type of every resource's content.
+const MARKDOWN_MIME_TYPE: &str = "text/markdown";
+
 // ============================================================================
 // Generic Parameter Wrapper Implementation
 // ============================================================================
@@ -73,12 +82,40 @@ where
 
 // Type aliases for cleaner usage in function signatures
 pub type Id = McpParams<core::Id>;
+pub type ShowPlan = McpParams<core::ShowPlan>;
 pub type CreatePlan = McpParams<core::CreatePlan>;
+pub type UpdatePlan = McpParams<core::UpdatePlan>;
+pub type ArchivePlan = McpParams<core::ArchivePlan>;
+pub type ArchiveCompletedPlans = McpParams<core::ArchiveCompletedPlans>;
+pub type DeletePlan = McpParams<core::DeletePlan>;
+pub type RemoveStep = McpParams<core::RemoveStep>;
 pub type ListPlans = McpParams<core::ListPlans>;
+pub type MergePlans = McpParams<core::MergePlans>;
+pub type ClonePlan = McpParams<core::ClonePlan>;
+pub type AppendPlanNote = McpParams<core::AppendPlanNote>;
 pub type SearchPlans = McpParams<core::SearchPlans>;
+pub type ReassignDirectory = McpParams<core::ReassignDirectory>;
+pub type ResetPlanSteps = McpParams<core::ResetPlanSteps>;
 pub type StepCreate = McpParams<core::StepCreate>;
 pub type InsertStep = McpParams<core::InsertStep>;
+pub type ListSteps = McpParams<core::ListSteps>;
+pub type FindSteps = McpParams<core::FindSteps>;
+pub type ListStepsByStatus = McpParams<core::ListStepsByStatus>;
 pub type SwapSteps = McpParams<core::SwapSteps>;
+pub type MoveStep = McpParams<core::MoveStep>;
+pub type MoveStepToPlan = McpParams<core::MoveStepToPlan>;
+pub type StepDependency = McpParams<core::StepDependency>;
+pub type AddStepNote = McpParams<core::AddStepNote>;
+pub type PlanChangelog = McpParams<core::PlanChangelog>;
+pub type GetPlanReport = McpParams<core::GetPlanReport>;
+pub type DuplicateStepsReport = McpParams<core::DuplicateStepsReport>;
+pub type ListEvents = McpParams<core::ListEvents>;
+pub type ApplyTemplate = McpParams<core::ApplyTemplate>;
+pub type GetStats = McpParams<core::GetStats>;
+pub type GetConfig = McpParams<core::GetConfig>;
+pub type SetConfig = McpParams<core::SetConfig>;
+pub type SetStepPhase = McpParams<core::SetStepPhase>;
+pub type VerifyStep = McpParams<core::VerifyStep>;
 pub type UpdateStep = McpParams<core::UpdateStep>;
 
 pub type McpResult = Result<CallToolResult, ErrorData>;
@@ -104,10 +141,7 @@ impl McpHandlers {
             .await
             .map_err(|e| to_mcp_error("Failed to create plan", &e))?;
 
-        let result = CreateResult::new(plan);
-        Ok(CallToolResult::success(vec![Content::text(
-            result.to_string(),
-        )]))
+        entity_result(&CreateResult::new(&plan), EntityKind::Plan, plan.id)
     }
 
     pub async fn list_plans(&self, Parameters(params): Parameters<ListPlans>) -> McpResult {
@@ -120,7 +154,7 @@ impl McpHandlers {
             .await
             .map_err(|e| to_mcp_error("Failed to list plans", &e))?;
 
-        let title = if plan_summaries.is_empty() {
+        let title = if plan_summaries.total == 0 {
             if inner_params.archived {
                 "No archived plans found"
             } else {
@@ -136,53 +170,267 @@ impl McpHandlers {
         Ok(CallToolResult::success(vec![Content::text(result)]))
     }
 
-    pub async fn show_plan(&self, Parameters(params): Parameters<Id>) -> McpResult {
+    pub async fn show_plan(&self, Parameters(params): Parameters<ShowPlan>) -> McpResult {
         debug!("show_plan: {:?}", params);
 
-        let plan = self
-            .planner
-            .lock()
-            .await
-            .get_plan(params.as_ref())
+        let params = params.as_ref();
+        let planner = self.planner.lock().await;
+        let Some(plan) = planner
+            .get_plan(&core::Id { id: params.id })
             .await
             .map_err(|e| to_mcp_error("Failed to get plan", &e))?
-            .ok_or_else(|| {
-                ErrorData::internal_error(
-                    format!("Plan with ID {} not found", params.as_ref().id),
-                    None,
-                )
-            })?;
+        else {
+            let error = planner.plan_not_found(params.id).await;
+            return Err(to_mcp_error("Failed to get plan", &error));
+        };
+
+        let summary = planner
+            .get_plan_summary(&core::Id { id: params.id })
+            .await
+            .map_err(|e| to_mcp_error("Failed to get plan", &e))?;
+        let counts = planner
+            .plan_progress(&core::Id { id: params.id })
+            .await
+            .map_err(|e| to_mcp_error("Failed to get plan", &e))?;
 
+        let mode = if params.compact {
+            StepsMode::Compact
+        } else {
+            StepsMode::Detailed
+        };
+        let mut plan = PlanAt::new(&plan, Timestamp::now())
+            .with_step_window(params.step_offset, params.step_limit)
+            .with_status_counts(counts)
+            .with_steps_mode(mode);
+        if let Some(summary) = &summary {
+            plan = plan.with_progress(summary);
+        }
         Ok(CallToolResult::success(vec![Content::text(
             plan.to_string(),
         )]))
     }
 
-    pub async fn archive_plan(&self, Parameters(params): Parameters<Id>) -> McpResult {
+    pub async fn update_plan(&self, Parameters(params): Parameters<UpdatePlan>) -> McpResult {
+        debug!("update_plan: {:?}", params);
+
+        let plan = self
+            .planner
+            .lock()
+            .await
+            .update_plan(params.as_ref())
+            .await
+            .map_err(|e| to_mcp_error("Failed to update plan", &e))?;
+
+        entity_result(&UpdateResult::new(&plan), EntityKind::Plan, plan.id)
+    }
+
+    pub async fn archive_plan(&self, Parameters(params): Parameters<ArchivePlan>) -> McpResult {
         debug!("archive_plan: {:?}", params);
 
         let planner = self.planner.lock().await;
         let inner_params = params.as_ref();
-        let _archived_plan = planner
-            .archive_plan(inner_params)
+        // Agents archive strictly, so forgotten steps aren't hidden away
+        let Some(archived_plan) = planner
+            .archive_plan_checked(inner_params, true)
             .await
-            .map_err(|e| ErrorData::internal_error(format!("Failed to archive plan: {e}"), None))?
-            .ok_or_else(|| {
-                ErrorData::internal_error(
-                    format!("Plan with ID {} not found", inner_params.id),
-                    None,
-                )
-            })?;
+            .map_err(|e| to_mcp_error("Failed to archive plan", &e))?
+        else {
+            let error = planner.plan_not_found(inner_params.id).await;
+            return Err(to_mcp_error("Failed to archive plan", &error));
+        };
 
-        let result = OperationStatus::success(format!(
+        let active_children = archived_plan
+            .child_plans
+            .iter()
+            .filter(|child| child.status == PlanStatus::Active)
+            .count();
+
+        let mut message = format!(
             "Archived plan with ID {}. Use 'unarchive_plan' to restore it.",
             inner_params.id
-        ));
+        );
+        if active_children > 0 {
+            message.push_str(&format!(
+                " Warning: {active_children} child plan(s) are still active."
+            ));
+        }
+        let result = OperationStatus::success(message);
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    pub async fn archive_completed_plans(
+        &self,
+        Parameters(params): Parameters<ArchiveCompletedPlans>,
+    ) -> McpResult {
+        debug!("archive_completed_plans: {:?}", params);
+
+        let plans = self
+            .planner
+            .lock()
+            .await
+            .archive_completed_plans(params.as_ref().directory.clone())
+            .await
+            .map_err(|e| to_mcp_error("Failed to archive completed plans", &e))?;
+
+        let result = if plans.is_empty() {
+            OperationStatus::success("No completed plans to archive".to_string()).to_string()
+        } else {
+            format!(
+                "# Archived {} Completed Plan(s)\n\n{}\n\nUse 'unarchive_plan' to restore any of them.",
+                plans.len(),
+                PlanSummaries(plans)
+            )
+        };
+        Ok(CallToolResult::success(vec![Content::text(result)]))
+    }
+
+    pub async fn delete_plan(&self, Parameters(params): Parameters<DeletePlan>) -> McpResult {
+        debug!("delete_plan: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let Some(plan) = planner
+            .delete_plan(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to delete plan", &e))?
+        else {
+            let error = planner.plan_not_found(inner_params.id).await;
+            return Err(to_mcp_error("Failed to delete plan", &error));
+        };
+
+        let message = format!(
+            "Permanently deleted plan '{}' (ID: {}). This action cannot be undone.",
+            plan.title, plan.id
+        );
+        let result = OperationStatus::success(message);
         Ok(CallToolResult::success(vec![Content::text(
             result.to_string(),
         )]))
     }
 
+    pub async fn clone_plan(&self, Parameters(params): Parameters<ClonePlan>) -> McpResult {
+        debug!("clone_plan: {:?}", params);
+
+        let inner_params = params.as_ref();
+        let plan = self
+            .planner
+            .lock()
+            .await
+            .clone_plan(
+                &core::Id {
+                    id: inner_params.id,
+                },
+                inner_params.title.clone(),
+            )
+            .await
+            .map_err(|e| to_mcp_error("Failed to clone plan", &e))?;
+
+        entity_result(&CreateResult::new(&plan), EntityKind::Plan, plan.id)
+    }
+
+    pub async fn append_plan_note(
+        &self,
+        Parameters(params): Parameters<AppendPlanNote>,
+    ) -> McpResult {
+        debug!("append_plan_note: {:?}", params);
+
+        let inner_params = params.as_ref();
+        let plan = self
+            .planner
+            .lock()
+            .await
+            .append_plan_note(
+                &core::Id {
+                    id: inner_params.id,
+                },
+                &inner_params.text,
+            )
+            .await
+            .map_err(|e| to_mcp_error("Failed to add plan note", &e))?;
+
+        let result = OperationStatus::success(format!("Added note to plan {}", plan.id));
+        entity_result(&result, EntityKind::Plan, plan.id)
+    }
+
+    pub async fn merge_plans(&self, Parameters(params): Parameters<MergePlans>) -> McpResult {
+        debug!("merge_plans: {:?}", params);
+
+        let inner_params = params.as_ref();
+        let plan = self
+            .planner
+            .lock()
+            .await
+            .merge_plans_confirmed(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to merge plans", &e))?;
+
+        let message = format!(
+            "Merged plan {} into plan {}, which now has {} step(s). Plan {} was archived with a pointer to plan {}.",
+            inner_params.source_id,
+            plan.id,
+            plan.steps.len(),
+            inner_params.source_id,
+            plan.id
+        );
+        entity_result(
+            &OperationStatus::success(message),
+            EntityKind::Plan,
+            plan.id,
+        )
+    }
+
+    pub async fn reassign_directory(
+        &self,
+        Parameters(params): Parameters<ReassignDirectory>,
+    ) -> McpResult {
+        debug!("reassign_directory: {:?}", params);
+
+        let params = params.as_ref();
+        let moved = self
+            .planner
+            .lock()
+            .await
+            .reassign_directory(&params.from, &params.to)
+            .await
+            .map_err(|e| to_mcp_error("Failed to reassign directory", &e))?;
+
+        let message = if moved == 0 {
+            format!("No plans found in {}", params.from)
+        } else {
+            format!(
+                "Moved {moved} plan(s) from {} to {}",
+                params.from, params.to
+            )
+        };
+        Ok(CallToolResult::success(vec![Content::text(
+            OperationStatus::success(message).to_string(),
+        )]))
+    }
+
+    pub async fn reset_plan_steps(
+        &self,
+        Parameters(params): Parameters<ResetPlanSteps>,
+    ) -> McpResult {
+        debug!("reset_plan_steps: {:?}", params);
+
+        let params = params.as_ref();
+        let reset = self
+            .planner
+            .lock()
+            .await
+            .reset_plan_steps(&core::Id { id: params.id }, !params.keep_results)
+            .await
+            .map_err(|e| to_mcp_error("Failed to reset plan steps", &e))?;
+
+        let result = OperationStatus::success(format!(
+            "Reset {reset} step(s) of plan {} to todo",
+            params.id
+        ));
+        entity_result(&result, EntityKind::Plan, params.id)
+    }
+
     pub async fn search_plans(&self, Parameters(params): Parameters<SearchPlans>) -> McpResult {
         debug!("search_plans: {:?}", params);
 
@@ -191,7 +439,7 @@ impl McpHandlers {
         let plan_summaries = planner
             .search_plans_summary(inner_params)
             .await
-            .map_err(|e| ErrorData::internal_error(format!("Failed to search plans: {e}"), None))?;
+            .map_err(|e| to_mcp_error("Failed to search plans", &e))?;
 
         let result = if plan_summaries.is_empty() {
             let status_text = if inner_params.archived {
@@ -220,226 +468,727 @@ impl McpHandlers {
         Ok(CallToolResult::success(vec![Content::text(result)]))
     }
 
-    pub async fn add_step(&self, Parameters(params): Parameters<StepCreate>) -> McpResult {
-        debug!("add_step: {:?}", params);
+    pub async fn handoff_summary(&self, Parameters(params): Parameters<Id>) -> McpResult {
+        debug!("handoff_summary: {:?}", params);
 
-        let planner = self.planner.lock().await;
-        let inner_params = params.as_ref();
-        let step = planner
-            .add_step(inner_params)
+        let brief = self
+            .planner
+            .lock()
+            .await
+            .handoff_summary(params.as_ref())
             .await
-            .map_err(|e| ErrorData::internal_error(format!("Failed to add step: {e}"), None))?;
+            .map_err(|e| to_mcp_error("Failed to summarize plan", &e))?;
 
-        let result = CreateResult::new(step);
         Ok(CallToolResult::success(vec![Content::text(
-            result.to_string(),
+            brief.to_string(),
         )]))
     }
 
-    pub async fn insert_step(&self, Parameters(params): Parameters<InsertStep>) -> McpResult {
-        debug!("insert_step: {:?}", params);
+    pub async fn plan_changelog(&self, Parameters(params): Parameters<PlanChangelog>) -> McpResult {
+        debug!("plan_changelog: {:?}", params);
 
-        let planner = self.planner.lock().await;
-        let inner_params = params.as_ref();
-        let step = planner
-            .insert_step(inner_params)
+        let changelog = self
+            .planner
+            .lock()
+            .await
+            .changelog(params.as_ref())
             .await
-            .map_err(|e| ErrorData::internal_error(format!("Failed to insert step: {e}"), None))?;
+            .map_err(|e| to_mcp_error("Failed to get plan changelog", &e))?;
 
-        let result = CreateResult::new(step);
         Ok(CallToolResult::success(vec![Content::text(
-            result.to_string(),
+            changelog.to_string(),
         )]))
     }
 
-    pub async fn swap_steps(&self, Parameters(params): Parameters<SwapSteps>) -> McpResult {
-        debug!("swap_steps: {:?}", params);
+    pub async fn plan_report(&self, Parameters(params): Parameters<GetPlanReport>) -> McpResult {
+        debug!("plan_report: {:?}", params);
 
-        let planner = self.planner.lock().await;
-        let inner_params = params.as_ref();
-        planner
-            .swap_steps(inner_params)
+        let report = self
+            .planner
+            .lock()
             .await
-            .map_err(|e| ErrorData::internal_error(format!("Failed to swap steps: {e}"), None))?;
-
-        let result = OperationStatus::success(format!(
-            "Successfully swapped the order of steps {} and {}",
-            inner_params.step1_id, inner_params.step2_id
-        ));
+            .report(params.as_ref())
+            .await
+            .map_err(|e| to_mcp_error("Failed to get plan report", &e))?;
 
         Ok(CallToolResult::success(vec![Content::text(
-            result.to_string(),
+            report.to_string(),
         )]))
     }
 
-    pub async fn update_step(&self, Parameters(params): Parameters<UpdateStep>) -> McpResult {
-        debug!("update_step: {:?}", params);
+    pub async fn list_events(&self, Parameters(params): Parameters<ListEvents>) -> McpResult {
+        debug!("list_events: {:?}", params);
 
-        let planner = self.planner.lock().await;
-        let inner_params = params.as_ref();
-        let _updated_step = planner
-            .update_step_validated(inner_params)
+        let events = self
+            .planner
+            .lock()
             .await
-            .map_err(|e| to_mcp_error("Failed to update step", &e))?
-            .ok_or_else(|| {
-                ErrorData::internal_error(
-                    format!("Step with ID {} not found", inner_params.id),
-                    None,
-                )
-            })?;
-
-        // Build update messages based on what was provided
-        let mut messages = Vec::new();
-        if inner_params.status.is_some() {
-            messages.push(format!(
-                "Updated status to '{}'",
-                inner_params.status.as_ref().unwrap()
-            ));
-        }
-        if inner_params.title.is_some() {
-            messages.push("Updated title".to_string());
-        }
-        if inner_params.description.is_some() {
-            messages.push("Updated description".to_string());
-        }
-        if inner_params.acceptance_criteria.is_some() {
-            messages.push("Updated acceptance criteria".to_string());
-        }
-        if inner_params.references.is_some() {
-            messages.push("Updated references".to_string());
-        }
-
-        let result = if messages.is_empty() {
-            "No updates provided for step".to_string()
-        } else {
-            format!("Step {} updated: {}", inner_params.id, messages.join(", "))
-        };
+            .events(params.as_ref())
+            .await
+            .map_err(|e| to_mcp_error("Failed to list events", &e))?;
 
-        Ok(CallToolResult::success(vec![Content::text(result)]))
+        let json = serde_json::to_string_pretty(&events).map_err(|e| {
+            McpError::internal_error(format!("Failed to serialize events: {e}"), None)
+        })?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    pub async fn show_step(&self, Parameters(params): Parameters<Id>) -> McpResult {
-        debug!("show_step: {:?}", params);
+    pub async fn get_stats(&self, Parameters(params): Parameters<GetStats>) -> McpResult {
+        debug!("get_stats: {:?}", params);
 
-        let planner = self.planner.lock().await;
-        let inner_params = params.as_ref();
-        let step = planner
-            .get_step(inner_params)
+        let stats = self
+            .planner
+            .lock()
             .await
-            .map_err(|e| ErrorData::internal_error(format!("Failed to get step: {e}"), None))?
-            .ok_or_else(|| {
-                ErrorData::internal_error(
-                    format!("Step with ID {} not found", inner_params.id),
-                    None,
-                )
-            })?;
+            .stats_since(params.as_ref())
+            .await
+            .map_err(|e| to_mcp_error("Failed to compute statistics", &e))?;
 
         Ok(CallToolResult::success(vec![Content::text(
-            step.to_string(),
+            stats.to_string(),
         )]))
     }
 
-    pub async fn claim_step(&self, Parameters(params): Parameters<Id>) -> McpResult {
-        debug!("claim_step: {:?}", params);
+    pub async fn database_info(&self) -> McpResult {
+        debug!("database_info");
 
         let planner = self.planner.lock().await;
-        let inner_params = params.as_ref();
+        let text = format!(
+            "- **Database**: `{}`\n- **Connections opened**: {}\n- **Reconnects**: {}\n",
+            planner.database_path().display(),
+            planner.connections_opened(),
+            planner.reconnects()
+        );
 
-        match planner.claim_step(inner_params).await {
-            Ok(Some(_step)) => {
-                let message = format!(
-                    "Successfully claimed step {} - it is now marked as 'in progress'\n\n<system-reminder>\nLaunch a focused subagent for this step. Once completed, use `update_step` with the detailed results of what was accomplished.\n</system-reminder>",
-                    inner_params.id
-                );
-                Ok(CallToolResult::success(vec![Content::text(message)]))
-            }
-            Ok(None) => {
-                // Step was not found or not in todo status, get current status
-                let step = planner.get_step(inner_params).await.map_err(|e| {
-                    ErrorData::internal_error(format!("Failed to get step: {e}"), None)
-                })?;
-
-                if let Some(step) = step {
-                    use beacon_core::models::StepStatus;
-                    let status_description = match step.status {
-                        StepStatus::InProgress => "already in progress",
-                        StepStatus::Done => "already completed",
-                        StepStatus::Todo => "in todo status but could not be claimed",
-                    };
-                    let message = format!(
-                        "Cannot claim step {} - it is {}",
-                        inner_params.id, status_description
-                    );
-                    Ok(CallToolResult::success(vec![Content::text(message)]))
-                } else {
-                    Err(ErrorData::internal_error(
-                        format!("Step with ID {} not found", inner_params.id),
-                        None,
-                    ))
-                }
-            }
-            Err(e) => Err(ErrorData::internal_error(
-                format!("Failed to claim step: {e}"),
-                None,
-            )),
-        }
+        Ok(CallToolResult::success(vec![Content::text(text)]))
     }
 
-    /// List all available prompts
-    pub async fn list_prompts(
-        &self,
-        _request: Option<PaginatedRequestParam>,
-        _context: RequestContext<RoleServer>,
-    ) -> Result<ListPromptsResult, McpError> {
-        debug!("list_prompts");
+    pub async fn apply_template(&self, Parameters(params): Parameters<ApplyTemplate>) -> McpResult {
+        debug!("apply_template: {:?}", params);
 
-        let templates = &PROMPT_TEMPLATES;
-        let prompts = templates
-            .iter()
-            .map(|template| {
-                Prompt::new(
-                    &template.name,
-                    Some(&template.description),
-                    Some(
-                        template
-                            .arguments
-                            .iter()
-                            .map(|arg| PromptArgument {
-                                name: arg.name.clone(),
-                                description: Some(arg.description.clone()),
-                                required: Some(arg.required),
-                            })
-                            .collect(),
-                    ),
-                )
-            })
-            .collect();
+        let params = params.as_ref();
+        let steps = self
+            .planner
+            .lock()
+            .await
+            .apply_template(params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to apply template", &e))?;
 
-        Ok(ListPromptsResult {
-            next_cursor: None,
-            prompts,
-        })
+        let message = format!(
+            "Applied template '{}' to plan {}, adding {} step(s)",
+            params.name.trim(),
+            params.plan_id,
+            steps.len()
+        );
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "{}\n{}",
+            OperationStatus::success(message),
+            Steps::compact(&steps)
+        ))]))
     }
 
-    /// Get a specific prompt by name and apply arguments
-    pub async fn get_prompt(
+    pub async fn duplicate_steps_report(
         &self,
-        request: GetPromptRequestParam,
-        _context: RequestContext<RoleServer>,
-    ) -> Result<GetPromptResult, McpError> {
-        debug!("get_prompt: {}", request.name);
+        Parameters(params): Parameters<DuplicateStepsReport>,
+    ) -> McpResult {
+        debug!("duplicate_steps_report: {:?}", params);
 
-        let templates = &PROMPT_TEMPLATES;
-        let template = templates
-            .iter()
-            .find(|t| t.name == request.name)
-            .ok_or_else(|| McpError::invalid_params("Prompt not found", None))?;
+        let groups = self
+            .planner
+            .lock()
+            .await
+            .find_duplicate_steps(params.as_ref().into())
+            .await
+            .map_err(|e| to_mcp_error("Failed to find duplicate steps", &e))?;
 
-        let mut prompt_text = template.template.clone();
+        Ok(CallToolResult::success(vec![Content::text(
+            DuplicateGroups::owned(groups).to_string(),
+        )]))
+    }
 
-        // Apply argument substitution if arguments are provided
-        if let Some(args) = &request.arguments {
-            for arg_def in &template.arguments {
+    pub async fn get_config(&self, Parameters(params): Parameters<GetConfig>) -> McpResult {
+        debug!("get_config: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let text = match &params.as_ref().key {
+            Some(key) => planner
+                .get_setting(key)
+                .await
+                .map_err(|e| to_mcp_error("Failed to read setting", &e))?
+                .to_string(),
+            None => SettingValues::owned(
+                planner
+                    .list_settings()
+                    .await
+                    .map_err(|e| to_mcp_error("Failed to read settings", &e))?,
+            )
+            .to_string(),
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    pub async fn set_config(&self, Parameters(params): Parameters<SetConfig>) -> McpResult {
+        debug!("set_config: {:?}", params);
+
+        let setting = self
+            .planner
+            .lock()
+            .await
+            .set_agent_setting(params.as_ref())
+            .await
+            .map_err(|e| to_mcp_error("Failed to change setting", &e))?;
+
+        let status = OperationStatus::success(format!("Set {} to {}", setting.key, setting.value));
+        Ok(CallToolResult::success(vec![Content::text(
+            status.to_string(),
+        )]))
+    }
+
+    pub async fn plan_outline(&self, Parameters(params): Parameters<Id>) -> McpResult {
+        debug!("plan_outline: {:?}", params);
+
+        let outline = self
+            .planner
+            .lock()
+            .await
+            .get_plan_outline(params.as_ref().id)
+            .await
+            .map_err(|e| to_mcp_error("Failed to get plan outline", &e))?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            StepOutlines(outline).to_string(),
+        )]))
+    }
+
+    pub async fn list_steps(&self, Parameters(params): Parameters<ListSteps>) -> McpResult {
+        debug!("list_steps: {:?}", params);
+
+        let steps = self
+            .planner
+            .lock()
+            .await
+            .list_steps(params.as_ref())
+            .await
+            .map_err(|e| to_mcp_error("Failed to list steps", &e))?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            ListedSteps(steps).to_string(),
+        )]))
+    }
+
+    pub async fn find_steps(&self, Parameters(params): Parameters<FindSteps>) -> McpResult {
+        debug!("find_steps: {:?}", params);
+
+        let steps = self
+            .planner
+            .lock()
+            .await
+            .find_steps_validated(params.as_ref())
+            .await
+            .map_err(|e| to_mcp_error("Failed to find steps", &e))?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            steps.to_string(),
+        )]))
+    }
+
+    pub async fn list_steps_by_status(
+        &self,
+        Parameters(params): Parameters<ListStepsByStatus>,
+    ) -> McpResult {
+        debug!("list_steps_by_status: {:?}", params);
+
+        let steps = self
+            .planner
+            .lock()
+            .await
+            .list_steps_by_status_validated(params.as_ref())
+            .await
+            .map_err(|e| to_mcp_error("Failed to list steps", &e))?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            steps.to_string(),
+        )]))
+    }
+
+    pub async fn add_step(&self, Parameters(params): Parameters<StepCreate>) -> McpResult {
+        debug!("add_step: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let step = planner
+            .add_step(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to add step", &e))?;
+
+        entity_result(&CreateResult::new(&step), EntityKind::Step, step.id)
+    }
+
+    pub async fn insert_step(&self, Parameters(params): Parameters<InsertStep>) -> McpResult {
+        debug!("insert_step: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let step = planner
+            .insert_step(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to insert step", &e))?;
+
+        entity_result(&CreateResult::new(&step), EntityKind::Step, step.id)
+    }
+
+    pub async fn swap_steps(&self, Parameters(params): Parameters<SwapSteps>) -> McpResult {
+        debug!("swap_steps: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        planner
+            .swap_steps(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to swap steps", &e))?;
+
+        let result = OperationStatus::success(format!(
+            "Successfully swapped the order of steps {} and {}",
+            inner_params.step1_id, inner_params.step2_id
+        ));
+
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    pub async fn move_step(&self, Parameters(params): Parameters<MoveStep>) -> McpResult {
+        debug!("move_step: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        planner
+            .move_step(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to move step", &e))?;
+
+        let result = OperationStatus::success(format!(
+            "Successfully moved step {} to position {}",
+            inner_params.step_id, inner_params.new_position
+        ));
+
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    pub async fn move_step_to_plan(
+        &self,
+        Parameters(params): Parameters<MoveStepToPlan>,
+    ) -> McpResult {
+        debug!("move_step_to_plan: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let step = planner
+            .move_step_to_plan(
+                inner_params.step_id,
+                inner_params.plan_id,
+                inner_params.position,
+            )
+            .await
+            .map_err(|e| to_mcp_error("Failed to move step to plan", &e))?;
+
+        let result = OperationStatus::success(format!(
+            "Successfully moved step {} to plan {} at position {}",
+            step.id, step.plan_id, step.order
+        ));
+
+        entity_result(&result, EntityKind::Step, step.id)
+    }
+
+    pub async fn add_step_dependency(
+        &self,
+        Parameters(params): Parameters<StepDependency>,
+    ) -> McpResult {
+        debug!("add_step_dependency: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let added = planner
+            .add_step_dependency(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to add step dependency", &e))?;
+
+        let message = if added {
+            format!(
+                "Step {} now depends on step {} and cannot be claimed until it is done",
+                inner_params.step_id, inner_params.depends_on
+            )
+        } else {
+            format!(
+                "Step {} already depends on step {}",
+                inner_params.step_id, inner_params.depends_on
+            )
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(
+            OperationStatus::success(message).to_string(),
+        )]))
+    }
+
+    pub async fn remove_step(&self, Parameters(params): Parameters<RemoveStep>) -> McpResult {
+        debug!("remove_step: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let Some(step) = planner
+            .remove_step_checked(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to remove step", &e))?
+        else {
+            let error = planner.step_not_found(inner_params.id).await;
+            return Err(to_mcp_error("Failed to remove step", &error));
+        };
+
+        let message = format!(
+            "Removed step '{}' (ID: {}) from plan {}. It was moved to the trash, from where the user can restore it with `b step restore {}`.",
+            step.title, step.id, step.plan_id, step.id
+        );
+        let result = OperationStatus::success(message);
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    pub async fn remove_step_dependency(
+        &self,
+        Parameters(params): Parameters<StepDependency>,
+    ) -> McpResult {
+        debug!("remove_step_dependency: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let removed = planner
+            .remove_step_dependency(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to remove step dependency", &e))?;
+
+        let result = if removed {
+            OperationStatus::success(format!(
+                "Step {} no longer depends on step {}",
+                inner_params.step_id, inner_params.depends_on
+            ))
+        } else {
+            OperationStatus::failure(format!(
+                "Step {} does not depend on step {}",
+                inner_params.step_id, inner_params.depends_on
+            ))
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
+    pub async fn add_step_note(&self, Parameters(params): Parameters<AddStepNote>) -> McpResult {
+        debug!("add_step_note: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let note = planner
+            .add_step_note(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to add step note", &e))?;
+
+        let result =
+            OperationStatus::success(format!("Added note {} to step {}", note.id, note.step_id));
+
+        entity_result(&result, EntityKind::Note, note.id)
+    }
+
+    pub async fn verify_step(&self, Parameters(params): Parameters<VerifyStep>) -> McpResult {
+        debug!("verify_step: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let step = planner
+            .verify_step(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to verify step", &e))?;
+
+        let result = OperationStatus::success(format!(
+            "Step {} verified by {}",
+            step.id, inner_params.verifier
+        ));
+
+        entity_result(&result, EntityKind::Step, step.id)
+    }
+
+    pub async fn set_step_phase(&self, Parameters(params): Parameters<SetStepPhase>) -> McpResult {
+        debug!("set_step_phase: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let Some(step) = planner
+            .set_step_phase(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to set step phase", &e))?
+        else {
+            let error = planner.step_not_found(inner_params.id).await;
+            return Err(to_mcp_error("Failed to set step phase", &error));
+        };
+
+        let result = OperationStatus::success(match &step.phase {
+            Some(phase) => format!("Moved step {} to phase '{phase}'", step.id),
+            None => format!("Removed step {} from its phase", step.id),
+        });
+
+        entity_result(&result, EntityKind::Step, step.id)
+    }
+
+    pub async fn update_step(&self, Parameters(params): Parameters<UpdateStep>) -> McpResult {
+        debug!("update_step: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let Some(result) = planner
+            .update_step_with_changes(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to update step", &e))?
+        else {
+            let error = planner.step_not_found(inner_params.id).await;
+            return Err(to_mcp_error("Failed to update step", &error));
+        };
+
+        entity_result(&result, EntityKind::Step, result.resource.id)
+    }
+
+    pub async fn show_step(&self, Parameters(params): Parameters<Id>) -> McpResult {
+        debug!("show_step: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let Some(step) = planner
+            .get_step(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to get step", &e))?
+        else {
+            let error = planner.step_not_found(inner_params.id).await;
+            return Err(to_mcp_error("Failed to get step", &error));
+        };
+        let labels = planner
+            .get_step_status_labels(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to get status labels", &e))?;
+
+        let step = StepAt::new(&step, Timestamp::now()).with_status_labels(&labels);
+        Ok(CallToolResult::success(vec![Content::text(
+            step.to_string(),
+        )]))
+    }
+
+    pub async fn next_step(&self, Parameters(params): Parameters<Id>) -> McpResult {
+        debug!("next_step: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let step = planner
+            .next_step(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to find next step", &e))?;
+
+        let message = match step {
+            Some(step) => {
+                let labels = planner
+                    .get_step_status_labels(&core::Id { id: step.id })
+                    .await
+                    .map_err(|e| to_mcp_error("Failed to get status labels", &e))?;
+                let step = StepAt::new(&step, Timestamp::now()).with_status_labels(&labels);
+                format!(
+                    "{step}\nUse `claim_step` with ID {} before starting work on it.",
+                    step.step.id
+                )
+            }
+            None => {
+                let summary = planner
+                    .get_plan_summary(inner_params)
+                    .await
+                    .map_err(|e| to_mcp_error("Failed to find next step", &e))?;
+                no_next_step_message(inner_params.id, summary.as_ref())
+            }
+        };
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    pub async fn claim_next_step(&self, Parameters(params): Parameters<Id>) -> McpResult {
+        debug!("claim_next_step: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let step = planner
+            .claim_next_step(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to claim next step", &e))?;
+
+        let message = match step {
+            Some(step) => claimed_message(&planner, &step, UPDATE_WHEN_DONE_REMINDER).await?,
+            None => {
+                let summary = planner
+                    .get_plan_summary(inner_params)
+                    .await
+                    .map_err(|e| to_mcp_error("Failed to claim next step", &e))?;
+                no_next_step_message(inner_params.id, summary.as_ref())
+            }
+        };
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    pub async fn allowed_transitions(&self, Parameters(params): Parameters<Id>) -> McpResult {
+        debug!("allowed_transitions: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let Some(transitions) = planner
+            .allowed_transitions(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to check step transitions", &e))?
+        else {
+            let error = planner.step_not_found(inner_params.id).await;
+            return Err(to_mcp_error("Failed to check step transitions", &error));
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(
+            transitions.to_string(),
+        )]))
+    }
+
+    pub async fn claim_step(&self, Parameters(params): Parameters<Id>) -> McpResult {
+        debug!("claim_step: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+
+        match try_claim(&planner, inner_params).await? {
+            ClaimOutcome::Claimed(step) => {
+                let reminder =
+                    format!("Launch a focused subagent for this step. {UPDATE_WHEN_DONE_REMINDER}");
+                let message = claimed_message(&planner, &step, &reminder).await?;
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            ClaimOutcome::NotClaimable(status) => Ok(CallToolResult::success(vec![Content::text(
+                not_claimable_message(inner_params.id, status),
+            )])),
+            ClaimOutcome::Blocked(blockers) => Ok(CallToolResult::success(vec![Content::text(
+                blocked_message(inner_params.id, &blockers),
+            )])),
+            ClaimOutcome::NotFound => {
+                let error = planner.step_not_found(inner_params.id).await;
+                Err(to_mcp_error("Failed to claim step", &error))
+            }
+        }
+    }
+
+    pub async fn release_step(&self, Parameters(params): Parameters<Id>) -> McpResult {
+        debug!("release_step: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+        let released = planner
+            .release_step(inner_params)
+            .await
+            .map_err(|e| to_mcp_error("Failed to release step", &e))?;
+
+        let message = if released {
+            format!(
+                "Released step {} - it is back in 'todo' and can be claimed again. Use `update_step` to record any partial progress in its description.",
+                inner_params.id
+            )
+        } else {
+            format!(
+                "Cannot release step {} - only steps in progress can be released",
+                inner_params.id
+            )
+        };
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    pub async fn claim_and_show(&self, Parameters(params): Parameters<Id>) -> McpResult {
+        debug!("claim_and_show: {:?}", params);
+
+        let planner = self.planner.lock().await;
+        let inner_params = params.as_ref();
+
+        match try_claim(&planner, inner_params).await? {
+            ClaimOutcome::Claimed(step) => {
+                let message = claimed_message(&planner, &step, UPDATE_WHEN_DONE_REMINDER).await?;
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            ClaimOutcome::NotClaimable(status) => Ok(CallToolResult::success(vec![Content::text(
+                not_claimable_message(inner_params.id, status),
+            )])),
+            ClaimOutcome::Blocked(blockers) => Ok(CallToolResult::success(vec![Content::text(
+                blocked_message(inner_params.id, &blockers),
+            )])),
+            ClaimOutcome::NotFound => {
+                let error = planner.step_not_found(inner_params.id).await;
+                Err(to_mcp_error("Failed to claim step", &error))
+            }
+        }
+    }
+
+    /// List all available prompts
+    pub async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, McpError> {
+        debug!("list_prompts");
+
+        let templates = &PROMPT_TEMPLATES;
+        let prompts = templates
+            .iter()
+            .map(|template| {
+                Prompt::new(
+                    &template.name,
+                    Some(&template.description),
+                    Some(
+                        template
+                            .arguments
+                            .iter()
+                            .map(|arg| PromptArgument {
+                                name: arg.name.clone(),
+                                description: Some(arg.description.clone()),
+                                required: Some(arg.required),
+                            })
+                            .collect(),
+                    ),
+                )
+            })
+            .collect();
+
+        Ok(ListPromptsResult {
+            next_cursor: None,
+            prompts,
+        })
+    }
+
+    /// Get a specific prompt by name and apply arguments
+    pub async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, McpError> {
+        debug!("get_prompt: {}", request.name);
+
+        let templates = &PROMPT_TEMPLATES;
+        let template = templates
+            .iter()
+            .find(|t| t.name == request.name)
+            .ok_or_else(|| McpError::invalid_params("Prompt not found", None))?;
+
+        let mut prompt_text = template.template.clone();
+
+        // Apply argument substitution if arguments are provided
+        if let Some(args) = &request.arguments {
+            for arg_def in &template.arguments {
                 if let Some(arg_value) = args.get(&arg_def.name) {
                     if let Some(arg_str) = arg_value.as_str() {
                         let placeholder = format!("{{{}}}", arg_def.name);
@@ -473,12 +1222,1339 @@ impl McpHandlers {
             }
         }
 
-        Ok(GetPromptResult {
-            description: Some(template.description.clone()),
-            messages: vec![PromptMessage {
-                role: PromptMessageRole::User,
-                content: PromptMessageContent::text(prompt_text),
-            }],
-        })
+        Ok(GetPromptResult {
+            description: Some(template.description.clone()),
+            messages: vec![PromptMessage {
+                role: PromptMessageRole::User,
+                content: PromptMessageContent::text(prompt_text),
+            }],
+        })
+    }
+
+    /// Lists the index of active plans followed by each active plan as a
+    /// resource.
+    pub async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+    ) -> Result<ListResourcesResult, McpError> {
+        debug!("list_resources");
+
+        let summaries = self
+            .planner
+            .lock()
+            .await
+            .list_plans_summary(&core::ListPlans::default())
+            .await
+            .map_err(|e| to_mcp_error("Failed to list resources", &e))?;
+
+        let index = RawResource {
+            description: Some("Summaries of all active plans".to_string()),
+            mime_type: Some(MARKDOWN_MIME_TYPE.to_string()),
+            ..RawResource::new(BeaconUri::plans().to_string(), "Active Plans")
+        };
+        let plans = summaries.items.iter().map(|summary| RawResource {
+            description: summary.description.clone(),
+            mime_type: Some(MARKDOWN_MIME_TYPE.to_string()),
+            ..RawResource::new(BeaconUri::plan(summary.id).to_string(), &summary.title)
+        });
+
+        Ok(ListResourcesResult {
+            next_cursor: None,
+            resources: std::iter::once(index)
+                .chain(plans)
+                .map(RawResource::no_annotation)
+                .collect(),
+        })
+    }
+
+    /// Reads the index of active plans or an active plan, rendered as
+    /// markdown when read so that it is never stale. Resource URIs are
+    /// `beacon://` URLs, parsed like those `b open` takes.
+    pub async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+    ) -> Result<ReadResourceResult, McpError> {
+        debug!("read_resource: {}", request.uri);
+
+        let uri = request.uri;
+        let not_found = |message: String| {
+            McpError::resource_not_found(message, Some(serde_json::json!({ "uri": uri })))
+        };
+        let planner = self.planner.lock().await;
+
+        let target = uri
+            .parse::<BeaconUri>()
+            .and_then(|parsed| {
+                parsed.check_workspace(planner.database_path())?;
+                Ok(parsed.target)
+            })
+            .map_err(|e| not_found(format!("Unknown resource URI: {}", e.display_chain())))?;
+
+        let text = match target {
+            UriTarget::Plans => {
+                let summaries = planner
+                    .list_plans_summary(&core::ListPlans::default())
+                    .await
+                    .map_err(|e| to_mcp_error("Failed to read resource", &e))?;
+                format!("# Active Plans\n\n{summaries}")
+            }
+            UriTarget::Step(_) => {
+                return Err(not_found(format!(
+                    "Unknown resource URI: {uri}; only plans are resources"
+                )));
+            }
+            UriTarget::Plan(id) => {
+                let plan = planner
+                    .get_plan(&core::Id { id })
+                    .await
+                    .map_err(|e| to_mcp_error("Failed to read resource", &e))?;
+                let plan = match plan {
+                    Some(plan) if plan.status == PlanStatus::Active => plan,
+                    Some(_) => {
+                        return Err(not_found(format!(
+                            "Failed to read resource: plan {id} is archived; only active plans \
+                             are resources"
+                        )));
+                    }
+                    None => {
+                        let error = planner.plan_not_found(id).await;
+                        return Err(not_found(format!(
+                            "Failed to read resource: {}",
+                            error.display_chain()
+                        )));
+                    }
+                };
+                let summary = planner
+                    .get_plan_summary(&core::Id { id })
+                    .await
+                    .map_err(|e| to_mcp_error("Failed to read resource", &e))?;
+
+                let mut plan = PlanAt::new(&plan, Timestamp::now());
+                if let Some(summary) = &summary {
+                    plan = plan.with_progress(summary);
+                }
+                plan.to_string()
+            }
+        };
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri: uri.clone(),
+                mime_type: Some(MARKDOWN_MIME_TYPE.to_string()),
+                text,
+            }],
+        })
+    }
+}
+
+/// Claims a step, mapping planner failures to MCP errors.
+async fn try_claim(planner: &Planner, params: &core::Id) -> Result<ClaimOutcome, ErrorData> {
+    planner
+        .try_claim_step(params)
+        .await
+        .map_err(|e| to_mcp_error("Failed to claim step", &e))
+}
+
+/// Reminder given with a claimed step to record its result once done.
+const UPDATE_WHEN_DONE_REMINDER: &str =
+    "Once completed, use `update_step` with the detailed results of what was accomplished.";
+
+/// Confirms that `step` was claimed, showing its full details followed by
+/// `reminder`, so the agent needn't call `show_step` next.
+async fn claimed_message(
+    planner: &Planner,
+    step: &Step,
+    reminder: &str,
+) -> Result<String, ErrorData> {
+    let labels = planner
+        .get_step_status_labels(&core::Id { id: step.id })
+        .await
+        .map_err(|e| to_mcp_error("Failed to get status labels", &e))?;
+    let shown = StepAt::new(step, Timestamp::now()).with_status_labels(&labels);
+    Ok(format!(
+        "Successfully claimed step {} - it is now marked as 'in progress'\n\n{shown}\n\n<system-reminder>\n{reminder}\n</system-reminder>",
+        step.id
+    ))
+}
+
+/// Explains why a step in `status` could not be claimed.
+fn not_claimable_message(step_id: u64, status: StepStatus) -> String {
+    let status_description = match status {
+        StepStatus::InProgress => "already in progress",
+        StepStatus::Done => "already completed",
+        StepStatus::Todo => "in todo status but could not be claimed",
+        StepStatus::Blocked => "blocked; set it back to 'todo' once it is unblocked",
+    };
+    format!("Cannot claim step {step_id} - it is {status_description}")
+}
+
+/// Explains why a plan has no step ready to start.
+fn no_next_step_message(plan_id: u64, summary: Option<&PlanSummary>) -> String {
+    match summary {
+        Some(summary) if summary.is_complete() => format!(
+            "Plan {plan_id} appears complete — all its steps are done. Consider archiving it with `archive_plan`."
+        ),
+        Some(summary) if summary.total_steps == 0 => {
+            format!("Plan {plan_id} has no steps yet. Use `add_step` to add some.")
+        }
+        _ => format!(
+            "No step of plan {plan_id} is ready to start: the remaining steps are in progress, awaiting verification, or blocked by unfinished dependencies."
+        ),
+    }
+}
+
+/// Explains that a step could not be claimed because of unfinished
+/// dependencies.
+fn blocked_message(step_id: u64, blockers: &[u64]) -> String {
+    let ids = blockers
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("Cannot claim step {step_id} - it depends on unfinished steps: {ids}")
+}
+
+/// Kind of entity a tool created or changed, as named in the `type` of its
+/// structured result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum EntityKind {
+    Plan,
+    Step,
+    Note,
+}
+
+/// Machine-readable reference to the entity a tool created or changed.
+#[derive(Debug, Serialize)]
+struct EntityRef {
+    id: u64,
+    #[serde(rename = "type")]
+    kind: EntityKind,
+}
+
+/// Result of a tool that created or changed the `kind` entity with `id`.
+///
+/// The human-readable `text` comes first, as it always has, followed by
+/// `{"id": ..., "type": ...}` as JSON, so agents don't have to parse the ID
+/// out of the text.
+fn entity_result(text: &impl fmt::Display, kind: EntityKind, id: u64) -> McpResult {
+    Ok(CallToolResult::success(vec![
+        Content::text(text.to_string()),
+        Content::json(EntityRef { id, kind })?,
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use beacon_core::{PlannerBuilder, TemplateStep};
+    use rmcp::model::ErrorCode;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    async fn create_handlers() -> (TempDir, McpHandlers) {
+        let temp_dir = TempDir::new().unwrap();
+        let planner = PlannerBuilder::new()
+            .with_database_path(Some(temp_dir.path().join("test.db")))
+            .build()
+            .await
+            .unwrap();
+        (temp_dir, McpHandlers::new(Arc::new(Mutex::new(planner))))
+    }
+
+    #[tokio::test]
+    async fn test_add_step_criteria_render_canonically_in_show_plan() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let plan = handlers
+            .planner
+            .lock()
+            .await
+            .create_plan(&core::CreatePlan {
+                title: "Criteria".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let canonical = "Done when:\n- [ ] tests pass\n- [x] docs updated";
+        let messy = "Done when:  \r\n  * tests pass\r\n  2. [X] docs updated  \r\n";
+        for (title, criteria) in [("Canonical", canonical), ("Messy", messy)] {
+            handlers
+                .add_step(Parameters(McpParams(core::StepCreate {
+                    plan_id: plan.id,
+                    title: title.to_string(),
+                    acceptance_criteria: Some(criteria.to_string()),
+                    ..Default::default()
+                })))
+                .await
+                .unwrap();
+        }
+
+        let shown = handlers
+            .show_plan(Parameters(McpParams(core::ShowPlan {
+                id: plan.id,
+                ..Default::default()
+            })))
+            .await
+            .unwrap();
+        let shown = &shown.content[0].as_text().unwrap().text;
+
+        // Both steps render exactly the canonical checklist
+        let section = format!("#### Acceptance\n\n{canonical}\n\n");
+        assert_eq!(shown.matches(&section).count(), 2, "{shown}");
+    }
+
+    #[tokio::test]
+    async fn test_show_plan_compact_lists_one_line_per_step() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let plan = handlers
+            .planner
+            .lock()
+            .await
+            .create_plan(&core::CreatePlan {
+                title: "Outlined".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let mut ids = Vec::new();
+        for title in ["Write parser", "Document parser"] {
+            let step = handlers
+                .planner
+                .lock()
+                .await
+                .add_step(&core::StepCreate {
+                    plan_id: plan.id,
+                    title: title.to_string(),
+                    description: Some("Long details".to_string()),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            ids.push(step.id);
+        }
+
+        let shown = handlers
+            .show_plan(Parameters(McpParams(core::ShowPlan {
+                id: plan.id,
+                compact: true,
+                ..Default::default()
+            })))
+            .await
+            .unwrap();
+        let shown = &shown.content[0].as_text().unwrap().text;
+
+        let expected = format!(
+            "## Steps\n\n- [ ] {}. Write parser (○ Todo)\n- [ ] {}. Document parser (○ Todo)\n",
+            ids[0], ids[1]
+        );
+        assert!(shown.contains(&expected), "{shown}");
+        assert!(!shown.contains("Long details"), "{shown}");
+        assert!(
+            shown.contains("- Progress: 0/2 done, 0 in progress, 2 todo\n"),
+            "{shown}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_next_step_suggests_archiving_complete_plans() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await;
+        let plan = planner
+            .create_plan(&core::CreatePlan {
+                title: "Almost there".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let step = planner
+            .add_step(&core::StepCreate {
+                plan_id: plan.id,
+                title: "Last step".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        drop(planner);
+
+        let next = || handlers.next_step(Parameters(McpParams(core::Id { id: plan.id })));
+        let shown = next().await.unwrap();
+        let shown = &shown.content[0].as_text().unwrap().text;
+        assert!(
+            shown.contains(&format!("### {}. Last step", step.id)),
+            "{shown}"
+        );
+        assert!(shown.contains("claim_step"), "{shown}");
+
+        handlers
+            .planner
+            .lock()
+            .await
+            .update_step_validated(&core::UpdateStep {
+                id: step.id,
+                status: Some("done".to_string()),
+                result: Some("Shipped".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let shown = next().await.unwrap();
+        let shown = &shown.content[0].as_text().unwrap().text;
+        assert!(shown.contains("appears complete"), "{shown}");
+        assert!(shown.contains("archive_plan"), "{shown}");
+    }
+
+    #[tokio::test]
+    async fn test_claim_next_step_returns_claimed_step_details() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await;
+        let plan = planner
+            .create_plan(&core::CreatePlan {
+                title: "Queue".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let step = planner
+            .add_step(&core::StepCreate {
+                plan_id: plan.id,
+                title: "Only step".to_string(),
+                description: Some("Do the thing".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        drop(planner);
+
+        let claim = || handlers.claim_next_step(Parameters(McpParams(core::Id { id: plan.id })));
+        let shown = claim().await.unwrap();
+        let shown = &shown.content[0].as_text().unwrap().text;
+        assert!(
+            shown.contains(&format!("Successfully claimed step {}", step.id)),
+            "{shown}"
+        );
+        assert!(shown.contains("Do the thing"), "{shown}");
+
+        let shown = claim().await.unwrap();
+        let shown = &shown.content[0].as_text().unwrap().text;
+        assert!(shown.contains("is ready to start"), "{shown}");
+    }
+
+    #[tokio::test]
+    async fn test_claim_step_returns_claimed_step_details() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await.clone();
+        let plan = planner
+            .create_plan(&core::CreatePlan {
+                title: "Claims".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let step = planner
+            .add_step(&core::StepCreate {
+                plan_id: plan.id,
+                title: "Write parser".to_string(),
+                description: Some("Handle nested lists".to_string()),
+                acceptance_criteria: Some("Round-trips the fixtures".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let claim = || handlers.claim_step(Parameters(McpParams(core::Id { id: step.id })));
+        let shown = claim().await.unwrap();
+        let shown = &shown.content[0].as_text().unwrap().text;
+        assert!(
+            shown.contains(&format!("Successfully claimed step {}", step.id)),
+            "{shown}"
+        );
+        assert!(shown.contains("Handle nested lists"), "{shown}");
+        assert!(shown.contains("Round-trips the fixtures"), "{shown}");
+        assert!(shown.contains("Launch a focused subagent"), "{shown}");
+
+        let shown = claim().await.unwrap();
+        let shown = &shown.content[0].as_text().unwrap().text;
+        assert!(shown.contains("it is already in progress"), "{shown}");
+        assert!(!shown.contains("Handle nested lists"), "{shown}");
+    }
+
+    #[tokio::test]
+    async fn test_blocked_step_cannot_be_claimed() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await.clone();
+        let plan = planner
+            .create_plan(&core::CreatePlan {
+                title: "Blocked".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let step = planner
+            .add_step(&core::StepCreate {
+                plan_id: plan.id,
+                title: "Deploy".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let updated = handlers
+            .update_step(Parameters(McpParams(core::UpdateStep {
+                id: step.id,
+                status: Some("blocked".to_string()),
+                blocked_reason: Some("Waiting on API keys".to_string()),
+                ..Default::default()
+            })))
+            .await
+            .unwrap();
+        let updated = &updated.content[0].as_text().unwrap().text;
+        assert!(
+            updated.contains("- Blocked: Waiting on API keys"),
+            "{updated}"
+        );
+
+        let shown = handlers
+            .claim_step(Parameters(McpParams(core::Id { id: step.id })))
+            .await
+            .unwrap();
+        let shown = &shown.content[0].as_text().unwrap().text;
+        assert!(
+            shown.contains(&format!("Cannot claim step {} - it is blocked", step.id)),
+            "{shown}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_plan_with_pending_steps_asks_for_force() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let plan = handlers
+            .planner
+            .lock()
+            .await
+            .create_plan(&core::CreatePlan {
+                title: "Pending".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        handlers
+            .add_step(Parameters(McpParams(core::StepCreate {
+                plan_id: plan.id,
+                title: "Unfinished".to_string(),
+                ..Default::default()
+            })))
+            .await
+            .unwrap();
+
+        let delete = |force| {
+            handlers.delete_plan(Parameters(McpParams(core::DeletePlan {
+                id: plan.id,
+                confirmed: true,
+                force,
+            })))
+        };
+        let error = delete(false).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert!(error.message.contains("1 step(s) that are not done"));
+        assert!(error.message.contains("force"));
+
+        let deleted = delete(true).await.unwrap();
+        assert!(
+            deleted.content[0]
+                .as_text()
+                .unwrap()
+                .text
+                .contains("Permanently deleted plan 'Pending'")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remove_step_in_progress_requires_force() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await;
+        let plan = planner
+            .create_plan(&core::CreatePlan {
+                title: "Duplicates".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let step = planner
+            .add_step(&core::StepCreate {
+                plan_id: plan.id,
+                title: "Claimed twice".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        planner.claim_step(&core::Id { id: step.id }).await.unwrap();
+        drop(planner);
+
+        let remove = |force| {
+            handlers.remove_step(Parameters(McpParams(core::RemoveStep {
+                id: step.id,
+                force,
+            })))
+        };
+        let error = remove(false).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert!(error.message.contains("in progress"), "{}", error.message);
+        assert!(error.message.contains("force"), "{}", error.message);
+
+        let removed = remove(true).await.unwrap();
+        let removed = &removed.content[0].as_text().unwrap().text;
+        assert!(
+            removed.contains(&format!(
+                "Removed step 'Claimed twice' (ID: {}) from plan {}",
+                step.id, plan.id
+            )),
+            "{removed}"
+        );
+        let steps = handlers
+            .planner
+            .lock()
+            .await
+            .get_steps(&core::Id { id: plan.id })
+            .await
+            .unwrap();
+        assert!(steps.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_remove_step_not_found() {
+        let (_temp_dir, handlers) = create_handlers().await;
+
+        let error = handlers
+            .remove_step(Parameters(McpParams(core::RemoveStep {
+                id: 999,
+                force: false,
+            })))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+        assert!(error.message.contains("999"), "{}", error.message);
+    }
+
+    #[tokio::test]
+    async fn test_list_events() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await;
+        let plan = planner
+            .create_plan(&core::CreatePlan {
+                title: "Synced".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let step = planner
+            .add_step(&core::StepCreate {
+                plan_id: plan.id,
+                title: "Mirrored".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        drop(planner);
+
+        let list = |limit| {
+            handlers.list_events(Parameters(McpParams(core::ListEvents {
+                since: Some("1h".to_string()),
+                limit,
+            })))
+        };
+        let result = list(None).await.unwrap();
+        let events: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(events[0]["entity_type"], "plan");
+        assert_eq!(events[0]["kind"], "created");
+        assert_eq!(events[1]["entity_id"], step.id);
+        assert_eq!(events[1]["kind"], "added");
+        assert_eq!(events[1]["payload"]["title"], "Mirrored");
+
+        let result = list(Some(1)).await.unwrap();
+        let events: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(events.as_array().unwrap().len(), 1);
+
+        let error = handlers
+            .list_events(Parameters(McpParams(core::ListEvents {
+                since: Some("soon".to_string()),
+                limit: None,
+            })))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_create_and_update_results_include_entity_ids() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let entity = |result: CallToolResult| {
+            // The text comes first, as before
+            assert!(result.content[0].as_text().unwrap().text.contains("ID"));
+            serde_json::from_str::<serde_json::Value>(&result.content[1].as_text().unwrap().text)
+                .unwrap()
+        };
+
+        let created = handlers
+            .create_plan(Parameters(McpParams(core::CreatePlan {
+                title: "Structured".to_string(),
+                ..Default::default()
+            })))
+            .await
+            .unwrap();
+        let plans = handlers
+            .planner
+            .lock()
+            .await
+            .list_plans(None)
+            .await
+            .unwrap();
+        assert_eq!(
+            entity(created),
+            serde_json::json!({ "id": plans[0].id, "type": "plan" })
+        );
+
+        let added = handlers
+            .add_step(Parameters(McpParams(core::StepCreate {
+                plan_id: plans[0].id,
+                title: "Parse me".to_string(),
+                ..Default::default()
+            })))
+            .await
+            .unwrap();
+        let plan = handlers
+            .planner
+            .lock()
+            .await
+            .get_plan(&core::Id { id: plans[0].id })
+            .await
+            .unwrap()
+            .unwrap();
+        let step_id = plan.steps[0].id;
+        assert_eq!(
+            entity(added),
+            serde_json::json!({ "id": step_id, "type": "step" })
+        );
+
+        let updated = handlers
+            .update_step(Parameters(McpParams(core::UpdateStep {
+                id: step_id,
+                status: Some("in_progress".to_string()),
+                ..Default::default()
+            })))
+            .await
+            .unwrap();
+        assert_eq!(
+            entity(updated),
+            serde_json::json!({ "id": step_id, "type": "step" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_template() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await;
+        let plan = planner
+            .create_plan(&core::CreatePlan {
+                title: "Release 2.0".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        planner
+            .create_template(&core::CreateTemplate {
+                name: "Release".to_string(),
+                steps: vec![
+                    TemplateStep {
+                        title: "Bump version".to_string(),
+                        ..Default::default()
+                    },
+                    TemplateStep {
+                        title: "Tag".to_string(),
+                        ..Default::default()
+                    },
+                ],
+            })
+            .await
+            .unwrap();
+        drop(planner);
+
+        let apply = |name: &str| {
+            handlers.apply_template(Parameters(McpParams(core::ApplyTemplate {
+                plan_id: plan.id,
+                name: name.to_string(),
+            })))
+        };
+        let result = apply("release").await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains("adding 2 step(s)"));
+        assert!(text.contains("Bump version"));
+        assert!(text.contains("Tag"));
+
+        let error = apply("missing").await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_get_stats() {
+        let (_temp_dir, handlers) = create_handlers().await;
+
+        let stats = |since: Option<&str>| {
+            handlers.get_stats(Parameters(McpParams(core::GetStats {
+                since: since.map(str::to_string),
+            })))
+        };
+        let result = stats(None).await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains("- **Plans created**: 0"));
+        assert!(text.contains("- **Average time to done**: n/a"));
+
+        let result = stats(Some("7d")).await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.starts_with("# Statistics since "));
+
+        let error = stats(Some("lately")).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_database_info_counts_reconnects() {
+        let (temp_dir, handlers) = create_handlers().await;
+        let info = || async {
+            let result = handlers.database_info().await.unwrap();
+            result.content[0].as_text().unwrap().text.clone()
+        };
+
+        let db_path = temp_dir.path().join("test.db");
+        let text = info().await;
+        assert!(text.contains(&format!("- **Database**: `{}`", db_path.display())));
+        assert!(text.contains("- **Reconnects**: 0"));
+
+        // Swap in another database, as restoring a backup would
+        let replacement_path = temp_dir.path().join("replacement.db");
+        drop(
+            PlannerBuilder::new()
+                .with_database_path(Some(replacement_path.clone()))
+                .build()
+                .await
+                .unwrap(),
+        );
+        std::fs::rename(&replacement_path, &db_path).unwrap();
+        handlers
+            .list_plans(Parameters(McpParams(core::ListPlans::default())))
+            .await
+            .unwrap();
+
+        assert!(info().await.contains("- **Reconnects**: 1"));
+    }
+
+    #[tokio::test]
+    async fn test_find_steps() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let plan = handlers
+            .planner
+            .lock()
+            .await
+            .create_plan(&core::CreatePlan {
+                title: "Searchable".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        for (title, description) in [
+            ("Add login", Some("Check the Authentication token")),
+            ("Write docs", None),
+        ] {
+            handlers
+                .add_step(Parameters(McpParams(core::StepCreate {
+                    plan_id: plan.id,
+                    title: title.to_string(),
+                    description: description.map(str::to_string),
+                    ..Default::default()
+                })))
+                .await
+                .unwrap();
+        }
+
+        let find = |query: &str, status: Option<&str>| {
+            handlers.find_steps(Parameters(McpParams(core::FindSteps {
+                plan_id: plan.id,
+                query: query.to_string(),
+                status: status.map(str::to_string),
+            })))
+        };
+        let result = find("authentication", None).await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains("Add login"));
+        assert!(!text.contains("Write docs"));
+
+        let result = find("authentication", Some("done")).await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains("No steps found."));
+
+        let error = find("  ", None).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_list_steps_by_status() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await.clone();
+        for (title, archived) in [("Active", false), ("Shelved", true)] {
+            let plan = planner
+                .create_plan(&core::CreatePlan {
+                    title: title.to_string(),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            planner
+                .add_step(&core::StepCreate {
+                    plan_id: plan.id,
+                    title: format!("{title} work"),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            planner
+                .claim_next_step(&core::Id { id: plan.id })
+                .await
+                .unwrap();
+            if archived {
+                planner
+                    .archive_plan(&core::ArchivePlan {
+                        id: plan.id,
+                        force: true,
+                    })
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let list = |status: &str, include_archived_plans: bool| {
+            handlers.list_steps_by_status(Parameters(McpParams(core::ListStepsByStatus {
+                status: status.to_string(),
+                include_archived_plans,
+            })))
+        };
+        let result = list("inprogress", false).await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains("## Active (ID: 1)"));
+        assert!(text.contains("Active work"));
+        assert!(!text.contains("Shelved"));
+
+        let result = list("inprogress", true).await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains("Shelved work"));
+
+        let result = list("done", false).await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert_eq!(text, "No steps found.\n");
+
+        let error = list("finished", false).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_archive_plan_is_strict() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await.clone();
+        let plan = planner
+            .create_plan(&core::CreatePlan {
+                title: "Unfinished".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        planner
+            .add_step(&core::StepCreate {
+                plan_id: plan.id,
+                title: "Pending".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let archive = |force: bool| {
+            handlers.archive_plan(Parameters(McpParams(core::ArchivePlan {
+                id: plan.id,
+                force,
+            })))
+        };
+        let error = archive(false).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        let data = error.data.unwrap();
+        assert_eq!(data["kind"], "plan_has_active_steps");
+        assert_eq!(data["todo"], 1);
+
+        let result = archive(true).await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains("Archived plan with ID 1"), "{text}");
+    }
+
+    #[tokio::test]
+    async fn test_list_resources() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await.clone();
+        for title in ["Listed", "Archived"] {
+            planner
+                .create_plan(&core::CreatePlan {
+                    title: title.to_string(),
+                    description: Some(format!("{title} description")),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+        planner
+            .archive_plan(&core::ArchivePlan { id: 2, force: true })
+            .await
+            .unwrap();
+
+        let result = handlers.list_resources(None).await.unwrap();
+        let resources = result
+            .resources
+            .iter()
+            .map(|resource| (resource.uri.as_str(), resource.name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            resources,
+            [
+                ("beacon://open/plans", "Active Plans"),
+                ("beacon://open/plan/1", "Listed")
+            ]
+        );
+        assert_eq!(
+            result.resources[1].description.as_deref(),
+            Some("Listed description")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_resource() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await.clone();
+        let plan = planner
+            .create_plan(&core::CreatePlan {
+                title: "Readable".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let read = |uri: &str| {
+            handlers.read_resource(ReadResourceRequestParam {
+                uri: uri.to_string(),
+            })
+        };
+        let text = |result: ReadResourceResult| match result.contents.into_iter().next() {
+            Some(ResourceContents::TextResourceContents { text, .. }) => text,
+            other => panic!("expected text contents, got {other:?}"),
+        };
+
+        let plan_text = text(read("beacon://open/plan/1").await.unwrap());
+        assert!(plan_text.contains("Readable"));
+        // Generated on read, so later changes show up
+        planner
+            .add_step(&core::StepCreate {
+                plan_id: plan.id,
+                title: "Fresh step".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let plan_text = text(read("beacon://open/plan/1").await.unwrap());
+        assert!(plan_text.contains("Fresh step"));
+
+        let index = text(read("beacon://open/plans").await.unwrap());
+        assert!(index.starts_with("# Active Plans"));
+        assert!(index.contains("Readable"));
+
+        for uri in [
+            "beacon://open/plan/999",
+            "beacon://open/plan/abc",
+            "beacon://open/step/1",
+            "beacon://open/plan/1?workspace=elsewhere",
+            "beacon://plan/1",
+            "file:///etc/passwd",
+        ] {
+            let error = read(uri).await.unwrap_err();
+            assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND, "{uri}");
+        }
+        let error = read("beacon://open/plan/999").await.unwrap_err();
+        assert!(error.message.contains("999"));
+    }
+
+    #[tokio::test]
+    async fn test_read_resource_hides_archived_plans() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await.clone();
+        planner
+            .create_plan(&core::CreatePlan {
+                title: "Shelved".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        planner
+            .archive_plan(&core::ArchivePlan { id: 1, force: true })
+            .await
+            .unwrap();
+
+        let error = handlers
+            .read_resource(ReadResourceRequestParam {
+                uri: "beacon://open/plan/1".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+        assert!(error.message.contains("archived"), "{}", error.message);
+    }
+
+    #[tokio::test]
+    async fn test_reassign_directory() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await.clone();
+        planner
+            .create_plan(&core::CreatePlan {
+                title: "Moved".to_string(),
+                directory: Some("/old/repo".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let reassign = |from: &str, to: &str| {
+            handlers.reassign_directory(Parameters(McpParams(core::ReassignDirectory {
+                from: from.to_string(),
+                to: to.to_string(),
+            })))
+        };
+        let result = reassign("/old/repo", "/new/repo").await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains("Moved 1 plan(s) from /old/repo to /new/repo"));
+
+        let result = reassign("/old/repo", "/new/repo").await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains("No plans found in /old/repo"));
+
+        let error = reassign("/new/repo", "/new/repo/sub").await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_reset_plan_steps() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await.clone();
+        let plan = planner
+            .create_plan(&core::CreatePlan {
+                title: "Weekly".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let step = planner
+            .add_step(&core::StepCreate {
+                plan_id: plan.id,
+                title: "Rotate logs".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        planner.claim_step(&core::Id { id: step.id }).await.unwrap();
+
+        let reset = |id| {
+            handlers.reset_plan_steps(Parameters(McpParams(core::ResetPlanSteps {
+                id,
+                keep_results: false,
+            })))
+        };
+        let result = reset(plan.id).await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains(&format!("Reset 1 step(s) of plan {} to todo", plan.id)));
+        let step = planner
+            .get_step(&core::Id { id: step.id })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(step.status, StepStatus::Todo);
+
+        let error = reset(999).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_move_step_to_plan() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await.clone();
+        let mut plan_ids = Vec::new();
+        for title in ["Source", "Target"] {
+            let plan = planner
+                .create_plan(&core::CreatePlan {
+                    title: title.to_string(),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            plan_ids.push(plan.id);
+        }
+        let step = planner
+            .add_step(&core::StepCreate {
+                plan_id: plan_ids[0],
+                title: "Misplaced".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let move_to = |plan_id: u64| {
+            handlers.move_step_to_plan(Parameters(McpParams(core::MoveStepToPlan {
+                step_id: step.id,
+                plan_id,
+                position: None,
+            })))
+        };
+        let result = move_to(plan_ids[1]).await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains(&format!(
+            "moved step {} to plan {} at position 0",
+            step.id, plan_ids[1]
+        )));
+
+        let error = move_to(404).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+        planner
+            .archive_plan(&core::ArchivePlan {
+                id: plan_ids[0],
+                force: true,
+            })
+            .await
+            .unwrap();
+        let error = move_to(plan_ids[0]).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_plan_report() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await.clone();
+        let plan = planner
+            .create_plan(&core::CreatePlan {
+                title: "Standup".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let step = planner
+            .add_step(&core::StepCreate {
+                plan_id: plan.id,
+                title: "Write docs".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        planner.claim_step(&core::Id { id: step.id }).await.unwrap();
+
+        let report = |since: Option<&str>| {
+            handlers.plan_report(Parameters(McpParams(core::GetPlanReport {
+                id: plan.id,
+                since: since.map(String::from),
+            })))
+        };
+        let result = report(None).await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains("## In Progress\n\n- Write docs"), "{text}");
+
+        let error = report(Some("yesterday")).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_append_plan_note() {
+        let (_temp_dir, handlers) = create_handlers().await;
+        let planner = handlers.planner.lock().await.clone();
+        let plan = planner
+            .create_plan(&core::CreatePlan {
+                title: "Noted".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let append = |id: u64, text: &str| {
+            handlers.append_plan_note(Parameters(McpParams(core::AppendPlanNote {
+                id,
+                text: text.to_string(),
+            })))
+        };
+        let result = append(plan.id, "Keep the API stable").await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains(&format!("Added note to plan {}", plan.id)));
+
+        let result = handlers
+            .show_plan(Parameters(McpParams(core::ShowPlan {
+                id: plan.id,
+                ..Default::default()
+            })))
+            .await
+            .unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains("## Notes"));
+        assert!(text.contains("Keep the API stable"));
+
+        let error = append(404, "Lost").await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+        let error = append(plan.id, "").await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_missing_ids_report_entity() {
+        let (_temp_dir, handlers) = create_handlers().await;
+
+        let error = handlers
+            .show_plan(Parameters(McpParams(core::ShowPlan {
+                id: 404,
+                ..Default::default()
+            })))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+        assert_eq!(
+            error.data,
+            Some(serde_json::json!({
+                "kind": "not_found",
+                "retryable": false,
+                "entity": "plan",
+                "id": 404,
+            }))
+        );
+
+        let error = handlers
+            .show_step(Parameters(McpParams(core::Id { id: 405 })))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+        let data = error.data.unwrap();
+        assert_eq!(data["entity"], "step");
+        assert_eq!(data["id"], 405);
+
+        let error = handlers
+            .create_plan(Parameters(McpParams(core::CreatePlan {
+                title: " ".to_string(),
+                ..Default::default()
+            })))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert_eq!(
+            error.data,
+            Some(serde_json::json!({
+                "kind": "invalid_input",
+                "retryable": false,
+                "field": "title",
+            }))
+        );
     }
 }
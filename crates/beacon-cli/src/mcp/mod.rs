@@ -7,14 +7,15 @@
 use std::{future::Future, sync::Arc};
 
 use anyhow::Result;
-use beacon_core::{Planner, PlannerError};
+use beacon_core::Planner;
 use log::{debug, error, info};
 use rmcp::{
-    ErrorData as McpError, ErrorData, RoleServer, ServerHandler,
+    ErrorData as McpError, RoleServer, ServerHandler,
     handler::server::{router::tool::ToolRouter, tool::Parameters},
     model::{
         GetPromptRequestParam, GetPromptResult, Implementation, ListPromptsResult,
-        PaginatedRequestParam, ProtocolVersion, ServerCapabilities, ServerInfo,
+        ListResourcesResult, PaginatedRequestParam, ProtocolVersion, ReadResourceRequestParam,
+        ReadResourceResult, ServerCapabilities, ServerInfo,
     },
     service::RequestContext,
     tool, tool_handler, tool_router,
@@ -24,14 +25,19 @@ use tokio::{
     sync::Mutex,
 };
 
-// pub mod errors;
+pub mod errors;
 pub mod handlers;
 pub mod prompts;
 
+pub use errors::to_mcp_error;
 // Re-export parameter types and result type from handlers for external use
 pub use handlers::{
-    CreatePlan, Id, InsertStep, ListPlans, McpResult, SearchPlans, StepCreate, SwapSteps,
-    UpdateStep,
+    AddStepNote, AppendPlanNote, ApplyTemplate, ArchiveCompletedPlans, ArchivePlan, ClonePlan,
+    CreatePlan, DeletePlan, DuplicateStepsReport, FindSteps, GetConfig, GetPlanReport, GetStats,
+    Id, InsertStep, ListEvents, ListPlans, ListSteps, ListStepsByStatus, McpResult, MergePlans,
+    MoveStep, MoveStepToPlan, PlanChangelog, ReassignDirectory, RemoveStep, ResetPlanSteps,
+    SearchPlans, SetConfig, SetStepPhase, ShowPlan, StepCreate, StepDependency, SwapSteps,
+    UpdatePlan, UpdateStep, VerifyStep,
 };
 
 /// MCP server for Beacon
@@ -54,7 +60,7 @@ impl BeaconMcpServer {
     // Tool methods that delegate to handlers::McpHandlers methods
     #[tool(
         name = "create_plan",
-        description = "Create a new task plan to organize work. Provide a clear title (required), optional detailed description for context, and optional directory to associate with specific project location. Returns the new plan ID for adding steps."
+        description = "Create a new task plan to organize work. Provide a clear title (required, at most 500 characters), optional detailed description for context (at most 64 KB), and optional directory to associate with specific project location. Set infer_git_root=true to store the enclosing git repository root instead of a subdirectory. Set parent_plan_id to create the plan as a workstream of a larger parent plan (nesting is one level deep). Set auto_archive=true to have the plan archive itself once all its steps are done; it defaults to the workspace's auto_archive setting. Set due_date (YYYY-MM-DD) to give the plan a deadline; active plans past it are shown as overdue. Returns the new plan, followed by its ID as JSON ({\"id\": ..., \"type\": \"plan\"}) for adding steps."
     )]
     async fn create_plan(&self, params: Parameters<CreatePlan>) -> McpResult {
         handlers::McpHandlers::new(self.planner.clone())
@@ -64,7 +70,7 @@ impl BeaconMcpServer {
 
     #[tool(
         name = "list_plans",
-        description = "List all task plans. Use archived=false (default) for active plans you're working on, or archived=true to see completed/hidden plans. Returns formatted list with IDs, titles, descriptions, and directories."
+        description = "List all task plans. Use archived=false (default) for active plans you're working on, or archived=true to see completed/hidden plans. Set children_of to a plan ID to list only its child plans. Set completion to 'complete' (all steps done), 'incomplete' (some step not done) or 'empty' (no steps) to filter by progress; it combines with archived. Set due_before (YYYY-MM-DD) to list only plans due on or before that date. With many plans, set limit and offset to page through them; the response then says how many of all the matching plans are shown, and an offset past the end gives an empty page. Returns formatted list with IDs, titles, descriptions, directories, and rolled-up child plan progress for parent plans."
     )]
     async fn list_plans(&self, params: Parameters<ListPlans>) -> McpResult {
         handlers::McpHandlers::new(self.planner.clone())
@@ -74,27 +80,90 @@ impl BeaconMcpServer {
 
     #[tool(
         name = "show_plan",
-        description = "Display complete details of a specific plan including all its steps, their status (todo/done), descriptions, and acceptance criteria. Use the plan ID to retrieve. Essential for understanding project scope and progress."
+        description = "Display complete details of a specific plan including all its steps, their status (todo/done), descriptions, and acceptance criteria, followed by its child plans with their progress. Use the plan ID to retrieve. A Progress line after the plan's metadata counts its steps by status (e.g. '12/40 done, 2 in progress, 26 todo'), so how far along it is can be read without going through every step. For plans with many steps, page through them with step_offset (0-based) and step_limit; a footer then reports which steps are shown out of the total. Set compact=true to show each step as a single checklist line (ID, title and status) instead of all its sections. Essential for understanding project scope and progress."
     )]
-    async fn show_plan(&self, params: Parameters<Id>) -> McpResult {
+    async fn show_plan(&self, params: Parameters<ShowPlan>) -> McpResult {
         handlers::McpHandlers::new(self.planner.clone())
             .show_plan(params)
             .await
     }
 
+    #[tool(
+        name = "update_plan",
+        description = "Modify an existing plan's title, description or directory, or move it under a parent plan with parent_plan_id. Fields left out keep their current values; a relative directory is made absolute as when creating a plan. Set clear_parent=true to make it a top-level plan again. Set auto_archive to turn archiving on completion of the last step on or off. Set due_date (YYYY-MM-DD) to change the plan's deadline, or clear_due_date=true to remove it. Set status_labels (e.g. {\"inprogress\": \"doing\"}) to display step statuses under team-specific names; an empty object clears them. Labels only affect display: statuses are still set by their canonical names. Plans nest one level deep: a parent cannot itself have a parent, and a plan with children cannot become a child."
+    )]
+    async fn update_plan(&self, params: Parameters<UpdatePlan>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .update_plan(params)
+            .await
+    }
+
     #[tool(
         name = "archive_plan",
-        description = "Archive a completed or inactive plan to hide it from the active list. Archived plans are preserved and can be restored later with unarchive_plan. Use when a project is finished or temporarily on hold."
+        description = "Archive a completed or inactive plan to hide it from the active list. Archived plans are preserved and can be restored later with unarchive_plan. Use when a project is finished or temporarily on hold. Archiving a plan with steps that are not done, or a parent plan while child plans are still active, is refused unless force=true; the error data gives the number of steps in progress and to do."
     )]
-    async fn archive_plan(&self, params: Parameters<Id>) -> McpResult {
+    async fn archive_plan(&self, params: Parameters<ArchivePlan>) -> McpResult {
         handlers::McpHandlers::new(self.planner.clone())
             .archive_plan(params)
             .await
     }
 
+    #[tool(
+        name = "archive_completed_plans",
+        description = "Archive every active plan whose steps are all done in one go, e.g. to tidy up after a busy month. Plans without steps are not considered completed, and a parent plan stays active while any of its child plans does. Pass directory to only archive plans in that directory or below it. Either all matching plans are archived or none are. Returns the archived plans."
+    )]
+    async fn archive_completed_plans(
+        &self,
+        params: Parameters<ArchiveCompletedPlans>,
+    ) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .archive_completed_plans(params)
+            .await
+    }
+
+    #[tool(
+        name = "delete_plan",
+        description = "Permanently delete a plan and all its steps. This cannot be undone; prefer archive_plan for plans that may be needed again. Requires confirmed=true. Deleting a plan that still has steps that are not done is refused, reporting how many, unless force=true."
+    )]
+    async fn delete_plan(&self, params: Parameters<DeletePlan>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .delete_plan(params)
+            .await
+    }
+
+    #[tool(
+        name = "clone_plan",
+        description = "Copy an existing plan as a template for repeated work, such as a release checklist. The copy gets the plan's description, directory and all its steps, with every step reset to todo and its result cleared. Pass title to name the copy; it defaults to the original's title. Cloning an archived plan produces an active copy. Returns the new plan's ID."
+    )]
+    async fn clone_plan(&self, params: Parameters<ClonePlan>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .clone_plan(params)
+            .await
+    }
+
+    #[tool(
+        name = "append_plan_note",
+        description = "Append to a plan's working notes: decisions, open questions or context that concern the whole plan rather than one step and should survive to the next session. The text is added under a heading with the current time, after any earlier notes. show_plan shows the notes under 'Notes' after the description; list_plans leaves them out."
+    )]
+    async fn append_plan_note(&self, params: Parameters<AppendPlanNote>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .append_plan_note(params)
+            .await
+    }
+
+    #[tool(
+        name = "merge_plans",
+        description = "Merge two plans for the same effort into one. Appends the source plan's steps to the end of the target plan, keeping their statuses, results, references and order, and concatenates the descriptions. Set prefix_titles=true to prefix the moved steps' titles with the source plan's title. The source plan is archived with a pointer to the target rather than deleted; merging it again is refused. Requires confirmed=true."
+    )]
+    async fn merge_plans(&self, params: Parameters<MergePlans>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .merge_plans(params)
+            .await
+    }
+
     #[tool(
         name = "search_plans",
-        description = "Find all plans associated with a specific directory path. Use archived=false (default) for active plans you're working on, or archived=true to see completed/hidden plans for the directory. Useful for discovering existing plans in a project folder or organizing plans by location."
+        description = "Find all plans associated with a specific directory path. Use archived=false (default) for active plans you're working on, or archived=true to see completed/hidden plans for the directory. Plans in subdirectories match too; set include_subdirectories=false for only the exact directory. Useful for discovering existing plans in a project folder or organizing plans by location."
     )]
     async fn search_plans(&self, params: Parameters<SearchPlans>) -> McpResult {
         handlers::McpHandlers::new(self.planner.clone())
@@ -102,9 +171,189 @@ impl BeaconMcpServer {
             .await
     }
 
+    #[tool(
+        name = "reassign_directory",
+        description = "Move every plan in the 'from' directory or below it, archived plans included, to the corresponding path under 'to', e.g. after a repository was moved on disk and its plans stopped showing up in search_plans. Returns the number of plans moved, reporting zero matches without error. 'to' must not be inside 'from' or contain it."
+    )]
+    async fn reassign_directory(&self, params: Parameters<ReassignDirectory>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .reassign_directory(params)
+            .await
+    }
+
+    #[tool(
+        name = "reset_plan_steps",
+        description = "Put every step of a plan back to 'todo' in one transaction, so that a recurring plan (e.g. a weekly checklist) can be worked through again under the same ID instead of cloning it. Start and completion times, blocked reasons and verifications are cleared, and so are step results unless keep_results=true. Archived plans are refused. Returns the number of steps reset."
+    )]
+    async fn reset_plan_steps(&self, params: Parameters<ResetPlanSteps>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .reset_plan_steps(params)
+            .await
+    }
+
+    #[tool(
+        name = "handoff_summary",
+        description = "Produce a concise markdown handoff brief for a plan: the goal (description), completed steps with their results, steps in progress, and remaining steps. Use when handing work over to another person or agent."
+    )]
+    async fn handoff_summary(&self, params: Parameters<Id>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .handoff_summary(params)
+            .await
+    }
+
+    #[tool(
+        name = "plan_changelog",
+        description = "Get a plan's changelog: its changes newest first, grouped under a header per day, listing steps created, claimed, completed (with the first line of their result) and verified, and plan-level changes. Reconstructed from timestamps, so only each step's latest change is known. Pass since (e.g. \"7d\", \"2w\" or \"2024-11-01\") to limit it to recent changes, such as for a weekly review."
+    )]
+    async fn plan_changelog(&self, params: Parameters<PlanChangelog>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .plan_changelog(params)
+            .await
+    }
+
+    #[tool(
+        name = "plan_report",
+        description = "Get a short markdown standup report of a plan to post to a chat: the steps completed since a cutoff with their results, the steps in progress, and the next todo steps. Pass since (e.g. \"24h\", \"3d\" or \"2024-11-01\") to choose the period; it defaults to the last 24 hours. Says so explicitly when nothing was completed and nothing is in progress."
+    )]
+    async fn plan_report(&self, params: Parameters<GetPlanReport>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .plan_report(params)
+            .await
+    }
+
+    #[tool(
+        name = "list_events",
+        description = "List the change log of plans and steps as a JSON array, oldest first. Each event has an id, entity_type (plan or step), entity_id, kind (created, updated, archived, unarchived, merged or deleted for plans; added, updated, claimed, released, verified, removed, restored, purged, swapped or moved for steps), payload (the plan or step as JSON, as it was after the change or just before a deletion, and a purged step as it was listed in the trash) and created_at. Pass since (e.g. \"2h\", \"7d\" or \"2024-11-01\") and limit to page through recent changes, e.g. to sync with another tool."
+    )]
+    async fn list_events(&self, params: Parameters<ListEvents>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .list_events(params)
+            .await
+    }
+
+    #[tool(
+        name = "get_stats",
+        description = "Report statistics for a review as markdown: plans created, archived and touched, steps created and completed, the share of created steps that are done, the average time from creating a step to completing it, and the directories with the most completed steps. Pass since (e.g. \"7d\", \"2w\" or \"2024-11-01\") to only count what happened since then; omit it for all time."
+    )]
+    async fn get_stats(&self, params: Parameters<GetStats>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .get_stats(params)
+            .await
+    }
+
+    #[tool(
+        name = "database_info",
+        description = "Show the database file the server uses, how many connections it has opened, and how many times it reconnected after the file was replaced on disk, e.g. by restoring a backup."
+    )]
+    async fn database_info(&self) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .database_info()
+            .await
+    }
+
+    #[tool(
+        name = "apply_template",
+        description = "Append the steps of a step template to the end of a plan, in one go. Templates are named lists of steps defined with `b template create`; the name is matched ignoring case. Fails if no template has the name or the plan is archived. Returns the new steps with their IDs."
+    )]
+    async fn apply_template(&self, params: Parameters<ApplyTemplate>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .apply_template(params)
+            .await
+    }
+
+    #[tool(
+        name = "duplicate_steps_report",
+        description = "Find work tracked more than once: groups the open (not done) steps of active plans whose titles match when case, whitespace and punctuation are ignored, listing each step's ID and status with its plan's ID and title. Pass directory to only compare plans in that directory or below it. Consolidate the duplicates by hand, e.g. with merge_plans."
+    )]
+    async fn duplicate_steps_report(&self, params: Parameters<DuplicateStepsReport>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .duplicate_steps_report(params)
+            .await
+    }
+
+    #[tool(
+        name = "get_config",
+        description = "Read the workspace settings shared by everyone using this database, such as auto_archive and in_progress_warning_threshold. Pass key to read one setting; omit it to list all of them with what they control. Each value says whether it is the default, stored for the workspace, or overridden by the server's configuration."
+    )]
+    async fn get_config(&self, params: Parameters<GetConfig>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .get_config(params)
+            .await
+    }
+
+    #[tool(
+        name = "set_config",
+        description = "Change a workspace setting for everyone using this database. Only auto_archive (true/false: new plans archive themselves once done) and in_progress_warning_threshold (e.g. 7d or 36h) can be changed this way; other settings are changed by the user with `b config set`."
+    )]
+    async fn set_config(&self, params: Parameters<SetConfig>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .set_config(params)
+            .await
+    }
+
+    #[tool(
+        name = "plan_outline",
+        description = "Get a compact outline of a plan: one line per step with its ID, title and status, in order. Much smaller than show_plan since descriptions, acceptance criteria, references and results are left out. Use it to get oriented in large plans, then show_step for details."
+    )]
+    async fn plan_outline(&self, params: Parameters<Id>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .plan_outline(params)
+            .await
+    }
+
+    #[tool(
+        name = "next_step",
+        description = "Find the step to work on next in a plan: the first step, in order, that is still todo and not blocked by an unfinished dependency. Steps in progress, blocked or done are skipped. Returns the step's full details; claim it with claim_step before starting work. When every step is done, says the plan appears complete so it can be archived."
+    )]
+    async fn next_step(&self, params: Parameters<Id>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .next_step(params)
+            .await
+    }
+
+    #[tool(
+        name = "claim_next_step",
+        description = "Claim the step to work on next in a plan (as next_step would find it) and mark it as in progress, in one atomic operation so two agents never get the same step. Returns the claimed step's full details so work can start right away. When no step is ready, says why instead, e.g. that the plan appears complete. Fails with retryable=true in the error data while another agent is writing; retry the call then."
+    )]
+    async fn claim_next_step(&self, params: Parameters<Id>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .claim_next_step(params)
+            .await
+    }
+
+    #[tool(
+        name = "list_steps",
+        description = "List the steps of a plan, optionally only those with a given status ('todo', 'inprogress', 'done' or 'blocked'). Returns one line per step with its ID, title, status and position in the plan, in order. Use status='todo' to find the next step to work on without reading the whole plan; a plan without matching steps returns an empty list."
+    )]
+    async fn list_steps(&self, params: Parameters<ListSteps>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .list_steps(params)
+            .await
+    }
+
+    #[tool(
+        name = "find_steps",
+        description = "Find the steps of a plan whose title or description contains the query, ignoring case, optionally only those with a given status ('todo', 'inprogress', 'done' or 'blocked'). Returns the matching steps in full with their IDs, in order. Use it to locate a step, such as the one about authentication, without reading the whole plan. The query must not be empty."
+    )]
+    async fn find_steps(&self, params: Parameters<FindSteps>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .find_steps(params)
+            .await
+    }
+
+    #[tool(
+        name = "list_steps_by_status",
+        description = "List the steps with a given status ('todo', 'inprogress', 'done' or 'blocked') across all plans, grouped under their plans, most recently updated first. Use status='inprogress' to see all the work in flight. Steps of archived plans are left out unless include_archived_plans is true."
+    )]
+    async fn list_steps_by_status(&self, params: Parameters<ListStepsByStatus>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .list_steps_by_status(params)
+            .await
+    }
+
     #[tool(
         name = "add_step",
-        description = "Add a new step to an existing plan. Requires plan_id and title. Optionally include: description (detailed info), acceptance_criteria (completion requirements), and references (URLs/files). Steps start with 'todo' status and are added at the end of the plan."
+        description = "Add a new step to an existing plan. Requires plan_id and title (at most 500 characters). Optionally include: description (detailed info), acceptance_criteria (completion requirements), expected_output (the artifact the step should produce, compared against its result once done), and references (URLs/files, each a string or a {url, label} object; with the workspace's strict_references setting on, malformed URLs are rejected and each reference is classified as url, file, missing_file or text). Set verification_required=true for high-risk steps that a second agent must verify with verify_step once done. Titles are trimmed and must not be blank; description, acceptance_criteria and expected_output are limited to 64 KB each. Steps start with 'todo' status and are added at the end of the plan."
     )]
     async fn add_step(&self, params: Parameters<StepCreate>) -> McpResult {
         handlers::McpHandlers::new(self.planner.clone())
@@ -132,12 +381,96 @@ impl BeaconMcpServer {
             .await
     }
 
+    #[tool(
+        name = "move_step",
+        description = "Move a step to another position in its plan's step order. Position is 0-indexed (0 = first position); the steps in between shift by one to make room. Use it instead of repeated swap_steps to reorder a step in one go. Moving a step to its current position does nothing."
+    )]
+    async fn move_step(&self, params: Parameters<MoveStep>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .move_step(params)
+            .await
+    }
+
+    #[tool(
+        name = "move_step_to_plan",
+        description = "Move a step that was added to the wrong plan into another plan. The step keeps its ID, status, result, references, notes and dependencies; the steps after it in its old plan move up to close the gap. Pass position (0-indexed) to place it in the target plan; it goes to the end by default. Moving a step to its own plan works like move_step. The target plan must exist and be active."
+    )]
+    async fn move_step_to_plan(&self, params: Parameters<MoveStepToPlan>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .move_step_to_plan(params)
+            .await
+    }
+
+    #[tool(
+        name = "add_step_dependency",
+        description = "Make a step depend on another: step_id cannot be claimed or set in progress until depends_on is done, so agents working in parallel don't start it too early. claim_step and claim_and_show report such a step as blocked, listing the unfinished steps. Dependencies that would form a cycle, including a step depending on itself, are rejected. Adding an existing dependency does nothing."
+    )]
+    async fn add_step_dependency(&self, params: Parameters<StepDependency>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .add_step_dependency(params)
+            .await
+    }
+
+    #[tool(
+        name = "remove_step",
+        description = "Remove a step from its plan, e.g. a duplicate created by mistake. The step is moved to the trash, from where the user can restore it. Removing a step that is in progress is refused, since another agent may be working on it, unless force=true. Returns the removed step's title and plan ID."
+    )]
+    async fn remove_step(&self, params: Parameters<RemoveStep>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .remove_step(params)
+            .await
+    }
+
+    #[tool(
+        name = "remove_step_dependency",
+        description = "Remove a dependency added with add_step_dependency, so that step_id no longer waits for depends_on to be done."
+    )]
+    async fn remove_step_dependency(&self, params: Parameters<StepDependency>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .remove_step_dependency(params)
+            .await
+    }
+
+    #[tool(
+        name = "add_step_note",
+        description = "Record a note on a step while working on it: something discovered along the way that doesn't belong in the final result but shouldn't be lost, e.g. 'the API returns 429 under load'. Notes are kept in the order they were added and shown under 'Notes' by show_step and show_plan."
+    )]
+    async fn add_step_note(&self, params: Parameters<AddStepNote>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .add_step_note(params)
+            .await
+    }
+
+    #[tool(
+        name = "verify_step",
+        description = "Verify the completion of a done step that requires verification. Must be called by a different agent than the one that completed the step; pass your own identity as verifier and describe what you checked in comment. Until verified, such steps show as 'Done (awaiting verification)' and don't count towards plan progress."
+    )]
+    async fn verify_step(&self, params: Parameters<VerifyStep>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .verify_step(params)
+            .await
+    }
+
+    #[tool(
+        name = "set_step_phase",
+        description = "Group a step under a phase such as 'Design', 'Implementation' or 'Testing'. show_plan lists phased steps under a '## Phase: <name>' header per phase, in the order each phase first appears, keeping step order within each phase. Omit phase to remove the step from its phase."
+    )]
+    async fn set_step_phase(&self, params: Parameters<SetStepPhase>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .set_step_phase(params)
+            .await
+    }
+
     #[tool(
         name = "update_step",
-        description = "Modify an existing step's properties. Use step ID to identify. Can update: status ('todo', 'inprogress', or 'done'), title, description, acceptance_criteria, and references.
+        description = "Modify an existing step's properties. Use step ID to identify. Can update: status ('todo', 'inprogress', 'done', or 'blocked'), title, description, acceptance_criteria, expected_output, references, and verification_required.
         
         IMPORTANT: When changing status to 'done', you MUST provide a 'result' field describing what was actually accomplished, technically in detail, with proper Markdown format. The result will be permanently recorded and shown when viewing completed steps. The result field is ignored for all other status values.
 
+        Set status 'blocked' when a step cannot progress until something outside the plan changes, with an optional blocked_reason (e.g. \"waiting on API keys\"); no result is needed. Blocked steps cannot be claimed and count as pending; set the status back to 'todo' once unblocked, which drops the reason. To change the reason of a blocked step, give blocked_reason without a status: setting a status the step already has is refused, as are completing a step whose dependencies are unfinished and blocking a done step.
+
+        Reopening a done step (status 'todo' or 'inprogress') keeps its result, hidden until the step is done again; marking it done without a new result then reuses it. Set clear_result=true when reopening to drop the old result instead. To correct the result of a done step, give result without a status.
+
         Format the result with clear sections using **bold headers** and detailed bullet points describing:
         - What was created/modified (with file paths)
         - Technical implementation details
@@ -167,9 +500,19 @@ impl BeaconMcpServer {
             .await
     }
 
+    #[tool(
+        name = "allowed_transitions",
+        description = "Check what can be done with a step next before trying it: lists each transition (claim, release, start, complete, reopen, block, verify) with the status it leads to and whether it is allowed, giving the reason for those that are not, e.g. that the step is already done or its plan is archived. update_step, claim_step, release_step and verify_step apply the same rules."
+    )]
+    async fn allowed_transitions(&self, params: Parameters<Id>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .allowed_transitions(params)
+            .await
+    }
+
     #[tool(
         name = "claim_step",
-        description = "Atomically claim a step by transitioning it from 'todo' to 'inprogress' status. This prevents multiple agents from working on the same task simultaneously. Returns success if the step was claimed, or indicates if the step was already claimed or completed."
+        description = "Atomically claim a step by transitioning it from 'todo' to 'inprogress' status. This prevents multiple agents from working on the same task simultaneously. Returns the claimed step's full details (description, acceptance criteria, references) so there is no need to call show_step next; otherwise indicates if the step was already claimed, completed or blocked, or lists the steps it depends on that are not done yet. Fails with retryable=true in the error data while another agent is writing; retry the call then."
     )]
     async fn claim_step(&self, params: Parameters<Id>) -> McpResult {
         handlers::McpHandlers::new(self.planner.clone())
@@ -177,6 +520,26 @@ impl BeaconMcpServer {
             .await
     }
 
+    #[tool(
+        name = "release_step",
+        description = "Give up a claimed step: atomically transitions it from 'inprogress' back to 'todo' so that another agent can claim it. Use it when you cannot finish a step you claimed, instead of leaving it in progress. Steps that are not in progress are left unchanged."
+    )]
+    async fn release_step(&self, params: Parameters<Id>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .release_step(params)
+            .await
+    }
+
+    #[tool(
+        name = "claim_and_show",
+        description = "Atomically claim a step and return its full details (description, acceptance criteria, references) in one call. Same as claim_step, without the reminder to hand the step to a subagent. If the step cannot be claimed, returns the reason (already in progress, completed or blocked) without the step details."
+    )]
+    async fn claim_and_show(&self, params: Parameters<Id>) -> McpResult {
+        handlers::McpHandlers::new(self.planner.clone())
+            .claim_and_show(params)
+            .await
+    }
+
     /// List all available prompts
     async fn list_prompts(
         &self,
@@ -198,6 +561,26 @@ impl BeaconMcpServer {
             .get_prompt(request, context)
             .await
     }
+
+    /// List the plan index and each active plan as resources
+    async fn list_resources(
+        &self,
+        request: Option<PaginatedRequestParam>,
+    ) -> Result<ListResourcesResult, McpError> {
+        handlers::McpHandlers::new(self.planner.clone())
+            .list_resources(request)
+            .await
+    }
+
+    /// Read the plan index or a plan by its resource URI
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+    ) -> Result<ReadResourceResult, McpError> {
+        handlers::McpHandlers::new(self.planner.clone())
+            .read_resource(request)
+            .await
+    }
 }
 
 #[tool_handler(router = self.tool_router)]
@@ -208,6 +591,7 @@ impl ServerHandler for BeaconMcpServer {
             capabilities: ServerCapabilities::builder()
                 .enable_tools()
                 .enable_prompts()
+                .enable_resources()
                 .build(),
             server_info: Implementation {
                 name: "beacon".to_string(),
@@ -217,6 +601,7 @@ impl ServerHandler for BeaconMcpServer {
 
 ## Core Concepts
 - **Plans**: High-level projects or goals with title, description, and optional working directory
+- **Child plans**: Plans can be grouped one level deep under a parent plan, one child per workstream; parents show rolled-up progress
 - **Steps**: Individual tasks within a plan, each with status (todo/inprogress/done), descriptions, and acceptance criteria
 
 ## Workflow Examples
@@ -228,23 +613,37 @@ impl ServerHandler for BeaconMcpServer {
 
 ### Tracking Progress
 1. Use `list_plans` to see all active projects
-2. Claim steps with `claim_step` to mark them as in progress (prevents conflicts when multiple agents work on the same plan)
+2. Find the step to work on with `next_step`, then claim it with `claim_step` to mark it as in progress (prevents conflicts when multiple agents work on the same plan), or with `claim_and_show` to claim a step and get its details in one call. `claim_next_step` finds and claims the next step in one atomic call
 3. Update step status with `update_step` as work progresses (todo → inprogress → done)
-4. Archive finished plans with `archive_plan` to keep workspace organized
+4. Archive finished plans with `archive_plan` to keep workspace organized, or start a recurring plan over with `reset_plan_steps`
 
 ### Managing Multiple Projects
 - Use directories to organize plans by project location
 - Search plans by directory with `search_plans`
+- Move plans to a repository's new location with `reassign_directory`
 - View archived plans with `list_plans` (archived=true) for reference
 
 ## Best Practices
 - Create clear, actionable step titles
 - Use acceptance criteria to define 'done' for complex steps
-- Add references (URLs, files) to steps for quick access to resources
+- Add references (URLs, files) to steps for quick access to resources; give one a label with {"url": ..., "label": ...}
 
 ## Tool Categories
-- **Plan Management**: create_plan, list_plans, show_plan, archive_plan, unarchive_plan, delete_plan, search_plans
-- **Step Management**: add_step, insert_step, update_step, show_step, claim_step, swap_steps
+- **Plan Management**: create_plan, list_plans, show_plan, update_plan, archive_plan, archive_completed_plans, unarchive_plan, delete_plan, clone_plan, append_plan_note, merge_plans, search_plans, reassign_directory, reset_plan_steps, handoff_summary, plan_outline, plan_changelog, plan_report, duplicate_steps_report, list_events, get_stats
+- **Configuration**: get_config, set_config, database_info
+- **Step Management**: add_step, insert_step, apply_template, list_steps, find_steps, list_steps_by_status, update_step, show_step, claim_step, claim_and_show, release_step, swap_steps, move_step, move_step_to_plan, add_step_dependency, remove_step_dependency, add_step_note, set_step_phase, verify_step, allowed_transitions
+
+## Resources
+- `beacon://open/plans`: summaries of all active plans
+- `beacon://open/plan/{id}`: an active plan with all its steps, e.g. `beacon://open/plan/42`; archived plans are not resources
+
+## Errors
+Failed calls return an MCP error whose data says what went wrong: `kind` is one of not_found, invalid_input, plan_not_empty, conflict, read_only, busy, timeout, unavailable or internal, and `retryable` says whether the same call may succeed later.
+- **not_found** (resource not found): `entity` ('plan', 'step' or 'template') and `id` name what is missing; create it or look up the right ID, following `suggested_tool` when given
+- **invalid_input** (invalid params): `field` names the parameter to correct
+- **busy** and **timeout** (internal error, retryable): another process held the database; retry the call
+- **unavailable** (internal error, retryable): the database file was moved or deleted; retry once it is restored
+- Any other internal error will not go away by retrying
 
 ## Concurrency Support
 The `claim_step` tool provides atomic step claiming, ensuring that multiple agents or LLMs can safely work on the same plan without conflicts. When a step is claimed, it transitions from 'todo' to 'inprogress' status, preventing other agents from claiming the same step."#.to_string()),
@@ -266,6 +665,22 @@ The `claim_step` tool provides atomic step claiming, ensuring that multiple agen
     ) -> Result<GetPromptResult, McpError> {
         self.get_prompt(request, context).await
     }
+
+    async fn list_resources(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        self.list_resources(request).await
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        self.read_resource(request).await
+    }
 }
 
 /// Run the MCP server with stdio transport
@@ -304,8 +719,3 @@ pub async fn run_stdio_server(server: BeaconMcpServer) -> Result<()> {
     info!("MCP server shutdown complete");
     Ok(())
 }
-
-/// Helper to convert planner errors to MCP errors
-pub fn to_mcp_error(message: &str, error: &PlannerError) -> ErrorData {
-    ErrorData::internal_error(format!("{}: {}", message, error), None)
-}
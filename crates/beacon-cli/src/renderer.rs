@@ -2,8 +2,106 @@
 //!
 //! This module provides terminal rendering capabilities using termimad
 //! for rich markdown display with optional fallback to plain text.
+//! Rich output also draws progress bars under plan headers and marks
+//! in-progress steps in checklists; plain output is the markdown as is.
+//!
+//! Commands return an [`Output`] holding the display wrappers they show,
+//! which the renderer formats once the command is done.
+
+use std::fmt::{self, Display};
+
+use beacon_core::{PlanSummary, display::StepsMode};
+use termimad::{
+    MadSkin,
+    crossterm::{style::Color, terminal},
+};
+
+/// Cells in a progress bar when the terminal width is unknown.
+const DEFAULT_BAR_WIDTH: usize = 20;
+/// Fewest cells in a progress bar on a narrow terminal.
+const MIN_BAR_WIDTH: usize = 10;
+/// Most cells in a progress bar on a wide terminal.
+const MAX_BAR_WIDTH: usize = 40;
+
+/// A progress bar shown under the header line of the plan it belongs to.
+pub struct PlanProgress {
+    header: String,
+    ratio: f64,
+    completed: u32,
+    total: u32,
+}
+
+impl PlanProgress {
+    /// Progress of `summary`, shown under the first header line that is
+    /// `header` or starts with it followed by a space.
+    pub fn new(header: impl Into<String>, summary: &PlanSummary) -> Self {
+        Self {
+            header: header.into(),
+            ratio: summary.progress_ratio(),
+            completed: summary.completed_steps,
+            total: summary.total_steps,
+        }
+    }
+
+    fn heads(&self, line: &str) -> bool {
+        line.strip_prefix(self.header.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+    }
+}
+
+/// What a command shows, rendered in a single call once it is done.
+///
+/// The parts are shown one after another. They are kept as the display
+/// values they are, owning their data, rather than formatted into a string
+/// up front, so the renderer decides how and when they are written.
+#[derive(Default)]
+pub struct Output {
+    parts: Vec<Box<dyn Display + Send>>,
+    progress: Vec<PlanProgress>,
+}
+
+impl Output {
+    /// Output showing `part`.
+    pub fn new(part: impl Display + Send + 'static) -> Self {
+        Self::default().then(part)
+    }
+
+    /// Output showing what `fmt` writes, for views that borrow what they
+    /// show and are only built when the output is rendered.
+    pub fn from_fn<F>(fmt: F) -> Self
+    where
+        F: Fn(&mut fmt::Formatter<'_>) -> fmt::Result + Send + 'static,
+    {
+        Self::new(FromFn(fmt))
+    }
+
+    /// Shows `part` after what is shown so far.
+    pub fn then(mut self, part: impl Display + Send + 'static) -> Self {
+        self.parts.push(Box::new(part));
+        self
+    }
+
+    /// Draws the progress bars of the plans shown under their headers.
+    pub fn with_progress(mut self, progress: Vec<PlanProgress>) -> Self {
+        self.progress = progress;
+        self
+    }
+}
+
+impl Display for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.parts.iter().try_for_each(|part| write!(f, "{part}"))
+    }
+}
 
-use termimad::{MadSkin, crossterm::style::Color};
+/// Displays what its function writes.
+struct FromFn<F>(F);
+
+impl<F: Fn(&mut fmt::Formatter<'_>) -> fmt::Result> Display for FromFn<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (self.0)(f)
+    }
+}
 
 /// Terminal renderer that can switch between rich and plain text output
 pub struct TerminalRenderer {
@@ -30,26 +128,75 @@ impl TerminalRenderer {
     }
 
     /// Render markdown text to terminal
-    pub fn render(&self, markdown: impl std::fmt::Display) {
+    pub fn render(&self, markdown: impl Display) {
+        self.render_with_progress(markdown, Vec::new());
+    }
+
+    /// Render what a command shows, with the progress bars it carries.
+    pub fn render_output(&self, mut output: Output) {
+        let progress = std::mem::take(&mut output.progress);
+        self.render_with_progress(output, progress);
+    }
+
+    /// Render markdown text to terminal like [`render`](Self::render),
+    /// drawing each plan's progress bar under its header when rich.
+    pub fn render_with_progress(&self, markdown: impl Display, mut progress: Vec<PlanProgress>) {
         let markdown = markdown.to_string();
-        if self.rich_enabled {
-            // Process line by line to show hash symbols for headers
-            for line in markdown.lines() {
-                if line.starts_with('#') {
-                    print!("\x1b[34m{line}\x1b[0m");
-                    println!();
-                } else {
-                    // For non-header lines, use regular rendering
-                    self.skin.print_inline(line);
-                    println!();
+        if !self.rich_enabled {
+            print!("{}", markdown);
+            return;
+        }
+
+        let width = bar_width(terminal::size().ok().map(|(columns, _)| columns));
+        // Process line by line to show hash symbols for headers
+        for line in markdown.lines() {
+            if line.starts_with('#') {
+                print!("\x1b[34m{line}\x1b[0m");
+                println!();
+                if let Some(index) = progress.iter().position(|plan| plan.heads(line)) {
+                    let plan = progress.remove(index);
+                    println!(
+                        "{}",
+                        progress_bar(plan.ratio, plan.completed, plan.total, width)
+                    );
                 }
+            } else {
+                // For non-header lines, use regular rendering
+                self.skin.print_inline(line);
+                println!();
             }
+        }
+    }
+
+    /// How compact step listings are shown: rich output also marks
+    /// in-progress steps, plain output sticks to markdown task lists.
+    pub fn checklist_mode(&self) -> StepsMode {
+        if self.rich_enabled {
+            StepsMode::Checklist
         } else {
-            print!("{}", markdown);
+            StepsMode::Compact
         }
     }
 }
 
+/// Cells in a progress bar for a terminal `columns` wide, if known.
+fn bar_width(columns: Option<u16>) -> usize {
+    columns.map_or(DEFAULT_BAR_WIDTH, |columns| {
+        (usize::from(columns) / 4).clamp(MIN_BAR_WIDTH, MAX_BAR_WIDTH)
+    })
+}
+
+/// Draws `ratio` as a bar `width` cells wide, rounded to the nearest
+/// cell, followed by the step counts, as in `[█████-----] 5/10`.
+fn progress_bar(ratio: f64, completed: u32, total: u32, width: usize) -> String {
+    let filled = ((ratio.clamp(0.0, 1.0) * width as f64).round() as usize).min(width);
+    format!(
+        "[{}{}] {completed}/{total}",
+        "█".repeat(filled),
+        "-".repeat(width - filled)
+    )
+}
+
 impl Default for TerminalRenderer {
     fn default() -> Self {
         Self::new(true)
@@ -77,4 +224,77 @@ mod tests {
         let renderer = TerminalRenderer::default();
         assert!(renderer.rich_enabled);
     }
+
+    #[test]
+    fn test_checklist_mode() {
+        assert_eq!(
+            TerminalRenderer::new(true).checklist_mode(),
+            StepsMode::Checklist
+        );
+        assert_eq!(
+            TerminalRenderer::new(false).checklist_mode(),
+            StepsMode::Compact
+        );
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        assert_eq!(progress_bar(0.5, 5, 10, 10), "[█████-----] 5/10");
+        assert_eq!(progress_bar(0.0, 0, 4, 10), "[----------] 0/4");
+        assert_eq!(progress_bar(1.0, 4, 4, 5), "[█████] 4/4");
+        // No steps draws an empty bar
+        assert_eq!(progress_bar(0.0, 0, 0, 5), "[-----] 0/0");
+    }
+
+    #[test]
+    fn test_progress_bar_rounds_to_nearest_cell() {
+        // 3.33 cells round down, 6.67 round up
+        assert_eq!(progress_bar(1.0 / 3.0, 1, 3, 10), "[███-------] 1/3");
+        assert_eq!(progress_bar(2.0 / 3.0, 2, 3, 10), "[███████---] 2/3");
+        // 0.5 of a cell rounds up, so any progress shows on a short bar
+        assert_eq!(progress_bar(1.0 / 20.0, 1, 20, 10), "[█---------] 1/20");
+        assert_eq!(progress_bar(1.0 / 30.0, 1, 30, 10), "[----------] 1/30");
+        // A bar is never longer than its width
+        assert_eq!(progress_bar(1.5, 3, 2, 4), "[████] 3/2");
+    }
+
+    #[test]
+    fn test_bar_width() {
+        assert_eq!(bar_width(None), DEFAULT_BAR_WIDTH);
+        assert_eq!(bar_width(Some(80)), 20);
+        assert_eq!(bar_width(Some(20)), MIN_BAR_WIDTH);
+        assert_eq!(bar_width(Some(400)), MAX_BAR_WIDTH);
+    }
+
+    #[test]
+    fn test_output_shows_its_parts_in_order() {
+        let steps = ["Design", "Build"];
+        let output = Output::new("# Steps\n\n")
+            .then(1)
+            .then(Output::from_fn(move |f| {
+                writeln!(f, ". {}", steps.join(", "))
+            }));
+        assert_eq!(output.to_string(), "# Steps\n\n1. Design, Build\n");
+        assert_eq!(Output::default().to_string(), "");
+    }
+
+    #[test]
+    fn test_output_is_send() {
+        fn assert_send<T: Send + 'static>(_: &T) {}
+        assert_send(&Output::new(String::new()));
+    }
+
+    #[test]
+    fn test_plan_progress_heads_only_its_header() {
+        let plan = PlanProgress {
+            header: "## Title (ID: 1)".to_string(),
+            ratio: 0.0,
+            completed: 0,
+            total: 0,
+        };
+        assert!(plan.heads("## Title (ID: 1)"));
+        assert!(plan.heads("## Title (ID: 1) (2/3)"));
+        assert!(!plan.heads("## Title (ID: 12)"));
+        assert!(!plan.heads("### Title (ID: 1)"));
+    }
 }
@@ -7,10 +7,15 @@ fn create_cli_test_environment() -> TempDir {
     TempDir::new().expect("Failed to create temporary directory")
 }
 
-/// Helper function to create a Command with --no-color flag for testing
+/// Helper function to create a Command with --no-color flag for testing,
+/// ignoring the user's configuration file
 fn beacon_cmd() -> Command {
     let mut cmd = Command::cargo_bin("b").expect("Failed to find b binary");
-    cmd.arg("--no-color");
+    cmd.env(
+        "XDG_CONFIG_HOME",
+        std::env::temp_dir().join("beacon-no-config"),
+    )
+    .arg("--no-color");
     cmd
 }
 
@@ -87,6 +92,166 @@ fn test_cli_list_plans_text_format() {
         .stdout(predicate::str::contains("List Title"));
 }
 
+#[test]
+fn test_cli_list_plans_pages() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+
+    for title in ["Oldest Plan", "Middle Plan", "Newest Plan"] {
+        beacon_cmd()
+            .args(["--database-file", db_arg, "plan", "create", title])
+            .assert()
+            .success();
+    }
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "plan",
+            "list",
+            "--limit",
+            "1",
+            "--offset",
+            "1",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Middle Plan"))
+        .stdout(predicate::str::contains("Newest Plan").not())
+        .stdout(predicate::str::contains(
+            "Showing 1 of 3 plans, skipping the first 1.",
+        ));
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "list", "--offset", "10"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No plans found."))
+        .stdout(predicate::str::contains("Showing 0 of 3 plans"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "--explain",
+            "plan",
+            "list",
+            "--limit",
+            "2",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "- **Pagination**: at most 2 plans from offset 0",
+        ))
+        .stdout(predicate::str::contains("Showing 2 of 3 plans."));
+}
+
+#[test]
+fn test_cli_show_plan_compact() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "create", "Compact Plan"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "add",
+            "1",
+            "First step",
+            "--description",
+            "Detailed notes",
+        ])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "show", "1", "--compact"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- [ ] 1. First step (○ Todo)"))
+        .stdout(predicate::str::contains("Detailed notes").not());
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1. First step (○ Todo)"))
+        .stdout(predicate::str::contains("Detailed notes"));
+}
+
+#[test]
+fn test_cli_step_next() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "create", "Next Plan"])
+        .assert()
+        .success();
+    for title in ["First step", "Second step"] {
+        beacon_cmd()
+            .args(["--database-file", db_arg, "step", "add", "1", title])
+            .assert()
+            .success();
+    }
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "update",
+            "1",
+            "--status",
+            "done",
+            "--result",
+            "Done first",
+        ])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "step", "next", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2. Second step"))
+        .stdout(predicate::str::contains("First step").not());
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "update",
+            "2",
+            "--status",
+            "done",
+            "--result",
+            "Done second",
+        ])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db_arg, "step", "next", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Plan 1 appears complete"));
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "step", "next", "99"])
+        .assert()
+        .failure();
+}
+
 #[test]
 fn test_cli_show_plan() {
     let temp_dir = create_cli_test_environment();
@@ -209,6 +374,48 @@ fn test_cli_update_step_status_to_done() {
         .stdout(predicate::str::contains("Updated step"));
 }
 
+#[test]
+fn test_cli_reopen_and_recomplete_step() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+    let run = |args: &[&str]| {
+        beacon_cmd()
+            .args(["--database-file", db])
+            .args(args)
+            .assert()
+    };
+
+    run(&["plan", "create", "Reopen Plan"]).success();
+    run(&["step", "add", "1", "Reopened"]).success();
+    run(&[
+        "step",
+        "update",
+        "1",
+        "--status",
+        "done",
+        "--result",
+        "First report",
+    ])
+    .success();
+
+    // Reopening keeps the result, and re-completing reuses it
+    run(&["step", "update", "1", "--status", "todo"]).success();
+    run(&["step", "show", "1"])
+        .success()
+        .stdout(predicate::str::contains("First report").not());
+    run(&["step", "update", "1", "--status", "done"]).success();
+    run(&["step", "show", "1"])
+        .success()
+        .stdout(predicate::str::contains("First report"));
+
+    // Clearing the result on reopening requires a new one
+    run(&["step", "update", "1", "--status", "todo", "--clear-result"]).success();
+    run(&["step", "update", "1", "--status", "done"])
+        .failure()
+        .stderr(predicate::str::contains("Result description is required"));
+}
+
 #[test]
 fn test_cli_update_step_status_to_in_progress() {
     let temp_dir = create_cli_test_environment();
@@ -314,7 +521,9 @@ fn test_cli_update_step_details() {
             "--description",
             "Updated Step",
             "--references",
-            "ref1.txt,ref2.md",
+            "ref1.txt",
+            "--references",
+            "ref2.md",
         ])
         .assert()
         .success()
@@ -363,6 +572,237 @@ fn test_cli_archive_plan() {
         .stdout(predicate::str::contains("Archive Title"));
 }
 
+#[test]
+fn test_cli_archive_plan_with_pending_steps() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Unfinished"])
+        .assert()
+        .success();
+    for title in ["Write code", "Write docs"] {
+        beacon_cmd()
+            .args(["--database-file", db, "step", "add", "1", title])
+            .assert()
+            .success();
+    }
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "step",
+            "update",
+            "1",
+            "--status",
+            "inprogress",
+        ])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "archive", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "still has 1 step(s) in progress and 1 to do",
+        ));
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "archive", "1", "--force"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Archived plan 'Unfinished'"))
+        .stdout(predicate::str::contains("with 2 step(s) not done"))
+        .stdout(predicate::str::contains("- Write code (inprogress)"))
+        .stdout(predicate::str::contains("- Write docs (todo)"));
+}
+
+#[test]
+fn test_cli_block_step() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+    let run = |args: &[&str]| {
+        beacon_cmd()
+            .args(["--database-file", db])
+            .args(args)
+            .assert()
+    };
+
+    run(&["plan", "create", "Blocked Plan"]).success();
+    run(&["step", "add", "1", "Deploy"]).success();
+    run(&["step", "update", "1", "--blocked-reason", "Waiting on keys"])
+        .failure()
+        .stderr(predicate::str::contains("only be given for a blocked step"));
+    run(&[
+        "step",
+        "update",
+        "1",
+        "--status",
+        "blocked",
+        "--blocked-reason",
+        "Waiting on keys",
+    ])
+    .success();
+    run(&["step", "show", "1"])
+        .success()
+        .stdout(predicate::str::contains("⛔ Blocked"))
+        .stdout(predicate::str::contains("- Blocked: Waiting on keys"));
+
+    // A blocked step's reason changes without blocking it again
+    run(&["step", "update", "1", "--status", "blocked"])
+        .failure()
+        .stderr(predicate::str::contains("Step 1 is already blocked"));
+    run(&[
+        "step",
+        "update",
+        "1",
+        "--blocked-reason",
+        "Waiting on review",
+    ])
+    .success();
+    run(&["step", "show", "1"])
+        .success()
+        .stdout(predicate::str::contains("- Blocked: Waiting on review"));
+
+    // Unblocking drops the reason
+    run(&["step", "update", "1", "--status", "todo"]).success();
+    run(&["step", "show", "1"])
+        .success()
+        .stdout(predicate::str::contains("Waiting on review").not());
+}
+
+#[test]
+fn test_cli_plan_list_completion() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    for title in ["Empty Plan", "Open Plan"] {
+        beacon_cmd()
+            .args(["--database-file", db, "plan", "create", title])
+            .assert()
+            .success();
+    }
+    beacon_cmd()
+        .args(["--database-file", db, "step", "add", "2", "Open step"])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "list",
+            "--completion",
+            "incomplete",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Open Plan"))
+        .stdout(predicate::str::contains("Empty Plan").not());
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "list",
+            "--archived",
+            "--completion",
+            "empty",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("# Archived Plans"))
+        .stdout(predicate::str::contains("Empty Plan").not());
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "list",
+            "--completion",
+            "done",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "possible values: complete, incomplete, empty",
+        ));
+}
+
+#[test]
+fn test_cli_plan_due_dates() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "create",
+            "Past Plan",
+            "--due",
+            "2000-01-01",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- Due: 2000-01-01 (overdue)"));
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Undated Plan"])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "list",
+            "--due-before",
+            "2000-01-31",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Past Plan"))
+        .stdout(predicate::str::contains("- **Due**: 2000-01-01 (overdue)"))
+        .stdout(predicate::str::contains("Undated Plan").not());
+
+    // Archived plans are never overdue
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "archive", "1"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- Due: 2000-01-01\n"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "update",
+            "2",
+            "--due",
+            "soon",
+        ])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "Invalid input for field 'due_date'",
+        ));
+}
+
 #[test]
 fn test_cli_unarchive_plan() {
     let temp_dir = create_cli_test_environment();
@@ -576,7 +1016,8 @@ fn test_cli_invalid_plan_id() {
             "99999",
         ])
         .assert()
-        .failure();
+        .code(2)
+        .stderr(predicate::str::contains("Plan with ID 99999 not found"));
 }
 
 #[test]
@@ -593,28 +1034,131 @@ fn test_cli_invalid_step_id() {
             "99999",
         ])
         .assert()
-        .failure();
+        .code(2)
+        .stderr(predicate::str::contains("Step with ID 99999 not found"));
 }
 
 #[test]
-fn test_cli_add_step_with_all_fields() {
+fn test_cli_not_found_hints_at_other_kind_of_item() {
     let temp_dir = create_cli_test_environment();
     let db_path = temp_dir.path().join("cli_test.db");
     let db_arg = db_path.to_str().unwrap();
 
-    // Create a plan
-    let output = beacon_cmd()
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "create", "Hints"])
+        .assert()
+        .success();
+    for title in ["First", "Second"] {
+        beacon_cmd()
+            .args(["--database-file", db_arg, "step", "add", "1", title])
+            .assert()
+            .success();
+    }
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "show", "2"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "Plan with ID 2 not found, but step 2 exists in plan 1",
+        ));
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "step", "show", "1"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_cli_explain_search_filtered_out_by_directory() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+    let project = temp_dir.path().join("project");
+    let elsewhere = temp_dir.path().join("elsewhere");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::create_dir_all(&elsewhere).unwrap();
+
+    for title in ["One", "Two"] {
+        beacon_cmd()
+            .args([
+                "--database-file",
+                db_arg,
+                "plan",
+                "create",
+                title,
+                "--directory",
+                project.to_str().unwrap(),
+            ])
+            .assert()
+            .success();
+    }
+
+    // Both plans are active, but none is in the searched directory
+    let canonical = elsewhere.canonicalize().unwrap();
+    beacon_cmd()
         .args([
             "--database-file",
             db_arg,
+            "--explain",
             "plan",
-            "create",
-            "Full Step Title",
+            "search",
+            elsewhere.to_str().unwrap(),
         ])
         .assert()
         .success()
-        .get_output()
-        .stdout
+        .stdout(predicate::str::contains("Explanation").not())
+        .stderr(predicate::str::contains(format!(
+            "- **Database**: `{}`",
+            db_path.display()
+        )))
+        .stderr(predicate::str::contains(format!(
+            "- **Directories**: `{}` (and below)",
+            canonical.display()
+        )))
+        .stderr(predicate::str::contains(
+            "- **Order**: created_at DESC, id DESC",
+        ))
+        .stderr(predicate::str::contains(
+            "- status: 2\n- directory: 0\n- title, dates and parent: 0\n- completion: 0",
+        ));
+}
+
+#[test]
+fn test_cli_unusable_database_exits_with_failure() {
+    let temp_dir = create_cli_test_environment();
+
+    // A directory cannot be opened as a database; this is not recoverable
+    beacon_cmd()
+        .args([
+            "--database-file",
+            temp_dir.path().to_str().unwrap(),
+            "plan",
+            "list",
+        ])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_cli_add_step_with_all_fields() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+
+    // Create a plan
+    let output = beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "plan",
+            "create",
+            "Full Step Title",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
         .clone();
 
     let output_str = String::from_utf8(output).expect("Invalid UTF-8");
@@ -633,14 +1177,222 @@ fn test_cli_add_step_with_all_fields() {
             "A complete step with all fields",
             "--acceptance-criteria",
             "Should have all fields populated",
-            "--references",
-            "doc1.md,https://example.com",
+            "-r",
+            "doc1.md",
+            "-r",
+            "https://example.com",
         ])
         .assert()
         .success()
         .stdout(predicate::str::contains("Created step with ID:"));
 }
 
+#[test]
+fn test_cli_add_step_quick_syntax() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+
+    let output = beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "create", "Quick Add"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let plan_id = extract_id_from_output(&String::from_utf8(output).expect("Invalid UTF-8"));
+
+    // Tokens are detected without --quick
+    let output = beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "add",
+            &plan_id,
+            "Implement retries !crit: exponential backoff, max 5 @ref:src/client.rs \
+             @ref:https://docs/retry ~30m",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let step_id = extract_id_from_output(&String::from_utf8(output).expect("Invalid UTF-8"));
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "step", "show", &step_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Implement retries (○ Todo)"))
+        .stdout(predicate::str::contains("- Estimate: 30m"))
+        .stdout(predicate::str::contains("exponential backoff, max 5"))
+        .stdout(predicate::str::contains("- src/client.rs"))
+        .stdout(predicate::str::contains("- https://docs/retry"));
+
+    // --quick applies escapes; criteria can't be given both ways
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "insert",
+            &plan_id,
+            "0",
+            "Email \\@ref:team",
+            "--quick",
+        ])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "show", &plan_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Email @ref:team"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "add",
+            &plan_id,
+            "Task !crit: inline",
+            "--acceptance-criteria",
+            "flag",
+        ])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("both inline and as a parameter"));
+}
+
+#[test]
+fn test_cli_plan_report() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Standup"])
+        .assert()
+        .success();
+    for title in ["Write code", "Review"] {
+        beacon_cmd()
+            .args(["--database-file", db, "step", "add", "1", title])
+            .assert()
+            .success();
+    }
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "report", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Standup: Standup"))
+        .stdout(predicate::str::contains("No activity since"))
+        .stdout(predicate::str::contains("Up Next"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "step",
+            "update",
+            "1",
+            "--status",
+            "done",
+            "--result",
+            "Merged the parser",
+        ])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "report",
+            "1",
+            "--since",
+            "2d",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Completed"))
+        .stdout(predicate::str::contains("Merged the parser"))
+        .stdout(predicate::str::contains("No activity").not());
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "report",
+            "1",
+            "--since",
+            "soon",
+        ])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cli_plan_changelog() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+
+    let output = beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "create", "Changelog"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let plan_id = extract_id_from_output(&String::from_utf8(output).expect("Invalid UTF-8"));
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "add",
+            &plan_id,
+            "Write code",
+        ])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "plan",
+            "changelog",
+            &plan_id,
+            "--since",
+            "1d",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("# Changelog: Changelog"))
+        .stdout(predicate::str::contains("Created step 1: Write code"))
+        .stdout(predicate::str::contains("Plan created"))
+        .stdout(predicate::str::is_match(r"## \d{4}-\d{2}-\d{2} \(\w{3}\)").unwrap());
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "plan",
+            "changelog",
+            &plan_id,
+            "--since",
+            "soon",
+        ])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Invalid input for field 'since'"));
+}
+
 #[test]
 fn test_cli_update_step_title() {
     let temp_dir = create_cli_test_environment();
@@ -690,33 +1442,2446 @@ fn test_cli_update_step_title() {
         ])
         .assert()
         .success()
-        .stdout(predicate::str::contains("Updated step"));
+        .stdout(predicate::str::contains("Updated step"))
+        .stdout(predicate::str::contains(
+            "title: 'Original Title' → 'Updated Title'",
+        ));
+
+    // Setting the title it already has changes nothing
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "update",
+            &step_id,
+            "--title",
+            "Updated Title",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing changed in step"));
 }
 
-/// Helper function to extract ID from command output
-fn extract_id_from_output(output: &str) -> String {
-    // Try new format: look for "# <number>. " pattern
-    // Skip "# Plan Created" and similar headers
-    for line in output.lines() {
-        if let Some(stripped) = line.strip_prefix("# ") {
-            let after_hash = &stripped.trim();
-            // Check if this line starts with a number followed by a dot
-            if let Some(dot_pos) = after_hash.find('.') {
-                let potential_id = &after_hash[..dot_pos];
-                if !potential_id.is_empty() && potential_id.chars().all(|c| c.is_numeric()) {
-                    return potential_id.to_string();
-                }
-            }
-        }
-    }
+#[test]
+fn test_cli_child_plans() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
 
-    // Fall back to old format: "ID: <number>"
-    if let Some(start) = output.find("ID: ") {
-        let id_str = &output[start + 4..];
-        if let Some(end) = id_str.find(|c: char| !c.is_numeric()) {
-            return id_str[..end].to_string();
-        }
-    }
+    let create = |args: &[&str]| {
+        let output = beacon_cmd()
+            .args(["--database-file", db_arg, "plan"])
+            .args(args)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        extract_id_from_output(&String::from_utf8(output).unwrap())
+    };
+
+    let parent_id = create(&["create", "Big Effort"]);
+    create(&["new", "Workstream A", "--parent", &parent_id]);
+    create(&["create", "Unrelated"]);
 
-    panic!("Could not extract ID from output: {output}");
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "plan",
+            "list",
+            "--children-of",
+            &parent_id,
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Workstream A"))
+        .stdout(predicate::str::contains("Unrelated").not());
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "show", &parent_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Child Plans"))
+        .stdout(predicate::str::contains("Workstream A"));
+
+    // Archiving the parent needs --force while the child is active
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "archive", &parent_id])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("active child plans"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "plan",
+            "archive",
+            &parent_id,
+            "--force",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("child plan(s) are still active"));
+}
+
+#[test]
+fn test_cli_metrics_week() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+
+    let output = beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "create", "Busy Week"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let plan_id = extract_id_from_output(&String::from_utf8(output).unwrap());
+
+    let output = beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "add",
+            &plan_id,
+            "Ship it",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let step_id = extract_id_from_output(&String::from_utf8(output).unwrap());
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "metrics", "--week"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("**Steps completed this week**: 0"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "update",
+            &step_id,
+            "--status",
+            "done",
+            "--result",
+            "Shipped",
+        ])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "metrics", "--week"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("**Steps completed this week**: 1"));
+
+    // A window is required
+    beacon_cmd()
+        .args(["--database-file", db_arg, "metrics"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cli_plan_show_url_and_open() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+
+    let output = beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "create", "Linked Plan"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let plan_id = extract_id_from_output(&String::from_utf8(output).unwrap());
+
+    let url_output = beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "show", &plan_id, "--url"])
+        .assert()
+        .success()
+        .stdout(format!("beacon://open/plan/{plan_id}\n"))
+        .get_output()
+        .stdout
+        .clone();
+    let url = String::from_utf8(url_output).unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "open", url.trim()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Linked Plan"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "open",
+            &format!("beacon://open/plan/{plan_id}?workspace=cli_test"),
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Linked Plan"));
+
+    // A URL from another workspace is not resolved against this database
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "open",
+            &format!("beacon://open/plan/{plan_id}?workspace=work"),
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("belongs to workspace 'work'"))
+        .stdout(predicate::str::contains("Linked Plan").not());
+}
+
+#[test]
+fn test_cli_step_show_url() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "create", "Plan"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db_arg, "step", "add", "1", "Linked Step"])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "step", "show", "1", "--url"])
+        .assert()
+        .success()
+        .stdout("beacon://open/step/1\n");
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "open", "beacon://open/step/1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Linked Step"));
+}
+
+#[test]
+fn test_cli_open_rejects_malformed_url() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_path.to_str().unwrap(),
+            "open",
+            "beacon://open/task/1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown entity 'task'"));
+}
+
+/// Helper function to extract ID from command output
+fn extract_id_from_output(output: &str) -> String {
+    // Try new format: look for "# <number>. " pattern
+    // Skip "# Plan Created" and similar headers
+    for line in output.lines() {
+        if let Some(stripped) = line.strip_prefix("# ") {
+            let after_hash = &stripped.trim();
+            // Check if this line starts with a number followed by a dot
+            if let Some(dot_pos) = after_hash.find('.') {
+                let potential_id = &after_hash[..dot_pos];
+                if !potential_id.is_empty() && potential_id.chars().all(|c| c.is_numeric()) {
+                    return potential_id.to_string();
+                }
+            }
+        }
+    }
+
+    // Fall back to old format: "ID: <number>"
+    if let Some(start) = output.find("ID: ") {
+        let id_str = &output[start + 4..];
+        if let Some(end) = id_str.find(|c: char| !c.is_numeric()) {
+            return id_str[..end].to_string();
+        }
+    }
+
+    panic!("Could not extract ID from output: {output}");
+}
+
+/// Writes a fake `git` into `bin_dir` that reports a repository named
+/// `my-repo` and prints three commits newest first, one of them a merge that
+/// is dropped when `--no-merges` is passed.
+#[cfg(unix)]
+fn install_fake_git(bin_dir: &std::path::Path) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let script = r#"#!/bin/sh
+for arg in "$@"; do
+    case "$arg" in
+        rev-parse) echo "/home/user/my-repo"; exit 0 ;;
+        --no-merges) no_merges=1 ;;
+    esac
+done
+printf 'ccc333\037Add feature C\037Body of C\n\036\n'
+if [ -z "$no_merges" ]; then
+    printf 'mmm000\037Merge branch topic\037\036\n'
+fi
+printf 'bbb222\037Add feature B\037\036\n'
+printf 'aaa111\037Add feature A\037Body of A\n\036\n'
+"#;
+    let path = bin_dir.join("git");
+    std::fs::write(&path, script).expect("Failed to write fake git");
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+        .expect("Failed to make fake git executable");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_create_plan_from_git_log() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+    install_fake_git(&bin_dir);
+
+    let output = beacon_cmd()
+        .env("PATH", &bin_dir)
+        .args([
+            "--database-file",
+            db_arg,
+            "plan",
+            "new",
+            "--from-git-log",
+            "--since",
+            "2w",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("# 1. my-repo"))
+        .stdout(predicate::str::contains("Merge branch topic").not())
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+
+    // Oldest commit first, each step done with the commit as reference
+    let a = output
+        .find("### 1. Add feature A (✓ Done)")
+        .expect("step A");
+    let b = output
+        .find("### 2. Add feature B (✓ Done)")
+        .expect("step B");
+    let c = output
+        .find("### 3. Add feature C (✓ Done)")
+        .expect("step C");
+    assert!(a < b && b < c);
+    assert!(output.contains("Body of A"));
+    assert!(output.contains("- aaa111"));
+    assert!(output.contains("imported from commit aaa111"));
+
+    // Merge commits are kept on request
+    beacon_cmd()
+        .env("PATH", &bin_dir)
+        .args([
+            "--database-file",
+            db_arg,
+            "plan",
+            "new",
+            "History",
+            "--from-git-log",
+            "--include-merges",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("# 2. History"))
+        .stdout(predicate::str::contains("Merge branch topic"));
+}
+
+#[test]
+fn test_cli_create_plan_from_git_log_without_git() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+
+    beacon_cmd()
+        .env("PATH", temp_dir.path())
+        .args([
+            "--database-file",
+            db_path.to_str().unwrap(),
+            "plan",
+            "new",
+            "--from-git-log",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("git is not installed"));
+
+    // Nothing was created
+    beacon_cmd()
+        .args(["--database-file", db_path.to_str().unwrap(), "plan", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("# 1.").not());
+}
+
+#[test]
+fn test_cli_where_prints_database_path() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "where"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("`{db_arg}`")))
+        .stdout(predicate::str::contains("**Exists**: no"));
+    assert!(!db_path.exists());
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "create", "Some Plan"])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "db-path"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("`{db_arg}`")))
+        .stdout(predicate::str::contains("**Exists**: yes"));
+}
+
+#[test]
+fn test_cli_in_memory_database() {
+    let temp_dir = create_cli_test_environment();
+    let run = |args: &[&str]| {
+        beacon_cmd()
+            .current_dir(temp_dir.path())
+            .args(["--database-file", ":memory:"])
+            .args(args)
+            .assert()
+            .success()
+    };
+
+    run(&["where"]).stdout(predicate::str::contains("`:memory:`"));
+    run(&["plan", "create", "Throwaway"]).stdout(predicate::str::contains("Throwaway"));
+
+    // Each session starts empty and leaves no file behind
+    run(&[]).stdout(predicate::str::contains("No plans found."));
+    run(&["plan", "list"]).stdout(predicate::str::contains("Throwaway").not());
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_cli_plan_outline() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "create", "Outlined"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "add",
+            "1",
+            "Write code",
+            "--description",
+            "A long description that the outline leaves out",
+        ])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "outline", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("# Outline of Plan 1"))
+        .stdout(predicate::str::contains("(0/1 done)"))
+        .stdout(predicate::str::contains("- 1. Write code (○ Todo)"))
+        .stdout(predicate::str::contains("long description").not());
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "o", "42"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not found"));
+}
+
+#[test]
+fn test_cli_plan_status_labels() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "create", "Labelled"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db_arg, "step", "add", "1", "Write code"])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "plan",
+            "update",
+            "1",
+            "--status-label",
+            "todo=Backlog",
+            "--status-label",
+            "in_progress=Doing",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("status labels"));
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "step", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("○ Backlog"));
+
+    // Labels are display-only, so statuses are still set by canonical name
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "update",
+            "1",
+            "--status",
+            "in-progress",
+        ])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("➤ Doing"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "plan",
+            "update",
+            "1",
+            "--status-label",
+            "doing=Busy",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown status 'doing'"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "plan",
+            "update",
+            "1",
+            "--clear-status-labels",
+        ])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db_arg, "step", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("➤ In Progress"));
+}
+
+#[test]
+fn test_cli_plan_update_directory() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+    let project = temp_dir.path().join("project");
+    std::fs::create_dir(&project).unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "create", "Mispalced"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db_arg, "step", "add", "1", "Keep me"])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "plan",
+            "update",
+            "1",
+            "--title",
+            "Misplaced",
+            "--directory",
+            project.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- directory"));
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Misplaced"))
+        .stdout(predicate::str::contains(project.to_str().unwrap()))
+        .stdout(predicate::str::contains("Keep me"));
+}
+
+#[test]
+fn test_cli_step_phase() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "create", "Phased"])
+        .assert()
+        .success();
+    for title in ["Sketch API", "Write code"] {
+        beacon_cmd()
+            .args(["--database-file", db_arg, "step", "add", "1", title])
+            .assert()
+            .success();
+    }
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "step", "phase", "1", "Design"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Moved step 1 to phase 'Design'"));
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "phase",
+            "2",
+            "Implementation",
+        ])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("## Phase: Design"))
+        .stdout(predicate::str::contains("## Phase: Implementation"));
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "step", "phase", "1", "--clear"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed step 1 from its phase"));
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "step", "phase", "1"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cli_step_verification() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db_arg = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "create", "Migration"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "add",
+            "1",
+            "Migrate production",
+            "--require-verification",
+        ])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "update",
+            "1",
+            "--status",
+            "done",
+            "--result",
+            "Migrated",
+        ])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db_arg, "plan", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("✓ Done (awaiting verification)"))
+        .stdout(predicate::str::contains("Awaiting verification: steps 1"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "verify",
+            "1",
+            "--by",
+            "reviewer",
+            "-m",
+            "Row counts match",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Verified by reviewer"))
+        .stdout(predicate::str::contains("Row counts match"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_arg,
+            "step",
+            "verify",
+            "1",
+            "--by",
+            "again",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already verified by reviewer"));
+}
+
+#[test]
+fn test_cli_bench_miniature_run() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_path.to_str().unwrap(),
+            "bench",
+            "--plans",
+            "3",
+            "--steps",
+            "2",
+            "--samples",
+            "1",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("3 plans × 2 steps"))
+        .stdout(predicate::str::contains("100 status updates"))
+        .stdout(predicate::str::contains("claim loop"))
+        .stdout(predicate::str::contains(r#""sqlite_version":"#))
+        .stdout(predicate::str::contains(r#""operation":"list summaries""#));
+
+    // The benchmark runs on a throwaway database, never the given one
+    assert!(!db_path.exists());
+}
+
+#[test]
+fn test_cli_streak_and_momentum_opt_out() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Momentum Plan"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db, "step", "add", "1", "Finish it"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "step",
+            "update",
+            "1",
+            "--status",
+            "done",
+            "--result",
+            "Finished",
+        ])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db, "streak"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1-day streak"))
+        .stdout(predicate::str::contains("Momentum Plan (ID: 1): ▲ 1 vs 0"));
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1-day streak"))
+        .stdout(predicate::str::contains("- **Momentum**: ▲ 1 vs 0"));
+
+    beacon_cmd()
+        .args(["--database-file", db, "--no-momentum", "plan", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Momentum Plan"))
+        .stdout(predicate::str::contains("Momentum**").not())
+        .stdout(predicate::str::contains("streak").not());
+
+    beacon_cmd()
+        .env("BEACON_NO_MOMENTUM", "1")
+        .args(["--database-file", db, "plan", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("streak").not());
+}
+
+#[test]
+fn test_cli_plan_merge_requires_confirmation() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    for (title, step) in [
+        ("Target Plan", "Target step"),
+        ("Source Plan", "Source step"),
+    ] {
+        beacon_cmd()
+            .args(["--database-file", db, "plan", "create", title])
+            .assert()
+            .success();
+        let plan_id = if title == "Target Plan" { "1" } else { "2" };
+        beacon_cmd()
+            .args(["--database-file", db, "step", "add", plan_id, step])
+            .assert()
+            .success();
+    }
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "merge", "2", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("confirmation"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "merge",
+            "2",
+            "1",
+            "--prefix-titles",
+            "--confirm",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Merged plan 2 into 'Target Plan' (ID: 1)",
+        ));
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Source Plan: Source step"));
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "show", "2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- Merged into: plan 1"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "merge",
+            "2",
+            "1",
+            "--confirm",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already merged into plan 1"));
+}
+
+#[test]
+fn test_cli_plan_clone() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Release checklist"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db, "step", "add", "1", "Tag release"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "step",
+            "update",
+            "1",
+            "--status",
+            "done",
+            "--result",
+            "Tagged v1.0",
+        ])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "clone",
+            "1",
+            "--title",
+            "Sprint 12 release",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created plan with ID: 2"))
+        .stdout(predicate::str::contains("Sprint 12 release"))
+        .stdout(predicate::str::contains("Tag release"))
+        .stdout(predicate::str::contains("Tagged v1.0").not());
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "clone", "99"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to clone plan 99"));
+}
+
+#[test]
+fn test_cli_plan_delete_requires_force_with_pending_steps() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Abandoned"])
+        .assert()
+        .success();
+    for title in ["First", "Second"] {
+        beacon_cmd()
+            .args(["--database-file", db, "step", "add", "1", title])
+            .assert()
+            .success();
+    }
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "delete", "1", "--confirm"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Failed to delete plan 1"))
+        .stderr(predicate::str::contains("2 step(s) that are not done"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "delete",
+            "1",
+            "--confirm",
+            "--force",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Permanently deleted plan 'Abandoned'",
+        ));
+}
+
+#[test]
+fn test_cli_plan_delete_dry_run_and_confirmation() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Doomed"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db, "step", "add", "1", "Unfinished"])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "delete", "1", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Would delete plan 'Doomed' (ID: 1) and its 1 step(s)",
+        ))
+        .stdout(predicate::str::contains(
+            "1 step(s) are not done, so deleting it requires --force",
+        ));
+
+    // Without a terminal to ask on, nothing is deleted unless --yes is given
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "delete", "1", "--force"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Pass --yes"));
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Doomed"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "delete",
+            "1",
+            "--force",
+            "--yes",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Permanently deleted plan 'Doomed'",
+        ));
+}
+
+#[test]
+fn test_cli_step_remove_dry_run_and_confirmation() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Cleanup"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db, "step", "add", "1", "Drop me"])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db, "step", "remove", "1", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Would move step 'Drop me' (ID: 1) of plan 1 to the trash",
+        ));
+    beacon_cmd()
+        .args(["--database-file", db, "step", "remove", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Pass --yes"));
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Drop me"));
+
+    beacon_cmd()
+        .args(["--database-file", db, "step", "remove", "1", "-y"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Moved step 1 to the trash"));
+    beacon_cmd()
+        .args(["--database-file", db, "step", "remove", "9", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Step with ID 9 not found"));
+}
+
+#[test]
+fn test_cli_plan_archive_all_completed() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    for title in ["Shipped", "Ongoing", "Empty"] {
+        beacon_cmd()
+            .args(["--database-file", db, "plan", "create", title])
+            .assert()
+            .success();
+    }
+    for plan_id in ["1", "2"] {
+        beacon_cmd()
+            .args(["--database-file", db, "step", "add", plan_id, "Work"])
+            .assert()
+            .success();
+    }
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "step",
+            "update",
+            "1",
+            "--status",
+            "done",
+            "--result",
+            "Shipped it",
+        ])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "archive", "--all-completed"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Archived 1 Completed Plan(s)"))
+        .stdout(predicate::str::contains("Shipped"))
+        .stdout(predicate::str::contains("Ongoing").not())
+        .stdout(predicate::str::contains("Empty").not());
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "archive", "--all-completed"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No completed plans to archive"));
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "archive"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cli_dedupe_report() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    for (plan_id, directory, step) in [
+        ("1", "/work/api", "Upgrade to Rust 1.80"),
+        ("2", "/work/web", "upgrade to rust 1.80."),
+        ("3", "/other", "Upgrade to Rust 1.80"),
+    ] {
+        beacon_cmd()
+            .args([
+                "--database-file",
+                db,
+                "plan",
+                "create",
+                &format!("Plan {plan_id}"),
+                "--directory",
+                directory,
+            ])
+            .assert()
+            .success();
+        beacon_cmd()
+            .args(["--database-file", db, "step", "add", plan_id, step])
+            .assert()
+            .success();
+    }
+
+    beacon_cmd()
+        .args(["--database-file", db, "dedupe-report"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Upgrade to Rust 1.80 (3 steps in 3 plans)",
+        ))
+        .stdout(predicate::str::contains(
+            "Step 2 (todo): upgrade to rust 1.80. — plan 2: Plan 2",
+        ));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "dedupe-report",
+            "--directory",
+            "/work",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(2 steps in 2 plans)"))
+        .stdout(predicate::str::contains("plan 3").not());
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "dedupe-report",
+            "--directory",
+            "/other",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No duplicate steps found."));
+}
+
+#[test]
+fn test_cli_config() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "config", "get", "auto_archive"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("auto_archive = false (default)"));
+
+    beacon_cmd()
+        .args(["--database-file", db, "config", "set", "auto_archive", "on"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Set auto_archive to true"));
+
+    // Stored settings apply to later invocations
+    beacon_cmd()
+        .args(["--database-file", db, "config", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("auto_archive = true (workspace)"))
+        .stdout(predicate::str::contains(
+            "in_progress_warning_threshold = 7d (default)",
+        ));
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Tidy up"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Auto-archive"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "config",
+            "set",
+            "max_result_length",
+            "lots",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "max_result_length must be a positive number of characters or none",
+        ));
+    beacon_cmd()
+        .args(["--database-file", db, "config", "get", "colour"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown setting 'colour'"));
+}
+
+#[test]
+fn test_cli_default_listing_matches_plan_list() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+    let stdout = |args: &[&str]| {
+        let output = beacon_cmd()
+            .args(["--database-file", db])
+            .args(args)
+            .output()
+            .expect("Failed to run b");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    // Without a database the listing falls back to creating one
+    assert!(stdout(&[]).contains("# Active Plans"));
+    assert!(db_path.exists());
+
+    stdout(&["plan", "create", "Listed Plan"]);
+    stdout(&["step", "add", "1", "First"]);
+    stdout(&["step", "add", "1", "Second"]);
+    stdout(&[
+        "step", "update", "1", "--status", "done", "--result", "Done",
+    ]);
+    stdout(&["plan", "create", "Archived Plan"]);
+    stdout(&["plan", "archive", "2"]);
+
+    let listing = stdout(&[]);
+    assert!(listing.contains("Listed Plan"));
+    assert!(listing.contains("1-day streak"));
+    assert!(!listing.contains("Archived Plan"));
+    assert_eq!(listing, stdout(&["plan", "list"]));
+    assert_eq!(
+        stdout(&["--no-momentum"]),
+        stdout(&["--no-momentum", "plan", "list"])
+    );
+}
+
+#[test]
+fn test_cli_badge() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+    let project = temp_dir.path().join("project");
+    std::fs::create_dir_all(project.join("src")).unwrap();
+    let project = project.to_str().unwrap();
+    let badge = |directory: &str| {
+        let output = beacon_cmd()
+            .env("RUST_LOG", "info")
+            .args(["--database-file", db, "badge", directory])
+            .output()
+            .expect("Failed to run b");
+        assert!(output.status.success());
+        (
+            String::from_utf8(output.stdout).unwrap(),
+            String::from_utf8(output.stderr).unwrap(),
+        )
+    };
+
+    // Without a database the badge falls back to the planner, which
+    // creates one, and shows nothing
+    let (stdout, stderr) = badge(project);
+    assert_eq!(stdout, "");
+    assert!(stderr.contains("Quick badge unavailable"));
+    assert!(db_path.exists());
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Badged"])
+        .args(["--directory", project])
+        .assert()
+        .success();
+    for title in ["First", "Second"] {
+        beacon_cmd()
+            .args(["--database-file", db, "step", "add", "1", title])
+            .assert()
+            .success();
+    }
+
+    // Plans count in their directory and those above it, read through the
+    // quick path
+    for directory in [project, temp_dir.path().to_str().unwrap()] {
+        let (stdout, stderr) = badge(directory);
+        assert_eq!(stdout, "1 plan · 2 pending\n");
+        assert!(!stderr.contains("Quick badge unavailable"));
+    }
+    assert_eq!(badge(&format!("{project}/src")).0, "");
+    assert_eq!(badge("/nowhere").0, "");
+}
+
+#[test]
+fn test_cli_step_move() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Reordered"])
+        .assert()
+        .success();
+    for title in ["First", "Second", "Third"] {
+        beacon_cmd()
+            .args(["--database-file", db, "step", "add", "1", title])
+            .assert()
+            .success();
+    }
+
+    beacon_cmd()
+        .args(["--database-file", db, "step", "move", "3", "0"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Moved step 3 to position 0"));
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "outline", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("(?s)Third.*First.*Second").unwrap());
+
+    beacon_cmd()
+        .args(["--database-file", db, "step", "mv", "3", "3"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("valid positions are 0 to 2"));
+}
+
+#[test]
+fn test_cli_step_move_to_plan() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    for (plan_id, title, steps) in [
+        ("1", "Frontend", ["Style page", "Add API route"]),
+        ("2", "Backend", ["Schema", "Auth"]),
+    ] {
+        beacon_cmd()
+            .args(["--database-file", db, "plan", "create", title])
+            .assert()
+            .success();
+        for step in steps {
+            beacon_cmd()
+                .args(["--database-file", db, "step", "add", plan_id, step])
+                .assert()
+                .success();
+        }
+    }
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "step",
+            "move-to-plan",
+            "2",
+            "2",
+            "--position",
+            "1",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Moved step 2 to plan 2 at position 1",
+        ));
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "outline", "2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("(?s)Schema.*Add API route.*Auth").unwrap());
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "outline", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Add API route").not());
+
+    beacon_cmd()
+        .args(["--database-file", db, "step", "move-to-plan", "1", "9"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("9"));
+}
+
+#[test]
+fn test_cli_step_trash_and_restore() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Cleanup"])
+        .assert()
+        .success();
+    for title in ["Keep me", "Drop me"] {
+        beacon_cmd()
+            .args(["--database-file", db, "step", "add", "1", title])
+            .assert()
+            .success();
+    }
+
+    beacon_cmd()
+        .args(["--database-file", db, "step", "remove", "2", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Moved step 2 to the trash"));
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Drop me").not());
+    beacon_cmd()
+        .args(["--database-file", db, "step", "trash", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("# Trash of Plan 1"))
+        .stdout(predicate::str::contains("2. Drop me"));
+
+    beacon_cmd()
+        .args(["--database-file", db, "step", "restore", "2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored step 2 'Drop me'"));
+    beacon_cmd()
+        .args(["--database-file", db, "step", "trash", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("The trash is empty."));
+
+    // Recently trashed steps survive the default purge
+    beacon_cmd()
+        .args(["--database-file", db, "step", "remove", "2", "--yes"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db, "step", "purge"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Permanently deleted 0 step(s)"));
+    beacon_cmd()
+        .args(["--database-file", db, "step", "restore", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Step 1 is not in the trash"));
+}
+
+#[test]
+fn test_cli_step_release() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Abandoned"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db, "step", "add", "1", "Half done"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "step",
+            "update",
+            "1",
+            "--status",
+            "in-progress",
+        ])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db, "step", "release", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Released step 1"));
+    beacon_cmd()
+        .args(["--database-file", db, "step", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Todo"));
+
+    // Only steps in progress can be released
+    beacon_cmd()
+        .args(["--database-file", db, "step", "release", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Step 1 is not in progress"));
+    beacon_cmd()
+        .args(["--database-file", db, "step", "release", "99"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_cli_db_maintenance() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Some Plan"])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db, "db", "maintenance"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Size before:"))
+        .stdout(predicate::str::contains("Size after:"))
+        .stdout(predicate::str::contains("Integrity: ok"));
+}
+
+#[test]
+fn test_cli_db_backup_and_restore() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+    let backup_path = temp_dir.path().join("backup.db");
+    let backup = backup_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Kept Plan"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db, "db", "backup", backup])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Backed up database to"));
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Lost Plan"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db, "step", "add", "2", "Underway"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "step",
+            "update",
+            "1",
+            "--status",
+            "in-progress",
+        ])
+        .assert()
+        .success();
+
+    // A step is in progress, so restoring needs confirming
+    beacon_cmd()
+        .args(["--database-file", db, "db", "restore", backup])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("in progress"));
+    beacon_cmd()
+        .args(["--database-file", db, "db", "restore", backup, "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored database from"));
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Kept Plan"))
+        .stdout(predicate::str::contains("Lost Plan").not());
+}
+
+#[test]
+fn test_cli_completions() {
+    for shell in ["bash", "zsh", "fish"] {
+        beacon_cmd()
+            .args(["completions", shell])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("b complete --"));
+    }
+}
+
+#[test]
+fn test_cli_complete_ids() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Complete me"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db, "step", "add", "1", "First step"])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["complete", "--", "--database-file", db, "plan", "show", ""])
+        .assert()
+        .success()
+        .stdout("1:Complete me\n");
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "complete",
+            "--",
+            "step",
+            "update",
+            "",
+        ])
+        .assert()
+        .success()
+        .stdout("1:First step\n");
+    beacon_cmd()
+        .args(["complete", "--", "pla"])
+        .assert()
+        .success()
+        .stdout("plan:Manage plans\n");
+}
+
+#[test]
+fn test_cli_complete_without_database() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("missing.db");
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db_path.to_str().unwrap(),
+            "complete",
+            "--",
+            "plan",
+            "show",
+            "",
+        ])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    assert!(!db_path.exists());
+}
+
+#[test]
+fn test_cli_plan_repair() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Ordered"])
+        .assert()
+        .success();
+    for title in ["First", "Second"] {
+        beacon_cmd()
+            .args(["--database-file", db, "step", "add", "1", title])
+            .assert()
+            .success();
+    }
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "repair", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Renumbered 2 step(s) of plan 1 in their current order",
+        ));
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "repair", "9"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Failed to repair plan 9"));
+}
+
+#[test]
+fn test_cli_plan_reset() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+    let run = |args: &[&str]| {
+        beacon_cmd()
+            .args(["--database-file", db])
+            .args(args)
+            .assert()
+    };
+
+    run(&["plan", "create", "Weekly"]).success();
+    run(&["step", "add", "1", "Rotate logs"]).success();
+    run(&["step", "add", "1", "Check backups"]).success();
+    run(&[
+        "step", "update", "1", "--status", "done", "--result", "Rotated",
+    ])
+    .success();
+
+    // Kept results are reused when the step is done again
+    run(&["plan", "reset", "1", "--keep-results"])
+        .success()
+        .stdout(predicate::str::contains(
+            "Reset 2 step(s) of plan 1 to todo",
+        ));
+    run(&["step", "update", "1", "--status", "done"]).success();
+
+    run(&["plan", "reset", "1"]).success();
+    run(&["step", "update", "1", "--status", "done"])
+        .failure()
+        .stderr(predicate::str::contains("Result description is required"));
+
+    run(&["plan", "archive", "1", "--force"]).success();
+    run(&["plan", "reset", "1"])
+        .code(2)
+        .stderr(predicate::str::contains("is archived"));
+}
+
+#[test]
+fn test_cli_events() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "events"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No events recorded."));
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Logged"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db, "step", "add", "1", "Traced"])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db, "events", "--since", "1h"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("plan 1 created: Logged"))
+        .stdout(predicate::str::contains("step 1 added: Traced"));
+    beacon_cmd()
+        .args(["--database-file", db, "events", "--limit", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("plan 1 created"))
+        .stdout(predicate::str::contains("step 1 added").not());
+}
+
+#[test]
+fn test_cli_plan_watch_stops_when_plan_is_deleted() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "watch", "1"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Plan with ID 1 not found"));
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "watch",
+            "1",
+            "--interval",
+            "0",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not a positive number of seconds"));
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Watched"])
+        .assert()
+        .success();
+    let watch = std::process::Command::new(assert_cmd::cargo::cargo_bin("b"))
+        .args(["--no-color", "--database-file", db])
+        .args(["plan", "watch", "1", "--interval", "0.05"])
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    beacon_cmd()
+        .args(["--database-file", db, "step", "add", "1", "Seen"])
+        .assert()
+        .success();
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "delete",
+            "1",
+            "--yes",
+            "--force",
+        ])
+        .assert()
+        .success();
+
+    let output = watch.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("# 1. Watched").count(), 2, "{stdout}");
+    assert!(stdout.contains("Seen"), "{stdout}");
+    assert!(
+        stdout.contains("Plan 1 was deleted; stopped watching."),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_cli_templates() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "template", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No templates defined."));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "template",
+            "create",
+            "Release",
+            "--step",
+            "Bump version",
+            "--step",
+            "Tag release",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Created template 'Release' with 2 step(s)",
+        ));
+    beacon_cmd()
+        .args(["--database-file", db, "template", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Release (2 step(s))"))
+        .stdout(predicate::str::contains("2. Tag release"));
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Release 2.0"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db, "template", "apply", "1", "release"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("adding 2 step(s)"))
+        .stdout(predicate::str::contains("Bump version"));
+    beacon_cmd()
+        .args(["--database-file", db, "template", "apply", "1", "Missing"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Template 'Missing' not found"));
+}
+
+#[test]
+fn test_cli_stats() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "stats"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Plans created**: 0"))
+        .stdout(predicate::str::contains("Average time to done**: n/a"));
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Reviewed"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db, "step", "add", "1", "Counted"])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args(["--database-file", db, "stats", "--since", "7d"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("# Statistics since"))
+        .stdout(predicate::str::contains("Plans created**: 1"))
+        .stdout(predicate::str::contains("Steps created**: 1"));
+    beacon_cmd()
+        .args(["--database-file", db, "stats", "--since", "lately"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid value 'lately'"));
+}
+
+#[test]
+fn test_cli_step_find() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Searched"])
+        .assert()
+        .success();
+    for title in ["Add OAuth login", "Write docs"] {
+        beacon_cmd()
+            .args(["--database-file", db, "step", "add", "1", title])
+            .assert()
+            .success();
+    }
+
+    beacon_cmd()
+        .args(["--database-file", db, "step", "find", "1", "oauth"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Steps of Plan 1 matching 'oauth'"))
+        .stdout(predicate::str::contains("1. Add OAuth login"))
+        .stdout(predicate::str::contains("Write docs").not());
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "step",
+            "find",
+            "1",
+            "oauth",
+            "--status",
+            "done",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No steps found."));
+    beacon_cmd()
+        .args(["--database-file", db, "step", "find", "1", " "])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("A search query cannot be empty"));
+}
+
+#[test]
+fn test_cli_step_list_by_status() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    for title in ["Backend", "Frontend"] {
+        beacon_cmd()
+            .args(["--database-file", db, "plan", "create", title])
+            .assert()
+            .success();
+    }
+    for (plan, title) in [
+        ("1", "Build API"),
+        ("1", "Write tests"),
+        ("2", "Style pages"),
+    ] {
+        beacon_cmd()
+            .args(["--database-file", db, "step", "add", plan, title])
+            .assert()
+            .success();
+    }
+    for id in ["1", "3"] {
+        beacon_cmd()
+            .args([
+                "--database-file",
+                db,
+                "step",
+                "update",
+                id,
+                "--status",
+                "inprogress",
+            ])
+            .assert()
+            .success();
+    }
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "step",
+            "list",
+            "--status",
+            "inprogress",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("In Progress Steps"))
+        .stdout(predicate::str::contains("Backend (ID: 1)"))
+        .stdout(predicate::str::contains("Frontend (ID: 2)"))
+        .stdout(predicate::str::contains("Build API"))
+        .stdout(predicate::str::contains("Style pages"))
+        .stdout(predicate::str::contains("Write tests").not());
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "archive", "2", "--force"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", db, "step", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Build API"))
+        .stdout(predicate::str::contains("Style pages").not());
+    beacon_cmd()
+        .args(["--database-file", db, "step", "list", "--archived"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Style pages"));
+}
+
+#[test]
+fn test_cli_step_references_keep_commas_and_labels() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+    let export_path = temp_dir.path().join("plans.json");
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Referenced"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "step",
+            "add",
+            "1",
+            "Read up",
+            "-r",
+            "https://example.com/search?q=a,b&page=2",
+            "-r",
+            "Design doc=https://example.com/design",
+        ])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "step",
+            "add",
+            "1",
+            "Unlinked",
+            "-r",
+            "Design doc=",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("without a URL"));
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "export",
+            "-o",
+            export_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    let exported: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&export_path).unwrap()).unwrap();
+    assert_eq!(
+        exported[0]["steps"][0]["references"],
+        serde_json::json!([
+            "https://example.com/search?q=a,b&page=2",
+            {"url": "https://example.com/design", "label": "Design doc"}
+        ])
+    );
+}
+
+#[test]
+fn test_cli_export_and_import_plans() {
+    let temp_dir = create_cli_test_environment();
+    let source_path = temp_dir.path().join("source.db");
+    let source = source_path.to_str().unwrap();
+    let target_path = temp_dir.path().join("target.db");
+    let target = target_path.to_str().unwrap();
+    let export_path = temp_dir.path().join("plans.json");
+    let export = export_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            source,
+            "plan",
+            "create",
+            "Release",
+            "--directory",
+            "/workspace",
+        ])
+        .assert()
+        .success();
+    for title in ["Build", "Ship"] {
+        beacon_cmd()
+            .args(["--database-file", source, "step", "add", "1", title])
+            .assert()
+            .success();
+    }
+    beacon_cmd()
+        .args(["--database-file", source, "plan", "export", "-o", export])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 1 plan(s)"));
+
+    // A plan without a conflict is created as it was
+    beacon_cmd()
+        .args(["--database-file", target, "plan", "import", export])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Imported 'Release' (ID: 1)"));
+
+    // Unattended imports skip conflicts
+    beacon_cmd()
+        .args(["--database-file", target, "plan", "import", export])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Skipped 'Release'"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            target,
+            "plan",
+            "import",
+            export,
+            "--on-conflict",
+            "duplicate",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Release (imported "));
+
+    // Add a step to the export that the first plan doesn't have yet
+    beacon_cmd()
+        .args(["--database-file", source, "step", "add", "1", "Announce"])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args(["--database-file", source, "plan", "export", "-o", export])
+        .assert()
+        .success();
+    beacon_cmd()
+        .args([
+            "--database-file",
+            target,
+            "plan",
+            "import",
+            export,
+            "--on-conflict",
+            "merge",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Merged 'Release' into plan 1, appending 1 step(s) and skipping 2",
+        ));
+    beacon_cmd()
+        .args(["--database-file", target, "plan", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Announce"))
+        .stdout(predicate::str::contains("Merged imported plan 'Release'"));
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            target,
+            "plan",
+            "import",
+            export,
+            "--on-conflict",
+            "replace",
+        ])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cli_config_file_and_show() {
+    let temp_dir = create_cli_test_environment();
+    let config_dir = temp_dir.path().join("beacon");
+    std::fs::create_dir(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("config.toml"),
+        "database_file = \"from_file.db\"\npager = \"more\"\ncolour = false\n",
+    )
+    .unwrap();
+    let beacon = || {
+        let mut cmd = beacon_cmd();
+        cmd.env("XDG_CONFIG_HOME", temp_dir.path())
+            .env_remove("BEACON_DATABASE_FILE")
+            .env_remove("BEACON_PAGER");
+        cmd
+    };
+
+    // The database named in the file is used, relative to the file
+    beacon()
+        .args(["plan", "create", "Configured"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Unknown key 'colour'"));
+    assert!(config_dir.join("from_file.db").exists());
+
+    beacon()
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "from_file.db` (configuration file)",
+        ))
+        .stdout(predicate::str::contains("no_color = true (flag)"))
+        .stdout(predicate::str::contains(
+            "pager = `more` (configuration file)",
+        ))
+        .stdout(predicate::str::contains("format = rich (default)"));
+
+    let flag_db = temp_dir.path().join("flag.db");
+    beacon()
+        .env("BEACON_PAGER", "most")
+        .args([
+            "--database-file",
+            flag_db.to_str().unwrap(),
+            "config",
+            "show",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("flag.db` (flag)"))
+        .stdout(predicate::str::contains(
+            "pager = `most` (environment (BEACON_PAGER))",
+        ));
+    assert!(!flag_db.exists());
+
+    std::fs::write(config_dir.join("config.toml"), "no_color = \"yes\"").unwrap();
+    beacon()
+        .args(["config", "show"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid configuration file"));
+}
+
+#[test]
+fn test_cli_plan_move_dir() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "create",
+            "Moved",
+            "--directory",
+            "/old/repo/app",
+        ])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "move-dir",
+            "/old/repo",
+            "/new/repo",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Moved 1 plan(s) from /old/repo to /new/repo",
+        ));
+    // Directories are echoed as they are matched
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "search",
+            "/new//repo/./app/../",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "ACTIVE plans in directory: /new/repo\n",
+        ))
+        .stdout(predicate::str::contains("Moved"));
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "move-dir",
+            "/old/repo",
+            "/new/repo",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No plans found in /old/repo"));
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "move-dir",
+            "/new",
+            "/new/repo",
+        ])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cli_plan_note() {
+    let temp_dir = create_cli_test_environment();
+    let db_path = temp_dir.path().join("cli_test.db");
+    let db = db_path.to_str().unwrap();
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "create", "Noted"])
+        .assert()
+        .success();
+
+    beacon_cmd()
+        .args([
+            "--database-file",
+            db,
+            "plan",
+            "note",
+            "1",
+            "Decided to vendor the parser",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added note to plan 1"));
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Notes"))
+        .stdout(predicate::str::contains("Decided to vendor the parser"));
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("vendor").not());
+
+    beacon_cmd()
+        .args(["--database-file", db, "plan", "note", "99", "Lost"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("99"));
 }
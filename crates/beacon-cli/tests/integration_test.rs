@@ -7,10 +7,11 @@ use std::process::Command;
 
 use beacon_core::{
     PlanFilter, PlanStatus, PlanSummary, Planner, PlannerBuilder, StepStatus,
-    display::{CreateResult, PlanSummaries},
+    display::{CreateResult, PlanAt, PlanSummaries},
     params::{CreatePlan, Id, StepCreate},
 };
 use futures::future;
+use jiff::Timestamp;
 use tempfile::TempDir;
 
 /// Helper function to create a test planner with temporary database
@@ -65,6 +66,7 @@ async fn test_plan_display_consistency() {
         title: "Integration Test Plan Direct".to_string(),
         description: Some("Test plan for integration testing".to_string()),
         directory: None,
+        ..Default::default()
     };
 
     let plan = planner
@@ -112,6 +114,7 @@ async fn test_step_display_consistency() {
         title: "Direct Step Test Plan".to_string(),
         description: None,
         directory: None,
+        ..Default::default()
     };
 
     let plan = planner
@@ -124,7 +127,10 @@ async fn test_step_display_consistency() {
         title: "Test Step".to_string(),
         description: Some("Step added via direct call".to_string()),
         acceptance_criteria: None,
+        expected_output: None,
         references: vec![],
+        verification_required: false,
+        estimate_minutes: None,
     };
 
     let step = planner
@@ -174,11 +180,13 @@ async fn test_list_plans_consistency() {
         title: "Direct List Test Plan 1".to_string(),
         description: None,
         directory: None,
+        ..Default::default()
     };
     let plan_params2 = CreatePlan {
         title: "Direct List Test Plan 2".to_string(),
         description: Some("Second plan".to_string()),
         directory: None,
+        ..Default::default()
     };
 
     let plan1 = planner
@@ -270,6 +278,7 @@ async fn test_show_plan_consistency() {
         title: "Show Test Plan".to_string(),
         description: Some("Plan for show testing".to_string()),
         directory: None,
+        ..Default::default()
     };
 
     let plan = planner
@@ -282,7 +291,10 @@ async fn test_show_plan_consistency() {
         title: "Test Step".to_string(),
         description: Some("Step for testing".to_string()),
         acceptance_criteria: None,
+        expected_output: None,
         references: vec![],
+        verification_required: false,
+        estimate_minutes: None,
     };
 
     let _step = planner
@@ -301,7 +313,7 @@ async fn test_show_plan_consistency() {
         .get_steps(&params)
         .await
         .expect("Failed to get steps");
-    full_plan.steps = steps_wrapper.0;
+    full_plan.steps = steps_wrapper.into_inner();
 
     let direct_output = full_plan.to_string();
 
@@ -350,6 +362,7 @@ async fn test_show_step_consistency() {
         title: "Direct Step Show Test Plan".to_string(),
         description: None,
         directory: None,
+        ..Default::default()
     };
 
     let plan = planner
@@ -362,7 +375,10 @@ async fn test_show_step_consistency() {
         title: "Show Step Test".to_string(),
         description: Some("Detailed step description".to_string()),
         acceptance_criteria: Some("Should show all fields correctly".to_string()),
+        expected_output: None,
         references: vec![],
+        verification_required: false,
+        estimate_minutes: None,
     };
 
     let step = planner
@@ -424,10 +440,12 @@ async fn test_cli_vs_mcp_list_output() {
         .await
         .expect("Failed to create planner");
 
-    let filter = Some(PlanFilter {
-        status: Some(PlanStatus::Active),
-        ..Default::default()
-    });
+    let filter = Some(
+        PlanFilter::builder()
+            .status(PlanStatus::Active)
+            .build()
+            .expect("Valid filter"),
+    );
 
     let plans = planner
         .list_plans(filter)
@@ -443,11 +461,10 @@ async fn test_cli_vs_mcp_list_output() {
                 .expect("Failed to get steps");
 
             let completed_steps = steps
-                .0
                 .iter()
                 .filter(|s| s.status == StepStatus::Done)
                 .count() as u32;
-            let total_steps = steps.0.len() as u32;
+            let total_steps = steps.len() as u32;
 
             PlanSummary::from_plan(plan, total_steps, completed_steps)
         }
@@ -548,9 +565,16 @@ async fn test_cli_vs_mcp_show_plan_output() {
         .get_steps(&params)
         .await
         .expect("Failed to get steps");
-    plan.steps = steps_wrapper.0;
+    plan.steps = steps_wrapper.into_inner();
+    let counts = planner
+        .plan_progress(&params)
+        .await
+        .expect("Failed to count steps");
 
-    let mcp_show = plan.to_string();
+    let mcp_show = PlanAt::new(&plan, Timestamp::now())
+        .with_status_counts(counts)
+        .to_string();
+    assert!(mcp_show.contains("- Progress: 0/2 done, 0 in progress, 2 todo\n"));
 
     // Both outputs should be identical since they use the same Display impl
     assert_eq!(cli_show.trim(), mcp_show.trim());
@@ -577,8 +601,10 @@ async fn test_cli_vs_mcp_show_step_output() {
             "Step for CLI vs MCP comparison",
             "--acceptance-criteria",
             "Both outputs should match exactly",
-            "--references",
-            "doc1.md,https://example.com",
+            "-r",
+            "doc1.md",
+            "-r",
+            "https://example.com",
         ],
     );
 
@@ -1,8 +1,9 @@
 -- Enable foreign key constraints (must be done per connection in SQLite)
 PRAGMA foreign_keys = ON;
 
--- Migration: Add status column to existing plans table if it doesn't exist
--- SQLite doesn't support ALTER TABLE ADD COLUMN IF NOT EXISTS, so we handle this in application code
+-- The current layout, used for new databases. Existing databases are brought
+-- to it by the migrations in src/db/migrations.rs, so every change here needs
+-- a migration making the same change
 
 -- Plans table: stores task plans with metadata
 CREATE TABLE IF NOT EXISTS plans (
@@ -11,6 +12,14 @@ CREATE TABLE IF NOT EXISTS plans (
     description TEXT, -- Detailed multi-line description of the plan
     status TEXT NOT NULL DEFAULT 'active' CHECK(status IN ('active', 'archived')),
     directory TEXT, -- Working directory for the plan (defaults to CWD)
+    subpath TEXT, -- Requested path below directory when directory was widened to the git root
+    parent_plan_id INTEGER REFERENCES plans(id) ON DELETE SET NULL, -- Parent plan; nesting is one level deep
+    auto_archive INTEGER NOT NULL DEFAULT 0, -- Archive the plan automatically once all its steps are done
+    status_labels TEXT, -- JSON object of display names for step statuses, e.g. {"inprogress":"doing"}
+    merged_into_plan_id INTEGER REFERENCES plans(id) ON DELETE SET NULL, -- Plan this archived plan's steps were merged into
+    due_date TEXT, -- Optional date (YYYY-MM-DD) the plan should be done by
+    notes TEXT, -- Working notes kept while carrying out the plan
+    unique_title INTEGER NOT NULL DEFAULT 0, -- Created deduplicated: no other such active plan has the same directory and title
     created_at TEXT NOT NULL, -- ISO 8601 format (e.g., "2024-01-15T10:30:00Z")
     updated_at TEXT NOT NULL  -- ISO 8601 format
 );
@@ -22,15 +31,86 @@ CREATE TABLE IF NOT EXISTS steps (
     title TEXT NOT NULL, -- Brief title/summary of the step
     description TEXT, -- Detailed multi-line description of the step
     acceptance_criteria TEXT, -- Clear completion criteria for the step
-    step_references TEXT, -- Comma-separated list of references (URLs, file paths)
-    status TEXT NOT NULL DEFAULT 'todo' CHECK(status IN ('todo', 'inprogress', 'done')),
+    expected_output TEXT, -- Artifact the step is expected to produce, compared against its result
+    status TEXT NOT NULL DEFAULT 'todo' CHECK(status IN ('todo', 'inprogress', 'done', 'blocked')),
     result TEXT, -- Description of what was accomplished (required when status = 'done')
-    step_order INTEGER NOT NULL, -- 'order' is a SQL reserved keyword
+    blocked_reason TEXT, -- Why the step is blocked, kept only while status = 'blocked'
+    step_order INTEGER NOT NULL, -- Spaced sort key ('order' is a SQL reserved keyword); positions are computed when reading
+    phase TEXT, -- Optional section the step is grouped under when displayed
+    estimate_minutes INTEGER, -- Optional estimate of the effort the step takes
+    verification_required INTEGER NOT NULL DEFAULT 0, -- Completion must be confirmed by a second agent
+    verified_by TEXT, -- Who confirmed the completion, once verified
+    verification_comment TEXT,
+    verified_at TEXT,
+    started_at TEXT, -- When the step was first claimed or put in progress
+    completed_at TEXT, -- When the step was last marked done; cleared when it is reopened
+    deleted_at TEXT, -- When the step was moved to the trash; trashed steps are hidden until restored or purged
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL,
     FOREIGN KEY (plan_id) REFERENCES plans(id) ON DELETE CASCADE
 );
 
+-- Settings table: per-workspace configuration as key-value pairs. Keys
+-- without a row take their default value
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+-- Step dependencies: a row means step_id cannot be claimed until
+-- depends_on_step_id is done
+CREATE TABLE IF NOT EXISTS step_dependencies (
+    step_id INTEGER NOT NULL REFERENCES steps(id) ON DELETE CASCADE,
+    depends_on_step_id INTEGER NOT NULL REFERENCES steps(id) ON DELETE CASCADE,
+    PRIMARY KEY (step_id, depends_on_step_id)
+);
+
+-- Step notes: recorded while working on a step, oldest first
+CREATE TABLE IF NOT EXISTS step_notes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    step_id INTEGER NOT NULL REFERENCES steps(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+-- Step references: resources a step refers to (URLs, file paths), in
+-- order, each with an optional label shown instead of it
+CREATE TABLE IF NOT EXISTS step_references (
+    step_id INTEGER NOT NULL REFERENCES steps(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL, -- 0-based order of the reference within its step
+    url TEXT NOT NULL,
+    label TEXT,
+    kind TEXT, -- url, file, missing_file or text; NULL unless validated
+    PRIMARY KEY (step_id, position)
+);
+
+-- Events: append-only log of changes to plans and steps, each recorded in
+-- the transaction of the change it describes
+CREATE TABLE IF NOT EXISTS events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity_type TEXT NOT NULL, -- 'plan' or 'step'
+    entity_id INTEGER NOT NULL,
+    kind TEXT NOT NULL, -- what happened, e.g. 'created' or 'claimed'
+    payload TEXT NOT NULL, -- the plan or step as JSON
+    created_at TEXT NOT NULL
+);
+
+-- Step templates: named lists of steps appended to plans on request
+CREATE TABLE IF NOT EXISTS templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS template_steps (
+    template_id INTEGER NOT NULL REFERENCES templates(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL, -- 0-based order of the step within its template
+    title TEXT NOT NULL,
+    description TEXT,
+    acceptance_criteria TEXT,
+    PRIMARY KEY (template_id, position)
+);
+
 -- Indexes for query performance
 CREATE INDEX IF NOT EXISTS idx_steps_plan_id ON steps(plan_id);
 CREATE INDEX IF NOT EXISTS idx_steps_status ON steps(status);
@@ -38,40 +118,9 @@ CREATE INDEX IF NOT EXISTS idx_steps_plan_id_order ON steps(plan_id, step_order)
 CREATE INDEX IF NOT EXISTS idx_plans_created_at ON plans(created_at);
 CREATE INDEX IF NOT EXISTS idx_plans_title ON plans(title COLLATE NOCASE);
 CREATE INDEX IF NOT EXISTS idx_plans_status ON plans(status);
-
--- View for active plans with step counts (useful for summary queries)
-CREATE VIEW IF NOT EXISTS plan_summaries AS
-SELECT 
-    p.id,
-    p.title,
-    p.description,
-    p.status,
-    p.directory,
-    p.created_at,
-    p.updated_at,
-    COUNT(s.id) as total_steps,
-    SUM(CASE WHEN s.status = 'done' THEN 1 ELSE 0 END) as completed_steps,
-    SUM(CASE WHEN s.status = 'todo' THEN 1 ELSE 0 END) as pending_steps,
-    SUM(CASE WHEN s.status = 'inprogress' THEN 1 ELSE 0 END) as in_progress_steps
-FROM plans p
-LEFT JOIN steps s ON p.id = s.plan_id
-WHERE p.status = 'active'
-GROUP BY p.id;
-
--- View for all plans including archived ones
-CREATE VIEW IF NOT EXISTS all_plan_summaries AS
-SELECT 
-    p.id,
-    p.title,
-    p.description,
-    p.status,
-    p.directory,
-    p.created_at,
-    p.updated_at,
-    COUNT(s.id) as total_steps,
-    SUM(CASE WHEN s.status = 'done' THEN 1 ELSE 0 END) as completed_steps,
-    SUM(CASE WHEN s.status = 'todo' THEN 1 ELSE 0 END) as pending_steps,
-    SUM(CASE WHEN s.status = 'inprogress' THEN 1 ELSE 0 END) as in_progress_steps
-FROM plans p
-LEFT JOIN steps s ON p.id = s.plan_id
-GROUP BY p.id;
\ No newline at end of file
+CREATE INDEX IF NOT EXISTS idx_plans_directory_title ON plans(directory, title);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_plans_unique_directory_title ON plans(IFNULL(directory, ''), title) WHERE status = 'active' AND unique_title = 1;
+CREATE INDEX IF NOT EXISTS idx_plans_parent_plan_id ON plans(parent_plan_id);
+CREATE INDEX IF NOT EXISTS idx_step_dependencies_depends_on ON step_dependencies(depends_on_step_id);
+CREATE INDEX IF NOT EXISTS idx_step_notes_step_id ON step_notes(step_id);
+CREATE INDEX IF NOT EXISTS idx_events_created_at ON events(created_at);
@@ -0,0 +1,54 @@
+-- Triggers maintaining updated_at, so that every writer keeps it current.
+-- Like views, they are created after migrations, since they name columns
+-- added by a migration. Migrations adding a watched column drop the stale
+-- trigger so that it is recreated here.
+--
+-- An UPDATE that sets updated_at to a new value itself keeps that value, so
+-- imports can write historical timestamps. Triggers only fire on updates of
+-- the listed columns and only write updated_at, which is never listed, so
+-- they cannot fire each other or themselves.
+--
+-- Timestamps are written in UTC at millisecond precision, as the application
+-- writes them. Changes to the tables holding a step's references,
+-- dependencies and notes fire none of them.
+
+-- A plan is updated when any of its own columns change
+CREATE TRIGGER IF NOT EXISTS plans_touch_on_update
+AFTER UPDATE OF title, description, status, directory, subpath, parent_plan_id, auto_archive, status_labels, due_date, notes ON plans
+FOR EACH ROW WHEN NEW.updated_at IS OLD.updated_at
+BEGIN
+    UPDATE plans SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+END;
+
+-- A step is updated when its content changes. Reordering steps leaves them
+-- unchanged, though it does update their plan.
+CREATE TRIGGER IF NOT EXISTS steps_touch_on_update
+AFTER UPDATE OF plan_id, title, description, acceptance_criteria, expected_output, status, result, blocked_reason, phase, estimate_minutes, verification_required, verified_by, verification_comment, verified_at ON steps
+FOR EACH ROW WHEN NEW.updated_at IS OLD.updated_at
+BEGIN
+    UPDATE steps SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+END;
+
+-- A plan is updated whenever one of its steps is added, changed, moved,
+-- trashed, restored or removed. An added step updates its plan at the step's
+-- creation time.
+CREATE TRIGGER IF NOT EXISTS steps_touch_plan_on_insert
+AFTER INSERT ON steps
+FOR EACH ROW
+BEGIN
+    UPDATE plans SET updated_at = NEW.created_at WHERE id = NEW.plan_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS steps_touch_plan_on_update
+AFTER UPDATE OF plan_id, title, description, acceptance_criteria, expected_output, status, result, blocked_reason, step_order, phase, estimate_minutes, verification_required, verified_by, verification_comment, verified_at, deleted_at ON steps
+FOR EACH ROW
+BEGIN
+    UPDATE plans SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id IN (OLD.plan_id, NEW.plan_id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS steps_touch_plan_on_delete
+AFTER DELETE ON steps
+FOR EACH ROW
+BEGIN
+    UPDATE plans SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = OLD.plan_id;
+END;
@@ -0,0 +1,72 @@
+-- Views are created after migrations, since they may reference columns added
+-- by a migration. Migrations that change a view's columns drop the stale view
+-- so that it is recreated here.
+--
+-- Steps in the trash (deleted_at set) are left out of every count.
+
+-- View for active plans with step counts (useful for summary queries)
+CREATE VIEW IF NOT EXISTS plan_summaries AS
+SELECT 
+    p.id,
+    p.title,
+    p.description,
+    p.status,
+    p.directory,
+    p.created_at,
+    p.updated_at,
+    p.subpath,
+    p.parent_plan_id,
+    p.auto_archive,
+    p.status_labels,
+    p.due_date,
+    COUNT(s.id) as total_steps,
+    SUM(CASE WHEN s.status = 'done' AND (s.verification_required = 0 OR s.verified_at IS NOT NULL)
+        THEN 1 ELSE 0 END) as completed_steps,
+    SUM(CASE WHEN s.status IN ('todo', 'blocked') THEN 1 ELSE 0 END) as pending_steps,
+    SUM(CASE WHEN s.status = 'inprogress' THEN 1 ELSE 0 END) as in_progress_steps,
+    MIN(CASE WHEN s.status = 'inprogress' THEN s.updated_at END) as oldest_in_progress_at,
+    SUM(CASE WHEN s.status = 'done' AND s.verification_required = 1 AND s.verified_at IS NULL
+        THEN 1 ELSE 0 END) as awaiting_verification_steps,
+    (SELECT COUNT(*) FROM plans c WHERE c.parent_plan_id = p.id) as child_plans,
+    (SELECT COUNT(*) FROM steps cs JOIN plans c ON cs.plan_id = c.id
+        WHERE c.parent_plan_id = p.id AND cs.deleted_at IS NULL) as child_total_steps,
+    (SELECT COUNT(*) FROM steps cs JOIN plans c ON cs.plan_id = c.id
+        WHERE c.parent_plan_id = p.id AND cs.deleted_at IS NULL AND cs.status = 'done'
+            AND (cs.verification_required = 0 OR cs.verified_at IS NOT NULL)) as child_completed_steps
+FROM plans p
+LEFT JOIN steps s ON p.id = s.plan_id AND s.deleted_at IS NULL
+WHERE p.status = 'active'
+GROUP BY p.id;
+
+-- View for all plans including archived ones
+CREATE VIEW IF NOT EXISTS all_plan_summaries AS
+SELECT 
+    p.id,
+    p.title,
+    p.description,
+    p.status,
+    p.directory,
+    p.created_at,
+    p.updated_at,
+    p.subpath,
+    p.parent_plan_id,
+    p.auto_archive,
+    p.status_labels,
+    p.due_date,
+    COUNT(s.id) as total_steps,
+    SUM(CASE WHEN s.status = 'done' AND (s.verification_required = 0 OR s.verified_at IS NOT NULL)
+        THEN 1 ELSE 0 END) as completed_steps,
+    SUM(CASE WHEN s.status IN ('todo', 'blocked') THEN 1 ELSE 0 END) as pending_steps,
+    SUM(CASE WHEN s.status = 'inprogress' THEN 1 ELSE 0 END) as in_progress_steps,
+    MIN(CASE WHEN s.status = 'inprogress' THEN s.updated_at END) as oldest_in_progress_at,
+    SUM(CASE WHEN s.status = 'done' AND s.verification_required = 1 AND s.verified_at IS NULL
+        THEN 1 ELSE 0 END) as awaiting_verification_steps,
+    (SELECT COUNT(*) FROM plans c WHERE c.parent_plan_id = p.id) as child_plans,
+    (SELECT COUNT(*) FROM steps cs JOIN plans c ON cs.plan_id = c.id
+        WHERE c.parent_plan_id = p.id AND cs.deleted_at IS NULL) as child_total_steps,
+    (SELECT COUNT(*) FROM steps cs JOIN plans c ON cs.plan_id = c.id
+        WHERE c.parent_plan_id = p.id AND cs.deleted_at IS NULL AND cs.status = 'done'
+            AND (cs.verification_required = 0 OR cs.verified_at IS NOT NULL)) as child_completed_steps
+FROM plans p
+LEFT JOIN steps s ON p.id = s.plan_id AND s.deleted_at IS NULL
+GROUP BY p.id;
\ No newline at end of file
@@ -0,0 +1,296 @@
+//! Canonical formatting of acceptance criteria.
+//!
+//! Acceptance criteria are usually written as markdown checklists, but by
+//! hand and by agents alike they arrive with mixed bullets, numbered items,
+//! stray indentation and Windows line endings. Normalizing them when steps
+//! are written gives anything parsing the checklist later one shape to read:
+//!
+//! ```text
+//!   * Tests pass            - [ ] Tests pass
+//!   2) [x] Docs updated  →  - [x] Docs updated
+//! ```
+//!
+//! List items with a `-`, `*`, `+` or `•` bullet, or a number followed by `.`
+//! or `)`, become `- [ ]` items, keeping a `[x]` check mark, and lose their
+//! indentation. Other lines are prose and are kept as written, as are lines
+//! in fenced code blocks and thematic breaks such as `* * *`. Trailing
+//! whitespace is stripped.
+//!
+//! Normalization must not change what the criteria say. The result is parsed
+//! again and compared with the original; if they differ, as when collapsing
+//! the indentation of a nested checklist would flatten it, the original is
+//! kept unchanged.
+
+/// Columns a tab counts for when comparing the indentation of list items.
+const TAB_WIDTH: usize = 4;
+
+/// Normalizes acceptance criteria into a canonical markdown checklist.
+///
+/// Returns the criteria unchanged if normalizing them would lose
+/// information.
+///
+/// # Examples
+///
+/// ```
+/// use beacon_core::normalize_acceptance_criteria;
+///
+/// let criteria = "Done when:\r\n  * tests pass  \r\n  2. [X] docs updated\r\n";
+/// assert_eq!(
+///     normalize_acceptance_criteria(criteria),
+///     "Done when:\n- [ ] tests pass\n- [x] docs updated"
+/// );
+/// ```
+pub fn normalize_acceptance_criteria(criteria: &str) -> String {
+    let lines = parse(criteria);
+    let normalized = render(&lines);
+
+    if parse(&normalized) == lines {
+        normalized
+    } else {
+        criteria.to_string()
+    }
+}
+
+/// A line of acceptance criteria, as far as normalization is concerned.
+#[derive(Debug, PartialEq, Eq)]
+enum Line<'a> {
+    Blank,
+    /// Text that isn't a list item, without trailing whitespace
+    Prose(&'a str),
+    /// A line of a fenced code block, kept exactly
+    Code(&'a str),
+    Item {
+        /// Nesting level among the items' distinct indentations
+        depth: usize,
+        checked: bool,
+        text: &'a str,
+    },
+}
+
+/// Splits criteria into lines, dropping trailing blank lines.
+fn parse(criteria: &str) -> Vec<Line<'_>> {
+    let mut in_code = false;
+    let mut indents = Vec::new();
+    let mut lines: Vec<Line<'_>> = criteria
+        .lines()
+        .map(|line| {
+            let fence = is_fence(line);
+            if in_code && !fence {
+                return Line::Code(line.strip_suffix('\r').unwrap_or(line));
+            }
+            in_code ^= fence;
+
+            let line = line.trim_end();
+            if line.is_empty() {
+                Line::Blank
+            } else if let Some((indent, checked, text)) = parse_item(line).filter(|_| !fence) {
+                indents.push(indent);
+                Line::Item {
+                    depth: indent,
+                    checked,
+                    text,
+                }
+            } else {
+                Line::Prose(line)
+            }
+        })
+        .collect();
+
+    while lines.last() == Some(&Line::Blank) {
+        lines.pop();
+    }
+
+    // Replace indentation widths by nesting levels, so that only the
+    // relative indentation of items matters
+    indents.sort_unstable();
+    indents.dedup();
+    for line in &mut lines {
+        if let Line::Item { depth, .. } = line {
+            *depth = indents.binary_search(depth).unwrap_or_default();
+        }
+    }
+
+    lines
+}
+
+/// Parses a list item into its indentation width, check mark and text.
+fn parse_item(line: &str) -> Option<(usize, bool, &str)> {
+    let rest = line.trim_start_matches([' ', '\t']);
+    let indent = line[..line.len() - rest.len()]
+        .chars()
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum();
+
+    if is_thematic_break(rest) {
+        return None;
+    }
+
+    let after_marker = strip_marker(rest)?;
+    let body = if after_marker.is_empty() {
+        after_marker
+    } else {
+        after_marker
+            .strip_prefix([' ', '\t'])?
+            .trim_start_matches([' ', '\t'])
+    };
+
+    match strip_checkbox(body) {
+        Some((checked, text)) => Some((indent, checked, text)),
+        // A bare bullet is not an item
+        None if body.is_empty() => None,
+        None => Some((indent, false, body)),
+    }
+}
+
+/// Strips a bullet or a number followed by `.` or `)`.
+fn strip_marker(text: &str) -> Option<&str> {
+    if let Some(rest) = text.strip_prefix(['-', '*', '+', '•']) {
+        return Some(rest);
+    }
+
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 || digits > 9 {
+        return None;
+    }
+    text[digits..].strip_prefix(['.', ')'])
+}
+
+/// Strips a leading `[ ]`, `[x]` or `[X]`, returning whether it was checked.
+fn strip_checkbox(body: &str) -> Option<(bool, &str)> {
+    [("[ ]", false), ("[x]", true), ("[X]", true)]
+        .into_iter()
+        .find_map(|(checkbox, checked)| {
+            let rest = body.strip_prefix(checkbox)?;
+            (rest.is_empty() || rest.starts_with([' ', '\t']))
+                .then(|| (checked, rest.trim_start_matches([' ', '\t'])))
+        })
+}
+
+/// Whether a line opens or closes a fenced code block.
+fn is_fence(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("```") || line.starts_with("~~~")
+}
+
+/// Whether text is a thematic break such as `---` or `* * *`.
+fn is_thematic_break(text: &str) -> bool {
+    let mut markers = text.chars().filter(|c| !c.is_whitespace());
+    let Some(first) = markers.next() else {
+        return false;
+    };
+    let count = 1 + markers.clone().count();
+    matches!(first, '-' | '*' | '_') && count >= 3 && markers.all(|c| c == first)
+}
+
+/// Writes lines back as text, with items in canonical form.
+fn render(lines: &[Line<'_>]) -> String {
+    lines
+        .iter()
+        .map(|line| match line {
+            Line::Blank => String::new(),
+            Line::Prose(text) | Line::Code(text) => (*text).to_string(),
+            Line::Item { checked, text, .. } => {
+                let checkbox = if *checked { "[x]" } else { "[ ]" };
+                if text.is_empty() {
+                    format!("- {checkbox}")
+                } else {
+                    format!("- {checkbox} {text}")
+                }
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mixed_bullets() {
+        let criteria = "- tests pass\n* docs updated\n+ changelog entry\n• release notes";
+        assert_eq!(
+            normalize_acceptance_criteria(criteria),
+            "- [ ] tests pass\n- [ ] docs updated\n- [ ] changelog entry\n- [ ] release notes"
+        );
+    }
+
+    #[test]
+    fn test_numbered_lists() {
+        let criteria = "1. build passes\n2) lint is clean\n10. [x] benchmarks run";
+        assert_eq!(
+            normalize_acceptance_criteria(criteria),
+            "- [ ] build passes\n- [ ] lint is clean\n- [x] benchmarks run"
+        );
+    }
+
+    #[test]
+    fn test_check_marks_are_kept() {
+        let criteria = "- [X] done\n- [x] also done\n-   [ ]   open\n- [ ]";
+        assert_eq!(
+            normalize_acceptance_criteria(criteria),
+            "- [x] done\n- [x] also done\n- [ ] open\n- [ ]"
+        );
+    }
+
+    #[test]
+    fn test_tabs_and_indentation_collapse() {
+        // As copied from an indented block in a prompt template
+        let criteria = "\t- [ ] migrates existing rows\n\t- [ ] rolls back cleanly";
+        assert_eq!(
+            normalize_acceptance_criteria(criteria),
+            "- [ ] migrates existing rows\n- [ ] rolls back cleanly"
+        );
+
+        let criteria = "    * first\n    * second";
+        assert_eq!(
+            normalize_acceptance_criteria(criteria),
+            "- [ ] first\n- [ ] second"
+        );
+    }
+
+    #[test]
+    fn test_windows_line_endings_and_trailing_whitespace() {
+        let criteria = "Verify:  \r\n- works on Windows \t\r\n\r\n* and elsewhere\r\n\r\n";
+        assert_eq!(
+            normalize_acceptance_criteria(criteria),
+            "Verify:\n- [ ] works on Windows\n\n- [ ] and elsewhere"
+        );
+    }
+
+    #[test]
+    fn test_prose_is_untouched() {
+        let criteria = "The endpoint returns **200** for valid input.\n  *Not* for invalid input.\n-not a bullet";
+        assert_eq!(normalize_acceptance_criteria(criteria), criteria);
+    }
+
+    #[test]
+    fn test_thematic_breaks_and_code_are_untouched() {
+        let criteria = "- [ ] output matches:\n\n```\n  - literal dash  \n1. literal number\n```\n\n* * *\n---";
+        assert_eq!(
+            normalize_acceptance_criteria(criteria),
+            "- [ ] output matches:\n\n```\n  - literal dash  \n1. literal number\n```\n\n* * *\n---"
+        );
+    }
+
+    #[test]
+    fn test_nested_checklist_is_kept() {
+        // Collapsing the indentation would flatten the sub-items into
+        // siblings of their parent
+        let criteria = "* API\n    * returns 404 for unknown IDs\n* CLI";
+        assert_eq!(normalize_acceptance_criteria(criteria), criteria);
+    }
+
+    #[test]
+    fn test_normalization_is_idempotent() {
+        for criteria in [
+            "  * a\r\n  2. [X] b  \r\n\r\nnote",
+            "1) one\n\n```sh\ncargo test\n```\n- [ ] two",
+            "plain prose only",
+            "",
+        ] {
+            let once = normalize_acceptance_criteria(criteria);
+            assert_eq!(normalize_acceptance_criteria(&once), once);
+        }
+    }
+}
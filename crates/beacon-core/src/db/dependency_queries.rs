@@ -0,0 +1,188 @@
+//! Dependencies between steps.
+//!
+//! A step depending on another cannot be claimed until the other is done.
+//! Dependencies form a directed acyclic graph: adding one that would close a
+//! cycle is rejected, since the steps on it could never be claimed.
+//!
+//! Dependencies on steps in the trash are kept for when they are restored,
+//! but neither shown nor blocking meanwhile.
+
+use std::collections::HashMap;
+
+use rusqlite::{Connection, params};
+
+use crate::{
+    error::{DatabaseResultExt, PlannerError, Result},
+    models::EventKind,
+};
+
+const CHECK_STEP_EXISTS_SQL: &str =
+    "SELECT EXISTS(SELECT 1 FROM steps WHERE id = ?1 AND deleted_at IS NULL)";
+const INSERT_STEP_DEPENDENCY_SQL: &str =
+    "INSERT OR IGNORE INTO step_dependencies (step_id, depends_on_step_id) VALUES (?1, ?2)";
+const DELETE_STEP_DEPENDENCY_SQL: &str =
+    "DELETE FROM step_dependencies WHERE step_id = ?1 AND depends_on_step_id = ?2";
+const SELECT_STEP_DEPENDENCIES_SQL: &str = "SELECT d.depends_on_step_id FROM step_dependencies d JOIN steps s ON s.id = d.depends_on_step_id WHERE d.step_id = ?1 AND s.deleted_at IS NULL ORDER BY d.depends_on_step_id";
+const SELECT_PLAN_DEPENDENCIES_SQL: &str = "SELECT d.step_id, d.depends_on_step_id FROM step_dependencies d JOIN steps s ON s.id = d.step_id JOIN steps t ON t.id = d.depends_on_step_id WHERE s.plan_id = ?1 AND t.deleted_at IS NULL ORDER BY d.step_id, d.depends_on_step_id";
+const SELECT_UNFINISHED_DEPENDENCIES_SQL: &str = "SELECT d.depends_on_step_id FROM step_dependencies d JOIN steps s ON s.id = d.depends_on_step_id WHERE d.step_id = ?1 AND s.deleted_at IS NULL AND s.status != 'done' ORDER BY d.depends_on_step_id";
+const DEPENDS_ON_TRANSITIVELY_SQL: &str = "WITH RECURSIVE reachable(id) AS (SELECT ?1 UNION SELECT d.depends_on_step_id FROM step_dependencies d JOIN reachable r ON d.step_id = r.id) SELECT EXISTS(SELECT 1 FROM reachable WHERE id = ?2)";
+
+impl super::Database {
+    /// Makes `step_id` depend on `depends_on`, so that it cannot be claimed
+    /// until `depends_on` is done.
+    ///
+    /// Returns false if the dependency already existed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if either step doesn't exist
+    /// Returns `PlannerError::InvalidInput` if the dependency would form a
+    /// cycle, including a step depending on itself
+    pub fn add_step_dependency(&mut self, step_id: u64, depends_on: u64) -> Result<bool> {
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+
+        Self::check_step_exists(&tx, step_id)?;
+        Self::check_step_exists(&tx, depends_on)?;
+
+        if step_id == depends_on {
+            return Err(PlannerError::invalid_input("depends_on")
+                .with_reason(format!("Step {step_id} cannot depend on itself")));
+        }
+        let cycle: bool = tx
+            .query_row(
+                DEPENDS_ON_TRANSITIVELY_SQL,
+                params![depends_on as i64, step_id as i64],
+                |row| row.get(0),
+            )
+            .db_context("Failed to check for dependency cycles")?;
+        if cycle {
+            return Err(PlannerError::invalid_input("depends_on").with_reason(format!(
+                "Step {depends_on} already depends on step {step_id}, so the dependency would form a cycle"
+            )));
+        }
+
+        let added = Self::insert_step_dependency(&tx, step_id, depends_on)?;
+        if added {
+            Self::record_step_event(&tx, step_id, EventKind::Updated)?;
+        }
+
+        tx.commit().db_context("Failed to commit transaction")?;
+
+        Ok(added)
+    }
+
+    /// Stores a dependency without checking it, returning false if it
+    /// already existed. The caller owns the transaction.
+    pub(super) fn insert_step_dependency(
+        conn: &Connection,
+        step_id: u64,
+        depends_on: u64,
+    ) -> Result<bool> {
+        let added = conn
+            .execute(
+                INSERT_STEP_DEPENDENCY_SQL,
+                params![step_id as i64, depends_on as i64],
+            )
+            .db_context("Failed to add step dependency")?;
+        Ok(added > 0)
+    }
+
+    /// Removes the dependency of `step_id` on `depends_on`.
+    ///
+    /// Returns false if there was no such dependency.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if `step_id` doesn't exist
+    pub fn remove_step_dependency(&mut self, step_id: u64, depends_on: u64) -> Result<bool> {
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+
+        Self::check_step_exists(&tx, step_id)?;
+
+        let removed = tx
+            .execute(
+                DELETE_STEP_DEPENDENCY_SQL,
+                params![step_id as i64, depends_on as i64],
+            )
+            .db_context("Failed to remove step dependency")?;
+        if removed > 0 {
+            Self::record_step_event(&tx, step_id, EventKind::Updated)?;
+        }
+
+        tx.commit().db_context("Failed to commit transaction")?;
+
+        Ok(removed > 0)
+    }
+
+    /// Returns the IDs of the steps a step depends on, in ascending order.
+    pub(super) fn load_step_dependencies(conn: &Connection, step_id: u64) -> Result<Vec<u64>> {
+        Self::query_step_ids(conn, SELECT_STEP_DEPENDENCIES_SQL, step_id)
+    }
+
+    /// Returns the IDs of the steps a step depends on that are not done yet,
+    /// in ascending order.
+    pub(super) fn load_unfinished_dependencies(
+        conn: &Connection,
+        step_id: u64,
+    ) -> Result<Vec<u64>> {
+        Self::query_step_ids(conn, SELECT_UNFINISHED_DEPENDENCIES_SQL, step_id)
+    }
+
+    /// Returns the dependencies of every step of a plan that has any, keyed
+    /// by step ID.
+    pub(super) fn load_plan_dependencies(
+        conn: &Connection,
+        plan_id: u64,
+    ) -> Result<HashMap<u64, Vec<u64>>> {
+        let mut stmt = conn
+            .prepare(SELECT_PLAN_DEPENDENCIES_SQL)
+            .db_context("Failed to prepare dependency query")?;
+
+        let mut dependencies = HashMap::<u64, Vec<u64>>::new();
+        let rows = stmt
+            .query_map(params![plan_id as i64], |row| {
+                Ok((row.get::<_, i64>(0)? as u64, row.get::<_, i64>(1)? as u64))
+            })
+            .db_context("Failed to query step dependencies")?;
+        for row in rows {
+            let (step_id, depends_on) = row.db_context("Failed to read step dependency")?;
+            dependencies.entry(step_id).or_default().push(depends_on);
+        }
+
+        Ok(dependencies)
+    }
+
+    /// Runs a query for the step IDs related to `step_id`.
+    fn query_step_ids(conn: &Connection, sql: &str, step_id: u64) -> Result<Vec<u64>> {
+        let mut stmt = conn
+            .prepare(sql)
+            .db_context("Failed to prepare dependency query")?;
+
+        stmt.query_map(params![step_id as i64], |row| {
+            row.get::<_, i64>(0).map(|id| id as u64)
+        })
+        .db_context("Failed to query step dependencies")?
+        .collect::<rusqlite::Result<_>>()
+        .db_context("Failed to read step dependencies")
+    }
+
+    /// Fails with `PlannerError::StepNotFound` unless the step exists.
+    pub(super) fn check_step_exists(conn: &Connection, step_id: u64) -> Result<()> {
+        let exists: bool = conn
+            .query_row(CHECK_STEP_EXISTS_SQL, params![step_id as i64], |row| {
+                row.get(0)
+            })
+            .db_context("Failed to check step existence")?;
+        if exists {
+            Ok(())
+        } else {
+            Err(PlannerError::StepNotFound { id: step_id })
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Append-only log of changes to plans and steps.
+//!
+//! Each mutation records its event on the connection of its own
+//! transaction, so a change that is rolled back leaves no event behind and
+//! a committed one is never missing from the log.
+
+use jiff::Timestamp;
+use rusqlite::{Connection, params, types::Type};
+use serde::Serialize;
+
+use crate::{
+    error::{DatabaseResultExt, Result},
+    models::{Event, EventEntity, EventKind},
+};
+
+const INSERT_EVENT_SQL: &str = "INSERT INTO events (entity_type, entity_id, kind, payload, created_at) VALUES (?1, ?2, ?3, ?4, ?5)";
+const SELECT_EVENTS_SQL: &str = "SELECT id, entity_type, entity_id, kind, payload, created_at FROM events WHERE ?1 IS NULL OR created_at >= ?1 ORDER BY id LIMIT ?2";
+
+impl super::Database {
+    /// Records an event with `payload` serialized as JSON. Callers are
+    /// responsible for the transaction.
+    pub(super) fn record_event<T: Serialize>(
+        conn: &Connection,
+        entity_type: EventEntity,
+        entity_id: u64,
+        kind: EventKind,
+        payload: &T,
+    ) -> Result<()> {
+        conn.execute(
+            INSERT_EVENT_SQL,
+            params![
+                entity_type.as_str(),
+                entity_id as i64,
+                kind.as_str(),
+                serde_json::to_string(payload)?,
+                super::format_timestamp(super::now())
+            ],
+        )
+        .db_context("Failed to record event")?;
+        Ok(())
+    }
+
+    /// Records an event about a plan, with the plan as currently stored on
+    /// `conn`, including its notes, as payload. Nothing is recorded if the
+    /// plan doesn't exist.
+    pub(super) fn record_plan_event(
+        conn: &Connection,
+        plan_id: u64,
+        kind: EventKind,
+    ) -> Result<()> {
+        let Some(mut plan) = Self::read_plan_row(conn, plan_id)? else {
+            return Ok(());
+        };
+        plan.notes = Self::plan_notes(conn, plan_id)?;
+        plan.merged_into_plan_id = Self::merged_into(conn, plan_id)?;
+        Self::record_event(conn, EventEntity::Plan, plan_id, kind, &plan)
+    }
+
+    /// Records an event about a step, with the step as currently stored on
+    /// `conn` as payload. Nothing is recorded if the step doesn't exist or
+    /// is in the trash.
+    pub(super) fn record_step_event(
+        conn: &Connection,
+        step_id: u64,
+        kind: EventKind,
+    ) -> Result<()> {
+        match Self::read_step(conn, step_id)? {
+            Some(step) => Self::record_event(conn, EventEntity::Step, step_id, kind, &step),
+            None => Ok(()),
+        }
+    }
+
+    /// Lists recorded events, oldest first, optionally only those recorded
+    /// at or after `since` and at most `limit` of them.
+    pub fn list_events(&self, since: Option<Timestamp>, limit: Option<u32>) -> Result<Vec<Event>> {
+        let mut stmt = self
+            .connection
+            .prepare(SELECT_EVENTS_SQL)
+            .db_context("Failed to prepare event query")?;
+
+        // A negative limit means no limit to SQLite
+        let since = since.map(super::format_timestamp);
+        let limit = limit.map_or(-1, i64::from);
+        stmt.query_map(params![since, limit], |row| {
+            let conversion = |index, e: String| {
+                rusqlite::Error::FromSqlConversionFailure(index, Type::Text, e.into())
+            };
+            let payload: String = row.get(4)?;
+            Ok(Event {
+                id: row.get::<_, i64>(0)? as u64,
+                entity_type: row
+                    .get::<_, String>(1)?
+                    .parse()
+                    .map_err(|e| conversion(1, e))?,
+                entity_id: row.get::<_, i64>(2)? as u64,
+                kind: row
+                    .get::<_, String>(3)?
+                    .parse()
+                    .map_err(|e| conversion(3, e))?,
+                payload: serde_json::from_str(&payload).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(4, Type::Text, Box::new(e))
+                })?,
+                created_at: row.get::<_, String>(5)?.parse::<Timestamp>().map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(5, Type::Text, Box::new(e))
+                })?,
+            })
+        })
+        .and_then(Iterator::collect)
+        .db_context("Failed to query events")
+    }
+}
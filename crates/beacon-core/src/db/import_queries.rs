@@ -0,0 +1,270 @@
+//! Plans imported from another database.
+//!
+//! An exported plan is imported as a new plan unless an active plan with the
+//! same title already exists in the same directory. What happens to such a
+//! conflict is decided by a [`ConflictPolicy`].
+
+use std::collections::HashSet;
+
+use jiff::tz::TimeZone;
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior, params};
+
+use super::{
+    plan_queries::{
+        APPEND_PLAN_NOTE_SQL, SELECT_PLAN_ID_BY_DIRECTORY_TITLE_SQL, SET_PLAN_NOTES_SQL,
+    },
+    step_queries::SELECT_PLAN_STEP_IDS_SQL,
+};
+use crate::{
+    error::{DatabaseResultExt, PlannerError, Result},
+    models::{
+        ConflictPolicy, CreatePlanRequest, CreateStepRequest, EventKind, ImportOutcome, Plan, Step,
+        StepStatus, duplicates::normalize_title, imported_title,
+    },
+};
+
+const SELECT_STEP_TITLES_BY_PLAN_SQL: &str =
+    "SELECT title FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL";
+const UPDATE_STEP_WORK_TIMES_SQL: &str =
+    "UPDATE steps SET started_at = ?1, completed_at = ?2 WHERE id = ?3";
+
+impl super::Database {
+    /// Finds the active plan that importing `plan` would conflict with: one
+    /// with the same title in the same directory, normalized as for a new
+    /// plan.
+    pub fn find_import_conflict(&self, plan: &Plan) -> Result<Option<Plan>> {
+        let (directory, _) = self.plan_location(plan.directory.as_deref(), false)?;
+        match Self::conflicting_plan_id(&self.connection, directory.as_deref(), &plan.title)? {
+            Some(id) => self.get_plan(id),
+            None => Ok(None),
+        }
+    }
+
+    /// Imports a plan exported from this or another database, in one
+    /// transaction.
+    ///
+    /// The plan keeps its title, description, directory, auto-archive
+    /// setting, due date, notes and timestamps, and its steps keep their
+    /// order, statuses, results, references and timestamps, so imported
+    /// work counts as done when it was done; IDs, dependencies and parent
+    /// plans are not carried over, and the plan is imported active.
+    ///
+    /// When an active plan with the same title exists in the same directory,
+    /// `policy` decides what happens: [`ConflictPolicy::Skip`] imports
+    /// nothing, [`ConflictPolicy::DuplicateWithSuffix`] imports the plan
+    /// with " (imported YYYY-MM-DD)" appended to its title, numbered from
+    /// " (imported YYYY-MM-DD #2)" on if that is taken too, and
+    /// [`ConflictPolicy::Merge`] appends the imported steps whose titles the
+    /// existing plan doesn't have yet to its end, ignoring case, whitespace
+    /// and punctuation, and records the merge in its notes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the plan has no title or a
+    /// done step without a result
+    pub fn import_plan(&mut self, plan: &Plan, policy: ConflictPolicy) -> Result<ImportOutcome> {
+        if plan.title.trim().is_empty() {
+            return Err(PlannerError::invalid_input("title").with_reason("Title cannot be empty"));
+        }
+        let (directory, _) = self.plan_location(plan.directory.as_deref(), false)?;
+
+        // Take the write lock up front so the conflict can't change between
+        // the lookup and the import
+        let tx = self
+            .connection
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .db_context("Failed to begin transaction")?;
+        let now = super::now();
+        let now_str = super::format_timestamp(now);
+        let conflict = Self::conflicting_plan_id(&tx, directory.as_deref(), &plan.title)?;
+
+        let title = match (conflict, policy) {
+            (None, _) => plan.title.clone(),
+            (Some(existing_id), ConflictPolicy::Skip) => {
+                return Ok(ImportOutcome::Skipped { existing_id });
+            }
+            (Some(_), ConflictPolicy::DuplicateWithSuffix) => {
+                let date = now.to_zoned(TimeZone::system()).date();
+                let mut number = 1;
+                loop {
+                    let title = imported_title(&plan.title, date, number);
+                    if Self::conflicting_plan_id(&tx, directory.as_deref(), &title)?.is_none() {
+                        break title;
+                    }
+                    number += 1;
+                }
+            }
+            (Some(existing_id), ConflictPolicy::Merge) => {
+                let (added, skipped) =
+                    Self::append_missing_steps(&tx, existing_id, &plan.steps, &now_str)?;
+                let note = merge_note(&plan.title, &added, &skipped);
+                tx.execute(
+                    APPEND_PLAN_NOTE_SQL,
+                    params![existing_id as i64, Self::plan_note_entry(&note)],
+                )
+                .db_context("Failed to update plan notes")?;
+                Self::record_plan_event(&tx, existing_id, EventKind::Updated)?;
+                tx.commit().db_context("Failed to commit transaction")?;
+
+                let plan = self
+                    .get_plan(existing_id)?
+                    .ok_or(PlannerError::PlanNotFound { id: existing_id })?;
+                return Ok(ImportOutcome::Merged {
+                    plan: Box::new(plan),
+                    added,
+                    skipped,
+                });
+            }
+        };
+        let id = Self::insert_imported_plan(&tx, plan, &title, directory.as_deref(), &now_str)?;
+
+        tx.commit().db_context("Failed to commit transaction")?;
+
+        let plan = Box::new(
+            self.get_plan(id)?
+                .ok_or(PlannerError::PlanNotFound { id })?,
+        );
+        Ok(match conflict {
+            Some(_) => ImportOutcome::Duplicated(plan),
+            None => ImportOutcome::Created(plan),
+        })
+    }
+
+    /// ID of the active plan titled `title` in `directory`, if any.
+    fn conflicting_plan_id(
+        conn: &Connection,
+        directory: Option<&str>,
+        title: &str,
+    ) -> Result<Option<u64>> {
+        let id: Option<i64> = conn
+            .query_row(
+                SELECT_PLAN_ID_BY_DIRECTORY_TITLE_SQL,
+                params![directory, title],
+                |row| row.get(0),
+            )
+            .optional()
+            .db_context("Failed to look up existing plan")?;
+        Ok(id.map(|id| id as u64))
+    }
+
+    /// Inserts `plan` titled `title` with its steps, carrying its notes and
+    /// timestamps over, and returns the new plan's ID. The caller owns the
+    /// transaction.
+    fn insert_imported_plan(
+        conn: &Connection,
+        plan: &Plan,
+        title: &str,
+        directory: Option<&str>,
+        now: &str,
+    ) -> Result<u64> {
+        let steps: Vec<CreateStepRequest> = plan.steps.iter().map(Into::into).collect();
+        let request = CreatePlanRequest {
+            title: title.to_string(),
+            description: plan.description.clone(),
+            auto_archive: plan.auto_archive,
+            due_date: plan.due_date,
+            ..Default::default()
+        };
+        let id = Self::insert_plan_row(
+            conn,
+            &request,
+            directory,
+            plan.subpath.as_deref(),
+            &steps,
+            now,
+        )?;
+        if let Some(notes) = &plan.notes {
+            conn.execute(SET_PLAN_NOTES_SQL, params![id as i64, notes])
+                .db_context("Failed to update plan notes")?;
+        }
+
+        let step_ids = conn
+            .prepare(SELECT_PLAN_STEP_IDS_SQL)
+            .db_context("Failed to prepare step query")?
+            .query_map(params![id as i64], |row| row.get::<_, i64>(0))
+            .db_context("Failed to query steps")?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .db_context("Failed to read steps")?;
+        for (step_id, step) in step_ids.into_iter().zip(&plan.steps) {
+            Self::carry_step_timestamps(conn, step_id as u64, step)?;
+        }
+        // Last, since the writes above keep the plan's updated_at current
+        Self::write_plan_timestamps(conn, id, plan.created_at, plan.updated_at)?;
+        Ok(id)
+    }
+
+    /// Gives step `step_id` the timestamps of the imported `step`. A step
+    /// exported without its start or completion time is taken to have
+    /// reached its status when it was last updated. The caller owns the
+    /// transaction.
+    fn carry_step_timestamps(conn: &Connection, step_id: u64, step: &Step) -> Result<()> {
+        let reached = |status: StepStatus| (step.status == status).then_some(step.updated_at);
+        let started_at = step.started_at.or_else(|| reached(StepStatus::InProgress));
+        let completed_at = step.completed_at.or_else(|| reached(StepStatus::Done));
+        conn.execute(
+            UPDATE_STEP_WORK_TIMES_SQL,
+            params![
+                started_at.map(super::format_timestamp),
+                completed_at.map(super::format_timestamp),
+                step_id as i64
+            ],
+        )
+        .db_context("Failed to set step timestamps")?;
+        Self::write_step_timestamps(conn, step_id, step.created_at, step.updated_at)?;
+        Ok(())
+    }
+
+    /// Appends the `steps` whose titles plan `plan_id` doesn't have yet to
+    /// its end, in order and with their timestamps, and returns the titles
+    /// of the steps appended and of those skipped. The caller owns the
+    /// transaction.
+    fn append_missing_steps(
+        conn: &Connection,
+        plan_id: u64,
+        steps: &[Step],
+        now: &str,
+    ) -> Result<(Vec<String>, Vec<String>)> {
+        let mut titles = conn
+            .prepare(SELECT_STEP_TITLES_BY_PLAN_SQL)
+            .db_context("Failed to prepare step query")?
+            .query_map(params![plan_id as i64], |row| row.get::<_, String>(0))
+            .db_context("Failed to query steps")?
+            .map(|title| title.map(|title| normalize_title(&title)))
+            .collect::<rusqlite::Result<HashSet<_>>>()
+            .db_context("Failed to read steps")?;
+
+        let (mut added, mut skipped) = (Vec::new(), Vec::new());
+        for step in steps {
+            // Also skips repeats within the imported plan
+            if !titles.insert(normalize_title(&step.title)) {
+                skipped.push(step.title.clone());
+                continue;
+            }
+            let step_id = Self::insert_step_row(conn, plan_id, &step.into(), now)?;
+            Self::carry_step_timestamps(conn, step_id, step)?;
+            Self::record_step_event(conn, step_id, EventKind::Added)?;
+            added.push(step.title.clone());
+        }
+        Ok((added, skipped))
+    }
+}
+
+/// Note recording that an imported plan titled `title` was merged.
+fn merge_note(title: &str, added: &[String], skipped: &[String]) -> String {
+    let mut note = format!("Merged imported plan '{title}'");
+    if added.is_empty() {
+        note.push_str(", adding no steps.");
+    } else {
+        note.push_str(&format!(", appending {} step(s):\n", added.len()));
+        for title in added {
+            note.push_str(&format!("\n- {title}"));
+        }
+    }
+    if !skipped.is_empty() {
+        note.push_str(&format!(
+            "\n\n{} step(s) were already in this plan and were skipped.",
+            skipped.len()
+        ));
+    }
+    note
+}
@@ -0,0 +1,159 @@
+//! Database maintenance: reclaiming space, checking integrity, and backing
+//! up and restoring the database file.
+
+use std::{fs::OpenOptions, path::Path};
+
+use rusqlite::MAIN_DB;
+
+use super::migrations::SCHEMA_VERSION;
+use crate::{
+    error::{DatabaseResultExt, PlannerError, Result},
+    models::MaintenanceReport,
+};
+
+const DATABASE_SIZE_SQL: &str =
+    "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()";
+const COUNT_BEACON_TABLES_SQL: &str =
+    "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name IN ('plans', 'steps')";
+const COUNT_IN_PROGRESS_STEPS_SQL: &str =
+    "SELECT COUNT(*) FROM steps WHERE status = 'inprogress' AND deleted_at IS NULL";
+
+impl super::Database {
+    /// Rebuilds the database file to reclaim the space of deleted rows,
+    /// refreshes the query planner's statistics and checks the database's
+    /// integrity.
+    ///
+    /// `VACUUM` cannot run inside a transaction, so this must be called
+    /// while none is open on the connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if a transaction is open, and a
+    /// database error that [is busy](PlannerError::is_busy) if another
+    /// connection kept the database locked.
+    pub fn vacuum_and_analyze(&mut self) -> Result<MaintenanceReport> {
+        if !self.connection.is_autocommit() {
+            return Err(PlannerError::invalid_input("database")
+                .with_reason("Maintenance cannot run inside a transaction"));
+        }
+
+        let size_before = self.database_size()?;
+        self.connection
+            .execute_batch("VACUUM; ANALYZE;")
+            .db_context("Failed to vacuum database")?;
+        let size_after = self.database_size()?;
+
+        let mut stmt = self
+            .connection
+            .prepare("PRAGMA integrity_check")
+            .db_context("Failed to prepare integrity check")?;
+        let integrity_problems = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .db_context("Failed to check database integrity")?
+            .filter(|message| !matches!(message.as_deref(), Ok("ok")))
+            .collect::<rusqlite::Result<_>>()
+            .db_context("Failed to check database integrity")?;
+
+        Ok(MaintenanceReport {
+            size_before,
+            size_after,
+            integrity_problems,
+        })
+    }
+
+    /// Size of the database in bytes, not counting its write-ahead log.
+    fn database_size(&self) -> Result<u64> {
+        self.connection
+            .query_row(DATABASE_SIZE_SQL, [], |row| row.get::<_, i64>(0))
+            .map(|size| size as u64)
+            .db_context("Failed to measure database size")
+    }
+
+    /// Copies the database to `path` with SQLite's online backup API, so
+    /// that other connections may keep using it meanwhile. A file already at
+    /// `path` is overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::FileSystem` if `path` cannot be written
+    pub fn backup_to(&self, path: &Path) -> Result<()> {
+        // SQLite only reports that it can't open the file, so the file is
+        // opened here first to report why
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|source| PlannerError::FileSystem {
+                path: path.to_path_buf(),
+                source,
+            })?;
+
+        self.connection
+            .backup(MAIN_DB, path, None)
+            .db_context("Failed to back up database")
+    }
+
+    /// Replaces the contents of the database with the beacon database at
+    /// `path`, such as a backup made by [`Self::backup_to`], and brings its
+    /// schema up to date.
+    ///
+    /// Other connections see the restored contents once it completes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::FileSystem` if `path` cannot be read
+    /// Returns `PlannerError::InvalidInput` if it is not a beacon database
+    /// Returns `PlannerError::SchemaVersion` if it was written by a newer
+    /// version of beacon
+    pub fn restore_from(&mut self, path: &Path) -> Result<()> {
+        Self::validate_backup(path)?;
+
+        self.connection
+            .restore(MAIN_DB, path, None::<fn(rusqlite::backup::Progress)>)
+            .db_context("Failed to restore database")?;
+        self.initialize_schema()
+    }
+
+    /// Checks that `path` holds a beacon database this version can open.
+    fn validate_backup(path: &Path) -> Result<()> {
+        // Opening a missing file would create it rather than fail
+        std::fs::metadata(path).map_err(|source| PlannerError::FileSystem {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let not_beacon = || {
+            PlannerError::invalid_input("file")
+                .with_reason(format!("'{}' is not a beacon database", path.display()))
+        };
+        let source = Self {
+            connection: Self::read_only_connection(path)?,
+            canonicalize_paths: true,
+        };
+        let tables: i64 = source
+            .connection
+            .query_row(COUNT_BEACON_TABLES_SQL, [], |row| row.get(0))
+            .map_err(|_| not_beacon())?;
+        if tables != 2 {
+            return Err(not_beacon());
+        }
+
+        let version = source.schema_version()?;
+        if version > SCHEMA_VERSION {
+            return Err(PlannerError::SchemaVersion {
+                found: version,
+                supported: SCHEMA_VERSION,
+            });
+        }
+        Ok(())
+    }
+
+    /// Number of steps currently in progress across all plans.
+    pub fn count_in_progress_steps(&self) -> Result<u64> {
+        self.connection
+            .query_row(COUNT_IN_PROGRESS_STEPS_SQL, [], |row| row.get::<_, i64>(0))
+            .map(|count| count as u64)
+            .db_context("Failed to count steps in progress")
+    }
+}
@@ -1,49 +1,610 @@
 //! Database schema initialization and migrations.
+//!
+//! A new database is given the current schema from `schema.sql` directly. An
+//! existing one is brought up to date by applying, in order, each of the
+//! [`MIGRATIONS`] newer than the version recorded in its `schema_version`
+//! table. Every migration runs in a transaction of its own, together with
+//! recording its version, so a failed migration leaves the database at the
+//! previous version.
+//!
+//! Migrations only ever move forward. To change the schema, update
+//! `schema.sql` for new databases and append a migration making the same
+//! change to existing ones.
+
+use rusqlite::{Connection, params};
 
 use crate::error::{DatabaseResultExt, PlannerError, Result};
 
+/// Columns added to the summary views after their initial definition. Views
+/// lacking any of these are dropped and recreated.
+const SUMMARY_VIEW_COLUMNS: &[&str] = &[
+    "subpath",
+    "oldest_in_progress_at",
+    "parent_plan_id",
+    "child_total_steps",
+    "auto_archive",
+    "status_labels",
+    "awaiting_verification_steps",
+    "due_date",
+];
+
+/// A change bringing the schema from the previous version to `version`.
+struct Migration {
+    version: i64,
+    description: &'static str,
+    apply: fn(&Connection) -> Result<()>,
+}
+
+/// Every migration, in the order they are applied.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "add what the releases before versioning added",
+        apply: add_unversioned_columns,
+    },
+    Migration {
+        version: 2,
+        description: "add step dependencies",
+        apply: create_step_dependencies,
+    },
+    Migration {
+        version: 3,
+        description: "add step notes",
+        apply: create_step_notes,
+    },
+    Migration {
+        version: 4,
+        description: "move removed steps to a trash",
+        apply: add_step_deleted_at,
+    },
+    Migration {
+        version: 5,
+        description: "add plan due dates",
+        apply: add_plan_due_date,
+    },
+    Migration {
+        version: 6,
+        description: "track when steps are started and completed",
+        apply: add_step_times,
+    },
+    Migration {
+        version: 7,
+        description: "store step references in their own table",
+        apply: move_step_references,
+    },
+    Migration {
+        version: 8,
+        description: "log changes to plans and steps",
+        apply: create_events,
+    },
+    Migration {
+        version: 9,
+        description: "add step templates",
+        apply: create_templates,
+    },
+    Migration {
+        version: 10,
+        description: "add plan notes",
+        apply: add_plan_notes,
+    },
+    Migration {
+        version: 11,
+        description: "classify step references",
+        apply: add_reference_kind,
+    },
+    Migration {
+        version: 12,
+        description: "add the blocked step status",
+        apply: add_blocked_status,
+    },
+    Migration {
+        version: 13,
+        description: "enforce unique titles of deduplicated plans",
+        apply: add_unique_plan_titles,
+    },
+    Migration {
+        version: 14,
+        description: "update steps when their blocked reason changes",
+        apply: watch_blocked_reason,
+    },
+];
+
+/// Version of the schema that opening a database brings it to: that of the
+/// last migration. Readers skipping initialization compare it with the
+/// recorded version to know whether a database is up to date.
+pub const SCHEMA_VERSION: i64 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+const CREATE_SCHEMA_VERSION_TABLE_SQL: &str = "CREATE TABLE IF NOT EXISTS schema_version (
+    version INTEGER PRIMARY KEY,
+    applied_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+)";
+const RECORD_SCHEMA_VERSION_SQL: &str =
+    "INSERT OR IGNORE INTO schema_version (version) VALUES (?1)";
+const SELECT_SCHEMA_VERSION_SQL: &str = "SELECT MAX(version) FROM schema_version";
+const WIDEN_STEP_STATUS_CHECK_SQL: &str = "UPDATE sqlite_master SET sql = replace(sql, 'CHECK(status IN (''todo'', ''inprogress'', ''done''))', 'CHECK(status IN (''todo'', ''inprogress'', ''done'', ''blocked''))') WHERE type = 'table' AND name = 'steps' AND instr(sql, 'CHECK(status IN (''todo'', ''inprogress'', ''done''))') > 0";
+
 impl super::Database {
-    /// Initializes the database schema using the embedded SQL file.
+    /// Initializes the database schema, creating it for a new database and
+    /// applying pending migrations to an existing one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::SchemaVersion` if the database was written by
+    /// a newer version of beacon
     pub(super) fn initialize_schema(&self) -> Result<()> {
         // Enable foreign keys for this connection
         self.connection
             .execute("PRAGMA foreign_keys = ON", [])
             .db_context("Failed to enable foreign keys")?;
 
-        // Execute the schema SQL
-        let schema_sql = include_str!("../../assets/schema.sql");
+        // Let readers and a writer on other connections proceed side by
+        // side; the mode is stored in the file, so later connections use it
+        self.connection
+            .pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))
+            .db_context("Failed to enable write-ahead logging")?;
+
+        let is_new = !table_exists(&self.connection, "plans")?;
+        let version = self.schema_version()?;
+        if version > SCHEMA_VERSION {
+            return Err(PlannerError::SchemaVersion {
+                found: version,
+                supported: SCHEMA_VERSION,
+            });
+        }
+
         self.connection
-            .execute_batch(schema_sql)
-            .db_context("Failed to initialize database schema")?;
+            .execute(CREATE_SCHEMA_VERSION_TABLE_SQL, [])
+            .db_context("Failed to create schema_version table")?;
 
-        // Apply migrations for existing databases
-        self.apply_migrations()?;
+        if is_new {
+            // schema.sql already has every migration's changes
+            let schema_sql = include_str!("../../assets/schema.sql");
+            self.connection
+                .execute_batch(schema_sql)
+                .db_context("Failed to initialize database schema")?;
+            record_version(&self.connection, SCHEMA_VERSION)?;
+        } else {
+            // Databases from before the table keep their version in
+            // user_version until it is recorded
+            if version > 0 {
+                record_version(&self.connection, version)?;
+            }
+            for migration in MIGRATIONS.iter().filter(|m| m.version > version) {
+                self.apply_migration(migration)?;
+            }
+        }
+
+        self.drop_outdated_views()?;
+
+        // Create views now that all columns they reference exist
+        let views_sql = include_str!("../../assets/views.sql");
+        self.connection
+            .execute_batch(views_sql)
+            .db_context("Failed to create database views")?;
+
+        // Maintain updated_at in the database rather than in every writer
+        let triggers_sql = include_str!("../../assets/triggers.sql");
+        self.connection
+            .execute_batch(triggers_sql)
+            .db_context("Failed to create database triggers")?;
 
         Ok(())
     }
 
-    /// Apply database migrations for existing databases
-    fn apply_migrations(&self) -> Result<()> {
-        // Check if result column exists in steps table
-        let has_result_column: bool = self
+    /// Returns the schema version recorded in the database, 0 if it was
+    /// never initialized.
+    ///
+    /// Databases initialized before versions were recorded in the
+    /// `schema_version` table report the version kept in `user_version`.
+    pub fn schema_version(&self) -> Result<i64> {
+        if table_exists(&self.connection, "schema_version")?
+            && let Some(version) = self
+                .connection
+                .query_row(SELECT_SCHEMA_VERSION_SQL, [], |row| row.get(0))
+                .db_context("Failed to query schema version")?
+        {
+            return Ok(version);
+        }
+        self.connection
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .db_context("Failed to query schema version")
+    }
+
+    /// Applies `migration` and records its version in one transaction.
+    ///
+    /// Another connection may have applied it since the version was read,
+    /// in which case it is skipped.
+    fn apply_migration(&self, migration: &Migration) -> Result<()> {
+        let tx = self
             .connection
-            .query_row(
-                "SELECT COUNT(*) FROM pragma_table_info('steps') WHERE name = 'result'",
-                [],
-                |row| row.get(0),
-            )
-            .map(|count: i64| count > 0)
-            .unwrap_or(false);
-
-        // Add result column if it doesn't exist
-        if !has_result_column {
-            self.connection
-                .execute("ALTER TABLE steps ADD COLUMN result TEXT", [])
-                .map_err(|e| {
-                    PlannerError::database_error("Failed to add result column to steps table", e)
-                })?;
+            .unchecked_transaction()
+            .db_context("Failed to begin migration transaction")?;
+
+        let applied: Option<i64> = tx
+            .query_row(SELECT_SCHEMA_VERSION_SQL, [], |row| row.get(0))
+            .db_context("Failed to query schema version")?;
+        if applied.is_some_and(|applied| applied >= migration.version) {
+            return Ok(());
         }
 
+        (migration.apply)(&tx).map_err(|e| match e {
+            PlannerError::Database { message, source } => PlannerError::Database {
+                message: format!(
+                    "Migration {} ({}) failed: {message}",
+                    migration.version, migration.description
+                ),
+                source,
+            },
+            e => e,
+        })?;
+        record_version(&tx, migration.version)?;
+
+        tx.commit().db_context("Failed to commit migration")
+    }
+
+    /// Drops summary views missing columns added since they were created so
+    /// that they get recreated with the current definition.
+    fn drop_outdated_views(&self) -> Result<()> {
+        let views_outdated = SUMMARY_VIEW_COLUMNS
+            .iter()
+            .any(|column| !has_column(&self.connection, "plan_summaries", column));
+
+        if views_outdated {
+            self.connection
+                .execute_batch(
+                    "DROP VIEW IF EXISTS plan_summaries; DROP VIEW IF EXISTS all_plan_summaries;",
+                )
+                .db_context("Failed to drop outdated plan summary views")?;
+        }
         Ok(())
     }
 }
+
+/// Records that the schema is at `version`.
+fn record_version(conn: &Connection, version: i64) -> Result<()> {
+    conn.execute(RECORD_SCHEMA_VERSION_SQL, params![version])
+        .db_context("Failed to record schema version")?;
+    Ok(())
+}
+
+/// Whether the database has a table named `table`.
+fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [table],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+    .db_context("Failed to look up table")
+}
+
+/// Whether `table` has a column named `column`.
+fn has_column(conn: &Connection, table: &str, column: &str) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        [table, column],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+    .unwrap_or(false)
+}
+
+/// Adds `column` to `table` unless it is already there.
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<()> {
+    if !has_column(conn, table, column) {
+        conn.execute(
+            &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+            [],
+        )
+        .db_context(&format!("Failed to add {column} column to {table} table"))?;
+    }
+    Ok(())
+}
+
+/// Version 1: the columns, tables and indexes added before schema versions
+/// were recorded.
+///
+/// Databases of that time may have any subset of them, so each is only
+/// added if missing.
+fn add_unversioned_columns(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_plans_directory_title ON plans(directory, title);",
+    )
+    .db_context("Failed to create settings table")?;
+
+    add_column_if_missing(conn, "steps", "result", "TEXT")?;
+
+    add_column_if_missing(conn, "plans", "subpath", "TEXT")?;
+    add_column_if_missing(
+        conn,
+        "plans",
+        "parent_plan_id",
+        "INTEGER REFERENCES plans(id) ON DELETE SET NULL",
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_plans_parent_plan_id ON plans(parent_plan_id)",
+        [],
+    )
+    .db_context("Failed to create parent plan index")?;
+    add_column_if_missing(conn, "plans", "auto_archive", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "plans", "status_labels", "TEXT")?;
+    add_column_if_missing(
+        conn,
+        "plans",
+        "merged_into_plan_id",
+        "INTEGER REFERENCES plans(id) ON DELETE SET NULL",
+    )?;
+
+    add_column_if_missing(conn, "steps", "phase", "TEXT")?;
+    add_column_if_missing(conn, "steps", "estimate_minutes", "INTEGER")?;
+    add_column_if_missing(
+        conn,
+        "steps",
+        "verification_required",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_column_if_missing(conn, "steps", "verified_by", "TEXT")?;
+    add_column_if_missing(conn, "steps", "verification_comment", "TEXT")?;
+    add_column_if_missing(conn, "steps", "verified_at", "TEXT")?;
+
+    // The step triggers name the columns they watch, so they are dropped to
+    // be recreated watching expected_output too
+    if !has_column(conn, "steps", "expected_output") {
+        conn.execute_batch(
+            "ALTER TABLE steps ADD COLUMN expected_output TEXT;
+             DROP TRIGGER IF EXISTS steps_touch_on_update;
+             DROP TRIGGER IF EXISTS steps_touch_plan_on_update;",
+        )
+        .db_context("Failed to add expected_output column to steps table")?;
+    }
+    Ok(())
+}
+
+/// Version 2: a row means step_id cannot be claimed until
+/// depends_on_step_id is done.
+fn create_step_dependencies(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS step_dependencies (
+            step_id INTEGER NOT NULL REFERENCES steps(id) ON DELETE CASCADE,
+            depends_on_step_id INTEGER NOT NULL REFERENCES steps(id) ON DELETE CASCADE,
+            PRIMARY KEY (step_id, depends_on_step_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_step_dependencies_depends_on
+            ON step_dependencies(depends_on_step_id);",
+    )
+    .db_context("Failed to create step_dependencies table")
+}
+
+/// Version 3: notes recorded while working on a step.
+fn create_step_notes(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS step_notes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            step_id INTEGER NOT NULL REFERENCES steps(id) ON DELETE CASCADE,
+            body TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_step_notes_step_id ON step_notes(step_id);",
+    )
+    .db_context("Failed to create step_notes table")
+}
+
+/// Version 4: when a step was moved to the trash. The summary views and the
+/// plan trigger leave out trashed steps by it, so they are dropped to be
+/// recreated.
+fn add_step_deleted_at(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE steps ADD COLUMN deleted_at TEXT;
+         DROP VIEW IF EXISTS plan_summaries;
+         DROP VIEW IF EXISTS all_plan_summaries;
+         DROP TRIGGER IF EXISTS steps_touch_plan_on_update;",
+    )
+    .db_context("Failed to add deleted_at column to steps table")
+}
+
+/// Version 5: the date a plan is due. The plan trigger names the columns it
+/// watches, so it is dropped to be recreated watching it too.
+fn add_plan_due_date(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE plans ADD COLUMN due_date TEXT;
+         DROP TRIGGER IF EXISTS plans_touch_on_update;",
+    )
+    .db_context("Failed to add due_date column to plans table")
+}
+
+/// Version 6: when a step was started and completed. Steps completed before
+/// they were tracked keep them NULL.
+fn add_step_times(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE steps ADD COLUMN started_at TEXT;
+         ALTER TABLE steps ADD COLUMN completed_at TEXT;",
+    )
+    .db_context("Failed to add time tracking columns to steps table")
+}
+
+/// Version 7: references move from a comma-separated column of steps, which
+/// split any reference containing a comma, to rows of their own that can
+/// carry a label.
+///
+/// The step triggers name the dropped column, so they are dropped before it
+/// to be recreated without it.
+fn move_step_references(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS step_references (
+            step_id INTEGER NOT NULL REFERENCES steps(id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            url TEXT NOT NULL,
+            label TEXT,
+            PRIMARY KEY (step_id, position)
+        );",
+    )
+    .db_context("Failed to create step_references table")?;
+
+    let mut select = conn
+        .prepare("SELECT id, step_references FROM steps WHERE step_references IS NOT NULL")
+        .db_context("Failed to prepare references query")?;
+    let rows = select
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })
+        .db_context("Failed to query step references")?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .db_context("Failed to read step references")?;
+
+    let mut insert = conn
+        .prepare("INSERT INTO step_references (step_id, position, url) VALUES (?1, ?2, ?3)")
+        .db_context("Failed to prepare reference insert")?;
+    for (step_id, joined) in rows {
+        let urls = joined.split(',').filter(|url| !url.trim().is_empty());
+        for (position, url) in urls.enumerate() {
+            insert
+                .execute(params![step_id, position as i64, url])
+                .db_context("Failed to move step reference")?;
+        }
+    }
+
+    conn.execute_batch(
+        "DROP TRIGGER IF EXISTS steps_touch_on_update;
+         DROP TRIGGER IF EXISTS steps_touch_plan_on_update;
+         ALTER TABLE steps DROP COLUMN step_references;",
+    )
+    .db_context("Failed to drop step_references column from steps table")
+}
+
+/// Version 8: the append-only log of changes to plans and steps.
+fn create_events(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entity_type TEXT NOT NULL,
+            entity_id INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_events_created_at ON events(created_at);",
+    )
+    .db_context("Failed to create events table")
+}
+
+/// Version 9: named lists of steps to append to plans.
+fn create_templates(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS templates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            created_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS template_steps (
+            template_id INTEGER NOT NULL REFERENCES templates(id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            description TEXT,
+            acceptance_criteria TEXT,
+            PRIMARY KEY (template_id, position)
+        );",
+    )
+    .db_context("Failed to create template tables")
+}
+
+/// Version 10: free-form working notes on a plan. Like the due date, the
+/// column is watched by the plan trigger, which is dropped to be recreated.
+fn add_plan_notes(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE plans ADD COLUMN notes TEXT;
+         DROP TRIGGER IF EXISTS plans_touch_on_update;",
+    )
+    .db_context("Failed to add notes column to plans table")
+}
+
+/// Version 11: what a validated reference points to. References stored
+/// before keep it NULL, as do those stored with validation off.
+fn add_reference_kind(conn: &Connection) -> Result<()> {
+    conn.execute("ALTER TABLE step_references ADD COLUMN kind TEXT", [])
+        .db_context("Failed to add kind column to step_references table")?;
+    Ok(())
+}
+
+/// Version 12: steps can be blocked, with an optional reason. The status
+/// check only allowed the other statuses, and SQLite can't alter a check
+/// constraint; rebuilding the table would cascade to the step's references,
+/// notes and dependencies, so the stored definition is edited in place
+/// instead, as the SQLite documentation describes. The summary views count
+/// blocked steps as pending, so they are dropped to be recreated.
+fn add_blocked_status(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE steps ADD COLUMN blocked_reason TEXT;
+         DROP VIEW IF EXISTS plan_summaries;
+         DROP VIEW IF EXISTS all_plan_summaries;",
+    )
+    .db_context("Failed to add blocked_reason column to steps table")?;
+
+    let schema_version: i64 = conn
+        .pragma_query_value(None, "schema_version", |row| row.get(0))
+        .db_context("Failed to query schema version")?;
+    conn.pragma_update(None, "writable_schema", true)
+        .db_context("Failed to allow editing the schema")?;
+    let widened = conn
+        .execute(WIDEN_STEP_STATUS_CHECK_SQL, [])
+        .db_context("Failed to allow the blocked status")?;
+    conn.pragma_update(None, "schema_version", schema_version + 1)
+        .db_context("Failed to update schema version")?;
+    conn.pragma_update(None, "writable_schema", false)
+        .db_context("Failed to stop editing the schema")?;
+
+    if widened == 0 {
+        return Err(PlannerError::database_error(
+            "Failed to allow the blocked status",
+            rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_SCHEMA),
+                Some("the steps table has an unexpected status check".into()),
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Version 13: plans created with `unique_per_directory` are marked, and a
+/// partial unique index keeps two marked active plans from sharing a
+/// directory and title. Plans created before are unmarked, so existing
+/// duplicates stay valid.
+fn add_unique_plan_titles(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE plans ADD COLUMN unique_title INTEGER NOT NULL DEFAULT 0;
+         CREATE UNIQUE INDEX IF NOT EXISTS idx_plans_unique_directory_title
+             ON plans(IFNULL(directory, ''), title) WHERE status = 'active' AND unique_title = 1;",
+    )
+    .db_context("Failed to add unique_title column to plans table")
+}
+
+/// Version 14: the step triggers watch the blocked reason, which version 12
+/// added without dropping them, so they are dropped to be recreated.
+fn watch_blocked_reason(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP TRIGGER IF EXISTS steps_touch_on_update;
+         DROP TRIGGER IF EXISTS steps_touch_plan_on_update;",
+    )
+    .db_context("Failed to drop step triggers")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_are_in_order() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, index as i64 + 1);
+        }
+        assert_eq!(SCHEMA_VERSION, MIGRATIONS.len() as i64);
+    }
+}
@@ -4,29 +4,392 @@
 //! planning system. It handles SQLite database connections, schema management,
 //! and provides specialized query interfaces for plans and steps.
 
-use std::path::Path;
+#[cfg(feature = "encryption")]
+use std::fmt;
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
 
-use rusqlite::Connection;
+use jiff::{RoundMode, Timestamp, TimestampRound, Unit};
+use rusqlite::{
+    Connection, ErrorCode, InterruptHandle, OpenFlags, Transaction, TransactionBehavior,
+};
 
-use crate::error::{DatabaseResultExt, Result};
+use crate::error::{DatabaseResultExt, PlannerError, Result};
 
+pub mod dependency_queries;
+pub mod event_queries;
+pub mod import_queries;
+pub mod maintenance_queries;
 pub mod migrations;
+pub mod note_queries;
 pub mod plan_queries;
+pub(crate) mod pool;
+pub mod reference_queries;
+pub mod report_queries;
+pub mod settings_queries;
+pub mod stats_queries;
 pub mod step_queries;
+pub mod template_queries;
+pub mod trash_queries;
 pub mod utils;
 
+/// Default time a connection waits for another connection's lock before
+/// failing as busy.
+pub const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Database path selecting an in-memory database, as in SQLite.
+pub const IN_MEMORY_PATH: &str = ":memory:";
+
 /// Database connection and operations handler.
 pub struct Database {
     connection: Connection,
+    /// Whether directories are resolved through symlinks when they exist
+    canonicalize_paths: bool,
 }
 
 impl Database {
     /// Creates a new database connection and initializes the schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::Encryption` if the file is encrypted
+    /// Returns `PlannerError::SchemaVersion` if the database was written by a
+    /// newer version of beacon
+    ///
+    /// The database is switched to write-ahead logging, so that readers and
+    /// a writer on other connections don't block each other, and waits up to
+    /// [`DEFAULT_BUSY_TIMEOUT`] for locks held by other connections.
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let connection = Self::open_connection(path.as_ref(), DEFAULT_BUSY_TIMEOUT)?;
+        Self::from_connection(connection, false, true)
+    }
+
+    /// Opens a connection waiting up to `busy_timeout` for locks held by
+    /// other connections.
+    fn open_connection(path: &Path, busy_timeout: Duration) -> Result<Connection> {
         let connection = Connection::open(path).db_context("Failed to open database connection")?;
+        connection
+            .busy_timeout(busy_timeout)
+            .db_context("Failed to set busy timeout")?;
+        Ok(connection)
+    }
+
+    /// Opens an encrypted database with the given key, creating it if it does
+    /// not exist, and initializes the schema.
+    ///
+    /// The key is applied with `PRAGMA key` before any other statement runs.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::Encryption` if the key does not match the one
+    /// the database was created with
+    #[cfg(feature = "encryption")]
+    pub fn new_encrypted<P: AsRef<Path>>(path: P, key: &str) -> Result<Self> {
+        let connection = Self::open_connection(path.as_ref(), DEFAULT_BUSY_TIMEOUT)?;
+        connection
+            .pragma_update(None, "key", key)
+            .db_context("Failed to set encryption key")?;
+        Self::from_connection(connection, true, true)
+    }
+
+    /// Opens an existing database read-only, without initializing the
+    /// schema.
+    ///
+    /// Skipping initialization is only safe on a database some earlier open
+    /// brought up to date, so this fails unless the recorded schema version
+    /// is [`SCHEMA_VERSION`](migrations::SCHEMA_VERSION).
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::Database` if the database does not exist or
+    /// cannot be read
+    /// Returns `PlannerError::SchemaVersion` if its schema is newer than
+    /// this version of beacon supports
+    /// Returns `PlannerError::Configuration` if its schema is older
+    pub fn open_read_only<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_read_only_connection(Self::read_only_connection(path.as_ref())?)
+    }
+
+    /// Opens a read-only connection to an existing database.
+    fn read_only_connection(path: &Path) -> Result<Connection> {
+        Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .db_context("Failed to open database read-only")
+    }
+
+    /// Wraps a read-only connection, checking that the schema is current.
+    fn from_read_only_connection(connection: Connection) -> Result<Self> {
+        let db = Self {
+            connection,
+            canonicalize_paths: true,
+        };
+        let version = db.schema_version()?;
+        if version > migrations::SCHEMA_VERSION {
+            return Err(PlannerError::SchemaVersion {
+                found: version,
+                supported: migrations::SCHEMA_VERSION,
+            });
+        }
+        if version != migrations::SCHEMA_VERSION {
+            return Err(PlannerError::Configuration {
+                message: format!(
+                    "Database schema version is {version}, expected {}",
+                    migrations::SCHEMA_VERSION
+                ),
+            });
+        }
+        Ok(db)
+    }
+
+    /// Wraps a newly opened connection, initializing the schema if
+    /// `initialize` is set and otherwise only enabling foreign keys.
+    ///
+    /// Transactions on the connection take the write lock when they begin,
+    /// so that they wait for other writers within the busy timeout instead
+    /// of failing when a read turns into a write.
+    fn from_connection(mut connection: Connection, keyed: bool, initialize: bool) -> Result<Self> {
+        connection.set_transaction_behavior(TransactionBehavior::Immediate);
 
-        let db = Self { connection };
-        db.initialize_schema()?;
+        // The first read is where SQLCipher reports a missing or wrong key
+        if let Err(e) = connection.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(())) {
+            return Err(match e.sqlite_error_code() {
+                Some(ErrorCode::NotADatabase) if keyed => PlannerError::Encryption {
+                    message: "Wrong encryption key for database".to_string(),
+                },
+                Some(ErrorCode::NotADatabase) => PlannerError::Encryption {
+                    message: "Database is encrypted or corrupt; an encryption key is required"
+                        .to_string(),
+                },
+                _ => PlannerError::database("Failed to read database").with_source(e),
+            });
+        }
+
+        let db = Self {
+            connection,
+            canonicalize_paths: true,
+        };
+        if initialize {
+            db.initialize_schema()?;
+        } else {
+            db.connection
+                .execute("PRAGMA foreign_keys = ON", [])
+                .db_context("Failed to enable foreign keys")?;
+        }
         Ok(db)
     }
+
+    /// Sets whether plan and search directories that exist are resolved
+    /// through symlinks to their canonical path, as by
+    /// [`std::fs::canonicalize`], before they are stored or compared.
+    /// Enabled by default; when disabled, directories are only made absolute
+    /// and normalized.
+    pub fn set_path_canonicalization(&mut self, enabled: bool) {
+        self.canonicalize_paths = enabled;
+    }
+
+    /// Returns the version of the SQLite library in use, as reported by
+    /// `sqlite3_libversion`.
+    pub fn sqlite_version() -> &'static str {
+        rusqlite::version()
+    }
+
+    /// Returns the journal mode of the database, such as `wal` or `delete`.
+    pub fn journal_mode(&self) -> Result<String> {
+        self.connection
+            .pragma_query_value(None, "journal_mode", |row| row.get(0))
+            .db_context("Failed to query journal mode")
+    }
+
+    /// Returns the number of rows inserted, updated or deleted on this
+    /// connection since it was opened.
+    pub(crate) fn total_changes(&self) -> u64 {
+        self.connection.total_changes()
+    }
+
+    /// Checkpoints the write-ahead log and truncates it, before closing a
+    /// connection to a database file that was replaced on disk.
+    ///
+    /// SQLite keeps the log of a file moved away in place when closing, and
+    /// a connection to the file now at that path would replay it. Failures
+    /// are ignored, since the connection is being discarded anyway.
+    pub(crate) fn discard_write_ahead_log(&self) {
+        let _ = self
+            .connection
+            .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()));
+    }
+
+    /// Returns a handle that interrupts the statement running on this
+    /// connection, from any thread.
+    pub(crate) fn interrupt_handle(&self) -> InterruptHandle {
+        self.connection.get_interrupt_handle()
+    }
+
+    /// Returns the underlying connection, for tests running raw SQL.
+    #[cfg(test)]
+    pub(crate) fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Runs `read` inside a single deferred read transaction, so that all of
+    /// its queries observe the same snapshot of the database while other
+    /// connections write. Writes committed meanwhile are only seen by later
+    /// reads.
+    ///
+    /// If a transaction is already open, `read` simply runs within it.
+    pub(crate) fn read_consistent<T>(&self, read: impl FnOnce(&Self) -> Result<T>) -> Result<T> {
+        if !self.connection.is_autocommit() {
+            return read(self);
+        }
+
+        let tx = Transaction::new_unchecked(&self.connection, TransactionBehavior::Deferred)
+            .db_context("Failed to begin read transaction")?;
+        let value = read(self)?;
+        tx.commit().db_context("Failed to end read transaction")?;
+        Ok(value)
+    }
+}
+
+/// Format timestamps are stored in: UTC at millisecond precision, as the
+/// `updated_at` triggers write them.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3fZ";
+
+/// Returns the current time at the precision timestamps are stored at, so
+/// that it compares consistently with the times triggers write.
+pub(crate) fn now() -> Timestamp {
+    Timestamp::now()
+        .round(
+            TimestampRound::new()
+                .smallest(Unit::Millisecond)
+                .mode(RoundMode::Trunc),
+        )
+        .expect("truncating the current time to milliseconds stays in range")
+}
+
+/// Formats `timestamp` as it is stored in the database.
+pub(crate) fn format_timestamp(timestamp: Timestamp) -> String {
+    timestamp.strftime(TIMESTAMP_FORMAT).to_string()
+}
+
+/// Everything needed to open a connection to the planner database.
+///
+/// Connections are opened by the planner's
+/// [`ConnectionPool`](pool::ConnectionPool), which also picks up a database
+/// file replaced on disk.
+#[derive(Clone)]
+pub(crate) struct DatabaseConfig {
+    pub(crate) path: PathBuf,
+    /// URI of the shared in-memory database connections open instead of
+    /// `path`, if any
+    pub(crate) memory_uri: Option<String>,
+    /// Open connections read-only, without initializing the schema
+    pub(crate) read_only: bool,
+    /// How long connections wait for locks held by other connections
+    pub(crate) busy_timeout: Duration,
+    /// Resolve existing directories through symlinks
+    pub(crate) canonicalize_paths: bool,
+    #[cfg(feature = "encryption")]
+    pub(crate) encryption_key: Option<EncryptionKey>,
+}
+
+impl DatabaseConfig {
+    /// Configuration of a new in-memory database, private to the planner
+    /// whose connections share it.
+    pub(crate) fn in_memory(busy_timeout: Duration, canonicalize_paths: bool) -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        Self {
+            path: PathBuf::from(IN_MEMORY_PATH),
+            memory_uri: Some(format!("file:beacon-memory-{id}?mode=memory&cache=shared")),
+            read_only: false,
+            busy_timeout,
+            canonicalize_paths,
+            #[cfg(feature = "encryption")]
+            encryption_key: None,
+        }
+    }
+
+    /// Whether connections open a shared in-memory database.
+    pub(crate) fn is_in_memory(&self) -> bool {
+        self.memory_uri.is_some()
+    }
+
+    /// What connections open: the database file, or the in-memory
+    /// database's URI.
+    fn target(&self) -> &Path {
+        self.memory_uri.as_deref().map_or(&self.path, Path::new)
+    }
+
+    /// Opens a connection, applying the encryption key if one is configured,
+    /// and initializes the schema.
+    ///
+    /// A read-only connection instead requires the database to exist with
+    /// a current schema, as [`Database::open_read_only`] does.
+    pub(crate) fn open(&self) -> Result<Database> {
+        if self.read_only {
+            let connection = Database::read_only_connection(self.target())?;
+            connection
+                .busy_timeout(self.busy_timeout)
+                .db_context("Failed to set busy timeout")?;
+            #[cfg(feature = "encryption")]
+            if let Some(key) = &self.encryption_key {
+                connection
+                    .pragma_update(None, "key", &key.0)
+                    .db_context("Failed to set encryption key")?;
+            }
+            return Database::from_read_only_connection(connection).map(|db| self.configure(db));
+        }
+
+        let connection = Database::open_connection(self.target(), self.busy_timeout)?;
+        #[cfg(feature = "encryption")]
+        if let Some(key) = &self.encryption_key {
+            connection
+                .pragma_update(None, "key", &key.0)
+                .db_context("Failed to set encryption key")?;
+            return Database::from_connection(connection, true, true).map(|db| self.configure(db));
+        }
+        Database::from_connection(connection, false, true).map(|db| self.configure(db))
+    }
+
+    /// Opens a connection like [`Self::open`] to a database whose schema an
+    /// earlier connection already initialized.
+    pub(crate) fn connect(&self) -> Result<Database> {
+        if self.read_only {
+            return self.open();
+        }
+
+        let connection = Database::open_connection(self.target(), self.busy_timeout)?;
+        #[cfg(feature = "encryption")]
+        if let Some(key) = &self.encryption_key {
+            connection
+                .pragma_update(None, "key", &key.0)
+                .db_context("Failed to set encryption key")?;
+            return Database::from_connection(connection, true, false).map(|db| self.configure(db));
+        }
+        Database::from_connection(connection, false, false).map(|db| self.configure(db))
+    }
+
+    /// Applies the options that live on the database handle rather than the
+    /// connection.
+    fn configure(&self, mut db: Database) -> Database {
+        db.set_path_canonicalization(self.canonicalize_paths);
+        db
+    }
+}
+
+/// Database encryption key, redacted from debug output.
+#[cfg(feature = "encryption")]
+#[derive(Clone)]
+pub(crate) struct EncryptionKey(pub(crate) String);
+
+#[cfg(feature = "encryption")]
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptionKey(<redacted>)")
+    }
 }
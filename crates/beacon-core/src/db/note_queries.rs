@@ -0,0 +1,111 @@
+//! Notes recorded on steps while working on them.
+
+use std::collections::HashMap;
+
+use jiff::Timestamp;
+use rusqlite::{Connection, params, types::Type};
+
+use crate::{
+    error::{DatabaseResultExt, PlannerError, Result},
+    models::{EventKind, StepNote},
+};
+
+const INSERT_STEP_NOTE_SQL: &str =
+    "INSERT INTO step_notes (step_id, body, created_at) VALUES (?1, ?2, ?3)";
+const SELECT_STEP_NOTES_SQL: &str = "SELECT id, step_id, body, created_at FROM step_notes WHERE step_id = ?1 ORDER BY created_at, id";
+const SELECT_PLAN_NOTES_SQL: &str = "SELECT n.id, n.step_id, n.body, n.created_at FROM step_notes n JOIN steps s ON s.id = n.step_id WHERE s.plan_id = ?1 ORDER BY n.created_at, n.id";
+
+impl super::Database {
+    /// Adds a note to a step.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist
+    /// Returns `PlannerError::InvalidInput` if the body is empty
+    pub fn add_step_note(&mut self, step_id: u64, body: &str) -> Result<StepNote> {
+        let body = body.trim();
+        if body.is_empty() {
+            return Err(PlannerError::invalid_input("body").with_reason("A note cannot be empty"));
+        }
+
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+
+        Self::check_step_exists(&tx, step_id)?;
+
+        let now = super::now();
+        tx.execute(
+            INSERT_STEP_NOTE_SQL,
+            params![step_id as i64, body, super::format_timestamp(now)],
+        )
+        .db_context("Failed to add step note")?;
+        let id = tx.last_insert_rowid() as u64;
+        Self::record_step_event(&tx, step_id, EventKind::Updated)?;
+
+        tx.commit().db_context("Failed to commit transaction")?;
+
+        Ok(StepNote {
+            id,
+            step_id,
+            body: body.to_string(),
+            created_at: now,
+        })
+    }
+
+    /// Retrieves the notes of a step, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist
+    pub fn get_step_notes(&self, step_id: u64) -> Result<Vec<StepNote>> {
+        Self::check_step_exists(&self.connection, step_id)?;
+        Self::load_step_notes(&self.connection, step_id)
+    }
+
+    /// Returns the notes of a step, oldest first.
+    pub(super) fn load_step_notes(conn: &Connection, step_id: u64) -> Result<Vec<StepNote>> {
+        let mut stmt = conn
+            .prepare(SELECT_STEP_NOTES_SQL)
+            .db_context("Failed to prepare notes query")?;
+
+        stmt.query_map(params![step_id as i64], Self::build_note_from_row)
+            .db_context("Failed to query step notes")?
+            .collect::<rusqlite::Result<_>>()
+            .db_context("Failed to read step notes")
+    }
+
+    /// Returns the notes of every step of a plan that has any, oldest first,
+    /// keyed by step ID.
+    pub(super) fn load_plan_notes(
+        conn: &Connection,
+        plan_id: u64,
+    ) -> Result<HashMap<u64, Vec<StepNote>>> {
+        let mut stmt = conn
+            .prepare(SELECT_PLAN_NOTES_SQL)
+            .db_context("Failed to prepare notes query")?;
+
+        let mut notes = HashMap::<u64, Vec<StepNote>>::new();
+        let rows = stmt
+            .query_map(params![plan_id as i64], Self::build_note_from_row)
+            .db_context("Failed to query step notes")?;
+        for row in rows {
+            let note = row.db_context("Failed to read step note")?;
+            notes.entry(note.step_id).or_default().push(note);
+        }
+
+        Ok(notes)
+    }
+
+    fn build_note_from_row(row: &rusqlite::Row) -> rusqlite::Result<StepNote> {
+        Ok(StepNote {
+            id: row.get::<_, i64>(0)? as u64,
+            step_id: row.get::<_, i64>(1)? as u64,
+            body: row.get(2)?,
+            created_at: row.get::<_, String>(3)?.parse::<Timestamp>().map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(3, Type::Text, Box::new(e))
+            })?,
+        })
+    }
+}
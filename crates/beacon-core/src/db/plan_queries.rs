@@ -1,30 +1,379 @@
 //! Plan CRUD operations and queries.
 
-use jiff::Timestamp;
-use rusqlite::{OptionalExtension, params, types::Type};
+use std::{collections::HashMap, path::Path, time::Duration};
 
+use jiff::{Timestamp, civil::Date};
+use rusqlite::{
+    Connection, OptionalExtension, Transaction, TransactionBehavior, params, types::Type,
+};
+
+use super::{step_queries::STEP_ORDER_SPACING, utils::subdirectory_pattern};
 use crate::{
     error::{DatabaseResultExt, PlannerError, Result},
-    models::{CompletionFilter, Plan, PlanFilter, PlanStatus},
+    models::{
+        CompletionFilter, CreatePlanRequest, CreateStepRequest, EventKind, FilterStage,
+        MergeOptions, PLAN_LISTING_ORDER, Page, Plan, PlanFilter, PlanStatus, PlanSummary,
+        StatusLabels, UpdatePlanRequest,
+    },
 };
 
+/// A plan row from the summary view together with its step statistics.
+struct PlanRow {
+    plan: Plan,
+    total_steps: i64,
+    completed_steps: i64,
+    oldest_in_progress_at: Option<Timestamp>,
+    child_plans: i64,
+    child_total_steps: i64,
+    child_completed_steps: i64,
+    awaiting_verification_steps: i64,
+    in_progress_steps: i64,
+}
+
+/// SQL conditions of a plan filter, grouped into the stages of filtering
+/// that explanations report.
+struct FilterConditions {
+    conditions: Vec<String>,
+    params: Vec<Box<dyn rusqlite::ToSql>>,
+    /// Name of each stage, with the number of conditions and parameters at
+    /// its end
+    stage_ends: Vec<(&'static str, usize, usize)>,
+}
+
+impl FilterConditions {
+    fn new(filter: Option<&PlanFilter>) -> Self {
+        let mut conditions = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        let mut stage_ends = Vec::new();
+
+        if let Some(f) = filter {
+            // Filter by specific status if provided
+            if let Some(status) = f.status() {
+                conditions.push("status = ?".to_string());
+                params.push(Box::new(status.as_str().to_string()));
+            }
+            stage_ends.push(("status", conditions.len(), params.len()));
+
+            // Plans in any of the directories match, and plans below them
+            // unless subdirectories are excluded
+            let directories = f.directories();
+            if !directories.is_empty() {
+                let matcher = if f.include_subdirectories() {
+                    r"(directory = ? OR directory LIKE ? ESCAPE '\')"
+                } else {
+                    "directory = ?"
+                };
+                let group = vec![matcher; directories.len()].join(" OR ");
+                conditions.push(format!("({group})"));
+                for directory in directories {
+                    params.push(Box::new(directory.to_string()));
+                    if f.include_subdirectories() {
+                        params.push(Box::new(subdirectory_pattern(directory)));
+                    }
+                }
+            }
+            stage_ends.push(("directory", conditions.len(), params.len()));
+
+            if let Some(title) = f.title_contains() {
+                conditions.push("title LIKE ?".to_string());
+                params.push(Box::new(format!("%{title}%")));
+            }
+
+            if let Some(after) = f.created_after() {
+                conditions.push("created_at >= ?".to_string());
+                params.push(Box::new(after.to_string()));
+            }
+
+            if let Some(before) = f.created_before() {
+                conditions.push("created_at <= ?".to_string());
+                params.push(Box::new(before.to_string()));
+            }
+
+            if let Some(after) = f.updated_after() {
+                conditions.push("updated_at >= ?".to_string());
+                params.push(Box::new(after.to_string()));
+            }
+
+            if let Some(due) = f.due_before() {
+                conditions.push("due_date <= ?".to_string());
+                params.push(Box::new(due.to_string()));
+            }
+
+            if let Some(parent_id) = f.parent_plan_id() {
+                conditions.push("parent_plan_id = ?".to_string());
+                params.push(Box::new(parent_id as i64));
+            }
+        }
+        stage_ends.push(("title, dates and parent", conditions.len(), params.len()));
+
+        if let Some(completion) = filter.and_then(PlanFilter::completion_status) {
+            conditions.push(
+                match completion {
+                    CompletionFilter::Complete => {
+                        "total_steps > 0 AND completed_steps = total_steps"
+                    }
+                    CompletionFilter::Incomplete => {
+                        "total_steps > 0 AND completed_steps < total_steps"
+                    }
+                    CompletionFilter::Empty => "total_steps = 0",
+                }
+                .to_string(),
+            );
+        }
+        stage_ends.push(("completion", conditions.len(), params.len()));
+
+        Self {
+            conditions,
+            params,
+            stage_ends,
+        }
+    }
+
+    /// The `WHERE` clause combining the first `count` conditions.
+    fn where_clause(&self, count: usize) -> String {
+        if count == 0 {
+            String::new()
+        } else {
+            format!(" WHERE {}", self.conditions[..count].join(" AND "))
+        }
+    }
+
+    /// The first `count` parameters.
+    fn params(&self, count: usize) -> Vec<&dyn rusqlite::ToSql> {
+        self.params[..count].iter().map(|b| &**b).collect()
+    }
+}
+
+/// The `LIMIT` clause selecting the page of a filter, if it is paged.
+fn page_clause(filter: Option<&PlanFilter>) -> String {
+    match filter.map(|f| (f.limit(), f.offset())) {
+        Some((Some(limit), offset)) => format!(" LIMIT {limit} OFFSET {}", offset.unwrap_or(0)),
+        // SQLite needs a limit to skip rows; a negative one means no limit
+        Some((None, Some(offset))) => format!(" LIMIT -1 OFFSET {offset}"),
+        _ => String::new(),
+    }
+}
+
+/// Separates the descriptions of merged plans and the notes recording merges.
+const MERGE_SEPARATOR: &str = "\n\n---\n\n";
+
+/// Joins the present, non-empty description parts with [`MERGE_SEPARATOR`].
+fn join_descriptions<'a>(parts: impl IntoIterator<Item = Option<&'a str>>) -> String {
+    parts
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(MERGE_SEPARATOR)
+}
+
+/// Elapsed time between `since` and `now`, clamped to zero for timestamps in
+/// the future (e.g. clock skew between writers).
+fn age_since(since: Timestamp, now: Timestamp) -> Duration {
+    Duration::try_from(now.duration_since(since)).unwrap_or_default()
+}
+
 // Optimized SQL queries as const strings for compile-time optimization
-const INSERT_PLAN_SQL: &str = "INSERT INTO plans (title, description, directory, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)";
-const SELECT_PLAN_SQL: &str = "SELECT id, title, description, status, directory, created_at, updated_at FROM plans WHERE id = ?1";
+const INSERT_PLAN_SQL: &str = "INSERT INTO plans (title, description, directory, subpath, parent_plan_id, auto_archive, due_date, created_at, updated_at, unique_title) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)";
+const SELECT_PLAN_SQL: &str = "SELECT id, title, description, status, directory, created_at, updated_at, subpath, parent_plan_id, auto_archive, status_labels, due_date FROM plans WHERE id = ?1";
+const SELECT_STATUS_LABELS_BY_STEP_SQL: &str =
+    "SELECT p.status_labels FROM plans p JOIN steps s ON s.plan_id = p.id WHERE s.id = ?1";
+const SELECT_PLAN_UPDATED_AT_SQL: &str = "SELECT updated_at FROM plans WHERE id = ?1";
+const SELECT_PLAN_PARENT_SQL: &str = "SELECT parent_plan_id FROM plans WHERE id = ?1";
+const COUNT_CHILD_PLANS_SQL: &str = "SELECT COUNT(*) FROM plans WHERE parent_plan_id = ?1";
+const SELECT_ACTIVE_CHILD_IDS_SQL: &str =
+    "SELECT id FROM plans WHERE parent_plan_id = ?1 AND status = ?2 ORDER BY id";
+const CLONE_PLAN_SQL: &str = "INSERT INTO plans (title, description, directory, subpath, auto_archive, status_labels, created_at, updated_at) SELECT COALESCE(?2, title), description, directory, subpath, auto_archive, status_labels, ?3, ?3 FROM plans WHERE id = ?1";
+const SELECT_STEP_IDS_BY_PLAN_SQL: &str =
+    "SELECT id FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL ORDER BY step_order, id";
+const CLONE_STEP_SQL: &str = "INSERT INTO steps (plan_id, title, description, acceptance_criteria, expected_output, status, result, step_order, phase, estimate_minutes, verification_required, created_at, updated_at) SELECT ?1, title, description, acceptance_criteria, expected_output, 'todo', NULL, step_order, phase, estimate_minutes, verification_required, ?3, ?3 FROM steps WHERE id = ?2";
+const UPDATE_PLAN_SQL: &str = "UPDATE plans SET title = ?1, description = ?2, directory = ?3, subpath = ?4, parent_plan_id = ?5, auto_archive = ?6, status_labels = ?7, due_date = ?8 WHERE id = ?9";
+pub(super) const SELECT_PLAN_ID_BY_DIRECTORY_TITLE_SQL: &str = "SELECT id FROM plans WHERE directory IS ?1 AND title = ?2 AND status = 'active' ORDER BY id LIMIT 1";
 const CHECK_PLAN_EXISTS_SQL: &str = "SELECT EXISTS(SELECT 1 FROM plans WHERE id = ?1)";
-const UPDATE_PLAN_ARCHIVE_SQL: &str =
-    "UPDATE plans SET status = ?1, updated_at = ?2 WHERE id = ?3 AND status = ?4";
+const UPDATE_PLAN_ARCHIVE_SQL: &str = "UPDATE plans SET status = ?1 WHERE id = ?2 AND status = ?3";
 const UPDATE_PLAN_UNARCHIVE_SQL: &str =
-    "UPDATE plans SET status = ?1, updated_at = ?2 WHERE id = ?3 AND status = ?4";
+    "UPDATE plans SET status = ?1 WHERE id = ?2 AND status = ?3";
+const REASSIGN_DIRECTORY_SQL: &str = "UPDATE plans SET directory = ?2 || substr(directory, ?3) WHERE directory = ?1 OR directory LIKE ?4 ESCAPE '\\' RETURNING id";
+const UPDATE_PLAN_TIMESTAMPS_SQL: &str =
+    "UPDATE plans SET created_at = ?1, updated_at = ?2 WHERE id = ?3";
+const SELECT_PLAN_MERGED_INTO_SQL: &str = "SELECT merged_into_plan_id FROM plans WHERE id = ?1";
+const SELECT_PLAN_NOTES_SQL: &str = "SELECT notes FROM plans WHERE id = ?1";
+pub(super) const APPEND_PLAN_NOTE_SQL: &str = "UPDATE plans SET notes = CASE WHEN notes IS NULL THEN ?2 ELSE notes || char(10) || char(10) || ?2 END WHERE id = ?1";
+pub(super) const SET_PLAN_NOTES_SQL: &str = "UPDATE plans SET notes = ?2 WHERE id = ?1";
+const SELECT_STEP_UPDATED_AT_BY_PLAN_SQL: &str =
+    "SELECT id, updated_at FROM steps WHERE plan_id = ?1";
+const SELECT_STEP_ORDER_RANGE_SQL: &str =
+    "SELECT MIN(step_order), MAX(step_order) FROM steps WHERE plan_id = ?1";
+const MOVE_PLAN_STEPS_SQL: &str = "UPDATE steps SET plan_id = ?1, step_order = step_order + ?2, title = COALESCE(?3 || title, title) WHERE plan_id = ?4";
+const RESTORE_STEP_UPDATED_AT_SQL: &str = "UPDATE steps SET updated_at = ?1 WHERE id = ?2";
+const REPARENT_CHILD_PLANS_SQL: &str =
+    "UPDATE plans SET parent_plan_id = ?1 WHERE parent_plan_id = ?2 AND id != ?1";
+const UPDATE_MERGED_TARGET_SQL: &str =
+    "UPDATE plans SET description = ?1, parent_plan_id = ?2 WHERE id = ?3";
+const UPDATE_MERGED_SOURCE_SQL: &str =
+    "UPDATE plans SET description = ?1, status = ?2, merged_into_plan_id = ?3 WHERE id = ?4";
 const DELETE_PLAN_STEPS_SQL: &str = "DELETE FROM steps WHERE plan_id = ?1";
+const COUNT_PENDING_PLAN_STEPS_SQL: &str =
+    "SELECT COUNT(*) FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL AND status != 'done'";
 const DELETE_PLAN_SQL: &str = "DELETE FROM plans WHERE id = ?1";
+const COUNT_ACTIVE_PLAN_STEPS_SQL: &str = "SELECT COALESCE(SUM(s.status = 'inprogress'), 0), COALESCE(SUM(s.status IN ('todo', 'blocked')), 0) FROM steps s JOIN plans p ON p.id = s.plan_id WHERE s.plan_id = ?1 AND p.status = 'active' AND s.deleted_at IS NULL";
 
 // Base queries for plan listing
-const PLAN_SUMMARY_COLUMNS: &str = "id, title, description, status, directory, created_at, updated_at, total_steps, completed_steps, pending_steps";
+const PLAN_SUMMARY_COLUMNS: &str = "id, title, description, status, directory, created_at, updated_at, subpath, parent_plan_id, auto_archive, status_labels, due_date, total_steps, completed_steps, pending_steps, oldest_in_progress_at, child_plans, child_total_steps, child_completed_steps, awaiting_verification_steps, in_progress_steps";
 const PLAN_SUMMARIES_VIEW: &str = "plan_summaries";
 const ALL_PLAN_SUMMARIES_VIEW: &str = "all_plan_summaries";
 
 impl super::Database {
+    /// Helper function to construct a Plan from a database row.
+    ///
+    /// Expects the columns in the order of [`SELECT_PLAN_SQL`]; steps are left
+    /// empty for the caller to load.
+    fn build_plan_from_row(row: &rusqlite::Row) -> rusqlite::Result<Plan> {
+        let status_str: String = row.get(3)?;
+        let status = status_str.parse::<PlanStatus>().map_err(|_| {
+            rusqlite::Error::FromSqlConversionFailure(
+                3,
+                Type::Text,
+                Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Invalid plan status: {status_str}"),
+                )),
+            )
+        })?;
+
+        Ok(Plan {
+            id: row.get::<_, i64>(0)? as u64,
+            title: row.get(1)?,
+            description: row.get(2)?,
+            status,
+            directory: row.get(4)?,
+            subpath: row.get(7)?,
+            parent_plan_id: row.get::<_, Option<i64>>(8)?.map(|id| id as u64),
+            auto_archive: row.get(9)?,
+            status_labels: Self::status_labels_from_row(row, 10)?,
+            merged_into_plan_id: None,
+            notes: None,
+            due_date: row
+                .get::<_, Option<String>>(11)?
+                .map(|date| date.parse::<Date>())
+                .transpose()
+                .map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(11, Type::Text, Box::new(e))
+                })?,
+            created_at: row.get::<_, String>(5)?.parse::<Timestamp>().map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(5, Type::Text, Box::new(e))
+            })?,
+            updated_at: row.get::<_, String>(6)?.parse::<Timestamp>().map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(6, Type::Text, Box::new(e))
+            })?,
+            steps: Vec::new(),
+            child_plans: Vec::new(),
+        })
+    }
+
+    /// Checks that `parent_id` may become the parent of `plan_id` (or of a
+    /// new plan when `plan_id` is `None`).
+    ///
+    /// Nesting is limited to one level: the parent must not itself have a
+    /// parent, and a plan that already has children cannot become a child.
+    fn validate_parent(conn: &Connection, plan_id: Option<u64>, parent_id: u64) -> Result<()> {
+        let invalid =
+            |reason: String| PlannerError::invalid_input("parent_plan_id").with_reason(reason);
+
+        if plan_id == Some(parent_id) {
+            return Err(invalid("A plan cannot be its own parent".into()));
+        }
+
+        let grandparent: Option<i64> = conn
+            .query_row(SELECT_PLAN_PARENT_SQL, params![parent_id as i64], |row| {
+                row.get(0)
+            })
+            .optional()
+            .db_context("Failed to look up parent plan")?
+            .ok_or_else(|| invalid(format!("Parent plan {parent_id} does not exist")))?;
+
+        if grandparent.is_some() {
+            return Err(invalid(format!(
+                "Plan {parent_id} is itself a child plan; plans can only be nested one level deep"
+            )));
+        }
+
+        if let Some(plan_id) = plan_id {
+            let children: i64 = conn
+                .query_row(COUNT_CHILD_PLANS_SQL, params![plan_id as i64], |row| {
+                    row.get(0)
+                })
+                .db_context("Failed to count child plans")?;
+
+            if children > 0 {
+                return Err(invalid(format!(
+                    "Plan {plan_id} has child plans and cannot become a child plan itself"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolves the directory and subpath stored for a plan in `directory`,
+    /// defaulting to the current working directory.
+    ///
+    /// The directory is always made absolute. With `infer_git_root`, a
+    /// directory inside a git work tree is split into the repository root and
+    /// the path below it.
+    pub(super) fn plan_location(
+        &self,
+        directory: Option<&str>,
+        infer_git_root: bool,
+    ) -> Result<(Option<String>, Option<String>)> {
+        Ok(match self.ensure_absolute_directory(directory)? {
+            Some(dir) if infer_git_root => Self::split_at_git_root(&dir),
+            other => (other, None),
+        })
+    }
+
+    /// The plan that `plan_id` was merged into, if any.
+    pub(super) fn merged_into(conn: &Connection, plan_id: u64) -> Result<Option<u64>> {
+        let merged_into: Option<Option<i64>> = conn
+            .query_row(
+                SELECT_PLAN_MERGED_INTO_SQL,
+                params![plan_id as i64],
+                |row| row.get(0),
+            )
+            .optional()
+            .db_context("Failed to look up merged plan")?;
+        Ok(merged_into.flatten().map(|id| id as u64))
+    }
+
+    /// The working notes of `plan_id`, if it has any.
+    pub(super) fn plan_notes(conn: &Connection, plan_id: u64) -> Result<Option<String>> {
+        let notes: Option<Option<String>> = conn
+            .query_row(SELECT_PLAN_NOTES_SQL, params![plan_id as i64], |row| {
+                row.get(0)
+            })
+            .optional()
+            .db_context("Failed to look up plan notes")?;
+        Ok(notes.flatten())
+    }
+
+    /// Loads a plan's steps, notes, the summaries of its child plans, and the
+    /// plan it was merged into.
+    fn load_plan_details(&self, plan: &mut Plan) -> Result<()> {
+        plan.steps = self.get_steps(plan.id)?;
+        plan.notes = Self::plan_notes(&self.connection, plan.id)?;
+        plan.merged_into_plan_id = Self::merged_into(&self.connection, plan.id)?;
+
+        let filter = PlanFilter::builder()
+            .parent_plan_id(plan.id)
+            .include_archived(true)
+            .build()?;
+        let mut children = self.list_plan_summaries(Some(&filter))?;
+        children.sort_by_key(|child| child.id);
+        plan.child_plans = children;
+
+        Ok(())
+    }
+
     /// Creates a new plan with the given title, optional description, and
     /// directory. The directory path will always be stored as an absolute path.
     /// If a relative path is provided, it will be converted to absolute using
@@ -36,226 +385,694 @@ impl super::Database {
         description: Option<&str>,
         directory: Option<&str>,
     ) -> Result<Plan> {
+        self.create_plan_with(&CreatePlanRequest {
+            title: title.into(),
+            description: description.map(String::from),
+            directory: directory.map(String::from),
+            ..Default::default()
+        })
+    }
+
+    /// Creates a new plan unless an active one with the same title already
+    /// exists in the same directory, in which case the existing plan is
+    /// returned instead. Archived plans are not matched.
+    ///
+    /// The lookup and insert run in an immediate transaction so that two
+    /// concurrent retries of the same request cannot both insert, and a
+    /// unique index on the active plans created this way rejects a duplicate
+    /// from any other writer. The directory is normalized exactly as in
+    /// [`Self::create_plan`] before the comparison.
+    pub fn create_plan_unique(
+        &mut self,
+        title: &str,
+        description: Option<&str>,
+        directory: Option<&str>,
+    ) -> Result<Plan> {
+        self.create_plan_with(&CreatePlanRequest {
+            title: title.into(),
+            description: description.map(String::from),
+            directory: directory.map(String::from),
+            unique_per_directory: true,
+            ..Default::default()
+        })
+    }
+
+    /// Creates a new plan from a [`CreatePlanRequest`].
+    ///
+    /// The directory is made absolute as in [`Self::create_plan`]. When
+    /// `infer_git_root` is set and the directory lies inside a git work tree,
+    /// the repository root is stored as the plan directory and the path below
+    /// it is kept as the plan's `subpath`. When `unique_per_directory` is set,
+    /// behaves like [`Self::create_plan_unique`].
+    pub fn create_plan_with(&mut self, request: &CreatePlanRequest) -> Result<Plan> {
+        self.create_plan_with_steps(request, &[])
+    }
+
+    /// Returns when a plan last changed, or None if it doesn't exist.
+    ///
+    /// Triggers keep a plan's `updated_at` current on changes to the plan
+    /// and to any of its steps, including reorders and removals.
+    pub fn plan_updated_at(&self, id: u64) -> Result<Option<Timestamp>> {
+        self.connection
+            .query_row(SELECT_PLAN_UPDATED_AT_SQL, params![id as i64], |row| {
+                row.get::<_, String>(0)?.parse::<Timestamp>().map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(0, Type::Text, Box::new(e))
+                })
+            })
+            .optional()
+            .db_context("Failed to query plan update time")
+    }
+
+    /// Reads a plan's own row on `conn`, which may be in a transaction,
+    /// without its steps or child plans.
+    pub(super) fn read_plan_row(conn: &Connection, id: u64) -> Result<Option<Plan>> {
+        conn.query_row(
+            SELECT_PLAN_SQL,
+            params![id as i64],
+            Self::build_plan_from_row,
+        )
+        .optional()
+        .db_context("Failed to query plan")
+    }
+
+    /// Creates a new plan together with its initial steps in one transaction.
+    ///
+    /// The plan is created as in [`Self::create_plan_with`] and the steps are
+    /// appended in the given order. Either the plan and all of its steps are
+    /// stored, or nothing is. When `unique_per_directory` finds an existing
+    /// plan, that plan is returned unchanged and no steps are added.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if a done step has no result
+    pub fn create_plan_with_steps(
+        &mut self,
+        request: &CreatePlanRequest,
+        steps: &[CreateStepRequest],
+    ) -> Result<Plan> {
+        let (directory, subpath) =
+            self.plan_location(request.directory.as_deref(), request.infer_git_root)?;
+
+        let behavior = if request.unique_per_directory {
+            TransactionBehavior::Immediate
+        } else {
+            TransactionBehavior::Deferred
+        };
         let tx = self
             .connection
-            .transaction()
+            .transaction_with_behavior(behavior)
             .db_context("Failed to begin transaction")?;
 
-        let now = Timestamp::now();
-        let now_str = now.to_string();
-
-        // Ensure directory is always absolute
-        let directory = Self::ensure_absolute_directory(directory)?;
+        if request.unique_per_directory {
+            let existing_id: Option<i64> = tx
+                .query_row(
+                    SELECT_PLAN_ID_BY_DIRECTORY_TITLE_SQL,
+                    params![directory.as_deref(), &request.title],
+                    |row| row.get(0),
+                )
+                .optional()
+                .db_context("Failed to look up existing plan")?;
 
-        tx.execute(
-            INSERT_PLAN_SQL,
-            params![title, description, directory.as_deref(), &now_str, &now_str],
-        )
-        .map_err(|e| PlannerError::database_error("Failed to insert plan", e))?;
+            if let Some(id) = existing_id {
+                tx.commit().db_context("Failed to commit transaction")?;
+                return self
+                    .get_plan(id as u64)?
+                    .ok_or(PlannerError::PlanNotFound { id: id as u64 });
+            }
+        }
 
-        let id = tx.last_insert_rowid() as u64;
+        let now = super::now();
+        let id = Self::insert_plan_row(
+            &tx,
+            request,
+            directory.as_deref(),
+            subpath.as_deref(),
+            steps,
+            &super::format_timestamp(now),
+        )?;
 
         tx.commit().db_context("Failed to commit transaction")?;
 
+        let steps = if steps.is_empty() {
+            Vec::new()
+        } else {
+            self.get_steps(id)?
+        };
+
         Ok(Plan {
             id,
-            title: title.into(),
-            description: description.map(String::from),
+            title: request.title.clone(),
+            description: request.description.clone(),
             status: PlanStatus::Active,
             directory,
+            subpath,
+            parent_plan_id: request.parent_plan_id,
+            auto_archive: request.auto_archive,
+            status_labels: StatusLabels::default(),
+            merged_into_plan_id: None,
+            notes: None,
+            due_date: request.due_date,
             created_at: now,
             updated_at: now,
-            steps: Vec::new(),
+            steps,
+            child_plans: Vec::new(),
         })
     }
 
-    /// Retrieves a plan by its ID.
+    /// Inserts a plan with its steps at the resolved `directory` and
+    /// `subpath`, recording their creation, and returns the plan's ID. The
+    /// caller owns the transaction.
+    pub(super) fn insert_plan_row(
+        conn: &Connection,
+        request: &CreatePlanRequest,
+        directory: Option<&str>,
+        subpath: Option<&str>,
+        steps: &[CreateStepRequest],
+        now: &str,
+    ) -> Result<u64> {
+        if let Some(parent_id) = request.parent_plan_id {
+            Self::validate_parent(conn, None, parent_id)?;
+        }
+
+        conn.execute(
+            INSERT_PLAN_SQL,
+            params![
+                &request.title,
+                request.description.as_deref(),
+                directory,
+                subpath,
+                request.parent_plan_id.map(|id| id as i64),
+                request.auto_archive,
+                request.due_date.map(|date| date.to_string()),
+                now,
+                now,
+                request.unique_per_directory
+            ],
+        )
+        .map_err(|e| PlannerError::database_error("Failed to insert plan", e))?;
+
+        let id = conn.last_insert_rowid() as u64;
+
+        Self::record_plan_event(conn, id, EventKind::Created)?;
+        for step in steps {
+            let step_id = Self::insert_step_row(conn, id, step, now)?;
+            Self::record_step_event(conn, step_id, EventKind::Added)?;
+        }
+
+        Ok(id)
+    }
+
+    /// Reads the JSON-encoded status labels in column `idx`, treating NULL
+    /// as no custom labels.
+    fn status_labels_from_row(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<StatusLabels> {
+        row.get::<_, Option<String>>(idx)?
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(idx, Type::Text, Box::new(e)))
+            .map(Option::unwrap_or_default)
+    }
+
+    /// Retrieves the status labels of the plan a step belongs to.
+    ///
+    /// Returns the default labels if the step doesn't exist.
+    pub fn get_step_status_labels(&self, step_id: u64) -> Result<StatusLabels> {
+        let labels = self
+            .connection
+            .query_row(
+                SELECT_STATUS_LABELS_BY_STEP_SQL,
+                params![step_id as i64],
+                |row| Self::status_labels_from_row(row, 0),
+            )
+            .optional()
+            .map_err(|e| PlannerError::database_error("Failed to query status labels", e))?;
+
+        Ok(labels.unwrap_or_default())
+    }
+
+    /// Retrieves a plan by its ID, together with its steps and child plans.
+    ///
+    /// The plan, its steps and its children are read from one snapshot, so
+    /// they agree even while other connections are writing.
     pub fn get_plan(&self, id: u64) -> Result<Option<Plan>> {
+        self.read_consistent(|db| db.read_plan(id))
+    }
+
+    /// Reads a plan and its details with one query each, outside of any
+    /// snapshot of their own.
+    fn read_plan(&self, id: u64) -> Result<Option<Plan>> {
         let mut stmt = self
             .connection
             .prepare(SELECT_PLAN_SQL)
             .map_err(|e| PlannerError::database_error("Failed to prepare query", e))?;
 
         let mut plan = stmt
-            .query_row(params![id as i64], |row| {
-                let status_str: String = row.get(3)?;
-                let status = status_str.parse::<PlanStatus>().map_err(|_| {
-                    rusqlite::Error::FromSqlConversionFailure(
-                        3,
-                        Type::Text,
-                        Box::new(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            format!("Invalid plan status: {status_str}"),
-                        )),
-                    )
-                })?;
-
-                Ok(Plan {
-                    id: row.get::<_, i64>(0)? as u64,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    status,
-                    directory: row.get(4)?,
-                    created_at: row.get::<_, String>(5)?.parse::<Timestamp>().map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(5, Type::Text, Box::new(e))
-                    })?,
-                    updated_at: row.get::<_, String>(6)?.parse::<Timestamp>().map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(6, Type::Text, Box::new(e))
-                    })?,
-                    steps: Vec::new(),
-                })
-            })
+            .query_row(params![id as i64], Self::build_plan_from_row)
             .optional()
             .map_err(|e| PlannerError::database_error("Failed to query plan", e))?;
 
-        // Eagerly load steps if plan exists
+        #[cfg(test)]
+        if let Some(mut hook) = tests::AFTER_PLAN_ROW_READ.take() {
+            hook();
+        }
+
+        // Eagerly load steps and child plans if plan exists
         if let Some(ref mut plan) = plan {
-            plan.steps = self.get_steps(plan.id)?;
+            self.load_plan_details(plan)?;
         }
 
         Ok(plan)
     }
 
-    /// Lists all plans with optional filtering.
-    pub fn list_plans(&self, filter: Option<&PlanFilter>) -> Result<Vec<Plan>> {
-        // Choose the appropriate view based on whether we want to include archived
-        // plans
-        let view_name = if filter.as_ref().is_some_and(|f| f.include_archived) {
-            ALL_PLAN_SUMMARIES_VIEW
+    /// Updates a plan's title, description, directory, or parent.
+    ///
+    /// Fields the request leaves unset keep their current values, so an empty
+    /// request changes nothing. A new directory is resolved as in
+    /// [`Self::create_plan_with`], replacing the plan's subpath.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    /// Returns `PlannerError::InvalidInput` if the new parent would nest plans
+    /// more than one level deep, or if the plan was created with
+    /// `unique_per_directory` and its new title or directory clashes with
+    /// another such active plan
+    pub fn update_plan(&mut self, id: u64, request: &UpdatePlanRequest) -> Result<Plan> {
+        let location = request
+            .directory
+            .as_deref()
+            .map(|dir| self.plan_location(Some(dir), request.infer_git_root))
+            .transpose()?;
+
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+
+        let current = tx
+            .query_row(
+                SELECT_PLAN_SQL,
+                params![id as i64],
+                Self::build_plan_from_row,
+            )
+            .optional()
+            .map_err(|e| PlannerError::database_error("Failed to query plan", e))?
+            .ok_or(PlannerError::PlanNotFound { id })?;
+
+        if request.is_empty() {
+            drop(tx);
+            return self.get_plan(id)?.ok_or(PlannerError::PlanNotFound { id });
+        }
+
+        if let Some(Some(parent_id)) = request.parent_plan_id {
+            Self::validate_parent(&tx, Some(id), parent_id)?;
+        }
+
+        // Use provided values or keep current ones
+        let title = request.title.as_ref().unwrap_or(&current.title);
+        let description = request
+            .description
+            .as_ref()
+            .or(current.description.as_ref());
+        let (directory, subpath) = location.unwrap_or((current.directory, current.subpath));
+        let parent_plan_id = request.parent_plan_id.unwrap_or(current.parent_plan_id);
+        let auto_archive = request.auto_archive.unwrap_or(current.auto_archive);
+        let due_date = request.due_date.unwrap_or(current.due_date);
+        let status_labels = request
+            .status_labels
+            .as_ref()
+            .unwrap_or(&current.status_labels);
+        status_labels
+            .validate()
+            .map_err(|reason| PlannerError::invalid_input("status_labels").with_reason(reason))?;
+        let status_labels = if status_labels.is_empty() {
+            None
         } else {
-            PLAN_SUMMARIES_VIEW
+            Some(serde_json::to_string(status_labels).map_err(|e| {
+                PlannerError::invalid_input("status_labels").with_reason(e.to_string())
+            })?)
         };
 
-        let mut query = format!("SELECT {PLAN_SUMMARY_COLUMNS} FROM {view_name}");
+        tx.execute(
+            UPDATE_PLAN_SQL,
+            params![
+                title,
+                description,
+                directory,
+                subpath,
+                parent_plan_id.map(|id| id as i64),
+                auto_archive,
+                status_labels,
+                due_date.map(|date| date.to_string()),
+                id as i64
+            ],
+        )
+        .map_err(|e| {
+            Self::unique_title_conflict(
+                PlannerError::database_error("Failed to update plan", e),
+                title,
+                directory.as_deref(),
+            )
+        })?;
+        Self::record_plan_event(&tx, id, EventKind::Updated)?;
 
-        let mut conditions = Vec::new();
-        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        tx.commit().db_context("Failed to commit transaction")?;
 
-        if let Some(f) = filter {
-            if let Some(ref title) = f.title_contains {
-                conditions.push("title LIKE ?");
-                params_vec.push(Box::new(format!("%{title}%")));
-            }
+        self.get_plan(id)?.ok_or(PlannerError::PlanNotFound { id })
+    }
 
-            if let Some(ref directory) = f.directory {
-                conditions.push("directory LIKE ?");
-                params_vec.push(Box::new(format!("{directory}%")));
+    /// Moves every plan whose directory is `from` or below it to the
+    /// corresponding path under `to`, archived plans included, and returns
+    /// the number of plans moved.
+    ///
+    /// Both directories are canonicalized as for a search, so `from` need
+    /// not exist any more. Plans are matched on their stored directory, which
+    /// is the repository root for plans whose git root was inferred.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if either directory is empty, or
+    /// if `to` is `from`, inside it or contains it, where moved plans would
+    /// be mixed up with the ones already there.
+    pub fn reassign_directory(&mut self, from: &str, to: &str) -> Result<u32> {
+        for (field, directory) in [("from", from), ("to", to)] {
+            if directory.trim().is_empty() {
+                return Err(PlannerError::invalid_input(field)
+                    .with_reason("A directory cannot be empty or only whitespace"));
             }
+        }
+        let from = self.canonicalize_directory_for_search(from)?;
+        let to = self.canonicalize_directory_for_search(to)?;
+        if Path::new(&to).starts_with(&from) || Path::new(&from).starts_with(&to) {
+            return Err(PlannerError::invalid_input("to").with_reason(format!(
+                "'{to}' overlaps '{from}'; plans can only be moved to a separate directory"
+            )));
+        }
 
-            if let Some(ref after) = f.created_after {
-                conditions.push("created_at >= ?");
-                params_vec.push(Box::new(after.to_string()));
-            }
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+        let moved = tx
+            .prepare(REASSIGN_DIRECTORY_SQL)
+            .db_context("Failed to prepare directory update")?
+            .query_map(
+                params![
+                    from,
+                    to,
+                    from.chars().count() as i64 + 1,
+                    subdirectory_pattern(&from)
+                ],
+                |row| row.get::<_, i64>(0),
+            )
+            .and_then(Iterator::collect::<rusqlite::Result<Vec<_>>>)
+            .db_context("Failed to reassign plan directories")?;
+        for id in &moved {
+            Self::record_plan_event(&tx, *id as u64, EventKind::Updated)?;
+        }
+        tx.commit().db_context("Failed to commit transaction")?;
 
-            if let Some(ref before) = f.created_before {
-                conditions.push("created_at <= ?");
-                params_vec.push(Box::new(before.to_string()));
-            }
+        Ok(moved.len() as u32)
+    }
 
-            // Filter by specific status if provided
-            if let Some(ref status) = f.status {
-                conditions.push("status = ?");
-                params_vec.push(Box::new(status.as_str().to_string()));
-            }
+    /// Appends `text` to a plan's notes under a heading with the current
+    /// time, and returns the updated plan.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    /// Returns `PlannerError::InvalidInput` if `text` is empty
+    pub fn append_plan_note(&mut self, id: u64, text: &str) -> Result<Plan> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(PlannerError::invalid_input("text").with_reason("A note cannot be empty"));
         }
 
-        if !conditions.is_empty() {
-            query.push_str(" WHERE ");
-            query.push_str(&conditions.join(" AND "));
+        let entry = Self::plan_note_entry(text);
+        self.write_plan_notes(id, APPEND_PLAN_NOTE_SQL, Some(&entry))
+    }
+
+    /// Formats `text` as an entry of a plan's notes, under a heading with
+    /// the current time.
+    pub(super) fn plan_note_entry(text: &str) -> String {
+        format!(
+            "### {}\n\n{text}",
+            super::now().strftime("%Y-%m-%d %H:%M:%S UTC")
+        )
+    }
+
+    /// Replaces a plan's notes with `text`, removing them if it is empty,
+    /// and returns the updated plan.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    pub fn set_plan_notes(&mut self, id: u64, text: &str) -> Result<Plan> {
+        let text = text.trim();
+        let notes = (!text.is_empty()).then_some(text);
+        self.write_plan_notes(id, SET_PLAN_NOTES_SQL, notes)
+    }
+
+    /// Runs `sql` to change the notes of plan `id` with `notes`, and reads
+    /// the plan back.
+    fn write_plan_notes(&mut self, id: u64, sql: &str, notes: Option<&str>) -> Result<Plan> {
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+        let updated = tx
+            .execute(sql, params![id as i64, notes])
+            .db_context("Failed to update plan notes")?;
+        if updated == 0 {
+            return Err(PlannerError::PlanNotFound { id });
+        }
+        Self::record_plan_event(&tx, id, EventKind::Updated)?;
+        tx.commit().db_context("Failed to commit transaction")?;
+
+        self.get_plan(id)?.ok_or(PlannerError::PlanNotFound { id })
+    }
+
+    /// Runs the plan summary view query for the given filter, returning each
+    /// plan with its step statistics. Steps are not loaded.
+    ///
+    /// If `stages` is given, the plans remaining after each stage of
+    /// filtering are counted into it.
+    fn query_plan_rows(
+        &self,
+        filter: Option<&PlanFilter>,
+        stages: Option<&mut Vec<FilterStage>>,
+    ) -> Result<Vec<PlanRow>> {
+        // Choose the appropriate view based on whether we want to include archived
+        // plans
+        let view_name = if filter.is_some_and(PlanFilter::include_archived) {
+            ALL_PLAN_SUMMARIES_VIEW
+        } else {
+            PLAN_SUMMARIES_VIEW
+        };
+
+        let conditions = FilterConditions::new(filter);
+
+        if let Some(stages) = stages {
+            for &(name, condition_count, param_count) in &conditions.stage_ends {
+                stages.push(FilterStage {
+                    name: name.to_string(),
+                    plans: self.count_matching(
+                        view_name,
+                        &conditions,
+                        condition_count,
+                        param_count,
+                    )?,
+                });
+            }
         }
 
-        query.push_str(" ORDER BY created_at DESC");
+        let query = format!(
+            "SELECT {PLAN_SUMMARY_COLUMNS} FROM {view_name}{} ORDER BY {PLAN_LISTING_ORDER}{}",
+            conditions.where_clause(conditions.conditions.len()),
+            page_clause(filter)
+        );
 
         let mut stmt = self
             .connection
             .prepare(&query)
             .map_err(|e| PlannerError::database_error("Failed to prepare query", e))?;
 
-        let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|b| &**b).collect();
-
-        let plans_with_counts: Vec<(Plan, i64, i64)> = stmt
-            .query_map(&params_refs[..], |row| {
-                let status_str: String = row.get(3)?;
-                let status = status_str.parse::<PlanStatus>().map_err(|_| {
-                    rusqlite::Error::FromSqlConversionFailure(
-                        3,
-                        Type::Text,
-                        Box::new(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            format!("Invalid plan status: {status_str}"),
-                        )),
-                    )
-                })?;
-
-                let total_steps: i64 = row.get(7)?;
-                let completed_steps: i64 = row.get(8)?;
-                let _pending_steps: i64 = row.get(9)?; // Not used but part of the view
-
-                let plan = Plan {
-                    id: row.get::<_, i64>(0)? as u64,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    status,
-                    directory: row.get(4)?,
-                    created_at: row.get::<_, String>(5)?.parse::<Timestamp>().map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(5, Type::Text, Box::new(e))
-                    })?,
-                    updated_at: row.get::<_, String>(6)?.parse::<Timestamp>().map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(6, Type::Text, Box::new(e))
-                    })?,
-                    steps: Vec::new(),
-                };
-                Ok((plan, total_steps, completed_steps))
-            })
+        let rows: Vec<PlanRow> = stmt
+            .query_map(
+                &conditions.params(conditions.params.len())[..],
+                Self::build_plan_row,
+            )
             .map_err(|e| PlannerError::database_error("Failed to query plans", e))?
             .collect::<std::result::Result<Vec<_>, _>>()
             .map_err(|e| PlannerError::database_error("Failed to fetch plans", e))?;
 
-        // Apply completion filter if specified
-        if let Some(f) = filter
-            && let Some(ref completion) = f.completion_status
-        {
-            let filtered_plans =
-                self.filter_by_completion_with_counts(plans_with_counts, completion);
-            // Eagerly load steps for each filtered plan using functional approach
-            return filtered_plans
+        Ok(rows)
+    }
+
+    /// Counts the plans of a summary view matching the first
+    /// `condition_count` conditions.
+    fn count_matching(
+        &self,
+        view_name: &str,
+        conditions: &FilterConditions,
+        condition_count: usize,
+        param_count: usize,
+    ) -> Result<usize> {
+        let query = format!(
+            "SELECT COUNT(*) FROM {view_name}{}",
+            conditions.where_clause(condition_count)
+        );
+        let plans: i64 = self
+            .connection
+            .query_row(&query, &conditions.params(param_count)[..], |row| {
+                row.get(0)
+            })
+            .db_context("Failed to count plans")?;
+        Ok(plans as usize)
+    }
+
+    /// Counts the plans matching a filter, ignoring its page.
+    pub fn count_plans(&self, filter: Option<&PlanFilter>) -> Result<usize> {
+        let view_name = if filter.is_some_and(PlanFilter::include_archived) {
+            ALL_PLAN_SUMMARIES_VIEW
+        } else {
+            PLAN_SUMMARIES_VIEW
+        };
+        let conditions = FilterConditions::new(filter);
+        self.count_matching(
+            view_name,
+            &conditions,
+            conditions.conditions.len(),
+            conditions.params.len(),
+        )
+    }
+
+    /// Builds a plan row from a summary view row with the columns of
+    /// [`PLAN_SUMMARY_COLUMNS`].
+    fn build_plan_row(row: &rusqlite::Row) -> rusqlite::Result<PlanRow> {
+        let plan = Self::build_plan_from_row(row)?;
+        let total_steps: i64 = row.get(12)?;
+        let completed_steps: i64 = row.get(13)?;
+        let _pending_steps: i64 = row.get(14)?; // Not used but part of the view
+        let oldest_in_progress_at = row
+            .get::<_, Option<String>>(15)?
+            .map(|s| s.parse::<Timestamp>())
+            .transpose()
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(15, Type::Text, Box::new(e)))?;
+
+        Ok(PlanRow {
+            plan,
+            total_steps,
+            completed_steps,
+            oldest_in_progress_at,
+            child_plans: row.get(16)?,
+            child_total_steps: row.get(17)?,
+            child_completed_steps: row.get(18)?,
+            awaiting_verification_steps: row.get(19)?,
+            in_progress_steps: row.get(20)?,
+        })
+    }
+
+    /// Lists all plans with optional filtering, only those on the page of
+    /// the filter if it is paged.
+    ///
+    /// The plans and their steps are read from one snapshot.
+    pub fn list_plans(&self, filter: Option<&PlanFilter>) -> Result<Vec<Plan>> {
+        self.read_consistent(|db| {
+            // Eagerly load steps for each plan using functional approach
+            db.query_plan_rows(filter, None)?
                 .into_iter()
-                .map(|mut plan| {
-                    plan.steps = self.get_steps(plan.id)?;
+                .map(|row| {
+                    let mut plan = row.plan;
+                    plan.steps = db.get_steps(plan.id)?;
                     Ok(plan)
                 })
-                .collect();
-        }
+                .collect()
+        })
+    }
 
-        // If no completion filter, populate steps for each plan and return
-        plans_with_counts
-            .into_iter()
-            .map(|(plan, _, _)| plan)
-            .map(|mut plan| {
-                plan.steps = self.get_steps(plan.id)?;
-                Ok(plan)
+    /// Lists plan summaries with optional filtering.
+    ///
+    /// Step counts and the age of the oldest in-progress step come straight
+    /// from the summary view, so steps are never loaded.
+    pub fn list_plan_summaries(&self, filter: Option<&PlanFilter>) -> Result<Vec<PlanSummary>> {
+        let rows = self.query_plan_rows(filter, None)?;
+        Ok(Self::summarize_rows(rows))
+    }
+
+    /// Lists the page of plan summaries selected by the limit and offset of
+    /// `filter`, together with the number of plans matching it on any page.
+    ///
+    /// An offset beyond the last matching plan gives an empty page.
+    pub fn list_plan_summaries_page(
+        &self,
+        filter: Option<&PlanFilter>,
+    ) -> Result<Page<PlanSummary>> {
+        self.read_consistent(|db| {
+            let items = db.list_plan_summaries(filter)?;
+            let offset = filter.and_then(PlanFilter::offset).unwrap_or(0) as usize;
+            let total = if filter.is_some_and(PlanFilter::is_paged) {
+                db.count_plans(filter)?
+            } else {
+                items.len()
+            };
+            Ok(Page {
+                items,
+                total,
+                offset,
             })
-            .collect()
+        })
     }
 
-    /// Filters plans by completion status using counts from the view.
-    fn filter_by_completion_with_counts(
+    /// Retrieves the summary of a single plan, archived or not, without
+    /// loading its steps.
+    ///
+    /// Returns `None` if the plan doesn't exist.
+    pub fn get_plan_summary(&self, id: u64) -> Result<Option<PlanSummary>> {
+        let query =
+            format!("SELECT {PLAN_SUMMARY_COLUMNS} FROM {ALL_PLAN_SUMMARIES_VIEW} WHERE id = ?1");
+        let row = self
+            .connection
+            .query_row(&query, params![id as i64], Self::build_plan_row)
+            .optional()
+            .db_context("Failed to query plan summary")?;
+        Ok(row.and_then(|row| Self::summarize_rows(vec![row]).pop()))
+    }
+
+    /// Lists plan summaries like [`Self::list_plan_summaries`], also
+    /// returning the plans remaining after each stage of filtering.
+    ///
+    /// Counting the stages takes one extra query per stage.
+    pub fn explain_plan_summaries(
         &self,
-        plans_with_counts: Vec<(Plan, i64, i64)>,
-        filter: &CompletionFilter,
-    ) -> Vec<Plan> {
-        plans_with_counts
-            .into_iter()
-            .filter_map(|(plan, total_steps, completed_steps)| {
-                let should_include = match filter {
-                    CompletionFilter::Complete => total_steps > 0 && total_steps == completed_steps,
-                    CompletionFilter::Incomplete => {
-                        total_steps > 0 && completed_steps < total_steps
-                    }
-                    CompletionFilter::Empty => total_steps == 0,
-                };
+        filter: Option<&PlanFilter>,
+    ) -> Result<(Vec<PlanSummary>, Vec<FilterStage>)> {
+        self.read_consistent(|db| {
+            let mut stages = Vec::new();
+            let rows = db.query_plan_rows(filter, Some(&mut stages))?;
+            Ok((Self::summarize_rows(rows), stages))
+        })
+    }
 
-                should_include.then_some(plan)
+    /// Builds plan summaries from summary view rows.
+    fn summarize_rows(rows: Vec<PlanRow>) -> Vec<PlanSummary> {
+        let now = Timestamp::now();
+
+        rows.into_iter()
+            .map(|row| {
+                let mut summary = PlanSummary::from_plan(
+                    row.plan,
+                    row.total_steps as u32,
+                    row.completed_steps as u32,
+                );
+                summary.oldest_in_progress_age = row
+                    .oldest_in_progress_at
+                    .map(|started| age_since(started, now));
+                summary.child_plans = row.child_plans as u32;
+                summary.child_total_steps = row.child_total_steps as u32;
+                summary.child_completed_steps = row.child_completed_steps as u32;
+                summary.awaiting_verification_steps = row.awaiting_verification_steps as u32;
+                summary.in_progress_steps = row.in_progress_steps as u32;
+                summary
             })
             .collect()
     }
@@ -263,19 +1080,62 @@ impl super::Database {
     /// Archives a plan (soft delete).
     /// Returns the archived plan details if successful, None if the plan
     /// doesn't exist.
-    pub fn archive_plan(&mut self, id: u64) -> Result<Option<Plan>> {
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the plan has active child plans
+    /// and `force` is not set, and `PlannerError::PlanHasActiveSteps` if
+    /// `strict` is set and an active plan has steps that are not done
+    pub fn archive_plan(&mut self, id: u64, force: bool, strict: bool) -> Result<Option<Plan>> {
         let tx = self
             .connection
             .transaction()
             .db_context("Failed to begin transaction")?;
 
-        let now = Timestamp::now().to_string();
+        if strict && !force {
+            let (in_progress, todo): (i64, i64) = tx
+                .query_row(COUNT_ACTIVE_PLAN_STEPS_SQL, params![id as i64], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })
+                .db_context("Failed to count active steps")?;
+            if in_progress + todo > 0 {
+                return Err(PlannerError::PlanHasActiveSteps {
+                    id,
+                    in_progress: in_progress as usize,
+                    todo: todo as usize,
+                });
+            }
+        }
+
+        if !force {
+            let active_children = {
+                let mut stmt = tx
+                    .prepare(SELECT_ACTIVE_CHILD_IDS_SQL)
+                    .map_err(|e| PlannerError::database_error("Failed to prepare query", e))?;
+                stmt.query_map(params![id as i64, PlanStatus::Active.as_str()], |row| {
+                    row.get::<_, i64>(0)
+                })
+                .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+                .map_err(|e| PlannerError::database_error("Failed to query child plans", e))?
+            };
+
+            if !active_children.is_empty() {
+                let ids = active_children
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                return Err(PlannerError::invalid_input("force").with_reason(format!(
+                    "Plan {id} has active child plans ({ids}). Archive them first, or force archiving the parent"
+                )));
+            }
+        }
+
         let rows_affected = tx
             .execute(
                 UPDATE_PLAN_ARCHIVE_SQL,
                 params![
                     PlanStatus::Archived.as_str(),
-                    &now,
                     id as i64,
                     PlanStatus::Active.as_str()
                 ],
@@ -293,72 +1153,177 @@ impl super::Database {
                 return Ok(None);
             }
             // Plan exists but is already archived - still return its details
+        } else {
+            Self::record_plan_event(&tx, id, EventKind::Archived)?;
         }
 
         // Get the updated plan details
         let mut plan = tx
-            .query_row(SELECT_PLAN_SQL, params![id as i64], |row| {
-                let status_str: String = row.get(3)?;
-                let status = status_str.parse::<PlanStatus>().map_err(|_| {
-                    rusqlite::Error::FromSqlConversionFailure(
-                        3,
-                        Type::Text,
-                        Box::new(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            format!("Invalid plan status: {status_str}"),
-                        )),
-                    )
-                })?;
-
-                Ok(Plan {
-                    id: row.get::<_, i64>(0)? as u64,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    status,
-                    directory: row.get(4)?,
-                    created_at: row.get::<_, String>(5)?.parse::<Timestamp>().map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(5, Type::Text, Box::new(e))
-                    })?,
-                    updated_at: row.get::<_, String>(6)?.parse::<Timestamp>().map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(6, Type::Text, Box::new(e))
-                    })?,
-                    steps: Vec::new(),
-                })
-            })
+            .query_row(
+                SELECT_PLAN_SQL,
+                params![id as i64],
+                Self::build_plan_from_row,
+            )
             .optional()
             .map_err(|e| PlannerError::database_error("Failed to query archived plan", e))?;
 
         tx.commit().db_context("Failed to commit transaction")?;
 
-        // Load steps for the plan if it exists
+        // Load steps and child plans for the plan if it exists
         if let Some(ref mut plan) = plan {
-            plan.steps = self.get_steps(plan.id)?;
+            self.load_plan_details(plan)?;
         }
 
         Ok(plan)
     }
 
+    /// Archives every active plan whose steps are all done, optionally only
+    /// those in `directory` or below it, in a single transaction: if any
+    /// plan fails to archive, none are.
+    ///
+    /// Plans without steps are not complete. A complete parent plan is left
+    /// active while any of its child plans stays active, as
+    /// [`Self::archive_plan`] would refuse it. Returns the summaries of the
+    /// archived plans.
+    pub fn archive_completed_plans(&mut self, directory: Option<&str>) -> Result<Vec<PlanSummary>> {
+        let mut filter = PlanFilter::builder().completion(CompletionFilter::Complete);
+        if let Some(directory) = directory {
+            filter = filter.directory(self.canonicalize_directory_for_search(directory)?);
+        }
+        let filter = filter.build()?;
+
+        let tx = Transaction::new_unchecked(&self.connection, TransactionBehavior::Immediate)
+            .db_context("Failed to begin transaction")?;
+
+        let mut summaries = self.list_plan_summaries(Some(&filter))?;
+        let complete: Vec<u64> = summaries.iter().map(|summary| summary.id).collect();
+        let mut stmt = tx
+            .prepare(SELECT_ACTIVE_CHILD_IDS_SQL)
+            .db_context("Failed to prepare query")?;
+        let mut blocked = Vec::new();
+        for &id in &complete {
+            let active_children = stmt
+                .query_map(params![id as i64, PlanStatus::Active.as_str()], |row| {
+                    row.get::<_, i64>(0)
+                })
+                .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+                .db_context("Failed to query child plans")?;
+            if active_children
+                .iter()
+                .any(|&child| !complete.contains(&(child as u64)))
+            {
+                blocked.push(id);
+            }
+        }
+        drop(stmt);
+        summaries.retain(|summary| !blocked.contains(&summary.id));
+
+        for summary in &mut summaries {
+            tx.execute(
+                UPDATE_PLAN_ARCHIVE_SQL,
+                params![
+                    PlanStatus::Archived.as_str(),
+                    summary.id as i64,
+                    PlanStatus::Active.as_str()
+                ],
+            )
+            .db_context("Failed to archive plan")?;
+            Self::record_plan_event(&tx, summary.id, EventKind::Archived)?;
+            summary.status = PlanStatus::Archived;
+        }
+
+        tx.commit().db_context("Failed to commit transaction")?;
+
+        Ok(summaries)
+    }
+
+    /// Sets when a plan was created and last updated, for imports that carry
+    /// historical timestamps. Other writes keep `updated_at` current through
+    /// triggers, so this should follow the plan's last other change.
+    ///
+    /// Returns `false` if the plan doesn't exist.
+    pub fn set_plan_timestamps(
+        &mut self,
+        id: u64,
+        created_at: Timestamp,
+        updated_at: Timestamp,
+    ) -> Result<bool> {
+        Self::write_plan_timestamps(&self.connection, id, created_at, updated_at)
+    }
+
+    /// Sets when a plan was created and last updated, as
+    /// [`Self::set_plan_timestamps`] does. The caller owns the transaction.
+    pub(super) fn write_plan_timestamps(
+        conn: &Connection,
+        id: u64,
+        created_at: Timestamp,
+        updated_at: Timestamp,
+    ) -> Result<bool> {
+        let updated = conn
+            .execute(
+                UPDATE_PLAN_TIMESTAMPS_SQL,
+                params![
+                    super::format_timestamp(created_at),
+                    super::format_timestamp(updated_at),
+                    id as i64
+                ],
+            )
+            .map_err(|e| PlannerError::database_error("Failed to set plan timestamps", e))?;
+        Ok(updated > 0)
+    }
+
+    /// Turns a violation of the unique index on active plans' directory and
+    /// title into an `InvalidInput` error naming the clash; other errors are
+    /// returned unchanged.
+    ///
+    /// Only plans created with `unique_per_directory` are in the index, so
+    /// the clash can only come from renaming, moving or restoring one of
+    /// those while another such plan is active.
+    fn unique_title_conflict(
+        error: PlannerError,
+        title: &str,
+        directory: Option<&str>,
+    ) -> PlannerError {
+        if !error.is_conflict() {
+            return error;
+        }
+        let place = directory.map_or_else(String::new, |dir| format!(" in '{dir}'"));
+        PlannerError::invalid_input("title").with_reason(format!(
+            "An active plan titled '{title}' already exists{place}"
+        ))
+    }
+
     /// Unarchives a plan (restores from archive).
     /// Returns the unarchived plan details if successful, None if the plan
     /// doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the plan was created with
+    /// `unique_per_directory` and another such active plan now has its title
+    /// in its directory
     pub fn unarchive_plan(&mut self, id: u64) -> Result<Option<Plan>> {
         let tx = self
             .connection
             .transaction()
             .db_context("Failed to begin transaction")?;
 
-        let now = Timestamp::now().to_string();
         let rows_affected = tx
             .execute(
                 UPDATE_PLAN_UNARCHIVE_SQL,
                 params![
                     PlanStatus::Active.as_str(),
-                    &now,
                     id as i64,
                     PlanStatus::Archived.as_str()
                 ],
             )
-            .map_err(|e| PlannerError::database_error("Failed to unarchive plan", e))?;
+            .map_err(|e| PlannerError::database_error("Failed to unarchive plan", e))
+            .map_err(|error| match Self::read_plan_row(&tx, id) {
+                Ok(Some(plan)) => {
+                    Self::unique_title_conflict(error, &plan.title, plan.directory.as_deref())
+                }
+                _ => error,
+            })?;
 
         if rows_affected == 0 {
             // Check if plan exists
@@ -371,46 +1336,25 @@ impl super::Database {
                 return Ok(None);
             }
             // Plan exists but is already active - still return its details
+        } else {
+            Self::record_plan_event(&tx, id, EventKind::Unarchived)?;
         }
 
         // Get the updated plan details
         let mut plan = tx
-            .query_row(SELECT_PLAN_SQL, params![id as i64], |row| {
-                let status_str: String = row.get(3)?;
-                let status = status_str.parse::<PlanStatus>().map_err(|_| {
-                    rusqlite::Error::FromSqlConversionFailure(
-                        3,
-                        Type::Text,
-                        Box::new(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            format!("Invalid plan status: {status_str}"),
-                        )),
-                    )
-                })?;
-
-                Ok(Plan {
-                    id: row.get::<_, i64>(0)? as u64,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    status,
-                    directory: row.get(4)?,
-                    created_at: row.get::<_, String>(5)?.parse::<Timestamp>().map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(5, Type::Text, Box::new(e))
-                    })?,
-                    updated_at: row.get::<_, String>(6)?.parse::<Timestamp>().map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(6, Type::Text, Box::new(e))
-                    })?,
-                    steps: Vec::new(),
-                })
-            })
+            .query_row(
+                SELECT_PLAN_SQL,
+                params![id as i64],
+                Self::build_plan_from_row,
+            )
             .optional()
             .map_err(|e| PlannerError::database_error("Failed to query unarchived plan", e))?;
 
         tx.commit().db_context("Failed to commit transaction")?;
 
-        // Load steps for the plan if it exists
+        // Load steps and child plans for the plan if it exists
         if let Some(ref mut plan) = plan {
-            plan.steps = self.get_steps(plan.id)?;
+            self.load_plan_details(plan)?;
         }
 
         Ok(plan)
@@ -418,7 +1362,13 @@ impl super::Database {
 
     /// Permanently deletes a plan and all its associated steps from the
     /// database. This operation cannot be undone.
-    pub fn delete_plan(&mut self, id: u64) -> Result<()> {
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    /// Returns `PlannerError::PlanNotEmpty` if the plan has steps that are not
+    /// done and `force` is not set
+    pub fn delete_plan(&mut self, id: u64, force: bool) -> Result<()> {
         let tx = self
             .connection
             .transaction()
@@ -433,6 +1383,23 @@ impl super::Database {
             return Err(PlannerError::PlanNotFound { id });
         }
 
+        if !force {
+            let pending_steps: i64 = tx
+                .query_row(COUNT_PENDING_PLAN_STEPS_SQL, params![id as i64], |row| {
+                    row.get(0)
+                })
+                .db_context("Failed to count pending steps")?;
+            if pending_steps > 0 {
+                return Err(PlannerError::PlanNotEmpty {
+                    id,
+                    pending_steps: pending_steps as usize,
+                });
+            }
+        }
+
+        // Recorded first, with the plan as it was before the deletion
+        Self::record_plan_event(&tx, id, EventKind::Deleted)?;
+
         // Delete all steps associated with this plan first
         // (Foreign key constraints should handle this automatically, but we'll be
         // explicit)
@@ -447,4 +1414,337 @@ impl super::Database {
 
         Ok(())
     }
+
+    /// Copies a plan and its steps into a new active plan, in one
+    /// transaction, e.g. to reuse a checklist as a template.
+    ///
+    /// The copy takes `title` or the original's title, and the original's
+    /// description, directory, status labels and auto-archive setting; it
+    /// is not nested under the original's parent. Its steps keep their
+    /// content, order, phases, estimates and dependencies on each other, but
+    /// start out todo without results or verification. Every copy gets fresh
+    /// timestamps. Cloning an archived plan gives an active copy.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    /// Returns `PlannerError::InvalidInput` if `title` is empty
+    pub fn clone_plan(&mut self, id: u64, title: Option<&str>) -> Result<Plan> {
+        let title = title.map(str::trim);
+        if title.is_some_and(str::is_empty) {
+            return Err(PlannerError::invalid_input("title").with_reason("Title cannot be empty"));
+        }
+
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+        let now = super::format_timestamp(super::now());
+
+        let cloned = tx
+            .execute(CLONE_PLAN_SQL, params![id as i64, title, &now])
+            .db_context("Failed to clone plan")?;
+        if cloned == 0 {
+            return Err(PlannerError::PlanNotFound { id });
+        }
+        let clone_id = tx.last_insert_rowid() as u64;
+        Self::record_plan_event(&tx, clone_id, EventKind::Created)?;
+
+        let step_ids = {
+            let mut stmt = tx
+                .prepare(SELECT_STEP_IDS_BY_PLAN_SQL)
+                .db_context("Failed to prepare step query")?;
+            stmt.query_map(params![id as i64], |row| row.get::<_, i64>(0))
+                .db_context("Failed to query steps")?
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .db_context("Failed to read steps")?
+        };
+
+        let mut clones = HashMap::new();
+        for &step_id in &step_ids {
+            tx.execute(CLONE_STEP_SQL, params![clone_id as i64, step_id, &now])
+                .db_context("Failed to clone step")?;
+            let clone = tx.last_insert_rowid() as u64;
+            Self::copy_step_references(&tx, step_id as u64, clone)?;
+            clones.insert(step_id as u64, clone);
+        }
+
+        // Dependencies on steps of other plans are not carried over
+        for (step_id, clone) in &clones {
+            for depends_on in Self::load_step_dependencies(&tx, *step_id)? {
+                if let Some(depends_on) = clones.get(&depends_on) {
+                    Self::insert_step_dependency(&tx, *clone, *depends_on)?;
+                }
+            }
+        }
+        for step_id in &step_ids {
+            Self::record_step_event(&tx, clones[&(*step_id as u64)], EventKind::Added)?;
+        }
+
+        tx.commit().db_context("Failed to commit transaction")?;
+
+        self.get_plan(clone_id)?
+            .ok_or(PlannerError::PlanNotFound { id: clone_id })
+    }
+
+    /// Merges plan `source_id` into plan `target_id` in one transaction.
+    ///
+    /// The source plan's steps are appended to the end of the target in their
+    /// current order, keeping their IDs, statuses, results, references,
+    /// phases, estimates and verification. Their `updated_at` is kept too, so
+    /// that completion times survive the move. With `prefix_titles`, the
+    /// moved steps' titles are prefixed with the source plan's title.
+    ///
+    /// The descriptions are concatenated into the target's, separated by a
+    /// horizontal rule, and both plans get a note recording the merge. The
+    /// source's child plans become children of the target. The source is
+    /// archived with a pointer to the target rather than deleted. The
+    /// target's directory, status labels and auto-archive setting are kept;
+    /// the source's are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if either plan doesn't exist
+    /// Returns `PlannerError::InvalidInput` if the plans are the same, either
+    /// was already merged, or the source's child plans could not be nested
+    /// under the target
+    pub fn merge_plans(
+        &mut self,
+        source_id: u64,
+        target_id: u64,
+        options: MergeOptions,
+    ) -> Result<Plan> {
+        if source_id == target_id {
+            return Err(PlannerError::invalid_input("target_id")
+                .with_reason("A plan cannot be merged into itself"));
+        }
+
+        let tx = self
+            .connection
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .db_context("Failed to begin transaction")?;
+
+        let [source, target] = [source_id, target_id].map(|id| {
+            tx.query_row(
+                SELECT_PLAN_SQL,
+                params![id as i64],
+                Self::build_plan_from_row,
+            )
+            .optional()
+            .map_err(|e| PlannerError::database_error("Failed to query plan", e))?
+            .ok_or(PlannerError::PlanNotFound { id })
+        });
+        let (source, target) = (source?, target?);
+
+        if let Some(merged_into) = Self::merged_into(&tx, source_id)? {
+            return Err(
+                PlannerError::invalid_input("source_id").with_reason(format!(
+                    "Plan {source_id} was already merged into plan {merged_into}"
+                )),
+            );
+        }
+        if let Some(merged_into) = Self::merged_into(&tx, target_id)? {
+            return Err(
+                PlannerError::invalid_input("target_id").with_reason(format!(
+                    "Plan {target_id} was merged into plan {merged_into} and cannot receive steps"
+                )),
+            );
+        }
+
+        // The target takes over the source's children, unless it is one of them
+        let target_parent = target.parent_plan_id.filter(|&parent| parent != source_id);
+        let children: i64 = tx
+            .query_row(COUNT_CHILD_PLANS_SQL, params![source_id as i64], |row| {
+                row.get(0)
+            })
+            .db_context("Failed to count child plans")?;
+        let moved_children = children - i64::from(target.parent_plan_id == Some(source_id));
+        if moved_children > 0 && target_parent.is_some() {
+            return Err(PlannerError::invalid_input("target_id").with_reason(format!(
+                "Plan {source_id} has child plans, but plan {target_id} is itself a child plan; plans can only be nested one level deep"
+            )));
+        }
+
+        let moved_steps = Self::move_steps(&tx, &source, target_id, options)?;
+
+        tx.execute(
+            REPARENT_CHILD_PLANS_SQL,
+            params![target_id as i64, source_id as i64],
+        )
+        .map_err(|e| PlannerError::database_error("Failed to move child plans", e))?;
+
+        let target_note = format!(
+            "Merged plan {source_id} ({}) into this plan, appending {moved_steps} step(s).",
+            source.title
+        );
+        let target_description = join_descriptions([
+            target.description.as_deref(),
+            source.description.as_deref(),
+            Some(&target_note),
+        ]);
+        tx.execute(
+            UPDATE_MERGED_TARGET_SQL,
+            params![
+                target_description,
+                target_parent.map(|id| id as i64),
+                target_id as i64
+            ],
+        )
+        .map_err(|e| PlannerError::database_error("Failed to update merged plan", e))?;
+
+        let source_note = format!(
+            "Merged into plan {target_id} ({}), which now holds this plan's steps.",
+            target.title
+        );
+        let source_description =
+            join_descriptions([source.description.as_deref(), Some(&source_note)]);
+        tx.execute(
+            UPDATE_MERGED_SOURCE_SQL,
+            params![
+                source_description,
+                PlanStatus::Archived.as_str(),
+                target_id as i64,
+                source_id as i64
+            ],
+        )
+        .map_err(|e| PlannerError::database_error("Failed to archive merged plan", e))?;
+        Self::record_plan_event(&tx, target_id, EventKind::Updated)?;
+        Self::record_plan_event(&tx, source_id, EventKind::Merged)?;
+
+        tx.commit().db_context("Failed to commit transaction")?;
+
+        self.get_plan(target_id)?
+            .ok_or(PlannerError::PlanNotFound { id: target_id })
+    }
+
+    /// Moves all steps of `source` to the end of plan `target_id`, keeping
+    /// their relative order and `updated_at`. Returns the number of steps
+    /// moved.
+    fn move_steps(
+        conn: &Connection,
+        source: &Plan,
+        target_id: u64,
+        options: MergeOptions,
+    ) -> Result<usize> {
+        let updated_at = {
+            let mut stmt = conn
+                .prepare(SELECT_STEP_UPDATED_AT_BY_PLAN_SQL)
+                .map_err(|e| PlannerError::database_error("Failed to prepare query", e))?;
+            stmt.query_map(params![source.id as i64], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+            .map_err(|e| PlannerError::database_error("Failed to query steps", e))?
+        };
+        if updated_at.is_empty() {
+            return Ok(0);
+        }
+
+        let order_range = |plan_id: u64| {
+            conn.query_row(
+                SELECT_STEP_ORDER_RANGE_SQL,
+                params![plan_id as i64],
+                |row| Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, Option<i64>>(1)?)),
+            )
+            .db_context("Failed to query step order")
+        };
+        let (source_first, _) = order_range(source.id)?;
+        let (_, target_last) = order_range(target_id)?;
+        // Shift the source's orders to start one spacing past the target's last
+        let offset = target_last.unwrap_or(0) + STEP_ORDER_SPACING - source_first.unwrap_or(0);
+        let prefix = options.prefix_titles.then(|| format!("{}: ", source.title));
+
+        conn.execute(
+            MOVE_PLAN_STEPS_SQL,
+            params![target_id as i64, offset, prefix, source.id as i64],
+        )
+        .map_err(|e| PlannerError::database_error("Failed to move steps", e))?;
+
+        // Moving a step bumps its updated_at, which would make done steps
+        // look freshly completed
+        for (id, updated_at) in &updated_at {
+            conn.execute(RESTORE_STEP_UPDATED_AT_SQL, params![updated_at, id])
+                .map_err(|e| PlannerError::database_error("Failed to restore step timestamp", e))?;
+            Self::record_step_event(conn, *id as u64, EventKind::Updated)?;
+        }
+
+        Ok(updated_at.len())
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::db::Database;
+
+    thread_local! {
+        /// Runs once between reading a plan row and its details, so tests
+        /// can interleave writes from another connection.
+        pub(in crate::db) static AFTER_PLAN_ROW_READ: RefCell<Option<Box<dyn FnMut()>>> =
+            const { RefCell::new(None) };
+    }
+
+    /// Reads plan 1 with `read` while another connection renames it and adds
+    /// a step between the plan row and step queries. Returns the plan read
+    /// and the outcome of the write.
+    fn read_during_write(read: fn(&Database, u64) -> Result<Option<Plan>>) -> (Plan, Result<()>) {
+        let dir = TempDir::new().expect("Failed to create temp dir");
+        let path = dir.path().join("snapshot.db");
+        let mut db = Database::new(&path).expect("Failed to create database");
+        let plan = db
+            .create_plan("Before", None, None)
+            .expect("Failed to create plan");
+        db.add_step(plan.id, "First", None, None, Vec::new())
+            .expect("Failed to add step");
+
+        let mut writer = Database::new(&path).expect("Failed to open second connection");
+        let outcome = Rc::new(RefCell::new(None));
+        let hook_outcome = Rc::clone(&outcome);
+        let hook = move || {
+            let request = UpdatePlanRequest {
+                title: Some("After".to_string()),
+                ..Default::default()
+            };
+            let result = writer.update_plan(plan.id, &request).and_then(|_| {
+                writer
+                    .add_step(plan.id, "Second", None, None, Vec::new())
+                    .map(|_| ())
+            });
+            hook_outcome.borrow_mut().get_or_insert(result);
+        };
+        AFTER_PLAN_ROW_READ.set(Some(Box::new(hook)));
+        let read_plan = read(&db, plan.id);
+        AFTER_PLAN_ROW_READ.set(None);
+
+        let read_plan = read_plan
+            .expect("Failed to read plan")
+            .expect("Plan exists");
+        let outcome = outcome.take().expect("Hook ran");
+        (read_plan, outcome)
+    }
+
+    #[test]
+    fn test_separate_plan_queries_can_disagree() {
+        // Without a snapshot the write lands between the two queries
+        let (plan, outcome) = read_during_write(Database::read_plan);
+
+        outcome.expect("Write should succeed");
+        assert_eq!(plan.title, "Before");
+        assert_eq!(plan.steps.len(), 2);
+    }
+
+    #[test]
+    fn test_get_plan_reads_one_snapshot() {
+        let (plan, outcome) = read_during_write(Database::get_plan);
+
+        // With write-ahead logging the write proceeds, while the read keeps
+        // seeing the snapshot it started with
+        outcome.expect("Write should succeed");
+        assert_eq!(plan.title, "Before");
+        assert_eq!(plan.steps.len(), 1);
+    }
 }
@@ -0,0 +1,313 @@
+//! Reusing database connections across planner operations.
+//!
+//! Opening a connection initializes the schema, which replays the schema
+//! script, migrations, views and triggers. The pool runs that once per
+//! database file and hands finished operations' connections to the next
+//! operation. Connections opened later only enable foreign keys.
+//!
+//! A database file replaced on disk, e.g. atomically by backup tooling, is
+//! noticed by comparing the file's identity on every checkout: idle
+//! connections to the old file are closed and the new file is initialized
+//! like a fresh one. SQLite leaves the write-ahead log of a file moved away
+//! in place, where the new file would pick it up, so it is emptied first.
+//! A file that disappears is not recreated empty in its place: operations
+//! fail with `PlannerError::DatabaseUnavailable` until it is restored.
+//!
+//! An in-memory database lives as long as a connection to it is open, so
+//! its pool keeps one open for its own lifetime, and its pooled connections
+//! share it through SQLite's shared cache. Shared-cache locks fail at once
+//! instead of waiting like file locks do, so its operations take turns.
+
+use std::{
+    ops::{Deref, DerefMut},
+    path::Path,
+    sync::{
+        Arc, Condvar, Mutex, PoisonError,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use tracing::{info, warn};
+
+use super::{Database, DatabaseConfig};
+use crate::error::{PlannerError, Result};
+
+/// Default number of idle connections a planner keeps open.
+pub const DEFAULT_CONNECTION_POOL_SIZE: usize = 4;
+
+/// Identity of a database file, telling a file replaced on disk apart from
+/// the one a connection was opened on.
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileId {
+    device: u64,
+    inode: u64,
+}
+
+/// Identity of a database file, telling a file replaced on disk apart from
+/// the one a connection was opened on.
+///
+/// Without stable file identities, the size and modification time stand in
+/// for one. The main file of a write-ahead logged database only changes on
+/// checkpoints, and a checkpoint mistaken for a replacement costs no more
+/// than initializing the schema again.
+#[cfg(not(unix))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileId {
+    len: u64,
+    modified: Option<std::time::SystemTime>,
+}
+
+impl FileId {
+    /// Identity of an in-memory database, which can't be replaced.
+    #[cfg(unix)]
+    const MEMORY: Self = Self {
+        device: 0,
+        inode: 0,
+    };
+
+    /// Identity of an in-memory database, which can't be replaced.
+    #[cfg(not(unix))]
+    const MEMORY: Self = Self {
+        len: 0,
+        modified: None,
+    };
+
+    /// Returns the identity of the file at `path`, if it exists.
+    #[cfg(unix)]
+    fn of(path: &Path) -> Option<Self> {
+        use std::os::unix::fs::MetadataExt;
+
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(Self {
+            device: metadata.dev(),
+            inode: metadata.ino(),
+        })
+    }
+
+    /// Returns the identity of the file at `path`, if it exists.
+    #[cfg(not(unix))]
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// An idle connection and the file it was opened on.
+struct Idle {
+    db: Database,
+    file: Option<FileId>,
+}
+
+/// Connections to one database, reused across operations.
+///
+/// The size bounds how many idle connections are kept, not how many can be
+/// in use: an operation finding none idle opens another, which is closed
+/// afterwards if the pool is full.
+pub(crate) struct ConnectionPool {
+    config: DatabaseConfig,
+    size: usize,
+    idle: Mutex<Vec<Idle>>,
+    /// The file whose schema was last initialized
+    initialized: Mutex<Option<FileId>>,
+    opened: AtomicUsize,
+    /// Number of times a replaced file was initialized
+    reconnects: AtomicUsize,
+    /// Connection holding an in-memory database open between operations
+    keep_alive: Mutex<Option<Database>>,
+    /// Whether an operation is using an in-memory database
+    in_use: Mutex<bool>,
+    checked_in: Condvar,
+}
+
+impl ConnectionPool {
+    /// Opens the first connection, initializing the schema, and keeps it
+    /// for the first operation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened or initialized
+    pub(crate) fn open(config: DatabaseConfig, size: usize) -> Result<Self> {
+        let pool = Self {
+            config,
+            size,
+            idle: Mutex::new(Vec::new()),
+            initialized: Mutex::new(None),
+            opened: AtomicUsize::new(0),
+            reconnects: AtomicUsize::new(0),
+            keep_alive: Mutex::new(None),
+            in_use: Mutex::new(false),
+            checked_in: Condvar::new(),
+        };
+        let (db, file) = pool.connect()?;
+        if pool.config.is_in_memory() {
+            *pool
+                .keep_alive
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = Some(pool.config.connect()?);
+        }
+        pool.release(db, file);
+        Ok(pool)
+    }
+
+    /// The configuration connections are opened with.
+    pub(crate) fn config(&self) -> &DatabaseConfig {
+        &self.config
+    }
+
+    /// Number of connections opened so far.
+    pub(crate) fn opened(&self) -> usize {
+        self.opened.load(Ordering::Relaxed)
+    }
+
+    /// Number of times the database file was replaced on disk and
+    /// connections to the new file were opened.
+    pub(crate) fn reconnects(&self) -> usize {
+        self.reconnects.load(Ordering::Relaxed)
+    }
+
+    /// Returns the identity of the current database file, if it exists.
+    fn current_file(&self) -> Option<FileId> {
+        if self.config.is_in_memory() {
+            Some(FileId::MEMORY)
+        } else {
+            FileId::of(&self.config.path)
+        }
+    }
+
+    /// Takes an idle connection to the current database file, or opens one.
+    ///
+    /// For an in-memory database, this waits until the connection checked
+    /// out before is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a new connection cannot be opened
+    pub(crate) fn get(self: &Arc<Self>) -> Result<PooledConnection> {
+        if self.config.is_in_memory() {
+            let in_use = self.in_use.lock().unwrap_or_else(PoisonError::into_inner);
+            let mut in_use = self
+                .checked_in
+                .wait_while(in_use, |in_use| *in_use)
+                .unwrap_or_else(PoisonError::into_inner);
+            *in_use = true;
+        }
+        self.checkout().inspect_err(|_| self.check_in())
+    }
+
+    /// Takes an idle connection like [`Self::get`], without waiting.
+    fn checkout(self: &Arc<Self>) -> Result<PooledConnection> {
+        let current = self.current_file();
+        let reused = {
+            let mut idle = self.idle.lock().unwrap_or_else(PoisonError::into_inner);
+            let (live, stale): (Vec<_>, Vec<_>) = idle
+                .drain(..)
+                .partition(|conn| current.is_some() && conn.file == current);
+            *idle = live;
+            for conn in stale {
+                conn.db.discard_write_ahead_log();
+            }
+            idle.pop()
+        };
+
+        let (db, file) = match reused {
+            Some(Idle { db, file }) => (db, file),
+            None => self.connect()?,
+        };
+        Ok(PooledConnection {
+            pool: Arc::clone(self),
+            db: Some(db),
+            file,
+        })
+    }
+
+    /// Opens a connection, initializing the schema unless it already was for
+    /// the current file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::DatabaseUnavailable` if the file connections
+    /// were opened on before no longer exists
+    fn connect(&self) -> Result<(Database, Option<FileId>)> {
+        let mut initialized = self
+            .initialized
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let current = self.current_file();
+        if current.is_none() && initialized.is_some() {
+            warn!(path = %self.config.path.display(), "Database file disappeared");
+            return Err(PlannerError::DatabaseUnavailable {
+                path: self.config.path.clone(),
+            });
+        }
+        let db = if current.is_some() && *initialized == current {
+            self.config.connect()?
+        } else {
+            if initialized.is_some() {
+                info!(path = %self.config.path.display(), "Database file was replaced, reconnecting");
+                self.reconnects.fetch_add(1, Ordering::Relaxed);
+            }
+            let db = self.config.open()?;
+            // A missing file only exists once opened
+            *initialized = self.current_file();
+            db
+        };
+        self.opened.fetch_add(1, Ordering::Relaxed);
+        Ok((db, *initialized))
+    }
+
+    /// Keeps a connection for reuse, unless the pool is full or the
+    /// connection was left inside a transaction.
+    fn release(&self, db: Database, file: Option<FileId>) {
+        if !db.connection.is_autocommit() {
+            return;
+        }
+        let mut idle = self.idle.lock().unwrap_or_else(PoisonError::into_inner);
+        if idle.len() < self.size {
+            idle.push(Idle { db, file });
+        }
+    }
+
+    /// Lets the next operation waiting on an in-memory database go ahead.
+    fn check_in(&self) {
+        if !self.config.is_in_memory() {
+            return;
+        }
+        *self.in_use.lock().unwrap_or_else(PoisonError::into_inner) = false;
+        self.checked_in.notify_one();
+    }
+}
+
+/// A connection checked out of a [`ConnectionPool`], returned to it when
+/// dropped.
+pub(crate) struct PooledConnection {
+    pool: Arc<ConnectionPool>,
+    db: Option<Database>,
+    file: Option<FileId>,
+}
+
+impl Deref for PooledConnection {
+    type Target = Database;
+
+    fn deref(&self) -> &Database {
+        self.db.as_ref().expect("connection is held until dropped")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Database {
+        self.db.as_mut().expect("connection is held until dropped")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let Some(db) = self.db.take() {
+            self.pool.release(db, self.file);
+        }
+        self.pool.check_in();
+    }
+}
@@ -0,0 +1,120 @@
+//! References from steps to related resources, kept in order.
+
+use std::collections::HashMap;
+
+use rusqlite::{Connection, params};
+
+use crate::{
+    error::{DatabaseResultExt, Result},
+    models::{ReferenceKind, StepReference},
+};
+
+const INSERT_STEP_REFERENCE_SQL: &str =
+    "INSERT INTO step_references (step_id, position, url, label, kind) VALUES (?1, ?2, ?3, ?4, ?5)";
+const DELETE_STEP_REFERENCES_SQL: &str = "DELETE FROM step_references WHERE step_id = ?1";
+const SELECT_STEP_REFERENCES_SQL: &str =
+    "SELECT url, label, kind FROM step_references WHERE step_id = ?1 ORDER BY position";
+const SELECT_PLAN_REFERENCES_SQL: &str = "SELECT r.step_id, r.url, r.label, r.kind FROM step_references r JOIN steps s ON s.id = r.step_id WHERE s.plan_id = ?1 ORDER BY r.step_id, r.position";
+const COPY_STEP_REFERENCES_SQL: &str = "INSERT INTO step_references (step_id, position, url, label, kind) SELECT ?1, position, url, label, kind FROM step_references WHERE step_id = ?2";
+
+impl super::Database {
+    /// Replaces the references of a step with `references`, in order.
+    ///
+    /// The caller owns the transaction.
+    pub(super) fn replace_step_references(
+        conn: &Connection,
+        step_id: u64,
+        references: &[StepReference],
+    ) -> Result<()> {
+        conn.execute(DELETE_STEP_REFERENCES_SQL, params![step_id as i64])
+            .db_context("Failed to clear step references")?;
+
+        let mut stmt = conn
+            .prepare(INSERT_STEP_REFERENCE_SQL)
+            .db_context("Failed to prepare reference insert")?;
+        for (position, reference) in references.iter().enumerate() {
+            stmt.execute(params![
+                step_id as i64,
+                position as i64,
+                &reference.url,
+                reference.label.as_deref(),
+                reference.kind.map(ReferenceKind::as_str)
+            ])
+            .db_context("Failed to add step reference")?;
+        }
+        Ok(())
+    }
+
+    /// Gives the step `to_step_id` a copy of the references of
+    /// `from_step_id`.
+    pub(super) fn copy_step_references(
+        conn: &Connection,
+        from_step_id: u64,
+        to_step_id: u64,
+    ) -> Result<()> {
+        conn.execute(
+            COPY_STEP_REFERENCES_SQL,
+            params![to_step_id as i64, from_step_id as i64],
+        )
+        .db_context("Failed to copy step references")?;
+        Ok(())
+    }
+
+    /// Returns the references of a step, in order.
+    pub(super) fn load_step_references(
+        conn: &Connection,
+        step_id: u64,
+    ) -> Result<Vec<StepReference>> {
+        let mut stmt = conn
+            .prepare(SELECT_STEP_REFERENCES_SQL)
+            .db_context("Failed to prepare references query")?;
+
+        stmt.query_map(params![step_id as i64], |row| {
+            Ok(StepReference {
+                url: row.get(0)?,
+                label: row.get(1)?,
+                kind: parse_kind(row.get(2)?),
+            })
+        })
+        .db_context("Failed to query step references")?
+        .collect::<rusqlite::Result<_>>()
+        .db_context("Failed to read step references")
+    }
+
+    /// Returns the references of every step of a plan that has any, in
+    /// order, keyed by step ID.
+    pub(super) fn load_plan_references(
+        conn: &Connection,
+        plan_id: u64,
+    ) -> Result<HashMap<u64, Vec<StepReference>>> {
+        let mut stmt = conn
+            .prepare(SELECT_PLAN_REFERENCES_SQL)
+            .db_context("Failed to prepare references query")?;
+
+        let mut references = HashMap::<u64, Vec<StepReference>>::new();
+        let rows = stmt
+            .query_map(params![plan_id as i64], |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as u64,
+                    StepReference {
+                        url: row.get(1)?,
+                        label: row.get(2)?,
+                        kind: parse_kind(row.get(3)?),
+                    },
+                ))
+            })
+            .db_context("Failed to query step references")?;
+        for row in rows {
+            let (step_id, reference) = row.db_context("Failed to read step reference")?;
+            references.entry(step_id).or_default().push(reference);
+        }
+
+        Ok(references)
+    }
+}
+
+/// Parses a stored reference kind. Kinds this version doesn't know, which
+/// can only get into the database by editing it by hand, are dropped.
+fn parse_kind(kind: Option<String>) -> Option<ReferenceKind> {
+    kind.and_then(|kind| kind.parse().ok())
+}
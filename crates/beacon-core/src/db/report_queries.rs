@@ -0,0 +1,66 @@
+//! Standup reports of a plan's recent progress, selected by the database.
+
+use jiff::Timestamp;
+use rusqlite::params;
+
+use crate::{
+    error::{DatabaseResultExt, PlannerError, Result},
+    models::{PlanReport, REPORT_UPCOMING_LIMIT, StepStatus},
+};
+
+const SELECT_RECENT_STEPS_SQL: &str = "SELECT id, plan_id, title, description, acceptance_criteria, status, result, step_index, created_at, updated_at, phase, verification_required, verified_by, verification_comment, verified_at, estimate_minutes, expected_output, started_at, completed_at, blocked_reason FROM (SELECT *, ROW_NUMBER() OVER (ORDER BY step_order, id) - 1 AS step_index FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL) WHERE status = 'inprogress' OR (status = 'done' AND julianday(COALESCE(completed_at, updated_at)) >= julianday(?2)) ORDER BY step_index";
+const SELECT_UPCOMING_STEPS_SQL: &str = "SELECT id, plan_id, title, description, acceptance_criteria, status, result, step_index, created_at, updated_at, phase, verification_required, verified_by, verification_comment, verified_at, estimate_minutes, expected_output, started_at, completed_at, blocked_reason FROM (SELECT *, ROW_NUMBER() OVER (ORDER BY step_order, id) - 1 AS step_index FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL) WHERE status = 'todo' ORDER BY step_index LIMIT ?2";
+
+impl super::Database {
+    /// Reports the steps of a plan completed at or after `since`, those in
+    /// progress and the first todo steps. Steps completed before completion
+    /// times were recorded count as completed when they were last updated.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    pub fn plan_report(&self, plan_id: u64, since: Timestamp) -> Result<PlanReport> {
+        self.read_consistent(|db| {
+            let plan = Self::read_plan_row(&db.connection, plan_id)?
+                .ok_or(PlannerError::PlanNotFound { id: plan_id })?;
+
+            let mut active = db
+                .connection
+                .prepare(SELECT_RECENT_STEPS_SQL)
+                .and_then(|mut stmt| {
+                    stmt.query_map(
+                        params![plan_id as i64, super::format_timestamp(since)],
+                        Self::build_step_from_row,
+                    )?
+                    .collect::<rusqlite::Result<Vec<_>>>()
+                })
+                .db_context("Failed to query recent steps")?;
+
+            let mut upcoming = db
+                .connection
+                .prepare(SELECT_UPCOMING_STEPS_SQL)
+                .and_then(|mut stmt| {
+                    stmt.query_map(
+                        params![plan_id as i64, REPORT_UPCOMING_LIMIT as i64],
+                        Self::build_step_from_row,
+                    )?
+                    .collect::<rusqlite::Result<Vec<_>>>()
+                })
+                .db_context("Failed to query upcoming steps")?;
+
+            db.load_step_details(plan_id, active.iter_mut().chain(&mut upcoming))?;
+            let (completed, in_progress) = active
+                .into_iter()
+                .partition(|step| step.status == StepStatus::Done);
+
+            Ok(PlanReport {
+                plan_id,
+                plan_title: plan.title,
+                since,
+                completed,
+                in_progress,
+                upcoming,
+            })
+        })
+    }
+}
@@ -0,0 +1,33 @@
+//! Workspace settings storage.
+
+use rusqlite::params;
+
+use crate::error::{DatabaseResultExt, Result};
+
+const SELECT_SETTINGS_SQL: &str = "SELECT key, value FROM settings ORDER BY key";
+const UPSERT_SETTING_SQL: &str = "INSERT INTO settings (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = excluded.value";
+
+impl super::Database {
+    /// Loads the stored settings as key-value pairs, in key order.
+    pub fn load_settings(&self) -> Result<Vec<(String, String)>> {
+        let mut stmt = self
+            .connection
+            .prepare(SELECT_SETTINGS_SQL)
+            .db_context("Failed to prepare settings query")?;
+
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .db_context("Failed to query settings")?
+            .collect::<rusqlite::Result<_>>()
+            .db_context("Failed to read settings")
+    }
+
+    /// Stores the value of a setting, replacing any previous value.
+    ///
+    /// The value is stored as given; validating it is up to the caller.
+    pub fn store_setting(&mut self, key: &str, value: &str) -> Result<()> {
+        self.connection
+            .execute(UPSERT_SETTING_SQL, params![key, value])
+            .db_context("Failed to store setting")?;
+        Ok(())
+    }
+}
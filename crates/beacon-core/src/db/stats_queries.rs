@@ -0,0 +1,102 @@
+//! Statistics over plans and steps, computed by the database.
+//!
+//! Every query takes the range as `?1` (start, inclusive) and `?2` (end,
+//! inclusive), either of which may be NULL to leave that side open.
+
+use jiff::Timestamp;
+use rusqlite::params;
+
+use crate::{
+    error::{DatabaseResultExt, Result},
+    models::{BUSIEST_DIRECTORIES_LIMIT, DirectoryActivity, PlannerStats},
+};
+
+const SELECT_STATS_SQL: &str = "SELECT
+    (SELECT COUNT(*) FROM plans
+        WHERE (?1 IS NULL OR julianday(created_at) >= julianday(?1))
+        AND (?2 IS NULL OR julianday(created_at) <= julianday(?2))),
+    (SELECT COUNT(*) FROM events WHERE entity_type = 'plan' AND kind = 'archived'
+        AND (?1 IS NULL OR julianday(created_at) >= julianday(?1))
+        AND (?2 IS NULL OR julianday(created_at) <= julianday(?2))),
+    (SELECT COUNT(*) FROM plans
+        WHERE (?1 IS NULL OR julianday(updated_at) >= julianday(?1))
+        AND (?2 IS NULL OR julianday(updated_at) <= julianday(?2))),
+    (SELECT COUNT(*) FROM steps WHERE deleted_at IS NULL
+        AND (?1 IS NULL OR julianday(created_at) >= julianday(?1))
+        AND (?2 IS NULL OR julianday(created_at) <= julianday(?2))),
+    (SELECT COUNT(*) FROM steps WHERE deleted_at IS NULL AND status = 'done'
+        AND (?1 IS NULL OR julianday(created_at) >= julianday(?1))
+        AND (?2 IS NULL OR julianday(created_at) <= julianday(?2))),
+    (SELECT COUNT(*) FROM steps WHERE deleted_at IS NULL AND status = 'done'
+        AND (?1 IS NULL OR julianday(COALESCE(completed_at, updated_at)) >= julianday(?1))
+        AND (?2 IS NULL OR julianday(COALESCE(completed_at, updated_at)) <= julianday(?2))),
+    (SELECT AVG((julianday(COALESCE(completed_at, updated_at)) - julianday(created_at)) * 86400)
+        FROM steps WHERE deleted_at IS NULL AND status = 'done'
+        AND (?1 IS NULL OR julianday(COALESCE(completed_at, updated_at)) >= julianday(?1))
+        AND (?2 IS NULL OR julianday(COALESCE(completed_at, updated_at)) <= julianday(?2)))";
+const SELECT_BUSIEST_DIRECTORIES_SQL: &str = "SELECT p.directory, COUNT(*) FROM steps s
+    JOIN plans p ON p.id = s.plan_id
+    WHERE s.deleted_at IS NULL AND s.status = 'done' AND p.directory IS NOT NULL
+    AND (?1 IS NULL OR julianday(COALESCE(s.completed_at, s.updated_at)) >= julianday(?1))
+    AND (?2 IS NULL OR julianday(COALESCE(s.completed_at, s.updated_at)) <= julianday(?2))
+    GROUP BY p.directory ORDER BY COUNT(*) DESC, p.directory LIMIT ?3";
+
+impl super::Database {
+    /// Computes statistics for `range`, from its start to its end, both
+    /// inclusive, or over all time without one. Steps completed before
+    /// completion times were recorded count as completed when they were
+    /// last updated.
+    pub fn stats(&self, range: Option<(Timestamp, Timestamp)>) -> Result<PlannerStats> {
+        let (since, until) = range.unzip();
+        let bounds = (
+            since.map(super::format_timestamp),
+            until.map(super::format_timestamp),
+        );
+
+        let mut stats = self
+            .connection
+            .query_row(SELECT_STATS_SQL, params![bounds.0, bounds.1], |row| {
+                let count = |index| row.get::<_, i64>(index).map(|count| count as u32);
+                let steps_created = count(3)?;
+                let created_done = count(4)?;
+                Ok(PlannerStats {
+                    since,
+                    until,
+                    plans_created: count(0)?,
+                    plans_archived: count(1)?,
+                    plans_touched: count(2)?,
+                    steps_created,
+                    steps_completed: count(5)?,
+                    completion_rate: if steps_created == 0 {
+                        0.0
+                    } else {
+                        f64::from(created_done) / f64::from(steps_created)
+                    },
+                    average_seconds_to_done: row
+                        .get::<_, Option<f64>>(6)?
+                        .map(|seconds| seconds.max(0.0).round() as u64),
+                    busiest_directories: Vec::new(),
+                })
+            })
+            .db_context("Failed to compute statistics")?;
+
+        let mut stmt = self
+            .connection
+            .prepare(SELECT_BUSIEST_DIRECTORIES_SQL)
+            .db_context("Failed to prepare directory query")?;
+        stats.busiest_directories = stmt
+            .query_map(
+                params![bounds.0, bounds.1, BUSIEST_DIRECTORIES_LIMIT as i64],
+                |row| {
+                    Ok(DirectoryActivity {
+                        directory: row.get(0)?,
+                        steps_completed: row.get::<_, i64>(1)? as u32,
+                    })
+                },
+            )
+            .and_then(Iterator::collect)
+            .db_context("Failed to query busiest directories")?;
+
+        Ok(stats)
+    }
+}
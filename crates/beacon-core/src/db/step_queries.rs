@@ -1,79 +1,269 @@
 //! Step CRUD operations and queries.
 
+use std::collections::{BTreeSet, HashMap};
+
 use jiff::Timestamp;
-use rusqlite::{OptionalExtension, params, types::Type};
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior, params, types::Type};
 
+use super::utils::{escape_like, subdirectory_pattern};
 use crate::{
     error::{DatabaseResultExt, PlannerError, Result},
-    models::{Step, StepStatus, UpdateStepRequest},
+    models::{
+        AllowedTransitions, ClaimOutcome, CreateStepRequest, DuplicateGroup, DuplicateStep,
+        EventEntity, EventKind, PlanStatus, PlanSummary, Step, StepOutline, StepReference,
+        StepStateMachine, StepStatus, StepStatusCounts, StepTransition, StepVerification,
+        UpdateStepRequest,
+    },
 };
 
 // Optimized SQL queries as const strings for compile-time optimization
 const CHECK_PLAN_EXISTS_SQL: &str = "SELECT EXISTS(SELECT 1 FROM plans WHERE id = ?1)";
-const GET_MAX_STEP_ORDER_SQL: &str =
-    "SELECT COALESCE(MAX(step_order), -1) + 1 FROM steps WHERE plan_id = ?1";
-const INSERT_STEP_SQL: &str = "INSERT INTO steps (plan_id, title, description, acceptance_criteria, step_references, status, result, step_order, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)";
-const UPDATE_PLAN_TIMESTAMP_SQL: &str = "UPDATE plans SET updated_at = ?1 WHERE id = ?2";
-const UPDATE_PLAN_TIMESTAMP_BY_STEP_SQL: &str =
-    "UPDATE plans SET updated_at = ?1 WHERE id = (SELECT plan_id FROM steps WHERE id = ?2)";
-const GET_MAX_STEP_ORDER_ONLY_SQL: &str = "SELECT MAX(step_order) FROM steps WHERE plan_id = ?1";
-const UPDATE_STEP_ORDERS_INCREMENT_SQL: &str =
-    "UPDATE steps SET step_order = step_order + 1 WHERE plan_id = ?1 AND step_order >= ?2";
-const SELECT_STEP_DETAILS_SQL: &str = "SELECT title, description, acceptance_criteria, step_references, status, result FROM steps WHERE id = ?1";
-const UPDATE_STEP_SQL: &str = "UPDATE steps SET title = ?1, description = ?2, acceptance_criteria = ?3, step_references = ?4, status = ?5, result = ?6, updated_at = ?7 WHERE id = ?8";
-const SELECT_STEPS_BY_PLAN_SQL: &str = "SELECT id, plan_id, title, description, acceptance_criteria, step_references, status, result, step_order, created_at, updated_at FROM steps WHERE plan_id = ?1 ORDER BY step_order";
-const SELECT_STEP_BY_ID_SQL: &str = "SELECT id, plan_id, title, description, acceptance_criteria, step_references, status, result, step_order, created_at, updated_at FROM steps WHERE id = ?1";
-const SELECT_STEP_STATUS_SQL: &str = "SELECT status FROM steps WHERE id = ?1";
-const UPDATE_STEP_STATUS_CLAIMED_SQL: &str =
-    "UPDATE steps SET status = ?1, updated_at = ?2 WHERE id = ?3 AND status = ?4";
-const SELECT_STEP_ORDER_SQL: &str = "SELECT plan_id, step_order FROM steps WHERE id = ?1";
-const UPDATE_STEP_ORDER_TEMP_SQL: &str =
-    "UPDATE steps SET step_order = -1, updated_at = ?1 WHERE id = ?2";
-const UPDATE_STEP_ORDER_SQL: &str =
-    "UPDATE steps SET step_order = ?1, updated_at = ?2 WHERE id = ?3";
-const DELETE_STEP_SQL: &str = "DELETE FROM steps WHERE id = ?1";
-const UPDATE_STEP_ORDERS_DECREMENT_SQL: &str =
-    "UPDATE steps SET step_order = step_order - 1 WHERE plan_id = ?1 AND step_order > ?2";
+const GET_NEXT_STEP_ORDER_SQL: &str = "SELECT COALESCE(MAX(step_order), 0) + ?2, COUNT(*) FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL";
+const INSERT_STEP_SQL: &str = "INSERT INTO steps (plan_id, title, description, acceptance_criteria, status, result, step_order, created_at, updated_at, started_at, completed_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)";
+const COUNT_PLAN_STEPS_SQL: &str =
+    "SELECT COUNT(*) FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL";
+const COUNT_PLAN_STEPS_BY_STATUS_SQL: &str = "SELECT COALESCE(SUM(status = 'done'), 0), COALESCE(SUM(status = 'inprogress'), 0), COALESCE(SUM(status = 'todo'), 0), COALESCE(SUM(status = 'blocked'), 0) FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL";
+const SELECT_NEIGHBOR_ORDERS_SQL: &str = "SELECT step_order FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL AND id IS NOT ?4 ORDER BY step_order, id LIMIT ?2 OFFSET ?3";
+const SELECT_STEP_POSITION_SQL: &str = "SELECT steps.plan_id, (SELECT COUNT(*) FROM steps s WHERE s.plan_id = steps.plan_id AND s.deleted_at IS NULL AND (s.step_order < steps.step_order OR (s.step_order = steps.step_order AND s.id < steps.id))) FROM steps WHERE id = ?1 AND deleted_at IS NULL";
+const HAS_SHARED_STEP_ORDERS_SQL: &str = "SELECT EXISTS(SELECT 1 FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL GROUP BY step_order HAVING COUNT(*) > 1)";
+const RESPACE_STEP_ORDERS_SQL: &str = "UPDATE steps SET step_order = ranked.position * ?2 FROM (SELECT id, ROW_NUMBER() OVER (ORDER BY step_order, id) AS position FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL) AS ranked WHERE steps.id = ranked.id";
+const SELECT_STEP_DETAILS_SQL: &str = "SELECT title, description, acceptance_criteria, status, result, blocked_reason FROM steps WHERE id = ?1 AND deleted_at IS NULL";
+const UPDATE_STEP_SQL: &str = "UPDATE steps SET title = ?1, description = ?2, acceptance_criteria = ?3, status = ?4, result = ?5, blocked_reason = ?6 WHERE id = ?7";
+const SELECT_STEPS_BY_PLAN_SQL: &str = "SELECT id, plan_id, title, description, acceptance_criteria, status, result, ROW_NUMBER() OVER (ORDER BY step_order, id) - 1, created_at, updated_at, phase, verification_required, verified_by, verification_comment, verified_at, estimate_minutes, expected_output, started_at, completed_at, blocked_reason FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL ORDER BY step_order, id";
+const SELECT_STEP_OUTLINE_BY_PLAN_SQL: &str = "SELECT id, ROW_NUMBER() OVER (ORDER BY step_order, id) - 1, title, status FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL ORDER BY step_order, id";
+const SELECT_MATCHING_STEPS_SQL: &str = "SELECT id, plan_id, title, description, acceptance_criteria, status, result, step_index, created_at, updated_at, phase, verification_required, verified_by, verification_comment, verified_at, estimate_minutes, expected_output, started_at, completed_at, blocked_reason FROM (SELECT *, ROW_NUMBER() OVER (ORDER BY step_order, id) - 1 AS step_index FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL) WHERE (title LIKE ?2 ESCAPE '\\' OR description LIKE ?2 ESCAPE '\\') AND (?3 IS NULL OR status = ?3) ORDER BY step_index";
+const SELECT_STEPS_BY_STATUS_ACROSS_PLANS_SQL: &str = "SELECT s.id, s.plan_id, s.title, s.description, s.acceptance_criteria, s.status, s.result, (SELECT COUNT(*) FROM steps o WHERE o.plan_id = s.plan_id AND o.deleted_at IS NULL AND (o.step_order < s.step_order OR (o.step_order = s.step_order AND o.id < s.id))), s.created_at, s.updated_at, s.phase, s.verification_required, s.verified_by, s.verification_comment, s.verified_at, s.estimate_minutes, s.expected_output, s.started_at, s.completed_at, s.blocked_reason FROM steps s JOIN plans p ON p.id = s.plan_id WHERE s.deleted_at IS NULL AND s.status = ?1 AND (?2 OR p.status = 'active') ORDER BY s.updated_at DESC, s.id DESC";
+const SELECT_STEP_OUTLINE_BY_STATUS_SQL: &str = "SELECT id, (SELECT COUNT(*) FROM steps s WHERE s.plan_id = steps.plan_id AND s.deleted_at IS NULL AND (s.step_order < steps.step_order OR (s.step_order = steps.step_order AND s.id < steps.id))), title, status FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL AND status = ?2 ORDER BY step_order, id";
+const SELECT_STEP_BY_ID_SQL: &str = "SELECT id, plan_id, title, description, acceptance_criteria, status, result, (SELECT COUNT(*) FROM steps s WHERE s.plan_id = steps.plan_id AND s.deleted_at IS NULL AND (s.step_order < steps.step_order OR (s.step_order = steps.step_order AND s.id < steps.id))), created_at, updated_at, phase, verification_required, verified_by, verification_comment, verified_at, estimate_minutes, expected_output, started_at, completed_at, blocked_reason FROM steps WHERE id = ?1 AND deleted_at IS NULL";
+const SELECT_NEXT_STEP_SQL: &str = "SELECT id, plan_id, title, description, acceptance_criteria, status, result, (SELECT COUNT(*) FROM steps s WHERE s.plan_id = steps.plan_id AND s.deleted_at IS NULL AND (s.step_order < steps.step_order OR (s.step_order = steps.step_order AND s.id < steps.id))), created_at, updated_at, phase, verification_required, verified_by, verification_comment, verified_at, estimate_minutes, expected_output, started_at, completed_at, blocked_reason FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL AND status = 'todo' AND NOT EXISTS (SELECT 1 FROM step_dependencies d JOIN steps t ON t.id = d.depends_on_step_id WHERE d.step_id = steps.id AND t.deleted_at IS NULL AND t.status != 'done') ORDER BY step_order, id LIMIT 1";
+const UPDATE_STEP_PHASE_SQL: &str =
+    "UPDATE steps SET phase = ?1 WHERE id = ?2 AND deleted_at IS NULL";
+const UPDATE_STEP_ESTIMATE_SQL: &str =
+    "UPDATE steps SET estimate_minutes = ?1 WHERE id = ?2 AND deleted_at IS NULL";
+const UPDATE_STEP_VERIFICATION_REQUIRED_SQL: &str =
+    "UPDATE steps SET verification_required = ?1 WHERE id = ?2";
+const UPDATE_STEP_EXPECTED_OUTPUT_SQL: &str = "UPDATE steps SET expected_output = ?1 WHERE id = ?2";
+const MARK_STEP_STARTED_SQL: &str =
+    "UPDATE steps SET started_at = COALESCE(started_at, ?1) WHERE id = ?2";
+const UPDATE_STEP_COMPLETED_AT_SQL: &str = "UPDATE steps SET completed_at = ?1 WHERE id = ?2";
+const CLEAR_STEP_VERIFICATION_SQL: &str = "UPDATE steps SET verified_by = NULL, verification_comment = NULL, verified_at = NULL WHERE id = ?1";
+const VERIFY_STEP_SQL: &str = "UPDATE steps SET verified_by = ?1, verification_comment = ?2, verified_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = ?3";
+const SELECT_STEP_PLAN_ID_SQL: &str =
+    "SELECT plan_id FROM steps WHERE id = ?1 AND deleted_at IS NULL";
+const SELECT_STEP_STATE_SQL: &str = "SELECT s.status, s.verification_required, s.verified_by, p.status = 'archived' FROM steps s JOIN plans p ON p.id = s.plan_id WHERE s.id = ?1 AND s.deleted_at IS NULL";
+const UPDATE_STEP_STATUS_FROM_SQL: &str =
+    "UPDATE steps SET status = ?1 WHERE id = ?2 AND status = ?3";
+pub(super) const SELECT_PLAN_STEP_IDS_SQL: &str =
+    "SELECT id FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL ORDER BY step_order, id";
+const RESET_PLAN_STEPS_SQL: &str = "UPDATE steps SET status = 'todo', result = CASE WHEN ?2 THEN NULL ELSE result END, blocked_reason = NULL, started_at = NULL, completed_at = NULL, verified_by = NULL, verification_comment = NULL, verified_at = NULL, updated_at = ?3 WHERE plan_id = ?1 AND deleted_at IS NULL";
+const SELECT_STEP_ORDER_SQL: &str =
+    "SELECT plan_id, step_order FROM steps WHERE id = ?1 AND deleted_at IS NULL";
+const UPDATE_STEP_ORDER_TEMP_SQL: &str = "UPDATE steps SET step_order = -1 WHERE id = ?1";
+const UPDATE_STEP_ORDER_SQL: &str = "UPDATE steps SET step_order = ?1 WHERE id = ?2";
+const MOVE_STEP_TO_PLAN_SQL: &str = "UPDATE steps SET plan_id = ?1, step_order = ?2 WHERE id = ?3";
+const COUNT_COMPLETIONS_IN_RANGE_SQL: &str = "SELECT COUNT(*) FROM steps WHERE status = ?1 AND deleted_at IS NULL AND julianday(COALESCE(completed_at, updated_at)) >= julianday(?2) AND julianday(COALESCE(completed_at, updated_at)) < julianday(?3)";
+const SELECT_DUPLICATE_CANDIDATES_SQL: &str = "SELECT lower(trim(s.title)) AS title_key, s.id, s.title, s.status, p.id, p.title, p.directory FROM steps s JOIN plans p ON p.id = s.plan_id WHERE p.status = 'active' AND s.deleted_at IS NULL AND s.status != 'done' AND (?1 IS NULL OR p.directory = ?1 OR p.directory LIKE ?2 ESCAPE '\\') ORDER BY p.id, s.step_order, s.id";
+const SELECT_COMPLETION_TIMES_SQL: &str = "SELECT plan_id, COALESCE(completed_at, updated_at) FROM steps WHERE status = ?1 AND deleted_at IS NULL";
+const AUTO_ARCHIVE_COMPLETED_PLAN_BY_STEP_SQL: &str = "UPDATE plans SET status = 'archived' WHERE id = (SELECT plan_id FROM steps WHERE id = ?1) AND auto_archive = 1 AND status = 'active' AND NOT EXISTS (SELECT 1 FROM steps WHERE plan_id = plans.id AND deleted_at IS NULL AND (status != 'done' OR (verification_required = 1 AND verified_at IS NULL))) AND NOT EXISTS (SELECT 1 FROM plans c WHERE c.parent_plan_id = plans.id AND c.status = 'active')";
+const UPDATE_STEP_TIMESTAMPS_SQL: &str =
+    "UPDATE steps SET created_at = ?1, updated_at = ?2 WHERE id = ?3";
+
+/// Gap left between the stored orders of adjacent steps.
+///
+/// Steps are stored with spaced orders (1024, 2048, ...) so that inserting
+/// between two steps takes the midpoint of their orders without renumbering
+/// the others. Only when two neighbors have no gap left is the plan
+/// respaced. The contiguous 0-based position exposed as `Step::order` is
+/// computed when reading.
+pub(super) const STEP_ORDER_SPACING: i64 = 1024;
+
+/// Title, description, acceptance criteria, status, result and blocked
+/// reason of a step, as stored.
+type StepDetails = (
+    String,
+    Option<String>,
+    Option<String>,
+    String,
+    Option<String>,
+    Option<String>,
+);
 
 impl super::Database {
     /// Helper function to construct a Step from a database row
-    fn build_step_from_row(row: &rusqlite::Row) -> rusqlite::Result<Step> {
-        let status_str: String = row.get(6)?;
+    ///
+    /// The step's references, dependencies and notes are stored separately
+    /// and left empty; see [`Self::with_related`].
+    pub(super) fn build_step_from_row(row: &rusqlite::Row) -> rusqlite::Result<Step> {
+        let status_str: String = row.get(5)?;
         let status = status_str.parse::<StepStatus>().map_err(|_| {
             rusqlite::Error::FromSqlConversionFailure(
-                6,
+                5,
                 Type::Text,
                 format!("Invalid status: {status_str}").into(),
             )
         })?;
 
-        // Parse references from comma-separated string
-        let references_str: Option<String> = row.get(5)?;
-        let references = references_str
-            .map(|s| s.split(',').map(String::from).collect())
-            .unwrap_or_default();
-
         Ok(Step {
             id: row.get::<_, i64>(0)? as u64,
             plan_id: row.get::<_, i64>(1)? as u64,
             title: row.get(2)?,
             description: row.get(3)?,
             acceptance_criteria: row.get(4)?,
-            references,
+            expected_output: row.get(16)?,
+            references: Vec::new(),
             status,
-            result: row.get(7)?,
-            order: row.get::<_, i64>(8)? as u32,
-            created_at: row.get::<_, String>(9)?.parse::<Timestamp>().map_err(|e| {
+            result: row.get(6)?,
+            order: row.get::<_, i64>(7)? as u32,
+            created_at: row.get::<_, String>(8)?.parse::<Timestamp>().map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(8, Type::Text, Box::new(e))
+            })?,
+            updated_at: row.get::<_, String>(9)?.parse::<Timestamp>().map_err(|e| {
                 rusqlite::Error::FromSqlConversionFailure(9, Type::Text, Box::new(e))
             })?,
-            updated_at: row
-                .get::<_, String>(10)?
-                .parse::<Timestamp>()
-                .map_err(|e| {
-                    rusqlite::Error::FromSqlConversionFailure(10, Type::Text, Box::new(e))
-                })?,
+            phase: row.get(10)?,
+            estimate_minutes: row.get(15)?,
+            verification_required: row.get(11)?,
+            verification: Self::verification_from_row(row)?,
+            depends_on: Vec::new(),
+            notes: Vec::new(),
+            blocked_reason: row.get(19)?,
+            started_at: Self::optional_timestamp(row, 17)?,
+            completed_at: Self::optional_timestamp(row, 18)?,
         })
     }
+
+    /// Reads a nullable timestamp column of a step row.
+    fn optional_timestamp(
+        row: &rusqlite::Row,
+        index: usize,
+    ) -> rusqlite::Result<Option<Timestamp>> {
+        row.get::<_, Option<String>>(index)?
+            .map(|text| {
+                text.parse::<Timestamp>().map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e))
+                })
+            })
+            .transpose()
+    }
+    /// Fills in the references, dependencies and notes of a step read with
+    /// [`Self::build_step_from_row`].
+    fn with_related(conn: &Connection, mut step: Step) -> Result<Step> {
+        step.references = Self::load_step_references(conn, step.id)?;
+        step.depends_on = Self::load_step_dependencies(conn, step.id)?;
+        step.notes = Self::load_step_notes(conn, step.id)?;
+        Ok(step)
+    }
+
+    /// Reads the verification columns of a step row, present once verified.
+    fn verification_from_row(row: &rusqlite::Row) -> rusqlite::Result<Option<StepVerification>> {
+        let Some(verifier) = row.get::<_, Option<String>>(12)? else {
+            return Ok(None);
+        };
+        let verified_at = row
+            .get::<_, String>(14)?
+            .parse::<Timestamp>()
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(14, Type::Text, Box::new(e)))?;
+
+        Ok(Some(StepVerification {
+            verifier,
+            comment: row.get(13)?,
+            verified_at,
+        }))
+    }
+
+    /// Inserts a step row after the last step of a plan, keeping the
+    /// request's status and result, without touching other steps or the
+    /// plan.
+    ///
+    /// Done steps must carry a result; the caller owns the transaction.
+    pub(super) fn insert_step_row(
+        conn: &Connection,
+        plan_id: u64,
+        step: &CreateStepRequest,
+        now: &str,
+    ) -> Result<u64> {
+        if step.status == StepStatus::Done && step.result.is_none() {
+            return Err(PlannerError::InvalidInput {
+                field: "result".into(),
+                reason: format!("Step '{}' is done but has no result", step.title),
+            });
+        }
+
+        let result = (step.status == StepStatus::Done)
+            .then_some(step.result.as_deref())
+            .flatten();
+        let (order, _): (i64, i64) = conn
+            .query_row(
+                GET_NEXT_STEP_ORDER_SQL,
+                params![plan_id as i64, STEP_ORDER_SPACING],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| PlannerError::database_error("Failed to get next step order", e))?;
+        let started_at = (step.status == StepStatus::InProgress).then_some(now);
+        let completed_at = (step.status == StepStatus::Done).then_some(now);
+
+        conn.execute(
+            INSERT_STEP_SQL,
+            params![
+                plan_id as i64,
+                &step.title,
+                step.description.as_deref(),
+                step.acceptance_criteria.as_deref(),
+                step.status.as_str(),
+                result,
+                order,
+                now,
+                now,
+                started_at,
+                completed_at
+            ],
+        )
+        .map_err(|e| PlannerError::database_error("Failed to insert step", e))?;
+
+        let id = conn.last_insert_rowid() as u64;
+        Self::replace_step_references(conn, id, &step.references)?;
+        Ok(id)
+    }
+
+    /// Inserts a todo step row after the last step of a plan, returning its
+    /// ID and position. The caller owns the transaction.
+    pub(super) fn append_step_row(
+        conn: &Connection,
+        plan_id: u64,
+        title: &str,
+        description: Option<&str>,
+        acceptance_criteria: Option<&str>,
+        now: &str,
+    ) -> Result<(u64, u32)> {
+        let (next_order, position): (i64, i64) = conn
+            .query_row(
+                GET_NEXT_STEP_ORDER_SQL,
+                params![plan_id as i64, STEP_ORDER_SPACING],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| PlannerError::database_error("Failed to get next step order", e))?;
+
+        conn.execute(
+            INSERT_STEP_SQL,
+            params![
+                plan_id as i64,
+                title,
+                description,
+                acceptance_criteria,
+                "todo",
+                None::<String>, // result is NULL for new steps
+                next_order,
+                now,
+                now,
+                None::<String>,
+                None::<String>
+            ],
+        )
+        .map_err(|e| PlannerError::database_error("Failed to insert step", e))?;
+
+        Ok((conn.last_insert_rowid() as u64, position as u32))
+    }
+
     /// Adds a new step to the specified plan.
     pub fn add_step(
         &mut self,
@@ -81,7 +271,7 @@ impl super::Database {
         title: &str,
         description: Option<&str>,
         acceptance_criteria: Option<&str>,
-        references: Vec<String>,
+        references: Vec<StepReference>,
     ) -> Result<Step> {
         let tx = self
             .connection
@@ -99,44 +289,19 @@ impl super::Database {
             return Err(PlannerError::PlanNotFound { id: plan_id });
         }
 
-        let next_order: i64 = tx
-            .query_row(GET_MAX_STEP_ORDER_SQL, params![plan_id as i64], |row| {
-                row.get(0)
-            })
-            .map_err(|e| PlannerError::database_error("Failed to get next step order", e))?;
-
-        let now = Timestamp::now();
-        let now_str = now.to_string();
+        let now = super::now();
+        let now_str = super::format_timestamp(now);
 
-        // Store references as comma-separated string
-        let references_str = if references.is_empty() {
-            None
-        } else {
-            Some(references.join(","))
-        };
-
-        tx.execute(
-            INSERT_STEP_SQL,
-            params![
-                plan_id as i64,
-                title,
-                description,
-                acceptance_criteria,
-                references_str.as_deref(),
-                "todo",
-                None::<String>, // result is NULL for new steps
-                next_order,
-                &now_str,
-                &now_str
-            ],
-        )
-        .map_err(|e| PlannerError::database_error("Failed to insert step", e))?;
-
-        let id = tx.last_insert_rowid() as u64;
-
-        // Update plan's updated_at
-        tx.execute(UPDATE_PLAN_TIMESTAMP_SQL, params![&now_str, plan_id as i64])
-            .map_err(|e| PlannerError::database_error("Failed to update plan timestamp", e))?;
+        let (id, position) = Self::append_step_row(
+            &tx,
+            plan_id,
+            title,
+            description,
+            acceptance_criteria,
+            &now_str,
+        )?;
+        Self::replace_step_references(&tx, id, &references)?;
+        Self::record_step_event(&tx, id, EventKind::Added)?;
 
         tx.commit().db_context("Failed to commit transaction")?;
 
@@ -146,18 +311,30 @@ impl super::Database {
             title: title.into(),
             description: description.map(String::from),
             acceptance_criteria: acceptance_criteria.map(String::from),
+            expected_output: None,
             references,
             status: StepStatus::Todo,
             result: None, // New steps have no result
-            order: next_order as u32,
+            order: position,
+            phase: None,
+            estimate_minutes: None,
+            verification_required: false,
+            verification: None,
+            depends_on: Vec::new(),
+            notes: Vec::new(),
+            blocked_reason: None,
+            started_at: None,
+            completed_at: None,
             created_at: now,
             updated_at: now,
         })
     }
 
     /// Inserts a new step at a specific position in the plan's step order.
-    /// All steps at or after the specified position will have their order
-    /// incremented.
+    /// Steps at or after the position move down by one.
+    ///
+    /// The new step takes the midpoint of its neighbors' stored orders, so no
+    /// other step is updated unless the plan has to be respaced.
     pub fn insert_step(
         &mut self,
         plan_id: u64,
@@ -165,7 +342,7 @@ impl super::Database {
         title: &str,
         description: Option<&str>,
         acceptance_criteria: Option<&str>,
-        references: Vec<String>,
+        references: Vec<StepReference>,
     ) -> Result<Step> {
         let tx = self
             .connection
@@ -183,16 +360,14 @@ impl super::Database {
             return Err(PlannerError::PlanNotFound { id: plan_id });
         }
 
-        let max_order: Option<i64> = tx
-            .query_row(
-                GET_MAX_STEP_ORDER_ONLY_SQL,
-                params![plan_id as i64],
-                |row| row.get(0),
-            )
-            .map_err(|e| PlannerError::database_error("Failed to get max step order", e))?;
+        let step_count: i64 = tx
+            .query_row(COUNT_PLAN_STEPS_SQL, params![plan_id as i64], |row| {
+                row.get(0)
+            })
+            .map_err(|e| PlannerError::database_error("Failed to count steps", e))?;
 
         // Validate position - allow inserting at the end (position == count)
-        let step_count = max_order.map(|m| m + 1).unwrap_or(0) as u32;
+        let step_count = step_count as u32;
         if position > step_count {
             return Err(PlannerError::InvalidInput {
                 field: "position".into(),
@@ -200,22 +375,10 @@ impl super::Database {
             });
         }
 
-        // Update existing steps' order to make room for the new step
-        tx.execute(
-            UPDATE_STEP_ORDERS_INCREMENT_SQL,
-            params![plan_id as i64, position as i64],
-        )
-        .map_err(|e| PlannerError::database_error("Failed to update step orders", e))?;
+        let order = Self::order_for_position(&tx, plan_id, position, None)?;
 
-        let now = Timestamp::now();
-        let now_str = now.to_string();
-
-        // Store references as comma-separated string
-        let references_str = if references.is_empty() {
-            None
-        } else {
-            Some(references.join(","))
-        };
+        let now = super::now();
+        let now_str = super::format_timestamp(now);
 
         // Insert the new step at the specified position
         tx.execute(
@@ -225,21 +388,21 @@ impl super::Database {
                 title,
                 description,
                 acceptance_criteria,
-                references_str.as_deref(),
                 "todo",
                 None::<String>, // result is NULL for new steps
-                position as i64,
+                order,
+                &now_str,
                 &now_str,
-                &now_str
+                None::<String>,
+                None::<String>
             ],
         )
         .map_err(|e| PlannerError::database_error("Failed to insert step", e))?;
 
         let id = tx.last_insert_rowid() as u64;
-
-        // Update plan's updated_at
-        tx.execute(UPDATE_PLAN_TIMESTAMP_SQL, params![&now_str, plan_id as i64])
-            .map_err(|e| PlannerError::database_error("Failed to update plan timestamp", e))?;
+        Self::replace_step_references(&tx, id, &references)?;
+        Self::repair_step_orders(&tx, plan_id)?;
+        Self::record_step_event(&tx, id, EventKind::Added)?;
 
         tx.commit().db_context("Failed to commit transaction")?;
 
@@ -249,37 +412,40 @@ impl super::Database {
             title: title.into(),
             description: description.map(String::from),
             acceptance_criteria: acceptance_criteria.map(String::from),
+            expected_output: None,
             references,
             status: StepStatus::Todo,
             result: None, // New steps have no result
             order: position,
+            phase: None,
+            estimate_minutes: None,
+            verification_required: false,
+            verification: None,
+            depends_on: Vec::new(),
+            notes: Vec::new(),
+            blocked_reason: None,
+            started_at: None,
+            completed_at: None,
             created_at: now,
             updated_at: now,
         })
     }
 
     /// Updates step details using a request struct to reduce argument count.
-    /// When changing status to Done, result is required.
-    /// Result is ignored when changing to Todo or InProgress.
+    /// When changing status to Done, a result is required unless the step
+    /// kept one from an earlier completion, which is then reused. Reopening
+    /// a done step keeps its result, hidden until it is done again, unless
+    /// `clear_result` is set. Result is ignored when changing to Todo or
+    /// InProgress, and replaces the stored one when a done step is edited
+    /// without changing its status. Likewise a blocked step's reason is
+    /// changed without setting its status again, which is refused as a
+    /// transition that changes nothing.
+    ///
+    /// Marking the last open step of a plan with `auto_archive` set as done
+    /// archives the plan in the same transaction.
     pub fn update_step(&mut self, step_id: u64, request: UpdateStepRequest) -> Result<()> {
-        // Validate result requirement when changing status to Done
-        if let Some(StepStatus::Done) = request.status
-            && request.result.is_none()
-        {
-            return Err(PlannerError::InvalidInput {
-                field: "result".into(),
-                reason: "Result description is required when marking a step as done".into(),
-            });
-        }
-
         // Check if there's anything to update
-        if request.title.is_none()
-            && request.description.is_none()
-            && request.acceptance_criteria.is_none()
-            && request.references.is_none()
-            && request.status.is_none()
-            && request.result.is_none()
-        {
+        if request.is_empty() {
             return Ok(());
         }
 
@@ -293,67 +459,65 @@ impl super::Database {
             current_title,
             current_desc,
             current_criteria,
-            current_refs,
             current_status,
             current_result,
-        ): (
-            String,
-            Option<String>,
-            Option<String>,
-            Option<String>,
-            String,
-            Option<String>,
-        ) = {
-            let mut stmt = tx.prepare(SELECT_STEP_DETAILS_SQL).map_err(|e| {
-                PlannerError::database_error("Failed to prepare select statement", e)
-            })?;
+            current_blocked_reason,
+        ) = Self::select_step_details(&tx, step_id)?;
+
+        if let Some(new_status) = request.status {
+            Self::check_transition(
+                &tx,
+                step_id,
+                StepTransition::for_status(new_status),
+                "status",
+            )?;
+        }
 
-            stmt.query_row(params![step_id as i64], |row| {
-                Ok((
-                    row.get(0)?,
-                    row.get(1)?,
-                    row.get(2)?,
-                    row.get(3)?,
-                    row.get(4)?,
-                    row.get(5)?,
-                ))
-            })
-            .map_err(|e| {
-                if matches!(e, rusqlite::Error::QueryReturnedNoRows) {
-                    PlannerError::StepNotFound { id: step_id }
-                } else {
-                    PlannerError::database_error("Failed to get current step", e)
-                }
-            })?
-        };
+        let verification_required = request.verification_required;
+        let expected_output = request.expected_output.clone();
+        let status_changed = request.status.is_some();
+        let was_done = current_status == StepStatus::Done.as_str();
 
         // Use provided values or keep current ones
         let new_title = request.title.unwrap_or(current_title);
         let new_description = request.description.or(current_desc);
         let new_criteria = request.acceptance_criteria.or(current_criteria);
-        let new_references = request
-            .references
-            .map(|refs| refs.join(","))
-            .or(current_refs);
         let new_status_str = request
             .status
             .map(|s| s.as_str().into())
             .unwrap_or(current_status);
 
-        // Determine the result value based on the status change
-        let new_result = if let Some(new_status) = request.status {
-            match new_status {
-                StepStatus::Done => request.result, // Use provided result (already validated as
-                // required)
-                StepStatus::Todo | StepStatus::InProgress => None, /* Clear result for non-done
-                                                                    * statuses */
+        // A new result only counts when completing the step or editing a
+        // done one; otherwise the stored one is kept, including when the step
+        // is reopened
+        let new_result = match request.status {
+            Some(StepStatus::Done) => request.result.or(current_result),
+            None if was_done => request.result.or(current_result),
+            _ => current_result,
+        };
+        let new_result = if request.clear_result {
+            if new_status_str == StepStatus::Done.as_str() {
+                return Err(PlannerError::invalid_input("clear_result")
+                    .with_reason("A done step keeps its result; reopen it to clear the result"));
             }
+            None
         } else {
-            // Status not changing, preserve existing result
-            current_result
+            new_result
         };
+        if request.status == Some(StepStatus::Done) && new_result.is_none() {
+            return Err(PlannerError::invalid_input("result")
+                .with_reason("Result description is required when marking a step as done"));
+        }
 
-        let now_str = Timestamp::now().to_string();
+        if request.blocked_reason.is_some() && new_status_str != StepStatus::Blocked.as_str() {
+            return Err(PlannerError::invalid_input("blocked_reason")
+                .with_reason("A blocked reason can only be given for a blocked step"));
+        }
+        // The reason is only kept while the step stays blocked
+        let new_blocked_reason = match request.status {
+            Some(StepStatus::Blocked) | None => request.blocked_reason.or(current_blocked_reason),
+            Some(_) => None,
+        };
 
         // Update the step
         tx.execute(
@@ -362,24 +526,192 @@ impl super::Database {
                 &new_title,
                 &new_description,
                 &new_criteria,
-                &new_references,
                 &new_status_str,
                 &new_result,
-                &now_str,
+                &new_blocked_reason,
                 step_id as i64
             ],
         )
         .map_err(|e| PlannerError::database_error("Failed to update step", e))?;
 
-        // Update plan's updated_at
+        if let Some(references) = &request.references {
+            Self::replace_step_references(&tx, step_id, references)?;
+        }
+        if let Some(new_status) = request.status {
+            Self::update_step_times(&tx, step_id, new_status, was_done)?;
+        }
+        Self::update_verification(&tx, step_id, verification_required, status_changed)?;
+        if let Some(expected_output) = expected_output {
+            tx.execute(
+                UPDATE_STEP_EXPECTED_OUTPUT_SQL,
+                params![expected_output, step_id as i64],
+            )
+            .map_err(|e| PlannerError::database_error("Failed to update step", e))?;
+        }
+
+        Self::record_step_event(&tx, step_id, EventKind::Updated)?;
+        if request.status == Some(StepStatus::Done) {
+            Self::auto_archive_completed_plan(&tx, step_id)?;
+        }
+
+        tx.commit().db_context("Failed to commit transaction")?;
+
+        Ok(())
+    }
+
+    /// Records when `step_id` was started and completed as its status changes
+    /// to `new_status`, `was_done` telling whether it was done before.
+    ///
+    /// The start is kept from the first time the step was put in progress;
+    /// the completion is set when the step becomes done and cleared when it
+    /// is reopened.
+    fn update_step_times(
+        conn: &Connection,
+        step_id: u64,
+        new_status: StepStatus,
+        was_done: bool,
+    ) -> Result<()> {
+        let now = super::format_timestamp(super::now());
+        match new_status {
+            StepStatus::Done if !was_done => {
+                conn.execute(UPDATE_STEP_COMPLETED_AT_SQL, params![now, step_id as i64])
+            }
+            StepStatus::Done => return Ok(()),
+            StepStatus::InProgress => conn
+                .execute(MARK_STEP_STARTED_SQL, params![now, step_id as i64])
+                .and_then(|_| {
+                    conn.execute(
+                        UPDATE_STEP_COMPLETED_AT_SQL,
+                        params![None::<String>, step_id as i64],
+                    )
+                }),
+            StepStatus::Todo | StepStatus::Blocked => conn.execute(
+                UPDATE_STEP_COMPLETED_AT_SQL,
+                params![None::<String>, step_id as i64],
+            ),
+        }
+        .map_err(|e| PlannerError::database_error("Failed to update step times", e))?;
+        Ok(())
+    }
+
+    /// Applies a change to whether `step_id` needs verifying, and drops any
+    /// recorded verification when its status changes: a step reopened or
+    /// completed again needs verifying afresh.
+    fn update_verification(
+        conn: &Connection,
+        step_id: u64,
+        verification_required: Option<bool>,
+        status_changed: bool,
+    ) -> Result<()> {
+        if let Some(required) = verification_required {
+            conn.execute(
+                UPDATE_STEP_VERIFICATION_REQUIRED_SQL,
+                params![required, step_id as i64],
+            )
+            .map_err(|e| PlannerError::database_error("Failed to update step", e))?;
+        }
+        if status_changed {
+            conn.execute(CLEAR_STEP_VERIFICATION_SQL, params![step_id as i64])
+                .map_err(|e| PlannerError::database_error("Failed to clear verification", e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads the fields of a step that [`Self::update_step`] may change.
+    fn select_step_details(conn: &Connection, step_id: u64) -> Result<StepDetails> {
+        let mut stmt = conn
+            .prepare(SELECT_STEP_DETAILS_SQL)
+            .map_err(|e| PlannerError::database_error("Failed to prepare select statement", e))?;
+
+        stmt.query_row(params![step_id as i64], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+            ))
+        })
+        .map_err(|e| {
+            if matches!(e, rusqlite::Error::QueryReturnedNoRows) {
+                PlannerError::StepNotFound { id: step_id }
+            } else {
+                PlannerError::database_error("Failed to get current step", e)
+            }
+        })
+    }
+
+    /// Records that `verifier` confirmed the completion of a step requiring
+    /// verification, which then counts towards its plan's progress.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist, and
+    /// `PlannerError::InvalidInput` if the verifier is empty or the step
+    /// doesn't require verification, isn't done, or was already verified
+    pub fn verify_step(
+        &mut self,
+        step_id: u64,
+        verifier: &str,
+        comment: Option<&str>,
+    ) -> Result<Step> {
+        if verifier.trim().is_empty() {
+            return Err(PlannerError::invalid_input("verifier")
+                .with_reason("A verifier is required to verify a step"));
+        }
+
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+
+        Self::check_transition(&tx, step_id, StepTransition::Verify, "id")?;
+
         tx.execute(
-            UPDATE_PLAN_TIMESTAMP_BY_STEP_SQL,
-            params![&now_str, step_id as i64],
+            VERIFY_STEP_SQL,
+            params![verifier.trim(), comment, step_id as i64],
         )
-        .map_err(|e| PlannerError::database_error("Failed to update plan timestamp", e))?;
+        .map_err(|e| PlannerError::database_error("Failed to verify step", e))?;
+        Self::record_step_event(&tx, step_id, EventKind::Verified)?;
+
+        // Verifying the last unverified step completes the plan
+        Self::auto_archive_completed_plan(&tx, step_id)?;
+
+        let step = tx
+            .query_row(
+                SELECT_STEP_BY_ID_SQL,
+                params![step_id as i64],
+                Self::build_step_from_row,
+            )
+            .map_err(|e| PlannerError::database_error("Failed to get verified step", e))?;
+        let step = Self::with_related(&tx, step)?;
 
         tx.commit().db_context("Failed to commit transaction")?;
 
+        Ok(step)
+    }
+
+    /// Archives the plan owning `step_id` if it opted into auto-archiving and
+    /// all of its steps are now done. Plans with active child plans are left
+    /// alone, as when archiving without force.
+    fn auto_archive_completed_plan(conn: &Connection, step_id: u64) -> Result<()> {
+        let archived = conn
+            .execute(
+                AUTO_ARCHIVE_COMPLETED_PLAN_BY_STEP_SQL,
+                params![step_id as i64],
+            )
+            .map_err(|e| PlannerError::database_error("Failed to auto-archive plan", e))?;
+
+        if archived > 0 {
+            let plan_id: i64 = conn
+                .query_row(SELECT_STEP_PLAN_ID_SQL, params![step_id as i64], |row| {
+                    row.get(0)
+                })
+                .db_context("Failed to look up step plan")?;
+            Self::record_plan_event(conn, plan_id as u64, EventKind::Archived)?;
+        }
         Ok(())
     }
 
@@ -390,19 +722,363 @@ impl super::Database {
             .prepare(SELECT_STEPS_BY_PLAN_SQL)
             .map_err(|e| PlannerError::database_error("Failed to prepare query", e))?;
 
-        let steps = stmt
+        let mut steps = stmt
             .query_map(params![plan_id as i64], Self::build_step_from_row)
             .map_err(|e| PlannerError::database_error("Failed to query steps", e))?
             .collect::<std::result::Result<Vec<_>, _>>()
             .map_err(|e| PlannerError::database_error("Failed to fetch steps", e))?;
 
+        self.load_step_details(plan_id, &mut steps)?;
         Ok(steps)
     }
 
-    /// Retrieves a single step by its ID.
-    pub fn get_step(&self, step_id: u64) -> Result<Option<Step>> {
+    /// Finds the steps of a plan whose title or description contains
+    /// `query`, ignoring ASCII case, only those with `status` if given, in
+    /// order.
+    ///
+    /// The query is matched literally: `%` and `_` are not wildcards. A plan
+    /// without matching steps gives an empty list.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    pub fn find_steps(
+        &self,
+        plan_id: u64,
+        query: &str,
+        status: Option<StepStatus>,
+    ) -> Result<Vec<Step>> {
         let mut stmt = self
             .connection
+            .prepare(SELECT_MATCHING_STEPS_SQL)
+            .db_context("Failed to prepare query")?;
+
+        let pattern = format!("%{}%", escape_like(query));
+        let mut steps = stmt
+            .query_map(
+                params![plan_id as i64, pattern, status.map(|s| s.as_str())],
+                Self::build_step_from_row,
+            )
+            .and_then(Iterator::collect::<rusqlite::Result<Vec<_>>>)
+            .db_context("Failed to query steps")?;
+
+        if steps.is_empty() {
+            let plan_exists: bool = self
+                .connection
+                .query_row(CHECK_PLAN_EXISTS_SQL, params![plan_id as i64], |row| {
+                    row.get(0)
+                })
+                .db_context("Failed to check plan existence")?;
+            if !plan_exists {
+                return Err(PlannerError::PlanNotFound { id: plan_id });
+            }
+        }
+
+        self.load_step_details(plan_id, &mut steps)?;
+        Ok(steps)
+    }
+
+    /// Lists the steps with `status` across plans, each with the summary of
+    /// its plan, most recently updated first.
+    ///
+    /// Steps of archived plans are left out unless `include_archived_plans`
+    /// is set. Plans are only read when they have a matching step, and the
+    /// steps and summaries are read from one snapshot.
+    pub fn list_steps_by_status(
+        &self,
+        status: StepStatus,
+        include_archived_plans: bool,
+    ) -> Result<Vec<(PlanSummary, Step)>> {
+        self.read_consistent(|db| {
+            let mut stmt = db
+                .connection
+                .prepare(SELECT_STEPS_BY_STATUS_ACROSS_PLANS_SQL)
+                .db_context("Failed to prepare query")?;
+            let mut steps = stmt
+                .query_map(
+                    params![status.as_str(), include_archived_plans],
+                    Self::build_step_from_row,
+                )
+                .and_then(Iterator::collect::<rusqlite::Result<Vec<_>>>)
+                .db_context("Failed to query steps")?;
+
+            let mut summaries = HashMap::new();
+            for plan_id in steps
+                .iter()
+                .map(|step| step.plan_id)
+                .collect::<BTreeSet<_>>()
+            {
+                db.load_step_details(
+                    plan_id,
+                    steps.iter_mut().filter(|step| step.plan_id == plan_id),
+                )?;
+                let summary = db
+                    .get_plan_summary(plan_id)?
+                    .ok_or(PlannerError::PlanNotFound { id: plan_id })?;
+                summaries.insert(plan_id, summary);
+            }
+
+            Ok(steps
+                .into_iter()
+                .map(|step| (summaries[&step.plan_id].clone(), step))
+                .collect())
+        })
+    }
+
+    /// Fills in the references, dependencies and notes of steps of a plan.
+    pub(super) fn load_step_details<'a>(
+        &self,
+        plan_id: u64,
+        steps: impl IntoIterator<Item = &'a mut Step>,
+    ) -> Result<()> {
+        let mut dependencies = Self::load_plan_dependencies(&self.connection, plan_id)?;
+        let mut notes = Self::load_plan_notes(&self.connection, plan_id)?;
+        let mut references = Self::load_plan_references(&self.connection, plan_id)?;
+        for step in steps {
+            step.references = references.remove(&step.id).unwrap_or_default();
+            step.depends_on = dependencies.remove(&step.id).unwrap_or_default();
+            step.notes = notes.remove(&step.id).unwrap_or_default();
+        }
+        Ok(())
+    }
+
+    /// Retrieves the outline of a plan's steps in order.
+    ///
+    /// Only ids, orders, titles and statuses are read, which keeps this cheap
+    /// for plans with large step descriptions or results.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    pub fn get_plan_outline(&self, plan_id: u64) -> Result<Vec<StepOutline>> {
+        self.list_steps(plan_id, None)
+    }
+
+    /// Counts a plan's steps in each status, without loading them.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    pub fn step_status_counts(&self, plan_id: u64) -> Result<StepStatusCounts> {
+        self.read_consistent(|db| {
+            let counts = db
+                .connection
+                .query_row(
+                    COUNT_PLAN_STEPS_BY_STATUS_SQL,
+                    params![plan_id as i64],
+                    |row| {
+                        Ok(StepStatusCounts {
+                            done: row.get(0)?,
+                            in_progress: row.get(1)?,
+                            todo: row.get(2)?,
+                            blocked: row.get(3)?,
+                        })
+                    },
+                )
+                .map_err(|e| PlannerError::database_error("Failed to count steps", e))?;
+
+            if counts.total() == 0 {
+                let plan_exists: bool = db
+                    .connection
+                    .query_row(CHECK_PLAN_EXISTS_SQL, params![plan_id as i64], |row| {
+                        row.get(0)
+                    })
+                    .map_err(|e| {
+                        PlannerError::database_error("Failed to check plan existence", e)
+                    })?;
+                if !plan_exists {
+                    return Err(PlannerError::PlanNotFound { id: plan_id });
+                }
+            }
+            Ok(counts)
+        })
+    }
+
+    /// Retrieves the outline of a plan's steps with the given status, or of
+    /// all its steps, in order.
+    ///
+    /// Steps keep their order within the whole plan, so the orders of a
+    /// filtered list may have gaps. A plan without matching steps gives an
+    /// empty list.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    pub fn list_steps(&self, plan_id: u64, status: Option<StepStatus>) -> Result<Vec<StepOutline>> {
+        let sql = match status {
+            Some(_) => SELECT_STEP_OUTLINE_BY_STATUS_SQL,
+            None => SELECT_STEP_OUTLINE_BY_PLAN_SQL,
+        };
+        let mut stmt = self
+            .connection
+            .prepare(sql)
+            .map_err(|e| PlannerError::database_error("Failed to prepare query", e))?;
+
+        let outline_row = |row: &rusqlite::Row<'_>| {
+            let status_str: String = row.get(3)?;
+            let status = status_str.parse::<StepStatus>().map_err(|_| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    3,
+                    Type::Text,
+                    format!("Invalid status: {status_str}").into(),
+                )
+            })?;
+
+            Ok(StepOutline {
+                id: row.get::<_, i64>(0)? as u64,
+                order: row.get::<_, i64>(1)? as u32,
+                title: row.get(2)?,
+                status,
+            })
+        };
+        let rows = match status {
+            Some(status) => stmt.query_map(params![plan_id as i64, status.as_str()], outline_row),
+            None => stmt.query_map(params![plan_id as i64], outline_row),
+        };
+        let outline = rows
+            .map_err(|e| PlannerError::database_error("Failed to query step outline", e))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| PlannerError::database_error("Failed to fetch step outline", e))?;
+
+        if outline.is_empty() {
+            let plan_exists: bool = self
+                .connection
+                .query_row(CHECK_PLAN_EXISTS_SQL, params![plan_id as i64], |row| {
+                    row.get(0)
+                })
+                .map_err(|e| PlannerError::database_error("Failed to check plan existence", e))?;
+
+            if !plan_exists {
+                return Err(PlannerError::PlanNotFound { id: plan_id });
+            }
+        }
+
+        Ok(outline)
+    }
+
+    /// Returns the first step of a plan, in order, that is still to do and
+    /// not blocked by an unfinished dependency, or None if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    pub fn next_step(&self, plan_id: u64) -> Result<Option<Step>> {
+        let step = self
+            .connection
+            .query_row(
+                SELECT_NEXT_STEP_SQL,
+                params![plan_id as i64],
+                Self::build_step_from_row,
+            )
+            .optional()
+            .db_context("Failed to query next step")?;
+
+        match step {
+            Some(step) => Self::with_related(&self.connection, step).map(Some),
+            None => {
+                let plan_exists: bool = self
+                    .connection
+                    .query_row(CHECK_PLAN_EXISTS_SQL, params![plan_id as i64], |row| {
+                        row.get(0)
+                    })
+                    .db_context("Failed to check plan existence")?;
+                if plan_exists {
+                    Ok(None)
+                } else {
+                    Err(PlannerError::PlanNotFound { id: plan_id })
+                }
+            }
+        }
+    }
+
+    /// Counts steps that were marked done within `[start, end)`.
+    ///
+    /// A done step's `completed_at` is its completion time, falling back to
+    /// `updated_at` for steps completed before it was recorded. Timestamps are compared as instants rather than as
+    /// strings, since stored values vary in fractional-second precision.
+    pub fn count_completions_in_range(&self, start: Timestamp, end: Timestamp) -> Result<u32> {
+        self.connection
+            .query_row(
+                COUNT_COMPLETIONS_IN_RANGE_SQL,
+                params![
+                    StepStatus::Done.as_str(),
+                    start.to_string(),
+                    end.to_string()
+                ],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|count| count as u32)
+            .db_context("Failed to count completed steps")
+    }
+
+    /// Returns the plan ID and completion time of every done step, taken
+    /// like [`Self::count_completions_in_range`] does.
+    pub fn completion_times(&self) -> Result<Vec<(u64, Timestamp)>> {
+        let mut stmt = self
+            .connection
+            .prepare(SELECT_COMPLETION_TIMES_SQL)
+            .db_context("Failed to prepare query")?;
+
+        stmt.query_map(params![StepStatus::Done.as_str()], |row| {
+            let completed_at = row.get::<_, String>(1)?.parse::<Timestamp>().map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(1, Type::Text, Box::new(e))
+            })?;
+            Ok((row.get::<_, i64>(0)? as u64, completed_at))
+        })
+        .and_then(Iterator::collect)
+        .db_context("Failed to query completion times")
+    }
+
+    /// Finds open steps of active plans whose titles match once case,
+    /// whitespace and punctuation are ignored.
+    ///
+    /// The database lowercases and trims the titles and leaves out done
+    /// steps and archived plans; the remaining normalization and the grouping
+    /// happen in [`DuplicateGroup::group`]. With `directory`, only plans in
+    /// that directory or below it are compared; it should already be
+    /// absolute, as in [`Self::canonicalize_directory_for_search`].
+    pub fn find_duplicate_steps(&self, directory: Option<&str>) -> Result<Vec<DuplicateGroup>> {
+        let mut stmt = self
+            .connection
+            .prepare(SELECT_DUPLICATE_CANDIDATES_SQL)
+            .db_context("Failed to prepare query")?;
+
+        let pattern = directory.map(subdirectory_pattern);
+        let candidates = stmt
+            .query_map(params![directory, pattern], |row| {
+                let status_str: String = row.get(3)?;
+                let status = status_str.parse::<StepStatus>().map_err(|_| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        3,
+                        Type::Text,
+                        format!("Invalid status: {status_str}").into(),
+                    )
+                })?;
+                let step = DuplicateStep {
+                    step_id: row.get::<_, i64>(1)? as u64,
+                    title: row.get(2)?,
+                    status,
+                    plan_id: row.get::<_, i64>(4)? as u64,
+                    plan_title: row.get(5)?,
+                    plan_directory: row.get(6)?,
+                };
+                Ok((row.get::<_, String>(0)?, step))
+            })
+            .and_then(Iterator::collect::<rusqlite::Result<Vec<_>>>)
+            .db_context("Failed to query steps")?;
+
+        Ok(DuplicateGroup::group(candidates))
+    }
+
+    /// Retrieves a single step by its ID.
+    pub fn get_step(&self, step_id: u64) -> Result<Option<Step>> {
+        Self::read_step(&self.connection, step_id)
+    }
+
+    /// Reads a step that is not in the trash on `conn`, which may be in a
+    /// transaction.
+    pub(super) fn read_step(conn: &Connection, step_id: u64) -> Result<Option<Step>> {
+        let mut stmt = conn
             .prepare(SELECT_STEP_BY_ID_SQL)
             .map_err(|e| PlannerError::database_error("Failed to prepare query", e))?;
 
@@ -411,71 +1087,488 @@ impl super::Database {
             .optional()
             .map_err(|e| PlannerError::database_error("Failed to get step", e))?;
 
-        Ok(step)
+        step.map(|step| Self::with_related(conn, step)).transpose()
+    }
+
+    /// Returns the ID of the plan a step belongs to, or None if the step
+    /// doesn't exist.
+    pub fn step_plan_id(&self, step_id: u64) -> Result<Option<u64>> {
+        self.connection
+            .query_row(SELECT_STEP_PLAN_ID_SQL, params![step_id as i64], |row| {
+                row.get::<_, i64>(0)
+            })
+            .optional()
+            .map(|plan_id| plan_id.map(|id| id as u64))
+            .db_context("Failed to look up step plan")
+    }
+
+    /// Returns true if a plan with the ID exists, archived or not.
+    pub fn plan_exists(&self, plan_id: u64) -> Result<bool> {
+        self.connection
+            .query_row(CHECK_PLAN_EXISTS_SQL, params![plan_id as i64], |row| {
+                row.get(0)
+            })
+            .db_context("Failed to check plan existence")
     }
 
     /// Atomically claims a step for processing by transitioning it from Todo to
     /// InProgress. Returns the step details if successfully claimed, None if
     /// the step doesn't exist or cannot be claimed.
     pub fn claim_step(&mut self, step_id: u64) -> Result<Option<Step>> {
+        self.try_claim_step(step_id).map(ClaimOutcome::into_claimed)
+    }
+
+    /// Atomically claims a step like [`Self::claim_step`], reporting why the
+    /// step could not be claimed when it isn't.
+    pub fn try_claim_step(&mut self, step_id: u64) -> Result<ClaimOutcome> {
         let tx = self
             .connection
             .transaction()
             .db_context("Failed to begin transaction")?;
 
-        // Check current status and update atomically
-        let current_status: Option<String> = tx
-            .query_row(SELECT_STEP_STATUS_SQL, params![step_id as i64], |row| {
+        let Some(machine) = Self::load_state_machine(&tx, step_id)? else {
+            return Ok(ClaimOutcome::NotFound);
+        };
+        if let Err(reason) = machine.check(StepTransition::Claim) {
+            return match machine.status {
+                StepStatus::Todo if !machine.plan_archived && !machine.blocked_by.is_empty() => {
+                    Ok(ClaimOutcome::Blocked(machine.blocked_by))
+                }
+                StepStatus::Todo => Err(PlannerError::invalid_input("id")
+                    .with_reason(format!("Step {step_id} {reason}"))),
+                status => Ok(ClaimOutcome::NotClaimable(status)),
+            };
+        }
+
+        let step = Self::mark_claimed(&tx, step_id)?;
+        tx.commit().db_context("Failed to commit transaction")?;
+
+        Ok(step.map_or(ClaimOutcome::NotFound, |step| {
+            ClaimOutcome::Claimed(Box::new(step))
+        }))
+    }
+
+    /// Claims the plan's next step (see [`Self::next_step`]) in a single
+    /// transaction, so two agents asking for work never get the same step.
+    /// Returns None if no step is ready to be claimed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist, and
+    /// `PlannerError::InvalidInput` if the plan is archived
+    pub fn claim_next_step(&mut self, plan_id: u64) -> Result<Option<Step>> {
+        // Take the write lock up front so no other connection can claim the
+        // same step between the select and the update
+        let tx = self
+            .connection
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .db_context("Failed to begin transaction")?;
+
+        let step_id: Option<i64> = tx
+            .query_row(SELECT_NEXT_STEP_SQL, params![plan_id as i64], |row| {
                 row.get(0)
             })
             .optional()
-            .map_err(|e| PlannerError::database_error("Failed to query step status", e))?;
-
-        match current_status {
-            None => {
-                // Step doesn't exist, return None
+            .db_context("Failed to query next step")?;
+        let Some(step_id) = step_id else {
+            let plan_exists: bool = tx
+                .query_row(CHECK_PLAN_EXISTS_SQL, params![plan_id as i64], |row| {
+                    row.get(0)
+                })
+                .db_context("Failed to check plan existence")?;
+            return if plan_exists {
                 Ok(None)
+            } else {
+                Err(PlannerError::PlanNotFound { id: plan_id })
+            };
+        };
+        let step_id = step_id as u64;
+
+        if let Some(machine) = Self::load_state_machine(&tx, step_id)?
+            && let Err(reason) = machine.check(StepTransition::Claim)
+        {
+            return Err(
+                PlannerError::invalid_input("id").with_reason(format!("Step {step_id} {reason}"))
+            );
+        }
+
+        let step = Self::mark_claimed(&tx, step_id)?;
+        tx.commit().db_context("Failed to commit transaction")?;
+        Ok(step)
+    }
+
+    /// Moves a todo step to in progress and stamps its start time, returning
+    /// the updated step. Callers are responsible for the transaction.
+    fn mark_claimed(conn: &Connection, step_id: u64) -> Result<Option<Step>> {
+        // Only update if the step is still todo, so concurrent claims can't
+        // both succeed
+        conn.execute(
+            UPDATE_STEP_STATUS_FROM_SQL,
+            params![StepStatus::InProgress.as_str(), step_id as i64, "todo"],
+        )
+        .map_err(|e| PlannerError::database_error("Failed to claim step", e))?;
+        conn.execute(
+            MARK_STEP_STARTED_SQL,
+            params![super::format_timestamp(super::now()), step_id as i64],
+        )
+        .map_err(|e| PlannerError::database_error("Failed to claim step", e))?;
+
+        let step = Self::read_step(conn, step_id)?;
+        if let Some(step) = &step {
+            Self::record_event(conn, EventEntity::Step, step_id, EventKind::Claimed, step)?;
+        }
+        Ok(step)
+    }
+
+    /// Atomically puts an in-progress step back to Todo, e.g. when the agent
+    /// that claimed it gives up. Returns false if the step is not in
+    /// progress.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist
+    pub fn release_step(&mut self, step_id: u64) -> Result<bool> {
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+
+        let machine = Self::load_state_machine(&tx, step_id)?
+            .ok_or(PlannerError::StepNotFound { id: step_id })?;
+        if machine.check(StepTransition::Release).is_err() {
+            return Ok(false);
+        }
+
+        // Only update if the step is still in progress, so a release racing
+        // a completion can't undo it
+        let released = tx
+            .execute(
+                UPDATE_STEP_STATUS_FROM_SQL,
+                params![
+                    StepStatus::Todo.as_str(),
+                    step_id as i64,
+                    StepStatus::InProgress.as_str()
+                ],
+            )
+            .map_err(|e| PlannerError::database_error("Failed to release step", e))?;
+        if released > 0 {
+            Self::record_step_event(&tx, step_id, EventKind::Released)?;
+        }
+
+        tx.commit().db_context("Failed to commit transaction")?;
+
+        Ok(released > 0)
+    }
+
+    /// Puts every step of a plan back to Todo in a single transaction, so
+    /// that the plan can be worked through again, and returns the number of
+    /// steps reset.
+    ///
+    /// The steps lose their start and completion times, blocked reasons and
+    /// verifications, and their results too if `clear_results` is set. The
+    /// write lock is taken up front, so a step claimed concurrently is
+    /// either claimed before the reset, and reset with the others, or
+    /// claimed afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    /// Returns `PlannerError::InvalidInput` if the plan is archived
+    pub fn reset_plan_steps(&mut self, plan_id: u64, clear_results: bool) -> Result<u32> {
+        let tx = self
+            .connection
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .db_context("Failed to begin transaction")?;
+
+        let plan =
+            Self::read_plan_row(&tx, plan_id)?.ok_or(PlannerError::PlanNotFound { id: plan_id })?;
+        if plan.status == PlanStatus::Archived {
+            return Err(PlannerError::invalid_input("id").with_reason(format!(
+                "Plan {plan_id} is archived; unarchive it before resetting its steps"
+            )));
+        }
+
+        let step_ids = tx
+            .prepare(SELECT_PLAN_STEP_IDS_SQL)
+            .map_err(|e| PlannerError::database_error("Failed to prepare query", e))?
+            .query_map(params![plan_id as i64], |row| row.get::<_, i64>(0))
+            .map_err(|e| PlannerError::database_error("Failed to query steps", e))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| PlannerError::database_error("Failed to fetch steps", e))?;
+        for step_id in &step_ids {
+            let machine = Self::load_state_machine(&tx, *step_id as u64)?.ok_or(
+                PlannerError::StepNotFound {
+                    id: *step_id as u64,
+                },
+            )?;
+            // Todo steps have nothing to reopen but are reset all the same
+            if machine.status != StepStatus::Todo {
+                machine.check(StepTransition::Reopen).map_err(|reason| {
+                    PlannerError::invalid_input("id")
+                        .with_reason(format!("Step {step_id} {reason}"))
+                })?;
             }
-            Some(status) if status == "todo" => {
-                // Atomically update to in_progress
-                let now_str = Timestamp::now().to_string();
-                tx.execute(
-                    UPDATE_STEP_STATUS_CLAIMED_SQL,
-                    params![
-                        StepStatus::InProgress.as_str(),
-                        &now_str,
-                        step_id as i64,
-                        "todo"
-                    ],
-                )
-                .map_err(|e| PlannerError::database_error("Failed to claim step", e))?;
+        }
+        tx.execute(
+            RESET_PLAN_STEPS_SQL,
+            params![
+                plan_id as i64,
+                clear_results,
+                super::format_timestamp(super::now())
+            ],
+        )
+        .map_err(|e| PlannerError::database_error("Failed to reset plan steps", e))?;
+        for step_id in &step_ids {
+            Self::record_step_event(&tx, *step_id as u64, EventKind::Updated)?;
+        }
 
-                // Update plan's updated_at
-                tx.execute(
-                    UPDATE_PLAN_TIMESTAMP_BY_STEP_SQL,
-                    params![&now_str, step_id as i64],
-                )
-                .map_err(|e| PlannerError::database_error("Failed to update plan timestamp", e))?;
-
-                // Get the updated step details
-                let step = tx
-                    .query_row(
-                        SELECT_STEP_BY_ID_SQL,
-                        params![step_id as i64],
-                        Self::build_step_from_row,
+        tx.commit().db_context("Failed to commit transaction")?;
+
+        Ok(step_ids.len() as u32)
+    }
+
+    /// Loads what decides the transitions a step may undergo, or `None` if
+    /// the step doesn't exist.
+    fn load_state_machine(conn: &Connection, step_id: u64) -> Result<Option<StepStateMachine>> {
+        let machine = conn
+            .query_row(SELECT_STEP_STATE_SQL, params![step_id as i64], |row| {
+                let status_str: String = row.get(0)?;
+                let status = status_str.parse::<StepStatus>().map_err(|_| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        0,
+                        Type::Text,
+                        format!("Invalid status: {status_str}").into(),
                     )
-                    .optional()
-                    .map_err(|e| PlannerError::database_error("Failed to query claimed step", e))?;
+                })?;
+                Ok(StepStateMachine {
+                    status,
+                    verification_required: row.get(1)?,
+                    verified_by: row.get(2)?,
+                    plan_archived: row.get(3)?,
+                    blocked_by: Vec::new(),
+                })
+            })
+            .optional()
+            .map_err(|e| PlannerError::database_error("Failed to query step state", e))?;
 
-                tx.commit().db_context("Failed to commit transaction")?;
+        machine
+            .map(|mut machine| {
+                machine.blocked_by = Self::load_unfinished_dependencies(conn, step_id)?;
+                Ok(machine)
+            })
+            .transpose()
+    }
 
-                Ok(step)
-            }
-            _ => {
-                // Step is not in Todo status, cannot claim
-                Ok(None)
-            }
+    /// Checks that a step may undergo `transition`, blaming `field` of the
+    /// request if it may not.
+    fn check_transition(
+        conn: &Connection,
+        step_id: u64,
+        transition: StepTransition,
+        field: &str,
+    ) -> Result<()> {
+        Self::load_state_machine(conn, step_id)?
+            .ok_or(PlannerError::StepNotFound { id: step_id })?
+            .check(transition)
+            .map_err(|reason| {
+                PlannerError::invalid_input(field).with_reason(format!("Step {step_id} {reason}"))
+            })
+    }
+
+    /// Lists the transitions a step may and may not undergo next, or `None`
+    /// if the step doesn't exist.
+    pub fn allowed_transitions(&self, step_id: u64) -> Result<Option<AllowedTransitions>> {
+        Ok(Self::load_state_machine(&self.connection, step_id)?
+            .map(|machine| AllowedTransitions::new(step_id, &machine)))
+    }
+
+    /// Sets or clears the phase a step is grouped under.
+    ///
+    /// Returns `false` if the step doesn't exist.
+    pub fn set_step_phase(&mut self, step_id: u64, phase: Option<&str>) -> Result<bool> {
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+
+        let updated = tx
+            .execute(UPDATE_STEP_PHASE_SQL, params![phase, step_id as i64])
+            .map_err(|e| PlannerError::database_error("Failed to set step phase", e))?;
+        Self::record_step_event(&tx, step_id, EventKind::Updated)?;
+
+        tx.commit().db_context("Failed to commit transaction")?;
+        Ok(updated > 0)
+    }
+
+    /// Sets or clears the estimated effort of a step, in minutes.
+    ///
+    /// Returns `false` if the step doesn't exist.
+    pub fn set_step_estimate(&mut self, step_id: u64, minutes: Option<u32>) -> Result<bool> {
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+
+        let updated = tx
+            .execute(UPDATE_STEP_ESTIMATE_SQL, params![minutes, step_id as i64])
+            .map_err(|e| PlannerError::database_error("Failed to set step estimate", e))?;
+        Self::record_step_event(&tx, step_id, EventKind::Updated)?;
+
+        tx.commit().db_context("Failed to commit transaction")?;
+        Ok(updated > 0)
+    }
+
+    /// Sets when a step was created and last updated, for imports that carry
+    /// historical timestamps. Its plan's `updated_at` is left as it is.
+    ///
+    /// Returns `false` if the step doesn't exist.
+    pub fn set_step_timestamps(
+        &mut self,
+        step_id: u64,
+        created_at: Timestamp,
+        updated_at: Timestamp,
+    ) -> Result<bool> {
+        Self::write_step_timestamps(&self.connection, step_id, created_at, updated_at)
+    }
+
+    /// Sets when a step was created and last updated, as
+    /// [`Self::set_step_timestamps`] does. The caller owns the transaction.
+    pub(super) fn write_step_timestamps(
+        conn: &Connection,
+        step_id: u64,
+        created_at: Timestamp,
+        updated_at: Timestamp,
+    ) -> Result<bool> {
+        let updated = conn
+            .execute(
+                UPDATE_STEP_TIMESTAMPS_SQL,
+                params![
+                    super::format_timestamp(created_at),
+                    super::format_timestamp(updated_at),
+                    step_id as i64
+                ],
+            )
+            .map_err(|e| PlannerError::database_error("Failed to set step timestamps", e))?;
+        Ok(updated > 0)
+    }
+
+    /// Returns a stored order for a step inserted at `position`, between the
+    /// orders of the steps around it, or `None` if they have no gap left.
+    ///
+    /// A step being moved is given as `moving`, and left out when counting
+    /// positions.
+    fn order_between_neighbors(
+        conn: &Connection,
+        plan_id: u64,
+        position: u32,
+        moving: Option<u64>,
+    ) -> Result<Option<i64>> {
+        // The step before the position, if any, and the one at it
+        let (limit, offset) = match position {
+            0 => (1, 0),
+            _ => (2, position - 1),
+        };
+        let mut stmt = conn
+            .prepare(SELECT_NEIGHBOR_ORDERS_SQL)
+            .map_err(|e| PlannerError::database_error("Failed to prepare query", e))?;
+        let orders = stmt
+            .query_map(
+                params![plan_id as i64, limit, offset, moving.map(|id| id as i64)],
+                |row| row.get(0),
+            )
+            .map_err(|e| PlannerError::database_error("Failed to query step orders", e))?
+            .collect::<std::result::Result<Vec<i64>, _>>()
+            .map_err(|e| PlannerError::database_error("Failed to fetch step orders", e))?;
+
+        let (before, after) = match (position, orders.as_slice()) {
+            (0, [after]) => (0, Some(*after)),
+            (0, _) => (0, None),
+            (_, [before]) => (*before, None),
+            (_, [before, after, ..]) => (*before, Some(*after)),
+            _ => (0, None),
+        };
+
+        Ok(match after {
+            None => Some(before + STEP_ORDER_SPACING),
+            Some(after) if after - before >= 2 => Some(before + (after - before) / 2),
+            Some(_) => None,
+        })
+    }
+
+    /// Returns a stored order for a step placed at `position`, respacing the
+    /// plan's steps first if its neighbors have no gap left.
+    fn order_for_position(
+        conn: &Connection,
+        plan_id: u64,
+        position: u32,
+        moving: Option<u64>,
+    ) -> Result<i64> {
+        if let Some(order) = Self::order_between_neighbors(conn, plan_id, position, moving)? {
+            return Ok(order);
+        }
+        Self::respace_step_orders(conn, plan_id)?;
+        Ok(
+            Self::order_between_neighbors(conn, plan_id, position, moving)?
+                .expect("respaced steps leave a gap between neighbors"),
+        )
+    }
+
+    /// Renumbers a plan's steps to evenly spaced orders, keeping their
+    /// sequence. The caller owns the transaction.
+    fn respace_step_orders(conn: &Connection, plan_id: u64) -> Result<usize> {
+        conn.execute(
+            RESPACE_STEP_ORDERS_SQL,
+            params![plan_id as i64, STEP_ORDER_SPACING],
+        )
+        .map_err(|e| PlannerError::database_error("Failed to respace step orders", e))
+    }
+
+    /// Respaces a plan's steps if any of them share a stored order, as a
+    /// write interrupted by a crash may leave them. Returns whether the plan
+    /// was respaced. The caller owns the transaction.
+    pub(super) fn repair_step_orders(conn: &Connection, plan_id: u64) -> Result<bool> {
+        let shared: bool = conn
+            .query_row(HAS_SHARED_STEP_ORDERS_SQL, params![plan_id as i64], |row| {
+                row.get(0)
+            })
+            .db_context("Failed to check step orders")?;
+        if shared {
+            Self::respace_step_orders(conn, plan_id)?;
+        }
+        Ok(shared)
+    }
+
+    /// Renumbers a plan's steps to evenly spaced orders following their
+    /// current sequence, with ties between steps sharing an order broken by
+    /// ID. Returns the number of steps renumbered.
+    ///
+    /// Insert, remove and swap already repair steps sharing an order; this
+    /// is for plans damaged before they did.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    pub fn renumber_steps(&mut self, plan_id: u64) -> Result<usize> {
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+
+        let plan_exists: bool = tx
+            .query_row(CHECK_PLAN_EXISTS_SQL, params![plan_id as i64], |row| {
+                row.get(0)
+            })
+            .db_context("Failed to check plan existence")?;
+        if !plan_exists {
+            return Err(PlannerError::PlanNotFound { id: plan_id });
+        }
+
+        let renumbered = Self::respace_step_orders(&tx, plan_id)?;
+        if renumbered > 0 {
+            Self::record_plan_event(&tx, plan_id, EventKind::Updated)?;
         }
+        tx.commit().db_context("Failed to commit transaction")?;
+        Ok(renumbered)
     }
 
     /// Swaps the order of two steps within the same plan.
@@ -490,29 +1583,20 @@ impl super::Database {
             .transaction()
             .db_context("Failed to begin transaction")?;
 
-        let (plan_id1, order1): (i64, i64) = tx
-            .query_row(SELECT_STEP_ORDER_SQL, params![step_id1 as i64], |row| {
+        let step_order = |step_id: u64, which: &str| -> Result<(i64, i64)> {
+            tx.query_row(SELECT_STEP_ORDER_SQL, params![step_id as i64], |row| {
                 Ok((row.get(0)?, row.get(1)?))
             })
             .map_err(|e| {
                 if matches!(e, rusqlite::Error::QueryReturnedNoRows) {
-                    PlannerError::StepNotFound { id: step_id1 }
+                    PlannerError::StepNotFound { id: step_id }
                 } else {
-                    PlannerError::database_error("Failed to query first step", e)
+                    PlannerError::database_error(&format!("Failed to query {which} step"), e)
                 }
-            })?;
-
-        let (plan_id2, order2): (i64, i64) = tx
-            .query_row(SELECT_STEP_ORDER_SQL, params![step_id2 as i64], |row| {
-                Ok((row.get(0)?, row.get(1)?))
             })
-            .map_err(|e| {
-                if matches!(e, rusqlite::Error::QueryReturnedNoRows) {
-                    PlannerError::StepNotFound { id: step_id2 }
-                } else {
-                    PlannerError::database_error("Failed to query second step", e)
-                }
-            })?;
+        };
+        let (plan_id1, mut order1) = step_order(step_id1, "first")?;
+        let (plan_id2, mut order2) = step_order(step_id2, "second")?;
 
         // Verify both steps are in the same plan
         if plan_id1 != plan_id2 {
@@ -522,74 +1606,400 @@ impl super::Database {
             });
         }
 
-        // Swap the orders
-        let now_str = Timestamp::now().to_string();
+        // Exchanging orders only swaps steps whose orders differ
+        if Self::repair_step_orders(&tx, plan_id1 as u64)? {
+            order1 = step_order(step_id1, "first")?.1;
+            order2 = step_order(step_id2, "second")?.1;
+        }
 
-        // Use a temporary negative value to avoid unique constraint violation
-        tx.execute(
-            UPDATE_STEP_ORDER_TEMP_SQL,
-            params![&now_str, step_id1 as i64],
-        )
-        .map_err(|e| PlannerError::database_error("Failed to update first step order", e))?;
+        // Swap the orders, using a temporary negative value to avoid unique
+        // constraint violation
+        tx.execute(UPDATE_STEP_ORDER_TEMP_SQL, params![step_id1 as i64])
+            .map_err(|e| PlannerError::database_error("Failed to update first step order", e))?;
 
-        tx.execute(
-            UPDATE_STEP_ORDER_SQL,
-            params![order1, &now_str, step_id2 as i64],
-        )
-        .map_err(|e| PlannerError::database_error("Failed to update second step order", e))?;
+        tx.execute(UPDATE_STEP_ORDER_SQL, params![order1, step_id2 as i64])
+            .map_err(|e| PlannerError::database_error("Failed to update second step order", e))?;
 
-        tx.execute(
-            UPDATE_STEP_ORDER_SQL,
-            params![order2, &now_str, step_id1 as i64],
-        )
-        .map_err(|e| PlannerError::database_error("Failed to update first step final order", e))?;
-
-        // Update plan's updated_at
-        tx.execute(UPDATE_PLAN_TIMESTAMP_SQL, params![&now_str, plan_id1])
-            .map_err(|e| PlannerError::database_error("Failed to update plan timestamp", e))?;
+        tx.execute(UPDATE_STEP_ORDER_SQL, params![order2, step_id1 as i64])
+            .map_err(|e| {
+                PlannerError::database_error("Failed to update first step final order", e)
+            })?;
+        Self::record_step_event(&tx, step_id1, EventKind::Swapped)?;
+        Self::record_step_event(&tx, step_id2, EventKind::Swapped)?;
 
         tx.commit().db_context("Failed to commit transaction")?;
 
         Ok(())
     }
 
-    /// Removes a step from a plan.
-    pub fn remove_step(&mut self, step_id: u64) -> Result<()> {
+    /// Moves a step to `position` in its plan's step order, 0 being the
+    /// first position. The steps between its old and new positions shift by
+    /// one towards the old position.
+    ///
+    /// Like [`Self::insert_step`], the step takes the midpoint of its new
+    /// neighbors' stored orders, so no other step is updated unless the plan
+    /// has to be respaced. Moving a step to its current position changes
+    /// nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist
+    /// Returns `PlannerError::InvalidInput` if the position is past the last
+    /// step
+    pub fn move_step(&mut self, step_id: u64, position: u32) -> Result<()> {
         let tx = self
             .connection
             .transaction()
             .db_context("Failed to begin transaction")?;
 
-        let (plan_id, step_order): (i64, i64) = tx
-            .query_row(SELECT_STEP_ORDER_SQL, params![step_id as i64], |row| {
+        let (plan_id, current): (i64, i64) = tx
+            .query_row(SELECT_STEP_POSITION_SQL, params![step_id as i64], |row| {
                 Ok((row.get(0)?, row.get(1)?))
             })
-            .map_err(|e| {
-                if matches!(e, rusqlite::Error::QueryReturnedNoRows) {
-                    PlannerError::StepNotFound { id: step_id }
-                } else {
-                    PlannerError::database_error("Failed to query step", e)
-                }
-            })?;
+            .optional()
+            .map_err(|e| PlannerError::database_error("Failed to query step position", e))?
+            .ok_or(PlannerError::StepNotFound { id: step_id })?;
+        Self::reposition_step(
+            &tx,
+            step_id,
+            plan_id as u64,
+            current,
+            position,
+            "new_position",
+        )?;
+        if i64::from(position) != current {
+            Self::record_step_event(&tx, step_id, EventKind::Moved)?;
+        }
 
-        // Delete the step
-        tx.execute(DELETE_STEP_SQL, params![step_id as i64])
-            .map_err(|e| PlannerError::database_error("Failed to delete step", e))?;
+        tx.commit().db_context("Failed to commit transaction")?;
 
-        // Update order of subsequent steps
-        tx.execute(
-            UPDATE_STEP_ORDERS_DECREMENT_SQL,
-            params![plan_id, step_order],
-        )
-        .map_err(|e| PlannerError::database_error("Failed to update step orders", e))?;
+        Ok(())
+    }
+
+    /// Moves a step from `current` to `position` within plan `plan_id`,
+    /// reporting an out-of-range position against `field`. The caller owns
+    /// the transaction.
+    fn reposition_step(
+        conn: &Connection,
+        step_id: u64,
+        plan_id: u64,
+        current: i64,
+        position: u32,
+        field: &str,
+    ) -> Result<()> {
+        let step_count = Self::count_plan_steps(conn, plan_id)?;
+        if i64::from(position) >= step_count {
+            return Err(PlannerError::invalid_input(field).with_reason(format!(
+                "Position {position} is out of range. Plan {plan_id} has {step_count} steps, so valid positions are 0 to {}",
+                step_count - 1
+            )));
+        }
+        if i64::from(position) == current {
+            return Ok(());
+        }
+
+        let order = Self::order_for_position(conn, plan_id, position, Some(step_id))?;
+        conn.execute(UPDATE_STEP_ORDER_SQL, params![order, step_id as i64])
+            .map_err(|e| PlannerError::database_error("Failed to update step order", e))?;
+        Ok(())
+    }
 
-        // Update plan's updated_at
-        let now_str = Timestamp::now().to_string();
-        tx.execute(UPDATE_PLAN_TIMESTAMP_SQL, params![&now_str, plan_id])
-            .map_err(|e| PlannerError::database_error("Failed to update plan timestamp", e))?;
+    /// Counts a plan's steps, leaving out those in the trash.
+    fn count_plan_steps(conn: &Connection, plan_id: u64) -> Result<i64> {
+        conn.query_row(COUNT_PLAN_STEPS_SQL, params![plan_id as i64], |row| {
+            row.get(0)
+        })
+        .map_err(|e| PlannerError::database_error("Failed to count steps", e))
+    }
+
+    /// Moves a step to another plan at `position` in its step order, or to
+    /// the end, and returns the moved step.
+    ///
+    /// The step keeps its status, result, references, notes and
+    /// dependencies; the steps after it in its old plan move up to close the
+    /// gap. Both plans are marked as updated. Moving a step to its own plan
+    /// repositions it like [`Self::move_step`], to the end if no position is
+    /// given.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist
+    /// Returns `PlannerError::PlanNotFound` if the target plan doesn't exist
+    /// Returns `PlannerError::InvalidInput` if the target plan is archived or
+    /// the position is past its last step
+    pub fn move_step_to_plan(
+        &mut self,
+        step_id: u64,
+        target_plan_id: u64,
+        position: Option<u32>,
+    ) -> Result<Step> {
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+
+        let (source_plan_id, current): (i64, i64) = tx
+            .query_row(SELECT_STEP_POSITION_SQL, params![step_id as i64], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .optional()
+            .map_err(|e| PlannerError::database_error("Failed to query step position", e))?
+            .ok_or(PlannerError::StepNotFound { id: step_id })?;
+
+        let target = Self::read_plan_row(&tx, target_plan_id)?
+            .ok_or(PlannerError::PlanNotFound { id: target_plan_id })?;
+        if target.status == PlanStatus::Archived {
+            return Err(PlannerError::invalid_input("plan_id").with_reason(format!(
+                "Plan {target_plan_id} is archived; unarchive it before moving steps to it"
+            )));
+        }
+
+        let step_count = Self::count_plan_steps(&tx, target_plan_id)?;
+        if source_plan_id as u64 == target_plan_id {
+            let position = position.unwrap_or((step_count - 1) as u32);
+            Self::reposition_step(&tx, step_id, target_plan_id, current, position, "position")?;
+        } else {
+            let position = position.unwrap_or(step_count as u32);
+            if i64::from(position) > step_count {
+                return Err(PlannerError::invalid_input("position").with_reason(format!(
+                    "Position {position} is out of range. Plan {target_plan_id} has {step_count} steps, so valid positions are 0 to {step_count}"
+                )));
+            }
+            let order = Self::order_for_position(&tx, target_plan_id, position, None)?;
+            tx.execute(
+                MOVE_STEP_TO_PLAN_SQL,
+                params![target_plan_id as i64, order, step_id as i64],
+            )
+            .map_err(|e| PlannerError::database_error("Failed to move step", e))?;
+            Self::repair_step_orders(&tx, target_plan_id)?;
+            Self::record_step_event(&tx, step_id, EventKind::Updated)?;
+        }
 
+        let step =
+            Self::read_step(&tx, step_id)?.ok_or(PlannerError::StepNotFound { id: step_id })?;
         tx.commit().db_context("Failed to commit transaction")?;
 
-        Ok(())
+        Ok(step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::Database, models::CreatePlanRequest};
+
+    /// Creates an in-memory database holding a plan with `count` steps.
+    fn plan_with_steps(count: usize) -> (Database, u64) {
+        let mut db = Database::new(":memory:").expect("Failed to create database");
+        let steps: Vec<_> = (0..count)
+            .map(|i| CreateStepRequest {
+                title: format!("Step {i}"),
+                description: None,
+                acceptance_criteria: None,
+                references: Vec::new(),
+                status: StepStatus::Todo,
+                result: None,
+            })
+            .collect();
+        let request = CreatePlanRequest {
+            title: "Ordering".into(),
+            ..Default::default()
+        };
+        let plan = db
+            .create_plan_with_steps(&request, &steps)
+            .expect("Failed to create plan");
+        (db, plan.id)
+    }
+
+    fn stored_orders(db: &Database, plan_id: u64) -> Vec<i64> {
+        let mut stmt = db
+            .connection
+            .prepare("SELECT step_order FROM steps WHERE plan_id = ?1 ORDER BY step_order, id")
+            .unwrap();
+        stmt.query_map([plan_id as i64], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_front_insert_into_large_plan_updates_constant_rows() {
+        let (mut db, plan_id) = plan_with_steps(1000);
+        let before = stored_orders(&db, plan_id);
+
+        let changes = db.connection.total_changes();
+        let step = db
+            .insert_step(plan_id, 0, "First", None, None, Vec::new())
+            .expect("Failed to insert step");
+        // The new step, the plan's timestamp and the event; no existing step
+        // is renumbered
+        assert_eq!(db.connection.total_changes() - changes, 3);
+        assert_eq!(step.order, 0);
+        assert_eq!(&stored_orders(&db, plan_id)[1..], before.as_slice());
+
+        let steps = db.get_steps(plan_id).unwrap();
+        assert_eq!(steps.len(), 1001);
+        assert_eq!(steps[0].id, step.id);
+        assert!(steps.iter().enumerate().all(|(i, s)| s.order == i as u32));
+    }
+
+    #[test]
+    fn test_respacing_when_neighbors_have_no_gap() {
+        let (mut db, plan_id) = plan_with_steps(3);
+        let mut expected: Vec<u64> = db
+            .get_steps(plan_id)
+            .unwrap()
+            .iter()
+            .map(|s| s.id)
+            .collect();
+
+        // Each insert between the first two steps halves the gap between them,
+        // until they have to be respaced
+        let mut respaced = 0;
+        for i in 0..15 {
+            let changes = db.connection.total_changes();
+            let step = db
+                .insert_step(plan_id, 1, &format!("Inserted {i}"), None, None, Vec::new())
+                .unwrap();
+            if db.connection.total_changes() - changes > 3 {
+                respaced += 1;
+            }
+            assert_eq!(step.order, 1);
+            expected.insert(1, step.id);
+
+            let ids: Vec<u64> = db
+                .get_steps(plan_id)
+                .unwrap()
+                .iter()
+                .map(|s| s.id)
+                .collect();
+            assert_eq!(ids, expected);
+            let orders = stored_orders(&db, plan_id);
+            assert!(orders.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+        assert_eq!(respaced, 1);
+    }
+
+    #[test]
+    fn test_legacy_dense_orders_are_respaced_on_insert() {
+        let (mut db, plan_id) = plan_with_steps(3);
+        db.connection
+            .execute(
+                "UPDATE steps SET step_order = step_order / ?1 - 1",
+                [STEP_ORDER_SPACING],
+            )
+            .unwrap();
+        assert_eq!(stored_orders(&db, plan_id), [0, 1, 2]);
+
+        let step = db
+            .insert_step(plan_id, 0, "First", None, None, Vec::new())
+            .unwrap();
+        let steps = db.get_steps(plan_id).unwrap();
+        assert_eq!(steps[0].id, step.id);
+        assert_eq!(
+            steps.iter().map(|s| s.order).collect::<Vec<_>>(),
+            [0, 1, 2, 3]
+        );
+        assert_eq!(
+            stored_orders(&db, plan_id),
+            [STEP_ORDER_SPACING / 2, 1024, 2048, 3072]
+        );
+    }
+
+    /// Gives the second and third steps the first one's order, and leaves a
+    /// gap after them, as an interrupted write might.
+    fn damage_orders(db: &Database, plan_id: u64) -> Vec<u64> {
+        let ids: Vec<u64> = db
+            .get_steps(plan_id)
+            .unwrap()
+            .iter()
+            .map(|s| s.id)
+            .collect();
+        for &id in &ids[1..3] {
+            db.connection
+                .execute(
+                    "UPDATE steps SET step_order = ?1 WHERE id = ?2",
+                    params![STEP_ORDER_SPACING, id as i64],
+                )
+                .unwrap();
+        }
+        assert_eq!(stored_orders(db, plan_id), [1024, 1024, 1024, 4096, 5120]);
+        ids
+    }
+
+    #[test]
+    fn test_renumber_steps_repairs_shared_orders() {
+        let (mut db, plan_id) = plan_with_steps(5);
+        let ids = damage_orders(&db, plan_id);
+
+        assert_eq!(db.renumber_steps(plan_id).unwrap(), 5);
+        assert_eq!(stored_orders(&db, plan_id), [1024, 2048, 3072, 4096, 5120]);
+        let steps = db.get_steps(plan_id).unwrap();
+        assert_eq!(steps.iter().map(|s| s.id).collect::<Vec<_>>(), ids);
+        assert!(steps.iter().enumerate().all(|(i, s)| s.order == i as u32));
+
+        assert!(matches!(
+            db.renumber_steps(9999),
+            Err(PlannerError::PlanNotFound { id: 9999 })
+        ));
+    }
+
+    #[test]
+    fn test_writes_repair_shared_orders() {
+        // Swapping steps that share an order still swaps them
+        let (mut db, plan_id) = plan_with_steps(5);
+        let ids = damage_orders(&db, plan_id);
+        db.swap_steps(ids[1], ids[2]).unwrap();
+        let swapped: Vec<u64> = db
+            .get_steps(plan_id)
+            .unwrap()
+            .iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(swapped, [ids[0], ids[2], ids[1], ids[3], ids[4]]);
+        let orders = stored_orders(&db, plan_id);
+        assert!(orders.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let (mut db, plan_id) = plan_with_steps(5);
+        let ids = damage_orders(&db, plan_id);
+        db.remove_step(ids[4]).unwrap();
+        let orders = stored_orders(&db, plan_id);
+        assert!(orders.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let (mut db, plan_id) = plan_with_steps(5);
+        damage_orders(&db, plan_id);
+        db.insert_step(plan_id, 5, "Last", None, None, Vec::new())
+            .unwrap();
+        let orders = stored_orders(&db, plan_id);
+        assert!(orders.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_outline_query_skips_heavy_columns() {
+        let columns = SELECT_STEP_OUTLINE_BY_PLAN_SQL
+            .strip_prefix("SELECT ")
+            .and_then(|sql| sql.split(" FROM ").next())
+            .expect("Unexpected outline query shape");
+        let columns: Vec<_> = columns.split(", ").collect();
+
+        assert_eq!(
+            columns,
+            [
+                "id",
+                "ROW_NUMBER() OVER (ORDER BY step_order",
+                "id) - 1",
+                "title",
+                "status"
+            ]
+        );
+        for heavy in [
+            "description",
+            "acceptance_criteria",
+            "expected_output",
+            "result",
+        ] {
+            assert!(!SELECT_STEP_OUTLINE_BY_PLAN_SQL.contains(heavy));
+            assert!(!SELECT_STEP_OUTLINE_BY_STATUS_SQL.contains(heavy));
+        }
     }
 }
@@ -0,0 +1,182 @@
+//! Step templates, appended to plans by name.
+//!
+//! A template is a named list of steps. Applying it copies its steps to the
+//! end of a plan as new todo steps; later changes to the template don't
+//! affect plans it was applied to.
+
+use jiff::Timestamp;
+use rusqlite::{Connection, OptionalExtension, params, types::Type};
+
+use crate::{
+    error::{DatabaseResultExt, PlannerError, Result},
+    models::{EventKind, PlanStatus, Step, Template, TemplateStep},
+};
+
+const INSERT_TEMPLATE_SQL: &str = "INSERT INTO templates (name, created_at) VALUES (?1, ?2)";
+const INSERT_TEMPLATE_STEP_SQL: &str = "INSERT INTO template_steps (template_id, position, title, description, acceptance_criteria) VALUES (?1, ?2, ?3, ?4, ?5)";
+const SELECT_TEMPLATES_SQL: &str =
+    "SELECT id, name, created_at FROM templates ORDER BY name COLLATE NOCASE";
+const SELECT_TEMPLATE_BY_NAME_SQL: &str =
+    "SELECT id, name, created_at FROM templates WHERE name = ?1 COLLATE NOCASE";
+const SELECT_TEMPLATE_STEPS_SQL: &str = "SELECT title, description, acceptance_criteria FROM template_steps WHERE template_id = ?1 ORDER BY position";
+
+impl super::Database {
+    /// Creates a template named `name` with the given steps.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if a template with the name
+    /// already exists, ignoring case
+    pub fn create_template(&mut self, name: &str, steps: &[TemplateStep]) -> Result<Template> {
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+
+        if Self::read_template(&tx, name)?.is_some() {
+            return Err(PlannerError::invalid_input("name")
+                .with_reason(format!("A template named '{name}' already exists")));
+        }
+
+        let now = super::now();
+        tx.execute(
+            INSERT_TEMPLATE_SQL,
+            params![name, super::format_timestamp(now)],
+        )
+        .db_context("Failed to insert template")?;
+        let id = tx.last_insert_rowid() as u64;
+
+        for (position, step) in steps.iter().enumerate() {
+            tx.execute(
+                INSERT_TEMPLATE_STEP_SQL,
+                params![
+                    id as i64,
+                    position as i64,
+                    &step.title,
+                    step.description.as_deref(),
+                    step.acceptance_criteria.as_deref()
+                ],
+            )
+            .db_context("Failed to insert template step")?;
+        }
+
+        tx.commit().db_context("Failed to commit transaction")?;
+
+        Ok(Template {
+            id,
+            name: name.to_string(),
+            steps: steps.to_vec(),
+            created_at: now,
+        })
+    }
+
+    /// Lists every template with its steps, by name.
+    pub fn list_templates(&self) -> Result<Vec<Template>> {
+        let mut stmt = self
+            .connection
+            .prepare(SELECT_TEMPLATES_SQL)
+            .db_context("Failed to prepare template query")?;
+        let templates = stmt
+            .query_map([], Self::build_template_from_row)
+            .and_then(Iterator::collect::<rusqlite::Result<Vec<_>>>)
+            .db_context("Failed to query templates")?;
+
+        templates
+            .into_iter()
+            .map(|template| Self::with_template_steps(&self.connection, template))
+            .collect()
+    }
+
+    /// Appends the steps of the template named `name` to a plan, in one
+    /// transaction, returning the new steps.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    /// Returns `PlannerError::TemplateNotFound` if no template has the name
+    /// Returns `PlannerError::InvalidInput` if the plan is archived
+    pub fn apply_template(&mut self, plan_id: u64, name: &str) -> Result<Vec<Step>> {
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+
+        let plan =
+            Self::read_plan_row(&tx, plan_id)?.ok_or(PlannerError::PlanNotFound { id: plan_id })?;
+        if plan.status == PlanStatus::Archived {
+            return Err(PlannerError::invalid_input("plan_id").with_reason(format!(
+                "Plan {plan_id} is archived; unarchive it before applying a template"
+            )));
+        }
+        let template =
+            Self::read_template(&tx, name)?.ok_or_else(|| PlannerError::TemplateNotFound {
+                name: name.to_string(),
+            })?;
+
+        let now = super::format_timestamp(super::now());
+        let mut ids = Vec::with_capacity(template.steps.len());
+        for step in &template.steps {
+            let (id, _) = Self::append_step_row(
+                &tx,
+                plan_id,
+                &step.title,
+                step.description.as_deref(),
+                step.acceptance_criteria.as_deref(),
+                &now,
+            )?;
+            Self::record_step_event(&tx, id, EventKind::Added)?;
+            ids.push(id);
+        }
+        let steps = ids
+            .into_iter()
+            .map(|id| Self::read_step(&tx, id)?.ok_or(PlannerError::StepNotFound { id }))
+            .collect::<Result<Vec<_>>>()?;
+
+        tx.commit().db_context("Failed to commit transaction")?;
+        Ok(steps)
+    }
+
+    /// Reads the template named `name`, ignoring case, with its steps.
+    fn read_template(conn: &Connection, name: &str) -> Result<Option<Template>> {
+        conn.query_row(
+            SELECT_TEMPLATE_BY_NAME_SQL,
+            params![name],
+            Self::build_template_from_row,
+        )
+        .optional()
+        .db_context("Failed to query template")?
+        .map(|template| Self::with_template_steps(conn, template))
+        .transpose()
+    }
+
+    /// Builds a template without its steps from a row of
+    /// [`SELECT_TEMPLATES_SQL`].
+    fn build_template_from_row(row: &rusqlite::Row) -> rusqlite::Result<Template> {
+        Ok(Template {
+            id: row.get::<_, i64>(0)? as u64,
+            name: row.get(1)?,
+            steps: Vec::new(),
+            created_at: row.get::<_, String>(2)?.parse::<Timestamp>().map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(2, Type::Text, Box::new(e))
+            })?,
+        })
+    }
+
+    /// Fills in the steps of a template read without them.
+    fn with_template_steps(conn: &Connection, mut template: Template) -> Result<Template> {
+        let mut stmt = conn
+            .prepare(SELECT_TEMPLATE_STEPS_SQL)
+            .db_context("Failed to prepare template step query")?;
+        template.steps = stmt
+            .query_map(params![template.id as i64], |row| {
+                Ok(TemplateStep {
+                    title: row.get(0)?,
+                    description: row.get(1)?,
+                    acceptance_criteria: row.get(2)?,
+                })
+            })
+            .and_then(Iterator::collect)
+            .db_context("Failed to query template steps")?;
+        Ok(template)
+    }
+}
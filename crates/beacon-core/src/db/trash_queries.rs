@@ -0,0 +1,174 @@
+//! Steps moved to the trash.
+//!
+//! Removing a step only sets its `deleted_at`, which hides it from its plan,
+//! from step counts and from lookups by ID. Until it is purged, a trashed
+//! step can be restored to the end of its plan.
+
+use jiff::Timestamp;
+use rusqlite::{OptionalExtension, Row, params, types::Type};
+
+use super::step_queries::STEP_ORDER_SPACING;
+use crate::{
+    error::{DatabaseResultExt, PlannerError, Result},
+    models::{EventEntity, EventKind, Step, StepStatus, TrashedStep},
+};
+
+const TRASH_STEP_SQL: &str =
+    "UPDATE steps SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL RETURNING plan_id";
+const SELECT_STEP_TRASHED_SQL: &str =
+    "SELECT plan_id, deleted_at IS NOT NULL FROM steps WHERE id = ?1";
+const SELECT_NEXT_STEP_ORDER_SQL: &str =
+    "SELECT COALESCE(MAX(step_order), 0) + ?2 FROM steps WHERE plan_id = ?1 AND deleted_at IS NULL";
+const RESTORE_STEP_SQL: &str = "UPDATE steps SET deleted_at = NULL, step_order = ?1 WHERE id = ?2";
+const SELECT_TRASHED_STEPS_SQL: &str = "SELECT id, plan_id, title, status, deleted_at FROM steps WHERE plan_id = ?1 AND deleted_at IS NOT NULL ORDER BY deleted_at DESC, id DESC";
+const SELECT_PURGEABLE_STEPS_SQL: &str = "SELECT id, plan_id, title, status, deleted_at FROM steps WHERE deleted_at IS NOT NULL AND julianday(deleted_at) < julianday(?1) ORDER BY id";
+const PURGE_TRASHED_STEPS_SQL: &str =
+    "DELETE FROM steps WHERE deleted_at IS NOT NULL AND julianday(deleted_at) < julianday(?1)";
+
+impl super::Database {
+    /// Moves a step to the trash, from where [`Self::restore_step`] brings it
+    /// back.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist or is
+    /// already in the trash
+    pub fn remove_step(&mut self, step_id: u64) -> Result<()> {
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+
+        // Recorded first, as the step can't be read once in the trash
+        Self::record_step_event(&tx, step_id, EventKind::Removed)?;
+
+        // The gap the step leaves in the order needs no renumbering
+        let plan_id: i64 = tx
+            .query_row(
+                TRASH_STEP_SQL,
+                params![super::format_timestamp(super::now()), step_id as i64],
+                |row| row.get(0),
+            )
+            .optional()
+            .db_context("Failed to move step to the trash")?
+            .ok_or(PlannerError::StepNotFound { id: step_id })?;
+        Self::repair_step_orders(&tx, plan_id as u64)?;
+
+        tx.commit().db_context("Failed to commit transaction")?;
+        Ok(())
+    }
+
+    /// Takes a step out of the trash, placing it at the end of its plan.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist, e.g.
+    /// because it was purged
+    /// Returns `PlannerError::InvalidInput` if the step is not in the trash
+    pub fn restore_step(&mut self, step_id: u64) -> Result<Step> {
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+
+        let (plan_id, trashed): (i64, bool) = tx
+            .query_row(SELECT_STEP_TRASHED_SQL, params![step_id as i64], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .optional()
+            .db_context("Failed to look up step")?
+            .ok_or(PlannerError::StepNotFound { id: step_id })?;
+        if !trashed {
+            return Err(PlannerError::invalid_input("id")
+                .with_reason(format!("Step {step_id} is not in the trash")));
+        }
+
+        let order: i64 = tx
+            .query_row(
+                SELECT_NEXT_STEP_ORDER_SQL,
+                params![plan_id, STEP_ORDER_SPACING],
+                |row| row.get(0),
+            )
+            .db_context("Failed to get next step order")?;
+        tx.execute(RESTORE_STEP_SQL, params![order, step_id as i64])
+            .db_context("Failed to restore step")?;
+        Self::record_step_event(&tx, step_id, EventKind::Restored)?;
+
+        tx.commit().db_context("Failed to commit transaction")?;
+
+        self.get_step(step_id)?
+            .ok_or(PlannerError::StepNotFound { id: step_id })
+    }
+
+    /// Lists the steps of a plan that are in the trash, most recently
+    /// trashed first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    pub fn get_trashed_steps(&self, plan_id: u64) -> Result<Vec<TrashedStep>> {
+        if !self.plan_exists(plan_id)? {
+            return Err(PlannerError::PlanNotFound { id: plan_id });
+        }
+
+        let mut stmt = self
+            .connection
+            .prepare(SELECT_TRASHED_STEPS_SQL)
+            .db_context("Failed to prepare trash query")?;
+
+        stmt.query_map(params![plan_id as i64], Self::build_trashed_step_from_row)
+            .and_then(Iterator::collect)
+            .db_context("Failed to query trashed steps")
+    }
+
+    /// Permanently deletes the steps of every plan that were moved to the
+    /// trash before `before`, returning how many were deleted. Each is
+    /// recorded as purged, with the step as listed in the trash as payload.
+    pub fn purge_trashed_steps(&mut self, before: Timestamp) -> Result<usize> {
+        let tx = self
+            .connection
+            .transaction()
+            .db_context("Failed to begin transaction")?;
+
+        let purged: Vec<TrashedStep> = tx
+            .prepare(SELECT_PURGEABLE_STEPS_SQL)
+            .db_context("Failed to prepare trash query")?
+            .query_map(
+                params![before.to_string()],
+                Self::build_trashed_step_from_row,
+            )
+            .and_then(Iterator::collect)
+            .db_context("Failed to query trashed steps")?;
+        for step in &purged {
+            Self::record_event(&tx, EventEntity::Step, step.id, EventKind::Purged, step)?;
+        }
+        let deleted = tx
+            .execute(PURGE_TRASHED_STEPS_SQL, params![before.to_string()])
+            .db_context("Failed to purge trashed steps")?;
+
+        tx.commit().db_context("Failed to commit transaction")?;
+        Ok(deleted)
+    }
+
+    /// Builds a [`TrashedStep`] from a row of id, plan_id, title, status and
+    /// deleted_at.
+    fn build_trashed_step_from_row(row: &Row) -> rusqlite::Result<TrashedStep> {
+        let status_str: String = row.get(3)?;
+        let status = status_str.parse::<StepStatus>().map_err(|_| {
+            rusqlite::Error::FromSqlConversionFailure(
+                3,
+                Type::Text,
+                format!("Invalid status: {status_str}").into(),
+            )
+        })?;
+        Ok(TrashedStep {
+            id: row.get::<_, i64>(0)? as u64,
+            plan_id: row.get::<_, i64>(1)? as u64,
+            title: row.get(2)?,
+            status,
+            deleted_at: row.get::<_, String>(4)?.parse::<Timestamp>().map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(4, Type::Text, Box::new(e))
+            })?,
+        })
+    }
+}
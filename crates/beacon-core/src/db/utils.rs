@@ -1,88 +1,112 @@
 //! Database utility functions for path handling.
 
-use std::{env::current_dir, path::Path};
+use std::path::{Path, PathBuf};
 
-use crate::error::{PlannerError, Result};
+use crate::{
+    error::{PlannerError, Result},
+    path,
+};
 
 impl super::Database {
     /// Canonicalize a directory path for search purposes using the same logic
-    /// as plan creation
+    /// as plan creation. Absolute paths have "." and ".." components and
+    /// trailing separators removed, so that they compare equal to the stored
+    /// directories, and existing directories are resolved through symlinks
+    /// unless path canonicalization is disabled.
     pub fn canonicalize_directory_for_search(&self, directory: &str) -> Result<String> {
-        let path = Path::new(directory);
-        if path.is_absolute() {
-            Ok(directory.into())
-        } else {
-            // Convert relative path to absolute
-            let cwd = current_dir().map_err(|_| PlannerError::InvalidInput {
+        let absolute_path = path::to_absolute(Some(Path::new(directory)))?;
+        self.resolve_directory(&absolute_path)
+            .to_str()
+            .map(String::from)
+            .ok_or_else(|| PlannerError::InvalidInput {
                 field: "directory".into(),
-                reason: "Cannot resolve current working directory to make path absolute".into(),
-            })?;
-            let absolute_path = cwd.join(path);
-            // Normalize the path to resolve ".." and "." components without requiring the
-            // path to exist
-            let normalized_path = Self::normalize_path(&absolute_path);
-            normalized_path
-                .to_str()
-                .map(String::from)
-                .ok_or_else(|| PlannerError::InvalidInput {
-                    field: "directory".into(),
-                    reason: "Cannot convert path to string".into(),
-                })
-        }
+                reason: "Cannot convert path to string".into(),
+            })
     }
 
-    /// Normalizes a path by resolving "." and ".." components without requiring
-    /// the path to exist
-    fn normalize_path(path: &Path) -> std::path::PathBuf {
-        path.components()
-            .fold(std::path::PathBuf::new(), |mut acc, component| {
-                match component {
-                    std::path::Component::CurDir => acc, // Skip "." components
-                    std::path::Component::ParentDir => {
-                        // Handle ".." by popping the last component if possible
-                        acc.pop();
-                        acc
-                    }
-                    _ => {
-                        // Keep all other components (Normal, RootDir, Prefix)
-                        acc.push(component);
-                        acc
-                    }
-                }
-            })
+    /// Resolves an absolute, normalized directory to the path stored and
+    /// searched for it: its canonical path if it exists and path
+    /// canonicalization is enabled, otherwise the path itself.
+    fn resolve_directory(&self, path: &Path) -> PathBuf {
+        self.canonicalize_paths
+            .then(|| std::fs::canonicalize(path).ok())
+            .flatten()
+            .unwrap_or_else(|| path.to_path_buf())
     }
 
     /// Ensures a directory path is absolute. Converts relative paths to
-    /// absolute using the current working directory.
-    pub(crate) fn ensure_absolute_directory(directory: Option<&str>) -> Result<Option<String>> {
-        match directory {
-            Some(dir) => {
-                let path = Path::new(dir);
-                if path.is_absolute() {
-                    Ok(Some(dir.into()))
-                } else {
-                    // Convert relative path to absolute
-                    let cwd = current_dir().map_err(|_| PlannerError::InvalidInput {
-                        field: "directory".into(),
-                        reason: "Cannot resolve current working directory to make path absolute"
-                            .into(),
-                    })?;
-                    let absolute_path = cwd.join(path);
-                    // Normalize the path to resolve ".." and "." components without requiring the
-                    // path to exist
-                    let normalized_path = Self::normalize_path(&absolute_path);
-                    Ok(normalized_path.to_str().map(String::from))
-                }
-            }
-            None => {
-                // Use current working directory as default
-                let cwd = current_dir().map_err(|_| PlannerError::InvalidInput {
-                    field: "directory".into(),
-                    reason: "Cannot determine current working directory".into(),
-                })?;
-                let normalized_cwd = Self::normalize_path(&cwd);
-                Ok(normalized_cwd.to_str().map(String::from))
-            }
+    /// absolute using the current working directory, which is the default,
+    /// and resolves existing directories through symlinks unless path
+    /// canonicalization is disabled.
+    pub(crate) fn ensure_absolute_directory(
+        &self,
+        directory: Option<&str>,
+    ) -> Result<Option<String>> {
+        if let Some(dir) = directory
+            && Path::new(dir).is_absolute()
+            && !self.canonicalize_paths
+        {
+            return Ok(Some(dir.into()));
         }
+        let absolute_path = path::to_absolute(directory.map(Path::new))?;
+        Ok(self
+            .resolve_directory(&absolute_path)
+            .to_str()
+            .map(String::from))
+    }
+
+    /// Finds the root of the git work tree containing `path`, if any.
+    ///
+    /// Walks up from `path` looking for a `.git` entry. Both directories and
+    /// files are accepted so that linked worktrees and submodules, whose
+    /// `.git` is a file, are detected as well. Does not shell out to git.
+    pub fn find_git_root(path: &Path) -> Option<PathBuf> {
+        path.ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .map(Path::to_path_buf)
     }
+
+    /// Splits an absolute directory into the enclosing git repository root
+    /// and the path below it.
+    ///
+    /// Returns the directory unchanged with no subpath when it is not inside
+    /// a git work tree or already is the root.
+    pub(crate) fn split_at_git_root(directory: &str) -> (Option<String>, Option<String>) {
+        let path = Path::new(directory);
+        let Some(root) = Self::find_git_root(path) else {
+            return (Some(directory.into()), None);
+        };
+
+        let subpath = path
+            .strip_prefix(&root)
+            .ok()
+            .filter(|sub| !sub.as_os_str().is_empty())
+            .and_then(|sub| sub.to_str().map(String::from));
+
+        match root.to_str() {
+            Some(root) => (Some(root.into()), subpath),
+            None => (Some(directory.into()), None),
+        }
+    }
+}
+
+/// Escapes the `LIKE` wildcards `%` and `_`, and the escape character `\`
+/// itself, for use in a pattern with `ESCAPE '\'`.
+pub(crate) fn escape_like(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut escaped, c| {
+            if matches!(c, '%' | '_' | '\\') {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+            escaped
+        })
+}
+
+/// The `LIKE` pattern, with `ESCAPE '\'`, matching the directories below
+/// `directory` but not `directory` itself or its siblings sharing a prefix:
+/// `/work/api` gives a pattern matching `/work/api/v2` but not
+/// `/work/api-docs`.
+pub(crate) fn subdirectory_pattern(directory: &str) -> String {
+    format!("{}/%", escape_like(directory.trim_end_matches('/')))
 }
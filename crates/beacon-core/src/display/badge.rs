@@ -0,0 +1,22 @@
+//! Prompt badges: the work in a directory, in a few characters.
+
+use std::fmt;
+
+use crate::models::PromptBadge;
+
+/// Renders as `2 plans · 5 pending`, followed by ` · 1 stale` when a plan
+/// has a step in progress for too long, or as nothing when there are no
+/// active plans, so that prompts stay clean outside planned work.
+impl fmt::Display for PromptBadge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let plans = if self.plans == 1 { "plan" } else { "plans" };
+        write!(f, "{} {plans} · {} pending", self.plans, self.pending_steps)?;
+        if self.stale_plans > 0 {
+            write!(f, " · {} stale", self.stale_plans)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+//! Changelog formatting for plans.
+//!
+//! Renders a [`Changelog`] as markdown with one section per day, newest day
+//! first, for reviewing what happened to a plan over a period.
+
+use std::fmt;
+
+use crate::models::{Change, ChangeKind, Changelog};
+
+/// Caveat shown above every changelog, as it is reconstructed from
+/// timestamps rather than read from a history of changes.
+const RECONSTRUCTED_NOTE: &str = "_Reconstructed from timestamps: only the creation and latest change of each step are known, so earlier changes, reorders and removed steps are not shown._";
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self.kind {
+            ChangeKind::PlanCreated => return write!(f, "Plan created"),
+            ChangeKind::PlanUpdated => return write!(f, "Plan updated"),
+            ChangeKind::PlanArchived => return write!(f, "Plan archived"),
+            ChangeKind::StepCreated => "Created",
+            ChangeKind::StepUpdated => "Updated",
+            ChangeKind::StepClaimed => "Claimed",
+            ChangeKind::StepCompleted => "Completed",
+            ChangeKind::StepVerified => "Verified",
+        };
+        write!(f, "{action} step")?;
+        if let Some(id) = self.step_id {
+            write!(f, " {id}")?;
+        }
+        write!(f, ": {}", self.title)?;
+        match (self.kind, &self.detail) {
+            (ChangeKind::StepVerified, Some(detail)) => write!(f, " ({detail})"),
+            (_, Some(detail)) => write!(f, " — {detail}"),
+            (_, None) => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Changelog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "# Changelog: {} (plan {})",
+            self.plan_title, self.plan_id
+        )?;
+        writeln!(f)?;
+
+        let since = self.since.map(|since| {
+            since
+                .to_zoned(self.time_zone.clone())
+                .strftime("%Y-%m-%d %H:%M %Z")
+                .to_string()
+        });
+        if self.changes.is_empty() {
+            return match since {
+                Some(since) => writeln!(f, "No changes since {since}."),
+                None => writeln!(f, "No changes recorded."),
+            };
+        }
+
+        if let Some(since) = since {
+            writeln!(f, "Changes since {since}.")?;
+            writeln!(f)?;
+        }
+        writeln!(f, "{RECONSTRUCTED_NOTE}")?;
+
+        for (day, changes) in self.days() {
+            writeln!(f)?;
+            writeln!(f, "## {}", day.strftime("%Y-%m-%d (%a)"))?;
+            writeln!(f)?;
+            for change in changes {
+                let time = change.at.to_zoned(self.time_zone.clone());
+                writeln!(f, "- {} {change}", time.strftime("%H:%M"))?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+//! Claim age annotations for in-progress steps.
+//!
+//! Someone watching a plan while agents work on it wants to tell a step that
+//! was claimed a few minutes ago from one that has been stuck for days. Steps
+//! carry no dedicated claim time, so the time of their last update stands in
+//! for it.
+
+use std::{fmt, time::Duration};
+
+use jiff::Timestamp;
+
+use super::datetime::Age;
+
+/// Default age after which a claim is flagged as possibly stuck.
+pub const DEFAULT_CLAIM_WARNING_THRESHOLD: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Wrapper type for displaying how long ago an in-progress step was claimed.
+///
+/// Renders as `claimed 4m ago`, or `⚠ claimed 3d ago` once the age exceeds
+/// the warning threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClaimAge {
+    /// Time elapsed since the claim
+    pub age: Duration,
+    /// Age above which the claim is flagged
+    pub warning_threshold: Duration,
+}
+
+impl ClaimAge {
+    /// Computes the claim age at `now`, clamped to zero for claims that
+    /// appear to be in the future (e.g. clock skew between writers).
+    pub fn between(claimed_at: Timestamp, now: Timestamp, warning_threshold: Duration) -> Self {
+        Self {
+            age: Duration::try_from(now.duration_since(claimed_at)).unwrap_or_default(),
+            warning_threshold,
+        }
+    }
+
+    /// Whether the claim is older than the warning threshold.
+    pub fn is_stale(&self) -> bool {
+        self.age > self.warning_threshold
+    }
+}
+
+impl fmt::Display for ClaimAge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_stale() {
+            write!(f, "⚠ ")?;
+        }
+        write!(f, "claimed {} ago", Age(self.age))
+    }
+}
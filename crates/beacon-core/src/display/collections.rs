@@ -3,66 +3,490 @@
 //! This module provides wrapper types that format collections of domain objects
 //! with consistent structure and empty collection handling.
 
-use std::{fmt, ops::Deref};
+use std::{borrow::Borrow, fmt, ops::Deref};
 
-use crate::models::{PlanSummary, Step};
+use jiff::Timestamp;
+
+use super::StepAt;
+use crate::models::{
+    DuplicateGroup, Event, Page, PlanSummary, SettingValue, Step, StepOutline, Template,
+    TrashedStep,
+};
 
 /// Newtype wrapper for displaying collections of plan summaries.
 ///
 /// This provides clean Display formatting for plan collections without title
 /// handling, allowing consumers to handle titles separately. Handles empty
 /// collections gracefully.
-pub struct PlanSummaries(pub Vec<PlanSummary>);
+///
+/// The wrapper either owns its summaries, the default, or borrows them as a
+/// slice, so it can be returned from async handlers as well as built over data
+/// the caller keeps.
+pub struct PlanSummaries<S = Vec<PlanSummary>>(pub S);
 
-impl Deref for PlanSummaries {
-    type Target = Vec<PlanSummary>;
+impl PlanSummaries {
+    /// Create a wrapper owning `summaries`.
+    pub fn owned(summaries: Vec<PlanSummary>) -> Self {
+        Self(summaries)
+    }
+}
+
+impl<'a> PlanSummaries<&'a [PlanSummary]> {
+    /// Create a wrapper borrowing `summaries`.
+    pub fn borrowed(summaries: &'a [PlanSummary]) -> Self {
+        Self(summaries)
+    }
+}
+
+impl<S: Borrow<[PlanSummary]>> Deref for PlanSummaries<S> {
+    type Target = [PlanSummary];
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        self.0.borrow()
     }
 }
 
-impl fmt::Display for PlanSummaries {
+impl<S: Borrow<[PlanSummary]>> fmt::Display for PlanSummaries<S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.0.is_empty() {
+        if self.is_empty() {
             writeln!(f, "No plans found.")
         } else {
-            self.0.iter().try_for_each(|plan| write!(f, "{plan}"))
+            self.iter().try_for_each(|plan| write!(f, "{plan}"))
+        }
+    }
+}
+
+/// Shows the plans of a page like [`PlanSummaries`], followed by how many
+/// of all the matching plans they are when the page doesn't hold them all.
+impl fmt::Display for Page<PlanSummary> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", PlanSummaries::borrowed(&self.items))?;
+        if !self.is_partial() {
+            return Ok(());
+        }
+        write!(f, "\nShowing {} of {} plans", self.items.len(), self.total)?;
+        if self.offset > 0 {
+            write!(f, ", skipping the first {}", self.offset)?;
         }
+        writeln!(f, ".")
+    }
+}
+
+/// How much of each step a listing of full steps shows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StepsMode {
+    /// Every step with all its sections
+    #[default]
+    Detailed,
+    /// One checklist line per step with its ID, title and status
+    Compact,
+    /// Like [`StepsMode::Compact`], also checking in-progress steps as
+    /// `[~]`, which markdown task lists don't support
+    Checklist,
+}
+
+impl StepsMode {
+    /// Whether each step is shown as a single checklist line.
+    pub fn is_one_line(self) -> bool {
+        matches!(self, Self::Compact | Self::Checklist)
     }
 }
 
-/// Newtype wrapper for displaying collections of steps.
+/// Wrapper for displaying collections of steps.
 ///
 /// This wrapper provides Display implementation for collections of steps
 /// without requiring title formatting logic. It handles empty collections
-/// gracefully and formats each step using the existing Step Display trait.
-pub struct Steps(pub Vec<Step>);
+/// gracefully and formats each step using the existing Step Display trait,
+/// or as a one-line checklist item in [`StepsMode::Compact`].
+/// Like [`PlanSummaries`], it either owns or borrows its steps.
+pub struct Steps<S = Vec<Step>> {
+    steps: S,
+    mode: StepsMode,
+}
+
+impl Steps {
+    /// Create a wrapper owning `steps`, shown in detail.
+    pub fn owned(steps: Vec<Step>) -> Self {
+        Self {
+            steps,
+            mode: StepsMode::Detailed,
+        }
+    }
+}
 
-impl Deref for Steps {
-    type Target = Vec<Step>;
+impl<'a> Steps<&'a [Step]> {
+    /// Create a wrapper borrowing `steps`, shown in detail.
+    pub fn borrowed(steps: &'a [Step]) -> Self {
+        Self::detailed(steps)
+    }
+
+    /// Create a wrapper borrowing `steps`, showing every section of each.
+    pub fn detailed(steps: &'a [Step]) -> Self {
+        Self {
+            steps,
+            mode: StepsMode::Detailed,
+        }
+    }
+
+    /// Create a wrapper borrowing `steps`, showing one line for each.
+    pub fn compact(steps: &'a [Step]) -> Self {
+        Self {
+            steps,
+            mode: StepsMode::Compact,
+        }
+    }
+}
+
+impl<S> Steps<S> {
+    /// Show the steps in the given mode.
+    pub fn with_mode(mut self, mode: StepsMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// The mode the steps are shown in.
+    pub fn mode(&self) -> StepsMode {
+        self.mode
+    }
+
+    /// Unwraps the steps.
+    pub fn into_inner(self) -> S {
+        self.steps
+    }
+}
+
+impl<S: Borrow<[Step]>> Deref for Steps<S> {
+    type Target = [Step];
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        self.steps.borrow()
+    }
+}
+
+impl<S: Borrow<[Step]>> fmt::Display for Steps<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No steps found.");
+        }
+        let now = Timestamp::now();
+        self.iter()
+            .try_for_each(|step| write!(f, "{}", StepAt::new(step, now).with_mode(self.mode)))
+    }
+}
+
+/// Newtype wrapper for displaying steps from several plans, each paired
+/// with the summary of its plan.
+///
+/// Steps are grouped under a heading per plan, with the plans in the order
+/// their first step appears and the steps of each plan in the order given,
+/// as one checklist line each. Like [`Steps`], it either owns or borrows its
+/// steps.
+pub struct StepsByPlan<S = Vec<(PlanSummary, Step)>>(pub S);
+
+impl StepsByPlan {
+    /// Create a wrapper owning `steps`.
+    pub fn owned(steps: Vec<(PlanSummary, Step)>) -> Self {
+        Self(steps)
+    }
+}
+
+impl<'a> StepsByPlan<&'a [(PlanSummary, Step)]> {
+    /// Create a wrapper borrowing `steps`.
+    pub fn borrowed(steps: &'a [(PlanSummary, Step)]) -> Self {
+        Self(steps)
     }
 }
 
-impl fmt::Display for Steps {
+impl<S: Borrow<[(PlanSummary, Step)]>> Deref for StepsByPlan<S> {
+    type Target = [(PlanSummary, Step)];
+
+    fn deref(&self) -> &Self::Target {
+        self.0.borrow()
+    }
+}
+
+impl<S: Borrow<[(PlanSummary, Step)]>> fmt::Display for StepsByPlan<S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.0.is_empty() {
+        if self.is_empty() {
+            return writeln!(f, "No steps found.");
+        }
+
+        let mut groups: Vec<(&PlanSummary, Vec<&Step>)> = Vec::new();
+        for (plan, step) in self.iter() {
+            match groups.iter_mut().find(|(group, _)| group.id == plan.id) {
+                Some((_, steps)) => steps.push(step),
+                None => groups.push((plan, vec![step])),
+            }
+        }
+
+        let now = Timestamp::now();
+        for (index, (plan, steps)) in groups.into_iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "## {} (ID: {})", plan.title, plan.id)?;
+            writeln!(f)?;
+            if let Some(directory) = &plan.directory {
+                writeln!(f, "- **Directory**: {directory}")?;
+                writeln!(f)?;
+            }
+            for step in steps {
+                let step = StepAt::new(step, now)
+                    .with_status_labels(&plan.status_labels)
+                    .with_mode(StepsMode::Compact);
+                write!(f, "{step}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Newtype wrapper for displaying a plan outline, one line per step. Like
+/// [`Steps`], it either owns or borrows its outlines.
+pub struct StepOutlines<S = Vec<StepOutline>>(pub S);
+
+impl StepOutlines {
+    /// Create a wrapper owning `outlines`.
+    pub fn owned(outlines: Vec<StepOutline>) -> Self {
+        Self(outlines)
+    }
+}
+
+impl<'a> StepOutlines<&'a [StepOutline]> {
+    /// Create a wrapper borrowing `outlines`.
+    pub fn borrowed(outlines: &'a [StepOutline]) -> Self {
+        Self(outlines)
+    }
+}
+
+impl<S: Borrow<[StepOutline]>> Deref for StepOutlines<S> {
+    type Target = [StepOutline];
+
+    fn deref(&self) -> &Self::Target {
+        self.0.borrow()
+    }
+}
+
+impl<S: Borrow<[StepOutline]>> fmt::Display for StepOutlines<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
             writeln!(f, "No steps found.")
         } else {
-            self.0.iter().try_for_each(|step| write!(f, "{}", step))
+            self.iter().try_for_each(|step| writeln!(f, "- {step}"))
+        }
+    }
+}
+
+/// Newtype wrapper for displaying the steps in a plan's trash, one line per
+/// step. Like [`Steps`], it either owns or borrows them.
+pub struct TrashedSteps<S = Vec<TrashedStep>>(pub S);
+
+impl TrashedSteps {
+    /// Create a wrapper owning `steps`.
+    pub fn owned(steps: Vec<TrashedStep>) -> Self {
+        Self(steps)
+    }
+}
+
+impl<'a> TrashedSteps<&'a [TrashedStep]> {
+    /// Create a wrapper borrowing `steps`.
+    pub fn borrowed(steps: &'a [TrashedStep]) -> Self {
+        Self(steps)
+    }
+}
+
+impl<S: Borrow<[TrashedStep]>> Deref for TrashedSteps<S> {
+    type Target = [TrashedStep];
+
+    fn deref(&self) -> &Self::Target {
+        self.0.borrow()
+    }
+}
+
+impl<S: Borrow<[TrashedStep]>> fmt::Display for TrashedSteps<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            writeln!(f, "The trash is empty.")
+        } else {
+            self.iter().try_for_each(|step| writeln!(f, "- {step}"))
+        }
+    }
+}
+
+/// Newtype wrapper for displaying recorded events, one line per event.
+/// Like [`Steps`], it either owns or borrows them.
+pub struct Events<S = Vec<Event>>(pub S);
+
+impl Events {
+    /// Create a wrapper owning `events`.
+    pub fn owned(events: Vec<Event>) -> Self {
+        Self(events)
+    }
+}
+
+impl<'a> Events<&'a [Event]> {
+    /// Create a wrapper borrowing `events`.
+    pub fn borrowed(events: &'a [Event]) -> Self {
+        Self(events)
+    }
+}
+
+impl<S: Borrow<[Event]>> Deref for Events<S> {
+    type Target = [Event];
+
+    fn deref(&self) -> &Self::Target {
+        self.0.borrow()
+    }
+}
+
+impl<S: Borrow<[Event]>> fmt::Display for Events<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            writeln!(f, "No events recorded.")
+        } else {
+            self.iter().try_for_each(|event| writeln!(f, "- {event}"))
+        }
+    }
+}
+
+/// Newtype wrapper for displaying step templates, each followed by its
+/// numbered step titles.
+pub struct Templates(pub Vec<Template>);
+
+impl Deref for Templates {
+    type Target = [Template];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl fmt::Display for Templates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            writeln!(f, "No templates defined.")
+        } else {
+            self.iter()
+                .try_for_each(|template| write!(f, "- {template}"))
+        }
+    }
+}
+
+/// Newtype wrapper for displaying steps picked from a plan, such as those
+/// with a given status, one line per step followed by its position in the
+/// plan. Like [`Steps`], it either owns or borrows its outlines.
+pub struct ListedSteps<S = Vec<StepOutline>>(pub S);
+
+impl ListedSteps {
+    /// Create a wrapper owning `outlines`.
+    pub fn owned(outlines: Vec<StepOutline>) -> Self {
+        Self(outlines)
+    }
+}
+
+impl<'a> ListedSteps<&'a [StepOutline]> {
+    /// Create a wrapper borrowing `outlines`.
+    pub fn borrowed(outlines: &'a [StepOutline]) -> Self {
+        Self(outlines)
+    }
+}
+
+impl<S: Borrow<[StepOutline]>> Deref for ListedSteps<S> {
+    type Target = [StepOutline];
+
+    fn deref(&self) -> &Self::Target {
+        self.0.borrow()
+    }
+}
+
+impl<S: Borrow<[StepOutline]>> fmt::Display for ListedSteps<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            writeln!(f, "No matching steps found.")
+        } else {
+            self.iter()
+                .try_for_each(|step| writeln!(f, "- {step} · position {}", step.order + 1))
+        }
+    }
+}
+
+/// Newtype wrapper for displaying a duplicate step report, one section per
+/// group. Like [`Steps`], it either owns or borrows its groups.
+pub struct DuplicateGroups<S = Vec<DuplicateGroup>>(pub S);
+
+impl DuplicateGroups {
+    /// Create a wrapper owning `groups`.
+    pub fn owned(groups: Vec<DuplicateGroup>) -> Self {
+        Self(groups)
+    }
+}
+
+impl<'a> DuplicateGroups<&'a [DuplicateGroup]> {
+    /// Create a wrapper borrowing `groups`.
+    pub fn borrowed(groups: &'a [DuplicateGroup]) -> Self {
+        Self(groups)
+    }
+}
+
+impl<S: Borrow<[DuplicateGroup]>> Deref for DuplicateGroups<S> {
+    type Target = [DuplicateGroup];
+
+    fn deref(&self) -> &Self::Target {
+        self.0.borrow()
+    }
+}
+
+impl<S: Borrow<[DuplicateGroup]>> fmt::Display for DuplicateGroups<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            writeln!(f, "No duplicate steps found.")
+        } else {
+            self.iter().try_for_each(|group| write!(f, "{group}"))
         }
     }
 }
 
+/// Newtype wrapper for displaying workspace settings, one per line with
+/// where its value comes from and what it controls.
+pub struct SettingValues<S = Vec<SettingValue>>(pub S);
+
+impl SettingValues {
+    /// Create a wrapper owning `settings`.
+    pub fn owned(settings: Vec<SettingValue>) -> Self {
+        Self(settings)
+    }
+}
+
+impl<'a> SettingValues<&'a [SettingValue]> {
+    /// Create a wrapper borrowing `settings`.
+    pub fn borrowed(settings: &'a [SettingValue]) -> Self {
+        Self(settings)
+    }
+}
+
+impl<S: Borrow<[SettingValue]>> Deref for SettingValues<S> {
+    type Target = [SettingValue];
+
+    fn deref(&self) -> &Self::Target {
+        self.0.borrow()
+    }
+}
+
+impl<S: Borrow<[SettingValue]>> fmt::Display for SettingValues<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.iter().try_for_each(|setting| {
+            writeln!(f, "- {setting}")?;
+            writeln!(f, "  {}", setting.key.description())
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use jiff::Timestamp;
 
     use super::*;
-    use crate::models::{PlanStatus, StepStatus};
+    use crate::models::{PlanStatus, StatusLabels, StepStatus};
 
     fn create_test_plan_summary() -> PlanSummary {
         PlanSummary {
@@ -71,11 +495,22 @@ mod tests {
             description: Some("A test plan".to_string()),
             status: PlanStatus::Active,
             directory: Some("/test".to_string()),
+            parent_plan_id: None,
+            due_date: None,
+            status_labels: StatusLabels::default(),
             created_at: Timestamp::from_second(1640995200).unwrap(), // 2022-01-01 00:00:00 UTC
             updated_at: Timestamp::from_second(1640995200).unwrap(),
             total_steps: 3,
             completed_steps: 1,
             pending_steps: 2,
+            in_progress_steps: 0,
+            awaiting_verification_steps: 0,
+            child_plans: 0,
+            child_total_steps: 0,
+            child_completed_steps: 0,
+            oldest_in_progress_age: None,
+            in_progress_warning_threshold: None,
+            momentum: None,
         }
     }
 
@@ -86,10 +521,20 @@ mod tests {
             title: "Test Step".to_string(),
             description: Some("A test step".to_string()),
             acceptance_criteria: Some("Should work".to_string()),
-            references: vec!["http://example.com".to_string()],
+            expected_output: None,
+            references: vec!["http://example.com".into()],
             status: StepStatus::Todo,
             result: None,
             order: 0,
+            phase: None,
+            estimate_minutes: None,
+            verification_required: false,
+            verification: None,
+            depends_on: Vec::new(),
+            notes: Vec::new(),
+            blocked_reason: None,
+            started_at: None,
+            completed_at: None,
             created_at: Timestamp::from_second(1640995200).unwrap(),
             updated_at: Timestamp::from_second(1640995200).unwrap(),
         }
@@ -132,7 +577,7 @@ mod tests {
 
     #[test]
     fn test_steps_display_empty() {
-        let steps = Steps(vec![]);
+        let steps = Steps::owned(vec![]);
         let output = format!("{}", steps);
         assert_eq!(output, "No steps found.\n");
     }
@@ -140,7 +585,7 @@ mod tests {
     #[test]
     fn test_steps_display_single_step() {
         let step = create_test_step();
-        let steps = Steps(vec![step]);
+        let steps = Steps::owned(vec![step]);
         let output = format!("{}", steps);
 
         assert!(output.contains("Test Step"));
@@ -156,7 +601,7 @@ mod tests {
         step2.title = "Second Step".to_string();
         step2.status = StepStatus::Done;
 
-        let steps = Steps(vec![step1, step2]);
+        let steps = Steps::owned(vec![step1, step2]);
         let output = format!("{}", steps);
 
         assert!(output.contains("Test Step"));
@@ -164,4 +609,112 @@ mod tests {
         assert!(output.contains("○ Todo"));
         assert!(output.contains("✓ Done"));
     }
+
+    #[test]
+    fn test_steps_display_modes() {
+        let mut done = create_test_step();
+        done.id = 2;
+        done.title = "Second Step".to_string();
+        done.status = StepStatus::Done;
+        let steps = vec![create_test_step(), done];
+
+        assert_eq!(
+            Steps::compact(&steps).to_string(),
+            "- [ ] 1. Test Step (○ Todo)\n- [x] 2. Second Step (✓ Done)\n"
+        );
+        assert_eq!(Steps::compact(&[]).to_string(), "No steps found.\n");
+
+        let mut in_progress = create_test_step();
+        in_progress.status = StepStatus::InProgress;
+        let in_progress = [in_progress];
+        assert!(
+            Steps::compact(&in_progress)
+                .to_string()
+                .starts_with("- [ ] 1.")
+        );
+        assert!(
+            Steps::compact(&in_progress)
+                .with_mode(StepsMode::Checklist)
+                .to_string()
+                .starts_with("- [~] 1.")
+        );
+
+        let detailed = Steps::detailed(&steps).to_string();
+        assert_eq!(
+            detailed,
+            Steps::compact(&steps)
+                .with_mode(StepsMode::Detailed)
+                .to_string()
+        );
+        assert!(detailed.starts_with("### 1. Test Step (○ Todo)\n\n"));
+        assert!(detailed.contains("### 2. Second Step (✓ Done)\n\n"));
+        assert!(detailed.contains("Should work"));
+        assert_eq!(Steps::owned(steps).mode(), StepsMode::Detailed);
+    }
+
+    #[test]
+    fn test_step_outlines_display() {
+        let outline = StepOutlines(vec![
+            StepOutline {
+                id: 7,
+                order: 0,
+                title: "First Step".to_string(),
+                status: StepStatus::Done,
+            },
+            StepOutline {
+                id: 3,
+                order: 1,
+                title: "Second Step".to_string(),
+                status: StepStatus::InProgress,
+            },
+        ]);
+
+        assert_eq!(
+            outline.to_string(),
+            "- 7. First Step (✓ Done)\n- 3. Second Step (➤ In Progress)\n"
+        );
+        assert_eq!(StepOutlines(vec![]).to_string(), "No steps found.\n");
+    }
+
+    #[test]
+    fn test_borrowed_wrappers_display_as_owned() {
+        let summaries = vec![create_test_plan_summary()];
+        assert_eq!(
+            PlanSummaries::borrowed(&summaries).to_string(),
+            PlanSummaries::owned(summaries.clone()).to_string()
+        );
+
+        let mut done = create_test_step();
+        done.status = StepStatus::Done;
+        let steps = vec![create_test_step(), done];
+        assert_eq!(
+            Steps::borrowed(&steps).to_string(),
+            Steps::owned(steps.clone()).to_string()
+        );
+        assert_eq!(Steps::borrowed(&steps).len(), 2);
+        assert_eq!(Steps::borrowed(&[]).to_string(), "No steps found.\n");
+
+        let outlines = vec![StepOutline {
+            id: 1,
+            order: 0,
+            title: "Only Step".to_string(),
+            status: StepStatus::Todo,
+        }];
+        assert_eq!(
+            StepOutlines::borrowed(&outlines).to_string(),
+            StepOutlines::owned(outlines.clone()).to_string()
+        );
+    }
+
+    #[test]
+    fn test_owned_wrappers_are_send() {
+        fn assert_send<T: Send + 'static>() {}
+
+        assert_send::<PlanSummaries>();
+        assert_send::<Steps>();
+        assert_send::<StepOutlines>();
+        assert_send::<crate::display::HandoffSummary>();
+        assert_send::<crate::display::CreateResult<Step>>();
+        assert_send::<crate::display::UpdateResult<Step>>();
+    }
 }
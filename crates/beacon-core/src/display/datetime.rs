@@ -3,7 +3,7 @@
 //! This module provides wrapper types for formatting timestamps in a
 //! consistent, human-readable format using system timezone.
 
-use std::fmt;
+use std::{fmt, time::Duration};
 
 use jiff::{Timestamp, tz::TimeZone};
 
@@ -34,3 +34,52 @@ impl<'a> fmt::Display for LocalDateTime<'a> {
         )
     }
 }
+
+/// A wrapper around `Duration` that displays it as a compact age.
+///
+/// Only the largest whole unit is shown: days (`21d`), hours (`5h`) or
+/// minutes (`12m`). Durations under a minute are displayed as `<1m`.
+pub struct Age(pub Duration);
+
+impl fmt::Display for Age {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MINUTE: u64 = 60;
+        const HOUR: u64 = 60 * MINUTE;
+        const DAY: u64 = 24 * HOUR;
+
+        match self.0.as_secs() {
+            secs if secs >= DAY => write!(f, "{}d", secs / DAY),
+            secs if secs >= HOUR => write!(f, "{}h", secs / HOUR),
+            secs if secs >= MINUTE => write!(f, "{}m", secs / MINUTE),
+            _ => write!(f, "<1m"),
+        }
+    }
+}
+
+/// A wrapper around `Duration` that displays it as a compact elapsed time.
+///
+/// Unlike [`Age`], the two largest units are shown, such as `2h 13m` or
+/// `3d 4h`; a zero second unit is left out. Durations under a minute are
+/// displayed as `<1m`.
+pub struct Elapsed(pub Duration);
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MINUTE: u64 = 60;
+        const HOUR: u64 = 60 * MINUTE;
+        const DAY: u64 = 24 * HOUR;
+
+        let secs = self.0.as_secs();
+        let (major, minor) = match secs {
+            secs if secs >= DAY => ((secs / DAY, "d"), (secs % DAY / HOUR, "h")),
+            secs if secs >= HOUR => ((secs / HOUR, "h"), (secs % HOUR / MINUTE, "m")),
+            secs if secs >= MINUTE => return write!(f, "{}m", secs / MINUTE),
+            _ => return write!(f, "<1m"),
+        };
+        write!(f, "{}{}", major.0, major.1)?;
+        if minor.0 > 0 {
+            write!(f, " {}{}", minor.0, minor.1)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+//! Duplicate step report formatting.
+//!
+//! Renders each [`DuplicateGroup`] as a section listing its steps together
+//! with the plans they belong to, so that they can be consolidated by hand.
+
+use std::fmt;
+
+use crate::models::{DuplicateGroup, DuplicateStep};
+
+impl fmt::Display for DuplicateStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Step {} ({}): {} — plan {}: {}",
+            self.step_id, self.status, self.title, self.plan_id, self.plan_title
+        )
+    }
+}
+
+impl fmt::Display for DuplicateGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let title = self
+            .steps
+            .first()
+            .map_or(self.key.as_str(), |step| &step.title);
+        let plans = self.plan_count();
+        writeln!(
+            f,
+            "## {title} ({} steps in {plans} {})",
+            self.steps.len(),
+            if plans == 1 { "plan" } else { "plans" }
+        )?;
+        writeln!(f)?;
+        for step in &self.steps {
+            writeln!(f, "- {step}")?;
+        }
+        writeln!(f)
+    }
+}
@@ -0,0 +1,77 @@
+//! Explanation formatting for plan listings.
+
+use std::fmt;
+
+use crate::models::{CompletionFilter, Explanation, PLAN_LISTING_ORDER};
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "## Explanation\n")?;
+        writeln!(f, "- **Database**: `{}`", self.database_path.display())?;
+
+        if let Some(filter) = &self.filter {
+            let directories = filter.directories();
+            if directories.is_empty() {
+                writeln!(f, "- **Directories**: any")?;
+            } else {
+                let directories: Vec<String> =
+                    directories.iter().map(|dir| format!("`{dir}`")).collect();
+                writeln!(
+                    f,
+                    "- **Directories**: {} (and below)",
+                    directories.join(" or ")
+                )?;
+            }
+
+            let status = match (filter.status(), filter.include_archived()) {
+                (Some(status), _) => status.as_str(),
+                (None, true) => "any",
+                (None, false) => "active",
+            };
+            writeln!(f, "- **Status**: {status}")?;
+
+            if let Some(title) = filter.title_contains() {
+                writeln!(f, "- **Title contains**: {title}")?;
+            }
+            if let Some(after) = filter.created_after() {
+                writeln!(f, "- **Created after**: {after}")?;
+            }
+            if let Some(before) = filter.created_before() {
+                writeln!(f, "- **Created before**: {before}")?;
+            }
+            if let Some(after) = filter.updated_after() {
+                writeln!(f, "- **Updated after**: {after}")?;
+            }
+            if let Some(parent) = filter.parent_plan_id() {
+                writeln!(f, "- **Parent plan**: {parent}")?;
+            }
+            if let Some(completion) = filter.completion_status() {
+                let completion = match completion {
+                    CompletionFilter::Complete => "complete",
+                    CompletionFilter::Incomplete => "incomplete",
+                    CompletionFilter::Empty => "empty",
+                };
+                writeln!(f, "- **Completion**: {completion}")?;
+            }
+        } else {
+            writeln!(f, "- **Filter**: none (active plans)")?;
+        }
+
+        writeln!(f, "- **Order**: {PLAN_LISTING_ORDER}")?;
+        match self.filter.as_ref().map(|f| (f.limit(), f.offset())) {
+            Some((Some(limit), offset)) => writeln!(
+                f,
+                "- **Pagination**: at most {limit} plans from offset {}",
+                offset.unwrap_or(0)
+            )?,
+            Some((None, Some(offset))) => writeln!(f, "- **Pagination**: from offset {offset}")?,
+            _ => writeln!(f, "- **Pagination**: none")?,
+        }
+
+        writeln!(f, "\n### Plans remaining after each stage\n")?;
+        for stage in &self.stages {
+            writeln!(f, "- {}: {}", stage.name, stage.plans)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+//! Handoff brief formatting for plans.
+//!
+//! This module provides a wrapper type that condenses a plan into a short
+//! markdown brief suitable for pasting into a handoff message: the goal,
+//! what has been done, what is in progress, and what is left, blocked steps
+//! included.
+
+use std::{borrow::Borrow, fmt};
+
+use crate::models::{Plan, Step, StepStatus};
+
+/// Wrapper type for displaying a plan as a handoff brief.
+///
+/// Unlike the full [`Plan`] display, only step titles are listed, together
+/// with the recorded result for completed steps. The plan may be owned, the
+/// default, or borrowed.
+pub struct HandoffSummary<P = Plan>(pub P);
+
+impl<P: Borrow<Plan>> HandoffSummary<P> {
+    fn write_section(
+        f: &mut fmt::Formatter<'_>,
+        heading: &str,
+        steps: &[&Step],
+        with_result: bool,
+    ) -> fmt::Result {
+        writeln!(f, "## {heading}")?;
+        writeln!(f)?;
+
+        if steps.is_empty() {
+            writeln!(f, "- None")?;
+        }
+
+        for step in steps {
+            write!(f, "- {} (step {})", step.title, step.id)?;
+            match (step.status, &step.blocked_reason) {
+                (StepStatus::Blocked, Some(reason)) => writeln!(f, " — blocked: {reason}")?,
+                (StepStatus::Blocked, None) => writeln!(f, " — blocked")?,
+                _ => writeln!(f)?,
+            }
+            if with_result && let Some(result) = &step.result {
+                // Indent the result so that it stays within the list item
+                result
+                    .lines()
+                    .try_for_each(|line| writeln!(f, "  {line}"))?;
+            }
+        }
+
+        writeln!(f)
+    }
+}
+
+impl<P: Borrow<Plan>> fmt::Display for HandoffSummary<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plan = self.0.borrow();
+        let by_status = |statuses: &[StepStatus]| -> Vec<&Step> {
+            plan.steps
+                .iter()
+                .filter(|s| statuses.contains(&s.status))
+                .collect()
+        };
+
+        writeln!(f, "# Handoff: {} (plan {})", plan.title, plan.id)?;
+        writeln!(f)?;
+
+        writeln!(f, "## Goal")?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{}",
+            plan.description
+                .as_deref()
+                .unwrap_or("No description provided.")
+        )?;
+        writeln!(f)?;
+
+        Self::write_section(f, "Done", &by_status(&[StepStatus::Done]), true)?;
+        Self::write_section(
+            f,
+            "In Progress",
+            &by_status(&[StepStatus::InProgress]),
+            false,
+        )?;
+        // Blocked steps are still left to do, flagged with why
+        Self::write_section(
+            f,
+            "Remaining",
+            &by_status(&[StepStatus::Todo, StepStatus::Blocked]),
+            false,
+        )
+    }
+}
@@ -0,0 +1,26 @@
+//! Database maintenance reports.
+
+use std::fmt;
+
+use crate::models::MaintenanceReport;
+
+/// Lists the size before and after vacuuming and the integrity check's
+/// outcome, with each problem found on a line of its own.
+impl fmt::Display for MaintenanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "- Size before: {} bytes", self.size_before)?;
+        writeln!(f, "- Size after: {} bytes", self.size_after)?;
+        writeln!(f, "- Reclaimed: {} bytes", self.bytes_reclaimed())?;
+        if self.is_intact() {
+            return writeln!(f, "- Integrity: ok");
+        }
+        writeln!(
+            f,
+            "- Integrity: {} problem(s) found",
+            self.integrity_problems.len()
+        )?;
+        self.integrity_problems
+            .iter()
+            .try_for_each(|problem| writeln!(f, "  - {problem}"))
+    }
+}
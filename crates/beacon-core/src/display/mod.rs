@@ -4,14 +4,32 @@
 //! types for operation results, enabling consistent formatting across different
 //! output contexts.
 
+pub mod badge;
+pub mod changelog;
+pub mod claim;
 pub mod collections;
 pub mod datetime;
+pub mod duplicates;
+pub mod explanation;
+pub mod handoff;
+pub mod maintenance;
 pub mod models;
+pub mod momentum;
+pub mod report;
 pub mod results;
+pub mod settings;
+pub mod stats;
 pub mod status;
+pub mod transitions;
 
 // Re-export commonly used types for convenience
-pub use collections::{PlanSummaries, Steps};
-pub use datetime::LocalDateTime;
+pub use claim::{ClaimAge, DEFAULT_CLAIM_WARNING_THRESHOLD};
+pub use collections::{
+    DuplicateGroups, Events, ListedSteps, PlanSummaries, SettingValues, StepOutlines, Steps,
+    StepsByPlan, StepsMode, Templates, TrashedSteps,
+};
+pub use datetime::{Age, Elapsed, LocalDateTime};
+pub use handoff::HandoffSummary;
+pub use models::{PlanAt, StepAt};
 pub use results::{CreateResult, DeleteResult, UpdateResult};
 pub use status::OperationStatus;
@@ -9,10 +9,22 @@
 //! - Consistent formatting with status icons and structured sections
 //! - Context-aware display behavior for different use cases
 
-use std::fmt;
+use std::{fmt, time::Duration};
 
-use super::datetime::LocalDateTime;
-use crate::models::{Plan, PlanStatus, PlanSummary, Step, StepStatus};
+use jiff::{Timestamp, Zoned, civil::Date, tz::TimeZone};
+
+use super::{
+    StepsMode,
+    claim::{ClaimAge, DEFAULT_CLAIM_WARNING_THRESHOLD},
+    datetime::{Age, Elapsed, LocalDateTime},
+};
+use crate::{
+    models::{
+        Event, EventEntity, EventKind, Plan, PlanStatus, PlanSummary, ReferenceKind, StatusLabels,
+        Step, StepOutline, StepReference, StepStatus, StepStatusCounts, Template, TrashedStep,
+    },
+    quickadd::format_estimate,
+};
 
 impl fmt::Display for PlanStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -26,76 +38,443 @@ impl fmt::Display for StepStatus {
     }
 }
 
+/// Wrapper type for displaying a plan as of a given instant.
+///
+/// In-progress steps are annotated with how long ago they were claimed,
+/// relative to `now`. The plain [`Plan`] display uses the current time; this
+/// wrapper lets callers (and tests) pin it. Long step lists can be paged with
+/// [`PlanAt::with_step_window`].
+pub struct PlanAt<'a> {
+    pub plan: &'a Plan,
+    pub now: Timestamp,
+    pub claim_warning_threshold: Duration,
+    /// Number of steps skipped before the first one shown
+    pub step_offset: usize,
+    /// Maximum number of steps shown, all remaining ones if `None`
+    pub step_limit: Option<usize>,
+    /// Completed and total step counts shown after the title, if any
+    pub progress: Option<(u32, u32)>,
+    /// Step counts by status shown after the metadata, if any
+    pub status_counts: Option<StepStatusCounts>,
+    /// How much of each step is shown
+    pub steps_mode: StepsMode,
+}
+
+impl<'a> PlanAt<'a> {
+    /// Create a wrapper rendering `plan` as of `now`.
+    pub fn new(plan: &'a Plan, now: Timestamp) -> Self {
+        Self {
+            plan,
+            now,
+            claim_warning_threshold: DEFAULT_CLAIM_WARNING_THRESHOLD,
+            step_offset: 0,
+            step_limit: None,
+            progress: None,
+            status_counts: None,
+            steps_mode: StepsMode::Detailed,
+        }
+    }
+
+    /// Set the claim age above which in-progress steps are flagged.
+    pub fn with_claim_warning_threshold(mut self, threshold: Duration) -> Self {
+        self.claim_warning_threshold = threshold;
+        self
+    }
+
+    /// Show only up to `limit` steps starting after the first `offset`,
+    /// followed by a footer saying which steps are shown out of how many.
+    pub fn with_step_window(mut self, offset: usize, limit: Option<usize>) -> Self {
+        self.step_offset = offset;
+        self.step_limit = limit;
+        self
+    }
+
+    /// Show the plan's progress from its summary after the title, as in
+    /// `(3/7 done)`.
+    pub fn with_progress(mut self, summary: &PlanSummary) -> Self {
+        self.progress = Some((summary.completed_steps, summary.total_steps));
+        self
+    }
+
+    /// Show how many of the plan's steps are in each status after the
+    /// metadata, as in `- Progress: 3/7 done, 1 in progress, 3 todo`.
+    pub fn with_status_counts(mut self, counts: StepStatusCounts) -> Self {
+        self.status_counts = Some(counts);
+        self
+    }
+
+    /// Show the steps in the given mode, e.g. one line each when compact.
+    pub fn with_steps_mode(mut self, mode: StepsMode) -> Self {
+        self.steps_mode = mode;
+        self
+    }
+
+    fn is_paged(&self) -> bool {
+        self.step_offset > 0 || self.step_limit.is_some()
+    }
+
+    /// Writes the plan's steps in the step window, grouped by phase,
+    /// followed by which steps are shown if the window doesn't cover them all.
+    fn write_steps(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plan = self.plan;
+        if plan.steps.is_empty() {
+            return writeln!(f, "\nNo steps in this plan.");
+        }
+
+        let total = plan.steps.len();
+        let start = self.step_offset.min(total);
+        let end = self
+            .step_limit
+            .map_or(total, |limit| start.saturating_add(limit).min(total));
+
+        // Phased steps are grouped under a header per phase, in the order
+        // each phase first appears; the rest stay under "Steps"
+        for (phase, steps) in phase_groups(&plan.steps[start..end]) {
+            match phase {
+                Some(phase) => writeln!(f, "\n## Phase: {phase}")?,
+                None => writeln!(f, "\n## Steps")?,
+            }
+            writeln!(f)?;
+            steps.into_iter().try_for_each(|step| {
+                let step = StepAt::new(step, self.now)
+                    .with_claim_warning_threshold(self.claim_warning_threshold)
+                    .with_status_labels(&plan.status_labels)
+                    .with_mode(self.steps_mode);
+                write!(f, "{step}")
+            })?;
+        }
+        if start == end {
+            writeln!(f, "\n## Steps")?;
+            writeln!(f)?;
+        }
+
+        if self.is_paged() {
+            if start == end {
+                writeln!(f, "No steps in this range; the plan has {total} steps.")?;
+            } else {
+                // Detailed steps end in a blank line, checklist lines don't
+                if self.steps_mode.is_one_line() {
+                    writeln!(f)?;
+                }
+                writeln!(f, "Showing steps {}–{end} of {total}.", start + 1)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl fmt::Display for Plan {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "# {}. {}", self.id, self.title)?;
+        write!(f, "{}", PlanAt::new(self, Timestamp::now()))
+    }
+}
+
+impl fmt::Display for PlanAt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plan = self.plan;
+        write!(f, "# {}. {}", plan.id, plan.title)?;
+        if let Some((completed, total)) = self.progress {
+            write!(f, " ({completed}/{total} done)")?;
+        }
+        writeln!(f)?;
         writeln!(f)?;
 
         // Metadata section
-        writeln!(f, "- Status: {}", self.status.as_str())?;
-        if let Some(dir) = &self.directory {
+        writeln!(f, "- Status: {}", plan.status.as_str())?;
+        if let Some(due) = plan.due_date {
+            let today = self.now.to_zoned(TimeZone::system()).date();
+            writeln!(f, "- Due: {}", DueDate(due, plan.is_overdue(today)))?;
+        }
+        if let Some(dir) = &plan.directory {
             writeln!(f, "- Directory: {dir}")?;
         }
-        writeln!(f, "- Created: {}", LocalDateTime(&self.created_at))?;
-        writeln!(f, "- Updated: {}", LocalDateTime(&self.updated_at))?;
+        if let Some(subpath) = &plan.subpath {
+            writeln!(f, "- Subpath: {subpath}")?;
+        }
+        if let Some(parent_id) = plan.parent_plan_id {
+            writeln!(f, "- Parent: plan {parent_id}")?;
+        }
+        if let Some(target_id) = plan.merged_into_plan_id {
+            writeln!(f, "- Merged into: plan {target_id}")?;
+        }
+        if plan.auto_archive {
+            writeln!(f, "- Auto-archive: when all steps are done")?;
+        }
+        if !plan.status_labels.is_empty() {
+            writeln!(f, "- Status labels: {}", plan.status_labels)?;
+        }
+        write_step_warning(
+            f,
+            plan,
+            "Awaiting verification",
+            Step::awaiting_verification,
+        )?;
+        write_step_warning(
+            f,
+            plan,
+            "Results shorter than expected output",
+            Step::result_may_be_underdocumented,
+        )?;
+        writeln!(f, "- Created: {}", LocalDateTime(&plan.created_at))?;
+        writeln!(f, "- Updated: {}", LocalDateTime(&plan.updated_at))?;
+        if let Some(counts) = self.status_counts {
+            write_status_counts(f, counts, &plan.status_labels)?;
+        }
 
         // Description as a paragraph
-        if let Some(desc) = &self.description {
+        if let Some(desc) = &plan.description {
             writeln!(f)?;
             writeln!(f, "{desc}")?;
         }
 
-        if !self.steps.is_empty() {
-            writeln!(f, "\n## Steps")?;
+        if let Some(notes) = &plan.notes {
+            writeln!(f, "\n## Notes")?;
             writeln!(f)?;
-            self.steps
-                .iter()
-                .try_for_each(|step| write!(f, "{}", step))?;
-        } else {
-            writeln!(f, "\nNo steps in this plan.")?;
+            writeln!(f, "{notes}")?;
+        }
+
+        self.write_steps(f)?;
+
+        if !plan.child_plans.is_empty() {
+            writeln!(f, "\n## Child Plans")?;
+            writeln!(f)?;
+            plan.child_plans.iter().try_for_each(|child| {
+                let archived = if child.status == PlanStatus::Archived {
+                    " [archived]"
+                } else {
+                    ""
+                };
+                writeln!(
+                    f,
+                    "- {}. {} ({}/{}){archived}",
+                    child.id, child.title, child.completed_steps, child.total_steps
+                )
+            })?;
         }
 
         Ok(())
     }
 }
 
+/// Writes how many steps are in each status, named by the plan's labels.
+/// Blocked steps are only mentioned when there are any.
+fn write_status_counts(
+    f: &mut fmt::Formatter<'_>,
+    counts: StepStatusCounts,
+    labels: &StatusLabels,
+) -> fmt::Result {
+    if counts.total() == 0 {
+        return writeln!(f, "- Progress: no steps yet");
+    }
+
+    let label = |status| labels.label(status).to_lowercase();
+    write!(
+        f,
+        "- Progress: {}/{} {}",
+        counts.done,
+        counts.total(),
+        label(StepStatus::Done)
+    )?;
+    for status in [
+        StepStatus::InProgress,
+        StepStatus::Todo,
+        StepStatus::Blocked,
+    ] {
+        let count = counts.count(status);
+        if count > 0 || status != StepStatus::Blocked {
+            write!(f, ", {count} {}", label(status))?;
+        }
+    }
+    writeln!(f)
+}
+
+/// Writes a warning line listing the plan's steps matching `flagged`, if any.
+fn write_step_warning(
+    f: &mut fmt::Formatter<'_>,
+    plan: &Plan,
+    warning: &str,
+    flagged: impl Fn(&Step) -> bool,
+) -> fmt::Result {
+    let ids = plan
+        .steps
+        .iter()
+        .filter(|step| flagged(step))
+        .map(|step| step.id.to_string())
+        .collect::<Vec<_>>();
+    if ids.is_empty() {
+        return Ok(());
+    }
+    writeln!(f, "- ⚠ {warning}: steps {}", ids.join(", "))
+}
+
+/// Groups steps by phase, keeping step order within each group and
+/// ordering groups by where their first step appears.
+fn phase_groups(steps: &[Step]) -> Vec<(Option<&str>, Vec<&Step>)> {
+    let mut groups: Vec<(Option<&str>, Vec<&Step>)> = Vec::new();
+    for step in steps {
+        let phase = step.phase.as_deref();
+        match groups.iter_mut().find(|(group, _)| *group == phase) {
+            Some((_, members)) => members.push(step),
+            None => groups.push((phase, vec![step])),
+        }
+    }
+    groups
+}
+
+/// Wrapper type for displaying a step as of a given instant.
+///
+/// An in-progress step's status is followed by how long ago it was claimed
+/// relative to `now`, flagged once older than the warning threshold. The
+/// status is shown with its plan's custom label when one is set.
+pub struct StepAt<'a> {
+    pub step: &'a Step,
+    pub now: Timestamp,
+    pub claim_warning_threshold: Duration,
+    pub status_labels: Option<&'a StatusLabels>,
+    /// Whether the step is shown with all its sections or as one line
+    pub mode: StepsMode,
+}
+
+impl<'a> StepAt<'a> {
+    /// Create a wrapper rendering `step` as of `now`.
+    pub fn new(step: &'a Step, now: Timestamp) -> Self {
+        Self {
+            step,
+            now,
+            claim_warning_threshold: DEFAULT_CLAIM_WARNING_THRESHOLD,
+            status_labels: None,
+            mode: StepsMode::Detailed,
+        }
+    }
+
+    /// Set the claim age above which the step is flagged.
+    pub fn with_claim_warning_threshold(mut self, threshold: Duration) -> Self {
+        self.claim_warning_threshold = threshold;
+        self
+    }
+
+    /// Show the status using the given custom labels.
+    pub fn with_status_labels(mut self, labels: &'a StatusLabels) -> Self {
+        self.status_labels = Some(labels);
+        self
+    }
+
+    /// Show the step in the given mode; compact steps are a single checklist
+    /// line such as `- [x] 12. Title (✓ Done)`.
+    pub fn with_mode(mut self, mode: StepsMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// The step's ID, title and status, followed by its claim age if it is
+    /// in progress.
+    fn heading(&self) -> String {
+        let step = self.step;
+        let claim = self
+            .claim_age()
+            .map(|age| format!(" — {age}"))
+            .unwrap_or_default();
+
+        let mut status = self
+            .status_labels
+            .map(|labels| labels.with_icon(step.status))
+            .unwrap_or_else(|| step.status.with_icon().to_string());
+        if step.awaiting_verification() {
+            status.push_str(" (awaiting verification)");
+        }
+        format!("{}. {} ({status}{claim})", step.id, step.title)
+    }
+
+    /// Age of the step's claim, if it is in progress.
+    pub fn claim_age(&self) -> Option<ClaimAge> {
+        (self.step.status == StepStatus::InProgress).then(|| {
+            ClaimAge::between(self.step.updated_at, self.now, self.claim_warning_threshold)
+        })
+    }
+}
+
 impl fmt::Display for Step {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(
-            f,
-            "### {}. {} ({})",
-            self.id,
-            self.title,
-            self.status.with_icon()
-        )?;
+        write!(f, "{}", StepAt::new(self, Timestamp::now()))
+    }
+}
+
+impl fmt::Display for StepAt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let step = self.step;
+        let heading = self.heading();
+        if self.mode.is_one_line() {
+            let check = match step.status {
+                StepStatus::Done => 'x',
+                StepStatus::InProgress if self.mode == StepsMode::Checklist => '~',
+                _ => ' ',
+            };
+            return writeln!(f, "- [{check}] {heading}");
+        }
+
+        writeln!(f, "### {heading}")?;
         writeln!(f)?;
 
-        if let Some(desc) = &self.description {
+        write_details(f, step)?;
+
+        if let Some(desc) = &step.description {
             writeln!(f, "{desc}")?;
             writeln!(f)?;
         }
 
-        if let Some(criteria) = &self.acceptance_criteria {
+        // Show result only for completed steps, right after the expected
+        // output so the two read as a comparison
+        let result = (step.status == StepStatus::Done)
+            .then_some(step.result.as_ref())
+            .flatten();
+        let expected = |f: &mut fmt::Formatter<'_>| match &step.expected_output {
+            Some(expected) => writeln!(f, "#### Expected\n\n{expected}\n"),
+            None => Ok(()),
+        };
+
+        if result.is_none() {
+            expected(f)?;
+        }
+
+        if let Some(criteria) = &step.acceptance_criteria {
             writeln!(f, "#### Acceptance")?;
             writeln!(f)?;
             writeln!(f, "{criteria}")?;
             writeln!(f)?;
         }
 
-        // Show result only for completed steps
-        if self.status == StepStatus::Done
-            && let Some(result) = &self.result
-        {
+        if let Some(result) = result {
+            expected(f)?;
             writeln!(f, "#### Result")?;
             writeln!(f)?;
             writeln!(f, "{result}")?;
             writeln!(f)?;
+            if step.result_may_be_underdocumented() {
+                writeln!(
+                    f,
+                    "⚠ The result is much shorter than the expected output and may not document it"
+                )?;
+                writeln!(f)?;
+            }
+        }
+
+        if step.verification_required {
+            write_verification(f, step)?;
+        }
+
+        if !step.notes.is_empty() {
+            writeln!(f, "#### Notes")?;
+            writeln!(f)?;
+            step.notes.iter().try_for_each(|note| {
+                writeln!(f, "- {}: {}", LocalDateTime(&note.created_at), note.body)
+            })?;
+            writeln!(f)?;
         }
 
-        if !self.references.is_empty() {
+        if !step.references.is_empty() {
             writeln!(f, "#### References")?;
             writeln!(f)?;
-            self.references
+            step.references
                 .iter()
                 .try_for_each(|reference| writeln!(f, "- {reference}"))?;
             writeln!(f)?;
@@ -105,15 +484,208 @@ impl fmt::Display for Step {
     }
 }
 
+/// Writes the list of a step's details, such as its estimate and when it
+/// was started, followed by a blank line if there are any.
+fn write_details(f: &mut fmt::Formatter<'_>, step: &Step) -> fmt::Result {
+    let blocked_reason = step
+        .blocked_reason
+        .as_ref()
+        .filter(|_| step.status == StepStatus::Blocked);
+    if let Some(reason) = blocked_reason {
+        writeln!(f, "- Blocked: {reason}")?;
+    }
+    if let Some(minutes) = step.estimate_minutes {
+        writeln!(f, "- Estimate: {}", format_estimate(minutes))?;
+    }
+    if !step.depends_on.is_empty() {
+        let ids = step
+            .depends_on
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>();
+        writeln!(f, "- Depends on: {}", ids.join(", "))?;
+    }
+    if let Some(started_at) = &step.started_at {
+        writeln!(f, "- Started: {}", LocalDateTime(started_at))?;
+    }
+    if let Some(completed_at) = &step.completed_at {
+        write!(f, "- Completed: {}", LocalDateTime(completed_at))?;
+        if let Some(duration) = step.duration() {
+            write!(f, " (took {})", Elapsed(duration))?;
+        }
+        writeln!(f)?;
+    }
+    if blocked_reason.is_some()
+        || step.estimate_minutes.is_some()
+        || !step.depends_on.is_empty()
+        || step.started_at.is_some()
+        || step.completed_at.is_some()
+    {
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+/// Writes the verification section of a step that requires verification.
+fn write_verification(f: &mut fmt::Formatter<'_>, step: &Step) -> fmt::Result {
+    writeln!(f, "#### Verification")?;
+    writeln!(f)?;
+    match &step.verification {
+        Some(verification) => {
+            writeln!(
+                f,
+                "Verified by {} on {}",
+                verification.verifier,
+                LocalDateTime(&verification.verified_at)
+            )?;
+            if let Some(comment) = &verification.comment {
+                writeln!(f)?;
+                writeln!(f, "{comment}")?;
+            }
+        }
+        None if step.status == StepStatus::Done => {
+            writeln!(f, "Awaiting verification by a second agent")?;
+        }
+        None => writeln!(f, "Required from a second agent once done")?,
+    }
+    writeln!(f)
+}
+
+/// Labelled references render as Markdown links and unlabelled URLs as
+/// autolinks; others show the bare URL. File paths found missing when the
+/// reference was validated are marked as such.
+impl fmt::Display for StepReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.label, self.kind) {
+            (Some(label), _) => write!(f, "[{label}]({})", self.url)?,
+            (None, Some(ReferenceKind::Url)) => write!(f, "<{}>", self.url)?,
+            (None, _) => write!(f, "{}", self.url)?,
+        }
+        if self.kind == Some(ReferenceKind::MissingFile) {
+            write!(f, " (not found)")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for StatusLabels {
+    /// Lists the custom labels as `inprogress → doing, done → shipped`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let labels = StepStatus::ALL
+            .into_iter()
+            .filter_map(|status| {
+                self.get(status)
+                    .map(|label| format!("{} → {label}", status.as_str()))
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", labels.join(", "))
+    }
+}
+
+impl fmt::Display for StepOutline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}. {} ({})",
+            self.id,
+            self.title,
+            self.status.with_icon()
+        )
+    }
+}
+
+impl fmt::Display for TrashedStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}. {} ({}), trashed {}",
+            self.id,
+            self.title,
+            self.status.with_icon(),
+            LocalDateTime(&self.deleted_at)
+        )
+    }
+}
+
+impl fmt::Display for EventEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}. {} {} {} {}",
+            self.id,
+            LocalDateTime(&self.created_at),
+            self.entity_type,
+            self.entity_id,
+            self.kind
+        )?;
+        if let Some(title) = self.payload.get("title").and_then(|title| title.as_str()) {
+            write!(f, ": {title}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} ({} step(s))", self.name, self.steps.len())?;
+        for (index, step) in self.steps.iter().enumerate() {
+            writeln!(f, "  {}. {}", index + 1, step.title)?;
+        }
+        Ok(())
+    }
+}
+
+/// A plan's due date, marked when the plan is overdue.
+struct DueDate(Date, bool);
+
+impl fmt::Display for DueDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)?;
+        if self.1 {
+            write!(f, " (overdue)")?;
+        }
+        Ok(())
+    }
+}
+
 impl fmt::Display for PlanSummary {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let progress = if self.total_steps > 0 {
-            format!(" ({}/{})", self.completed_steps, self.total_steps)
+            let in_progress = if self.in_progress_steps > 0 {
+                let label = self.status_labels.label(StepStatus::InProgress);
+                format!(", {} {}", self.in_progress_steps, label.to_lowercase())
+            } else {
+                String::new()
+            };
+            format!(
+                " ({}/{}{in_progress})",
+                self.completed_steps, self.total_steps
+            )
         } else {
             String::new()
         };
 
-        writeln!(f, "## {} (ID: {}){progress}", self.title, self.id)?;
+        let warning = self
+            .stale_in_progress_age()
+            .map(|age| {
+                let in_progress = self.status_labels.label(StepStatus::InProgress);
+                format!(" ⚠ step {} {}", in_progress.to_lowercase(), Age(age))
+            })
+            .unwrap_or_default();
+
+        writeln!(f, "## {} (ID: {}){progress}{warning}", self.title, self.id)?;
         writeln!(f)?;
 
         if let Some(desc) = &self.description {
@@ -124,6 +696,46 @@ impl fmt::Display for PlanSummary {
             writeln!(f, "- **Directory**: {dir}")?;
         }
 
+        if let Some(parent_id) = self.parent_plan_id {
+            writeln!(f, "- **Parent**: plan {parent_id}")?;
+        }
+
+        if self.child_plans > 0 {
+            writeln!(
+                f,
+                "- **Child plans**: {} ({}/{} steps {})",
+                self.child_plans,
+                self.child_completed_steps,
+                self.child_total_steps,
+                self.status_labels.label(StepStatus::Done).to_lowercase()
+            )?;
+        }
+
+        if self.awaiting_verification_steps > 0 {
+            writeln!(
+                f,
+                "- **Awaiting verification**: {} {}",
+                self.awaiting_verification_steps,
+                if self.awaiting_verification_steps == 1 {
+                    "step"
+                } else {
+                    "steps"
+                }
+            )?;
+        }
+
+        if let Some(trend) = self.momentum.filter(|trend| !trend.is_empty()) {
+            writeln!(
+                f,
+                "- **Momentum**: {trend} steps done (last 7 days vs prior 7)"
+            )?;
+        }
+
+        if let Some(due) = self.due_date {
+            let overdue = self.is_overdue(Zoned::now().date());
+            writeln!(f, "- **Due**: {}", DueDate(due, overdue))?;
+        }
+
         writeln!(f, "- **Created**: {}", LocalDateTime(&self.created_at))?;
         writeln!(f)?; // Add blank line after each plan
 
@@ -0,0 +1,35 @@
+//! Momentum indicators: completion streaks and week-over-week trends.
+
+use std::{cmp::Ordering, fmt};
+
+use crate::models::{Momentum, Trend};
+
+/// Renders as `▲ 5 vs 2`: steps completed in the last seven days, pointing up
+/// when more than in the seven days before, down when fewer.
+impl fmt::Display for Trend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let arrow = match self.recent.cmp(&self.prior) {
+            Ordering::Greater => "▲",
+            Ordering::Less => "▼",
+            Ordering::Equal => "=",
+        };
+        write!(f, "{arrow} {} vs {}", self.recent, self.prior)
+    }
+}
+
+/// Renders as a single line such as
+/// `3-day streak · ▲ 5 vs 2 steps done (last 7 days vs prior 7)`.
+impl fmt::Display for Momentum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.streak_days {
+            0 => write!(f, "No streak yet")?,
+            days => {
+                write!(f, "{days}-day streak")?;
+                if !self.completed_today {
+                    write!(f, " (complete a step today to keep it)")?;
+                }
+            }
+        }
+        write!(f, " · {} steps done (last 7 days vs prior 7)", self.trend)
+    }
+}
@@ -0,0 +1,57 @@
+//! Standup report formatting for plans.
+//!
+//! Renders a [`PlanReport`] as a short markdown report to post to a chat:
+//! what was completed, what is in progress and what comes next.
+
+use std::fmt;
+
+use super::LocalDateTime;
+use crate::models::{PlanReport, Step};
+
+/// Writes a section listing `steps`, with the result of completed ones.
+/// Sections without steps are left out.
+fn write_section(
+    f: &mut fmt::Formatter<'_>,
+    heading: &str,
+    steps: &[Step],
+    with_result: bool,
+) -> fmt::Result {
+    if steps.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(f)?;
+    writeln!(f, "## {heading}")?;
+    writeln!(f)?;
+    for step in steps {
+        writeln!(f, "- {} (step {})", step.title, step.id)?;
+        if with_result && let Some(result) = &step.result {
+            // Indent the result so that it stays within the list item
+            result
+                .lines()
+                .try_for_each(|line| writeln!(f, "  {line}"))?;
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for PlanReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# Standup: {} (plan {})", self.plan_title, self.plan_id)?;
+        writeln!(f)?;
+
+        let since = LocalDateTime(&self.since);
+        if self.has_activity() {
+            writeln!(f, "Since {since}.")?;
+        } else {
+            writeln!(
+                f,
+                "No activity since {since}: no steps were completed and none are in progress."
+            )?;
+        }
+
+        write_section(f, "Completed", &self.completed, true)?;
+        write_section(f, "In Progress", &self.in_progress, false)?;
+        write_section(f, "Up Next", &self.upcoming, false)
+    }
+}
@@ -6,7 +6,7 @@
 
 use std::fmt;
 
-use crate::models::{Plan, Step};
+use crate::models::{Plan, Step, StepChange, StepReference};
 
 /// Wrapper type for displaying the result of create operations.
 ///
@@ -17,6 +17,8 @@ use crate::models::{Plan, Step};
 /// - Success message with resource type and ID
 /// - Full details of the created resource
 /// - Consistent markdown structure
+///
+/// The resource may be owned or borrowed; both display the same.
 pub struct CreateResult<T> {
     pub resource: T,
 }
@@ -28,19 +30,38 @@ impl<T> CreateResult<T> {
     }
 }
 
-impl fmt::Display for CreateResult<Plan> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Created plan with ID: {}", self.resource.id)?;
+impl<T> CreateResult<T> {
+    fn write(&self, f: &mut fmt::Formatter<'_>, kind: &str, id: u64) -> fmt::Result
+    where
+        T: fmt::Display,
+    {
+        writeln!(f, "Created {kind} with ID: {id}")?;
         writeln!(f)?;
         write!(f, "{}", self.resource)
     }
 }
 
+impl fmt::Display for CreateResult<Plan> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, "plan", self.resource.id)
+    }
+}
+
+impl fmt::Display for CreateResult<&Plan> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, "plan", self.resource.id)
+    }
+}
+
 impl fmt::Display for CreateResult<Step> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Created step with ID: {}", self.resource.id)?;
-        writeln!(f)?;
-        write!(f, "{}", self.resource)
+        self.write(f, "step", self.resource.id)
+    }
+}
+
+impl fmt::Display for CreateResult<&Step> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, "step", self.resource.id)
     }
 }
 
@@ -50,13 +71,15 @@ impl fmt::Display for CreateResult<Step> {
 /// including success messages and the updated resource information.
 ///
 /// The wrapper can track and display specific changes made during the update,
-/// providing users with clear feedback about what was modified.
-pub struct UpdateResult<T> {
+/// providing users with clear feedback about what was modified. Changes are
+/// names of the fields updated by default; step updates carry
+/// [`StepChange`]s with the old and new values instead.
+pub struct UpdateResult<T, C = String> {
     pub resource: T,
-    pub changes: Vec<String>,
+    pub changes: Vec<C>,
 }
 
-impl<T> UpdateResult<T> {
+impl<T, C> UpdateResult<T, C> {
     /// Create a new UpdateResult wrapper.
     pub fn new(resource: T) -> Self {
         Self {
@@ -66,14 +89,17 @@ impl<T> UpdateResult<T> {
     }
 
     /// Create an UpdateResult with a list of changes made.
-    pub fn with_changes(resource: T, changes: Vec<String>) -> Self {
+    pub fn with_changes(resource: T, changes: Vec<C>) -> Self {
         Self { resource, changes }
     }
 }
 
-impl fmt::Display for UpdateResult<Plan> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Updated plan with ID: {}", self.resource.id)?;
+impl<T, C: fmt::Display> UpdateResult<T, C> {
+    fn write(&self, f: &mut fmt::Formatter<'_>, kind: &str, id: u64) -> fmt::Result
+    where
+        T: fmt::Display,
+    {
+        writeln!(f, "Updated {kind} with ID: {id}")?;
 
         if !self.changes.is_empty() {
             writeln!(f)?;
@@ -88,20 +114,87 @@ impl fmt::Display for UpdateResult<Plan> {
     }
 }
 
+impl fmt::Display for UpdateResult<Plan> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, "plan", self.resource.id)
+    }
+}
+
+impl fmt::Display for UpdateResult<&Plan> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, "plan", self.resource.id)
+    }
+}
+
 impl fmt::Display for UpdateResult<Step> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Updated step with ID: {}", self.resource.id)?;
+        self.write(f, "step", self.resource.id)
+    }
+}
 
-        if !self.changes.is_empty() {
+impl fmt::Display for UpdateResult<&Step> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, "step", self.resource.id)
+    }
+}
+
+impl fmt::Display for UpdateResult<Step, StepChange> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.changes.is_empty() {
+            writeln!(f, "Nothing changed in step with ID: {}", self.resource.id)?;
             writeln!(f)?;
-            writeln!(f, "Changes made:")?;
-            self.changes
-                .iter()
-                .try_for_each(|change| writeln!(f, "- {change}"))?;
+            return write!(f, "{}", self.resource);
         }
+        self.write(f, "step", self.resource.id)
+    }
+}
 
-        writeln!(f)?;
-        write!(f, "{}", self.resource)
+/// Writes an optional text field's value, or `(none)` if it is unset.
+fn write_text(f: &mut fmt::Formatter<'_>, value: Option<&str>) -> fmt::Result {
+    match value {
+        Some(value) => write!(f, "'{value}'"),
+        None => write!(f, "(none)"),
+    }
+}
+
+impl fmt::Display for StepChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (field, from, to) = match self {
+            StepChange::TitleChanged { from, to } => {
+                return write!(f, "title: '{from}' → '{to}'");
+            }
+            StepChange::StatusChanged { from, to } => {
+                return write!(f, "status: {} → {}", from.label(), to.label());
+            }
+            StepChange::ReferencesChanged { from, to } => {
+                let list = |references: &[StepReference]| {
+                    if references.is_empty() {
+                        "(none)".to_string()
+                    } else {
+                        references
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    }
+                };
+                return write!(f, "references: {} → {}", list(from), list(to));
+            }
+            StepChange::VerificationRequiredChanged { from, to } => {
+                let required = |required: bool| {
+                    if required { "required" } else { "not required" }
+                };
+                return write!(f, "verification: {} → {}", required(*from), required(*to));
+            }
+            StepChange::DescriptionChanged { from, to } => ("description", from, to),
+            StepChange::AcceptanceCriteriaChanged { from, to } => ("acceptance criteria", from, to),
+            StepChange::ExpectedOutputChanged { from, to } => ("expected output", from, to),
+            StepChange::ResultChanged { from, to } => ("result", from, to),
+        };
+        write!(f, "{field}: ")?;
+        write_text(f, from.as_deref())?;
+        write!(f, " → ")?;
+        write_text(f, to.as_deref())
     }
 }
 
@@ -111,31 +204,67 @@ impl fmt::Display for UpdateResult<Step> {
 /// including confirmation messages and resource identification.
 pub struct DeleteResult<T> {
     pub resource: T,
+    dry_run: bool,
 }
 
 impl<T> DeleteResult<T> {
     /// Create a new DeleteResult wrapper.
     pub fn new(resource: T) -> Self {
-        Self { resource }
+        Self {
+            resource,
+            dry_run: false,
+        }
+    }
+
+    /// Create a DeleteResult wrapper describing what a deletion would remove,
+    /// for previewing it without touching the database.
+    pub fn dry_run(resource: T) -> Self {
+        Self {
+            resource,
+            dry_run: true,
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, kind: &str, title: &str, id: u64) -> fmt::Result {
+        let verb = if self.dry_run {
+            "Would delete"
+        } else {
+            "Deleted"
+        };
+        write!(f, "{verb} {kind} '{title}' (ID: {id})")
+    }
+
+    fn write_plan(&self, f: &mut fmt::Formatter<'_>, plan: &Plan) -> fmt::Result {
+        self.write(f, "plan", &plan.title, plan.id)?;
+        writeln!(f, " and its {} step(s)", plan.steps.len())
+    }
+
+    fn write_step(&self, f: &mut fmt::Formatter<'_>, step: &Step) -> fmt::Result {
+        self.write(f, "step", &step.title, step.id)?;
+        writeln!(f)
     }
 }
 
 impl fmt::Display for DeleteResult<Plan> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(
-            f,
-            "Deleted plan '{}' (ID: {})",
-            self.resource.title, self.resource.id
-        )
+        self.write_plan(f, &self.resource)
+    }
+}
+
+impl fmt::Display for DeleteResult<&Plan> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_plan(f, self.resource)
     }
 }
 
 impl fmt::Display for DeleteResult<Step> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(
-            f,
-            "Deleted step '{}' (ID: {})",
-            self.resource.title, self.resource.id
-        )
+        self.write_step(f, &self.resource)
+    }
+}
+
+impl fmt::Display for DeleteResult<&Step> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_step(f, self.resource)
     }
 }
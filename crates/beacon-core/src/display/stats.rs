@@ -0,0 +1,51 @@
+//! Markdown report of planner statistics.
+
+use std::{fmt, time::Duration};
+
+use super::{Elapsed, LocalDateTime};
+use crate::models::PlannerStats;
+
+/// Renders as a markdown report with a heading naming the range, the
+/// counts as a list and the busiest directories under a heading of their
+/// own.
+impl fmt::Display for PlannerStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.since {
+            Some(since) => writeln!(f, "# Statistics since {}", LocalDateTime(since))?,
+            None => writeln!(f, "# Statistics")?,
+        }
+        writeln!(f)?;
+        writeln!(f, "- **Plans created**: {}", self.plans_created)?;
+        writeln!(f, "- **Plans archived**: {}", self.plans_archived)?;
+        writeln!(f, "- **Plans touched**: {}", self.plans_touched)?;
+        writeln!(f, "- **Steps created**: {}", self.steps_created)?;
+        writeln!(f, "- **Steps completed**: {}", self.steps_completed)?;
+        writeln!(
+            f,
+            "- **Completion rate**: {:.0}% of steps created",
+            self.completion_rate * 100.0
+        )?;
+        match self.average_seconds_to_done {
+            Some(seconds) => writeln!(
+                f,
+                "- **Average time to done**: {}",
+                Elapsed(Duration::from_secs(seconds))
+            )?,
+            None => writeln!(f, "- **Average time to done**: n/a")?,
+        }
+
+        writeln!(f)?;
+        writeln!(f, "## Busiest directories")?;
+        writeln!(f)?;
+        if self.busiest_directories.is_empty() {
+            writeln!(f, "- None")?;
+        }
+        self.busiest_directories.iter().try_for_each(|activity| {
+            writeln!(
+                f,
+                "- {}: {} step(s) completed",
+                activity.directory, activity.steps_completed
+            )
+        })
+    }
+}
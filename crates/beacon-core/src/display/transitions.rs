@@ -0,0 +1,38 @@
+//! Formatting of the transitions a step may undergo.
+
+use std::fmt;
+
+use crate::models::{AllowedTransitions, StepTransition};
+
+impl fmt::Display for StepTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for AllowedTransitions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "# Transitions of Step {} ({})",
+            self.step_id, self.status
+        )?;
+        writeln!(f)?;
+        for check in &self.transitions {
+            let target = check
+                .transition
+                .target()
+                .map(|status| format!(" → {status}"))
+                .unwrap_or_default();
+            match &check.denied {
+                None => writeln!(f, "- {}{target}: allowed", check.transition)?,
+                Some(reason) => writeln!(
+                    f,
+                    "- {}{target}: not allowed, Step {} {reason}",
+                    check.transition, self.step_id
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
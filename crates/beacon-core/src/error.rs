@@ -2,6 +2,7 @@
 
 use std::{fmt, path::PathBuf};
 
+use rusqlite::ErrorCode;
 use thiserror::Error;
 
 /// Comprehensive error type for all planner operations.
@@ -20,6 +21,15 @@ pub enum PlannerError {
     /// Step not found for the given ID
     #[error("Step with ID {id} not found")]
     StepNotFound { id: u64 },
+    /// Step template not found for the given name
+    #[error("Template '{name}' not found")]
+    TemplateNotFound { name: String },
+    /// Plan or step not found, but an item of the other kind has the ID
+    #[error("{source}, but {hint}")]
+    NotFoundWithHint {
+        source: Box<PlannerError>,
+        hint: IdHint,
+    },
     /// File system operation errors
     #[error("File system error at path '{path}': {source}")]
     FileSystem {
@@ -32,6 +42,22 @@ pub enum PlannerError {
     /// Invalid input validation errors
     #[error("Invalid input for field '{field}': {reason}")]
     InvalidInput { field: String, reason: String },
+    /// Deleting a plan that still has steps that are not done, without
+    /// forcing it
+    #[error(
+        "Plan {id} still has {pending_steps} step(s) that are not done; force the deletion to delete them anyway"
+    )]
+    PlanNotEmpty { id: u64, pending_steps: usize },
+    /// Archiving a plan strictly while some of its steps are not done,
+    /// without forcing it
+    #[error(
+        "Plan {id} still has {in_progress} step(s) in progress and {todo} to do; force archiving to archive it anyway"
+    )]
+    PlanHasActiveSteps {
+        id: u64,
+        in_progress: usize,
+        todo: usize,
+    },
     /// Serialization/deserialization errors
     #[error("Serialization error: {source}")]
     Serialization {
@@ -41,6 +67,59 @@ pub enum PlannerError {
     /// Configuration errors
     #[error("Configuration error: {message}")]
     Configuration { message: String },
+    /// The database was written by a newer version of beacon, with a schema
+    /// this version doesn't know
+    #[error(
+        "Database schema version {found} is newer than the latest supported version {supported}; upgrade beacon to open it"
+    )]
+    SchemaVersion { found: i64, supported: i64 },
+    /// Database encryption errors, such as a missing or wrong key
+    #[error("Encryption error: {message}")]
+    Encryption { message: String },
+    /// A database operation exceeded the planner's operation timeout
+    #[error("Database operation '{operation}' timed out")]
+    Timeout { operation: String },
+    /// The database stayed locked by another connection, even after the
+    /// operation waited and, for writes, was retried
+    #[error(
+        "Database operation '{operation}' failed: the database is locked by another connection"
+    )]
+    Busy { operation: String },
+    /// The database file the planner was using was moved or deleted
+    #[error(
+        "Database file '{}' is no longer available; restore it to continue",
+        path.display()
+    )]
+    DatabaseUnavailable { path: PathBuf },
+    /// A mutating operation was attempted on a read-only planner
+    #[error("Operation '{operation}' is not allowed on a read-only planner")]
+    ReadOnly { operation: String },
+    /// A blocking database task panicked or was cancelled
+    #[error("Background task failed: {source}")]
+    TaskJoin {
+        #[from]
+        source: tokio::task::JoinError,
+    },
+}
+
+/// What an ID that was not found as a plan or step refers to instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdHint {
+    /// A step with the ID exists in the given plan
+    StepExists { step_id: u64, plan_id: u64 },
+    /// A plan with the ID exists
+    PlanExists { plan_id: u64 },
+}
+
+impl fmt::Display for IdHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StepExists { step_id, plan_id } => {
+                write!(f, "step {step_id} exists in plan {plan_id}")
+            }
+            Self::PlanExists { plan_id } => write!(f, "plan {plan_id} exists"),
+        }
+    }
 }
 
 /// Builder for creating database errors with optional context.
@@ -102,6 +181,105 @@ impl PlannerError {
     pub fn database_error(message: &str, source: rusqlite::Error) -> Self {
         Self::database(message).with_source(source)
     }
+
+    /// Returns the SQLite result code of the underlying database error, if
+    /// any.
+    pub fn sqlite_error_code(&self) -> Option<ErrorCode> {
+        match self {
+            Self::Database { source, .. } => source.sqlite_error_code(),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the error refers to a plan or step that does not
+    /// exist.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Self::PlanNotFound { .. }
+            | Self::StepNotFound { .. }
+            | Self::TemplateNotFound { .. } => true,
+            Self::NotFoundWithHint { source, .. } => source.is_not_found(),
+            Self::Database { source, .. } => {
+                matches!(source, rusqlite::Error::QueryReturnedNoRows)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the hint at the other kind of item with the ID, if the error
+    /// is a plan or step not found error that carries one.
+    pub fn id_hint(&self) -> Option<IdHint> {
+        match self {
+            Self::NotFoundWithHint { hint, .. } => Some(*hint),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the operation violated a database constraint, such
+    /// as a uniqueness or foreign key check.
+    pub fn is_conflict(&self) -> bool {
+        self.sqlite_error_code() == Some(ErrorCode::ConstraintViolation)
+    }
+
+    /// Returns true if the database was busy or locked by another
+    /// connection; retrying the operation may succeed.
+    pub fn is_busy(&self) -> bool {
+        matches!(self, Self::Busy { .. })
+            || matches!(
+                self.sqlite_error_code(),
+                Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+            )
+    }
+
+    /// Returns true if the operation was stopped for exceeding the operation
+    /// timeout; retrying may succeed once the database is responsive again.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Self::Timeout { .. })
+    }
+
+    /// Returns true if the database file went missing while in use;
+    /// retrying may succeed once it is restored.
+    pub fn is_unavailable(&self) -> bool {
+        matches!(self, Self::DatabaseUnavailable { .. })
+    }
+
+    /// Returns true if the database file is damaged or not a database.
+    pub fn is_corruption(&self) -> bool {
+        matches!(
+            self.sqlite_error_code(),
+            Some(ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase)
+        )
+    }
+
+    /// Returns true if the caller can recover by correcting its input or
+    /// retrying, as opposed to a fatal error with the database or
+    /// environment.
+    pub fn is_recoverable(&self) -> bool {
+        matches!(
+            self,
+            Self::InvalidInput { .. } | Self::PlanNotEmpty { .. } | Self::PlanHasActiveSteps { .. }
+        ) || self.is_not_found()
+            || self.is_conflict()
+            || self.is_busy()
+            || self.is_timeout()
+            || self.is_unavailable()
+    }
+
+    /// Formats the error followed by each underlying cause not already
+    /// included in its message, separated by `": "`.
+    pub fn display_chain(&self) -> String {
+        let mut message = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(cause) = source {
+            let cause_message = cause.to_string();
+            if !message.contains(&cause_message) {
+                message.push_str(": ");
+                message.push_str(&cause_message);
+            }
+            source = cause.source();
+        }
+        message
+    }
 }
 
 /// Extension trait for Result to provide concise error mapping with
@@ -171,3 +349,134 @@ impl<T> ConfigResultExt<T> for std::result::Result<T, rusqlite::Error> {
 
 /// Result type alias for planner operations
 pub type Result<T> = std::result::Result<T, PlannerError>;
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::ffi;
+
+    use super::*;
+
+    fn sqlite_error(code: i32) -> PlannerError {
+        PlannerError::database_error(
+            "Failed to update step",
+            rusqlite::Error::SqliteFailure(ffi::Error::new(code), None),
+        )
+    }
+
+    #[test]
+    fn test_predicates_follow_sqlite_error_codes() {
+        let unique = sqlite_error(ffi::SQLITE_CONSTRAINT_UNIQUE);
+        assert!(unique.is_conflict());
+        assert!(unique.is_recoverable());
+        assert!(!unique.is_busy());
+
+        for code in [ffi::SQLITE_BUSY, ffi::SQLITE_LOCKED] {
+            let error = sqlite_error(code);
+            assert!(error.is_busy());
+            assert!(error.is_recoverable());
+            assert!(!error.is_conflict());
+        }
+
+        for code in [ffi::SQLITE_CORRUPT, ffi::SQLITE_NOTADB] {
+            let error = sqlite_error(code);
+            assert!(error.is_corruption());
+            assert!(!error.is_recoverable());
+        }
+
+        let io = sqlite_error(ffi::SQLITE_IOERR);
+        assert!(!io.is_busy() && !io.is_conflict() && !io.is_corruption());
+        assert!(!io.is_recoverable());
+        assert_eq!(io.sqlite_error_code(), Some(ErrorCode::SystemIoFailure));
+
+        let timeout = PlannerError::Timeout {
+            operation: "get_plan".to_string(),
+        };
+        assert!(timeout.is_timeout() && timeout.is_recoverable());
+        assert!(!io.is_timeout());
+
+        let busy = PlannerError::Busy {
+            operation: "add_step".to_string(),
+        };
+        assert!(busy.is_busy() && busy.is_recoverable());
+        assert!(!busy.is_timeout());
+
+        let unavailable = PlannerError::DatabaseUnavailable {
+            path: "/tmp/beacon.db".into(),
+        };
+        assert!(unavailable.is_unavailable() && unavailable.is_recoverable());
+        assert!(!unavailable.is_busy() && !busy.is_unavailable());
+    }
+
+    #[test]
+    fn test_not_found_and_input_errors_are_recoverable() {
+        assert!(PlannerError::PlanNotFound { id: 1 }.is_not_found());
+        assert!(PlannerError::StepNotFound { id: 1 }.is_not_found());
+        let template = PlannerError::TemplateNotFound {
+            name: "release".into(),
+        };
+        assert!(template.is_not_found() && template.is_recoverable());
+        assert!(
+            PlannerError::database_error(
+                "Failed to get plan",
+                rusqlite::Error::QueryReturnedNoRows
+            )
+            .is_not_found()
+        );
+
+        let input = PlannerError::invalid_input("title").with_reason("Title cannot be empty");
+        assert!(input.is_recoverable());
+        assert!(!input.is_not_found());
+
+        let not_empty = PlannerError::PlanNotEmpty {
+            id: 3,
+            pending_steps: 2,
+        };
+        assert!(not_empty.is_recoverable());
+        assert!(!not_empty.is_not_found());
+
+        let active_steps = PlannerError::PlanHasActiveSteps {
+            id: 3,
+            in_progress: 1,
+            todo: 2,
+        };
+        assert!(active_steps.is_recoverable());
+        assert_eq!(
+            active_steps.to_string(),
+            "Plan 3 still has 1 step(s) in progress and 2 to do; force archiving to archive it anyway"
+        );
+
+        let config = PlannerError::Configuration {
+            message: "bad".into(),
+        };
+        assert!(!config.is_recoverable());
+        assert_eq!(config.sqlite_error_code(), None);
+    }
+
+    #[test]
+    fn test_display_output() {
+        assert_eq!(
+            PlannerError::StepNotFound { id: 7 }.to_string(),
+            "Step with ID 7 not found"
+        );
+        assert_eq!(
+            PlannerError::invalid_input("title")
+                .with_reason("Title cannot be empty")
+                .to_string(),
+            "Invalid input for field 'title': Title cannot be empty"
+        );
+
+        let busy = sqlite_error(ffi::SQLITE_BUSY);
+        assert_eq!(busy.to_string(), "Database error: Failed to update step");
+        assert_eq!(
+            busy.display_chain(),
+            "Database error: Failed to update step: Error code 5: The database file is locked"
+        );
+
+        // Causes already part of the message are not repeated
+        let io = PlannerError::FileSystem {
+            path: "/tmp/beacon.db".into(),
+            source: std::io::Error::other("disk full"),
+        };
+        assert_eq!(io.display_chain(), io.to_string());
+    }
+}
@@ -3,24 +3,44 @@
 //! This crate provides the core business logic for managing plans and steps,
 //! including database operations, data models, and error handling.
 
+pub mod criteria;
 pub mod db;
 pub mod display;
 pub mod error;
 pub mod models;
 pub mod params;
+pub mod path;
 pub mod planner;
+pub mod quick;
+pub mod quickadd;
+pub mod references;
+pub mod uri;
 
 // Re-export commonly used types
+pub use criteria::normalize_acceptance_criteria;
 pub use db::Database;
 pub use display::{
-    CreateResult, DeleteResult, LocalDateTime, OperationStatus, PlanSummaries, Steps, UpdateResult,
+    CreateResult, DeleteResult, DuplicateGroups, Events, ListedSteps, LocalDateTime,
+    OperationStatus, PlanSummaries, SettingValues, StepOutlines, Steps, StepsByPlan, Templates,
+    TrashedSteps, UpdateResult,
 };
-pub use error::{PlannerError, Result};
+pub use error::{IdHint, PlannerError, Result};
 pub use models::{
-    CompletionFilter, Plan, PlanFilter, PlanStatus, PlanSummary, Step, StepStatus,
-    UpdateStepRequest,
+    AllowedTransitions, Changelog, ClaimOutcome, CompletionFilter, ConflictPolicy,
+    CreateStepRequest, DirectoryActivity, DuplicateGroup, DuplicateScope, DuplicateStep, Event,
+    EventEntity, EventKind, Explanation, FilterStage, ImportOutcome, MaintenanceReport,
+    MergeOptions, Momentum, Page, Plan, PlanFilter, PlanFilterBuilder, PlanReport, PlanStatus,
+    PlanSummary, PlannerStats, PromptBadge, ReferenceKind, SettingKey, SettingSource, SettingValue,
+    Settings, StatusLabels, Step, StepChange, StepNote, StepOutline, StepReference,
+    StepStateMachine, StepStatus, StepStatusCounts, StepTransition, StepVerification, Template,
+    TemplateStep, TrashedStep, Trend, UpdatePlanRequest, UpdateStepRequest,
 };
 pub use params::{
-    CreatePlan, Id, InsertStep, ListPlans, SearchPlans, StepCreate, SwapSteps, UpdateStep,
+    ApplyTemplate, ArchivePlan, CreatePlan, CreateTemplate, DuplicateStepsReport, FindSteps,
+    GetConfig, GetPlanReport, GetStats, Id, InsertStep, ListEvents, ListPlans, ListSteps,
+    ListStepsByStatus, MAX_DESCRIPTION_LEN, MAX_TITLE_LEN, MergePlans, MoveStep, MoveStepToPlan,
+    PlanChangelog, ReassignDirectory, RemoveStep, SearchPlans, SetConfig, SetStepPhase, ShowPlan,
+    StepCreate, SwapSteps, UpdatePlan, UpdateStep, VerifyStep,
 };
 pub use planner::{Planner, PlannerBuilder};
+pub use uri::{BeaconUri, UriTarget};
@@ -0,0 +1,35 @@
+//! A one-line summary of the work in a directory, for shell prompts.
+
+use super::PlanSummary;
+
+/// Counts of the active plans in a directory and their remaining work.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PromptBadge {
+    /// Active plans in the directory or below it
+    pub plans: u32,
+    /// Steps of those plans that are not done yet
+    pub pending_steps: u32,
+    /// Plans with a step in progress for longer than the warning threshold
+    pub stale_plans: u32,
+}
+
+impl PromptBadge {
+    /// Counts the plans and steps of `summaries`.
+    pub fn from_summaries<'a>(summaries: impl IntoIterator<Item = &'a PlanSummary>) -> Self {
+        summaries
+            .into_iter()
+            .fold(Self::default(), |mut badge, summary| {
+                badge.plans += 1;
+                badge.pending_steps += summary.pending_steps;
+                if summary.stale_in_progress_age().is_some() {
+                    badge.stale_plans += 1;
+                }
+                badge
+            })
+    }
+
+    /// Returns true if there are no active plans.
+    pub fn is_empty(&self) -> bool {
+        self.plans == 0
+    }
+}
@@ -0,0 +1,208 @@
+//! Changelog of a plan reconstructed from its timestamps.
+
+use std::cmp::Reverse;
+
+use jiff::{Timestamp, ToSpan, Zoned, civil::Date, tz::TimeZone};
+
+use super::{Plan, PlanStatus, StepStatus};
+use crate::error::{PlannerError, Result};
+
+/// Kind of change recorded in a [`Changelog`].
+///
+/// Variants are ordered as they happen to an item, which orders changes
+/// sharing a timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    PlanCreated,
+    StepCreated,
+    StepUpdated,
+    StepClaimed,
+    StepCompleted,
+    StepVerified,
+    PlanUpdated,
+    PlanArchived,
+}
+
+/// A single change to a plan or one of its steps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// When the change happened
+    pub at: Timestamp,
+    pub kind: ChangeKind,
+    /// The step changed, or `None` for plan-level changes
+    pub step_id: Option<u64>,
+    /// Title of the step or plan changed
+    pub title: String,
+    /// Extra context, such as the first line of a completed step's result
+    pub detail: Option<String>,
+}
+
+/// Changes to a plan, newest first.
+///
+/// There is no history of changes, so the changelog is reconstructed from
+/// each step's creation, latest update and verification timestamps. Earlier
+/// changes to a step, reorders and removed steps are not known.
+#[derive(Debug, Clone)]
+pub struct Changelog {
+    pub plan_id: u64,
+    pub plan_title: String,
+    /// Start of the window, if the changelog is limited to recent changes
+    pub since: Option<Timestamp>,
+    pub changes: Vec<Change>,
+    /// Time zone whose days the changes are grouped by when displayed
+    pub time_zone: TimeZone,
+}
+
+impl Changelog {
+    /// Reconstructs the changelog of `plan` from the timestamps of the plan
+    /// and its steps, keeping only changes at or after `since`.
+    pub fn from_plan(plan: &Plan, since: Option<Timestamp>) -> Self {
+        let mut changes = Vec::new();
+        let mut change = |at, kind, step_id, title: &str, detail: Option<String>| {
+            changes.push(Change {
+                at,
+                kind,
+                step_id,
+                title: title.to_string(),
+                detail,
+            });
+        };
+
+        change(
+            plan.created_at,
+            ChangeKind::PlanCreated,
+            None,
+            &plan.title,
+            None,
+        );
+        for step in &plan.steps {
+            let id = Some(step.id);
+            change(
+                step.created_at,
+                ChangeKind::StepCreated,
+                id,
+                &step.title,
+                None,
+            );
+            if step.updated_at > step.created_at {
+                let (kind, detail) = match step.status {
+                    StepStatus::Done => (
+                        ChangeKind::StepCompleted,
+                        step.result
+                            .as_deref()
+                            .and_then(|result| result.lines().next())
+                            .map(String::from),
+                    ),
+                    StepStatus::InProgress => (ChangeKind::StepClaimed, None),
+                    StepStatus::Todo => (ChangeKind::StepUpdated, None),
+                    StepStatus::Blocked => (ChangeKind::StepUpdated, step.blocked_reason.clone()),
+                };
+                change(step.updated_at, kind, id, &step.title, detail);
+            }
+            if let Some(verification) = &step.verification {
+                let by = Some(format!("by {}", verification.verifier));
+                change(
+                    verification.verified_at,
+                    ChangeKind::StepVerified,
+                    id,
+                    &step.title,
+                    by,
+                );
+            }
+        }
+
+        // The plan is touched by every step change, so its own update time
+        // only stands for a plan-level change when no step changed then
+        if plan.status == PlanStatus::Archived {
+            change(
+                plan.updated_at,
+                ChangeKind::PlanArchived,
+                None,
+                &plan.title,
+                None,
+            );
+        } else if plan.updated_at > plan.created_at
+            && !plan.steps.iter().any(|step| {
+                step.created_at == plan.updated_at || step.updated_at == plan.updated_at
+            })
+        {
+            change(
+                plan.updated_at,
+                ChangeKind::PlanUpdated,
+                None,
+                &plan.title,
+                None,
+            );
+        }
+
+        changes.retain(|change| since.is_none_or(|since| change.at >= since));
+        changes.sort_by_key(|change| Reverse((change.at, change.kind, change.step_id)));
+
+        Self {
+            plan_id: plan.id,
+            plan_title: plan.title.clone(),
+            since,
+            changes,
+            time_zone: TimeZone::system(),
+        }
+    }
+
+    /// Groups changes by day in the changelog's time zone.
+    pub fn days(&self) -> Vec<(Date, Vec<&Change>)> {
+        let mut days: Vec<(Date, Vec<&Change>)> = Vec::new();
+        for change in &self.changes {
+            let date = change.at.to_zoned(self.time_zone.clone()).date();
+            match days.last_mut() {
+                Some((day, changes)) if *day == date => changes.push(change),
+                _ => days.push((date, vec![change])),
+            }
+        }
+        days
+    }
+
+    /// Sets the time zone whose days changes are grouped by.
+    pub fn with_time_zone(mut self, time_zone: TimeZone) -> Self {
+        self.time_zone = time_zone;
+        self
+    }
+}
+
+/// Parses the start of a changelog window: an age such as `30d`, `2w` or
+/// `12h` before `now`, or a date such as `2024-11-01`, taken as the start of
+/// that day in `now`'s time zone.
+///
+/// # Errors
+///
+/// Returns `PlannerError::InvalidInput` if the text is neither.
+pub fn parse_since(text: &str, now: &Zoned) -> Result<Timestamp> {
+    let invalid = || {
+        PlannerError::invalid_input("since").with_reason(format!(
+            "Invalid value '{text}': expected an age such as 30d, 2w or 12h, or a date such as 2024-11-01"
+        ))
+    };
+
+    let text = text.trim();
+    if let Ok(date) = text.parse::<Date>() {
+        return date
+            .to_zoned(now.time_zone().clone())
+            .map(|start| start.timestamp())
+            .map_err(|_| invalid());
+    }
+
+    let unit = text.chars().last().ok_or_else(invalid)?;
+    let count: i64 = text[..text.len() - unit.len_utf8()]
+        .parse()
+        .map_err(|_| invalid())?;
+    if count < 0 {
+        return Err(invalid());
+    }
+    let span = match unit {
+        'h' => count.hours(),
+        'd' => count.days(),
+        'w' => count.weeks(),
+        _ => return Err(invalid()),
+    };
+    now.checked_sub(span)
+        .map(|start| start.timestamp())
+        .map_err(|_| invalid())
+}
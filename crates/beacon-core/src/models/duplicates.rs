@@ -0,0 +1,96 @@
+//! Open steps tracked more than once under the same title.
+
+use std::collections::BTreeMap;
+
+use super::StepStatus;
+
+/// Which plans to look for duplicate steps in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DuplicateScope {
+    /// All active plans
+    #[default]
+    AllPlans,
+    /// Active plans in a directory or below it
+    Directory(String),
+}
+
+impl From<&crate::params::DuplicateStepsReport> for DuplicateScope {
+    fn from(params: &crate::params::DuplicateStepsReport) -> Self {
+        match &params.directory {
+            Some(directory) => Self::Directory(directory.clone()),
+            None => Self::AllPlans,
+        }
+    }
+}
+
+/// A step in a [`DuplicateGroup`], with the plan it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateStep {
+    pub step_id: u64,
+    /// The step's title as written
+    pub title: String,
+    pub status: StepStatus,
+    pub plan_id: u64,
+    pub plan_title: String,
+    pub plan_directory: Option<String>,
+}
+
+/// Open steps whose titles match once case, whitespace and punctuation are
+/// ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// The normalized title shared by the steps
+    pub key: String,
+    /// The steps, in plan order and then step order
+    pub steps: Vec<DuplicateStep>,
+}
+
+impl DuplicateGroup {
+    /// Number of distinct plans the group's steps belong to.
+    pub fn plan_count(&self) -> usize {
+        let mut plans: Vec<u64> = self.steps.iter().map(|step| step.plan_id).collect();
+        plans.dedup();
+        plans.len()
+    }
+
+    /// Groups candidate steps by their normalized title, keeping groups of
+    /// two or more steps.
+    ///
+    /// Each candidate comes with a title key that the database already
+    /// lowercased and trimmed; it is normalized further with
+    /// [`normalize_title`]. Steps keep the order they are given in within
+    /// their group. Groups are ordered by size, largest first, then by key.
+    pub fn group(candidates: impl IntoIterator<Item = (String, DuplicateStep)>) -> Vec<Self> {
+        let mut groups: BTreeMap<String, Vec<DuplicateStep>> = BTreeMap::new();
+        for (key, step) in candidates {
+            let key = normalize_title(&key);
+            if !key.is_empty() {
+                groups.entry(key).or_default().push(step);
+            }
+        }
+
+        let mut groups: Vec<Self> = groups
+            .into_iter()
+            .filter(|(_, steps)| steps.len() > 1)
+            .map(|(key, steps)| Self { key, steps })
+            .collect();
+        groups.sort_by(|a, b| b.steps.len().cmp(&a.steps.len()).then(a.key.cmp(&b.key)));
+        groups
+    }
+}
+
+/// Normalizes a step title for comparison: lowercase, with apostrophes
+/// dropped, other punctuation treated as whitespace, and whitespace runs
+/// collapsed to single spaces.
+///
+/// `"Upgrade to Rust 1.80!"` and `"upgrade  to rust 1.80"` both become
+/// `"upgrade to rust 1 80"`.
+pub fn normalize_title(title: &str) -> String {
+    title
+        .to_lowercase()
+        .replace(['\'', '’'], "")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,144 @@
+//! Events recording changes to plans and steps.
+
+use std::str::FromStr;
+
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+
+/// Kind of item an [`Event`] is about.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventEntity {
+    Plan,
+    Step,
+}
+
+impl EventEntity {
+    /// Convert to database string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventEntity::Plan => "plan",
+            EventEntity::Step => "step",
+        }
+    }
+}
+
+impl FromStr for EventEntity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plan" => Ok(EventEntity::Plan),
+            "step" => Ok(EventEntity::Step),
+            _ => Err(format!("Invalid event entity: {s}")),
+        }
+    }
+}
+
+/// What happened to the item an [`Event`] is about.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    /// A plan was created
+    Created,
+    /// A plan was archived, by hand or when its last step was done
+    Archived,
+    /// An archived plan was made active again
+    Unarchived,
+    /// A plan was deleted with its steps
+    Deleted,
+    /// A plan was merged into another one, which took over its steps, and
+    /// archived
+    Merged,
+    /// A step was added to a plan
+    Added,
+    /// A plan's or step's details, notes or status were changed, or a
+    /// step's dependencies
+    Updated,
+    /// A step was claimed for work
+    Claimed,
+    /// A claimed step was put back to todo
+    Released,
+    /// A done step's completion was confirmed by a verifier
+    Verified,
+    /// A step was moved to the trash
+    Removed,
+    /// A step was taken out of the trash
+    Restored,
+    /// A step in the trash was deleted for good
+    Purged,
+    /// A step traded places with another step of its plan
+    Swapped,
+    /// A step was moved to another position in its plan
+    Moved,
+}
+
+impl EventKind {
+    /// Convert to database string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::Created => "created",
+            EventKind::Archived => "archived",
+            EventKind::Unarchived => "unarchived",
+            EventKind::Deleted => "deleted",
+            EventKind::Merged => "merged",
+            EventKind::Added => "added",
+            EventKind::Updated => "updated",
+            EventKind::Claimed => "claimed",
+            EventKind::Released => "released",
+            EventKind::Verified => "verified",
+            EventKind::Removed => "removed",
+            EventKind::Restored => "restored",
+            EventKind::Purged => "purged",
+            EventKind::Swapped => "swapped",
+            EventKind::Moved => "moved",
+        }
+    }
+}
+
+impl FromStr for EventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "created" => Ok(EventKind::Created),
+            "archived" => Ok(EventKind::Archived),
+            "unarchived" => Ok(EventKind::Unarchived),
+            "deleted" => Ok(EventKind::Deleted),
+            "merged" => Ok(EventKind::Merged),
+            "added" => Ok(EventKind::Added),
+            "updated" => Ok(EventKind::Updated),
+            "claimed" => Ok(EventKind::Claimed),
+            "released" => Ok(EventKind::Released),
+            "verified" => Ok(EventKind::Verified),
+            "removed" => Ok(EventKind::Removed),
+            "restored" => Ok(EventKind::Restored),
+            "purged" => Ok(EventKind::Purged),
+            "swapped" => Ok(EventKind::Swapped),
+            "moved" => Ok(EventKind::Moved),
+            _ => Err(format!("Invalid event kind: {s}")),
+        }
+    }
+}
+
+/// A change to a plan or step, recorded in the same transaction as the
+/// change itself.
+///
+/// Events are only ever appended, so their IDs increase in the order the
+/// changes were made.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Event {
+    /// Unique identifier for the event
+    pub id: u64,
+    pub entity_type: EventEntity,
+    /// ID of the plan or step changed
+    pub entity_id: u64,
+    pub kind: EventKind,
+    /// The plan or step as serialized JSON: as it was after the change, or
+    /// just before it was deleted or removed. Plans are recorded without
+    /// their steps, which have events of their own, and purged steps as
+    /// they were listed in the trash.
+    pub payload: serde_json::Value,
+    /// When the change happened
+    pub created_at: Timestamp,
+}
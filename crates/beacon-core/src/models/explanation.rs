@@ -0,0 +1,31 @@
+//! Explanation of how a plan listing was computed.
+
+use std::path::PathBuf;
+
+use super::PlanFilter;
+
+/// Order plan listings are returned in, as applied by the database. Plans
+/// created in the same instant are ordered by ID, so pages of a listing
+/// never overlap.
+pub const PLAN_LISTING_ORDER: &str = "created_at DESC, id DESC";
+
+/// A stage of filtering a plan listing and the plans that remained after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterStage {
+    /// What the stage filtered on, e.g. `directory`
+    pub name: String,
+    /// Plans remaining after this and all earlier stages
+    pub plans: usize,
+}
+
+/// How a plan listing was computed, for debugging why a plan does or doesn't
+/// show up.
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    /// Database file the plans were read from
+    pub database_path: PathBuf,
+    /// The effective filter, with directories already canonicalized
+    pub filter: Option<PlanFilter>,
+    /// Stages of filtering in the order they were applied
+    pub stages: Vec<FilterStage>,
+}
@@ -1,28 +1,109 @@
 //! Filter types for querying plans and steps.
 
-use jiff::Timestamp;
+use jiff::{Timestamp, civil::Date};
 
 use super::PlanStatus;
+use crate::error::{PlannerError, Result};
 
 /// Filter options for querying plans.
+///
+/// Build filters with [`PlanFilter::builder`], which validates the
+/// combination of options. The fields are deprecated since 0.1.0 and stay
+/// public, so that code setting them keeps compiling, until they become
+/// private in the next release; read them through the accessor methods
+/// instead.
 #[derive(Debug, Clone)]
+#[allow(deprecated)]
 pub struct PlanFilter {
     /// Filter by plan title (case-insensitive partial match)
+    #[deprecated(
+        since = "0.1.0",
+        note = "use `PlanFilter::builder().title_contains(..)` and `title_contains()`"
+    )]
     pub title_contains: Option<String>,
-    /// Filter by directory path (exact match or prefix match)
+    /// Filter by directory path (exact match or subdirectory match)
+    #[deprecated(
+        since = "0.1.0",
+        note = "use `PlanFilter::builder().directory(..)` and `directories()`"
+    )]
     pub directory: Option<String>,
     /// Filter by creation date range
+    #[deprecated(
+        since = "0.1.0",
+        note = "use `PlanFilter::builder().created_after(..)` and `created_after()`"
+    )]
     pub created_after: Option<Timestamp>,
+    #[deprecated(
+        since = "0.1.0",
+        note = "use `PlanFilter::builder().created_before(..)` and `created_before()`"
+    )]
     pub created_before: Option<Timestamp>,
     /// Filter by completion status
+    #[deprecated(
+        since = "0.1.0",
+        note = "use `PlanFilter::builder().completion(..)` and `completion_status()`"
+    )]
     pub completion_status: Option<CompletionFilter>,
     /// Filter by plan status (active/archived)
     /// If None, defaults to showing only active plans
+    #[deprecated(
+        since = "0.1.0",
+        note = "use `PlanFilter::builder().status(..)` and `status()`"
+    )]
     pub status: Option<PlanStatus>,
     /// Show all plans regardless of status
+    #[deprecated(
+        since = "0.1.0",
+        note = "use `PlanFilter::builder().include_archived(..)` and `include_archived()`"
+    )]
     pub include_archived: bool,
+    /// Only plans that are children of this plan
+    #[deprecated(
+        since = "0.1.0",
+        note = "use `PlanFilter::builder().parent_plan_id(..)` and `parent_plan_id()`"
+    )]
+    pub parent_plan_id: Option<u64>,
+    /// Only plans in any of these directories (exact match or subdirectory
+    /// match)
+    #[deprecated(
+        since = "0.1.0",
+        note = "use `PlanFilter::builder().directory_any(..)` and `directories()`"
+    )]
+    pub directory_any: Vec<String>,
+    /// Only plans updated at or after this time
+    #[deprecated(
+        since = "0.1.0",
+        note = "use `PlanFilter::builder().updated_after(..)` and `updated_after()`"
+    )]
+    pub updated_after: Option<Timestamp>,
+    /// Only plans due on or before this date
+    #[deprecated(
+        since = "0.1.0",
+        note = "use `PlanFilter::builder().due_before(..)` and `due_before()`"
+    )]
+    pub due_before: Option<Date>,
+    /// Maximum number of plans to return
+    #[deprecated(
+        since = "0.1.0",
+        note = "use `PlanFilter::builder().limit(..)` and `limit()`"
+    )]
+    pub limit: Option<u32>,
+    /// Number of matching plans to skip before the first one returned
+    #[deprecated(
+        since = "0.1.0",
+        note = "use `PlanFilter::builder().offset(..)` and `offset()`"
+    )]
+    pub offset: Option<u32>,
+    /// Whether plans in subdirectories of the filtered directories match,
+    /// rather than only plans in those exact directories
+    #[deprecated(
+        since = "0.1.0",
+        note = "use `PlanFilter::builder().include_subdirectories(..)` and `include_subdirectories()`"
+    )]
+    pub include_subdirectories: bool,
 }
 
+#[allow(deprecated)]
 impl PlanFilter {
     /// Create a new filter builder with default values.
     pub const fn new() -> Self {
@@ -34,9 +115,22 @@ impl PlanFilter {
             completion_status: None,
             status: None,
             include_archived: false,
+            parent_plan_id: None,
+            directory_any: Vec::new(),
+            updated_after: None,
+            due_before: None,
+            limit: None,
+            offset: None,
+            include_subdirectories: true,
         }
     }
 
+    /// Starts building a filter whose options are validated by
+    /// [`PlanFilterBuilder::build`].
+    pub fn builder() -> PlanFilterBuilder {
+        PlanFilterBuilder::default()
+    }
+
     /// Set directory filter.
     pub fn directory(mut self, directory: String) -> Self {
         self.directory = Some(directory);
@@ -58,6 +152,82 @@ impl PlanFilter {
     pub fn for_directory(directory: String, archived: bool) -> Self {
         Self::new().directory(directory).archived(archived)
     }
+
+    /// Title text plans must contain.
+    pub fn title_contains(&self) -> Option<&str> {
+        self.title_contains.as_deref()
+    }
+
+    /// Directories plans must be in, any one of which matches. Empty if
+    /// plans are not filtered by directory.
+    pub fn directories(&self) -> Vec<&str> {
+        self.directory
+            .iter()
+            .chain(&self.directory_any)
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Whether plans below the filtered directories match as well as those
+    /// in them.
+    pub fn include_subdirectories(&self) -> bool {
+        self.include_subdirectories
+    }
+
+    /// Earliest creation time of matching plans.
+    pub fn created_after(&self) -> Option<Timestamp> {
+        self.created_after
+    }
+
+    /// Latest creation time of matching plans.
+    pub fn created_before(&self) -> Option<Timestamp> {
+        self.created_before
+    }
+
+    /// Earliest last update time of matching plans.
+    pub fn updated_after(&self) -> Option<Timestamp> {
+        self.updated_after
+    }
+
+    /// Latest due date of matching plans; plans without one never match.
+    pub fn due_before(&self) -> Option<Date> {
+        self.due_before
+    }
+
+    /// Completion status of matching plans.
+    pub fn completion_status(&self) -> Option<&CompletionFilter> {
+        self.completion_status.as_ref()
+    }
+
+    /// Status of matching plans.
+    pub fn status(&self) -> Option<PlanStatus> {
+        self.status
+    }
+
+    /// Whether archived plans are included.
+    pub fn include_archived(&self) -> bool {
+        self.include_archived
+    }
+
+    /// Parent of matching plans.
+    pub fn parent_plan_id(&self) -> Option<u64> {
+        self.parent_plan_id
+    }
+
+    /// Maximum number of plans listed; all matching plans if `None`.
+    pub fn limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    /// Number of matching plans skipped before the first one listed.
+    pub fn offset(&self) -> Option<u32> {
+        self.offset
+    }
+
+    /// Whether only a page of the matching plans is listed.
+    pub fn is_paged(&self) -> bool {
+        self.limit.is_some() || self.offset.is_some()
+    }
 }
 
 impl Default for PlanFilter {
@@ -66,6 +236,155 @@ impl Default for PlanFilter {
     }
 }
 
+/// Builder for [`PlanFilter`].
+///
+/// # Examples
+///
+/// ```
+/// use beacon_core::{CompletionFilter, PlanFilter};
+///
+/// let filter = PlanFilter::builder()
+///     .directory_any(["/work/api", "/work/web"])
+///     .completion(CompletionFilter::Incomplete)
+///     .build()?;
+/// assert_eq!(filter.directories(), ["/work/api", "/work/web"]);
+/// # Ok::<(), beacon_core::PlannerError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PlanFilterBuilder {
+    filter: PlanFilter,
+}
+
+#[allow(deprecated)]
+impl PlanFilterBuilder {
+    /// Only plans whose title contains `text`, ignoring case.
+    pub fn title_contains(mut self, text: impl Into<String>) -> Self {
+        self.filter.title_contains = Some(text.into());
+        self
+    }
+
+    /// Only plans in `directory` or below it. Several directories match
+    /// plans in any of them.
+    pub fn directory(mut self, directory: impl Into<String>) -> Self {
+        self.filter.directory_any.push(directory.into());
+        self
+    }
+
+    /// Only plans in any of `directories` or below them.
+    pub fn directory_any<I, S>(mut self, directories: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.filter
+            .directory_any
+            .extend(directories.into_iter().map(Into::into));
+        self
+    }
+
+    /// Whether plans below the filtered directories match; only plans in
+    /// exactly those directories do if `include` is unset. Included by
+    /// default.
+    pub fn include_subdirectories(mut self, include: bool) -> Self {
+        self.filter.include_subdirectories = include;
+        self
+    }
+
+    /// Only plans created at or after `time`.
+    pub fn created_after(mut self, time: Timestamp) -> Self {
+        self.filter.created_after = Some(time);
+        self
+    }
+
+    /// Only plans created at or before `time`.
+    pub fn created_before(mut self, time: Timestamp) -> Self {
+        self.filter.created_before = Some(time);
+        self
+    }
+
+    /// Only plans updated at or after `time`.
+    pub fn updated_after(mut self, time: Timestamp) -> Self {
+        self.filter.updated_after = Some(time);
+        self
+    }
+
+    /// Only plans due on or before `date`.
+    pub fn due_before(mut self, date: Date) -> Self {
+        self.filter.due_before = Some(date);
+        self
+    }
+
+    /// Only plans with the given completion status.
+    pub fn completion(mut self, completion: CompletionFilter) -> Self {
+        self.filter.completion_status = Some(completion);
+        self
+    }
+
+    /// Only plans with the given status.
+    pub fn status(mut self, status: PlanStatus) -> Self {
+        self.filter.status = Some(status);
+        self
+    }
+
+    /// Whether to include archived plans; only active plans are included by
+    /// default.
+    pub fn include_archived(mut self, include: bool) -> Self {
+        self.filter.include_archived = include;
+        self
+    }
+
+    /// Only archived plans if `archived` is set, otherwise only active plans.
+    pub fn archived(mut self, archived: bool) -> Self {
+        self.filter = self.filter.archived(archived);
+        self
+    }
+
+    /// Only child plans of the given plan.
+    pub fn parent_plan_id(mut self, parent_plan_id: u64) -> Self {
+        self.filter.parent_plan_id = Some(parent_plan_id);
+        self
+    }
+
+    /// List at most `limit` plans.
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.filter.limit = Some(limit);
+        self
+    }
+
+    /// Skip the first `offset` matching plans.
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.filter.offset = Some(offset);
+        self
+    }
+
+    /// Validates the options and builds the filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if archived plans are requested
+    /// without including them, the creation range is empty, or a directory
+    /// is empty.
+    pub fn build(self) -> Result<PlanFilter> {
+        let filter = self.filter;
+        if filter.status == Some(PlanStatus::Archived) && !filter.include_archived {
+            return Err(PlannerError::invalid_input("status")
+                .with_reason("Archived plans are only listed when archived plans are included"));
+        }
+        if let (Some(after), Some(before)) = (filter.created_after, filter.created_before)
+            && after > before
+        {
+            return Err(PlannerError::invalid_input("created_after")
+                .with_reason(format!("{after} is after created_before {before}")));
+        }
+        if filter.directory_any.iter().any(|dir| dir.trim().is_empty()) {
+            return Err(
+                PlannerError::invalid_input("directory").with_reason("Directory cannot be empty")
+            );
+        }
+        Ok(filter)
+    }
+}
+
 /// Completion status filter options.
 #[derive(Debug, Clone, PartialEq)]
 pub enum CompletionFilter {
@@ -77,8 +396,21 @@ pub enum CompletionFilter {
     Empty,
 }
 
-impl From<&crate::params::ListPlans> for PlanFilter {
-    fn from(params: &crate::params::ListPlans) -> Self {
-        Self::new().archived(params.archived)
+impl TryFrom<&crate::params::ListPlans> for PlanFilter {
+    type Error = PlannerError;
+
+    #[allow(deprecated)]
+    fn try_from(params: &crate::params::ListPlans) -> Result<Self> {
+        let mut filter = Self::new().archived(params.archived);
+        filter.parent_plan_id = params.children_of;
+        filter.completion_status = params.validate()?;
+        filter.due_before = params
+            .due_before
+            .as_deref()
+            .map(|date| super::parse_date("due_before", date))
+            .transpose()?;
+        filter.limit = params.limit;
+        filter.offset = params.offset;
+        Ok(filter)
     }
 }
@@ -0,0 +1,100 @@
+//! Importing plans exported from another database.
+
+use std::{fmt, str::FromStr};
+
+use jiff::civil::Date;
+
+use super::Plan;
+use crate::error::PlannerError;
+
+/// What to do with an imported plan when an active plan with the same title
+/// already exists in the same directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the existing plan and leave the imported one out
+    #[default]
+    Skip,
+    /// Import the plan as a new one, its title suffixed with the import date
+    DuplicateWithSuffix,
+    /// Append the imported steps the existing plan doesn't have yet to it
+    Merge,
+}
+
+impl ConflictPolicy {
+    /// All policies, in the order they are listed.
+    pub const ALL: [Self; 3] = [Self::Skip, Self::DuplicateWithSuffix, Self::Merge];
+
+    /// Name of the policy as given on the command line.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::DuplicateWithSuffix => "duplicate",
+            Self::Merge => "merge",
+        }
+    }
+}
+
+impl fmt::Display for ConflictPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = PlannerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|policy| policy.as_str() == s.trim().to_lowercase())
+            .ok_or_else(|| {
+                PlannerError::invalid_input("on_conflict").with_reason(format!(
+                    "Unknown conflict policy '{s}'. Expected 'skip', 'duplicate' or 'merge'"
+                ))
+            })
+    }
+}
+
+/// Title of the `number`th plan imported next to an existing one under
+/// [`ConflictPolicy::DuplicateWithSuffix`] on `date`, e.g.
+/// "Release (imported 2025-01-31)" for the first and
+/// "Release (imported 2025-01-31 #2)" for the second.
+pub fn imported_title(title: &str, date: Date, number: u32) -> String {
+    match number {
+        0 | 1 => format!("{title} (imported {date})"),
+        _ => format!("{title} (imported {date} #{number})"),
+    }
+}
+
+/// What importing a plan did.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportOutcome {
+    /// No active plan had the same title and directory, so the plan was
+    /// created as it was
+    Created(Box<Plan>),
+    /// The plan was created next to the conflicting one with a suffixed
+    /// title
+    Duplicated(Box<Plan>),
+    /// The steps missing from the conflicting plan were appended to it
+    Merged {
+        plan: Box<Plan>,
+        /// Titles of the steps appended, in order
+        added: Vec<String>,
+        /// Titles of the imported steps the plan already had
+        skipped: Vec<String>,
+    },
+    /// The conflicting plan was kept and nothing was imported
+    Skipped { existing_id: u64 },
+}
+
+impl ImportOutcome {
+    /// ID of the plan the import created or changed, if any.
+    pub fn plan_id(&self) -> Option<u64> {
+        match self {
+            Self::Created(plan) | Self::Duplicated(plan) | Self::Merged { plan, .. } => {
+                Some(plan.id)
+            }
+            Self::Skipped { .. } => None,
+        }
+    }
+}
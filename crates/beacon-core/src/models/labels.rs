@@ -0,0 +1,103 @@
+//! Per-plan display names for step statuses.
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::StepStatus;
+
+/// Maximum length of a status label, in characters.
+pub const MAX_STATUS_LABEL_LEN: usize = 24;
+
+/// Team-specific names for step statuses, e.g. "doing" for in progress.
+///
+/// Labels only change how statuses are rendered. Stored statuses, status
+/// parsing and serialized steps keep the canonical names. Serialized as an
+/// object keyed by canonical status name, e.g.
+/// `{"inprogress": "doing", "done": "shipped"}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct StatusLabels {
+    /// Label shown instead of "Todo"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub todo: Option<String>,
+    /// Label shown instead of "In Progress"
+    #[serde(
+        default,
+        rename = "inprogress",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub in_progress: Option<String>,
+    /// Label shown instead of "Done"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub done: Option<String>,
+    /// Label shown instead of "Blocked"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocked: Option<String>,
+}
+
+impl StatusLabels {
+    /// Whether no status has a custom label.
+    pub fn is_empty(&self) -> bool {
+        self.todo.is_none()
+            && self.in_progress.is_none()
+            && self.done.is_none()
+            && self.blocked.is_none()
+    }
+
+    /// The custom label for `status`, if any.
+    pub fn get(&self, status: StepStatus) -> Option<&str> {
+        match status {
+            StepStatus::Todo => self.todo.as_deref(),
+            StepStatus::InProgress => self.in_progress.as_deref(),
+            StepStatus::Done => self.done.as_deref(),
+            StepStatus::Blocked => self.blocked.as_deref(),
+        }
+    }
+
+    /// Sets or clears the custom label for `status`.
+    pub fn set(&mut self, status: StepStatus, label: Option<String>) {
+        match status {
+            StepStatus::Todo => self.todo = label,
+            StepStatus::InProgress => self.in_progress = label,
+            StepStatus::Done => self.done = label,
+            StepStatus::Blocked => self.blocked = label,
+        }
+    }
+
+    /// The name to display for `status`: its custom label, or the default
+    /// name when it has none.
+    pub fn label(&self, status: StepStatus) -> &str {
+        self.get(status).unwrap_or(status.label())
+    }
+
+    /// Like [`StepStatus::with_icon`], with the custom label in place of
+    /// the default name.
+    pub fn with_icon(&self, status: StepStatus) -> String {
+        format!("{} {}", status.icon(), self.label(status))
+    }
+
+    /// Checks that every label is a short, non-empty, single line.
+    ///
+    /// Returns the reason for the first invalid label.
+    pub fn validate(&self) -> Result<(), String> {
+        StepStatus::ALL
+            .into_iter()
+            .filter_map(|status| self.get(status).map(|label| (status, label)))
+            .try_for_each(|(status, label)| {
+                let status = status.as_str();
+                if label.trim().is_empty() {
+                    Err(format!("Label for '{status}' cannot be empty"))
+                } else if label.contains(['\n', '\r']) {
+                    Err(format!("Label for '{status}' must be a single line"))
+                } else if label.chars().count() > MAX_STATUS_LABEL_LEN {
+                    Err(format!(
+                        "Label for '{status}' must be at most {MAX_STATUS_LABEL_LEN} characters"
+                    ))
+                } else {
+                    Ok(())
+                }
+            })
+    }
+}
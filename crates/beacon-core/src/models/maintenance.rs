@@ -0,0 +1,24 @@
+//! Results of database maintenance.
+
+/// What running maintenance on the database did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaintenanceReport {
+    /// Size of the database in bytes before it was vacuumed
+    pub size_before: u64,
+    /// Size of the database in bytes after it was vacuumed
+    pub size_after: u64,
+    /// Problems found by the integrity check, empty if there were none
+    pub integrity_problems: Vec<String>,
+}
+
+impl MaintenanceReport {
+    /// Whether the integrity check found the database sound.
+    pub fn is_intact(&self) -> bool {
+        self.integrity_problems.is_empty()
+    }
+
+    /// Bytes freed by vacuuming, zero if the database didn't shrink.
+    pub fn bytes_reclaimed(&self) -> u64 {
+        self.size_before.saturating_sub(self.size_after)
+    }
+}
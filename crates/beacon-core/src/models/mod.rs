@@ -4,20 +4,57 @@
 //! in the Beacon task planning system. Display implementations for these models
 //! are located in [`crate::display::models`].
 
+pub mod badge;
+pub mod changelog;
+pub mod duplicates;
+pub mod event;
+pub mod explanation;
 pub mod filters;
+pub mod import;
+pub mod labels;
+pub mod maintenance;
+pub mod momentum;
+pub mod page;
 pub mod plan;
+pub mod reference;
+pub mod report;
 pub mod requests;
+pub mod settings;
+pub mod stats;
 pub mod status;
 pub mod step;
 pub mod summary;
+pub mod template;
+pub mod transitions;
 
 #[cfg(test)]
 mod tests;
 
 // Re-export all public types at the models level for backward compatibility
-pub use filters::{CompletionFilter, PlanFilter};
-pub use plan::Plan;
-pub use requests::UpdateStepRequest;
+pub use badge::PromptBadge;
+pub use changelog::{Change, ChangeKind, Changelog};
+pub use duplicates::{DuplicateGroup, DuplicateScope, DuplicateStep};
+pub use event::{Event, EventEntity, EventKind};
+pub use explanation::{Explanation, FilterStage, PLAN_LISTING_ORDER};
+pub use filters::{CompletionFilter, PlanFilter, PlanFilterBuilder};
+pub use import::{ConflictPolicy, ImportOutcome, imported_title};
+pub use labels::{MAX_STATUS_LABEL_LEN, StatusLabels};
+pub use maintenance::MaintenanceReport;
+pub use momentum::{Momentum, TREND_WINDOW_DAYS, Trend};
+pub use page::Page;
+pub use plan::{Plan, parse_date};
+pub use reference::{ReferenceKind, StepReference};
+pub use report::{PlanReport, REPORT_UPCOMING_LIMIT};
+pub use requests::{
+    CreatePlanRequest, CreateStepRequest, MergeOptions, UpdatePlanRequest, UpdateStepRequest,
+};
+pub use settings::{SettingKey, SettingOverrides, SettingSource, SettingValue, Settings};
+pub use stats::{BUSIEST_DIRECTORIES_LIMIT, DirectoryActivity, PlannerStats};
 pub use status::{PlanStatus, StepStatus};
-pub use step::Step;
-pub use summary::PlanSummary;
+pub use step::{
+    ClaimOutcome, Step, StepChange, StepNote, StepOutline, StepVerification, TrashedStep,
+    UNDERDOCUMENTED_RESULT_RATIO,
+};
+pub use summary::{DEFAULT_IN_PROGRESS_WARNING_THRESHOLD, PlanSummary, StepStatusCounts};
+pub use template::{Template, TemplateStep};
+pub use transitions::{AllowedTransitions, StepStateMachine, StepTransition, TransitionCheck};
@@ -0,0 +1,120 @@
+//! Completion momentum: streaks of days with completed steps and how the last
+//! week compares with the one before.
+//!
+//! Completions are bucketed into calendar days in the time zone of the
+//! caller's clock rather than into 24-hour periods, so days stay aligned with
+//! local midnight across daylight saving transitions.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use jiff::{Timestamp, ToSpan, Zoned, civil::Date};
+use serde::{Deserialize, Serialize};
+
+/// Number of days in each of the two windows a [`Trend`] compares.
+pub const TREND_WINDOW_DAYS: i32 = 7;
+
+/// Steps completed in the last [`TREND_WINDOW_DAYS`] days, today included,
+/// compared with the same number of days before.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trend {
+    /// Steps completed in the last seven days
+    pub recent: u32,
+    /// Steps completed in the seven days before those
+    pub prior: u32,
+}
+
+impl Trend {
+    /// Returns true if no steps were completed in either window.
+    pub fn is_empty(&self) -> bool {
+        self.recent == 0 && self.prior == 0
+    }
+}
+
+/// Completion momentum as of a given moment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Momentum {
+    /// Number of consecutive days with at least one completed step, ending
+    /// today. A day without completions only breaks the streak once it is
+    /// over, so a streak ending yesterday still counts until midnight.
+    pub streak_days: u32,
+    /// Whether a step was completed today
+    pub completed_today: bool,
+    /// Completions in the last week compared with the week before
+    pub trend: Trend,
+}
+
+impl Momentum {
+    /// Returns true if there is neither a streak nor a recent completion.
+    pub fn is_empty(&self) -> bool {
+        self.streak_days == 0 && self.trend.is_empty()
+    }
+
+    /// Computes momentum from the times steps were completed, as of `now`.
+    ///
+    /// Completions are bucketed into days in the time zone of `now`.
+    /// Completions after today are ignored.
+    pub fn from_completions(completions: impl IntoIterator<Item = Timestamp>, now: &Zoned) -> Self {
+        let today = now.date();
+        let days: Vec<Date> = completions
+            .into_iter()
+            .map(|completed| completed.to_zoned(now.time_zone().clone()).date())
+            .filter(|day| *day <= today)
+            .collect();
+
+        let completed_today = days.contains(&today);
+        Self {
+            streak_days: streak_days(&days.iter().copied().collect(), today, completed_today),
+            completed_today,
+            trend: trend(&days, today),
+        }
+    }
+
+    /// Computes the momentum of each plan from the times its steps were
+    /// completed, as of `now`, like [`Self::from_completions`]. Plans
+    /// without completions are left out.
+    pub fn by_plan(
+        completions: impl IntoIterator<Item = (u64, Timestamp)>,
+        now: &Zoned,
+    ) -> BTreeMap<u64, Self> {
+        let mut by_plan: BTreeMap<u64, Vec<Timestamp>> = BTreeMap::new();
+        for (plan_id, completed_at) in completions {
+            by_plan.entry(plan_id).or_default().push(completed_at);
+        }
+        by_plan
+            .into_iter()
+            .map(|(plan_id, completions)| (plan_id, Self::from_completions(completions, now)))
+            .collect()
+    }
+}
+
+/// Counts the consecutive days with completions ending today, or yesterday
+/// if nothing was completed today yet.
+fn streak_days(days: &BTreeSet<Date>, today: Date, completed_today: bool) -> u32 {
+    let mut day = if completed_today {
+        Some(today)
+    } else {
+        today.yesterday().ok()
+    };
+    let mut streak = 0;
+    while let Some(current) = day.filter(|day| days.contains(day)) {
+        streak += 1;
+        day = current.yesterday().ok();
+    }
+    streak
+}
+
+/// Counts the completions in the last window ending today and in the window
+/// before it.
+fn trend(days: &[Date], today: Date) -> Trend {
+    let recent_start = today.saturating_sub((TREND_WINDOW_DAYS - 1).days());
+    let prior_start = recent_start.saturating_sub(TREND_WINDOW_DAYS.days());
+
+    days.iter().fold(Trend::default(), |mut trend, day| {
+        if *day >= recent_start {
+            trend.recent += 1;
+        } else if *day >= prior_start {
+            trend.prior += 1;
+        }
+        trend
+    })
+}
@@ -0,0 +1,28 @@
+//! A page of a longer listing.
+
+/// A page of the items of a listing, with the position of the page in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    /// Items on this page, in listing order
+    pub items: Vec<T>,
+    /// Number of items in the whole listing
+    pub total: usize,
+    /// Number of items of the listing before this page
+    pub offset: usize,
+}
+
+impl<T> Page<T> {
+    /// A page holding the whole listing.
+    pub fn all(items: Vec<T>) -> Self {
+        Self {
+            total: items.len(),
+            items,
+            offset: 0,
+        }
+    }
+
+    /// Whether the page holds fewer items than the whole listing.
+    pub fn is_partial(&self) -> bool {
+        self.items.len() < self.total
+    }
+}
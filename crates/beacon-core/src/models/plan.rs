@@ -1,9 +1,10 @@
 //! Plan model definition and related functionality.
 
-use jiff::Timestamp;
+use jiff::{Timestamp, civil::Date};
 use serde::{Deserialize, Serialize};
 
-use super::{PlanStatus, Step};
+use super::{PlanStatus, PlanSummary, StatusLabels, Step};
+use crate::error::{PlannerError, Result};
 
 /// Represents a complete plan with metadata and steps.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -19,6 +20,28 @@ pub struct Plan {
     pub status: PlanStatus,
     /// Working directory for the plan (defaults to CWD when created)
     pub directory: Option<String>,
+    /// Path below `directory` the plan was requested for, when the directory
+    /// was widened to the enclosing git repository root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subpath: Option<String>,
+    /// ID of the parent plan, if this plan is a workstream of a larger one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_plan_id: Option<u64>,
+    /// Whether the plan archives itself once all of its steps are done
+    #[serde(default)]
+    pub auto_archive: bool,
+    /// Team-specific names for step statuses, used only when rendering
+    #[serde(default, skip_serializing_if = "StatusLabels::is_empty")]
+    pub status_labels: StatusLabels,
+    /// ID of the plan this plan's steps were merged into, if it was merged
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merged_into_plan_id: Option<u64>,
+    /// Date the plan should be done by, if it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<Date>,
+    /// Working notes kept while carrying out the plan, newest last
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
     /// Timestamp when the plan was created (UTC)
     pub created_at: Timestamp,
     /// Timestamp when the plan was last modified (UTC)
@@ -26,4 +49,35 @@ pub struct Plan {
     /// Associated steps (lazy-loaded by default)
     #[serde(default)]
     pub steps: Vec<Step>,
+    /// Summaries of the plan's child plans (loaded with the plan)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub child_plans: Vec<PlanSummary>,
+}
+
+impl Plan {
+    /// Whether the plan is still active past its due date, as of `today`.
+    ///
+    /// Archived plans are never overdue.
+    pub fn is_overdue(&self, today: Date) -> bool {
+        is_overdue(self.due_date, self.status, today)
+    }
+}
+
+/// Whether a plan with `status` and `due_date` is overdue as of `today`: it
+/// is active and the date has passed.
+pub(crate) fn is_overdue(due_date: Option<Date>, status: PlanStatus, today: Date) -> bool {
+    status == PlanStatus::Active && due_date.is_some_and(|due| due < today)
+}
+
+/// Parses an ISO 8601 calendar date such as `2025-01-31`, given for `field`.
+///
+/// # Errors
+///
+/// Returns `PlannerError::InvalidInput` if the text is not such a date.
+pub fn parse_date(field: &str, text: &str) -> Result<Date> {
+    text.trim().parse::<Date>().map_err(|_| {
+        PlannerError::invalid_input(field).with_reason(format!(
+            "Invalid date '{text}': expected a date such as 2025-01-31"
+        ))
+    })
 }
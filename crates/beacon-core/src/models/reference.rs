@@ -0,0 +1,143 @@
+//! References from a step to related resources.
+
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A resource a step refers to, such as a URL or file path, with an optional
+/// label to show in its place.
+///
+/// Serialized as a plain string when unlabelled and unclassified, and as
+/// `{"url": "...", "label": "...", "kind": "..."}` otherwise; both forms are
+/// accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(from = "ReferenceForm", into = "ReferenceForm")]
+pub struct StepReference {
+    /// URL, file path or other locator of the resource
+    pub url: String,
+    /// Optional text shown instead of the URL
+    pub label: Option<String>,
+    /// What the reference points to, recorded when references are validated
+    /// with the `strict_references` setting on
+    pub kind: Option<ReferenceKind>,
+}
+
+impl StepReference {
+    /// A reference to `url` shown as `label`.
+    pub fn labelled(url: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            label: Some(label.into()),
+            kind: None,
+        }
+    }
+}
+
+impl From<String> for StepReference {
+    fn from(url: String) -> Self {
+        Self {
+            url,
+            label: None,
+            kind: None,
+        }
+    }
+}
+
+impl From<&str> for StepReference {
+    fn from(url: &str) -> Self {
+        url.to_string().into()
+    }
+}
+
+/// Serialized form of a [`StepReference`].
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(untagged)]
+enum ReferenceForm {
+    /// A URL or file path without a label
+    Url(String),
+    /// A URL or file path with a label to show in its place
+    Labelled {
+        /// URL, file path or other locator of the resource
+        url: String,
+        /// Optional text shown instead of the URL
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+        /// What the reference points to, as classified by beacon; ignored
+        /// when given
+        #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+        kind: Option<ReferenceKind>,
+    },
+}
+
+impl From<ReferenceForm> for StepReference {
+    fn from(form: ReferenceForm) -> Self {
+        match form {
+            ReferenceForm::Url(url) => url.into(),
+            ReferenceForm::Labelled { url, label, kind } => Self { url, label, kind },
+        }
+    }
+}
+
+impl From<StepReference> for ReferenceForm {
+    fn from(reference: StepReference) -> Self {
+        match reference {
+            StepReference {
+                url,
+                label: None,
+                kind: None,
+            } => Self::Url(url),
+            StepReference { url, label, kind } => Self::Labelled { url, label, kind },
+        }
+    }
+}
+
+/// What a validated [`StepReference`] points to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    /// A URL with a scheme and host, such as `https://example.com/docs`
+    Url,
+    /// A file path that existed when the reference was added, or that could
+    /// not be checked because the plan has no directory
+    File,
+    /// A file path that didn't exist when the reference was added
+    MissingFile,
+    /// Anything else, such as `see the design doc`
+    Text,
+}
+
+impl ReferenceKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Url => "url",
+            Self::File => "file",
+            Self::MissingFile => "missing_file",
+            Self::Text => "text",
+        }
+    }
+}
+
+impl FromStr for ReferenceKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "url" => Ok(Self::Url),
+            "file" => Ok(Self::File),
+            "missing_file" => Ok(Self::MissingFile),
+            "text" => Ok(Self::Text),
+            _ => Err(format!("Invalid reference kind: {s}")),
+        }
+    }
+}
+
+impl fmt::Display for ReferenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
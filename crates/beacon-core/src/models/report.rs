@@ -0,0 +1,31 @@
+//! Standup report of a plan's recent progress.
+
+use jiff::Timestamp;
+
+use super::Step;
+
+/// Number of todo steps listed as up next in a [`PlanReport`].
+pub const REPORT_UPCOMING_LIMIT: usize = 3;
+
+/// What happened to a plan since a cutoff and what comes next, for a
+/// standup or a status post.
+#[derive(Debug, Clone)]
+pub struct PlanReport {
+    pub plan_id: u64,
+    pub plan_title: String,
+    /// Start of the reported period
+    pub since: Timestamp,
+    /// Steps completed since the cutoff, in plan order
+    pub completed: Vec<Step>,
+    /// Steps in progress now, in plan order
+    pub in_progress: Vec<Step>,
+    /// The first todo steps, at most [`REPORT_UPCOMING_LIMIT`] of them
+    pub upcoming: Vec<Step>,
+}
+
+impl PlanReport {
+    /// Whether any step was completed since the cutoff or is in progress.
+    pub fn has_activity(&self) -> bool {
+        !self.completed.is_empty() || !self.in_progress.is_empty()
+    }
+}
@@ -1,16 +1,161 @@
 //! Request types for updating models.
 
-use super::StepStatus;
+use jiff::civil::Date;
 
-/// Parameters for updating a step to reduce function argument count
+use super::{StatusLabels, Step, StepReference, StepStatus};
+use crate::{
+    error::PlannerError,
+    params::{validate_text, validate_title},
+};
+
+/// Parameters for creating a plan to reduce function argument count
+#[derive(Debug, Default, Clone)]
+pub struct CreatePlanRequest {
+    pub title: String,
+    pub description: Option<String>,
+    pub directory: Option<String>,
+    /// Store the enclosing git repository root instead of a subdirectory
+    pub infer_git_root: bool,
+    /// Return an existing plan with the same title and directory instead of
+    /// creating a duplicate
+    pub unique_per_directory: bool,
+    /// Parent plan to attach the new plan to
+    pub parent_plan_id: Option<u64>,
+    /// Archive the plan automatically once all of its steps are done
+    pub auto_archive: bool,
+    /// Date the plan should be done by
+    pub due_date: Option<Date>,
+}
+
+/// A step to create together with its plan.
+///
+/// Unlike steps added one at a time, these may start out done, in which case
+/// a result is required.
+#[derive(Debug, Clone)]
+pub struct CreateStepRequest {
+    pub title: String,
+    pub description: Option<String>,
+    pub acceptance_criteria: Option<String>,
+    pub references: Vec<StepReference>,
+    pub status: StepStatus,
+    pub result: Option<String>,
+}
+
+impl From<&Step> for CreateStepRequest {
+    /// Recreates `step` with its content, status and result.
+    fn from(step: &Step) -> Self {
+        Self {
+            title: step.title.clone(),
+            description: step.description.clone(),
+            acceptance_criteria: step.acceptance_criteria.clone(),
+            references: step.references.clone(),
+            status: step.status,
+            result: step.result.clone(),
+        }
+    }
+}
+
+/// Parameters for updating a plan
 #[derive(Debug, Default)]
+pub struct UpdatePlanRequest {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Made absolute before it is stored, like a new plan's directory
+    pub directory: Option<String>,
+    /// Store the enclosing git repository root of a new `directory`
+    pub infer_git_root: bool,
+    /// `Some(None)` detaches the plan from its parent
+    pub parent_plan_id: Option<Option<u64>>,
+    pub auto_archive: Option<bool>,
+    /// Replaces the plan's status labels; empty labels clear them
+    pub status_labels: Option<StatusLabels>,
+    /// `Some(None)` clears the plan's due date
+    pub due_date: Option<Option<Date>>,
+}
+
+impl TryFrom<&crate::params::UpdatePlan> for UpdatePlanRequest {
+    type Error = PlannerError;
+
+    fn try_from(params: &crate::params::UpdatePlan) -> Result<Self, PlannerError> {
+        let parent_plan_id = if params.clear_parent {
+            Some(None)
+        } else {
+            params.parent_plan_id.map(Some)
+        };
+        let due_date = if params.clear_due_date {
+            Some(None)
+        } else {
+            params
+                .due_date
+                .as_deref()
+                .map(|date| super::parse_date("due_date", date))
+                .transpose()?
+                .map(Some)
+        };
+
+        Ok(Self {
+            title: params
+                .title
+                .as_deref()
+                .map(|title| validate_title("title", title))
+                .transpose()?,
+            description: validate_text("description", params.description.as_deref())?,
+            directory: params.directory.clone(),
+            parent_plan_id,
+            auto_archive: params.auto_archive,
+            status_labels: params.status_labels.clone(),
+            due_date,
+            ..Default::default()
+        })
+    }
+}
+
+impl UpdatePlanRequest {
+    /// Whether the request leaves every field of the plan unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.description.is_none()
+            && self.directory.is_none()
+            && self.parent_plan_id.is_none()
+            && self.auto_archive.is_none()
+            && self.status_labels.is_none()
+            && self.due_date.is_none()
+    }
+}
+
+/// Options for merging one plan into another.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MergeOptions {
+    /// Prefix the moved steps' titles with the source plan's title
+    pub prefix_titles: bool,
+}
+
+impl From<&crate::params::MergePlans> for MergeOptions {
+    fn from(params: &crate::params::MergePlans) -> Self {
+        Self {
+            prefix_titles: params.prefix_titles,
+        }
+    }
+}
+
+/// Parameters for updating a step to reduce function argument count
+#[derive(Debug, Clone, Default)]
 pub struct UpdateStepRequest {
     pub title: Option<String>,
     pub description: Option<String>,
     pub acceptance_criteria: Option<String>,
-    pub references: Option<Vec<String>>,
+    /// Artifact the step is expected to produce
+    pub expected_output: Option<String>,
+    pub references: Option<Vec<StepReference>>,
     pub status: Option<StepStatus>,
     pub result: Option<String>,
+    /// Drop the result kept from an earlier completion of a reopened step
+    pub clear_result: bool,
+    /// Why the step is blocked, only accepted with status = Blocked or for a
+    /// step that already is
+    pub blocked_reason: Option<String>,
+    /// Whether completing the step must be confirmed by a second agent
+    pub verification_required: Option<bool>,
 }
 
 impl UpdateStepRequest {
@@ -37,7 +182,7 @@ impl UpdateStepRequest {
         title: Option<String>,
         description: Option<String>,
         acceptance_criteria: Option<String>,
-        references: Option<Vec<String>>,
+        references: Option<Vec<StepReference>>,
         status: Option<StepStatus>,
         result: Option<String>,
     ) -> Self {
@@ -45,21 +190,41 @@ impl UpdateStepRequest {
             title,
             description,
             acceptance_criteria,
+            expected_output: None,
             references,
             status,
             result,
+            clear_result: false,
+            blocked_reason: None,
+            verification_required: None,
         }
     }
+
+    /// Returns true when the request does not change any field.
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.description.is_none()
+            && self.acceptance_criteria.is_none()
+            && self.expected_output.is_none()
+            && self.references.is_none()
+            && self.status.is_none()
+            && self.result.is_none()
+            && !self.clear_result
+            && self.blocked_reason.is_none()
+            && self.verification_required.is_none()
+    }
 }
 
 impl TryFrom<crate::params::UpdateStep> for UpdateStepRequest {
-    type Error = crate::PlannerError;
+    type Error = PlannerError;
 
     /// Convert an UpdateStep parameter into a validated UpdateStepRequest.
     ///
     /// This trait implementation replaces the `create_update_request` function
     /// with an idiomatic Rust conversion. It performs validation of the status
-    /// field and ensures result requirements are met for 'done' status.
+    /// field and trims the text fields, rejecting blank titles and overlong
+    /// text; whether a step marked 'done' has a result is checked when
+    /// updating, since it may reuse the one kept from an earlier completion.
     ///
     /// # Arguments
     ///
@@ -73,18 +238,30 @@ impl TryFrom<crate::params::UpdateStep> for UpdateStepRequest {
     /// # Errors
     ///
     /// * `PlannerError::InvalidInput` - When status string is invalid
-    /// * `PlannerError::InvalidInput` - When result is missing for 'done'
-    ///   status
+    /// * `PlannerError::InvalidInput` - When the title is blank or a text
+    ///   field is too long
     fn try_from(params: crate::params::UpdateStep) -> Result<Self, Self::Error> {
         let (validated_status, validated_result) = params.validate()?;
 
         Ok(Self {
-            title: params.title,
-            description: params.description,
-            acceptance_criteria: params.acceptance_criteria,
+            title: params
+                .title
+                .as_deref()
+                .map(|title| validate_title("title", title))
+                .transpose()?,
+            description: validate_text("description", params.description.as_deref())?,
+            acceptance_criteria: validate_text(
+                "acceptance_criteria",
+                params.acceptance_criteria.as_deref(),
+            )?,
+            expected_output: validate_text("expected_output", params.expected_output.as_deref())?,
             references: params.references,
             status: validated_status,
             result: validated_result,
+            clear_result: params.clear_result,
+            blocked_reason: validate_text("blocked_reason", params.blocked_reason.as_deref())?
+                .filter(|reason| !reason.is_empty()),
+            verification_required: params.verification_required,
         })
     }
 }
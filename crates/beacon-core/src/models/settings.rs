@@ -0,0 +1,337 @@
+//! Per-workspace settings stored in the database.
+//!
+//! Each database holds the settings of its workspace, so that the CLI and
+//! the MCP server working on it behave the same. Settings that were never
+//! set take their default value, and flags given to
+//! [`PlannerBuilder`](crate::PlannerBuilder) override stored values for the
+//! planner being built.
+
+use std::{collections::BTreeSet, fmt, str::FromStr, time::Duration};
+
+use jiff::{SignedDuration, Span, SpanRelativeTo, SpanRound, Unit};
+
+use super::DEFAULT_IN_PROGRESS_WARNING_THRESHOLD;
+
+/// A setting stored in the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SettingKey {
+    AutoArchive,
+    InProgressWarningThreshold,
+    MaxResultLength,
+    UniquePlanPerDirectory,
+    InferGitRoot,
+    StrictReferences,
+}
+
+impl SettingKey {
+    /// All settings, in the order they are listed.
+    pub const ALL: [Self; 6] = [
+        Self::AutoArchive,
+        Self::InProgressWarningThreshold,
+        Self::MaxResultLength,
+        Self::UniquePlanPerDirectory,
+        Self::InferGitRoot,
+        Self::StrictReferences,
+    ];
+
+    /// Name of the setting as stored and as given on the command line.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::AutoArchive => "auto_archive",
+            Self::InProgressWarningThreshold => "in_progress_warning_threshold",
+            Self::MaxResultLength => "max_result_length",
+            Self::UniquePlanPerDirectory => "unique_plan_per_directory",
+            Self::InferGitRoot => "infer_git_root",
+            Self::StrictReferences => "strict_references",
+        }
+    }
+
+    /// What the setting controls, and the values it takes.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::AutoArchive => {
+                "Whether new plans archive themselves once all their steps are done (true/false)"
+            }
+            Self::InProgressWarningThreshold => {
+                "How long a step may stay in progress before it is flagged, e.g. 7d or 36h"
+            }
+            Self::MaxResultLength => {
+                "Maximum length of a step's result, in characters, or none for no limit"
+            }
+            Self::UniquePlanPerDirectory => {
+                "Whether creating a plan whose title exists in its directory returns the existing plan (true/false)"
+            }
+            Self::InferGitRoot => {
+                "Whether new plans store the enclosing git repository root as their directory (true/false)"
+            }
+            Self::StrictReferences => {
+                "Whether step references are validated, normalized and classified as URL, file or text (true/false)"
+            }
+        }
+    }
+
+    /// Whether agents may change the setting through MCP tools.
+    ///
+    /// Settings that lift limits or change how plans are stored can only be
+    /// changed from the command line.
+    pub fn agent_safe(self) -> bool {
+        matches!(self, Self::AutoArchive | Self::InProgressWarningThreshold)
+    }
+}
+
+impl FromStr for SettingKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|key| key.as_str() == s.trim())
+            .ok_or_else(|| {
+                let known: Vec<&str> = Self::ALL.into_iter().map(Self::as_str).collect();
+                format!(
+                    "Unknown setting '{s}'; expected one of: {}",
+                    known.join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for SettingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Where the value a setting has comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingSource {
+    /// The setting was never set
+    Default,
+    /// The setting is stored in the workspace's database
+    Workspace,
+    /// The setting was fixed when the planner was built
+    Override,
+}
+
+impl SettingSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Workspace => "workspace",
+            Self::Override => "override",
+        }
+    }
+}
+
+/// The value a setting has, and where it comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingValue {
+    pub key: SettingKey,
+    /// The value, formatted as it is stored
+    pub value: String,
+    pub source: SettingSource,
+}
+
+/// The settings of a workspace, with defaults for those never set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// Whether new plans archive themselves once all their steps are done,
+    /// unless their creation says otherwise
+    pub auto_archive: bool,
+    /// Age above which in-progress steps are flagged in plan summaries
+    pub in_progress_warning_threshold: Duration,
+    /// Maximum number of characters in a step's result
+    pub max_result_length: Option<usize>,
+    /// Whether plan creation deduplicates on `(directory, title)`
+    pub unique_plan_per_directory: bool,
+    /// Whether plan directories are widened to the enclosing git root,
+    /// unless their creation says otherwise
+    pub infer_git_root: bool,
+    /// Whether step references are validated and classified when written
+    pub strict_references: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            auto_archive: false,
+            in_progress_warning_threshold: DEFAULT_IN_PROGRESS_WARNING_THRESHOLD,
+            max_result_length: None,
+            unique_plan_per_directory: false,
+            infer_git_root: false,
+            strict_references: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Settings with the stored `entries` applied over the defaults.
+    ///
+    /// Entries with an unknown key or an invalid value, which can only get
+    /// into the database by editing it by hand, are ignored.
+    pub fn from_entries<K: AsRef<str>, V: AsRef<str>>(
+        entries: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        let mut settings = Self::default();
+        for (key, value) in entries {
+            if let Ok(key) = key.as_ref().parse() {
+                settings.set(key, value.as_ref()).ok();
+            }
+        }
+        settings
+    }
+
+    /// The value of `key`, formatted as it is stored.
+    pub fn get(&self, key: SettingKey) -> String {
+        match key {
+            SettingKey::AutoArchive => self.auto_archive.to_string(),
+            SettingKey::InProgressWarningThreshold => {
+                format_duration(self.in_progress_warning_threshold)
+            }
+            SettingKey::MaxResultLength => self
+                .max_result_length
+                .map_or_else(|| "none".to_string(), |length| length.to_string()),
+            SettingKey::UniquePlanPerDirectory => self.unique_plan_per_directory.to_string(),
+            SettingKey::InferGitRoot => self.infer_git_root.to_string(),
+            SettingKey::StrictReferences => self.strict_references.to_string(),
+        }
+    }
+
+    /// Validates `value` for `key` and sets it.
+    ///
+    /// # Errors
+    ///
+    /// Returns why the value is not valid for the setting.
+    pub fn set(&mut self, key: SettingKey, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        match key {
+            SettingKey::AutoArchive => self.auto_archive = parse_bool(key, value)?,
+            SettingKey::InProgressWarningThreshold => {
+                self.in_progress_warning_threshold = parse_duration(key, value)?;
+            }
+            SettingKey::MaxResultLength => {
+                self.max_result_length = if value.eq_ignore_ascii_case("none") {
+                    None
+                } else {
+                    match value.parse::<usize>() {
+                        Ok(length) if length > 0 => Some(length),
+                        _ => {
+                            return Err(format!(
+                                "{key} must be a positive number of characters or none, got '{value}'"
+                            ));
+                        }
+                    }
+                };
+            }
+            SettingKey::UniquePlanPerDirectory => {
+                self.unique_plan_per_directory = parse_bool(key, value)?;
+            }
+            SettingKey::InferGitRoot => self.infer_git_root = parse_bool(key, value)?,
+            SettingKey::StrictReferences => self.strict_references = parse_bool(key, value)?,
+        }
+        Ok(())
+    }
+
+    /// Checks that `result` fits within the maximum result length.
+    ///
+    /// # Errors
+    ///
+    /// Returns why the result is too long.
+    pub fn check_result_length(&self, result: &str) -> Result<(), String> {
+        match self.max_result_length {
+            Some(max) if result.chars().count() > max => Err(format!(
+                "Result must be at most {max} characters, got {}",
+                result.chars().count()
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Settings fixed when a planner is built, which take precedence over the
+/// workspace's stored settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingOverrides {
+    values: Settings,
+    keys: BTreeSet<SettingKey>,
+}
+
+impl SettingOverrides {
+    /// Overrides `key` with the value `update` gives it.
+    pub(crate) fn set(&mut self, key: SettingKey, update: impl FnOnce(&mut Settings)) {
+        update(&mut self.values);
+        self.keys.insert(key);
+    }
+
+    /// Whether `key` is overridden.
+    pub fn contains(&self, key: SettingKey) -> bool {
+        self.keys.contains(&key)
+    }
+
+    /// Replaces the overridden settings in `settings`.
+    pub fn apply(&self, settings: &mut Settings) {
+        let values = &self.values;
+        for key in &self.keys {
+            match key {
+                SettingKey::AutoArchive => settings.auto_archive = values.auto_archive,
+                SettingKey::InProgressWarningThreshold => {
+                    settings.in_progress_warning_threshold = values.in_progress_warning_threshold;
+                }
+                SettingKey::MaxResultLength => {
+                    settings.max_result_length = values.max_result_length;
+                }
+                SettingKey::UniquePlanPerDirectory => {
+                    settings.unique_plan_per_directory = values.unique_plan_per_directory;
+                }
+                SettingKey::InferGitRoot => settings.infer_git_root = values.infer_git_root,
+                SettingKey::StrictReferences => {
+                    settings.strict_references = values.strict_references;
+                }
+            }
+        }
+    }
+}
+
+fn parse_bool(key: SettingKey, value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" => Ok(false),
+        _ => Err(format!("{key} must be true or false, got '{value}'")),
+    }
+}
+
+/// Parses a positive duration such as `7d`, `36h` or `1d 12h`, counting
+/// days as 24 hours.
+fn parse_duration(key: SettingKey, value: &str) -> Result<Duration, String> {
+    let invalid = || format!("{key} must be a positive duration such as 7d or 36h, got '{value}'");
+    let span: Span = value.parse().map_err(|_| invalid())?;
+    let duration = span
+        .to_duration(SpanRelativeTo::days_are_24_hours())
+        .map_err(|_| invalid())?;
+    if duration.is_positive() {
+        Duration::try_from(duration).map_err(|_| invalid())
+    } else {
+        Err(invalid())
+    }
+}
+
+/// Formats a duration the way [`parse_duration`] reads it, in the largest
+/// units that fit, e.g. `7d` or `1d 12h`.
+fn format_duration(duration: Duration) -> String {
+    SignedDuration::try_from(duration)
+        .ok()
+        .and_then(|duration| Span::try_from(duration).ok())
+        .and_then(|span| {
+            span.round(
+                SpanRound::new()
+                    .largest(Unit::Day)
+                    .smallest(Unit::Nanosecond)
+                    .days_are_24_hours(),
+            )
+            .ok()
+        })
+        .map_or_else(
+            || format!("{}s", duration.as_secs()),
+            |span| format!("{span:#}"),
+        )
+}
@@ -0,0 +1,47 @@
+//! Planner statistics over a time range, for periodic reviews.
+
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+
+/// Number of directories listed in [`PlannerStats::busiest_directories`].
+pub const BUSIEST_DIRECTORIES_LIMIT: usize = 5;
+
+/// Steps completed in a plan directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirectoryActivity {
+    /// Working directory of the plans
+    pub directory: String,
+    /// Steps of those plans completed in the range
+    pub steps_completed: u32,
+}
+
+/// Counts of what happened to plans and steps in a time range, or over all
+/// time. The default is the zeroed report of an empty database.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlannerStats {
+    /// Start of the range, inclusive; `None` for all time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<Timestamp>,
+    /// End of the range, inclusive; `None` for all time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<Timestamp>,
+    /// Plans created in the range
+    pub plans_created: u32,
+    /// Plans archived in the range, as recorded in the change log
+    pub plans_archived: u32,
+    /// Plans whose details or steps were last changed in the range
+    pub plans_touched: u32,
+    /// Steps created in the range, not counting those in the trash
+    pub steps_created: u32,
+    /// Steps completed in the range, not counting those in the trash
+    pub steps_completed: u32,
+    /// Share of the steps created in the range that are done, from 0 to 1
+    pub completion_rate: f64,
+    /// Average seconds from creating a step to completing it, over the
+    /// steps completed in the range; `None` if there are none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub average_seconds_to_done: Option<u64>,
+    /// Directories with the most steps completed in the range, busiest
+    /// first, at most [`BUSIEST_DIRECTORIES_LIMIT`] of them
+    pub busiest_directories: Vec<DirectoryActivity>,
+}
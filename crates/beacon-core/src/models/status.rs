@@ -47,6 +47,8 @@ pub enum StepStatus {
     InProgress,
     /// Step has been completed
     Done,
+    /// Step cannot progress until something outside the plan changes
+    Blocked,
 }
 
 impl FromStr for StepStatus {
@@ -57,18 +59,23 @@ impl FromStr for StepStatus {
             "todo" => Ok(StepStatus::Todo),
             "inprogress" | "in_progress" => Ok(StepStatus::InProgress),
             "done" => Ok(StepStatus::Done),
+            "blocked" => Ok(StepStatus::Blocked),
             _ => Ok(StepStatus::Todo), // treat unknown as "todo"
         }
     }
 }
 
 impl StepStatus {
+    /// All statuses, in the order they are listed.
+    pub const ALL: [Self; 4] = [Self::Todo, Self::InProgress, Self::Done, Self::Blocked];
+
     /// Convert to database string representation (for backwards compatibility)
     pub fn as_str(&self) -> &'static str {
         match self {
             StepStatus::Todo => "todo",
             StepStatus::InProgress => "inprogress",
             StepStatus::Done => "done",
+            StepStatus::Blocked => "blocked",
         }
     }
 
@@ -82,11 +89,33 @@ impl StepStatus {
     /// - `✓ Done` - Checkmark for completed steps
     /// - `➤ In Progress` - Arrow for active steps
     /// - `○ Todo` - Circle for pending steps
+    /// - `⛔ Blocked` - No entry sign for blocked steps
     pub fn with_icon(&self) -> &'static str {
         match self {
             StepStatus::Done => "✓ Done",
             StepStatus::InProgress => "➤ In Progress",
             StepStatus::Todo => "○ Todo",
+            StepStatus::Blocked => "⛔ Blocked",
+        }
+    }
+
+    /// Get the icon used for the status in [`Self::with_icon`].
+    pub fn icon(&self) -> &'static str {
+        match self {
+            StepStatus::Done => "✓",
+            StepStatus::InProgress => "➤",
+            StepStatus::Todo => "○",
+            StepStatus::Blocked => "⛔",
+        }
+    }
+
+    /// Get the status name used in [`Self::with_icon`].
+    pub fn label(&self) -> &'static str {
+        match self {
+            StepStatus::Done => "Done",
+            StepStatus::InProgress => "In Progress",
+            StepStatus::Todo => "Todo",
+            StepStatus::Blocked => "Blocked",
         }
     }
 }
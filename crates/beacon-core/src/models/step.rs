@@ -1,9 +1,15 @@
 //! Step model definition and related functionality.
 
+use std::time::Duration;
+
 use jiff::Timestamp;
 use serde::{Deserialize, Serialize};
 
-use super::StepStatus;
+use super::{StepReference, StepStatus};
+
+/// How many times longer than a done step's result its expected output must
+/// be for the result to be flagged as possibly under-documented.
+pub const UNDERDOCUMENTED_RESULT_RATIO: usize = 4;
 
 /// Represents an individual step within a plan.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -18,17 +24,268 @@ pub struct Step {
     pub description: Option<String>,
     /// Clear completion criteria for the step
     pub acceptance_criteria: Option<String>,
-    /// References to relevant resources (URLs, file paths)
+    /// Artifact the step is expected to produce, such as "a new endpoint
+    /// POST /v1/tokens", to compare against its result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_output: Option<String>,
+    /// References to relevant resources (URLs, file paths), in order
     #[serde(default)]
-    pub references: Vec<String>,
+    pub references: Vec<StepReference>,
     /// Current status of the step
     pub status: StepStatus,
     /// Description of what was accomplished (required when status = Done)
     pub result: Option<String>,
-    /// Order of the step within the plan (0-indexed)
+    /// Why the step is blocked; only kept while status = Blocked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocked_reason: Option<String>,
+    /// Position of the step within the plan (0-indexed, contiguous)
     pub order: u32,
+    /// Section the step is grouped under when its plan is displayed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase: Option<String>,
+    /// Estimated effort in minutes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate_minutes: Option<u32>,
+    /// Whether completing the step must be confirmed by a second agent
+    #[serde(default)]
+    pub verification_required: bool,
+    /// Confirmation of the step's completion, if it has been verified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<StepVerification>,
+    /// IDs of the steps that must be done before this one can be claimed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<u64>,
+    /// Notes recorded while working on the step, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<StepNote>,
+    /// Timestamp when the step was first claimed or put in progress (UTC)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<Timestamp>,
+    /// Timestamp when the step was last marked done (UTC); cleared when it
+    /// is reopened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<Timestamp>,
     /// Timestamp when the step was created (UTC)
     pub created_at: Timestamp,
     /// Timestamp when the step was last updated (UTC)
     pub updated_at: Timestamp,
 }
+
+impl Step {
+    /// Whether the step is done but still needs its completion verified.
+    ///
+    /// Such steps don't count towards their plan's progress yet.
+    pub fn awaiting_verification(&self) -> bool {
+        self.status == StepStatus::Done && self.verification_required && self.verification.is_none()
+    }
+
+    /// Whether the step is done with a result much shorter than its expected
+    /// output, suggesting the result doesn't document what was produced.
+    ///
+    /// A cheap length heuristic: the expected output, in characters, must be
+    /// more than [`UNDERDOCUMENTED_RESULT_RATIO`] times the result's length.
+    pub fn result_may_be_underdocumented(&self) -> bool {
+        let Some(expected) = &self.expected_output else {
+            return false;
+        };
+        let result = self.result.as_deref().unwrap_or_default();
+        self.status == StepStatus::Done
+            && expected.trim().chars().count()
+                > result.trim().chars().count() * UNDERDOCUMENTED_RESULT_RATIO
+    }
+
+    /// Whether the step counts as complete: done, and verified if required.
+    pub fn is_complete(&self) -> bool {
+        self.status == StepStatus::Done && !self.awaiting_verification()
+    }
+
+    /// How long the step took from being started to being completed.
+    ///
+    /// `None` unless both times were recorded, which steps completed without
+    /// being started or before time tracking was added lack.
+    pub fn duration(&self) -> Option<Duration> {
+        let started = self.started_at?;
+        let completed = self.completed_at?;
+        Duration::try_from(completed.duration_since(started)).ok()
+    }
+}
+
+/// Record of a second agent confirming a step's completion.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StepVerification {
+    /// Who verified the step
+    pub verifier: String,
+    /// Optional notes from the verifier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Timestamp when the step was verified (UTC)
+    pub verified_at: Timestamp,
+}
+
+/// Something learned while working on a step that doesn't belong in its
+/// result, such as "the API returns 429 under load".
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StepNote {
+    /// Unique identifier for the note
+    pub id: u64,
+    /// ID of the step the note is about
+    pub step_id: u64,
+    /// Text of the note
+    pub body: String,
+    /// Timestamp when the note was added (UTC)
+    pub created_at: Timestamp,
+}
+
+/// Lightweight view of a step: just enough to list a plan's steps.
+///
+/// Leaves out descriptions, acceptance criteria, references and results,
+/// which make up most of a step's size.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StepOutline {
+    /// Unique identifier for the step
+    pub id: u64,
+    /// Position of the step within the plan (0-indexed, contiguous)
+    pub order: u32,
+    /// Brief title/summary of the step
+    pub title: String,
+    /// Current status of the step
+    pub status: StepStatus,
+}
+
+/// A step moved to the trash, as listed for restoring.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrashedStep {
+    /// Unique identifier for the step
+    pub id: u64,
+    /// ID of the plan the step belonged to
+    pub plan_id: u64,
+    /// Brief title/summary of the step
+    pub title: String,
+    /// Status the step had when it was trashed
+    pub status: StepStatus,
+    /// Timestamp when the step was moved to the trash (UTC)
+    pub deleted_at: Timestamp,
+}
+
+/// Result of trying to claim a step.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClaimOutcome {
+    /// The step was moved from Todo to InProgress; holds the claimed step
+    Claimed(Box<Step>),
+    /// The step exists but is not in Todo status
+    NotClaimable(StepStatus),
+    /// The step is todo but depends on steps that are not done yet; holds
+    /// their IDs
+    Blocked(Vec<u64>),
+    /// No step has the requested ID
+    NotFound,
+}
+
+impl ClaimOutcome {
+    /// The claimed step, if the claim succeeded.
+    pub fn into_claimed(self) -> Option<Step> {
+        match self {
+            ClaimOutcome::Claimed(step) => Some(*step),
+            ClaimOutcome::NotClaimable(_) | ClaimOutcome::Blocked(_) | ClaimOutcome::NotFound => {
+                None
+            }
+        }
+    }
+}
+
+/// A field of a step changed by an update, with its old and new values.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepChange {
+    TitleChanged {
+        from: String,
+        to: String,
+    },
+    DescriptionChanged {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    AcceptanceCriteriaChanged {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    ExpectedOutputChanged {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    ReferencesChanged {
+        from: Vec<StepReference>,
+        to: Vec<StepReference>,
+    },
+    StatusChanged {
+        from: StepStatus,
+        to: StepStatus,
+    },
+    ResultChanged {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    VerificationRequiredChanged {
+        from: bool,
+        to: bool,
+    },
+}
+
+impl StepChange {
+    /// Lists the fields an update can change that differ between `before`
+    /// and `after`, in the order the step displays them.
+    ///
+    /// An update that set fields to the values they already had yields no
+    /// changes.
+    pub fn between(before: &Step, after: &Step) -> Vec<Self> {
+        let mut changes = Vec::new();
+        if before.title != after.title {
+            changes.push(Self::TitleChanged {
+                from: before.title.clone(),
+                to: after.title.clone(),
+            });
+        }
+        if before.status != after.status {
+            changes.push(Self::StatusChanged {
+                from: before.status,
+                to: after.status,
+            });
+        }
+        if before.description != after.description {
+            changes.push(Self::DescriptionChanged {
+                from: before.description.clone(),
+                to: after.description.clone(),
+            });
+        }
+        if before.acceptance_criteria != after.acceptance_criteria {
+            changes.push(Self::AcceptanceCriteriaChanged {
+                from: before.acceptance_criteria.clone(),
+                to: after.acceptance_criteria.clone(),
+            });
+        }
+        if before.expected_output != after.expected_output {
+            changes.push(Self::ExpectedOutputChanged {
+                from: before.expected_output.clone(),
+                to: after.expected_output.clone(),
+            });
+        }
+        if before.references != after.references {
+            changes.push(Self::ReferencesChanged {
+                from: before.references.clone(),
+                to: after.references.clone(),
+            });
+        }
+        if before.result != after.result {
+            changes.push(Self::ResultChanged {
+                from: before.result.clone(),
+                to: after.result.clone(),
+            });
+        }
+        if before.verification_required != after.verification_required {
+            changes.push(Self::VerificationRequiredChanged {
+                from: before.verification_required,
+                to: after.verification_required,
+            });
+        }
+        changes
+    }
+}
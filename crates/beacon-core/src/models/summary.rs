@@ -1,12 +1,48 @@
 //! Plan summary types and functionality.
 
-use jiff::Timestamp;
+use std::time::Duration;
+
+use jiff::{Timestamp, civil::Date};
 use serde::{Deserialize, Serialize};
 
-use super::{Plan, PlanStatus, StepStatus};
+use super::{Plan, PlanStatus, StatusLabels, StepStatus, Trend};
+
+/// Default age after which an in-progress step is flagged in plan summaries.
+pub const DEFAULT_IN_PROGRESS_WARNING_THRESHOLD: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Number of a plan's steps in each status, counted without loading them.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StepStatusCounts {
+    /// Steps that are done, whether or not their completion was verified
+    pub done: u32,
+    /// Steps in progress
+    pub in_progress: u32,
+    /// Steps still to do
+    pub todo: u32,
+    /// Steps that are blocked
+    #[serde(default)]
+    pub blocked: u32,
+}
+
+impl StepStatusCounts {
+    /// Number of steps with `status`.
+    pub fn count(&self, status: StepStatus) -> u32 {
+        match status {
+            StepStatus::Done => self.done,
+            StepStatus::InProgress => self.in_progress,
+            StepStatus::Todo => self.todo,
+            StepStatus::Blocked => self.blocked,
+        }
+    }
+
+    /// Number of steps in the plan.
+    pub fn total(&self) -> u32 {
+        self.done + self.in_progress + self.todo + self.blocked
+    }
+}
 
 /// Summary information about a plan with step statistics.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PlanSummary {
     /// Plan ID
     pub id: u64,
@@ -18,6 +54,12 @@ pub struct PlanSummary {
     pub status: PlanStatus,
     /// Working directory for the plan
     pub directory: Option<String>,
+    /// ID of the parent plan, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_plan_id: Option<u64>,
+    /// Date the plan should be done by, if it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<Date>,
     /// Creation timestamp
     pub created_at: Timestamp,
     /// Last update timestamp
@@ -28,6 +70,36 @@ pub struct PlanSummary {
     pub completed_steps: u32,
     /// Number of pending steps
     pub pending_steps: u32,
+    /// Number of steps currently in progress; they count as pending too
+    #[serde(default)]
+    pub in_progress_steps: u32,
+    /// Number of done steps whose completion still has to be verified; they
+    /// count as pending rather than completed
+    #[serde(default)]
+    pub awaiting_verification_steps: u32,
+    /// Number of child plans, archived ones included
+    #[serde(default)]
+    pub child_plans: u32,
+    /// Total number of steps across all child plans
+    #[serde(default)]
+    pub child_total_steps: u32,
+    /// Number of completed steps across all child plans
+    #[serde(default)]
+    pub child_completed_steps: u32,
+    /// Time since the oldest in-progress step was last updated, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oldest_in_progress_age: Option<Duration>,
+    /// Age above which `oldest_in_progress_age` is flagged when displayed.
+    /// Falls back to [`DEFAULT_IN_PROGRESS_WARNING_THRESHOLD`] when unset.
+    #[serde(skip)]
+    pub in_progress_warning_threshold: Option<Duration>,
+    /// Completions in the last week compared with the week before, shown
+    /// when set and not empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub momentum: Option<Trend>,
+    /// Team-specific names for step statuses, used only when rendering
+    #[serde(default, skip_serializing_if = "StatusLabels::is_empty")]
+    pub status_labels: StatusLabels,
 }
 
 impl PlanSummary {
@@ -39,11 +111,22 @@ impl PlanSummary {
             description: plan.description,
             status: plan.status,
             directory: plan.directory,
+            parent_plan_id: plan.parent_plan_id,
+            due_date: plan.due_date,
             created_at: plan.created_at,
             updated_at: plan.updated_at,
             total_steps,
             completed_steps,
             pending_steps: total_steps - completed_steps,
+            in_progress_steps: 0,
+            awaiting_verification_steps: 0,
+            child_plans: 0,
+            child_total_steps: 0,
+            child_completed_steps: 0,
+            oldest_in_progress_age: None,
+            in_progress_warning_threshold: None,
+            momentum: None,
+            status_labels: plan.status_labels,
         }
     }
 }
@@ -51,12 +134,24 @@ impl PlanSummary {
 impl From<&Plan> for PlanSummary {
     fn from(plan: &Plan) -> Self {
         let total_steps = plan.steps.len() as u32;
-        let completed_steps = plan
+        let completed_steps = plan.steps.iter().filter(|step| step.is_complete()).count() as u32;
+        let awaiting_verification_steps = plan
             .steps
             .iter()
-            .filter(|step| step.status == StepStatus::Done)
+            .filter(|step| step.awaiting_verification())
             .count() as u32;
         let pending_steps = total_steps - completed_steps;
+        let in_progress = || {
+            plan.steps
+                .iter()
+                .filter(|step| step.status == StepStatus::InProgress)
+        };
+        let in_progress_steps = in_progress().count() as u32;
+        let now = Timestamp::now();
+        let oldest_in_progress_age = in_progress()
+            .map(|step| step.updated_at)
+            .min()
+            .map(|since| Duration::try_from(now.duration_since(since)).unwrap_or_default());
 
         Self {
             id: plan.id,
@@ -64,11 +159,65 @@ impl From<&Plan> for PlanSummary {
             description: plan.description.clone(),
             status: plan.status,
             directory: plan.directory.clone(),
+            parent_plan_id: plan.parent_plan_id,
+            due_date: plan.due_date,
             created_at: plan.created_at,
             updated_at: plan.updated_at,
             total_steps,
             completed_steps,
             pending_steps,
+            in_progress_steps,
+            awaiting_verification_steps,
+            child_plans: plan.child_plans.len() as u32,
+            child_total_steps: plan.child_plans.iter().map(|c| c.total_steps).sum(),
+            child_completed_steps: plan.child_plans.iter().map(|c| c.completed_steps).sum(),
+            oldest_in_progress_age,
+            in_progress_warning_threshold: None,
+            momentum: None,
+            status_labels: plan.status_labels.clone(),
+        }
+    }
+}
+
+impl PlanSummary {
+    /// Percentage of the plan's steps that are completed, from 0 to 100.
+    ///
+    /// A plan without steps is 0% complete.
+    pub fn progress_percent(&self) -> f64 {
+        if self.total_steps == 0 {
+            return 0.0;
+        }
+        f64::from(self.completed_steps) * 100.0 / f64::from(self.total_steps)
+    }
+
+    /// Fraction of the plan's steps that are completed, from 0 to 1.
+    ///
+    /// A plan without steps has made no progress.
+    pub fn progress_ratio(&self) -> f64 {
+        if self.total_steps == 0 {
+            return 0.0;
         }
+        f64::from(self.completed_steps) / f64::from(self.total_steps)
+    }
+
+    /// Whether the plan has steps and all of them are completed.
+    pub fn is_complete(&self) -> bool {
+        self.total_steps > 0 && self.completed_steps == self.total_steps
+    }
+
+    /// Returns the age of the oldest in-progress step if it exceeds the
+    /// warning threshold.
+    pub fn stale_in_progress_age(&self) -> Option<Duration> {
+        let threshold = self
+            .in_progress_warning_threshold
+            .unwrap_or(DEFAULT_IN_PROGRESS_WARNING_THRESHOLD);
+        self.oldest_in_progress_age.filter(|age| *age > threshold)
+    }
+
+    /// Whether the plan is still active past its due date, as of `today`.
+    ///
+    /// Archived plans are never overdue.
+    pub fn is_overdue(&self, today: Date) -> bool {
+        super::plan::is_overdue(self.due_date, self.status, today)
     }
 }
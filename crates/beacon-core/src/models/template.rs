@@ -0,0 +1,34 @@
+//! Step templates: named checklists of steps to append to plans.
+
+use jiff::Timestamp;
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A step of a [`Template`], added to a plan as a new todo step.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct TemplateStep {
+    /// Title of the step (required, at most 500 characters)
+    pub title: String,
+    /// Optional detailed description of the step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Optional acceptance criteria for the step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acceptance_criteria: Option<String>,
+}
+
+/// A named list of steps, such as a release checklist, that can be
+/// appended to any plan.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Template {
+    /// Unique identifier for the template
+    pub id: u64,
+    /// Name the template is applied by, unique regardless of case
+    pub name: String,
+    /// Steps in the order they are appended
+    pub steps: Vec<TemplateStep>,
+    /// When the template was created
+    pub created_at: Timestamp,
+}
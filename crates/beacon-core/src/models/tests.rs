@@ -1,10 +1,20 @@
 #[cfg(test)]
 mod model_tests {
-    use jiff::Timestamp;
+    use std::time::Duration;
+
+    use jiff::{Timestamp, ToSpan, Zoned, tz::TimeZone};
 
     use crate::{
-        display::LocalDateTime,
-        models::{Plan, PlanFilter, PlanStatus, PlanSummary, Step, StepStatus, UpdateStepRequest},
+        PlannerError,
+        display::{Age, ClaimAge, Elapsed, LocalDateTime, PlanAt, StepAt, StepsMode},
+        models::{
+            AllowedTransitions, ChangeKind, Changelog, CompletionFilter,
+            DEFAULT_IN_PROGRESS_WARNING_THRESHOLD, DuplicateGroup, DuplicateStep,
+            MAX_STATUS_LABEL_LEN, MaintenanceReport, Momentum, Plan, PlanFilter, PlanStatus,
+            PlanSummary, SettingKey, Settings, StatusLabels, Step, StepStateMachine, StepStatus,
+            StepStatusCounts, StepTransition, StepVerification, Trend, UpdateStepRequest,
+            duplicates::normalize_title, parse_date,
+        },
     };
 
     fn create_test_step(status: StepStatus) -> Step {
@@ -14,7 +24,8 @@ mod model_tests {
             title: "Test Step Title".to_string(),
             description: Some("This is a test step description".to_string()),
             acceptance_criteria: Some("Should pass all tests".to_string()),
-            references: vec!["https://example.com".to_string(), "file.txt".to_string()],
+            expected_output: None,
+            references: vec!["https://example.com".into(), "file.txt".into()],
             status,
             result: if status == StepStatus::Done {
                 Some("Successfully completed the test".to_string())
@@ -22,6 +33,15 @@ mod model_tests {
                 None
             },
             order: 2,
+            phase: None,
+            estimate_minutes: None,
+            verification_required: false,
+            verification: None,
+            depends_on: Vec::new(),
+            notes: Vec::new(),
+            blocked_reason: None,
+            started_at: None,
+            completed_at: None,
             created_at: Timestamp::from_second(1640995200).unwrap(), // 2022-01-01 00:00:00 UTC
             updated_at: Timestamp::from_second(1641081600).unwrap(), // 2022-01-02 00:00:00 UTC
         }
@@ -34,6 +54,13 @@ mod model_tests {
             description: Some("This is a test plan".to_string()),
             status: PlanStatus::Active,
             directory: Some("/test/path".to_string()),
+            subpath: None,
+            parent_plan_id: None,
+            auto_archive: false,
+            status_labels: StatusLabels::default(),
+            merged_into_plan_id: None,
+            notes: None,
+            due_date: None,
             created_at: Timestamp::from_second(1640995200).unwrap(),
             updated_at: Timestamp::from_second(1641081600).unwrap(),
             steps: vec![
@@ -41,6 +68,7 @@ mod model_tests {
                 create_test_step(StepStatus::InProgress),
                 create_test_step(StepStatus::Todo),
             ],
+            child_plans: vec![],
         }
     }
 
@@ -51,11 +79,22 @@ mod model_tests {
             description: Some("Summary description".to_string()),
             status: PlanStatus::Active,
             directory: Some("/test/summary".to_string()),
+            parent_plan_id: None,
+            due_date: None,
+            status_labels: StatusLabels::default(),
             created_at: Timestamp::from_second(1640995200).unwrap(),
             updated_at: Timestamp::from_second(1641081600).unwrap(),
             total_steps: 5,
             completed_steps: 2,
             pending_steps: 3,
+            in_progress_steps: 0,
+            awaiting_verification_steps: 0,
+            child_plans: 0,
+            child_total_steps: 0,
+            child_completed_steps: 0,
+            oldest_in_progress_age: None,
+            in_progress_warning_threshold: None,
+            momentum: None,
         }
     }
 
@@ -91,9 +130,9 @@ mod model_tests {
     #[test]
     fn test_step_display_independently_in_progress() {
         let step = create_test_step(StepStatus::InProgress);
-        let output = format!("{}", step);
+        let output = format!("{}", StepAt::new(&step, step.updated_at));
 
-        assert!(output.contains("### 123. Test Step Title (➤ In Progress)"));
+        assert!(output.contains("### 123. Test Step Title (➤ In Progress — claimed <1m ago)"));
         assert!(!output.contains("#### Result"));
     }
 
@@ -107,13 +146,72 @@ mod model_tests {
         assert!(output.contains("Successfully completed the test"));
     }
 
+    #[test]
+    fn test_step_display_expected_output_order() {
+        let position = |output: &str, section: &str| {
+            output
+                .find(section)
+                .unwrap_or_else(|| panic!("missing {section}"))
+        };
+        let mut step = create_test_step(StepStatus::Todo);
+        step.expected_output = Some("A new endpoint POST /v1/tokens".to_string());
+
+        // Before it is done, the expected output leads into the acceptance
+        let output = format!("{step}");
+        assert!(position(&output, "#### Expected") < position(&output, "#### Acceptance"));
+
+        // Once done, it sits right before the result to compare against
+        step.status = StepStatus::Done;
+        step.result = Some("Added POST /v1/tokens issuing signed tokens".to_string());
+        let output = format!("{step}");
+        let expected = position(&output, "#### Expected");
+        assert!(position(&output, "#### Acceptance") < expected);
+        assert!(
+            output[expected..]
+                .starts_with("#### Expected\n\nA new endpoint POST /v1/tokens\n\n#### Result\n")
+        );
+        assert!(!output.contains("⚠"));
+    }
+
+    #[test]
+    fn test_result_may_be_underdocumented_boundary() {
+        let mut step = create_test_step(StepStatus::Done);
+        step.expected_output = Some("x".repeat(40));
+
+        // Exactly a quarter of the expected length is still enough
+        step.result = Some("y".repeat(10));
+        assert!(!step.result_may_be_underdocumented());
+
+        step.result = Some("y".repeat(9));
+        assert!(step.result_may_be_underdocumented());
+        assert!(format!("{step}").contains("⚠ The result is much shorter"));
+
+        // Only done steps with an expected output are flagged
+        step.status = StepStatus::InProgress;
+        assert!(!step.result_may_be_underdocumented());
+        step.status = StepStatus::Done;
+        step.expected_output = None;
+        assert!(!step.result_may_be_underdocumented());
+    }
+
+    #[test]
+    fn test_plan_display_flags_underdocumented_results() {
+        let mut plan = create_test_plan();
+        plan.steps[0].id = 1;
+        plan.steps[0].expected_output = Some("x".repeat(200));
+
+        let output = format!("{plan}");
+        assert!(output.contains("- ⚠ Results shorter than expected output: steps 1\n"));
+    }
+
     #[test]
     fn test_step_display_within_plan_context() {
         let step = create_test_step(StepStatus::InProgress);
         let output = format!("{}", step);
 
-        // Should use consistent formatting with step ID
-        assert!(output.contains("### 123. Test Step Title (➤ In Progress)"));
+        // Should use consistent formatting with step ID; the fixed 2022 update
+        // time is long past the default claim warning threshold
+        assert!(output.contains("### 123. Test Step Title (➤ In Progress — ⚠ claimed "));
         assert!(output.contains("#### Acceptance"));
         assert!(output.contains("#### References"));
     }
@@ -200,6 +298,116 @@ mod model_tests {
         assert!(!output.contains("- **Directory**:"));
     }
 
+    #[test]
+    fn test_plan_summary_stale_in_progress_warning() {
+        const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+        // No in-progress steps: no warning
+        let summary = create_test_plan_summary();
+        assert!(!format!("{summary}").contains('⚠'));
+
+        // At the default threshold exactly: no warning
+        let mut summary = create_test_plan_summary();
+        summary.oldest_in_progress_age = Some(DEFAULT_IN_PROGRESS_WARNING_THRESHOLD);
+        assert!(!format!("{summary}").contains('⚠'));
+
+        // Past the default threshold: warning with age in days
+        summary.oldest_in_progress_age = Some(21 * DAY + Duration::from_secs(3600));
+        assert!(
+            format!("{summary}")
+                .contains("## Test Plan Summary (ID: 789) (2/5) ⚠ step in progress 21d")
+        );
+
+        // A custom threshold moves the boundary
+        summary.oldest_in_progress_age = Some(2 * DAY);
+        assert!(!format!("{summary}").contains('⚠'));
+        summary.in_progress_warning_threshold = Some(DAY);
+        assert!(format!("{summary}").contains("⚠ step in progress 2d"));
+        summary.in_progress_warning_threshold = Some(2 * DAY);
+        assert!(!format!("{summary}").contains('⚠'));
+    }
+
+    #[test]
+    fn test_age_display_units() {
+        assert_eq!(Age(Duration::from_secs(30)).to_string(), "<1m");
+        assert_eq!(Age(Duration::from_secs(12 * 60 + 5)).to_string(), "12m");
+        assert_eq!(Age(Duration::from_secs(5 * 3600 + 59)).to_string(), "5h");
+        assert_eq!(Age(Duration::from_secs(3 * 86400 + 7200)).to_string(), "3d");
+    }
+
+    #[test]
+    fn test_maintenance_report_display() {
+        let mut report = MaintenanceReport {
+            size_before: 8192,
+            size_after: 4096,
+            integrity_problems: Vec::new(),
+        };
+        let output = report.to_string();
+        assert!(output.contains("- Size before: 8192 bytes"));
+        assert!(output.contains("- Reclaimed: 4096 bytes"));
+        assert!(output.contains("- Integrity: ok"));
+
+        report.size_after = 9000;
+        report.integrity_problems = vec!["row 3 missing from index".to_string()];
+        let output = report.to_string();
+        assert!(output.contains("- Reclaimed: 0 bytes"));
+        assert!(output.contains("- Integrity: 1 problem(s) found\n  - row 3 missing from index"));
+    }
+
+    #[test]
+    fn test_elapsed_display_units() {
+        assert_eq!(Elapsed(Duration::from_secs(30)).to_string(), "<1m");
+        assert_eq!(Elapsed(Duration::from_secs(12 * 60 + 5)).to_string(), "12m");
+        assert_eq!(
+            Elapsed(Duration::from_secs(2 * 3600 + 13 * 60)).to_string(),
+            "2h 13m"
+        );
+        assert_eq!(
+            Elapsed(Duration::from_secs(5 * 3600 + 59)).to_string(),
+            "5h"
+        );
+        assert_eq!(
+            Elapsed(Duration::from_secs(3 * 86400 + 4 * 3600)).to_string(),
+            "3d 4h"
+        );
+    }
+
+    #[test]
+    fn test_step_time_tracking_display() {
+        let mut step = create_test_step(StepStatus::Todo);
+        let output = format!("{step}");
+        assert!(!output.contains("Started"));
+        assert!(!output.contains("Completed"));
+        assert_eq!(step.duration(), None);
+
+        let started = Timestamp::from_second(1640995200).unwrap();
+        step.status = StepStatus::InProgress;
+        step.started_at = Some(started);
+        let output = format!("{step}");
+        assert!(output.contains(&format!("- Started: {}", LocalDateTime(&started))));
+        assert!(!output.contains("took"));
+
+        let completed = started + 2.hours().minutes(13);
+        step.status = StepStatus::Done;
+        step.result = Some("Done".to_string());
+        step.completed_at = Some(completed);
+        assert_eq!(
+            step.duration(),
+            Some(Duration::from_secs(2 * 3600 + 13 * 60))
+        );
+        assert!(format!("{step}").contains(&format!(
+            "- Completed: {} (took 2h 13m)",
+            LocalDateTime(&completed)
+        )));
+
+        // Steps completed without being started have no duration
+        step.started_at = None;
+        assert_eq!(step.duration(), None);
+        let output = format!("{step}");
+        assert!(output.contains("- Completed: "));
+        assert!(!output.contains("took"));
+    }
+
     #[test]
     fn test_step_status_display_consistency() {
         // Test that status icons are consistent across all display contexts
@@ -245,6 +453,7 @@ mod model_tests {
         assert_eq!(summary.total_steps, 3);
         assert_eq!(summary.completed_steps, 1); // Only the Done step
         assert_eq!(summary.pending_steps, 2); // InProgress + Todo steps
+        assert_eq!(summary.in_progress_steps, 1);
     }
 
     #[test]
@@ -257,6 +466,7 @@ mod model_tests {
         assert_eq!(summary.total_steps, 0);
         assert_eq!(summary.completed_steps, 0);
         assert_eq!(summary.pending_steps, 0);
+        assert_eq!(summary.in_progress_steps, 0);
     }
 
     #[test]
@@ -272,38 +482,174 @@ mod model_tests {
         assert_eq!(summary.total_steps, 3);
         assert_eq!(summary.completed_steps, 3);
         assert_eq!(summary.pending_steps, 0);
+        assert_eq!(summary.in_progress_steps, 0);
+    }
+
+    #[test]
+    fn test_plan_summary_progress_percent() {
+        let mut plan = create_test_plan();
+
+        // Done, InProgress, Todo: a third complete
+        let summary = PlanSummary::from(&plan);
+        assert!((summary.progress_percent() - 100.0 / 3.0).abs() < 1e-9);
+        assert!((summary.progress_ratio() - 1.0 / 3.0).abs() < 1e-9);
+
+        plan.steps
+            .iter_mut()
+            .for_each(|step| step.status = StepStatus::Done);
+        assert_eq!(PlanSummary::from(&plan).progress_percent(), 100.0);
+        assert_eq!(PlanSummary::from(&plan).progress_ratio(), 1.0);
+
+        // No steps is 0% rather than a division by zero
+        plan.steps.clear();
+        assert_eq!(PlanSummary::from(&plan).progress_percent(), 0.0);
+        assert_eq!(PlanSummary::from(&plan).progress_ratio(), 0.0);
+    }
+
+    #[test]
+    fn test_plan_summary_display_in_progress_count() {
+        let mut summary = create_test_plan_summary();
+        summary.in_progress_steps = 1;
+        assert!(
+            format!("{summary}").contains("## Test Plan Summary (ID: 789) (2/5, 1 in progress)")
+        );
+
+        // Custom labels name the in-progress steps
+        summary
+            .status_labels
+            .set(StepStatus::InProgress, Some("Doing".to_string()));
+        assert!(format!("{summary}").contains("(2/5, 1 doing)"));
+    }
+
+    #[test]
+    fn test_plan_display_status_counts() {
+        let mut plan = create_test_plan();
+        let now = plan.updated_at;
+        assert!(!PlanAt::new(&plan, now).to_string().contains("- Progress"));
+
+        let mut counts = StepStatusCounts {
+            done: 12,
+            in_progress: 2,
+            todo: 26,
+            blocked: 0,
+        };
+        let output = PlanAt::new(&plan, now)
+            .with_status_counts(counts)
+            .to_string();
+        let progress = output
+            .find("- Progress: 12/40 done, 2 in progress, 26 todo\n")
+            .unwrap();
+        assert!(output.find("- Updated:").unwrap() < progress);
+
+        // Blocked steps are mentioned when there are any, in the plan's words
+        counts.blocked = 1;
+        plan.status_labels
+            .set(StepStatus::InProgress, Some("Doing".to_string()));
+        let output = PlanAt::new(&plan, now)
+            .with_status_counts(counts)
+            .to_string();
+        assert!(output.contains("- Progress: 12/41 done, 2 doing, 26 todo, 1 blocked\n"));
+
+        let output = PlanAt::new(&plan, now)
+            .with_status_counts(StepStatusCounts::default())
+            .to_string();
+        assert!(output.contains("- Progress: no steps yet\n"));
+    }
+
+    #[test]
+    fn test_due_date_overdue_display() {
+        let mut plan = create_test_plan();
+        let now = plan.updated_at;
+
+        plan.due_date = Some(jiff::civil::date(2021, 12, 25));
+        assert!(
+            PlanAt::new(&plan, now)
+                .to_string()
+                .contains("- Due: 2021-12-25 (overdue)")
+        );
+
+        plan.due_date = Some(jiff::civil::date(2022, 2, 1));
+        let output = PlanAt::new(&plan, now).to_string();
+        assert!(output.contains("- Due: 2022-02-01\n"));
+        assert!(!output.contains("(overdue)"));
+
+        // Archived plans are never overdue
+        plan.due_date = Some(jiff::civil::date(2021, 12, 25));
+        plan.status = PlanStatus::Archived;
+        assert!(!PlanAt::new(&plan, now).to_string().contains("(overdue)"));
+
+        let mut summary = create_test_plan_summary();
+        summary.due_date = Some(jiff::civil::date(2000, 1, 1));
+        assert!(format!("{summary}").contains("- **Due**: 2000-01-01 (overdue)"));
+        summary.status = PlanStatus::Archived;
+        assert!(!format!("{summary}").contains("(overdue)"));
+        summary.status = PlanStatus::Active;
+        summary.due_date = Some(jiff::civil::date(2999, 1, 1));
+        assert!(!format!("{summary}").contains("(overdue)"));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(
+            parse_date("due_date", " 2025-01-31 ").unwrap(),
+            jiff::civil::date(2025, 1, 31)
+        );
+        for invalid in ["2025-02-30", "31/01/2025", "tomorrow", ""] {
+            let error = parse_date("due_date", invalid).unwrap_err();
+            assert!(
+                error
+                    .to_string()
+                    .contains("Invalid input for field 'due_date'"),
+                "{invalid}: {error}"
+            );
+        }
+
+        let params = crate::params::ListPlans {
+            due_before: Some("soon".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            PlanFilter::try_from(&params),
+            Err(PlannerError::InvalidInput { field, .. }) if field == "due_before"
+        ));
     }
 
     #[test]
     fn test_plan_filter_from_list_plans_active() {
         use crate::params::ListPlans;
 
-        let params = ListPlans { archived: false };
-        let filter: PlanFilter = (&params).into();
-
-        assert_eq!(filter.status, Some(PlanStatus::Active));
-        assert!(!filter.include_archived);
-        assert_eq!(filter.title_contains, None);
-        assert_eq!(filter.directory, None);
-        assert_eq!(filter.created_after, None);
-        assert_eq!(filter.created_before, None);
-        assert_eq!(filter.completion_status, None);
+        let params = ListPlans {
+            archived: false,
+            ..Default::default()
+        };
+        let filter = PlanFilter::try_from(&params).expect("Valid filter");
+
+        assert_eq!(filter.status(), Some(PlanStatus::Active));
+        assert!(!filter.include_archived());
+        assert_eq!(filter.title_contains(), None);
+        assert!(filter.directories().is_empty());
+        assert_eq!(filter.created_after(), None);
+        assert_eq!(filter.created_before(), None);
+        assert_eq!(filter.completion_status(), None);
     }
 
     #[test]
     fn test_plan_filter_from_list_plans_archived() {
         use crate::params::ListPlans;
 
-        let params = ListPlans { archived: true };
-        let filter: PlanFilter = (&params).into();
-
-        assert_eq!(filter.status, Some(PlanStatus::Archived));
-        assert!(filter.include_archived);
-        assert_eq!(filter.title_contains, None);
-        assert_eq!(filter.directory, None);
-        assert_eq!(filter.created_after, None);
-        assert_eq!(filter.created_before, None);
-        assert_eq!(filter.completion_status, None);
+        let params = ListPlans {
+            archived: true,
+            ..Default::default()
+        };
+        let filter = PlanFilter::try_from(&params).expect("Valid filter");
+
+        assert_eq!(filter.status(), Some(PlanStatus::Archived));
+        assert!(filter.include_archived());
+        assert_eq!(filter.title_contains(), None);
+        assert!(filter.directories().is_empty());
+        assert_eq!(filter.created_after(), None);
+        assert_eq!(filter.created_before(), None);
+        assert_eq!(filter.completion_status(), None);
     }
 
     #[test]
@@ -311,14 +657,14 @@ mod model_tests {
         let directory = "/path/to/project".to_string();
         let filter = PlanFilter::for_directory(directory.clone(), false);
 
-        assert_eq!(filter.status, Some(PlanStatus::Active));
-        assert_eq!(filter.directory, Some(directory));
-        assert!(!filter.include_archived);
+        assert_eq!(filter.status(), Some(PlanStatus::Active));
+        assert_eq!(filter.directories(), [directory.as_str()]);
+        assert!(!filter.include_archived());
         // Verify other fields use defaults
-        assert_eq!(filter.title_contains, None);
-        assert_eq!(filter.created_after, None);
-        assert_eq!(filter.created_before, None);
-        assert_eq!(filter.completion_status, None);
+        assert_eq!(filter.title_contains(), None);
+        assert_eq!(filter.created_after(), None);
+        assert_eq!(filter.created_before(), None);
+        assert_eq!(filter.completion_status(), None);
     }
 
     #[test]
@@ -326,14 +672,60 @@ mod model_tests {
         let directory = "/path/to/archived".to_string();
         let filter = PlanFilter::for_directory(directory.clone(), true);
 
-        assert_eq!(filter.status, Some(PlanStatus::Archived));
-        assert_eq!(filter.directory, Some(directory));
-        assert!(filter.include_archived);
+        assert_eq!(filter.status(), Some(PlanStatus::Archived));
+        assert_eq!(filter.directories(), [directory.as_str()]);
+        assert!(filter.include_archived());
         // Verify other fields use defaults
-        assert_eq!(filter.title_contains, None);
-        assert_eq!(filter.created_after, None);
-        assert_eq!(filter.created_before, None);
-        assert_eq!(filter.completion_status, None);
+        assert_eq!(filter.title_contains(), None);
+        assert_eq!(filter.created_after(), None);
+        assert_eq!(filter.created_before(), None);
+        assert_eq!(filter.completion_status(), None);
+    }
+
+    #[test]
+    fn test_plan_filter_builder_validation() {
+        let error = PlanFilter::builder()
+            .status(PlanStatus::Archived)
+            .build()
+            .unwrap_err();
+        assert!(matches!(error, PlannerError::InvalidInput { ref field, .. } if field == "status"));
+
+        // Archived plans are fine once they're included
+        let filter = PlanFilter::builder().archived(true).build().unwrap();
+        assert_eq!(filter.status(), Some(PlanStatus::Archived));
+        assert!(filter.include_archived());
+
+        let now = Timestamp::now();
+        let error = PlanFilter::builder()
+            .created_after(now)
+            .created_before(now - 1.hour())
+            .build()
+            .unwrap_err();
+        assert!(
+            matches!(error, PlannerError::InvalidInput { ref field, .. } if field == "created_after")
+        );
+
+        let error = PlanFilter::builder()
+            .directory_any(["/work/api", " "])
+            .build()
+            .unwrap_err();
+        assert!(
+            matches!(error, PlannerError::InvalidInput { ref field, .. } if field == "directory")
+        );
+
+        let filter = PlanFilter::builder()
+            .directory("/work/api")
+            .directory_any(["/work/web"])
+            .updated_after(now)
+            .completion(CompletionFilter::Incomplete)
+            .build()
+            .unwrap();
+        assert_eq!(filter.directories(), ["/work/api", "/work/web"]);
+        assert_eq!(filter.updated_after(), Some(now));
+        assert_eq!(
+            filter.completion_status(),
+            Some(&CompletionFilter::Incomplete)
+        );
     }
 
     #[test]
@@ -342,7 +734,7 @@ mod model_tests {
             Some("Test Title".to_string()),
             Some("Test Description".to_string()),
             Some("Test Acceptance".to_string()),
-            Some(vec!["ref1.txt".to_string(), "ref2.txt".to_string()]),
+            Some(vec!["ref1.txt".into(), "ref2.txt".into()]),
             Some(StepStatus::Done),
             Some("Test Result".to_string()),
         );
@@ -355,7 +747,7 @@ mod model_tests {
         );
         assert_eq!(
             request.references,
-            Some(vec!["ref1.txt".to_string(), "ref2.txt".to_string()])
+            Some(vec!["ref1.txt".into(), "ref2.txt".into()])
         );
         assert_eq!(request.status, Some(StepStatus::Done));
         assert_eq!(request.result, Some("Test Result".to_string()));
@@ -404,7 +796,7 @@ mod model_tests {
             status: Some("done".to_string()),
             result: Some("Task completed successfully".to_string()),
             acceptance_criteria: Some("Must pass all tests".to_string()),
-            references: Some(vec!["file.txt".to_string()]),
+            references: Some(vec!["file.txt".into()]),
             ..Default::default()
         };
 
@@ -421,7 +813,7 @@ mod model_tests {
             request.acceptance_criteria,
             Some("Must pass all tests".to_string())
         );
-        assert_eq!(request.references, Some(vec!["file.txt".to_string()]));
+        assert_eq!(request.references, Some(vec!["file.txt".into()]));
     }
 
     #[test]
@@ -433,18 +825,11 @@ mod model_tests {
             status: Some("done".to_string()),
             result: None,
             ..Default::default()
-        }; // Missing result for done status
-
-        let result: Result<UpdateStepRequest, _> = params.try_into();
-        assert!(result.is_err());
+        }; // Missing result, checked when updating against the stored one
 
-        match result.unwrap_err() {
-            crate::PlannerError::InvalidInput { field, reason } => {
-                assert_eq!(field, "result");
-                assert!(reason.contains("Result description is required"));
-            }
-            _ => panic!("Expected InvalidInput error"),
-        }
+        let request: UpdateStepRequest = params.try_into().unwrap();
+        assert_eq!(request.status, Some(StepStatus::Done));
+        assert!(request.result.is_none());
     }
 
     #[test]
@@ -486,7 +871,7 @@ mod model_tests {
             Some("New Title".to_string()),
             Some("New Description".to_string()),
             Some("New Acceptance".to_string()),
-            Some(vec!["ref1.txt".to_string(), "ref2.txt".to_string()]),
+            Some(vec!["ref1.txt".into(), "ref2.txt".into()]),
             Some(StepStatus::Done),
             Some("Completed successfully".to_string()),
         );
@@ -499,7 +884,7 @@ mod model_tests {
         );
         assert_eq!(
             request.references,
-            Some(vec!["ref1.txt".to_string(), "ref2.txt".to_string()])
+            Some(vec!["ref1.txt".into(), "ref2.txt".into()])
         );
         assert_eq!(request.status, Some(StepStatus::Done));
         assert_eq!(request.result, Some("Completed successfully".to_string()));
@@ -522,9 +907,9 @@ mod model_tests {
         let directory = "/path/to/project".to_string();
         let filter = PlanFilter::for_directory(directory.clone(), false);
 
-        assert_eq!(filter.status, Some(PlanStatus::Active));
-        assert_eq!(filter.directory, Some(directory));
-        assert!(!filter.include_archived);
+        assert_eq!(filter.status(), Some(PlanStatus::Active));
+        assert_eq!(filter.directories(), [directory.as_str()]);
+        assert!(!filter.include_archived());
     }
 
     #[test]
@@ -532,9 +917,9 @@ mod model_tests {
         let directory = "/path/to/project".to_string();
         let filter = PlanFilter::for_directory(directory.clone(), true);
 
-        assert_eq!(filter.status, Some(PlanStatus::Archived));
-        assert_eq!(filter.directory, Some(directory));
-        assert!(filter.include_archived);
+        assert_eq!(filter.status(), Some(PlanStatus::Archived));
+        assert_eq!(filter.directories(), [directory.as_str()]);
+        assert!(filter.include_archived());
     }
 
     #[test]
@@ -606,21 +991,18 @@ mod model_tests {
             .directory("/test/path".to_string())
             .archived(false);
 
-        assert_eq!(filter.directory, Some("/test/path".to_string()));
-        assert_eq!(filter.status, Some(PlanStatus::Active));
-        assert!(!filter.include_archived);
+        assert_eq!(filter.directories(), ["/test/path"]);
+        assert_eq!(filter.status(), Some(PlanStatus::Active));
+        assert!(!filter.include_archived());
 
         // Test archived builder
         let archived_filter = PlanFilter::new()
             .directory("/archived/path".to_string())
             .archived(true);
 
-        assert_eq!(
-            archived_filter.directory,
-            Some("/archived/path".to_string())
-        );
-        assert_eq!(archived_filter.status, Some(PlanStatus::Archived));
-        assert!(archived_filter.include_archived);
+        assert_eq!(archived_filter.directories(), ["/archived/path"]);
+        assert_eq!(archived_filter.status(), Some(PlanStatus::Archived));
+        assert!(archived_filter.include_archived());
     }
 
     #[test]
@@ -628,13 +1010,13 @@ mod model_tests {
         // Test that const constructor creates proper defaults
         let filter = PlanFilter::new();
 
-        assert_eq!(filter.title_contains, None);
-        assert_eq!(filter.directory, None);
-        assert_eq!(filter.created_after, None);
-        assert_eq!(filter.created_before, None);
-        assert_eq!(filter.completion_status, None);
-        assert_eq!(filter.status, None);
-        assert!(!filter.include_archived);
+        assert_eq!(filter.title_contains(), None);
+        assert!(filter.directories().is_empty());
+        assert_eq!(filter.created_after(), None);
+        assert_eq!(filter.created_before(), None);
+        assert_eq!(filter.completion_status(), None);
+        assert_eq!(filter.status(), None);
+        assert!(!filter.include_archived());
     }
 
     #[test]
@@ -646,10 +1028,20 @@ mod model_tests {
             title: "Test".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: vec![],
             status: StepStatus::Todo,
             result: None,
             order: 0,
+            phase: None,
+            estimate_minutes: None,
+            verification_required: false,
+            verification: None,
+            depends_on: Vec::new(),
+            notes: Vec::new(),
+            blocked_reason: None,
+            started_at: None,
+            completed_at: None,
             created_at: Timestamp::from_second(1640995200).unwrap(),
             updated_at: Timestamp::from_second(1640995200).unwrap(),
         };
@@ -660,9 +1052,17 @@ mod model_tests {
             description: None,
             status: PlanStatus::Active,
             directory: None,
+            subpath: None,
+            parent_plan_id: None,
+            auto_archive: false,
+            status_labels: StatusLabels::default(),
+            merged_into_plan_id: None,
+            notes: None,
+            due_date: None,
             created_at: Timestamp::from_second(1640995200).unwrap(),
             updated_at: Timestamp::from_second(1640995200).unwrap(),
             steps: vec![],
+            child_plans: vec![],
         };
 
         let step_json = serde_json::to_string(&step_empty_refs).unwrap();
@@ -676,17 +1076,8 @@ mod model_tests {
 
         // Test with non-empty vectors
         let step_with_refs = Step {
-            id: 1,
-            plan_id: 1,
-            title: "Test".to_string(),
-            description: None,
-            acceptance_criteria: None,
-            references: vec!["ref1.txt".to_string()],
-            status: StepStatus::Todo,
-            result: None,
-            order: 0,
-            created_at: Timestamp::from_second(1640995200).unwrap(),
-            updated_at: Timestamp::from_second(1640995200).unwrap(),
+            references: vec!["ref1.txt".into()],
+            ..step_empty_refs
         };
 
         let plan_with_steps = Plan {
@@ -695,9 +1086,17 @@ mod model_tests {
             description: None,
             status: PlanStatus::Active,
             directory: None,
+            subpath: None,
+            parent_plan_id: None,
+            auto_archive: false,
+            status_labels: StatusLabels::default(),
+            merged_into_plan_id: None,
+            notes: None,
+            due_date: None,
             created_at: Timestamp::from_second(1640995200).unwrap(),
             updated_at: Timestamp::from_second(1640995200).unwrap(),
             steps: vec![step_with_refs.clone()],
+            child_plans: vec![],
         };
 
         let step_with_refs_json = serde_json::to_string(&step_with_refs).unwrap();
@@ -717,14 +1116,1048 @@ mod model_tests {
             title: "Test".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: vec![],
             status: StepStatus::Todo,
             result: Some("Completed successfully".to_string()),
             order: 0,
+            phase: None,
+            estimate_minutes: None,
+            verification_required: false,
+            verification: None,
+            depends_on: Vec::new(),
+            notes: Vec::new(),
+            blocked_reason: None,
+            started_at: None,
+            completed_at: None,
             created_at: Timestamp::from_second(1640995200).unwrap(),
             updated_at: Timestamp::from_second(1640995200).unwrap(),
         };
         let step_with_result_json = serde_json::to_string(&step_with_result).unwrap();
         assert!(step_with_result_json.contains("\"result\":\"Completed successfully\""));
     }
+
+    #[test]
+    fn test_claim_age_buckets() {
+        let step = create_test_step(StepStatus::InProgress);
+        let claimed_at = step.updated_at;
+        let cases = [
+            (30, "claimed <1m ago"),
+            (4 * 60, "claimed 4m ago"),
+            (3 * 60 * 60 + 59, "claimed 3h ago"),
+            (2 * 24 * 60 * 60, "⚠ claimed 2d ago"),
+        ];
+
+        for (secs, expected) in cases {
+            let now = claimed_at + Duration::from_secs(secs);
+            let output = format!("{}", StepAt::new(&step, now));
+            assert!(
+                output.contains(&format!("(➤ In Progress — {expected})")),
+                "{secs}s: {output}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_claim_age_threshold_switch() {
+        let step = create_test_step(StepStatus::InProgress);
+        let threshold = Duration::from_secs(60 * 60);
+
+        let at = |secs| {
+            let now = step.updated_at + Duration::from_secs(secs);
+            format!(
+                "{}",
+                StepAt::new(&step, now).with_claim_warning_threshold(threshold)
+            )
+        };
+
+        assert!(at(60 * 60).contains("(➤ In Progress — claimed 1h ago)"));
+        assert!(at(60 * 60 + 1).contains("(➤ In Progress — ⚠ claimed 1h ago)"));
+
+        let claim = ClaimAge::between(step.updated_at, step.updated_at, threshold);
+        assert!(!claim.is_stale());
+        // Claims timestamped in the future clamp to zero rather than erroring
+        let skewed = ClaimAge::between(
+            step.updated_at + Duration::from_secs(90),
+            step.updated_at,
+            threshold,
+        );
+        assert_eq!(skewed.age, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_claim_age_only_on_in_progress_steps() {
+        let now = Timestamp::from_second(1641168000).unwrap(); // 2022-01-03 00:00:00 UTC
+        for status in [StepStatus::Todo, StepStatus::Done] {
+            let step = create_test_step(status);
+            assert!(StepAt::new(&step, now).claim_age().is_none());
+            assert!(!format!("{}", StepAt::new(&step, now)).contains("claimed"));
+        }
+
+        let mut plan = create_test_plan();
+        plan.steps = vec![
+            create_test_step(StepStatus::Todo),
+            create_test_step(StepStatus::InProgress),
+        ];
+        let output = format!("{}", PlanAt::new(&plan, now));
+        assert!(output.contains("(○ Todo)"));
+        assert!(output.contains("(➤ In Progress — claimed 1d ago)"));
+    }
+
+    #[test]
+    fn test_plan_display_step_window() {
+        let mut plan = create_test_plan();
+        plan.steps = (0..50)
+            .map(|i| Step {
+                id: i + 1,
+                title: format!("Step number {}", i + 1),
+                order: i as u32,
+                status: StepStatus::Todo,
+                ..create_test_step(StepStatus::Todo)
+            })
+            .collect();
+        let now = plan.updated_at;
+
+        let page = |offset, limit| {
+            PlanAt::new(&plan, now)
+                .with_step_window(offset, limit)
+                .to_string()
+        };
+        let shown = |output: &str| {
+            (1..=50)
+                .filter(|id| output.contains(&format!("### {id}. Step number {id} ")))
+                .collect::<Vec<_>>()
+        };
+
+        let first = page(0, Some(20));
+        assert_eq!(shown(&first), (1..=20).collect::<Vec<_>>());
+        assert!(first.contains("Showing steps 1–20 of 50."));
+
+        let second = page(20, Some(20));
+        assert_eq!(shown(&second), (21..=40).collect::<Vec<_>>());
+        assert!(second.contains("Showing steps 21–40 of 50."));
+
+        let last = page(40, Some(20));
+        assert_eq!(shown(&last), (41..=50).collect::<Vec<_>>());
+        assert!(last.contains("Showing steps 41–50 of 50."));
+
+        let beyond = page(60, Some(20));
+        assert!(shown(&beyond).is_empty());
+        assert!(beyond.contains("No steps in this range; the plan has 50 steps."));
+
+        // Without a window every step is shown and there is no footer
+        let full = PlanAt::new(&plan, now).to_string();
+        assert_eq!(shown(&full).len(), 50);
+        assert!(!full.contains("Showing steps"));
+
+        // The progress from the plan's summary follows the title
+        assert!(full.starts_with("# 789. Test Plan Title\n"));
+        let mut summary = create_test_plan_summary();
+        (summary.completed_steps, summary.total_steps) = (3, 50);
+        let with_progress = PlanAt::new(&plan, now).with_progress(&summary).to_string();
+        assert!(with_progress.starts_with("# 789. Test Plan Title (3/50 done)\n"));
+    }
+
+    #[test]
+    fn test_status_labels_rename_statuses_for_display_only() {
+        let mut plan = create_test_plan();
+        plan.status_labels
+            .set(StepStatus::InProgress, Some("Doing".to_string()));
+        plan.status_labels
+            .set(StepStatus::Done, Some("Shipped".to_string()));
+        let now = plan.updated_at;
+
+        let output = PlanAt::new(&plan, now).to_string();
+        assert!(output.contains("- Status labels: inprogress → Doing, done → Shipped"));
+        assert!(output.contains("(✓ Shipped)"));
+        assert!(output.contains("(➤ Doing"));
+        assert!(output.contains("(○ Todo)"));
+        assert!(!output.contains("In Progress"));
+
+        let step = create_test_step(StepStatus::InProgress);
+        let output = StepAt::new(&step, now)
+            .with_status_labels(&plan.status_labels)
+            .to_string();
+        assert!(output.contains("(➤ Doing"));
+
+        // Stored and serialized statuses keep their canonical names
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(json["steps"][1]["status"], "inprogress");
+        assert_eq!(json["status_labels"]["inprogress"], "Doing");
+
+        // Labels are not accepted as statuses
+        assert_ne!(
+            "Doing".parse::<StepStatus>().ok(),
+            Some(StepStatus::InProgress)
+        );
+
+        let mut summary = create_test_plan_summary();
+        summary.status_labels = plan.status_labels.clone();
+        summary.child_plans = 1;
+        summary.child_total_steps = 2;
+        assert!(summary.to_string().contains("(0/2 steps shipped)"));
+    }
+
+    #[test]
+    fn test_status_labels_validation() {
+        let mut labels = StatusLabels::default();
+        assert!(labels.validate().is_ok());
+
+        labels.set(StepStatus::Todo, Some("  ".to_string()));
+        assert!(labels.validate().unwrap_err().contains("cannot be empty"));
+
+        labels.set(StepStatus::Todo, Some("two\nlines".to_string()));
+        assert!(labels.validate().unwrap_err().contains("single line"));
+
+        labels.set(StepStatus::Todo, Some("x".repeat(MAX_STATUS_LABEL_LEN + 1)));
+        assert!(labels.validate().unwrap_err().contains("at most"));
+
+        labels.set(StepStatus::Todo, Some("Backlog".to_string()));
+        assert!(labels.validate().is_ok());
+        assert_eq!(labels.label(StepStatus::Todo), "Backlog");
+        assert_eq!(labels.label(StepStatus::Done), "Done");
+    }
+
+    #[test]
+    fn test_plan_display_groups_steps_by_phase() {
+        let mut plan = create_test_plan();
+        let phases = [
+            None,
+            Some("Design"),
+            Some("Implementation"),
+            Some("Design"),
+            None,
+        ];
+        plan.steps = phases
+            .into_iter()
+            .enumerate()
+            .map(|(i, phase)| Step {
+                id: i as u64 + 1,
+                title: format!("Step number {}", i + 1),
+                order: i as u32,
+                phase: phase.map(String::from),
+                estimate_minutes: None,
+                ..create_test_step(StepStatus::Todo)
+            })
+            .collect();
+
+        let output = PlanAt::new(&plan, plan.updated_at).to_string();
+        let position = |needle: &str| {
+            output
+                .find(needle)
+                .unwrap_or_else(|| panic!("missing {needle:?} in:\n{output}"))
+        };
+
+        // Sections appear in the order their first step does
+        let steps = position("## Steps");
+        let design = position("## Phase: Design");
+        let implementation = position("## Phase: Implementation");
+        assert!(steps < design && design < implementation);
+        assert_eq!(output.matches("## Phase: Design").count(), 1);
+
+        // Steps keep their order within each section
+        let step = |id: usize| position(&format!("### {id}. Step number {id} "));
+        assert!(steps < step(1) && step(1) < step(5) && step(5) < design);
+        assert!(design < step(2) && step(2) < step(4) && step(4) < implementation);
+        assert!(implementation < step(3));
+
+        // Plans without phases keep a single "Steps" section
+        plan.steps.iter_mut().for_each(|step| step.phase = None);
+        let output = PlanAt::new(&plan, plan.updated_at).to_string();
+        assert_eq!(output.matches("## Steps").count(), 1);
+        assert!(!output.contains("## Phase"));
+    }
+
+    #[test]
+    fn test_plan_display_compact_steps() {
+        let mut plan = create_test_plan();
+        let steps = [
+            (StepStatus::Done, None),
+            (StepStatus::Todo, Some("Design")),
+            (StepStatus::Todo, None),
+        ];
+        plan.steps = steps
+            .into_iter()
+            .enumerate()
+            .map(|(i, (status, phase))| Step {
+                id: i as u64 + 10,
+                title: format!("Step number {}", i + 1),
+                order: i as u32,
+                phase: phase.map(String::from),
+                ..create_test_step(status)
+            })
+            .collect();
+        let render = |offset, limit| {
+            let output = PlanAt::new(&plan, plan.updated_at)
+                .with_step_window(offset, limit)
+                .with_steps_mode(StepsMode::Compact)
+                .to_string();
+            output[output.find("\n## ").unwrap()..].to_string()
+        };
+
+        assert_eq!(
+            render(0, None),
+            "\n## Steps\n\n\
+             - [x] 10. Step number 1 (✓ Done)\n\
+             - [ ] 12. Step number 3 (○ Todo)\n\
+             \n## Phase: Design\n\n\
+             - [ ] 11. Step number 2 (○ Todo)\n"
+        );
+        assert_eq!(
+            render(1, Some(1)),
+            "\n## Phase: Design\n\n\
+             - [ ] 11. Step number 2 (○ Todo)\n\
+             \n\
+             Showing steps 2–2 of 3.\n"
+        );
+
+        // Detailed mode keeps a section per step
+        let detailed = PlanAt::new(&plan, plan.updated_at)
+            .with_steps_mode(StepsMode::Detailed)
+            .to_string();
+        assert_eq!(detailed, PlanAt::new(&plan, plan.updated_at).to_string());
+        assert!(detailed.contains("### 10. Step number 1 (✓ Done)"));
+        assert!(!detailed.contains("- [x]"));
+    }
+
+    #[test]
+    fn test_step_display_verification() {
+        let mut step = create_test_step(StepStatus::Done);
+        step.verification_required = true;
+
+        let output = step.to_string();
+        assert!(output.contains("(✓ Done (awaiting verification))"));
+        assert!(output.contains("#### Verification\n\nAwaiting verification by a second agent"));
+
+        step.verification = Some(StepVerification {
+            verifier: "reviewer".to_string(),
+            comment: Some("Checked the migration output".to_string()),
+            verified_at: step.updated_at,
+        });
+        let output = step.to_string();
+        assert!(output.contains("(✓ Done)"));
+        assert!(output.contains("Verified by reviewer on "));
+        assert!(output.contains("Checked the migration output"));
+
+        // Steps without the requirement have no verification section
+        let output = create_test_step(StepStatus::Done).to_string();
+        assert!(!output.contains("Verification"));
+    }
+
+    fn ts(text: &str) -> Timestamp {
+        text.parse().unwrap()
+    }
+
+    /// A plan with changes spread over two days in UTC, the latest of which
+    /// falls on the next day east of UTC.
+    fn create_changelog_plan() -> Plan {
+        let step = |id, title: &str, status, created: &str, updated: &str| Step {
+            id,
+            plan_id: 789,
+            title: title.to_string(),
+            status,
+            result: (status == StepStatus::Done)
+                .then(|| "Added exponential backoff\nCapped at 5 retries".to_string()),
+            order: id as u32 - 1,
+            created_at: ts(created),
+            updated_at: ts(updated),
+            ..create_test_step(status)
+        };
+        Plan {
+            created_at: ts("2024-11-01T08:00:00Z"),
+            updated_at: ts("2024-11-02T23:30:00Z"),
+            steps: vec![
+                step(
+                    1,
+                    "Implement retries",
+                    StepStatus::Done,
+                    "2024-11-01T09:00:00Z",
+                    "2024-11-02T14:05:00Z",
+                ),
+                step(
+                    2,
+                    "Write docs",
+                    StepStatus::InProgress,
+                    "2024-11-02T10:00:00Z",
+                    "2024-11-02T23:30:00Z",
+                ),
+                step(
+                    3,
+                    "Release",
+                    StepStatus::Todo,
+                    "2024-11-01T09:00:00Z",
+                    "2024-11-01T09:00:00Z",
+                ),
+            ],
+            ..create_test_plan()
+        }
+    }
+
+    #[test]
+    fn test_changelog_groups_days_newest_first() {
+        let changelog =
+            Changelog::from_plan(&create_changelog_plan(), None).with_time_zone(TimeZone::UTC);
+
+        let output = changelog.to_string();
+        let expected = "\
+# Changelog: Test Plan Title (plan 789)
+
+_Reconstructed from timestamps: only the creation and latest change of each step are known, so earlier changes, reorders and removed steps are not shown._
+
+## 2024-11-02 (Sat)
+
+- 23:30 Claimed step 2: Write docs
+- 14:05 Completed step 1: Implement retries — Added exponential backoff
+- 10:00 Created step 2: Write docs
+
+## 2024-11-01 (Fri)
+
+- 09:00 Created step 3: Release
+- 09:00 Created step 1: Implement retries
+- 08:00 Plan created
+";
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_changelog_day_boundaries_follow_time_zone() {
+        let tokyo = TimeZone::fixed(jiff::tz::offset(9));
+        let changelog = Changelog::from_plan(&create_changelog_plan(), None).with_time_zone(tokyo);
+
+        // 23:30 UTC on Nov 2 is past midnight on Nov 3 in UTC+9
+        let days: Vec<_> = changelog
+            .days()
+            .into_iter()
+            .map(|(day, changes)| {
+                let kinds: Vec<_> = changes.iter().map(|change| change.kind).collect();
+                (day.to_string(), kinds)
+            })
+            .collect();
+        assert_eq!(
+            days,
+            [
+                ("2024-11-03".to_string(), vec![ChangeKind::StepClaimed]),
+                (
+                    "2024-11-02".to_string(),
+                    vec![ChangeKind::StepCompleted, ChangeKind::StepCreated]
+                ),
+                (
+                    "2024-11-01".to_string(),
+                    vec![
+                        ChangeKind::StepCreated,
+                        ChangeKind::StepCreated,
+                        ChangeKind::PlanCreated
+                    ]
+                ),
+            ]
+        );
+
+        let output = changelog.to_string();
+        assert!(output.contains("## 2024-11-03 (Sun)\n\n- 08:30 Claimed step 2: Write docs\n"));
+        assert!(output.contains("- 23:05 Completed step 1"));
+    }
+
+    #[test]
+    fn test_changelog_since_and_empty_window() {
+        let plan = create_changelog_plan();
+
+        let changelog = Changelog::from_plan(&plan, Some(ts("2024-11-02T12:00:00Z")))
+            .with_time_zone(TimeZone::UTC);
+        let kinds: Vec<_> = changelog.changes.iter().map(|change| change.kind).collect();
+        assert_eq!(kinds, [ChangeKind::StepClaimed, ChangeKind::StepCompleted]);
+        assert!(
+            changelog
+                .to_string()
+                .contains("Changes since 2024-11-02 12:00 UTC.")
+        );
+
+        let changelog = Changelog::from_plan(&plan, Some(ts("2024-12-01T00:00:00Z")))
+            .with_time_zone(TimeZone::UTC);
+        assert!(changelog.changes.is_empty());
+        assert_eq!(
+            changelog.to_string(),
+            "# Changelog: Test Plan Title (plan 789)\n\nNo changes since 2024-12-01 00:00 UTC.\n"
+        );
+    }
+
+    #[test]
+    fn test_changelog_plan_level_changes() {
+        let mut plan = create_changelog_plan();
+        plan.steps[0].verification = Some(StepVerification {
+            verifier: "reviewer".to_string(),
+            comment: None,
+            verified_at: ts("2024-11-03T09:00:00Z"),
+        });
+        plan.updated_at = ts("2024-11-04T10:00:00Z");
+
+        let changelog = Changelog::from_plan(&plan, None).with_time_zone(TimeZone::UTC);
+        let output = changelog.to_string();
+        assert!(output.contains("## 2024-11-04 (Mon)\n\n- 10:00 Plan updated\n"));
+        assert!(output.contains("- 09:00 Verified step 1: Implement retries (by reviewer)\n"));
+
+        plan.status = PlanStatus::Archived;
+        let changelog = Changelog::from_plan(&plan, None);
+        assert_eq!(changelog.changes[0].kind, ChangeKind::PlanArchived);
+        assert!(
+            !changelog
+                .changes
+                .iter()
+                .any(|change| change.kind == ChangeKind::PlanUpdated)
+        );
+    }
+
+    #[test]
+    fn test_parse_changelog_since() {
+        use crate::models::changelog::parse_since;
+
+        let now = ts("2024-11-10T12:00:00Z").to_zoned(TimeZone::UTC);
+        assert_eq!(
+            parse_since("30d", &now).unwrap(),
+            ts("2024-10-11T12:00:00Z")
+        );
+        assert_eq!(parse_since("2w", &now).unwrap(), ts("2024-10-27T12:00:00Z"));
+        assert_eq!(
+            parse_since("12h", &now).unwrap(),
+            ts("2024-11-10T00:00:00Z")
+        );
+        assert_eq!(
+            parse_since("2024-11-01", &now).unwrap(),
+            ts("2024-11-01T00:00:00Z")
+        );
+
+        for invalid in ["", "soon", "5y", "-3d", "d"] {
+            let error = parse_since(invalid, &now).unwrap_err();
+            assert!(error.to_string().contains("field 'since'"), "{invalid}");
+        }
+    }
+
+    /// Parses a zoned datetime such as `2026-03-09T00:30[America/New_York]`.
+    fn zoned(datetime: &str) -> Zoned {
+        datetime.parse().unwrap()
+    }
+
+    /// Computes momentum at `now` from completions given as zoned datetimes.
+    fn momentum_at(now: &str, completions: &[&str]) -> Momentum {
+        let completions = completions.iter().map(|at| zoned(at).timestamp());
+        Momentum::from_completions(completions, &zoned(now))
+    }
+
+    #[test]
+    fn test_streak_stops_at_gaps() {
+        let momentum = momentum_at(
+            "2026-06-15T18:00[Europe/Berlin]",
+            &[
+                "2026-06-15T09:00[Europe/Berlin]",
+                "2026-06-15T10:00[Europe/Berlin]",
+                "2026-06-13T09:00[Europe/Berlin]",
+                "2026-06-12T09:00[Europe/Berlin]",
+            ],
+        );
+        assert_eq!(momentum.streak_days, 1);
+        assert!(momentum.completed_today);
+        assert!(!momentum.is_empty());
+
+        let none = momentum_at("2026-06-15T18:00[Europe/Berlin]", &[]);
+        assert_eq!(none, Momentum::default());
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn test_streak_not_broken_before_midnight() {
+        // Nothing done today yet, but the streak up to yesterday still counts
+        let momentum = momentum_at(
+            "2026-06-15T23:59[Europe/Berlin]",
+            &[
+                "2026-06-14T23:00[Europe/Berlin]",
+                "2026-06-13T01:00[Europe/Berlin]",
+            ],
+        );
+        assert_eq!(momentum.streak_days, 2);
+        assert!(!momentum.completed_today);
+
+        // Once a whole day passes without completions the streak is over
+        let momentum = momentum_at(
+            "2026-06-16T00:00[Europe/Berlin]",
+            &[
+                "2026-06-14T23:00[Europe/Berlin]",
+                "2026-06-13T01:00[Europe/Berlin]",
+            ],
+        );
+        assert_eq!(momentum.streak_days, 0);
+    }
+
+    #[test]
+    fn test_streak_follows_local_days_across_dst() {
+        // March 8th is only 23 hours long in New York, so the first two
+        // completions are less than a day apart but on consecutive days
+        let momentum = momentum_at(
+            "2026-03-09T00:30[America/New_York]",
+            &[
+                "2026-03-09T00:10[America/New_York]",
+                "2026-03-08T00:10[America/New_York]",
+                "2026-03-07T23:50[America/New_York]",
+            ],
+        );
+        assert_eq!(momentum.streak_days, 3);
+
+        // November 1st is 25 hours long, so these completions are more than
+        // two days apart but still on consecutive days
+        let momentum = momentum_at(
+            "2026-11-02T00:30[America/New_York]",
+            &[
+                "2026-11-01T23:50[America/New_York]",
+                "2026-10-31T00:10[America/New_York]",
+            ],
+        );
+        assert_eq!(momentum.streak_days, 2);
+        assert!(!momentum.completed_today);
+
+        // The same instants fall on other days elsewhere
+        let completions = [zoned("2026-11-01T23:50[America/New_York]").timestamp()];
+        let momentum =
+            Momentum::from_completions(completions, &zoned("2026-11-02T12:00[Asia/Tokyo]"));
+        assert!(momentum.completed_today);
+    }
+
+    #[test]
+    fn test_trend_windows() {
+        let momentum = momentum_at(
+            "2026-06-15T12:00[Europe/Berlin]",
+            &[
+                "2026-06-15T08:00[Europe/Berlin]",
+                "2026-06-09T00:00:01[Europe/Berlin]",
+                "2026-06-08T23:59[Europe/Berlin]",
+                "2026-06-02T00:00[Europe/Berlin]",
+                "2026-06-01T23:59[Europe/Berlin]",
+                // Completions after today are ignored
+                "2026-06-16T08:00[Europe/Berlin]",
+            ],
+        );
+        assert_eq!(
+            momentum.trend,
+            Trend {
+                recent: 2,
+                prior: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_momentum_display() {
+        let trend = |recent, prior| Trend { recent, prior };
+        assert_eq!(trend(5, 2).to_string(), "▲ 5 vs 2");
+        assert_eq!(trend(2, 5).to_string(), "▼ 2 vs 5");
+        assert_eq!(trend(3, 3).to_string(), "= 3 vs 3");
+
+        let momentum = Momentum {
+            streak_days: 3,
+            completed_today: true,
+            trend: trend(5, 2),
+        };
+        assert_eq!(
+            momentum.to_string(),
+            "3-day streak · ▲ 5 vs 2 steps done (last 7 days vs prior 7)"
+        );
+
+        let pending = Momentum {
+            completed_today: false,
+            ..momentum
+        };
+        assert!(
+            pending
+                .to_string()
+                .starts_with("3-day streak (complete a step today")
+        );
+        assert!(
+            Momentum::default()
+                .to_string()
+                .starts_with("No streak yet · = 0 vs 0")
+        );
+    }
+
+    fn duplicate_candidate(step_id: u64, plan_id: u64, title: &str) -> (String, DuplicateStep) {
+        // The database lowercases and trims the key
+        let key = title.trim().to_lowercase();
+        let step = DuplicateStep {
+            step_id,
+            title: title.to_string(),
+            status: StepStatus::Todo,
+            plan_id,
+            plan_title: format!("Plan {plan_id}"),
+            plan_directory: None,
+        };
+        (key, step)
+    }
+
+    #[test]
+    fn test_normalize_title() {
+        assert_eq!(
+            normalize_title("Upgrade to Rust 1.80"),
+            "upgrade to rust 1 80"
+        );
+        assert_eq!(
+            normalize_title("  upgrade   TO rust 1.80!  "),
+            "upgrade to rust 1 80"
+        );
+        assert_eq!(
+            normalize_title("Upgrade to Rust: 1.80"),
+            "upgrade to rust 1 80"
+        );
+        assert_eq!(normalize_title("Don't panic"), "dont panic");
+        assert_eq!(normalize_title("Don’t panic"), "dont panic");
+        assert_eq!(normalize_title("--- ?"), "");
+    }
+
+    #[test]
+    fn test_duplicate_groups() {
+        let groups = DuplicateGroup::group([
+            duplicate_candidate(1, 1, "Upgrade to Rust 1.80"),
+            duplicate_candidate(2, 2, "upgrade to rust 1.80."),
+            duplicate_candidate(3, 3, "  Upgrade  to Rust 1.80  "),
+            // Similar, but different work
+            duplicate_candidate(4, 1, "Upgrade to Rust 1.81"),
+            duplicate_candidate(5, 2, "Upgrade to Rust"),
+            duplicate_candidate(6, 1, "Write docs"),
+            duplicate_candidate(7, 1, "Write docs!"),
+            // Nothing left to compare
+            duplicate_candidate(8, 2, "..."),
+            duplicate_candidate(9, 3, "?"),
+        ]);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].key, "upgrade to rust 1 80");
+        let ids: Vec<u64> = groups[0].steps.iter().map(|step| step.step_id).collect();
+        assert_eq!(ids, [1, 2, 3]);
+        assert_eq!(groups[0].plan_count(), 3);
+
+        // Duplicates within one plan are reported too
+        assert_eq!(groups[1].key, "write docs");
+        assert_eq!(groups[1].plan_count(), 1);
+        assert_eq!(
+            groups[1].to_string(),
+            "## Write docs (2 steps in 1 plan)\n\n\
+             - Step 6 (todo): Write docs — plan 1: Plan 1\n\
+             - Step 7 (todo): Write docs! — plan 1: Plan 1\n\n"
+        );
+    }
+
+    #[test]
+    fn test_step_state_machine_table() {
+        use StepTransition::*;
+
+        // (status, verification required, verified, plan archived, allowed)
+        #[rustfmt::skip]
+        let table: [(StepStatus, bool, bool, bool, &[StepTransition]); 32] = [
+            (StepStatus::Todo, false, false, false, &[Claim, Start, Complete, Block]),
+            (StepStatus::Todo, false, false, true, &[Complete, Block]),
+            (StepStatus::Todo, false, true, false, &[Claim, Start, Complete, Block]),
+            (StepStatus::Todo, false, true, true, &[Complete, Block]),
+            (StepStatus::Todo, true, false, false, &[Claim, Start, Complete, Block]),
+            (StepStatus::Todo, true, false, true, &[Complete, Block]),
+            (StepStatus::Todo, true, true, false, &[Claim, Start, Complete, Block]),
+            (StepStatus::Todo, true, true, true, &[Complete, Block]),
+            (StepStatus::InProgress, false, false, false, &[Release, Complete, Reopen, Block]),
+            (StepStatus::InProgress, false, false, true, &[Release, Complete, Reopen, Block]),
+            (StepStatus::InProgress, false, true, false, &[Release, Complete, Reopen, Block]),
+            (StepStatus::InProgress, false, true, true, &[Release, Complete, Reopen, Block]),
+            (StepStatus::InProgress, true, false, false, &[Release, Complete, Reopen, Block]),
+            (StepStatus::InProgress, true, false, true, &[Release, Complete, Reopen, Block]),
+            (StepStatus::InProgress, true, true, false, &[Release, Complete, Reopen, Block]),
+            (StepStatus::InProgress, true, true, true, &[Release, Complete, Reopen, Block]),
+            (StepStatus::Done, false, false, false, &[Start, Reopen]),
+            (StepStatus::Done, false, false, true, &[Reopen]),
+            (StepStatus::Done, false, true, false, &[Start, Reopen]),
+            (StepStatus::Done, false, true, true, &[Reopen]),
+            (StepStatus::Done, true, false, false, &[Start, Reopen, Verify]),
+            (StepStatus::Done, true, false, true, &[Reopen, Verify]),
+            (StepStatus::Done, true, true, false, &[Start, Reopen]),
+            (StepStatus::Done, true, true, true, &[Reopen]),
+            (StepStatus::Blocked, false, false, false, &[Start, Complete, Reopen]),
+            (StepStatus::Blocked, false, false, true, &[Complete, Reopen]),
+            (StepStatus::Blocked, false, true, false, &[Start, Complete, Reopen]),
+            (StepStatus::Blocked, false, true, true, &[Complete, Reopen]),
+            (StepStatus::Blocked, true, false, false, &[Start, Complete, Reopen]),
+            (StepStatus::Blocked, true, false, true, &[Complete, Reopen]),
+            (StepStatus::Blocked, true, true, false, &[Start, Complete, Reopen]),
+            (StepStatus::Blocked, true, true, true, &[Complete, Reopen]),
+        ];
+
+        for (status, verification_required, verified, plan_archived, allowed) in table {
+            let machine = StepStateMachine {
+                status,
+                verification_required,
+                verified_by: verified.then(|| "reviewer".to_string()),
+                plan_archived,
+                blocked_by: Vec::new(),
+            };
+            for check in machine.transitions() {
+                assert_eq!(
+                    check.is_allowed(),
+                    allowed.contains(&check.transition),
+                    "{:?} from {machine:?}: {:?}",
+                    check.transition,
+                    check.denied
+                );
+                assert_eq!(machine.check(check.transition).err(), check.denied);
+            }
+        }
+    }
+
+    #[test]
+    fn test_step_state_machine_status_table() {
+        use StepTransition::*;
+
+        const DEPENDENCIES: &str = "is blocked by step 2 which is not done yet";
+
+        // (status, transition, denied without dependencies, denied while
+        // step 2 is unfinished)
+        #[rustfmt::skip]
+        let table: [(StepStatus, StepTransition, Option<&str>, Option<&str>); 28] = [
+            (StepStatus::Todo, Claim, None, Some(DEPENDENCIES)),
+            (StepStatus::Todo, Release, Some("is not in progress"), Some("is not in progress")),
+            (StepStatus::Todo, Start, None, Some(DEPENDENCIES)),
+            (StepStatus::Todo, Complete, None, Some(DEPENDENCIES)),
+            (StepStatus::Todo, Reopen, Some("is already todo"), Some("is already todo")),
+            (StepStatus::Todo, Block, None, None),
+            (StepStatus::Todo, Verify, Some("does not require verification"), Some("does not require verification")),
+            (StepStatus::InProgress, Claim, Some("is already in progress"), Some("is already in progress")),
+            (StepStatus::InProgress, Release, None, None),
+            (StepStatus::InProgress, Start, Some("is already in progress"), Some("is already in progress")),
+            (StepStatus::InProgress, Complete, None, Some(DEPENDENCIES)),
+            (StepStatus::InProgress, Reopen, None, None),
+            (StepStatus::InProgress, Block, None, None),
+            (StepStatus::InProgress, Verify, Some("does not require verification"), Some("does not require verification")),
+            (StepStatus::Done, Claim, Some("is already done"), Some("is already done")),
+            (StepStatus::Done, Release, Some("is already done"), Some("is already done")),
+            (StepStatus::Done, Start, None, Some(DEPENDENCIES)),
+            (StepStatus::Done, Complete, Some("is already done"), Some("is already done")),
+            (StepStatus::Done, Reopen, None, None),
+            (StepStatus::Done, Block, Some("is already done; reopen it before blocking it"), Some("is already done; reopen it before blocking it")),
+            (StepStatus::Done, Verify, Some("does not require verification"), Some("does not require verification")),
+            (StepStatus::Blocked, Claim, Some("is blocked; set it back to todo once it is unblocked"), Some("is blocked; set it back to todo once it is unblocked")),
+            (StepStatus::Blocked, Release, Some("is not in progress"), Some("is not in progress")),
+            (StepStatus::Blocked, Start, None, Some(DEPENDENCIES)),
+            (StepStatus::Blocked, Complete, None, Some(DEPENDENCIES)),
+            (StepStatus::Blocked, Reopen, None, None),
+            (StepStatus::Blocked, Block, Some("is already blocked"), Some("is already blocked")),
+            (StepStatus::Blocked, Verify, Some("does not require verification"), Some("does not require verification")),
+        ];
+
+        for (status, transition, denied, denied_with_dependencies) in table {
+            let mut machine = StepStateMachine {
+                status,
+                verification_required: false,
+                verified_by: None,
+                plan_archived: false,
+                blocked_by: Vec::new(),
+            };
+            assert_eq!(
+                machine.check(transition).err().as_deref(),
+                denied,
+                "{transition:?} from {status:?}"
+            );
+            machine.blocked_by.push(2);
+            assert_eq!(
+                machine.check(transition).err().as_deref(),
+                denied_with_dependencies,
+                "{transition:?} from {status:?} with dependencies"
+            );
+        }
+    }
+
+    #[test]
+    fn test_step_state_machine_reasons() {
+        let mut machine = StepStateMachine {
+            status: StepStatus::InProgress,
+            verification_required: false,
+            verified_by: None,
+            plan_archived: false,
+            blocked_by: Vec::new(),
+        };
+        let reason =
+            |machine: &StepStateMachine, transition| machine.check(transition).unwrap_err();
+
+        assert_eq!(
+            reason(&machine, StepTransition::Claim),
+            "is already in progress"
+        );
+        assert_eq!(
+            reason(&machine, StepTransition::Verify),
+            "does not require verification"
+        );
+
+        machine.verification_required = true;
+        assert_eq!(
+            reason(&machine, StepTransition::Verify),
+            "must be done before it can be verified"
+        );
+
+        machine.status = StepStatus::Done;
+        machine.verified_by = Some("reviewer".into());
+        assert_eq!(reason(&machine, StepTransition::Claim), "is already done");
+        assert_eq!(
+            reason(&machine, StepTransition::Verify),
+            "was already verified by reviewer"
+        );
+
+        // An archived plan takes precedence over the step's own status
+        machine.plan_archived = true;
+        assert_eq!(
+            reason(&machine, StepTransition::Claim),
+            "belongs to an archived plan; unarchive the plan to work on it"
+        );
+    }
+
+    #[test]
+    fn test_step_state_machine_unfinished_dependencies() {
+        let mut machine = StepStateMachine {
+            status: StepStatus::Todo,
+            verification_required: false,
+            verified_by: None,
+            plan_archived: false,
+            blocked_by: vec![2],
+        };
+        assert_eq!(
+            machine.check(StepTransition::Claim).unwrap_err(),
+            "is blocked by step 2 which is not done yet"
+        );
+        assert_eq!(
+            machine.check(StepTransition::Start).unwrap_err(),
+            "is blocked by step 2 which is not done yet"
+        );
+
+        machine.blocked_by.push(3);
+        assert_eq!(
+            machine.check(StepTransition::Claim).unwrap_err(),
+            "is blocked by steps 2, 3 which are not done yet"
+        );
+
+        // A step already in progress stays so when dependencies are added,
+        // but is not done before they are
+        machine.status = StepStatus::InProgress;
+        assert!(machine.check(StepTransition::Release).is_ok());
+        assert_eq!(
+            machine.check(StepTransition::Complete).unwrap_err(),
+            "is blocked by steps 2, 3 which are not done yet"
+        );
+
+        machine.status = StepStatus::Todo;
+        machine.blocked_by.clear();
+        assert!(machine.check(StepTransition::Claim).is_ok());
+        assert!(machine.check(StepTransition::Start).is_ok());
+    }
+
+    #[test]
+    fn test_allowed_transitions_display() {
+        let machine = StepStateMachine {
+            status: StepStatus::Done,
+            verification_required: true,
+            verified_by: None,
+            plan_archived: false,
+            blocked_by: Vec::new(),
+        };
+        let transitions = AllowedTransitions::new(7, &machine);
+        assert!(transitions.allows(StepTransition::Verify));
+        assert!(!transitions.allows(StepTransition::Claim));
+        assert_eq!(
+            transitions.to_string(),
+            "# Transitions of Step 7 (done)\n\n\
+             - claim → inprogress: not allowed, Step 7 is already done\n\
+             - release → todo: not allowed, Step 7 is already done\n\
+             - start → inprogress: allowed\n\
+             - complete → done: not allowed, Step 7 is already done\n\
+             - reopen → todo: allowed\n\
+             - block → blocked: not allowed, Step 7 is already done; reopen it before blocking it\n\
+             - verify: allowed\n"
+        );
+    }
+
+    #[test]
+    fn test_setting_validation_per_key() {
+        let mut settings = Settings::default();
+
+        for (value, expected) in [
+            ("true", true),
+            ("off", false),
+            (" YES ", true),
+            ("0", false),
+        ] {
+            settings.set(SettingKey::AutoArchive, value).unwrap();
+            assert_eq!(settings.auto_archive, expected, "{value}");
+        }
+        assert!(settings.set(SettingKey::AutoArchive, "maybe").is_err());
+        assert!(settings.set(SettingKey::InferGitRoot, "").is_err());
+
+        settings
+            .set(SettingKey::InProgressWarningThreshold, "36h")
+            .unwrap();
+        assert_eq!(
+            settings.in_progress_warning_threshold,
+            Duration::from_secs(36 * 60 * 60)
+        );
+        assert_eq!(
+            settings.get(SettingKey::InProgressWarningThreshold),
+            "1d 12h"
+        );
+        settings
+            .set(SettingKey::InProgressWarningThreshold, "168h")
+            .unwrap();
+        assert_eq!(settings.get(SettingKey::InProgressWarningThreshold), "7d");
+        for invalid in ["0s", "-2d", "soon", "2mo"] {
+            let error = settings
+                .set(SettingKey::InProgressWarningThreshold, invalid)
+                .unwrap_err();
+            assert!(error.contains("positive duration"), "{invalid}: {error}");
+        }
+
+        settings.set(SettingKey::MaxResultLength, "500").unwrap();
+        assert_eq!(settings.max_result_length, Some(500));
+        assert!(settings.check_result_length(&"x".repeat(500)).is_ok());
+        assert!(settings.check_result_length(&"é".repeat(501)).is_err());
+        for invalid in ["0", "-1", "lots"] {
+            assert!(settings.set(SettingKey::MaxResultLength, invalid).is_err());
+        }
+        settings.set(SettingKey::MaxResultLength, "none").unwrap();
+        assert_eq!(settings.max_result_length, None);
+        assert_eq!(settings.get(SettingKey::MaxResultLength), "none");
+
+        // Rejected values leave the setting as it was
+        assert!(!settings.unique_plan_per_directory);
+        assert_eq!(
+            "nope".parse::<SettingKey>().unwrap_err(),
+            "Unknown setting 'nope'; expected one of: auto_archive, \
+             in_progress_warning_threshold, max_result_length, unique_plan_per_directory, \
+             infer_git_root, strict_references"
+        );
+    }
+
+    #[test]
+    fn test_settings_round_trip_through_stored_values() {
+        let mut settings = Settings::default();
+        settings.set(SettingKey::AutoArchive, "true").unwrap();
+        settings
+            .set(SettingKey::InProgressWarningThreshold, "90m")
+            .unwrap();
+        settings.set(SettingKey::MaxResultLength, "2000").unwrap();
+
+        let entries: Vec<(&str, String)> = SettingKey::ALL
+            .into_iter()
+            .map(|key| (key.as_str(), settings.get(key)))
+            .collect();
+        assert_eq!(Settings::from_entries(entries), settings);
+
+        // Hand-edited rows that don't parse keep the defaults
+        let settings = Settings::from_entries([
+            ("auto_archive", "sometimes"),
+            ("colour", "blue"),
+            ("infer_git_root", "true"),
+        ]);
+        assert_eq!(
+            settings,
+            Settings {
+                infer_git_root: true,
+                ..Settings::default()
+            }
+        );
+    }
 }
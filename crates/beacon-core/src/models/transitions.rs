@@ -0,0 +1,236 @@
+//! The transitions a step can undergo, decided in one place.
+//!
+//! Every code path that changes a step's status, or verifies it, asks the
+//! [`StepStateMachine`] first, so that the rules live here rather than being
+//! spread over the operations, and so that "what can this step do next?" can
+//! be answered before acting.
+//!
+//! What a step may do depends on its status, on whether its completion must
+//! be verified and already was, and on whether its plan is archived: no new
+//! work is started in archived plans, though their steps can still be
+//! completed or reopened. A step is neither claimed, started nor completed
+//! while steps it depends on are unfinished, and is not claimed while it is
+//! blocked. A transition that would leave the status as it is, such as
+//! reopening a todo step, is refused, as is blocking a done step.
+
+use super::StepStatus;
+
+/// A change of status, or a verification, that a step can undergo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StepTransition {
+    /// Atomically take a todo step to in progress
+    Claim,
+    /// Atomically put an in-progress step back to todo, giving up a claim
+    Release,
+    /// Set the status to in progress from any other status
+    Start,
+    /// Set the status to done, recording a result
+    Complete,
+    /// Set the status back to todo
+    Reopen,
+    /// Set the status to blocked, noting why
+    Block,
+    /// Confirm the result of a done step that requires verification
+    Verify,
+}
+
+impl StepTransition {
+    /// All transitions, in the order they are listed.
+    pub const ALL: [Self; 7] = [
+        Self::Claim,
+        Self::Release,
+        Self::Start,
+        Self::Complete,
+        Self::Reopen,
+        Self::Block,
+        Self::Verify,
+    ];
+
+    /// The transition made by setting a step's status to `status`.
+    pub fn for_status(status: StepStatus) -> Self {
+        match status {
+            StepStatus::Todo => Self::Reopen,
+            StepStatus::InProgress => Self::Start,
+            StepStatus::Done => Self::Complete,
+            StepStatus::Blocked => Self::Block,
+        }
+    }
+
+    /// The status a step has after the transition, or `None` if its status
+    /// stays the same.
+    pub fn target(self) -> Option<StepStatus> {
+        match self {
+            Self::Claim | Self::Start => Some(StepStatus::InProgress),
+            Self::Complete => Some(StepStatus::Done),
+            Self::Release | Self::Reopen => Some(StepStatus::Todo),
+            Self::Block => Some(StepStatus::Blocked),
+            Self::Verify => None,
+        }
+    }
+
+    /// Name of the transition as used by tools.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Claim => "claim",
+            Self::Release => "release",
+            Self::Start => "start",
+            Self::Complete => "complete",
+            Self::Reopen => "reopen",
+            Self::Block => "block",
+            Self::Verify => "verify",
+        }
+    }
+}
+
+/// Everything about a step and its plan that decides which transitions it
+/// may undergo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepStateMachine {
+    pub status: StepStatus,
+    /// Whether the step's completion must be verified by a second agent
+    pub verification_required: bool,
+    /// Who verified the step's current completion, if anyone
+    pub verified_by: Option<String>,
+    /// Whether the step's plan is archived
+    pub plan_archived: bool,
+    /// IDs of the steps the step depends on that are not done yet
+    pub blocked_by: Vec<u64>,
+}
+
+impl StepStateMachine {
+    /// Checks whether the step may undergo `transition`.
+    ///
+    /// # Errors
+    ///
+    /// Returns why the transition is not allowed, phrased to follow the
+    /// step's name, e.g. "is already done".
+    pub fn check(&self, transition: StepTransition) -> Result<(), String> {
+        match transition {
+            StepTransition::Claim | StepTransition::Start if self.plan_archived => {
+                Err("belongs to an archived plan; unarchive the plan to work on it".into())
+            }
+            StepTransition::Claim => match self.status {
+                StepStatus::Todo => self.check_dependencies(),
+                StepStatus::InProgress => Err("is already in progress".into()),
+                StepStatus::Done => Err("is already done".into()),
+                StepStatus::Blocked => {
+                    Err("is blocked; set it back to todo once it is unblocked".into())
+                }
+            },
+            StepTransition::Release => match self.status {
+                StepStatus::InProgress => Ok(()),
+                StepStatus::Todo | StepStatus::Blocked => Err("is not in progress".into()),
+                StepStatus::Done => Err("is already done".into()),
+            },
+            StepTransition::Start => match self.status {
+                StepStatus::InProgress => Err("is already in progress".into()),
+                StepStatus::Todo | StepStatus::Done | StepStatus::Blocked => {
+                    self.check_dependencies()
+                }
+            },
+            StepTransition::Complete => match self.status {
+                StepStatus::Done => Err("is already done".into()),
+                StepStatus::Todo | StepStatus::InProgress | StepStatus::Blocked => {
+                    self.check_dependencies()
+                }
+            },
+            StepTransition::Reopen => match self.status {
+                StepStatus::Todo => Err("is already todo".into()),
+                StepStatus::InProgress | StepStatus::Done | StepStatus::Blocked => Ok(()),
+            },
+            StepTransition::Block => match self.status {
+                StepStatus::Blocked => Err("is already blocked".into()),
+                StepStatus::Done => Err("is already done; reopen it before blocking it".into()),
+                StepStatus::Todo | StepStatus::InProgress => Ok(()),
+            },
+            StepTransition::Verify if !self.verification_required => {
+                Err("does not require verification".into())
+            }
+            StepTransition::Verify if self.status != StepStatus::Done => {
+                Err("must be done before it can be verified".into())
+            }
+            StepTransition::Verify => match &self.verified_by {
+                Some(verifier) => Err(format!("was already verified by {verifier}")),
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Checks that every step the step depends on is done.
+    fn check_dependencies(&self) -> Result<(), String> {
+        if self.blocked_by.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "is blocked by {}",
+                blocking_steps(&self.blocked_by)
+            ))
+        }
+    }
+
+    /// Checks every transition, allowed ones and those that are not.
+    pub fn transitions(&self) -> Vec<TransitionCheck> {
+        StepTransition::ALL
+            .into_iter()
+            .map(|transition| TransitionCheck {
+                transition,
+                denied: self.check(transition).err(),
+            })
+            .collect()
+    }
+}
+
+/// Names unfinished steps, e.g. "step 2 which is not done yet" or
+/// "steps 2, 3 which are not done yet".
+fn blocking_steps(ids: &[u64]) -> String {
+    let list = ids
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    match ids {
+        [_] => format!("step {list} which is not done yet"),
+        _ => format!("steps {list} which are not done yet"),
+    }
+}
+
+/// Whether a step may undergo a transition, and why not if it may not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransitionCheck {
+    pub transition: StepTransition,
+    /// Why the transition is not allowed, or `None` if it is
+    pub denied: Option<String>,
+}
+
+impl TransitionCheck {
+    /// Whether the transition is allowed.
+    pub fn is_allowed(&self) -> bool {
+        self.denied.is_none()
+    }
+}
+
+/// The transitions a step may and may not undergo next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowedTransitions {
+    pub step_id: u64,
+    pub status: StepStatus,
+    pub transitions: Vec<TransitionCheck>,
+}
+
+impl AllowedTransitions {
+    /// Checks every transition of step `step_id`.
+    pub fn new(step_id: u64, machine: &StepStateMachine) -> Self {
+        Self {
+            step_id,
+            status: machine.status,
+            transitions: machine.transitions(),
+        }
+    }
+
+    /// Whether `transition` is allowed.
+    pub fn allows(&self, transition: StepTransition) -> bool {
+        self.transitions
+            .iter()
+            .any(|check| check.transition == transition && check.is_allowed())
+    }
+}
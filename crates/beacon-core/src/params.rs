@@ -8,10 +8,62 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::models::{StatusLabels, StepReference, TemplateStep};
+
+/// Maximum length of a plan or step title, in characters.
+pub const MAX_TITLE_LEN: usize = 500;
+
+/// Maximum length of a description, acceptance criteria or expected output,
+/// in bytes.
+pub const MAX_DESCRIPTION_LEN: usize = 64 * 1024;
+
+/// Trims a title, which must not be empty or longer than [`MAX_TITLE_LEN`]
+/// characters.
+///
+/// # Errors
+///
+/// * `PlannerError::InvalidInput` - When the title is blank or too long
+pub(crate) fn validate_title(field: &str, title: &str) -> crate::Result<String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(crate::PlannerError::invalid_input(field)
+            .with_reason("A title cannot be empty or only whitespace"));
+    }
+    let len = title.chars().count();
+    if len > MAX_TITLE_LEN {
+        return Err(
+            crate::PlannerError::invalid_input(field).with_reason(format!(
+                "A title can be at most {MAX_TITLE_LEN} characters long, got {len}"
+            )),
+        );
+    }
+    Ok(title.to_string())
+}
+
+/// Trims a long text field such as a description, which must not be longer
+/// than [`MAX_DESCRIPTION_LEN`] bytes.
+///
+/// # Errors
+///
+/// * `PlannerError::InvalidInput` - When the text is too long
+pub(crate) fn validate_text(field: &str, text: Option<&str>) -> crate::Result<Option<String>> {
+    let Some(text) = text.map(str::trim) else {
+        return Ok(None);
+    };
+    if text.len() > MAX_DESCRIPTION_LEN {
+        return Err(
+            crate::PlannerError::invalid_input(field).with_reason(format!(
+                "Text can be at most {MAX_DESCRIPTION_LEN} bytes long, got {}",
+                text.len()
+            )),
+        );
+    }
+    Ok(Some(text.to_string()))
+}
+
 /// Generic parameters for operations requiring just an ID.
 ///
-/// Used for operations like show_plan, archive_plan, unarchive_plan, show_step,
-/// claim_step.
+/// Used for operations like show_plan, unarchive_plan, show_step, claim_step.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Id {
@@ -19,6 +71,27 @@ pub struct Id {
     pub id: u64,
 }
 
+/// Parameters for showing a plan.
+///
+/// Plans with many steps can be paged through with `step_offset` and
+/// `step_limit`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ShowPlan {
+    /// The ID of the plan to show
+    pub id: u64,
+    /// Number of steps to skip before the first one shown (0-based)
+    #[serde(default)]
+    pub step_offset: usize,
+    /// Maximum number of steps to show; all remaining steps if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_limit: Option<usize>,
+    /// Show each step as one checklist line with its ID, title and status
+    /// instead of all its sections
+    #[serde(default)]
+    pub compact: bool,
+}
+
 /// Parameters for creating a new plan.
 ///
 /// Used to create a new task plan with a title, optional description, and
@@ -26,12 +99,128 @@ pub struct Id {
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct CreatePlan {
-    /// Title of the plan (required)
+    /// Title of the plan (required, at most 500 characters)
     pub title: String,
-    /// Optional detailed description of the plan
+    /// Optional detailed description of the plan (at most 64 KB)
     pub description: Option<String>,
     /// Optional working directory for the plan
     pub directory: Option<String>,
+    /// Store the enclosing git repository root instead of the given (or
+    /// current) directory when it lies inside a git work tree. The path below
+    /// the root is kept as the plan's subpath. Defaults to the planner's
+    /// configuration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub infer_git_root: Option<bool>,
+    /// Optional ID of a parent plan to create this plan under. The parent
+    /// must not itself be a child plan.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_plan_id: Option<u64>,
+    /// Archive the plan automatically once all of its steps are done.
+    /// Defaults to the workspace's `auto_archive` setting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_archive: Option<bool>,
+    /// Optional date the plan should be done by, as YYYY-MM-DD. Active plans
+    /// past it are shown as overdue.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<String>,
+}
+
+impl CreatePlan {
+    /// Returns the parameters with the title and description trimmed.
+    ///
+    /// # Errors
+    ///
+    /// * `PlannerError::InvalidInput` - When the title is blank or longer
+    ///   than [`MAX_TITLE_LEN`], or the description is longer than
+    ///   [`MAX_DESCRIPTION_LEN`]
+    pub fn validate(&self) -> crate::Result<Self> {
+        Ok(Self {
+            title: validate_title("title", &self.title)?,
+            description: validate_text("description", self.description.as_deref())?,
+            ..self.clone()
+        })
+    }
+}
+
+/// Parameters for updating an existing plan.
+///
+/// Allows partial updates to plan properties, including moving the plan under
+/// a parent plan or detaching it from its parent. Fields left unset keep
+/// their current values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct UpdatePlan {
+    /// Plan ID to update (required)
+    pub id: u64,
+    /// Updated title of the plan (at most 500 characters)
+    pub title: Option<String>,
+    /// Updated description of the plan (at most 64 KB)
+    pub description: Option<String>,
+    /// Updated working directory for the plan. Relative paths are made
+    /// absolute, as when creating a plan.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directory: Option<String>,
+    /// ID of the plan to make this plan a child of. The parent must not
+    /// itself be a child plan, and this plan must not have children.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_plan_id: Option<u64>,
+    /// Detach the plan from its parent, making it a top-level plan again
+    #[serde(default)]
+    pub clear_parent: bool,
+    /// Turn archiving the plan on completion of its last step on or off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_archive: Option<bool>,
+    /// Team-specific display names for step statuses, keyed by canonical
+    /// status (todo, inprogress, done, blocked), e.g. {"inprogress":
+    /// "doing"}. Only rendering changes; statuses are still set with
+    /// canonical names.
+    /// Replaces any existing labels; an empty object clears them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_labels: Option<StatusLabels>,
+    /// Updated date the plan should be done by, as YYYY-MM-DD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<String>,
+    /// Remove the plan's due date
+    #[serde(default)]
+    pub clear_due_date: bool,
+}
+
+/// Parameters for archiving a plan.
+///
+/// Archiving a parent plan while any of its child plans are still active is
+/// refused unless `force` is set, as is archiving a plan with steps that are
+/// not done with [`Planner::archive_plan_checked`](crate::Planner::archive_plan_checked).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ArchivePlan {
+    /// The ID of the plan to archive
+    pub id: u64,
+    /// Archive even if the plan has active child plans or steps that are not
+    /// done
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Parameters for archiving every completed plan at once.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ArchiveCompletedPlans {
+    /// Only archive plans in this directory or below it
+    #[serde(default)]
+    pub directory: Option<String>,
+}
+
+/// Parameters for putting every step of a plan back to todo, so that a
+/// recurring plan can be worked through again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ResetPlanSteps {
+    /// The ID of the plan whose steps to reset
+    pub id: u64,
+    /// Keep the results of the steps that were done instead of clearing
+    /// them
+    #[serde(default)]
+    pub keep_results: bool,
 }
 
 /// Parameters for listing plans.
@@ -43,12 +232,57 @@ pub struct ListPlans {
     /// Whether to show archived plans instead of active ones
     #[serde(default)]
     pub archived: bool,
+    /// Only list the child plans of the plan with this ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub children_of: Option<u64>,
+    /// Only list plans with this completion status: 'complete' (all steps
+    /// done), 'incomplete' (some step not done), or 'empty' (no steps)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completion: Option<String>,
+    /// Only list plans due on or before this date (YYYY-MM-DD). Plans
+    /// without a due date are left out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_before: Option<String>,
+    /// Maximum number of plans to return
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    /// Number of plans to skip before the first one returned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+}
+
+impl ListPlans {
+    /// Parses the completion filter.
+    ///
+    /// # Errors
+    ///
+    /// * `PlannerError::InvalidInput` - When the completion string is invalid
+    pub fn validate(&self) -> crate::Result<Option<crate::models::CompletionFilter>> {
+        use crate::models::CompletionFilter;
+
+        let Some(completion) = &self.completion else {
+            return Ok(None);
+        };
+        match completion.to_lowercase().as_str() {
+            "complete" => Ok(Some(CompletionFilter::Complete)),
+            "incomplete" => Ok(Some(CompletionFilter::Incomplete)),
+            "empty" => Ok(Some(CompletionFilter::Empty)),
+            _ => Err(crate::PlannerError::InvalidInput {
+                field: "completion".to_string(),
+                reason: format!(
+                    "Invalid completion: {completion}. Must be 'complete', 'incomplete', or 'empty'"
+                ),
+            }),
+        }
+    }
 }
 
 /// Parameters for searching plans by directory.
 ///
-/// Allows filtering plans by directory path and archived status.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// Allows filtering plans by directory path and archived status. Plans in
+/// subdirectories of the directory match as well unless
+/// `include_subdirectories` is unset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct SearchPlans {
     /// Directory path to search for plans
@@ -56,12 +290,47 @@ pub struct SearchPlans {
     /// Whether to include archived plans in search results
     #[serde(default)]
     pub archived: bool,
+    /// Whether plans in subdirectories of the directory match too; only
+    /// plans in exactly the directory do if false (default: true)
+    #[serde(default = "default_include_subdirectories")]
+    pub include_subdirectories: bool,
+}
+
+impl Default for SearchPlans {
+    fn default() -> Self {
+        Self {
+            directory: String::new(),
+            archived: false,
+            include_subdirectories: default_include_subdirectories(),
+        }
+    }
+}
+
+const fn default_include_subdirectories() -> bool {
+    true
+}
+
+/// Parameters for moving plans to a new directory.
+///
+/// Every plan in the old directory or below it, archived or not, is moved to
+/// the corresponding path under the new directory, e.g. after a repository
+/// was moved on disk.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ReassignDirectory {
+    /// Directory the plans are in now; it need not exist any more
+    pub from: String,
+    /// Directory to move the plans to, outside `from`
+    pub to: String,
 }
 
 /// Parameters for deleting a plan.
 ///
 /// Requires explicit confirmation to prevent accidental deletion of plans
 /// and their associated steps. Deletion is permanent and cannot be undone.
+///
+/// Deleting a plan that still has steps that are not done is refused unless
+/// `force` is set.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct DeletePlan {
@@ -69,6 +338,52 @@ pub struct DeletePlan {
     pub id: u64,
     /// Confirmation flag required to prevent accidental deletion
     pub confirmed: bool,
+    /// Delete even if the plan has steps that are not done
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Parameters for merging one plan into another.
+///
+/// The source plan's steps are appended to the target plan and the source is
+/// archived with a pointer to the target. Requires explicit confirmation, like
+/// deletion.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct MergePlans {
+    /// The ID of the plan whose steps are moved and which gets archived
+    pub source_id: u64,
+    /// The ID of the plan receiving the steps
+    pub target_id: u64,
+    /// Prefix the moved steps' titles with the source plan's title
+    #[serde(default)]
+    pub prefix_titles: bool,
+    /// Confirmation flag required to prevent accidental merges
+    pub confirmed: bool,
+}
+
+/// Parameters for copying a plan as a template.
+///
+/// The copy's steps start out todo without results.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ClonePlan {
+    /// ID of the plan to copy
+    pub id: u64,
+    /// Title of the copy; defaults to the original's title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// Parameters for appending to a plan's working notes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct AppendPlanNote {
+    /// ID of the plan the note is about
+    pub id: u64,
+    /// Text to append, e.g. a decision made or an open question; it is
+    /// added under a heading with the current time
+    pub text: String,
 }
 
 /// Base parameters for step creation and modification.
@@ -79,15 +394,52 @@ pub struct DeletePlan {
 pub struct StepCreate {
     /// ID of the plan to add the step to
     pub plan_id: u64,
-    /// Title of the step (required)
+    /// Title of the step (required, at most 500 characters)
     pub title: String,
-    /// Optional detailed description of the step
+    /// Optional detailed description of the step (at most 64 KB)
     pub description: Option<String>,
     /// Optional acceptance criteria for the step
     pub acceptance_criteria: Option<String>,
-    /// References (URLs, file paths, etc.)
+    /// Optional artifact the step is expected to produce, e.g. "a new
+    /// endpoint POST /v1/tokens", shown next to the result once done
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_output: Option<String>,
+    /// References (URLs, file paths, etc.), each a plain string or an
+    /// object with a `url` and an optional `label` to show instead. With the
+    /// workspace's `strict_references` setting on, malformed URLs are
+    /// rejected and each reference is classified as a URL, file or text
+    #[serde(default)]
+    pub references: Vec<StepReference>,
+    /// Require a second agent to verify the step once it is done. Until
+    /// verified, the step doesn't count towards the plan's progress.
     #[serde(default)]
-    pub references: Vec<String>,
+    pub verification_required: bool,
+    /// Optional estimate of the effort the step takes, in minutes
+    #[serde(default)]
+    pub estimate_minutes: Option<u32>,
+}
+
+impl StepCreate {
+    /// Returns the parameters with the title, description, acceptance
+    /// criteria and expected output trimmed.
+    ///
+    /// # Errors
+    ///
+    /// * `PlannerError::InvalidInput` - When the title is blank or longer
+    ///   than [`MAX_TITLE_LEN`], or another text field is longer than
+    ///   [`MAX_DESCRIPTION_LEN`]
+    pub fn validate(&self) -> crate::Result<Self> {
+        Ok(Self {
+            title: validate_title("title", &self.title)?,
+            description: validate_text("description", self.description.as_deref())?,
+            acceptance_criteria: validate_text(
+                "acceptance_criteria",
+                self.acceptance_criteria.as_deref(),
+            )?,
+            expected_output: validate_text("expected_output", self.expected_output.as_deref())?,
+            ..self.clone()
+        })
+    }
 }
 
 /// Parameters for inserting a step at a specific position.
@@ -117,6 +469,223 @@ pub struct SwapSteps {
     pub step2_id: u64,
 }
 
+/// Parameters for moving a step to another position in its plan.
+///
+/// Steps between the old and new positions shift by one to make room.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct MoveStep {
+    /// ID of the step to move
+    pub step_id: u64,
+    /// Position to move the step to (0-indexed)
+    pub new_position: u32,
+}
+
+/// Parameters for moving a step to another plan.
+///
+/// The step keeps its status, result and references.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct MoveStepToPlan {
+    /// ID of the step to move
+    pub step_id: u64,
+    /// ID of the plan to move the step to
+    pub plan_id: u64,
+    /// Position in the target plan (0-indexed); defaults to the end
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<u32>,
+}
+
+/// Parameters for adding or removing a dependency between two steps.
+///
+/// A step cannot be claimed until every step it depends on is done.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct StepDependency {
+    /// ID of the step that has to wait
+    pub step_id: u64,
+    /// ID of the step that must be done first
+    pub depends_on: u64,
+}
+
+/// Parameters for removing a step by moving it to the trash.
+///
+/// Removing a step that is in progress is refused unless `force` is set,
+/// since someone may still be working on it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct RemoveStep {
+    /// The ID of the step to remove
+    pub id: u64,
+    /// Remove the step even if it is in progress
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Parameters for permanently deleting steps from the trash.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct PurgeTrash {
+    /// Only purge steps trashed more than this many days ago; 0 purges all
+    #[serde(default)]
+    pub older_than_days: u32,
+}
+
+/// Parameters for adding a note to a step.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct AddStepNote {
+    /// ID of the step the note is about
+    pub step_id: u64,
+    /// Text of the note, e.g. something learned along the way that doesn't
+    /// belong in the step's result
+    pub body: String,
+}
+
+/// Parameters for reading workspace settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct GetConfig {
+    /// Name of the setting to read; all settings if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+}
+
+/// Parameters for changing a workspace setting.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct SetConfig {
+    /// Name of the setting to change
+    pub key: String,
+    /// New value of the setting, e.g. `true` or `36h`
+    pub value: String,
+}
+
+/// Parameters for reporting open steps tracked more than once.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct DuplicateStepsReport {
+    /// Only compare steps of plans in this directory or below it; all active
+    /// plans if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directory: Option<String>,
+}
+
+/// Parameters for showing a plan's changelog.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct PlanChangelog {
+    /// ID of the plan
+    pub id: u64,
+    /// Only include changes newer than this: an age such as "30d", "2w" or
+    /// "12h", or a date such as "2024-11-01"; omit for all changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+}
+
+/// Parameters for a standup report of a plan.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct GetPlanReport {
+    /// ID of the plan
+    pub id: u64,
+    /// Start of the reported period: an age such as "24h" or "3d", or a date
+    /// such as "2024-11-01"; the last 24 hours if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+}
+
+/// Parameters for listing the changes made to plans and steps.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ListEvents {
+    /// Only include events newer than this: an age such as "30d", "2w" or
+    /// "12h", or a date such as "2024-11-01"; omit for all events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    /// Maximum number of events to return, oldest first; omit for all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+/// Parameters for computing planner statistics.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct GetStats {
+    /// Only count what happened since this: an age such as "7d", "2w" or
+    /// "12h", or a date such as "2024-11-01"; omit for all time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+}
+
+/// Parameters for creating a step template.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct CreateTemplate {
+    /// Name the template is applied by, unique regardless of case
+    pub name: String,
+    /// Steps appended to a plan when the template is applied, in order
+    pub steps: Vec<TemplateStep>,
+}
+
+impl CreateTemplate {
+    /// Returns the parameters with the name and every step's text trimmed.
+    ///
+    /// # Errors
+    ///
+    /// * `PlannerError::InvalidInput` - When the name or a step title is
+    ///   blank or too long, a step's text is too long, or there are no steps
+    pub fn validate(&self) -> crate::Result<Self> {
+        if self.steps.is_empty() {
+            return Err(crate::PlannerError::invalid_input("steps")
+                .with_reason("A template needs at least one step"));
+        }
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| {
+                Ok(TemplateStep {
+                    title: validate_title("title", &step.title)?,
+                    description: validate_text("description", step.description.as_deref())?,
+                    acceptance_criteria: validate_text(
+                        "acceptance_criteria",
+                        step.acceptance_criteria.as_deref(),
+                    )?,
+                })
+            })
+            .collect::<crate::Result<_>>()?;
+        Ok(Self {
+            name: validate_title("name", &self.name)?,
+            steps,
+        })
+    }
+}
+
+/// Parameters for appending a template's steps to a plan.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ApplyTemplate {
+    /// ID of the plan to append the steps to
+    pub plan_id: u64,
+    /// Name of the template, ignoring case
+    pub name: String,
+}
+
+/// Parameters for grouping a step under a phase.
+///
+/// Steps sharing a phase are shown together under a `## Phase: …` header
+/// when their plan is displayed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct SetStepPhase {
+    /// ID of the step to group
+    pub id: u64,
+    /// Name of the phase, e.g. "Design"; omit to remove the step from its
+    /// phase
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase: Option<String>,
+}
+
 /// Parameters for updating an existing step.
 ///
 /// Allows partial updates to step properties. When changing status to 'done',
@@ -126,22 +695,29 @@ pub struct SwapSteps {
 pub struct UpdateStep {
     /// Step ID to update (required)
     pub id: u64,
-    /// New status for the step ('todo', 'inprogress', or 'done')
+    /// New status for the step ('todo', 'inprogress', 'done', or 'blocked')
     pub status: Option<String>,
-    /// Updated title of the step
+    /// Updated title of the step (at most 500 characters)
     pub title: Option<String>,
-    /// Updated detailed description of the step
+    /// Updated detailed description of the step (at most 64 KB)
     pub description: Option<String>,
     /// Updated acceptance criteria for the step
     pub acceptance_criteria: Option<String>,
-    /// Updated references (URLs, file paths, etc.)
-    pub references: Option<Vec<String>>,
-    /// Result description - required when changing status to 'done'.
+    /// Updated artifact the step is expected to produce
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_output: Option<String>,
+    /// Updated references (URLs, file paths, etc.), replacing the current
+    /// ones; each a plain string or an object with a `url` and an optional
+    /// `label`
+    pub references: Option<Vec<StepReference>>,
+    /// Result description - required when changing status to 'done', unless
+    /// the step was done before and kept its result when reopened. Given
+    /// without a status for a done step, it replaces the step's result.
     ///
     /// This field documents what was actually accomplished when completing the
     /// step. It will be ignored when:
     /// - Changing status to 'todo' or 'inprogress'
-    /// - Updating other fields without changing status
+    /// - Updating other fields of a step that is not done
     /// - Creating a new step (steps always start as 'todo')
     ///
     /// Format using Markdown with **bold headers** and detailed bullet points:
@@ -163,13 +739,142 @@ pub struct UpdateStep {
     /// - Release build successful"
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub result: Option<String>,
+    /// Drop the result a reopened step kept from its earlier completion.
+    /// Reopening a done step keeps its result, hidden until the step is done
+    /// again, so that it can be re-completed without writing it anew
+    #[serde(default)]
+    pub clear_result: bool,
+    /// Why the step is blocked, such as "waiting on API keys" - only
+    /// accepted when changing status to 'blocked' or for a step that already
+    /// is. The reason is dropped when the step leaves the blocked status
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocked_reason: Option<String>,
+    /// Require (true) or stop requiring (false) a second agent to verify the
+    /// step once it is done
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_required: Option<bool>,
+}
+
+/// Parameters for verifying a step's completion.
+///
+/// Steps requiring verification only count as complete once an agent other
+/// than the one that did the work confirms it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct VerifyStep {
+    /// ID of the step to verify
+    pub id: u64,
+    /// Who is verifying the step; should identify a different agent than
+    /// the one that completed it
+    pub verifier: String,
+    /// Notes on what was checked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// Parameters for listing the steps of a plan, optionally by status.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ListSteps {
+    /// ID of the plan whose steps to list
+    pub plan_id: u64,
+    /// Only list steps with this status: 'todo', 'inprogress', 'done', or 'blocked'
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+impl ListSteps {
+    /// Parses the status filter.
+    ///
+    /// Unlike parsing a [`StepStatus`](crate::models::StepStatus), which
+    /// reads unknown statuses as todo, an unknown status is rejected: a
+    /// typo would otherwise silently list todo steps.
+    ///
+    /// # Errors
+    ///
+    /// * `PlannerError::InvalidInput` - When the status string is invalid
+    pub fn validate(&self) -> crate::Result<Option<crate::models::StepStatus>> {
+        parse_status_filter(self.status.as_deref())
+    }
+}
+
+/// Parses an optional step status filter, rejecting unknown statuses.
+fn parse_status_filter(status: Option<&str>) -> crate::Result<Option<crate::models::StepStatus>> {
+    status.map(parse_status).transpose()
+}
+
+/// Parses a step status, rejecting unknown statuses.
+fn parse_status(status: &str) -> crate::Result<crate::models::StepStatus> {
+    use crate::models::StepStatus;
+
+    match status.to_lowercase().as_str() {
+        "todo" => Ok(StepStatus::Todo),
+        "inprogress" | "in_progress" => Ok(StepStatus::InProgress),
+        "done" => Ok(StepStatus::Done),
+        "blocked" => Ok(StepStatus::Blocked),
+        _ => Err(crate::PlannerError::InvalidInput {
+            field: "status".to_string(),
+            reason: format!(
+                "Invalid status: {status}. Must be 'todo', 'inprogress', 'done', or 'blocked'"
+            ),
+        }),
+    }
+}
+
+/// Parameters for listing the steps with a status across plans.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ListStepsByStatus {
+    /// Status of the steps to list: 'todo', 'inprogress', 'done', or 'blocked'
+    pub status: String,
+    /// Whether to include the steps of archived plans
+    #[serde(default)]
+    pub include_archived_plans: bool,
+}
+
+impl ListStepsByStatus {
+    /// Parses the status, rejecting unknown statuses as
+    /// [`ListSteps::validate`] does.
+    ///
+    /// # Errors
+    ///
+    /// * `PlannerError::InvalidInput` - When the status string is invalid
+    pub fn validate(&self) -> crate::Result<crate::models::StepStatus> {
+        parse_status(&self.status)
+    }
+}
+
+/// Parameters for finding the steps of a plan by their text.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct FindSteps {
+    /// ID of the plan whose steps to search
+    pub plan_id: u64,
+    /// Text to find in step titles and descriptions, ignoring case
+    pub query: String,
+    /// Only find steps with this status: 'todo', 'inprogress', 'done', or 'blocked'
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+impl FindSteps {
+    /// Parses the status filter, rejecting unknown statuses as
+    /// [`ListSteps::validate`] does.
+    ///
+    /// # Errors
+    ///
+    /// * `PlannerError::InvalidInput` - When the status string is invalid
+    pub fn validate(&self) -> crate::Result<Option<crate::models::StepStatus>> {
+        parse_status_filter(self.status.as_deref())
+    }
 }
 
 impl UpdateStep {
     /// Validate step update parameters and return parsed status and result.
     ///
-    /// This method performs validation of step update requests, including
-    /// status parsing and result requirement validation for completed steps.
+    /// Only the status is checked here. Whether a step marked done has a
+    /// result is checked when updating, since a reopened step may reuse the
+    /// one kept from its earlier completion.
     ///
     /// # Returns
     ///
@@ -179,8 +884,6 @@ impl UpdateStep {
     /// # Errors
     ///
     /// * `PlannerError::InvalidInput` - When status string is invalid
-    /// * `PlannerError::InvalidInput` - When result is missing for 'done'
-    ///   status
     pub fn validate(&self) -> crate::Result<(Option<crate::models::StepStatus>, Option<String>)> {
         use std::str::FromStr;
 
@@ -191,7 +894,7 @@ impl UpdateStep {
                 crate::PlannerError::InvalidInput {
                     field: "status".to_string(),
                     reason: format!(
-                        "Invalid status: {}. Must be 'todo', 'inprogress', or 'done'",
+                        "Invalid status: {}. Must be 'todo', 'inprogress', 'done', or 'blocked'",
                         status_str
                     ),
                 }
@@ -200,16 +903,6 @@ impl UpdateStep {
             None
         };
 
-        // Validate result requirement for done status
-        if let Some(StepStatus::Done) = step_status
-            && self.result.is_none()
-        {
-            return Err(crate::PlannerError::InvalidInput {
-                    field: "result".to_string(),
-                    reason: "Result description is required when marking a step as done. Please provide a 'result' field describing what was accomplished.".to_string(),
-                });
-        }
-
         Ok((step_status, self.result.clone()))
     }
 }
@@ -244,29 +937,6 @@ mod tests {
         assert_eq!(result_desc, expected_result.map(|s| s.to_string()));
     }
 
-    /// Helper function to assert validation fails with specific error details
-    fn assert_validation_error(
-        params: &UpdateStep,
-        expected_field: &str,
-        expected_reason_contains: &str,
-    ) {
-        let result = params.validate();
-        assert!(result.is_err(), "Validation should fail");
-
-        match result.unwrap_err() {
-            PlannerError::InvalidInput { field, reason } => {
-                assert_eq!(field, expected_field);
-                assert!(
-                    reason.contains(expected_reason_contains),
-                    "Expected reason to contain '{}', got: {}",
-                    expected_reason_contains,
-                    reason
-                );
-            }
-            _ => panic!("Expected InvalidInput error"),
-        }
-    }
-
     #[test]
     fn test_valid_status_transitions() {
         // Test valid status changes without result
@@ -306,11 +976,65 @@ mod tests {
     }
 
     #[test]
-    fn test_done_status_requires_result() {
-        assert_validation_error(
+    fn test_done_status_result_checked_when_updating() {
+        // A reopened step may reuse its earlier result, which only the
+        // database knows about
+        assert_validates_to(
             &update_with_status(Some("done"), None),
-            "result",
-            "Result description is required",
+            Some(StepStatus::Done),
+            None,
         );
     }
+
+    #[test]
+    fn test_titles_are_trimmed_and_bounded() {
+        let plan = |title: &str| CreatePlan {
+            title: title.to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(plan("  Ship it \n").validate().unwrap().title, "Ship it");
+        for blank in ["", "   ", "\t\n"] {
+            match plan(blank).validate() {
+                Err(PlannerError::InvalidInput { field, .. }) => assert_eq!(field, "title"),
+                other => panic!("Expected InvalidInput error, got {other:?}"),
+            }
+        }
+
+        // The limit counts characters, not bytes
+        let longest = "é".repeat(MAX_TITLE_LEN);
+        assert_eq!(plan(&longest).validate().unwrap().title, longest);
+        assert!(plan(&format!("{longest}é")).validate().is_err());
+        assert_eq!(plan("計画 🚀").validate().unwrap().title, "計画 🚀");
+    }
+
+    #[test]
+    fn test_step_text_fields_are_bounded() {
+        let step = |description: String| StepCreate {
+            title: "Step".to_string(),
+            description: Some(description),
+            ..Default::default()
+        };
+
+        let longest = "x".repeat(MAX_DESCRIPTION_LEN);
+        assert!(step(longest.clone()).validate().is_ok());
+        // Surrounding whitespace doesn't count
+        assert!(step(format!(" {longest} ")).validate().is_ok());
+        match step(format!("{longest}x")).validate() {
+            Err(PlannerError::InvalidInput { field, .. }) => assert_eq!(field, "description"),
+            other => panic!("Expected InvalidInput error, got {other:?}"),
+        }
+
+        let criteria = StepCreate {
+            title: "Step".to_string(),
+            acceptance_criteria: Some("y".repeat(MAX_DESCRIPTION_LEN + 1)),
+            ..Default::default()
+        };
+        match criteria.validate() {
+            Err(PlannerError::InvalidInput { field, .. }) => {
+                assert_eq!(field, "acceptance_criteria");
+            }
+            other => panic!("Expected InvalidInput error, got {other:?}"),
+        }
+    }
 }
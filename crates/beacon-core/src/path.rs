@@ -0,0 +1,150 @@
+//! Lexical normalization of directory paths.
+//!
+//! Plans are stored with absolute directories, compared as strings, so the
+//! same directory must always be spelled the same way. These helpers resolve
+//! `.` and `..` components and drop repeated and trailing separators without
+//! touching the file system:
+//!
+//! ```text
+//!   /work//api/./src/../     →  /work/api
+//!   /../work                 →  /work        (nothing is above the root)
+//!   C:\work\..\..\api        →  C:\api       (on Windows)
+//! ```
+//!
+//! Symlinks are not resolved; the database canonicalizes existing
+//! directories on top of this unless path canonicalization is disabled.
+
+use std::{
+    env::current_dir,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::error::{PlannerError, Result};
+
+/// Resolves `.` and `..` components of `path` without requiring it to exist.
+///
+/// A `..` never climbs above the root or the prefix of a Windows path, so
+/// `/..` is `/`. Leading `..` components of a relative path are kept, as
+/// they refer to directories outside it. A relative path normalizing to
+/// nothing, such as `a/..`, is `.`.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+///
+/// use beacon_core::path::normalize;
+///
+/// assert_eq!(normalize(Path::new("/work//api/./src/../")), Path::new("/work/api"));
+/// assert_eq!(normalize(Path::new("../a/b/..")), Path::new("../a"));
+/// ```
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut components: Vec<Component> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match components.last() {
+                Some(Component::Normal(_)) => {
+                    components.pop();
+                }
+                Some(Component::Prefix(_) | Component::RootDir) => {}
+                Some(Component::ParentDir | Component::CurDir) | None => {
+                    components.push(component);
+                }
+            },
+            _ => components.push(component),
+        }
+    }
+
+    if components.is_empty() {
+        return PathBuf::from(Component::CurDir.as_os_str());
+    }
+    components.iter().collect()
+}
+
+/// Makes `path` absolute against the current working directory, which it
+/// defaults to, and normalizes it.
+///
+/// # Errors
+///
+/// * `PlannerError::InvalidInput` - When the current working directory is
+///   needed but can't be determined
+pub fn to_absolute(path: Option<&Path>) -> Result<PathBuf> {
+    let absolute = match path {
+        Some(path) if path.is_absolute() => path.to_path_buf(),
+        Some(path) => current_dir()
+            .map_err(|_| {
+                PlannerError::invalid_input("directory")
+                    .with_reason("Cannot resolve current working directory to make path absolute")
+            })?
+            .join(path),
+        None => current_dir().map_err(|_| {
+            PlannerError::invalid_input("directory")
+                .with_reason("Cannot determine current working directory")
+        })?,
+    };
+    Ok(normalize(&absolute))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalized(path: &str) -> PathBuf {
+        normalize(Path::new(path))
+    }
+
+    #[test]
+    fn test_normalize_relative_paths() {
+        assert_eq!(normalized("a/./b/../c"), Path::new("a/c"));
+        assert_eq!(normalized("./a//b/"), Path::new("a/b"));
+        assert_eq!(normalized("../../a/b/.."), Path::new("../../a"));
+        assert_eq!(normalized("a/../.."), Path::new(".."));
+        assert_eq!(normalized("a/.."), Path::new("."));
+        assert_eq!(normalized(""), Path::new("."));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_normalize_unix_paths() {
+        assert_eq!(normalized("/work//api/./src/../"), Path::new("/work/api"));
+        assert_eq!(normalized("/work/api///"), Path::new("/work/api"));
+        assert_eq!(normalized("/../../work"), Path::new("/work"));
+        assert_eq!(normalized("/work/.."), Path::new("/"));
+        assert_eq!(normalized("/"), Path::new("/"));
+        // Backslashes are ordinary characters
+        assert_eq!(normalized("/work\\..\\api"), Path::new("/work\\..\\api"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_normalize_windows_paths() {
+        assert_eq!(
+            normalized(r"C:\work\\api\.\src\..\"),
+            Path::new(r"C:\work\api")
+        );
+        assert_eq!(normalized("C:/work/api/"), Path::new(r"C:\work\api"));
+        assert_eq!(normalized(r"C:\..\..\work"), Path::new(r"C:\work"));
+        assert_eq!(normalized(r"C:\work\.."), Path::new(r"C:\"));
+        assert_eq!(normalized(r"C:..\work"), Path::new(r"C:work"));
+        assert_eq!(
+            normalized(r"\\server\share\a\..\..\b"),
+            Path::new(r"\\server\share\b")
+        );
+    }
+
+    #[test]
+    fn test_to_absolute() {
+        let cwd = current_dir().unwrap();
+        assert_eq!(to_absolute(None).unwrap(), normalize(&cwd));
+        assert_eq!(
+            to_absolute(Some(Path::new("a/../b/"))).unwrap(),
+            normalize(&cwd.join("b"))
+        );
+
+        let absolute = cwd.join("a").join("..").join("c");
+        let resolved = to_absolute(Some(&absolute)).unwrap();
+        assert!(resolved.is_absolute());
+        assert_eq!(resolved, normalize(&cwd).join("c"));
+    }
+}
@@ -0,0 +1,270 @@
+//! Running database work on the blocking thread pool with a time limit.
+//!
+//! Every operation checks a connection out of the planner's pool on a
+//! blocking thread. A query that never finishes, or a database on a hung
+//! network mount, would hold its thread forever, so each operation is given a
+//! time limit. When it expires, the operation's connection is interrupted so
+//! that the running statement fails and the thread is released, rather than
+//! being abandoned.
+//!
+//! Connections wait a while for locks held by other connections, e.g. a CLI
+//! command writing while an MCP server runs. Writes that still find the
+//! database busy are retried a few times with a growing pause, and a busy
+//! error that remains is reported as `PlannerError::Busy`.
+//!
+//! Each operation runs in a `database` span, under the span of the planner
+//! method that started it, and is timed to report slow ones; see
+//! [`instrumentation`](super::instrumentation).
+
+use std::{
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use rusqlite::InterruptHandle;
+use tokio::{task, time};
+use tracing::{Dispatch, debug_span, dispatcher, field};
+
+use super::Planner;
+use crate::{
+    db::Database,
+    error::{PlannerError, Result},
+};
+
+/// Default time limit of a single planner operation, generous enough that
+/// only stuck operations hit it.
+pub const DEFAULT_OPERATION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Number of times a write that found the database busy is retried.
+const BUSY_RETRIES: u32 = 3;
+
+/// Pause before retrying a busy write the first time, doubled before each
+/// further retry.
+const BUSY_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// How far a blocking operation got, as seen by the task waiting for it.
+enum Progress {
+    /// The connection is still being checked out or opened
+    Opening,
+    /// Statements are running on the connection
+    Running(InterruptHandle),
+    /// The operation timed out before its connection was open
+    Cancelled,
+}
+
+impl Planner {
+    /// Runs `work` on a pooled database connection on the blocking thread
+    /// pool.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::Timeout` naming `operation` if the work takes
+    /// longer than the planner's operation timeout. The statement running at
+    /// that point is interrupted, and work that has not started yet is
+    /// skipped.
+    /// Returns `PlannerError::Busy` naming `operation` if the database stayed
+    /// locked by another connection
+    pub(crate) async fn run_blocking<T, F>(&self, operation: &'static str, work: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Database) -> Result<T> + Send + 'static,
+    {
+        let pool = Arc::clone(&self.pool);
+        let slow_queries = Arc::clone(&self.slow_queries);
+        let progress = Arc::new(Mutex::new(Progress::Opening));
+        let worker_progress = Arc::clone(&progress);
+
+        // The blocking thread reports to the caller's subscriber, within
+        // the caller's span. Without one, events go to `log`, which setting
+        // even an empty subscriber as the default would stop.
+        let span = debug_span!("database", operation, elapsed_ms = field::Empty);
+        let dispatch = dispatcher::has_been_set().then(|| dispatcher::get_default(Dispatch::clone));
+        let task = task::spawn_blocking(move || {
+            let run = || {
+                let _entered = span.enter();
+                let mut db = pool.get()?;
+                {
+                    let mut progress = worker_progress
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner);
+                    if let Progress::Cancelled = *progress {
+                        return Err(PlannerError::Timeout {
+                            operation: operation.to_string(),
+                        });
+                    }
+                    *progress = Progress::Running(db.interrupt_handle());
+                }
+
+                let started = Instant::now();
+                let result = work(&mut db);
+                let elapsed = started.elapsed();
+                span.record("elapsed_ms", elapsed.as_millis() as u64);
+                slow_queries.record(operation, elapsed);
+                result
+            };
+            match &dispatch {
+                Some(dispatch) => dispatcher::with_default(dispatch, run),
+                None => run(),
+            }
+        });
+
+        match time::timeout(self.operation_timeout, task).await {
+            Ok(result) => result?.map_err(|e| {
+                if e.is_busy() {
+                    PlannerError::Busy {
+                        operation: operation.to_string(),
+                    }
+                } else {
+                    e
+                }
+            }),
+            Err(_) => {
+                let mut progress = progress.lock().unwrap_or_else(PoisonError::into_inner);
+                match &*progress {
+                    Progress::Running(handle) => handle.interrupt(),
+                    _ => *progress = Progress::Cancelled,
+                }
+                Err(PlannerError::Timeout {
+                    operation: operation.to_string(),
+                })
+            }
+        }
+    }
+
+    /// Runs `work` like [`Self::run_blocking`] for an operation that writes
+    /// to the database.
+    ///
+    /// Work that fails because the database is busy is run again, up to
+    /// [`BUSY_RETRIES`] times, unless it changed anything before failing:
+    /// work writing in several transactions is not repeated after the first
+    /// committed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::ReadOnly` naming `operation`, without touching
+    /// the database, if the planner is read-only
+    pub(crate) async fn run_blocking_write<T, F>(
+        &self,
+        operation: &'static str,
+        work: F,
+    ) -> Result<T>
+    where
+        T: Send + 'static,
+        F: Fn(&mut Database) -> Result<T> + Send + 'static,
+    {
+        if self.is_read_only() {
+            return Err(PlannerError::ReadOnly {
+                operation: operation.to_string(),
+            });
+        }
+        self.run_blocking(operation, move |db| {
+            let mut delay = BUSY_RETRY_DELAY;
+            for _ in 0..BUSY_RETRIES {
+                let changes = db.total_changes();
+                match work(db) {
+                    Err(e) if e.is_busy() && db.total_changes() == changes => {
+                        thread::sleep(delay);
+                        delay *= 2;
+                    }
+                    result => return result,
+                }
+            }
+            work(db)
+        })
+        .await
+    }
+
+    /// Path of the database file, or `:memory:` for an
+    /// [in-memory](super::PlannerBuilder::in_memory) database.
+    pub fn database_path(&self) -> &Path {
+        &self.pool.config().path
+    }
+
+    /// Returns true if the planner was built
+    /// [read-only](super::PlannerBuilder::read_only).
+    pub fn is_read_only(&self) -> bool {
+        self.pool.config().read_only
+    }
+
+    /// Number of database connections the planner has opened, including the
+    /// one opened when it was built.
+    ///
+    /// Operations reuse idle connections, so this only grows when operations
+    /// run concurrently or the database file is replaced.
+    pub fn connections_opened(&self) -> usize {
+        self.pool.opened()
+    }
+
+    /// Number of times the database file was found replaced on disk, e.g.
+    /// by restoring a backup, and connections to the new file were opened.
+    pub fn reconnects(&self) -> usize {
+        self.pool.reconnects()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use rusqlite::ErrorCode;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::planner::PlannerBuilder;
+
+    async fn planner_with_timeout(timeout: Duration) -> (TempDir, Planner) {
+        let temp_dir = TempDir::new().unwrap();
+        let planner = PlannerBuilder::new()
+            .with_database_path(Some(temp_dir.path().join("test.db")))
+            .with_operation_timeout(timeout)
+            .build()
+            .await
+            .unwrap();
+        (temp_dir, planner)
+    }
+
+    #[tokio::test]
+    async fn test_timeout_interrupts_running_statement() {
+        let (_temp_dir, planner) = planner_with_timeout(Duration::from_millis(200)).await;
+        let (sender, receiver) = mpsc::channel();
+
+        // Counts without end, so it only returns once interrupted
+        let result = planner
+            .run_blocking("count forever", move |db| {
+                let outcome = db.connection().query_row(
+                    "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n)
+                     SELECT count(*) FROM n",
+                    [],
+                    |row| row.get::<_, i64>(0),
+                );
+                sender.send(outcome).unwrap();
+                Ok(())
+            })
+            .await;
+
+        match result {
+            Err(PlannerError::Timeout { operation }) => assert_eq!(operation, "count forever"),
+            other => panic!("Expected a timeout, got {other:?}"),
+        }
+
+        // The worker thread observes the interrupt and finishes
+        let outcome = receiver
+            .recv_timeout(Duration::from_secs(10))
+            .expect("Blocking thread was not released");
+        let error = outcome.expect_err("Statement was not interrupted");
+        assert_eq!(
+            error.sqlite_error_code(),
+            Some(ErrorCode::OperationInterrupted)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_operations_within_timeout_succeed() {
+        let (_temp_dir, planner) = planner_with_timeout(DEFAULT_OPERATION_TIMEOUT).await;
+
+        let value = planner.run_blocking("answer", |_| Ok(42)).await.unwrap();
+        assert_eq!(value, 42);
+    }
+}
@@ -1,19 +1,43 @@
 //! Builder for creating and configuring Planner instances.
 
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 use tokio::task;
 
-use super::Planner;
+use super::{
+    DEFAULT_CONNECTION_POOL_SIZE, DEFAULT_OPERATION_TIMEOUT, DEFAULT_SLOW_QUERY_THRESHOLD, Planner,
+    instrumentation::SlowQueryLog,
+};
+#[cfg(feature = "encryption")]
+use crate::db::EncryptionKey;
 use crate::{
-    db::Database,
+    db::{DEFAULT_BUSY_TIMEOUT, DatabaseConfig, IN_MEMORY_PATH, pool::ConnectionPool},
     error::{PlannerError, Result},
+    models::{SettingKey, SettingOverrides},
 };
 
 /// Builder for creating and configuring Planner instances.
+///
+/// Behavior that can also be configured per workspace, with the settings
+/// stored in the database, is overridden by the corresponding builder flags
+/// for the planner being built.
 #[derive(Debug, Clone)]
 pub struct PlannerBuilder {
     database_path: Option<PathBuf>,
+    in_memory: bool,
+    setting_overrides: SettingOverrides,
+    operation_timeout: Duration,
+    slow_query_threshold: Duration,
+    busy_timeout: Duration,
+    connection_pool_size: usize,
+    read_only: bool,
+    path_canonicalization: bool,
+    #[cfg(feature = "encryption")]
+    encryption_key: Option<EncryptionKey>,
 }
 
 impl PlannerBuilder {
@@ -21,6 +45,16 @@ impl PlannerBuilder {
     pub fn new() -> Self {
         Self {
             database_path: None,
+            in_memory: false,
+            setting_overrides: SettingOverrides::default(),
+            operation_timeout: DEFAULT_OPERATION_TIMEOUT,
+            slow_query_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
+            busy_timeout: DEFAULT_BUSY_TIMEOUT,
+            connection_pool_size: DEFAULT_CONNECTION_POOL_SIZE,
+            read_only: false,
+            path_canonicalization: true,
+            #[cfg(feature = "encryption")]
+            encryption_key: None,
         }
     }
 
@@ -28,52 +62,290 @@ impl PlannerBuilder {
     ///
     /// If not specified, uses XDG Base Directory specification:
     /// `$XDG_DATA_HOME/beacon/tasks.db` or `~/.local/share/beacon/tasks.db`
+    ///
+    /// The path [`IN_MEMORY_PATH`] (`:memory:`) selects an in-memory
+    /// database, as [`in_memory`](Self::in_memory) does.
     pub fn with_database_path<P: AsRef<Path>>(mut self, path: Option<P>) -> Self {
         if let Some(path) = path {
-            self.database_path = Some(path.as_ref().to_path_buf());
+            let path = path.as_ref();
+            self.in_memory = path == Path::new(IN_MEMORY_PATH);
+            self.database_path = Some(path.to_path_buf());
         }
         self
     }
 
+    /// Keeps the database in memory instead of a file.
+    ///
+    /// The database starts empty and is dropped with the last clone of the
+    /// planner, which makes it suited to tests and throwaway sessions.
+    /// Every planner built this way has a database of its own, shared by
+    /// its clones. Its operations run one at a time.
+    pub fn in_memory(mut self) -> Self {
+        self.in_memory = true;
+        self.database_path = Some(PathBuf::from(IN_MEMORY_PATH));
+        self
+    }
+
+    /// Whether the planner will keep its database in memory.
+    pub fn is_in_memory(&self) -> bool {
+        self.in_memory
+    }
+
+    /// Treats `(directory, title)` as unique when creating plans.
+    ///
+    /// When enabled, creating a plan whose title an active plan in the same
+    /// directory already has returns that plan instead of inserting a
+    /// duplicate, which makes plan creation safe to retry. Overrides the
+    /// workspace's `unique_plan_per_directory` setting, which is disabled by
+    /// default.
+    pub fn unique_plan_per_directory(mut self, enabled: bool) -> Self {
+        self.setting_overrides
+            .set(SettingKey::UniquePlanPerDirectory, |settings| {
+                settings.unique_plan_per_directory = enabled;
+            });
+        self
+    }
+
+    /// Stores the enclosing git repository root as the plan directory.
+    ///
+    /// When enabled, creating a plan from inside a git work tree records the
+    /// repository root (found by walking up to the nearest `.git`) as the
+    /// plan's directory and keeps the requested subdirectory as its subpath.
+    /// Individual requests can override this. Overrides the workspace's
+    /// `infer_git_root` setting, which is disabled by default.
+    pub fn infer_git_root(mut self, enabled: bool) -> Self {
+        self.setting_overrides
+            .set(SettingKey::InferGitRoot, |settings| {
+                settings.infer_git_root = enabled;
+            });
+        self
+    }
+
+    /// Validates step references when steps are added or updated.
+    ///
+    /// When enabled, malformed URLs are rejected, URLs are normalized,
+    /// duplicates dropped, and each reference is recorded as a URL, a file
+    /// path (checked against the plan directory) or text. Disabling it
+    /// stores references as given, as earlier versions did. Overrides the
+    /// workspace's `strict_references` setting, which is disabled by
+    /// default.
+    pub fn strict_references(mut self, enabled: bool) -> Self {
+        self.setting_overrides
+            .set(SettingKey::StrictReferences, |settings| {
+                settings.strict_references = enabled;
+            });
+        self
+    }
+
+    /// Sets how long a step may stay in progress before plan summaries flag
+    /// it with a warning.
+    ///
+    /// Overrides the workspace's `in_progress_warning_threshold` setting,
+    /// which defaults to
+    /// [`DEFAULT_IN_PROGRESS_WARNING_THRESHOLD`](crate::models::DEFAULT_IN_PROGRESS_WARNING_THRESHOLD)
+    /// (7 days).
+    pub fn in_progress_warning_threshold(mut self, threshold: Duration) -> Self {
+        self.setting_overrides
+            .set(SettingKey::InProgressWarningThreshold, |settings| {
+                settings.in_progress_warning_threshold = threshold;
+            });
+        self
+    }
+
+    /// Sets how long a single operation may spend on the database before it
+    /// fails with `PlannerError::Timeout`.
+    ///
+    /// The statement running when the time is up is interrupted, so that a
+    /// stuck query doesn't hold on to its thread. Defaults to
+    /// [`DEFAULT_OPERATION_TIMEOUT`] (30 seconds).
+    pub fn with_operation_timeout(mut self, timeout: Duration) -> Self {
+        self.operation_timeout = timeout;
+        self
+    }
+
+    /// Sets how long a single operation may spend on the database before a
+    /// warning reports it as slow.
+    ///
+    /// The warning is a `tracing` event naming the operation and how long it
+    /// took; while the database stays slow, at most one is emitted every
+    /// [`SLOW_QUERY_WARNING_INTERVAL`](super::instrumentation::SLOW_QUERY_WARNING_INTERVAL).
+    /// Defaults to [`DEFAULT_SLOW_QUERY_THRESHOLD`] (1 second).
+    pub fn with_slow_query_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_query_threshold = threshold;
+        self
+    }
+
+    /// Sets how long a connection waits for a lock held by another
+    /// connection, such as a CLI command writing while an MCP server runs,
+    /// before the operation fails as busy.
+    ///
+    /// Write operations that still find the database busy are retried a few
+    /// times before failing with `PlannerError::Busy`. Defaults to
+    /// [`DEFAULT_BUSY_TIMEOUT`] (5 seconds).
+    pub fn with_busy_timeout(mut self, timeout: Duration) -> Self {
+        self.busy_timeout = timeout;
+        self
+    }
+
+    /// Sets how many idle database connections the planner keeps for reuse.
+    ///
+    /// Operations check a connection out of the pool and return it when
+    /// done, so the schema is only initialized when the planner is built.
+    /// Concurrent operations beyond the pool size open extra connections,
+    /// which are closed afterwards. Zero opens a connection for every
+    /// operation. Defaults to [`DEFAULT_CONNECTION_POOL_SIZE`] (4).
+    pub fn with_connection_pool_size(mut self, size: usize) -> Self {
+        self.connection_pool_size = size;
+        self
+    }
+
+    /// Opens the database read-only.
+    ///
+    /// The database must already exist with an up-to-date schema, as left
+    /// by a planner that was not read-only. Reads work as usual, while
+    /// operations that would write fail with `PlannerError::ReadOnly`
+    /// before the database is touched.
+    pub fn read_only(mut self, enabled: bool) -> Self {
+        self.read_only = enabled;
+        self
+    }
+
+    /// Sets whether plan directories are resolved through symlinks.
+    ///
+    /// When enabled, a directory that exists is replaced by its canonical
+    /// path, as by [`std::fs::canonicalize`], both when it is stored for a
+    /// plan and when plans are searched by it, so that a project reached
+    /// through a symlink is found under its real path and the other way
+    /// round. Directories that don't exist are only made absolute and
+    /// normalized, which is all that happens when disabled. Enabled by
+    /// default.
+    pub fn with_path_canonicalization(mut self, enabled: bool) -> Self {
+        self.path_canonicalization = enabled;
+        self
+    }
+
+    /// Encrypts the database with the given key using SQLCipher.
+    ///
+    /// The key is applied right after each connection is opened, before any
+    /// query runs. A new database is created encrypted; an existing one must
+    /// have been created with the same key. Requires the `encryption`
+    /// feature.
+    #[cfg(feature = "encryption")]
+    pub fn with_encryption_key(mut self, key: impl Into<String>) -> Self {
+        self.encryption_key = Some(EncryptionKey(key.into()));
+        self
+    }
+
+    /// Returns the database path [`build`](Self::build) would use, without
+    /// creating or opening anything.
+    ///
+    /// This is the explicitly configured path, or the XDG default when none
+    /// was given, made absolute against the current working directory. An
+    /// in-memory database has the path [`IN_MEMORY_PATH`] as is.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::XdgDirectory` if no path was configured and the
+    /// XDG data directory cannot be determined
+    /// Returns `PlannerError::FileSystem` if the current working directory
+    /// cannot be determined for a relative path
+    pub fn resolved_path(&self) -> Result<PathBuf> {
+        if self.in_memory {
+            return Ok(PathBuf::from(IN_MEMORY_PATH));
+        }
+        let path = match &self.database_path {
+            Some(path) => path.clone(),
+            None => Self::default_database_path()?,
+        };
+
+        std::path::absolute(&path).map_err(|e| PlannerError::FileSystem { path, source: e })
+    }
+
     /// Builds the configured planner instance.
     ///
     /// # Errors
     ///
     /// Returns `PlannerError::FileSystem` if the database path is invalid
     /// Returns `PlannerError::Database` if database initialization fails
+    /// Returns `PlannerError::Encryption` if the encryption key is missing or
+    /// wrong
+    /// Returns `PlannerError::Configuration` if a read-only database's schema
+    /// is not up to date, or the database is both read-only and in memory
     pub async fn build(self) -> Result<Planner> {
-        let db_path = if let Some(path) = self.database_path {
-            path
-        } else {
-            Self::default_database_path()?
-        };
+        #[cfg(feature = "encryption")]
+        if self
+            .encryption_key
+            .as_ref()
+            .is_some_and(|key| key.0.is_empty())
+        {
+            return Err(PlannerError::invalid_input("encryption_key")
+                .with_reason("Encryption key cannot be empty"));
+        }
+
+        if self.in_memory {
+            return self.build_in_memory().await;
+        }
+        let db_path = self.resolved_path()?;
 
-        if let Some(parent) = db_path.parent() {
+        if let Some(parent) = db_path.parent().filter(|_| !self.read_only) {
             std::fs::create_dir_all(parent).map_err(|e| PlannerError::FileSystem {
                 path: parent.to_path_buf(),
                 source: e,
             })?;
         }
 
-        let db_path_clone = db_path.clone();
-        task::spawn_blocking(move || {
-            let _db = Database::new(&db_path_clone)?;
-            Ok::<(), PlannerError>(())
+        let db_config = DatabaseConfig {
+            path: db_path,
+            read_only: self.read_only,
+            busy_timeout: self.busy_timeout,
+            canonicalize_paths: self.path_canonicalization,
+            memory_uri: None,
+            #[cfg(feature = "encryption")]
+            encryption_key: self.encryption_key.clone(),
+        };
+        self.build_with(db_config).await
+    }
+
+    /// Builds a planner on a new in-memory database.
+    async fn build_in_memory(self) -> Result<Planner> {
+        if self.read_only {
+            return Err(PlannerError::Configuration {
+                message: "An in-memory database cannot be read-only".to_string(),
+            });
+        }
+
+        let db_config = DatabaseConfig {
+            #[cfg(feature = "encryption")]
+            encryption_key: self.encryption_key.clone(),
+            ..DatabaseConfig::in_memory(self.busy_timeout, self.path_canonicalization)
+        };
+        self.build_with(db_config).await
+    }
+
+    /// Opens the connection pool for `db_config`.
+    async fn build_with(self, db_config: DatabaseConfig) -> Result<Planner> {
+        let pool_size = self.connection_pool_size;
+        let pool = task::spawn_blocking(move || {
+            Ok::<_, PlannerError>(Arc::new(ConnectionPool::open(db_config, pool_size)?))
         })
-        .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })??;
+        .await??;
 
-        Ok(Planner::new(db_path))
+        Ok(Planner {
+            pool,
+            setting_overrides: self.setting_overrides,
+            operation_timeout: self.operation_timeout,
+            slow_queries: Arc::new(SlowQueryLog::new(self.slow_query_threshold)),
+        })
     }
 
     /// Returns the default database path following XDG Base Directory
     /// specification.
     fn default_database_path() -> Result<PathBuf> {
         xdg::BaseDirectories::with_prefix("beacon")
-            .place_data_file("beacon.db")
-            .map_err(|e| PlannerError::XdgDirectory(e.to_string()))
+            .get_data_file("beacon.db")
+            .ok_or_else(|| {
+                PlannerError::XdgDirectory("Could not determine the home directory".into())
+            })
     }
 }
 
@@ -0,0 +1,54 @@
+//! Change log of plans and steps for the Planner.
+
+use jiff::{Timestamp, Zoned};
+use tracing::instrument;
+
+use super::Planner;
+use crate::{
+    error::Result,
+    models::{Event, changelog::parse_since},
+    params::ListEvents,
+};
+
+impl Planner {
+    /// Lists the changes made to plans and steps, oldest first, optionally
+    /// only those recorded at or after `since` and at most `limit` of them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use beacon_core::PlannerBuilder;
+    /// # async {
+    /// let planner = PlannerBuilder::new().build().await?;
+    /// for event in planner.list_events(None, Some(20)).await? {
+    ///     println!("{event}");
+    /// }
+    /// # Ok::<(), beacon_core::PlannerError>(())
+    /// # };
+    /// ```
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_events(
+        &self,
+        since: Option<Timestamp>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Event>> {
+        self.run_blocking("list_events", move |db| db.list_events(since, limit))
+            .await
+    }
+
+    /// Handle listing events, parsing `since` as an age such as "2h" or a
+    /// date.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if `since` can't be parsed
+    #[instrument(level = "debug", skip_all)]
+    pub async fn events(&self, params: &ListEvents) -> Result<Vec<Event>> {
+        let since = params
+            .since
+            .as_deref()
+            .map(|since| parse_since(since, &Zoned::now()))
+            .transpose()?;
+        self.list_events(since, params.limit).await
+    }
+}
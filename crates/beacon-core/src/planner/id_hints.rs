@@ -0,0 +1,64 @@
+//! Not-found errors that hint at the other kind of item with the same ID.
+//!
+//! Plans and steps share an ID space from the caller's point of view, so a
+//! step ID passed to a plan operation (or the reverse) is a common mistake.
+//! The check only runs once a lookup has already failed.
+
+use super::Planner;
+use crate::error::{IdHint, PlannerError, Result};
+
+impl Planner {
+    /// Builds a `PlannerError::PlanNotFound` error for `id`, hinting at the
+    /// step with that ID if one exists.
+    pub async fn plan_not_found(&self, id: u64) -> PlannerError {
+        self.with_id_hint(PlannerError::PlanNotFound { id }).await
+    }
+
+    /// Builds a `PlannerError::StepNotFound` error for `id`, hinting at the
+    /// plan with that ID if one exists.
+    pub async fn step_not_found(&self, id: u64) -> PlannerError {
+        self.with_id_hint(PlannerError::StepNotFound { id }).await
+    }
+
+    /// Adds a hint to a plan or step not found error when an item of the
+    /// other kind has the ID. Other errors, and not found errors whose check
+    /// fails, are returned unchanged.
+    pub async fn with_id_hint(&self, error: PlannerError) -> PlannerError {
+        let (id, is_plan) = match error {
+            PlannerError::PlanNotFound { id } => (id, true),
+            PlannerError::StepNotFound { id } => (id, false),
+            _ => return error,
+        };
+        let hint = self
+            .run_blocking("with_id_hint", move |db| -> Result<Option<IdHint>> {
+                if is_plan {
+                    Ok(db.step_plan_id(id)?.map(|plan_id| IdHint::StepExists {
+                        step_id: id,
+                        plan_id,
+                    }))
+                } else {
+                    Ok(db
+                        .plan_exists(id)?
+                        .then_some(IdHint::PlanExists { plan_id: id }))
+                }
+            })
+            .await;
+
+        match hint {
+            Ok(Some(hint)) => PlannerError::NotFoundWithHint {
+                source: Box::new(error),
+                hint,
+            },
+            _ => error,
+        }
+    }
+
+    /// Adds a hint to the error of a failed operation like
+    /// [`Self::with_id_hint`].
+    pub(crate) async fn hinted<T>(&self, result: Result<T>) -> Result<T> {
+        match result {
+            Err(error) => Err(self.with_id_hint(error).await),
+            ok => ok,
+        }
+    }
+}
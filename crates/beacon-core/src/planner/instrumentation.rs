@@ -0,0 +1,111 @@
+//! Warnings about slow database operations.
+//!
+//! Every planner method runs in a tracing span named after it, recording
+//! the plan and step IDs it was given, and each database operation in a
+//! `database` span recording the operation and how long it took. An
+//! operation slower than the planner's threshold is also reported as a
+//! warning event.
+//!
+//! A database that is slow is usually slow for every operation, so the
+//! warnings are rate-limited: after one is emitted, further slow operations
+//! are only counted for [`SLOW_QUERY_WARNING_INTERVAL`], and the next
+//! warning says how many were left out.
+//!
+//! Without a tracing subscriber, spans and events are forwarded to the
+//! `log` crate, so `RUST_LOG=beacon_core=debug` shows them through any
+//! logger, such as the CLI's.
+
+use std::{
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use tracing::warn;
+
+/// Default time a single database operation may take before it is reported
+/// as slow.
+pub const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// Minimum time between two slow operation warnings.
+pub const SLOW_QUERY_WARNING_INTERVAL: Duration = Duration::from_secs(10);
+
+/// When the last warning was emitted and how many slow operations were
+/// left out since.
+#[derive(Debug, Default)]
+struct WarningState {
+    last_warning: Option<Instant>,
+    suppressed: u64,
+}
+
+/// Reports database operations slower than a threshold, at most once per
+/// [`SLOW_QUERY_WARNING_INTERVAL`].
+#[derive(Debug)]
+pub(crate) struct SlowQueryLog {
+    threshold: Duration,
+    state: Mutex<WarningState>,
+}
+
+impl SlowQueryLog {
+    /// Creates a log of operations taking longer than `threshold`.
+    pub(crate) fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            state: Mutex::new(WarningState::default()),
+        }
+    }
+
+    /// Warns about `operation` if it took longer than the threshold and no
+    /// warning was emitted recently, and returns whether it did.
+    pub(crate) fn record(&self, operation: &'static str, elapsed: Duration) -> bool {
+        if elapsed <= self.threshold {
+            return false;
+        }
+
+        let suppressed = {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            let now = Instant::now();
+            if state
+                .last_warning
+                .is_some_and(|last| now.duration_since(last) < SLOW_QUERY_WARNING_INTERVAL)
+            {
+                state.suppressed += 1;
+                return false;
+            }
+            state.last_warning = Some(now);
+            std::mem::take(&mut state.suppressed)
+        };
+
+        warn!(
+            operation,
+            elapsed_ms = elapsed.as_millis() as u64,
+            threshold_ms = self.threshold.as_millis() as u64,
+            suppressed,
+            "Slow database operation"
+        );
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slow_operations_are_rate_limited() {
+        let log = SlowQueryLog::new(Duration::from_millis(100));
+
+        assert!(!log.record("fast", Duration::from_millis(100)));
+        assert!(log.record("slow", Duration::from_millis(101)));
+        assert!(!log.record("slow", Duration::from_secs(5)));
+        assert!(!log.record("slower", Duration::from_secs(9)));
+
+        // The next warning reports the ones left out meanwhile
+        {
+            let mut state = log.state.lock().unwrap();
+            assert_eq!(state.suppressed, 2);
+            state.last_warning = Some(Instant::now() - SLOW_QUERY_WARNING_INTERVAL);
+        }
+        assert!(log.record("slow", Duration::from_secs(1)));
+        assert_eq!(log.state.lock().unwrap().suppressed, 0);
+    }
+}
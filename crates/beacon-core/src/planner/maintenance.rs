@@ -0,0 +1,70 @@
+//! Database maintenance for the Planner.
+
+use std::path::Path;
+
+use tracing::instrument;
+
+use super::Planner;
+use crate::{
+    error::{PlannerError, Result},
+    models::MaintenanceReport,
+};
+
+impl Planner {
+    /// Vacuums and analyzes the database and checks its integrity, reporting
+    /// how much space was reclaimed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::ReadOnly` if the planner is read-only, and
+    /// `PlannerError::Busy` if another connection kept the database locked
+    #[instrument(level = "debug", skip_all)]
+    pub async fn maintenance(&self) -> Result<MaintenanceReport> {
+        self.run_blocking_write("maintenance", |db| db.vacuum_and_analyze())
+            .await
+    }
+
+    /// Copies the database to `path`. Safe while other planners, such as a
+    /// running MCP server, use the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::FileSystem` naming `path` if it cannot be
+    /// written
+    #[instrument(level = "debug", skip_all)]
+    pub async fn backup_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref().to_path_buf();
+        self.run_blocking("backup", move |db| db.backup_to(&path))
+            .await
+    }
+
+    /// Replaces the database's contents with the beacon database at `path`,
+    /// such as a file written by [`Self::backup_to`].
+    ///
+    /// Steps in progress would lose the work recorded since the backup was
+    /// made, so restoring over a database with any is refused unless
+    /// `confirmed` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if `path` is not a beacon
+    /// database, or if steps are in progress and `confirmed` is false
+    /// Returns `PlannerError::FileSystem` naming `path` if it cannot be read
+    #[instrument(level = "debug", skip_all)]
+    pub async fn restore_from<P: AsRef<Path>>(&self, path: P, confirmed: bool) -> Result<()> {
+        let path = path.as_ref().to_path_buf();
+        self.run_blocking_write("restore", move |db| {
+            let in_progress = db.count_in_progress_steps()?;
+            if in_progress > 0 && !confirmed {
+                return Err(
+                    PlannerError::invalid_input("confirmed").with_reason(format!(
+                        "{in_progress} step(s) are in progress and would lose their progress. \
+                         Set 'confirmed' to true to restore anyway."
+                    )),
+                );
+            }
+            db.restore_from(&path)
+        })
+        .await
+    }
+}
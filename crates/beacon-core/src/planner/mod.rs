@@ -5,29 +5,44 @@
 //! between the application layers and the database, implementing all business
 //! logic for plan and step operations.
 
-use std::path::PathBuf;
+use std::{sync::Arc, time::Duration};
+
+use crate::{db::pool::ConnectionPool, models::SettingOverrides};
+use instrumentation::SlowQueryLog;
 
 // Module declarations
+pub mod blocking;
 pub mod builder;
+pub mod events;
+pub mod id_hints;
+pub mod instrumentation;
+pub mod maintenance;
 pub mod plan_handlers;
 pub mod plan_ops;
+pub mod settings;
+pub mod stats;
 pub mod step_handlers;
 pub mod step_ops;
+pub mod templates;
 
 // Integration tests moved to /tests/planner_integration_tests.rs
 
 // Re-export the main types
+pub use crate::db::pool::DEFAULT_CONNECTION_POOL_SIZE;
+pub use blocking::DEFAULT_OPERATION_TIMEOUT;
 pub use builder::PlannerBuilder;
+pub use instrumentation::DEFAULT_SLOW_QUERY_THRESHOLD;
+pub use plan_ops::CHANGE_POLL_INTERVAL;
 
 /// Main planner interface for managing plans and steps.
 #[derive(Clone)]
 pub struct Planner {
-    pub(crate) db_path: PathBuf,
-}
-
-impl Planner {
-    /// Creates a new planner with the specified database path.
-    pub(crate) fn new(db_path: PathBuf) -> Self {
-        Self { db_path }
-    }
+    /// Connections to the database, shared by all clones of the planner
+    pub(crate) pool: Arc<ConnectionPool>,
+    /// Settings fixed by the builder, taking precedence over stored ones
+    pub(crate) setting_overrides: SettingOverrides,
+    /// Time limit of a single database operation
+    pub(crate) operation_timeout: Duration,
+    /// Warns about database operations slower than the configured threshold
+    pub(crate) slow_queries: Arc<SlowQueryLog>,
 }
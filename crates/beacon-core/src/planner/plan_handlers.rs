@@ -1,25 +1,37 @@
 //! Plan handler operations that return formatted wrapper types for the Planner.
 
+use jiff::Zoned;
+use tracing::instrument;
+
 use super::Planner;
 use crate::{
     error::Result,
-    models::{Plan, PlanFilter, PlanSummary},
-    params::{DeletePlan, Id, ListPlans, SearchPlans},
+    models::{
+        Changelog, Explanation, Page, Plan, PlanFilter, PlanReport, PlanSummary,
+        changelog::parse_since,
+    },
+    params::{
+        ArchivePlan, DeletePlan, GetPlanReport, Id, ListPlans, MergePlans, PlanChangelog,
+        SearchPlans,
+    },
 };
 
 impl Planner {
     /// Handle listing plans with optional archived filtering.
     ///
     /// Converts plans to summaries with step count information for consistent
-    /// list display across interfaces.
+    /// list display across interfaces. Only the page selected by the limit
+    /// and offset of `params` is listed; an offset beyond the last plan gives
+    /// an empty page.
     ///
     /// # Arguments
     ///
-    /// * `params` - List parameters containing archived flag
+    /// * `params` - List parameters containing archived flag and paging
     ///
     /// # Returns
     ///
-    /// A PlanSummaries wrapper containing plan summary objects with step counts
+    /// The page of plan summaries with step counts, and the number of plans
+    /// matching on any page
     ///
     /// # Examples
     ///
@@ -27,19 +39,51 @@ impl Planner {
     /// # use beacon_core::{params::ListPlans, PlannerBuilder};
     /// # async {
     /// let planner = PlannerBuilder::new().build().await?;
-    /// let params = ListPlans { archived: false };
-    /// let summaries = planner.list_plans_summary(&params).await?;
+    /// let params = ListPlans {
+    ///     archived: false,
+    ///     ..Default::default()
+    /// };
+    /// let page = planner.list_plans_summary(&params).await?;
+    /// println!("showing {} of {}", page.items.len(), page.total);
     /// # Result::<(), beacon_core::PlannerError>::Ok(())
     /// # };
     /// ```
-    pub async fn list_plans_summary(
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_plans_summary(&self, params: &ListPlans) -> Result<Page<PlanSummary>> {
+        let filter = Some(PlanFilter::try_from(params)?);
+        self.list_plan_summaries_page(filter).await
+    }
+
+    /// Handle archiving a plan, optionally checking that its work is
+    /// finished.
+    ///
+    /// Archiving a plan whose steps are not all done usually means something
+    /// was forgotten, so with `strict` set such a plan is only archived if
+    /// `force` is set. Plans without steps are archived either way.
+    ///
+    /// # Returns
+    ///
+    /// The archived plan, or None if the plan doesn't exist
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanHasActiveSteps` if `strict` is set, the
+    /// plan has steps in progress or to do, and `force` is not set
+    /// Returns `PlannerError::InvalidInput` if the plan has active child
+    /// plans and `force` is not set
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn archive_plan_checked(
         &self,
-        params: &ListPlans,
-    ) -> Result<crate::display::PlanSummaries> {
-        let filter = Some(PlanFilter::from(params));
-        let plans = self.list_plans(filter).await?;
-        let summaries: Vec<PlanSummary> = plans.iter().map(Into::into).collect();
-        Ok(crate::display::PlanSummaries(summaries))
+        params: &ArchivePlan,
+        strict: bool,
+    ) -> Result<Option<Plan>> {
+        let plan_id = params.id;
+        let force = params.force;
+
+        self.run_blocking_write("archive_plan", move |db| {
+            db.archive_plan(plan_id, force, strict)
+        })
+        .await
     }
 
     /// Handle permanently deleting a plan with confirmation.
@@ -50,6 +94,8 @@ impl Planner {
     ///
     /// Requires explicit confirmation via the `confirmed` field to prevent
     /// accidental deletion. Returns an error if confirmation is not provided.
+    /// A plan with steps that are not done is only deleted if `force` is
+    /// set.
     ///
     /// # Arguments
     ///
@@ -64,6 +110,8 @@ impl Planner {
     /// # Errors
     ///
     /// Returns `PlannerError::InvalidInput` if `confirmed` field is false
+    /// Returns `PlannerError::PlanNotEmpty` if the plan has steps that are not
+    /// done and `force` is false
     ///
     /// # Examples
     ///
@@ -74,11 +122,13 @@ impl Planner {
     /// let params = DeletePlan {
     ///     id: 1,
     ///     confirmed: true,
+    ///     force: false,
     /// };
     /// let deleted_plan = planner.delete_plan(&params).await?;
     /// # Result::<(), beacon_core::PlannerError>::Ok(())
     /// # };
     /// ```
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
     pub async fn delete_plan(&self, params: &DeletePlan) -> Result<Option<Plan>> {
         // Check confirmation flag first
         if !params.confirmed {
@@ -92,14 +142,38 @@ impl Planner {
         let id_params = Id { id: params.id };
         let plan = self.get_plan(&id_params).await?;
 
-        if let Some(ref _plan) = plan {
-            // Call the underlying delete_plan_by_id method from plan_ops
-            self.delete_plan_by_id(&id_params).await?;
+        if plan.is_some() {
+            let force = params.force;
+            let result = self
+                .run_blocking_write("delete_plan", move |db| db.delete_plan(id_params.id, force))
+                .await;
+            self.hinted(result).await?;
         }
 
         Ok(plan)
     }
 
+    /// Handle merging one plan into another.
+    ///
+    /// Requires explicit confirmation via the `confirmed` field, since the
+    /// source plan is archived and its steps move to the target.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if `confirmed` field is false
+    #[instrument(level = "debug", skip_all, fields(source_id = params.source_id, target_id = params.target_id))]
+    pub async fn merge_plans_confirmed(&self, params: &MergePlans) -> Result<Plan> {
+        if !params.confirmed {
+            return Err(crate::PlannerError::InvalidInput {
+                field: "confirmed".to_string(),
+                reason: "Merging plans requires explicit confirmation. Set 'confirmed' to true to archive the source plan and move its steps to the target.".to_string(),
+            });
+        }
+
+        self.merge_plans(params.source_id, params.target_id, params.into())
+            .await
+    }
+
     /// Handle searching for plans in a specific directory with summaries.
     ///
     /// Searches for plans associated with the specified directory path,
@@ -125,25 +199,156 @@ impl Planner {
     /// let params = SearchPlans {
     ///     directory: "/path/to/project".to_string(),
     ///     archived: false,
+    ///     include_subdirectories: true,
     /// };
     /// let summaries = planner.search_plans_summary(&params).await?;
     /// # Result::<(), beacon_core::PlannerError>::Ok(())
     /// # };
     /// ```
+    #[instrument(level = "debug", skip_all)]
     pub async fn search_plans_summary(
         &self,
         params: &SearchPlans,
     ) -> Result<crate::display::PlanSummaries> {
-        let plans = if params.archived {
-            // For archived plans, use list_plans with directory filter
-            let filter = PlanFilter::for_directory(params.directory.clone(), true);
-            self.list_plans(Some(filter)).await?
+        let filter = self.search_filter(params).await?;
+        let summaries = self.list_plan_summaries(Some(filter)).await?;
+        Ok(crate::display::PlanSummaries(summaries))
+    }
+
+    /// Handle listing plans like [`Self::list_plans_summary`], also
+    /// explaining how the listing was computed.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn explain_list_plans(
+        &self,
+        params: &ListPlans,
+    ) -> Result<(Page<PlanSummary>, Explanation)> {
+        let filter = Some(PlanFilter::try_from(params)?);
+        let (items, explanation) = self.explain_plan_summaries(filter).await?;
+        // The last stage counts the plans matching the whole filter
+        let total = explanation
+            .stages
+            .last()
+            .map_or(items.len(), |stage| stage.plans);
+        let page = Page {
+            items,
+            total,
+            offset: params.offset.unwrap_or(0) as usize,
+        };
+        Ok((page, explanation))
+    }
+
+    /// Handle searching plans like [`Self::search_plans_summary`], also
+    /// explaining how the listing was computed, including the canonicalized
+    /// directory searched.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn explain_search_plans(
+        &self,
+        params: &SearchPlans,
+    ) -> Result<(crate::display::PlanSummaries, Explanation)> {
+        let filter = self.search_filter(params).await?;
+        let (summaries, explanation) = self.explain_plan_summaries(Some(filter)).await?;
+        Ok((crate::display::PlanSummaries(summaries), explanation))
+    }
+
+    /// Builds the plan filter for a directory search.
+    async fn search_filter(&self, params: &SearchPlans) -> Result<PlanFilter> {
+        if params.archived {
+            // For archived plans, filter on the directory as given
+            PlanFilter::builder()
+                .directory(params.directory.clone())
+                .include_subdirectories(params.include_subdirectories)
+                .archived(true)
+                .build()
         } else {
-            // For active plans, use the specialized search method
-            self.search_plans_by_directory(params).await?
+            // For active plans, canonicalize the directory like plan creation
+            self.directory_filter(params).await
+        }
+    }
+
+    /// Handle producing the changelog of a plan.
+    ///
+    /// Parses the `since` window relative to the current local time and
+    /// reconstructs the plan's changes within it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if `since` is not an age or date,
+    /// and `PlannerError::PlanNotFound` if the plan doesn't exist
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use beacon_core::{params::PlanChangelog, PlannerBuilder};
+    /// # async {
+    /// let planner = PlannerBuilder::new().build().await?;
+    /// let params = PlanChangelog {
+    ///     id: 1,
+    ///     since: Some("30d".to_string()),
+    /// };
+    /// println!("{}", planner.changelog(&params).await?);
+    /// # Result::<(), beacon_core::PlannerError>::Ok(())
+    /// # };
+    /// ```
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn changelog(&self, params: &PlanChangelog) -> Result<Changelog> {
+        let since = params
+            .since
+            .as_deref()
+            .map(|since| parse_since(since, &Zoned::now()))
+            .transpose()?;
+        self.plan_changelog(params.id, since).await
+    }
+
+    /// Handle producing a standup report for a plan.
+    ///
+    /// Parses the `since` window relative to the current local time, the
+    /// last day if it is omitted, and reports the plan's progress within it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if `since` is not an age or date,
+    /// and `PlannerError::PlanNotFound` if the plan doesn't exist
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn report(&self, params: &GetPlanReport) -> Result<PlanReport> {
+        let since = params
+            .since
+            .as_deref()
+            .map(|since| parse_since(since, &Zoned::now()))
+            .transpose()?;
+        self.plan_report(&Id { id: params.id }, since).await
+    }
+
+    /// Handle producing a handoff brief for a plan.
+    ///
+    /// Composes the plan's description and steps into a concise markdown
+    /// brief listing the goal, completed steps with their results, steps in
+    /// progress, and remaining steps.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Id parameters containing the plan ID
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use beacon_core::{params::Id, PlannerBuilder};
+    /// # async {
+    /// let planner = PlannerBuilder::new().build().await?;
+    /// let brief = planner.handoff_summary(&Id { id: 1 }).await?;
+    /// println!("{brief}");
+    /// # Result::<(), beacon_core::PlannerError>::Ok(())
+    /// # };
+    /// ```
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn handoff_summary(&self, params: &Id) -> Result<crate::display::HandoffSummary> {
+        let Some(plan) = self.get_plan(params).await? else {
+            return Err(self.plan_not_found(params.id).await);
         };
 
-        let summaries: Vec<PlanSummary> = plans.iter().map(Into::into).collect();
-        Ok(crate::display::PlanSummaries(summaries))
+        Ok(crate::display::HandoffSummary(plan))
     }
 }
@@ -1,137 +1,581 @@
 //! Plan operations for the Planner.
 
-use tokio::task;
+use std::time::Duration;
+
+use jiff::Timestamp;
+use tokio::time::{Instant, sleep};
+use tracing::instrument;
 
 use super::Planner;
 use crate::{
-    db::Database,
+    criteria::normalize_acceptance_criteria,
     error::{PlannerError, Result},
-    models::{Plan, PlanFilter},
-    params::{CreatePlan, Id, SearchPlans},
+    models::{
+        Changelog, ConflictPolicy, CreatePlanRequest, CreateStepRequest, Explanation,
+        ImportOutcome, MergeOptions, Page, Plan, PlanFilter, PlanReport, PlanSummary, StepOutline,
+        StepStatusCounts, UpdatePlanRequest, parse_date,
+    },
+    params::{ArchivePlan, CreatePlan, Id, SearchPlans, UpdatePlan, validate_text, validate_title},
 };
 
+/// Time between checks in [`Planner::wait_for_change`].
+pub const CHANGE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Period covered by [`Planner::plan_report`] when no start is given: a day,
+/// as for a daily standup.
+pub const DEFAULT_REPORT_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+
 impl Planner {
     /// Creates a new plan with the given title, optional description, and
     /// optional directory. The directory path will always be stored as an
     /// absolute path. If a relative path is provided, it will be converted
     /// to absolute using the current working directory. If no directory is
-    /// provided, the current working directory will be used.
+    /// provided, the current working directory will be used. If git root
+    /// inference is enabled, either by the `infer_git_root` setting or by
+    /// the request, the enclosing repository root is stored instead.
+    ///
+    /// If the `unique_plan_per_directory` setting is enabled, an existing
+    /// active plan with the same title in the same directory is returned
+    /// instead of creating a duplicate. Plans archive themselves once done
+    /// if the request or, failing that, the `auto_archive` setting says so.
+    /// The title and description are checked and trimmed by
+    /// [`CreatePlan::validate`].
+    #[instrument(level = "debug", skip_all)]
     pub async fn create_plan(&self, params: &CreatePlan) -> Result<Plan> {
-        let db_path = self.db_path.clone();
-        let title = params.title.clone();
-        let description = params.description.clone();
-        let directory = params.directory.clone();
+        self.create_plan_with_steps(params, Vec::new()).await
+    }
+
+    /// Creates a new plan together with its initial steps.
+    ///
+    /// Behaves like [`Self::create_plan`], but also appends `steps` in order
+    /// within the same transaction, so a failure leaves no partial plan
+    /// behind. Steps may start out done when they carry a result, which
+    /// suits recording work that already happened.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn create_plan_with_steps(
+        &self,
+        params: &CreatePlan,
+        mut steps: Vec<CreateStepRequest>,
+    ) -> Result<Plan> {
+        let params = &params.validate()?;
+        let settings = self.settings().await?;
+        for step in &mut steps {
+            step.title = validate_title("title", &step.title)?;
+            step.description = validate_text("description", step.description.as_deref())?;
+            if let Some(result) = &step.result {
+                settings
+                    .check_result_length(result)
+                    .map_err(|reason| PlannerError::invalid_input("result").with_reason(reason))?;
+            }
+            step.acceptance_criteria = step
+                .acceptance_criteria
+                .as_deref()
+                .map(normalize_acceptance_criteria);
+        }
+        let request = CreatePlanRequest {
+            title: params.title.clone(),
+            description: params.description.clone(),
+            directory: params.directory.clone(),
+            infer_git_root: params.infer_git_root.unwrap_or(settings.infer_git_root),
+            unique_per_directory: settings.unique_plan_per_directory,
+            parent_plan_id: params.parent_plan_id,
+            auto_archive: params.auto_archive.unwrap_or(settings.auto_archive),
+            due_date: params
+                .due_date
+                .as_deref()
+                .map(|date| parse_date("due_date", date))
+                .transpose()?,
+        };
 
-        task::spawn_blocking(move || {
-            let mut db = Database::new(&db_path)?;
-            db.create_plan(&title, description.as_deref(), directory.as_deref())
+        self.run_blocking_write("create_plan_with_steps", move |db| {
+            db.create_plan_with_steps(&request, &steps)
         })
         .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })?
     }
 
     /// Retrieves a plan by its ID.
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
     pub async fn get_plan(&self, params: &Id) -> Result<Option<Plan>> {
-        let db_path = self.db_path.clone();
         let plan_id = params.id;
 
-        task::spawn_blocking(move || {
-            let db = Database::new(&db_path)?;
-            db.get_plan(plan_id)
-        })
-        .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })?
+        self.run_blocking("get_plan", move |db| db.get_plan(plan_id))
+            .await
+    }
+
+    /// Retrieves the summary of a plan, archived or not, with its step
+    /// counts.
+    ///
+    /// Cheaper than [`Self::get_plan`] when only the progress is needed.
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn get_plan_summary(&self, params: &Id) -> Result<Option<PlanSummary>> {
+        let plan_id = params.id;
+
+        self.run_blocking("get_plan_summary", move |db| db.get_plan_summary(plan_id))
+            .await
+    }
+
+    /// Retrieves the outline of a plan: the ids, orders, titles and statuses
+    /// of its steps, in order.
+    ///
+    /// Cheaper than [`Self::get_plan`] when step details are not needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    #[instrument(level = "debug", skip_all, fields(plan_id))]
+    pub async fn get_plan_outline(&self, plan_id: u64) -> Result<Vec<StepOutline>> {
+        let result = self
+            .run_blocking("get_plan_outline", move |db| db.get_plan_outline(plan_id))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Counts the steps of a plan in each status.
+    ///
+    /// Cheaper than [`Self::get_plan`] for telling how far along a plan is.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn plan_progress(&self, params: &Id) -> Result<StepStatusCounts> {
+        let plan_id = params.id;
+        let result = self
+            .run_blocking("plan_progress", move |db| db.step_status_counts(plan_id))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Reconstructs the changelog of a plan, limited to changes at or after
+    /// `since` if given.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    #[instrument(level = "debug", skip_all, fields(plan_id))]
+    pub async fn plan_changelog(
+        &self,
+        plan_id: u64,
+        since: Option<Timestamp>,
+    ) -> Result<Changelog> {
+        let Some(plan) = self.get_plan(&Id { id: plan_id }).await? else {
+            return Err(self.plan_not_found(plan_id).await);
+        };
+        Ok(Changelog::from_plan(&plan, since))
+    }
+
+    /// Reports a plan's progress since `since`, or over the last
+    /// [`DEFAULT_REPORT_PERIOD`]: the steps completed since then, those in
+    /// progress and the next todo steps.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn plan_report(&self, params: &Id, since: Option<Timestamp>) -> Result<PlanReport> {
+        let plan_id = params.id;
+        let since = since.unwrap_or_else(|| Timestamp::now() - DEFAULT_REPORT_PERIOD);
+
+        let result = self
+            .run_blocking("plan_report", move |db| db.plan_report(plan_id, since))
+            .await;
+        self.hinted(result).await
     }
 
     /// Lists all plans with optional filtering.
+    #[instrument(level = "debug", skip_all)]
     pub async fn list_plans(&self, filter: Option<PlanFilter>) -> Result<Vec<Plan>> {
-        let db_path = self.db_path.clone();
+        self.run_blocking("list_plans", move |db| db.list_plans(filter.as_ref()))
+            .await
+    }
 
-        task::spawn_blocking(move || {
-            let db = Database::new(&db_path)?;
-            db.list_plans(filter.as_ref())
-        })
-        .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })?
+    /// Lists plan summaries with optional filtering.
+    ///
+    /// Step statistics are computed by the database, so steps are not loaded.
+    /// Each summary carries the planner's in-progress warning threshold.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_plan_summaries(
+        &self,
+        filter: Option<PlanFilter>,
+    ) -> Result<Vec<PlanSummary>> {
+        let threshold = self.settings().await?.in_progress_warning_threshold;
+
+        let mut summaries = self
+            .run_blocking("list_plan_summaries", move |db| {
+                db.list_plan_summaries(filter.as_ref())
+            })
+            .await?;
+
+        for summary in &mut summaries {
+            summary.in_progress_warning_threshold = Some(threshold);
+        }
+
+        Ok(summaries)
+    }
+
+    /// Lists the page of plan summaries selected by the limit and offset of
+    /// `filter`, together with the number of plans matching it.
+    ///
+    /// An offset beyond the last matching plan gives an empty page.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_plan_summaries_page(
+        &self,
+        filter: Option<PlanFilter>,
+    ) -> Result<Page<PlanSummary>> {
+        let threshold = self.settings().await?.in_progress_warning_threshold;
+
+        let mut page = self
+            .run_blocking("list_plan_summaries_page", move |db| {
+                db.list_plan_summaries_page(filter.as_ref())
+            })
+            .await?;
+
+        for summary in &mut page.items {
+            summary.in_progress_warning_threshold = Some(threshold);
+        }
+
+        Ok(page)
+    }
+
+    /// Lists plan summaries like [`Self::list_plan_summaries`], also
+    /// explaining how the listing was computed.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn explain_plan_summaries(
+        &self,
+        filter: Option<PlanFilter>,
+    ) -> Result<(Vec<PlanSummary>, Explanation)> {
+        let threshold = self.settings().await?.in_progress_warning_threshold;
+        let database_path = self.pool.config().path.clone();
+
+        let (mut summaries, stages, filter) = self
+            .run_blocking("explain_plan_summaries", move |db| {
+                let (summaries, stages) = db.explain_plan_summaries(filter.as_ref())?;
+                Ok::<_, PlannerError>((summaries, stages, filter))
+            })
+            .await?;
+
+        for summary in &mut summaries {
+            summary.in_progress_warning_threshold = Some(threshold);
+        }
+
+        let explanation = Explanation {
+            database_path,
+            filter,
+            stages,
+        };
+        Ok((summaries, explanation))
+    }
+
+    /// Canonicalizes a search directory into a plan filter using the same
+    /// logic as plan creation.
+    pub(crate) async fn directory_filter(&self, params: &SearchPlans) -> Result<PlanFilter> {
+        let directory = params.directory.clone();
+
+        let canonicalized_directory = self
+            .run_blocking("directory_filter", move |db| {
+                db.canonicalize_directory_for_search(&directory)
+            })
+            .await?;
+
+        PlanFilter::builder()
+            .directory(canonicalized_directory)
+            .include_subdirectories(params.include_subdirectories)
+            .build()
     }
 
     /// Search for plans in a specific directory.
     /// The directory path can be relative or absolute.
-    /// Returns all plans that have directories starting with the provided path.
+    /// Returns all plans in the directory, and in its subdirectories unless
+    /// `include_subdirectories` is unset. A directory only sharing a prefix
+    /// with the path, such as `/work/api-docs` for `/work/api`, is not a
+    /// subdirectory.
+    #[instrument(level = "debug", skip_all)]
     pub async fn search_plans_by_directory(&self, params: &SearchPlans) -> Result<Vec<Plan>> {
-        let db_path = self.db_path.clone();
-        let directory = params.directory.clone();
+        let filter = self.directory_filter(params).await?;
+        self.list_plans(Some(filter)).await
+    }
+
+    /// Updates a plan's title, description, directory, or parent plan.
+    /// Returns the updated plan, including its steps and child plans.
+    ///
+    /// A new directory is stored as in [`Self::create_plan`], inferring the
+    /// git root if the `infer_git_root` setting is enabled. An update that
+    /// sets nothing leaves the plan untouched.
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn update_plan(&self, params: &UpdatePlan) -> Result<Plan> {
+        let plan_id = params.id;
+        let request = UpdatePlanRequest {
+            infer_git_root: self.settings().await?.infer_git_root,
+            ..UpdatePlanRequest::try_from(params)?
+        };
 
-        // Canonicalize the directory path using the same logic as plan creation
-        let canonicalized_directory = task::spawn_blocking(move || {
-            let db = Database::new(&db_path)?;
-            db.canonicalize_directory_for_search(&directory)
+        let result = self
+            .run_blocking_write("update_plan", move |db| db.update_plan(plan_id, &request))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Moves every plan in the `from` directory or below it to the
+    /// corresponding path under `to`, in one transaction, and returns the
+    /// number of plans moved, which is zero if none matched.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if either directory is empty, or
+    /// if one of the two directories contains the other
+    #[instrument(level = "debug", skip_all)]
+    pub async fn reassign_directory(&self, from: &str, to: &str) -> Result<u32> {
+        let (from, to) = (from.to_string(), to.to_string());
+        self.run_blocking_write("reassign_directory", move |db| {
+            db.reassign_directory(&from, &to)
         })
         .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })??;
-
-        let filter = PlanFilter {
-            directory: Some(canonicalized_directory),
-            ..Default::default()
-        };
-        self.list_plans(Some(filter)).await
     }
 
     /// Archives a plan (soft delete).
     /// Returns the archived plan details if successful, None if the plan
-    /// doesn't exist.
-    pub async fn archive_plan(&self, params: &Id) -> Result<Option<Plan>> {
-        let db_path = self.db_path.clone();
-        let plan_id = params.id;
+    /// doesn't exist. A plan with active child plans is only archived when
+    /// `force` is set.
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn archive_plan(&self, params: &ArchivePlan) -> Result<Option<Plan>> {
+        self.archive_plan_checked(params, false).await
+    }
 
-        task::spawn_blocking(move || {
-            let mut db = Database::new(&db_path)?;
-            db.archive_plan(plan_id)
+    /// Archives every active plan whose steps are all done, optionally only
+    /// those in `directory` or below it, and returns their summaries.
+    ///
+    /// Either all of the plans are archived or none are. Plans without steps
+    /// are not complete, and a parent plan stays active while any of its
+    /// child plans does.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn archive_completed_plans(
+        &self,
+        directory: Option<String>,
+    ) -> Result<Vec<PlanSummary>> {
+        self.run_blocking_write("archive_completed_plans", move |db| {
+            db.archive_completed_plans(directory.as_deref())
         })
         .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })?
     }
 
     /// Unarchives a plan (restores from archive).
     /// Returns the unarchived plan details if successful, None if the plan
     /// doesn't exist.
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
     pub async fn unarchive_plan(&self, params: &Id) -> Result<Option<Plan>> {
-        let db_path = self.db_path.clone();
         let plan_id = params.id;
 
-        task::spawn_blocking(move || {
-            let mut db = Database::new(&db_path)?;
-            db.unarchive_plan(plan_id)
+        self.run_blocking_write("unarchive_plan", move |db| db.unarchive_plan(plan_id))
+            .await
+    }
+
+    /// Merges plan `source_id` into plan `target_id` in one transaction and
+    /// returns the merged target plan.
+    ///
+    /// The source's steps are appended to the target in their current order
+    /// with their statuses, results and references intact, and the source is
+    /// archived with a pointer to the target. See
+    /// [`Database::merge_plans`](crate::db::Database::merge_plans) for what
+    /// is carried over.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the source was already merged
+    #[instrument(level = "debug", skip_all, fields(source_id, target_id))]
+    pub async fn merge_plans(
+        &self,
+        source_id: u64,
+        target_id: u64,
+        options: MergeOptions,
+    ) -> Result<Plan> {
+        let result = self
+            .run_blocking_write("merge_plans", move |db| {
+                db.merge_plans(source_id, target_id, options)
+            })
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Finds the active plan that importing `plan` would conflict with: one
+    /// with the same title in the same directory.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn find_import_conflict(&self, plan: &Plan) -> Result<Option<Plan>> {
+        let plan = plan.clone();
+
+        self.run_blocking("find_import_conflict", move |db| {
+            db.find_import_conflict(&plan)
         })
         .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })?
     }
 
-    /// Permanently deletes a plan and all its associated steps.
-    /// This operation cannot be undone.
+    /// Imports a plan exported from this or another database, resolving a
+    /// conflict with an active plan of the same title and directory as
+    /// `policy` says. See
+    /// [`Database::import_plan`](crate::db::Database::import_plan) for what
+    /// is carried over.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the plan has no title or a
+    /// done step without a result
+    #[instrument(level = "debug", skip_all, fields(policy = %policy))]
+    pub async fn import_plan(&self, plan: &Plan, policy: ConflictPolicy) -> Result<ImportOutcome> {
+        let plan = plan.clone();
+
+        self.run_blocking_write("import_plan", move |db| db.import_plan(&plan, policy))
+            .await
+    }
+
+    /// Copies a plan and its steps into a new active plan titled
+    /// `new_title`, or like the original, and returns the copy.
+    ///
+    /// The copied steps start out todo without results. See
+    /// [`Database::clone_plan`](crate::db::Database::clone_plan) for what is
+    /// carried over.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    /// Returns `PlannerError::InvalidInput` if `new_title` is empty or longer
+    /// than [`MAX_TITLE_LEN`](crate::params::MAX_TITLE_LEN)
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn clone_plan(&self, params: &Id, new_title: Option<String>) -> Result<Plan> {
+        let plan_id = params.id;
+        let new_title = new_title
+            .as_deref()
+            .map(|title| validate_title("title", title))
+            .transpose()?;
+
+        let result = self
+            .run_blocking_write("clone_plan", move |db| {
+                db.clone_plan(plan_id, new_title.as_deref())
+            })
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Appends `text` to a plan's working notes under a heading with the
+    /// current time, and returns the updated plan.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    /// Returns `PlannerError::InvalidInput` if `text` is empty
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn append_plan_note(&self, params: &Id, text: &str) -> Result<Plan> {
+        let plan_id = params.id;
+        let text = text.to_string();
+
+        let result = self
+            .run_blocking_write("append_plan_note", move |db| {
+                db.append_plan_note(plan_id, &text)
+            })
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Replaces a plan's working notes with `text`, removing them if it is
+    /// empty, and returns the updated plan.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn set_plan_notes(&self, params: &Id, text: &str) -> Result<Plan> {
+        let plan_id = params.id;
+        let text = text.to_string();
+
+        let result = self
+            .run_blocking_write("set_plan_notes", move |db| {
+                db.set_plan_notes(plan_id, &text)
+            })
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Renumbers a plan's steps in their current sequence, repairing steps
+    /// that share an order, e.g. after a crash in the middle of a write.
+    /// Returns the number of steps renumbered.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn repair_plan(&self, params: &Id) -> Result<usize> {
+        let plan_id = params.id;
+
+        let result = self
+            .run_blocking_write("repair_plan", move |db| db.renumber_steps(plan_id))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Permanently deletes a plan and all its associated steps, whatever
+    /// their status. This operation cannot be undone.
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
     pub async fn delete_plan_by_id(&self, params: &Id) -> Result<()> {
-        let db_path = self.db_path.clone();
         let plan_id = params.id;
 
-        task::spawn_blocking(move || {
-            let mut db = Database::new(&db_path)?;
-            db.delete_plan(plan_id)
-        })
-        .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })?
+        let result = self
+            .run_blocking_write("delete_plan_by_id", move |db| db.delete_plan(plan_id, true))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Waits until a plan changes after `since`, checking every
+    /// [`CHANGE_POLL_INTERVAL`]. Returns when it last changed, or None if
+    /// it didn't change within `timeout`.
+    ///
+    /// A change is anything that updates the plan's `updated_at`: the plan
+    /// itself, or any of its steps being added, changed, reordered or
+    /// removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist or is
+    /// deleted while waiting
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn wait_for_change(
+        &self,
+        params: &Id,
+        since: Timestamp,
+        timeout: Duration,
+    ) -> Result<Option<Timestamp>> {
+        self.wait_for_change_every(params, since, timeout, CHANGE_POLL_INTERVAL)
+            .await
+    }
+
+    /// Waits like [`Self::wait_for_change`], checking every `interval`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist or is
+    /// deleted while waiting
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn wait_for_change_every(
+        &self,
+        params: &Id,
+        since: Timestamp,
+        timeout: Duration,
+        interval: Duration,
+    ) -> Result<Option<Timestamp>> {
+        let plan_id = params.id;
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let updated_at = self
+                .run_blocking("wait_for_change", move |db| db.plan_updated_at(plan_id))
+                .await?
+                .ok_or(PlannerError::PlanNotFound { id: plan_id })?;
+            if updated_at > since {
+                return Ok(Some(updated_at));
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            sleep(interval.min(deadline - now)).await;
+        }
     }
 }
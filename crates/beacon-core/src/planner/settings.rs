@@ -0,0 +1,149 @@
+//! Workspace settings for the Planner.
+//!
+//! Stored settings are read from the database on every use rather than
+//! cached, so a long-running planner, such as the one behind `b serve`,
+//! sees settings changed by other processes without being rebuilt.
+
+use std::collections::BTreeSet;
+
+use tracing::instrument;
+
+use super::Planner;
+use crate::{
+    error::{PlannerError, Result},
+    models::{SettingKey, SettingSource, SettingValue, Settings},
+    params::SetConfig,
+};
+
+/// The settings stored in a workspace's database, and which of them were
+/// set rather than left at their default.
+#[derive(Debug, Default)]
+pub(crate) struct StoredSettings {
+    settings: Settings,
+    keys: BTreeSet<SettingKey>,
+}
+
+impl StoredSettings {
+    /// Parses stored `entries`, ignoring unknown keys and invalid values.
+    pub(crate) fn from_entries(entries: Vec<(String, String)>) -> Self {
+        let mut stored = Self::default();
+        for (key, value) in entries {
+            if let Ok(key) = key.parse()
+                && stored.settings.set(key, &value).is_ok()
+            {
+                stored.keys.insert(key);
+            }
+        }
+        stored
+    }
+}
+
+impl Planner {
+    /// The settings in effect: the workspace's stored settings over the
+    /// defaults, overridden by the flags the planner was built with.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn settings(&self) -> Result<Settings> {
+        let mut settings = self.stored_settings().await?.settings;
+        self.setting_overrides.apply(&mut settings);
+        Ok(settings)
+    }
+
+    /// The value a setting has, and where it comes from.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if there is no such setting
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_setting(&self, key: &str) -> Result<SettingValue> {
+        let key = parse_key(key)?;
+        let stored = self.stored_settings().await?;
+        Ok(self.setting_value(&stored, key))
+    }
+
+    /// Lists the value of every setting, and where it comes from.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_settings(&self) -> Result<Vec<SettingValue>> {
+        let stored = self.stored_settings().await?;
+        Ok(SettingKey::ALL
+            .into_iter()
+            .map(|key| self.setting_value(&stored, key))
+            .collect())
+    }
+
+    /// Validates `value` for a setting and stores it in the workspace's
+    /// database.
+    ///
+    /// The value takes effect right away for every planner on the
+    /// workspace, unless it was built with an override for the setting.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if there is no such setting or
+    /// the value is not valid for it
+    #[instrument(level = "debug", skip_all, fields(key))]
+    pub async fn set_setting(&self, key: &str, value: &str) -> Result<SettingValue> {
+        let key = parse_key(key)?;
+        let mut settings = Settings::default();
+        settings
+            .set(key, value)
+            .map_err(|reason| PlannerError::invalid_input("value").with_reason(reason))?;
+
+        // Stored in canonical form, e.g. "7d" for "168h"
+        let value = settings.get(key);
+        let entries = self
+            .run_blocking_write("set_setting", move |db| {
+                db.store_setting(key.as_str(), &value)?;
+                db.load_settings()
+            })
+            .await?;
+
+        Ok(self.setting_value(&StoredSettings::from_entries(entries), key))
+    }
+
+    /// Sets a setting like [`Self::set_setting`] on behalf of an agent,
+    /// which may only change [agent-safe](SettingKey::agent_safe) settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the setting is not agent-safe,
+    /// besides the errors of [`Self::set_setting`]
+    #[instrument(level = "debug", skip_all)]
+    pub async fn set_agent_setting(&self, params: &SetConfig) -> Result<SettingValue> {
+        let key = parse_key(&params.key)?;
+        if !key.agent_safe() {
+            return Err(PlannerError::invalid_input("key").with_reason(format!(
+                "Setting '{key}' can only be changed from the command line, with `b config set`"
+            )));
+        }
+        self.set_setting(&params.key, &params.value).await
+    }
+
+    async fn stored_settings(&self) -> Result<StoredSettings> {
+        let entries = self
+            .run_blocking("load_settings", |db| db.load_settings())
+            .await?;
+        Ok(StoredSettings::from_entries(entries))
+    }
+
+    fn setting_value(&self, stored: &StoredSettings, key: SettingKey) -> SettingValue {
+        let source = if self.setting_overrides.contains(key) {
+            SettingSource::Override
+        } else if stored.keys.contains(&key) {
+            SettingSource::Workspace
+        } else {
+            SettingSource::Default
+        };
+        let mut settings = stored.settings.clone();
+        self.setting_overrides.apply(&mut settings);
+        SettingValue {
+            key,
+            value: settings.get(key),
+            source,
+        }
+    }
+}
+
+fn parse_key(key: &str) -> Result<SettingKey> {
+    key.parse()
+        .map_err(|reason: String| PlannerError::invalid_input("key").with_reason(reason))
+}
@@ -0,0 +1,63 @@
+//! Statistics for periodic reviews for the Planner.
+
+use jiff::{Timestamp, Zoned};
+use tracing::instrument;
+
+use super::Planner;
+use crate::{
+    error::{PlannerError, Result},
+    models::{PlannerStats, changelog::parse_since},
+    params::GetStats,
+};
+
+impl Planner {
+    /// Computes statistics for `range`, from its start to its end, both
+    /// inclusive, or over all time without one: plans created, archived
+    /// and touched, steps created and completed, the completion rate, the
+    /// average time to done and the busiest directories. An empty database
+    /// gives a zeroed report.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use beacon_core::PlannerBuilder;
+    /// # use jiff::{Timestamp, ToSpan};
+    /// # async {
+    /// let planner = PlannerBuilder::new().build().await?;
+    /// let now = Timestamp::now();
+    /// let week = planner.stats(Some((now - 168.hours(), now))).await?;
+    /// println!("{week}");
+    /// # Ok::<(), beacon_core::PlannerError>(())
+    /// # };
+    /// ```
+    #[instrument(level = "debug", skip_all)]
+    pub async fn stats(&self, range: Option<(Timestamp, Timestamp)>) -> Result<PlannerStats> {
+        self.run_blocking("stats", move |db| db.stats(range)).await
+    }
+
+    /// Handle computing statistics, from `since`, an age such as "7d" or a
+    /// date, until now, or over all time without it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if `since` can't be parsed or
+    /// is in the future
+    #[instrument(level = "debug", skip_all)]
+    pub async fn stats_since(&self, params: &GetStats) -> Result<PlannerStats> {
+        let now = Zoned::now();
+        let range = match params.since.as_deref() {
+            Some(text) => {
+                let since = parse_since(text, &now)?;
+                if since > now.timestamp() {
+                    return Err(PlannerError::invalid_input("since").with_reason(format!(
+                        "Invalid value '{}': the date is in the future",
+                        text.trim()
+                    )));
+                }
+                Some((since, now.timestamp()))
+            }
+            None => None,
+        };
+        self.stats(range).await
+    }
+}
@@ -1,10 +1,13 @@
 //! Step handler operations that return formatted wrapper types for the Planner.
 
+use tracing::instrument;
+
 use super::Planner;
 use crate::{
-    error::Result,
-    models::Step,
-    params::{Id, UpdateStep},
+    display::{Steps, StepsByPlan, UpdateResult},
+    error::{PlannerError, Result},
+    models::{Step, StepChange, StepStatus},
+    params::{FindSteps, Id, ListStepsByStatus, RemoveStep, UpdateStep},
 };
 
 impl Planner {
@@ -34,24 +37,116 @@ impl Planner {
     ///     title: None,
     ///     description: None,
     ///     acceptance_criteria: None,
+    ///     expected_output: None,
     ///     references: None,
     ///     result: Some("Completed successfully".to_string()),
+    ///     clear_result: false,
+    ///     blocked_reason: None,
+    ///     verification_required: None,
     /// };
     /// let updated_step = planner.update_step_validated(&params).await?;
     /// # Result::<(), beacon_core::PlannerError>::Ok(())
     /// # };
     /// ```
+    #[instrument(level = "debug", skip_all, fields(step_id = params.id))]
     pub async fn update_step_validated(&self, params: &UpdateStep) -> Result<Option<Step>> {
-        let step = self.get_step(&Id { id: params.id }).await?;
+        Ok(self
+            .update_step_with_changes(params)
+            .await?
+            .map(|result| result.resource))
+    }
+
+    /// Updates a step like [`Self::update_step_validated`], also returning
+    /// the fields that changed with their old and new values.
+    ///
+    /// The changes are found by comparing the step before and after the
+    /// update, so fields set to the values they already had are left out,
+    /// and an update changing nothing yields no changes.
+    ///
+    /// # Returns
+    ///
+    /// The updated step and its changes, or None if the step doesn't exist
+    #[instrument(level = "debug", skip_all, fields(step_id = params.id))]
+    pub async fn update_step_with_changes(
+        &self,
+        params: &UpdateStep,
+    ) -> Result<Option<UpdateResult<Step, StepChange>>> {
+        let Some(before) = self.get_step(&Id { id: params.id }).await? else {
+            return Ok(None);
+        };
+
+        let update_request = params.clone().try_into()?;
+        self.update_step(params.id, update_request).await?;
 
-        if step.is_some() {
-            let update_request = params.clone().try_into()?;
+        Ok(self.get_step(&Id { id: params.id }).await?.map(|after| {
+            let changes = StepChange::between(&before, &after);
+            UpdateResult::with_changes(after, changes)
+        }))
+    }
 
-            self.update_step(params.id, update_request).await?;
+    /// Handle removing a step by moving it to the trash.
+    ///
+    /// Steps in progress are only removed with `force`, as another agent may
+    /// be working on them.
+    ///
+    /// # Returns
+    ///
+    /// The removed step, or None if the step doesn't exist
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the step is in progress and
+    /// `force` is not set
+    #[instrument(level = "debug", skip_all, fields(step_id = params.id))]
+    pub async fn remove_step_checked(&self, params: &RemoveStep) -> Result<Option<Step>> {
+        let id_params = Id { id: params.id };
+        let Some(step) = self.get_step(&id_params).await? else {
+            return Ok(None);
+        };
 
-            self.get_step(&Id { id: params.id }).await
-        } else {
-            Ok(None)
+        if step.status == StepStatus::InProgress && !params.force {
+            return Err(PlannerError::invalid_input("force").with_reason(format!(
+                "Step {} is in progress and may be worked on by someone else. Set 'force' to true to remove it anyway.",
+                step.id
+            )));
         }
+
+        self.remove_step(&id_params).await?;
+        Ok(Some(step))
+    }
+
+    /// Handle finding the steps of a plan by their text, as
+    /// [`Self::find_steps`] does, with the status filter validated.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the query is empty or the
+    /// status is not valid
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.plan_id))]
+    pub async fn find_steps_validated(&self, params: &FindSteps) -> Result<Steps> {
+        let status = params.validate()?;
+        let steps = self
+            .find_steps(params.plan_id, &params.query, status)
+            .await?;
+        Ok(Steps::owned(steps))
+    }
+
+    /// Handle listing the steps with a status across plans, as
+    /// [`Self::list_steps_by_status`] does, with the status validated.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the status is not valid
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_steps_by_status_validated(
+        &self,
+        params: &ListStepsByStatus,
+    ) -> Result<StepsByPlan> {
+        let status = params.validate()?;
+        let steps = self
+            .list_steps_by_status(status, params.include_archived_plans)
+            .await?;
+        Ok(StepsByPlan::owned(steps))
     }
 }
@@ -1,161 +1,752 @@
 //! Step operations for the Planner.
 
-use tokio::task;
+use std::{collections::BTreeMap, path::Path};
+
+use jiff::{SignedDuration, Timestamp, Zoned};
+use tracing::instrument;
 
 use super::Planner;
 use crate::{
+    criteria::normalize_acceptance_criteria,
     db::Database,
     error::{PlannerError, Result},
-    models::{Step, UpdateStepRequest},
-    params::{Id, InsertStep, StepCreate, SwapSteps},
+    models::{
+        AllowedTransitions, ClaimOutcome, DuplicateGroup, DuplicateScope, Momentum, PlanSummary,
+        StatusLabels, Step, StepNote, StepOutline, StepReference, StepStatus, TrashedStep,
+        UpdateStepRequest,
+    },
+    params::{
+        AddStepNote, Id, InsertStep, ListSteps, MoveStep, PurgeTrash, SetStepPhase, StepCreate,
+        StepDependency, SwapSteps, VerifyStep,
+    },
+    references::validate_references,
 };
 
 impl Planner {
     /// Adds a new step to the specified plan with optional description,
-    /// acceptance criteria and references.
+    /// acceptance criteria and references. The parameters are checked and
+    /// trimmed by [`StepCreate::validate`], the acceptance criteria
+    /// normalized as in [`normalize_acceptance_criteria`], and with the
+    /// `strict_references` setting on, the references validated as in
+    /// [`validate_references`].
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.plan_id))]
     pub async fn add_step(&self, params: &StepCreate) -> Result<Step> {
-        let db_path = self.db_path.clone();
+        let params = &params.validate()?;
         let title = params.title.clone();
         let description = params.description.clone();
-        let acceptance_criteria = params.acceptance_criteria.clone();
+        let acceptance_criteria = params
+            .acceptance_criteria
+            .as_deref()
+            .map(normalize_acceptance_criteria);
         let references = params.references.clone();
         let plan_id = params.plan_id;
+        let options = CreateOptions::from(params);
+        let strict_references = self.settings().await?.strict_references;
 
-        task::spawn_blocking(move || {
-            let mut db = Database::new(&db_path)?;
-            db.add_step(
-                plan_id,
-                &title,
-                description.as_deref(),
-                acceptance_criteria.as_deref(),
-                references,
-            )
-        })
-        .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })?
+        let result = self
+            .run_blocking_write("add_step", move |db| {
+                let references =
+                    Self::prepare_references(db, plan_id, &references, strict_references)?;
+                let step = db.add_step(
+                    plan_id,
+                    &title,
+                    description.as_deref(),
+                    acceptance_criteria.as_deref(),
+                    references,
+                )?;
+                Self::apply_create_options(db, step, options.clone())
+            })
+            .await;
+        self.hinted(result).await
     }
 
     /// Inserts a new step at a specific position in the plan's step order.
+    ///
+    /// The step is checked and trimmed, and its references validated, as when
+    /// adding it.
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.step.plan_id))]
     pub async fn insert_step(&self, params: &InsertStep) -> Result<Step> {
-        let db_path = self.db_path.clone();
+        let params = &InsertStep {
+            step: params.step.validate()?,
+            position: params.position,
+        };
         let title = params.step.title.clone();
         let description = params.step.description.clone();
-        let acceptance_criteria = params.step.acceptance_criteria.clone();
+        let acceptance_criteria = params
+            .step
+            .acceptance_criteria
+            .as_deref()
+            .map(normalize_acceptance_criteria);
         let references = params.step.references.clone();
         let plan_id = params.step.plan_id;
         let position = params.position;
+        let options = CreateOptions::from(&params.step);
+        let strict_references = self.settings().await?.strict_references;
 
-        task::spawn_blocking(move || {
-            let mut db = Database::new(&db_path)?;
-            db.insert_step(
-                plan_id,
-                position,
-                &title,
-                description.as_deref(),
-                acceptance_criteria.as_deref(),
-                references,
-            )
-        })
-        .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })?
+        let result = self
+            .run_blocking_write("insert_step", move |db| {
+                let references =
+                    Self::prepare_references(db, plan_id, &references, strict_references)?;
+                let step = db.insert_step(
+                    plan_id,
+                    position,
+                    &title,
+                    description.as_deref(),
+                    acceptance_criteria.as_deref(),
+                    references,
+                )?;
+                Self::apply_create_options(db, step, options.clone())
+            })
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Validates references to be stored with a step of plan `plan_id`
+    /// against the plan's directory when `strict` is set, and returns them
+    /// as given otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist, and
+    /// `PlannerError::InvalidInput` if a reference is blank or a malformed
+    /// URL
+    fn prepare_references(
+        db: &Database,
+        plan_id: u64,
+        references: &[StepReference],
+        strict: bool,
+    ) -> Result<Vec<StepReference>> {
+        if !strict {
+            return Ok(references.to_vec());
+        }
+        let plan = db
+            .get_plan(plan_id)?
+            .ok_or(PlannerError::PlanNotFound { id: plan_id })?;
+        validate_references(references, plan.directory.as_deref().map(Path::new))
+    }
+
+    /// Applies the creation options that aren't part of the step insert to a
+    /// newly created step.
+    fn apply_create_options(
+        db: &mut Database,
+        mut step: Step,
+        options: CreateOptions,
+    ) -> Result<Step> {
+        let request = UpdateStepRequest {
+            verification_required: options.verification_required.then_some(true),
+            expected_output: options.expected_output.clone(),
+            ..Default::default()
+        };
+        if !request.is_empty() {
+            db.update_step(step.id, request)?;
+            step.verification_required = options.verification_required;
+            step.expected_output = options.expected_output;
+        }
+        if options.estimate_minutes.is_some() {
+            db.set_step_estimate(step.id, options.estimate_minutes)?;
+            step.estimate_minutes = options.estimate_minutes;
+        }
+        Ok(step)
+    }
+
+    /// Records that a second agent verified the completion of a step that
+    /// requires verification. The step then counts towards its plan's
+    /// progress.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist, and
+    /// `PlannerError::InvalidInput` if the verifier is empty or the step
+    /// doesn't require verification, isn't done, or was already verified
+    #[instrument(level = "debug", skip_all, fields(step_id = params.id))]
+    pub async fn verify_step(&self, params: &VerifyStep) -> Result<Step> {
+        let params = params.clone();
+
+        let result = self
+            .run_blocking_write("verify_step", move |db| {
+                db.verify_step(params.id, &params.verifier, params.comment.as_deref())
+            })
+            .await;
+        self.hinted(result).await
     }
 
     /// Updates step details (title, description, acceptance criteria,
     /// references, and/or status).
-    pub async fn update_step(&self, step_id: u64, request: UpdateStepRequest) -> Result<()> {
-        let db_path = self.db_path.clone();
-
-        task::spawn_blocking(move || {
-            let mut db = Database::new(&db_path)?;
-            db.update_step(step_id, request)
-        })
-        .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })?
+    ///
+    /// New acceptance criteria are normalized as in
+    /// [`normalize_acceptance_criteria`], and new references validated as
+    /// when adding a step.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the result is longer than the
+    /// `max_result_length` setting allows, or a reference is invalid
+    #[instrument(level = "debug", skip_all, fields(step_id))]
+    pub async fn update_step(&self, step_id: u64, mut request: UpdateStepRequest) -> Result<()> {
+        let settings = self.settings().await?;
+        if let Some(result) = &request.result {
+            settings
+                .check_result_length(result)
+                .map_err(|reason| PlannerError::invalid_input("result").with_reason(reason))?;
+        }
+        request.acceptance_criteria = request
+            .acceptance_criteria
+            .as_deref()
+            .map(normalize_acceptance_criteria);
+        let strict_references = settings.strict_references;
+        let result = self
+            .run_blocking_write("update_step", move |db| {
+                let mut request = request.clone();
+                if let Some(references) = &request.references
+                    && strict_references
+                {
+                    let step = db
+                        .get_step(step_id)?
+                        .ok_or(PlannerError::StepNotFound { id: step_id })?;
+                    request.references = Some(Self::prepare_references(
+                        db,
+                        step.plan_id,
+                        references,
+                        true,
+                    )?);
+                }
+                db.update_step(step_id, request)
+            })
+            .await;
+        self.hinted(result).await
     }
 
     /// Atomically claims a step for processing by transitioning it from Todo to
     /// InProgress. Returns the step details if successfully claimed, None if
     /// the step doesn't exist or cannot be claimed.
+    #[instrument(level = "debug", skip_all, fields(step_id = params.id))]
     pub async fn claim_step(&self, params: &Id) -> Result<Option<Step>> {
-        let db_path = self.db_path.clone();
         let step_id = params.id;
 
-        task::spawn_blocking(move || {
-            let mut db = Database::new(&db_path)?;
-            db.claim_step(step_id)
+        self.run_blocking_write("claim_step", move |db| db.claim_step(step_id))
+            .await
+    }
+
+    /// Atomically claims a step like [`Self::claim_step`], reporting why the
+    /// step could not be claimed when it isn't.
+    #[instrument(level = "debug", skip_all, fields(step_id = params.id))]
+    pub async fn try_claim_step(&self, params: &Id) -> Result<ClaimOutcome> {
+        let step_id = params.id;
+
+        self.run_blocking_write("try_claim_step", move |db| db.try_claim_step(step_id))
+            .await
+    }
+
+    /// Atomically puts an in-progress step back to Todo, so that another
+    /// agent can claim it. Returns false if the step is not in progress.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist
+    #[instrument(level = "debug", skip_all, fields(step_id = params.id))]
+    pub async fn release_step(&self, params: &Id) -> Result<bool> {
+        let step_id = params.id;
+
+        let result = self
+            .run_blocking_write("release_step", move |db| db.release_step(step_id))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Lists the transitions a step may undergo next, with the reason each
+    /// of the others is not allowed.
+    ///
+    /// Returns None if the step doesn't exist.
+    #[instrument(level = "debug", skip_all, fields(step_id = params.id))]
+    pub async fn allowed_transitions(&self, params: &Id) -> Result<Option<AllowedTransitions>> {
+        let step_id = params.id;
+
+        self.run_blocking("allowed_transitions", move |db| {
+            db.allowed_transitions(step_id)
         })
         .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })?
     }
 
     /// Retrieves all steps for a given plan.
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
     pub async fn get_steps(&self, params: &Id) -> Result<crate::display::Steps> {
-        let db_path = self.db_path.clone();
         let plan_id = params.id;
 
-        let steps = task::spawn_blocking(move || {
-            let db = Database::new(&db_path)?;
-            db.get_steps(plan_id)
-        })
-        .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })??;
+        let steps = self
+            .run_blocking("get_steps", move |db| db.get_steps(plan_id))
+            .await?;
+
+        Ok(crate::display::Steps::owned(steps))
+    }
+
+    /// Lists the ids, orders, titles and statuses of a plan's steps, only
+    /// those with the requested status if one is given, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the status is not valid
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.plan_id))]
+    pub async fn list_steps(&self, params: &ListSteps) -> Result<Vec<StepOutline>> {
+        let plan_id = params.plan_id;
+        let status = params.validate()?;
+
+        let result = self
+            .run_blocking("list_steps", move |db| db.list_steps(plan_id, status))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Lists the steps with `status` across plans, each with the summary of
+    /// its plan, most recently updated first, such as all the work in
+    /// progress.
+    ///
+    /// Steps of archived plans are left out unless `include_archived_plans`
+    /// is set. Each summary carries the planner's in-progress warning
+    /// threshold.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_steps_by_status(
+        &self,
+        status: StepStatus,
+        include_archived_plans: bool,
+    ) -> Result<Vec<(PlanSummary, Step)>> {
+        let threshold = self.settings().await?.in_progress_warning_threshold;
+
+        let mut steps = self
+            .run_blocking("list_steps_by_status", move |db| {
+                db.list_steps_by_status(status, include_archived_plans)
+            })
+            .await?;
+
+        for (summary, _) in &mut steps {
+            summary.in_progress_warning_threshold = Some(threshold);
+        }
+        Ok(steps)
+    }
+
+    /// Finds the steps of a plan whose title or description contains
+    /// `query`, ignoring case, only those with `status` if given, in order.
+    ///
+    /// Surrounding whitespace is trimmed from the query, which is matched
+    /// literally.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the query is empty
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    #[instrument(level = "debug", skip_all, fields(plan_id))]
+    pub async fn find_steps(
+        &self,
+        plan_id: u64,
+        query: &str,
+        status: Option<StepStatus>,
+    ) -> Result<Vec<Step>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(PlannerError::invalid_input("query")
+                .with_reason("A search query cannot be empty or only whitespace"));
+        }
 
-        Ok(crate::display::Steps(steps))
+        let query = query.to_string();
+        let result = self
+            .run_blocking("find_steps", move |db| {
+                db.find_steps(plan_id, &query, status)
+            })
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Finds the step to work on next in a plan: the first one, in order,
+    /// that is still to do and not blocked by an unfinished dependency.
+    ///
+    /// Returns None if no step is ready, e.g. because all are done.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn next_step(&self, params: &Id) -> Result<Option<Step>> {
+        let plan_id = params.id;
+
+        let result = self
+            .run_blocking("next_step", move |db| db.next_step(plan_id))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Claims the plan's next step (see [`Self::next_step`]) atomically, so
+    /// concurrent agents never start on the same step.
+    ///
+    /// Returns None if no step is ready to be claimed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn claim_next_step(&self, params: &Id) -> Result<Option<Step>> {
+        let plan_id = params.id;
+
+        let result = self
+            .run_blocking_write("claim_next_step", move |db| db.claim_next_step(plan_id))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Puts every step of a plan back to Todo in one transaction, so that a
+    /// recurring plan can be worked through again under the same ID, and
+    /// returns the number of steps reset.
+    ///
+    /// Results are kept unless `clear_results` is set. A concurrent claim
+    /// either happens before the reset, and is undone by it, or after it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    /// Returns `PlannerError::InvalidInput` if the plan is archived
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn reset_plan_steps(&self, params: &Id, clear_results: bool) -> Result<u32> {
+        let plan_id = params.id;
+
+        let result = self
+            .run_blocking_write("reset_plan_steps", move |db| {
+                db.reset_plan_steps(plan_id, clear_results)
+            })
+            .await;
+        self.hinted(result).await
     }
 
     /// Retrieves a single step by its ID.
+    #[instrument(level = "debug", skip_all, fields(step_id = params.id))]
     pub async fn get_step(&self, params: &Id) -> Result<Option<Step>> {
-        let db_path = self.db_path.clone();
         let step_id = params.id;
 
-        task::spawn_blocking(move || {
-            let db = Database::new(&db_path)?;
-            db.get_step(step_id)
+        self.run_blocking("get_step", move |db| db.get_step(step_id))
+            .await
+    }
+
+    /// Retrieves the status labels of the plan a step belongs to, for
+    /// rendering the step on its own.
+    #[instrument(level = "debug", skip_all, fields(step_id = params.id))]
+    pub async fn get_step_status_labels(&self, params: &Id) -> Result<StatusLabels> {
+        let step_id = params.id;
+
+        self.run_blocking("get_step_status_labels", move |db| {
+            db.get_step_status_labels(step_id)
+        })
+        .await
+    }
+
+    /// Counts steps marked done within `[start, end)`, as a simple throughput
+    /// metric. Editing a done step later doesn't move its completion time.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn completions_in_range(&self, start: Timestamp, end: Timestamp) -> Result<u32> {
+        self.run_blocking("completions_in_range", move |db| {
+            db.count_completions_in_range(start, end)
+        })
+        .await
+    }
+
+    /// Computes the completion momentum across all plans as of `now`:
+    /// the current streak of days with completed steps and the last week's
+    /// completions compared with the week before.
+    ///
+    /// Days are calendar days in the time zone of `now`.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn momentum_stats(&self, now: &Zoned) -> Result<Momentum> {
+        let completions = self
+            .run_blocking("momentum_stats", |db| db.completion_times())
+            .await?;
+        Ok(Momentum::from_completions(
+            completions
+                .into_iter()
+                .map(|(_, completed_at)| completed_at),
+            now,
+        ))
+    }
+
+    /// Computes the completion momentum of each plan as of `now`, like
+    /// [`Self::momentum_stats`]. Plans without completed steps are left
+    /// out.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn momentum_stats_by_plan(&self, now: &Zoned) -> Result<BTreeMap<u64, Momentum>> {
+        let completions = self
+            .run_blocking("momentum_stats_by_plan", |db| db.completion_times())
+            .await?;
+        Ok(Momentum::by_plan(completions, now))
+    }
+
+    /// Groups the open steps of active plans that share a title, ignoring
+    /// case, whitespace and punctuation, to find work tracked more than
+    /// once.
+    ///
+    /// A directory scope may be relative and is made absolute as when
+    /// searching plans by directory.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn find_duplicate_steps(&self, scope: DuplicateScope) -> Result<Vec<DuplicateGroup>> {
+        self.run_blocking("find_duplicate_steps", move |db| {
+            let directory = match scope {
+                DuplicateScope::AllPlans => None,
+                DuplicateScope::Directory(directory) => {
+                    Some(db.canonicalize_directory_for_search(&directory)?)
+                }
+            };
+            db.find_duplicate_steps(directory.as_deref())
         })
         .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })?
     }
 
     /// Swaps the order of two steps within the same plan.
+    #[instrument(level = "debug", skip_all, fields(step_id = params.step1_id, other_step_id = params.step2_id))]
     pub async fn swap_steps(&self, params: &SwapSteps) -> Result<()> {
-        let db_path = self.db_path.clone();
         let step1_id = params.step1_id;
         let step2_id = params.step2_id;
 
-        task::spawn_blocking(move || {
-            let mut db = Database::new(&db_path)?;
-            db.swap_steps(step1_id, step2_id)
+        let result = self
+            .run_blocking_write("swap_steps", move |db| db.swap_steps(step1_id, step2_id))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Moves a step to another position in its plan's step order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist
+    /// Returns `PlannerError::InvalidInput` if the position is past the last
+    /// step
+    #[instrument(level = "debug", skip_all, fields(step_id = params.step_id))]
+    pub async fn move_step(&self, params: &MoveStep) -> Result<()> {
+        let step_id = params.step_id;
+        let new_position = params.new_position;
+
+        let result = self
+            .run_blocking_write("move_step", move |db| db.move_step(step_id, new_position))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Moves a step to another plan at `position`, or to the end, keeping its
+    /// status, result and references, and returns the moved step.
+    ///
+    /// See [`Database::move_step_to_plan`](crate::db::Database::move_step_to_plan)
+    /// for what moving a step within its own plan does.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist
+    /// Returns `PlannerError::PlanNotFound` if the target plan doesn't exist
+    /// Returns `PlannerError::InvalidInput` if the target plan is archived or
+    /// the position is past its last step
+    #[instrument(level = "debug", skip_all, fields(step_id, plan_id = target_plan_id))]
+    pub async fn move_step_to_plan(
+        &self,
+        step_id: u64,
+        target_plan_id: u64,
+        position: Option<u32>,
+    ) -> Result<Step> {
+        let result = self
+            .run_blocking_write("move_step_to_plan", move |db| {
+                db.move_step_to_plan(step_id, target_plan_id, position)
+            })
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Makes a step depend on another, so that it cannot be claimed until
+    /// the other is done.
+    ///
+    /// Returns false if the dependency already existed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if either step doesn't exist
+    /// Returns `PlannerError::InvalidInput` if the dependency would form a
+    /// cycle, including a step depending on itself
+    #[instrument(level = "debug", skip_all, fields(step_id = params.step_id))]
+    pub async fn add_step_dependency(&self, params: &StepDependency) -> Result<bool> {
+        let step_id = params.step_id;
+        let depends_on = params.depends_on;
+
+        let result = self
+            .run_blocking_write("add_step_dependency", move |db| {
+                db.add_step_dependency(step_id, depends_on)
+            })
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Removes a step's dependency on another.
+    ///
+    /// Returns false if there was no such dependency.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist
+    #[instrument(level = "debug", skip_all, fields(step_id = params.step_id))]
+    pub async fn remove_step_dependency(&self, params: &StepDependency) -> Result<bool> {
+        let step_id = params.step_id;
+        let depends_on = params.depends_on;
+
+        let result = self
+            .run_blocking_write("remove_step_dependency", move |db| {
+                db.remove_step_dependency(step_id, depends_on)
+            })
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Adds a note to a step, recording something learned while working on
+    /// it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist
+    /// Returns `PlannerError::InvalidInput` if the body is empty
+    #[instrument(level = "debug", skip_all, fields(step_id = params.step_id))]
+    pub async fn add_step_note(&self, params: &AddStepNote) -> Result<StepNote> {
+        let step_id = params.step_id;
+        let body = params.body.clone();
+
+        let result = self
+            .run_blocking_write("add_step_note", move |db| db.add_step_note(step_id, &body))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Retrieves the notes of a step, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist
+    #[instrument(level = "debug", skip_all, fields(step_id = params.id))]
+    pub async fn get_step_notes(&self, params: &Id) -> Result<Vec<StepNote>> {
+        let step_id = params.id;
+
+        let result = self
+            .run_blocking("get_step_notes", move |db| db.get_step_notes(step_id))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Groups a step under a phase, or removes it from its phase when
+    /// `params.phase` is `None`.
+    ///
+    /// Returns the updated step, or None if the step doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the phase name is empty or
+    /// spans several lines
+    #[instrument(level = "debug", skip_all, fields(step_id = params.id))]
+    pub async fn set_step_phase(&self, params: &SetStepPhase) -> Result<Option<Step>> {
+        let phase = params.phase.as_deref().map(str::trim);
+        if let Some(phase) = phase {
+            if phase.is_empty() {
+                return Err(PlannerError::invalid_input("phase").with_reason(
+                    "Phase cannot be empty; omit it to remove the step from its phase",
+                ));
+            }
+            if phase.contains(['\n', '\r']) {
+                return Err(
+                    PlannerError::invalid_input("phase").with_reason("Phase must be a single line")
+                );
+            }
+        }
+        let step_id = params.id;
+        let phase = phase.map(String::from);
+
+        self.run_blocking_write("set_step_phase", move |db| {
+            if db.set_step_phase(step_id, phase.as_deref())? {
+                db.get_step(step_id)
+            } else {
+                Ok(None)
+            }
         })
         .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })?
     }
 
-    /// Removes a step from a plan.
+    /// Removes a step from a plan by moving it to the trash, from where
+    /// [`Self::restore_step`] brings it back until it is purged.
+    #[instrument(level = "debug", skip_all, fields(step_id = params.id))]
     pub async fn remove_step(&self, params: &Id) -> Result<()> {
-        let db_path = self.db_path.clone();
         let step_id = params.id;
 
-        task::spawn_blocking(move || {
-            let mut db = Database::new(&db_path)?;
-            db.remove_step(step_id)
+        let result = self
+            .run_blocking_write("remove_step", move |db| db.remove_step(step_id))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Takes a step out of the trash, placing it at the end of its plan.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::StepNotFound` if the step doesn't exist
+    /// Returns `PlannerError::InvalidInput` if the step is not in the trash
+    #[instrument(level = "debug", skip_all, fields(step_id = params.id))]
+    pub async fn restore_step(&self, params: &Id) -> Result<Step> {
+        let step_id = params.id;
+
+        let result = self
+            .run_blocking_write("restore_step", move |db| db.restore_step(step_id))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Lists the steps of a plan that are in the trash, most recently
+    /// trashed first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.id))]
+    pub async fn get_trashed_steps(&self, params: &Id) -> Result<Vec<TrashedStep>> {
+        let plan_id = params.id;
+
+        let result = self
+            .run_blocking("get_trashed_steps", move |db| db.get_trashed_steps(plan_id))
+            .await;
+        self.hinted(result).await
+    }
+
+    /// Permanently deletes the steps of every plan that were moved to the
+    /// trash more than `older_than_days` days ago, returning how many were
+    /// deleted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the cutoff is out of range
+    #[instrument(level = "debug", skip_all)]
+    pub async fn purge_trashed_steps(&self, params: &PurgeTrash) -> Result<usize> {
+        let age = SignedDuration::from_hours(i64::from(params.older_than_days) * 24);
+        let before = Timestamp::now().checked_sub(age).map_err(|_| {
+            PlannerError::invalid_input("older_than_days").with_reason(format!(
+                "{} days is further back than dates go",
+                params.older_than_days
+            ))
+        })?;
+
+        self.run_blocking_write("purge_trashed_steps", move |db| {
+            db.purge_trashed_steps(before)
         })
         .await
-        .map_err(|e| PlannerError::Configuration {
-            message: format!("Task join error: {e}"),
-        })?
+    }
+}
+
+/// Step creation options applied after the step is inserted.
+#[derive(Clone)]
+struct CreateOptions {
+    verification_required: bool,
+    estimate_minutes: Option<u32>,
+    expected_output: Option<String>,
+}
+
+impl From<&StepCreate> for CreateOptions {
+    fn from(params: &StepCreate) -> Self {
+        Self {
+            verification_required: params.verification_required,
+            estimate_minutes: params.estimate_minutes,
+            expected_output: params.expected_output.clone(),
+        }
     }
 }
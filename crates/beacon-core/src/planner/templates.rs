@@ -0,0 +1,65 @@
+//! Step templates for the Planner.
+
+use tracing::instrument;
+
+use super::Planner;
+use crate::{
+    criteria::normalize_acceptance_criteria,
+    error::Result,
+    models::{Step, Template},
+    params::{ApplyTemplate, CreateTemplate},
+};
+
+impl Planner {
+    /// Creates a step template, a named list of steps that
+    /// [`Self::apply_template`] appends to plans. The name and steps are
+    /// checked and trimmed by [`CreateTemplate::validate`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the parameters are invalid or
+    /// a template with the name already exists
+    #[instrument(level = "debug", skip_all)]
+    pub async fn create_template(&self, params: &CreateTemplate) -> Result<Template> {
+        let mut params = params.validate()?;
+        for step in &mut params.steps {
+            step.acceptance_criteria = step
+                .acceptance_criteria
+                .as_deref()
+                .map(normalize_acceptance_criteria);
+        }
+
+        self.run_blocking_write("create_template", move |db| {
+            db.create_template(&params.name, &params.steps)
+        })
+        .await
+    }
+
+    /// Lists every template with its steps, by name.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn list_templates(&self) -> Result<Vec<Template>> {
+        self.run_blocking("list_templates", |db| db.list_templates())
+            .await
+    }
+
+    /// Appends the steps of a template to the end of a plan, in one
+    /// transaction, returning the new steps.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::PlanNotFound` if the plan doesn't exist
+    /// Returns `PlannerError::TemplateNotFound` if no template has the name
+    /// Returns `PlannerError::InvalidInput` if the plan is archived
+    #[instrument(level = "debug", skip_all, fields(plan_id = params.plan_id))]
+    pub async fn apply_template(&self, params: &ApplyTemplate) -> Result<Vec<Step>> {
+        let plan_id = params.plan_id;
+        let name = params.name.trim().to_string();
+
+        let result = self
+            .run_blocking_write("apply_template", move |db| {
+                db.apply_template(plan_id, &name)
+            })
+            .await;
+        self.hinted(result).await
+    }
+}
@@ -0,0 +1,94 @@
+//! Synchronous reads for commands run many times a minute.
+//!
+//! Shell prompt integrations run `b` on every prompt. Going through a
+//! [`Planner`](crate::Planner) for a few rows means starting an async
+//! runtime, creating the data directory, and opening the database twice,
+//! replaying the schema and migrations each time. The functions here instead
+//! open an existing database read-only on the calling thread and run their
+//! queries directly.
+//!
+//! Schema initialization is only skipped on databases an earlier open
+//! brought up to date, as recorded by their schema version. Anything the
+//! quick path can't handle, such as a missing, outdated or encrypted
+//! database, is an error; callers fall back to the planner, which creates or
+//! migrates the database as usual.
+//!
+//! Results match the planner's for the same database, with the workspace's
+//! stored settings applied. Settings overridden on a
+//! [`PlannerBuilder`](crate::PlannerBuilder) are not known here, so callers
+//! with overrides should use the planner.
+
+use std::{collections::BTreeMap, path::Path};
+
+use jiff::Zoned;
+
+use crate::{
+    db::Database,
+    error::Result,
+    models::{Momentum, PlanFilter, PlanSummary, PromptBadge, Settings},
+};
+
+/// Lists plan summaries matching `filter`, as
+/// [`Planner::list_plan_summaries`](crate::Planner::list_plan_summaries)
+/// does.
+///
+/// # Errors
+///
+/// Returns an error if the database does not exist, cannot be read, or its
+/// schema is not up to date
+pub fn quick_summaries(
+    db_path: impl AsRef<Path>,
+    filter: Option<&PlanFilter>,
+) -> Result<Vec<PlanSummary>> {
+    let db = Database::open_read_only(db_path)?;
+    summaries(&db, filter)
+}
+
+/// Counts the active plans in `dir` or below it and their pending steps.
+///
+/// A relative `dir` is made absolute as when searching plans by directory.
+///
+/// # Errors
+///
+/// Returns an error if the database does not exist, cannot be read, or its
+/// schema is not up to date
+pub fn quick_badge(db_path: impl AsRef<Path>, dir: &str) -> Result<PromptBadge> {
+    let db = Database::open_read_only(db_path)?;
+    let filter = PlanFilter::builder()
+        .directory(db.canonicalize_directory_for_search(dir)?)
+        .build()?;
+    Ok(PromptBadge::from_summaries(&summaries(&db, Some(&filter))?))
+}
+
+/// Computes the overall completion momentum and that of each plan as of
+/// `now`, as [`Planner::momentum_stats`](crate::Planner::momentum_stats) and
+/// [`Planner::momentum_stats_by_plan`](crate::Planner::momentum_stats_by_plan)
+/// do.
+///
+/// # Errors
+///
+/// Returns an error if the database does not exist, cannot be read, or its
+/// schema is not up to date
+pub fn quick_momentum(
+    db_path: impl AsRef<Path>,
+    now: &Zoned,
+) -> Result<(Momentum, BTreeMap<u64, Momentum>)> {
+    let completions = Database::open_read_only(db_path)?.completion_times()?;
+    let momentum = Momentum::from_completions(
+        completions.iter().map(|(_, completed_at)| *completed_at),
+        now,
+    );
+    Ok((momentum, Momentum::by_plan(completions, now)))
+}
+
+/// Lists plan summaries with the workspace's warning threshold applied.
+fn summaries(db: &Database, filter: Option<&PlanFilter>) -> Result<Vec<PlanSummary>> {
+    db.read_consistent(|db| {
+        let threshold = Settings::from_entries(db.load_settings()?).in_progress_warning_threshold;
+        let mut summaries = db.list_plan_summaries(filter)?;
+        for summary in &mut summaries {
+            summary.in_progress_warning_threshold = Some(threshold);
+        }
+        Ok(summaries)
+    })
+}
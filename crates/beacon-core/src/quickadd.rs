@@ -0,0 +1,448 @@
+//! Quick-add syntax for creating steps from a single line.
+//!
+//! A quick-add line starts with the step title, followed by inline tokens
+//! setting the step's metadata:
+//!
+//! ```text
+//! Implement retries !crit: exponential backoff, max 5 @ref:src/client.rs ~30m
+//! ```
+//!
+//! - `!crit:` sets the acceptance criteria to the text that follows, up to the
+//!   next token. It may appear once.
+//! - `@ref:` adds a reference, which extends to the next whitespace. A value
+//!   wrapped in double quotes may contain spaces; the quotes are removed. It
+//!   may appear any number of times.
+//! - `~` followed by a duration such as `30m`, `2h` or `1h30m` sets the
+//!   estimate. It may appear once.
+//!
+//! Tokens are only recognized at the start of a word. Everything before the
+//! first token is the title; only `!crit:` takes free text, so any other text
+//! after the first token is rejected. A `~` word that isn't a duration, like
+//! `~/.config`, is ordinary text.
+//!
+//! Text inside double quotes is taken literally and keeps its quotes, so
+//! `"@ref:"` in a title is not a token. A quote without a closing quote is an
+//! ordinary character. Outside quotes, a backslash before `@`, `!`, `~`, `"`
+//! or another backslash makes that character literal; other backslashes are
+//! kept as-is.
+
+use crate::{
+    error::{PlannerError, Result},
+    models::StepReference,
+    params::StepCreate,
+};
+
+const CRITERIA_TOKEN: &str = "!crit:";
+const REFERENCE_TOKEN: &str = "@ref:";
+const ESTIMATE_PREFIX: char = '~';
+
+/// Step fields parsed from a quick-add line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuickAdd {
+    /// Text before the first token
+    pub title: String,
+    /// Text following `!crit:`
+    pub acceptance_criteria: Option<String>,
+    /// Values of each `@ref:` token, in order
+    pub references: Vec<String>,
+    /// Duration following `~`, in minutes
+    pub estimate_minutes: Option<u32>,
+}
+
+impl QuickAdd {
+    /// Merges the parsed fields into step creation parameters, replacing the
+    /// title and appending the references.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the parameters already set
+    /// acceptance criteria or an estimate that the line also sets.
+    pub fn apply_to(self, step: &mut StepCreate) -> Result<()> {
+        if let Some(criteria) = self.acceptance_criteria {
+            if step.acceptance_criteria.is_some() {
+                return Err(PlannerError::invalid_input("acceptance_criteria")
+                    .with_reason("Acceptance criteria given both inline and as a parameter"));
+            }
+            step.acceptance_criteria = Some(criteria);
+        }
+        if let Some(minutes) = self.estimate_minutes {
+            if step.estimate_minutes.is_some() {
+                return Err(PlannerError::invalid_input("estimate")
+                    .with_reason("Estimate given both inline and as a parameter"));
+            }
+            step.estimate_minutes = Some(minutes);
+        }
+        step.title = self.title;
+        step.references
+            .extend(self.references.into_iter().map(StepReference::from));
+        Ok(())
+    }
+}
+
+/// A piece of a quick-add line.
+#[derive(Debug, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Criteria,
+    Reference(String),
+    Estimate(u32),
+}
+
+/// Parses a quick-add line into step fields.
+///
+/// # Errors
+///
+/// Returns `PlannerError::InvalidInput` if the title is empty, a token has
+/// no value, `!crit:` or an estimate appears more than once, or text follows
+/// a reference or estimate.
+pub fn parse(input: &str) -> Result<QuickAdd> {
+    let mut pieces = scan(input)?.into_iter();
+    let mut quick = QuickAdd::default();
+
+    if let Some(Piece::Text(title)) = pieces.as_slice().first() {
+        quick.title = title.trim().to_string();
+        pieces.next();
+    }
+    if quick.title.is_empty() {
+        return Err(PlannerError::invalid_input("title")
+            .with_reason("Quick-add line must start with the step title"));
+    }
+
+    let mut after = "the title";
+    while let Some(piece) = pieces.next() {
+        match piece {
+            Piece::Criteria => {
+                let criteria = match pieces.as_slice().first() {
+                    Some(Piece::Text(text)) => text.trim().to_string(),
+                    _ => String::new(),
+                };
+                if criteria.is_empty() {
+                    return Err(missing_value("acceptance_criteria", CRITERIA_TOKEN));
+                }
+                if quick.acceptance_criteria.replace(criteria).is_some() {
+                    return Err(PlannerError::invalid_input("acceptance_criteria")
+                        .with_reason(format!("{CRITERIA_TOKEN} may only be given once")));
+                }
+                pieces.next();
+                after = CRITERIA_TOKEN;
+            }
+            Piece::Reference(reference) => {
+                quick.references.push(reference);
+                after = REFERENCE_TOKEN;
+            }
+            Piece::Estimate(minutes) => {
+                if quick.estimate_minutes.replace(minutes).is_some() {
+                    return Err(PlannerError::invalid_input("estimate")
+                        .with_reason("An estimate may only be given once"));
+                }
+                after = "the estimate";
+            }
+            Piece::Text(text) if text.trim().is_empty() => {}
+            Piece::Text(text) => {
+                return Err(PlannerError::invalid_input("title").with_reason(format!(
+                    "Unexpected text '{}' after {after}; the title must come before inline tokens",
+                    text.trim()
+                )));
+            }
+        }
+    }
+
+    Ok(quick)
+}
+
+/// Returns true if the line contains any quick-add token, or something that
+/// looks like one but is malformed.
+pub fn contains_tokens(input: &str) -> bool {
+    scan(input).map_or(true, |pieces| {
+        pieces.iter().any(|piece| !matches!(piece, Piece::Text(_)))
+    })
+}
+
+/// Parses an estimate such as `30m`, `2h` or `1h30m` into minutes.
+///
+/// Returns `None` if the text is not a duration or amounts to zero minutes.
+pub fn parse_estimate(text: &str) -> Option<u32> {
+    let mut minutes: u32 = 0;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let (amount, unit) = rest.split_at(digits);
+        let amount: u32 = amount.parse().ok()?;
+        let factor = match unit.chars().next()? {
+            'h' => 60,
+            'm' => 1,
+            _ => return None,
+        };
+        minutes = minutes.checked_add(amount.checked_mul(factor)?)?;
+        rest = &unit[1..];
+    }
+    (minutes > 0).then_some(minutes)
+}
+
+/// Formats minutes in the estimate syntax, e.g. `1h30m`.
+pub fn format_estimate(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h{minutes}m"),
+    }
+}
+
+/// Splits a quick-add line into text and tokens.
+fn scan(input: &str) -> Result<Vec<Piece>> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut word_start = true;
+    let mut rest = input;
+
+    while let Some(c) = rest.chars().next() {
+        if word_start && rest.starts_with(CRITERIA_TOKEN) {
+            flush(&mut pieces, &mut text);
+            pieces.push(Piece::Criteria);
+            rest = &rest[CRITERIA_TOKEN.len()..];
+            continue;
+        }
+        if word_start && rest.starts_with(REFERENCE_TOKEN) {
+            flush(&mut pieces, &mut text);
+            let (reference, remaining) = reference_value(&rest[REFERENCE_TOKEN.len()..]);
+            if reference.is_empty() {
+                return Err(missing_value("references", REFERENCE_TOKEN));
+            }
+            pieces.push(Piece::Reference(reference));
+            rest = remaining;
+            word_start = false;
+            continue;
+        }
+        if word_start && c == ESTIMATE_PREFIX {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if let Some(minutes) = parse_estimate(&rest[1..end]) {
+                flush(&mut pieces, &mut text);
+                pieces.push(Piece::Estimate(minutes));
+                rest = &rest[end..];
+                word_start = false;
+                continue;
+            }
+        }
+
+        if c == '"'
+            && let Some(len) = rest[1..].find('"')
+        {
+            // Quoted text is literal, quotes included
+            text.push_str(&rest[..len + 2]);
+            rest = &rest[len + 2..];
+            word_start = false;
+            continue;
+        }
+
+        let mut chars = rest.chars();
+        chars.next();
+        match (c, chars.clone().next()) {
+            ('\\', Some(escaped @ ('@' | '!' | '~' | '"' | '\\'))) => {
+                text.push(escaped);
+                chars.next();
+            }
+            _ => text.push(c),
+        }
+        word_start = c.is_whitespace();
+        rest = chars.as_str();
+    }
+
+    flush(&mut pieces, &mut text);
+    Ok(pieces)
+}
+
+/// Reads a reference value: a quoted string, or everything up to the next
+/// whitespace. Returns the value and the remaining input.
+fn reference_value(input: &str) -> (String, &str) {
+    if let Some(quoted) = input.strip_prefix('"')
+        && let Some(len) = quoted.find('"')
+    {
+        return (quoted[..len].trim().to_string(), &quoted[len + 1..]);
+    }
+    let end = input.find(char::is_whitespace).unwrap_or(input.len());
+    (input[..end].to_string(), &input[end..])
+}
+
+fn flush(pieces: &mut Vec<Piece>, text: &mut String) {
+    if !text.is_empty() {
+        pieces.push(Piece::Text(std::mem::take(text)));
+    }
+}
+
+fn missing_value(field: &str, token: &str) -> PlannerError {
+    PlannerError::invalid_input(field).with_reason(format!("{token} must be followed by a value"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_full_line() {
+        let quick = parse(
+            "Implement retries !crit: exponential backoff, max 5 @ref:src/client.rs \
+             @ref:https://docs/retry ~30m",
+        )
+        .unwrap();
+        assert_eq!(
+            quick,
+            QuickAdd {
+                title: "Implement retries".to_string(),
+                acceptance_criteria: Some("exponential backoff, max 5".to_string()),
+                references: vec![
+                    "src/client.rs".to_string(),
+                    "https://docs/retry".to_string()
+                ],
+                estimate_minutes: Some(30),
+            }
+        );
+    }
+
+    #[test]
+    fn test_tokens_in_any_order() {
+        let quick = parse("Ship it ~1h30m @ref:a.rs !crit: tests pass @ref:b.rs").unwrap();
+        assert_eq!(quick.title, "Ship it");
+        assert_eq!(quick.estimate_minutes, Some(90));
+        assert_eq!(quick.acceptance_criteria.as_deref(), Some("tests pass"));
+        assert_eq!(quick.references, vec!["a.rs", "b.rs"]);
+    }
+
+    #[test]
+    fn test_plain_title() {
+        let quick = parse("  Write the docs  ").unwrap();
+        assert_eq!(quick.title, "Write the docs");
+        assert_eq!(quick.acceptance_criteria, None);
+        assert!(quick.references.is_empty());
+        assert!(!contains_tokens("Write the docs"));
+    }
+
+    #[test]
+    fn test_tokens_only_at_word_start() {
+        let quick = parse("Email user@ref:example and say hi!crit: now").unwrap();
+        assert_eq!(quick.title, "Email user@ref:example and say hi!crit: now");
+        assert!(!contains_tokens("Email user@ref:example"));
+    }
+
+    #[test]
+    fn test_tokens_inside_quotes_are_literal() {
+        let quick = parse(r#"Document "@ref:" and "!crit: x" syntax @ref:docs.md"#).unwrap();
+        assert_eq!(quick.title, r#"Document "@ref:" and "!crit: x" syntax"#);
+        assert_eq!(quick.references, vec!["docs.md"]);
+        assert!(!contains_tokens(r#"Document "@ref:x""#));
+    }
+
+    #[test]
+    fn test_quoted_reference_may_contain_spaces() {
+        let quick = parse(r#"Read notes @ref:"docs/meeting notes.md" ~2h"#).unwrap();
+        assert_eq!(quick.references, vec!["docs/meeting notes.md"]);
+        assert_eq!(quick.estimate_minutes, Some(120));
+    }
+
+    #[test]
+    fn test_escapes() {
+        let quick = parse(r"Mention \@ref: and \!crit: and \~5m and a\\b and C:\path").unwrap();
+        assert_eq!(
+            quick.title,
+            r"Mention @ref: and !crit: and ~5m and a\b and C:\path"
+        );
+        assert!(!contains_tokens(r"Mention \@ref:x"));
+    }
+
+    #[test]
+    fn test_unterminated_quote_is_literal() {
+        let quick = parse(r#"Buy a 27" monitor @ref:shop.example"#).unwrap();
+        assert_eq!(quick.title, r#"Buy a 27" monitor"#);
+        assert_eq!(quick.references, vec!["shop.example"]);
+    }
+
+    #[test]
+    fn test_tilde_words_that_are_not_durations() {
+        let quick = parse("Clean ~/.config and ~approx files").unwrap();
+        assert_eq!(quick.title, "Clean ~/.config and ~approx files");
+        assert!(!contains_tokens("Clean ~/.config"));
+        assert!(!contains_tokens("Wait ~0m"));
+    }
+
+    #[test]
+    fn test_missing_values() {
+        for input in [
+            "Task !crit:",
+            "Task !crit:   @ref:a",
+            "Task @ref:",
+            "Task @ref: a",
+        ] {
+            let error = parse(input).unwrap_err();
+            assert!(
+                matches!(error, PlannerError::InvalidInput { .. }),
+                "{input}: {error}"
+            );
+            assert!(error.to_string().contains("must be followed by a value"));
+            assert!(contains_tokens(input));
+        }
+    }
+
+    #[test]
+    fn test_invalid_lines() {
+        let missing_title = parse("@ref:a.rs").unwrap_err();
+        assert!(
+            missing_title
+                .to_string()
+                .contains("must start with the step title")
+        );
+
+        let twice = parse("Task !crit: a !crit: b").unwrap_err();
+        assert!(twice.to_string().contains("only be given once"));
+
+        let two_estimates = parse("Task ~1h ~2h").unwrap_err();
+        assert!(two_estimates.to_string().contains("only be given once"));
+
+        let trailing = parse("Task @ref:a.rs more words").unwrap_err();
+        assert!(
+            trailing
+                .to_string()
+                .contains("Unexpected text 'more words' after @ref:")
+        );
+    }
+
+    #[test]
+    fn test_estimates() {
+        assert_eq!(parse_estimate("45m"), Some(45));
+        assert_eq!(parse_estimate("2h"), Some(120));
+        assert_eq!(parse_estimate("1h15m"), Some(75));
+        assert_eq!(parse_estimate(""), None);
+        assert_eq!(parse_estimate("30"), None);
+        assert_eq!(parse_estimate("3d"), None);
+        assert_eq!(parse_estimate("0m"), None);
+        assert_eq!(format_estimate(45), "45m");
+        assert_eq!(format_estimate(120), "2h");
+        assert_eq!(format_estimate(75), "1h15m");
+    }
+
+    #[test]
+    fn test_apply_to_step() {
+        let mut step = StepCreate {
+            plan_id: 3,
+            title: "placeholder".to_string(),
+            references: vec!["existing.rs".into()],
+            ..Default::default()
+        };
+        parse("Do it !crit: done @ref:new.rs ~10m")
+            .unwrap()
+            .apply_to(&mut step)
+            .unwrap();
+        assert_eq!(step.title, "Do it");
+        assert_eq!(step.acceptance_criteria.as_deref(), Some("done"));
+        assert_eq!(step.references, vec!["existing.rs".into(), "new.rs".into()]);
+        assert_eq!(step.estimate_minutes, Some(10));
+
+        let mut step = StepCreate {
+            acceptance_criteria: Some("flag".to_string()),
+            ..Default::default()
+        };
+        let error = parse("Do it !crit: inline")
+            .unwrap()
+            .apply_to(&mut step)
+            .unwrap_err();
+        assert!(error.to_string().contains("both inline and as a parameter"));
+    }
+}
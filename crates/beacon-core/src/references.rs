@@ -0,0 +1,262 @@
+//! Validation and normalization of step references.
+//!
+//! References are free-form, and agents pass anything from URLs to
+//! `see docs`. With the `strict_references` setting on, references are
+//! checked when steps are written and classified by what they point to:
+//!
+//! ```text
+//!   HTTPS://Docs.Example.COM/Guide  →  url           https://docs.example.com/Guide
+//!   src/main.rs:42                  →  file          (or missing_file)
+//!   see the design doc              →  text
+//! ```
+//!
+//! Anything with a `scheme://` prefix must be a well-formed URL with a host;
+//! its scheme and host are lowercased. Other references without whitespace,
+//! or with a path separator, are file paths, checked relative to the plan's
+//! directory when the plan has one. A trailing `:line` or `:line:column` is
+//! ignored for the check. Everything else is text. Labels and URLs are
+//! trimmed, and exact duplicates are dropped, keeping the first.
+
+use std::path::Path;
+
+use crate::{
+    error::{PlannerError, Result},
+    models::{ReferenceKind, StepReference},
+};
+
+/// Validates, classifies and normalizes references, dropping exact
+/// duplicates.
+///
+/// # Errors
+///
+/// * `PlannerError::InvalidInput` - When a reference is blank or a malformed
+///   URL
+///
+/// # Examples
+///
+/// ```
+/// use beacon_core::{ReferenceKind, references::validate_references};
+///
+/// let references = validate_references(
+///     &[" HTTPS://Example.COM/Docs ".into(), "https://example.com/Docs".into()],
+///     None,
+/// )
+/// .unwrap();
+/// assert_eq!(references.len(), 1);
+/// assert_eq!(references[0].url, "https://example.com/Docs");
+/// assert_eq!(references[0].kind, Some(ReferenceKind::Url));
+/// ```
+pub fn validate_references(
+    references: &[StepReference],
+    directory: Option<&Path>,
+) -> Result<Vec<StepReference>> {
+    let mut validated: Vec<StepReference> = Vec::with_capacity(references.len());
+    for reference in references {
+        let reference = validate_reference(reference, directory)?;
+        if !validated.contains(&reference) {
+            validated.push(reference);
+        }
+    }
+    Ok(validated)
+}
+
+/// Validates and classifies a single reference.
+fn validate_reference(
+    reference: &StepReference,
+    directory: Option<&Path>,
+) -> Result<StepReference> {
+    let url = reference.url.trim();
+    if url.is_empty() {
+        return Err(PlannerError::invalid_input("references")
+            .with_reason("A reference cannot be empty or only whitespace"));
+    }
+    let label = reference
+        .label
+        .as_deref()
+        .map(str::trim)
+        .filter(|label| !label.is_empty())
+        .map(String::from);
+
+    let (url, kind) = if url.contains("://") {
+        let url = normalize_url(url).map_err(|reason| {
+            PlannerError::invalid_input("references")
+                .with_reason(format!("'{url}' is not a valid URL: {reason}"))
+        })?;
+        (url, ReferenceKind::Url)
+    } else if url.contains(['/', '\\']) || !url.contains(char::is_whitespace) {
+        (url.to_string(), classify_path(url, directory))
+    } else {
+        (url.to_string(), ReferenceKind::Text)
+    };
+
+    Ok(StepReference {
+        url,
+        label,
+        kind: Some(kind),
+    })
+}
+
+/// Checks that `url` has a valid scheme and a host, and lowercases both.
+fn normalize_url(url: &str) -> std::result::Result<String, String> {
+    if url.contains(char::is_whitespace) {
+        return Err("URLs cannot contain whitespace".to_string());
+    }
+    let (scheme, rest) = url
+        .split_once("://")
+        .ok_or_else(|| "missing scheme".to_string())?;
+    let valid_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    if !valid_scheme {
+        return Err(format!("invalid scheme '{scheme}'"));
+    }
+
+    let (authority, path) = rest.split_at(rest.find(['/', '?', '#']).unwrap_or(rest.len()));
+    let (userinfo, host) = match authority.rsplit_once('@') {
+        Some((userinfo, host)) => (&authority[..=userinfo.len()], host),
+        None => ("", authority),
+    };
+    let hostname = match host.rsplit_once(':') {
+        Some((hostname, port)) if !host.ends_with(']') => {
+            if !port.chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("invalid port '{port}'"));
+            }
+            hostname
+        }
+        _ => host,
+    };
+    if hostname.is_empty() && !scheme.eq_ignore_ascii_case("file") {
+        return Err("missing host".to_string());
+    }
+
+    Ok(format!(
+        "{}://{userinfo}{}{path}",
+        scheme.to_ascii_lowercase(),
+        host.to_ascii_lowercase()
+    ))
+}
+
+/// Whether the file at `path` exists, relative to `directory` unless it is
+/// absolute. Relative paths of plans without a directory can't be checked
+/// and count as files.
+fn classify_path(path: &str, directory: Option<&Path>) -> ReferenceKind {
+    let path = Path::new(strip_line_suffix(path));
+    let exists = if path.is_absolute() {
+        path.exists()
+    } else {
+        match directory {
+            Some(directory) => directory.join(path).exists(),
+            None => true,
+        }
+    };
+    if exists {
+        ReferenceKind::File
+    } else {
+        ReferenceKind::MissingFile
+    }
+}
+
+/// Strips a trailing `:line` or `:line:column` from a path.
+fn strip_line_suffix(path: &str) -> &str {
+    let mut path = path;
+    for _ in 0..2 {
+        match path.rsplit_once(':') {
+            Some((rest, number))
+                if !rest.is_empty()
+                    && !number.is_empty()
+                    && number.chars().all(|c| c.is_ascii_digit()) =>
+            {
+                path = rest;
+            }
+            _ => break,
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn kinds(references: &[StepReference]) -> Vec<(&str, Option<ReferenceKind>)> {
+        references
+            .iter()
+            .map(|reference| (reference.url.as_str(), reference.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_classifies_references() {
+        let directory = tempfile::tempdir().unwrap();
+        fs::create_dir(directory.path().join("src")).unwrap();
+        fs::write(directory.path().join("src/main.rs"), "").unwrap();
+
+        let references = validate_references(
+            &[
+                "HTTPS://Docs.Example.COM:8080/Guide?q=A".into(),
+                "src/main.rs:42:7".into(),
+                "src/missing.rs".into(),
+                "see the design doc".into(),
+                "file:///etc/hosts".into(),
+            ],
+            Some(directory.path()),
+        )
+        .unwrap();
+        assert_eq!(
+            kinds(&references),
+            [
+                (
+                    "https://docs.example.com:8080/Guide?q=A",
+                    Some(ReferenceKind::Url)
+                ),
+                ("src/main.rs:42:7", Some(ReferenceKind::File)),
+                ("src/missing.rs", Some(ReferenceKind::MissingFile)),
+                ("see the design doc", Some(ReferenceKind::Text)),
+                ("file:///etc/hosts", Some(ReferenceKind::Url)),
+            ]
+        );
+
+        // Relative paths can't be checked without a directory
+        let references = validate_references(&["src/missing.rs".into()], None).unwrap();
+        assert_eq!(references[0].kind, Some(ReferenceKind::File));
+    }
+
+    #[test]
+    fn test_normalizes_and_deduplicates() {
+        let references = validate_references(
+            &[
+                StepReference::labelled(" https://User@Example.com/A ", " Spec "),
+                StepReference::labelled("https://User@EXAMPLE.com/A", "Spec"),
+                StepReference::labelled("https://User@example.com/A", "  "),
+            ],
+            None,
+        )
+        .unwrap();
+        assert_eq!(references.len(), 2);
+        assert_eq!(references[0].url, "https://User@example.com/A");
+        assert_eq!(references[0].label.as_deref(), Some("Spec"));
+        assert_eq!(references[1].label, None);
+    }
+
+    #[test]
+    fn test_rejects_malformed_references() {
+        for invalid in [
+            "   ",
+            "https://",
+            "https:///path",
+            "ht tp://example.com",
+            "1http://example.com",
+            "http://example.com:port/",
+            "https://example.com/a b",
+        ] {
+            let result = validate_references(&[invalid.into()], None);
+            assert!(
+                matches!(result, Err(PlannerError::InvalidInput { ref field, .. }) if field == "references"),
+                "{invalid}: {result:?}"
+            );
+        }
+    }
+}
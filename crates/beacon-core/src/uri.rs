@@ -0,0 +1,335 @@
+//! `beacon://` URL parsing and formatting.
+//!
+//! Editors and note-taking tools can register a handler for the `beacon`
+//! scheme and hand the URL back to `b open`. The canonical forms are:
+//!
+//! - `beacon://open/plan/<id>`
+//! - `beacon://open/step/<id>`
+//! - `beacon://open/plans`, the list of active plans
+//!
+//! Any form may carry an optional workspace name as a query parameter,
+//! e.g. `beacon://open/plan/12?workspace=work`, so that a handler juggling
+//! several databases can route the URL to the right one. The workspace of a
+//! database is its file name without the extension, e.g. `work` for
+//! `work.db`.
+
+use std::{fmt, path::Path, str::FromStr};
+
+use crate::error::{PlannerError, Result};
+
+/// URL scheme used for all beacon links.
+pub const SCHEME: &str = "beacon";
+
+/// The only action currently understood by the handler.
+const ACTION_OPEN: &str = "open";
+
+/// Query parameter carrying the optional workspace name.
+const WORKSPACE_PARAM: &str = "workspace";
+
+/// The entity a [`BeaconUri`] points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UriTarget {
+    /// A plan, identified by its ID
+    Plan(u64),
+    /// A step, identified by its ID
+    Step(u64),
+    /// The list of active plans
+    Plans,
+}
+
+impl UriTarget {
+    /// Path segment naming the entity kind.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            UriTarget::Plan(_) => "plan",
+            UriTarget::Step(_) => "step",
+            UriTarget::Plans => "plans",
+        }
+    }
+
+    /// ID of the referenced entity, or `None` for the list of plans.
+    pub fn id(&self) -> Option<u64> {
+        match self {
+            UriTarget::Plan(id) | UriTarget::Step(id) => Some(*id),
+            UriTarget::Plans => None,
+        }
+    }
+}
+
+/// A parsed `beacon://` URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BeaconUri {
+    /// Entity the URL refers to
+    pub target: UriTarget,
+    /// Optional workspace name qualifying the URL
+    pub workspace: Option<String>,
+}
+
+impl BeaconUri {
+    /// Creates an unqualified URL for a plan.
+    pub fn plan(id: u64) -> Self {
+        Self {
+            target: UriTarget::Plan(id),
+            workspace: None,
+        }
+    }
+
+    /// Creates an unqualified URL for a step.
+    pub fn step(id: u64) -> Self {
+        Self {
+            target: UriTarget::Step(id),
+            workspace: None,
+        }
+    }
+
+    /// Creates an unqualified URL for the list of active plans.
+    pub fn plans() -> Self {
+        Self {
+            target: UriTarget::Plans,
+            workspace: None,
+        }
+    }
+
+    /// Qualifies the URL with a workspace name.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the name is empty or contains
+    /// characters other than ASCII letters, digits, `-`, `_` and `.`.
+    pub fn with_workspace(mut self, workspace: impl Into<String>) -> Result<Self> {
+        let workspace = workspace.into();
+        validate_workspace(&workspace)?;
+        self.workspace = Some(workspace);
+        Ok(self)
+    }
+
+    /// Checks that the URL can be resolved against the database at
+    /// `database`, i.e. that it is unqualified or qualified with the
+    /// database's workspace.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlannerError::InvalidInput` if the URL names another
+    /// workspace.
+    pub fn check_workspace(&self, database: &Path) -> Result<()> {
+        let Some(workspace) = &self.workspace else {
+            return Ok(());
+        };
+        let current = workspace_of(database);
+        if current == Some(workspace.as_str()) {
+            return Ok(());
+        }
+        Err(invalid(format!(
+            "'{self}' belongs to workspace '{workspace}', but the current database '{}' is \
+             workspace '{}'; open it against the '{workspace}' database instead",
+            database.display(),
+            current.unwrap_or_default()
+        )))
+    }
+}
+
+/// The workspace of the database at `path`: its file name without the
+/// extension.
+pub fn workspace_of(path: &Path) -> Option<&str> {
+    path.file_stem().and_then(|stem| stem.to_str())
+}
+
+impl fmt::Display for BeaconUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{SCHEME}://{ACTION_OPEN}/{}", self.target.kind())?;
+        if let Some(id) = self.target.id() {
+            write!(f, "/{id}")?;
+        }
+        if let Some(workspace) = &self.workspace {
+            write!(f, "?{WORKSPACE_PARAM}={workspace}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for BeaconUri {
+    type Err = PlannerError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let rest = s
+            .strip_prefix(SCHEME)
+            .and_then(|rest| rest.strip_prefix("://"))
+            .ok_or_else(|| invalid(format!("'{s}' does not start with '{SCHEME}://'")))?;
+
+        let (path, query) = match rest.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (rest, None),
+        };
+
+        let segments: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+        let (action, kind, id) = match segments[..] {
+            [action, "plans"] => (action, "plans", None),
+            [action, kind, id] => (action, kind, Some(id)),
+            _ => {
+                return Err(invalid(format!(
+                    "'{s}' must have the form {SCHEME}://{ACTION_OPEN}/<plan|step>/<id> or \
+                     {SCHEME}://{ACTION_OPEN}/plans"
+                )));
+            }
+        };
+
+        if action != ACTION_OPEN {
+            return Err(invalid(format!(
+                "Unknown action '{action}'. Only '{ACTION_OPEN}' is supported"
+            )));
+        }
+
+        let workspace = query.map(parse_workspace_query).transpose()?;
+        let Some(id) = id else {
+            return Ok(Self {
+                target: UriTarget::Plans,
+                workspace,
+            });
+        };
+
+        let id = id
+            .parse::<u64>()
+            .ok()
+            .filter(|id| *id > 0)
+            .ok_or_else(|| invalid(format!("'{id}' is not a valid {kind} ID")))?;
+
+        let target = match kind {
+            "plan" => UriTarget::Plan(id),
+            "step" => UriTarget::Step(id),
+            _ => {
+                return Err(invalid(format!(
+                    "Unknown entity '{kind}'. Expected 'plan' or 'step'"
+                )));
+            }
+        };
+
+        Ok(Self { target, workspace })
+    }
+}
+
+/// Parses the query string, accepting only a single `workspace` parameter.
+fn parse_workspace_query(query: &str) -> Result<String> {
+    let (key, value) = query
+        .split_once('=')
+        .ok_or_else(|| invalid(format!("Malformed query '{query}'")))?;
+
+    if key != WORKSPACE_PARAM {
+        return Err(invalid(format!(
+            "Unknown query parameter '{key}'. Only '{WORKSPACE_PARAM}' is supported"
+        )));
+    }
+
+    validate_workspace(value)?;
+    Ok(value.into())
+}
+
+fn validate_workspace(workspace: &str) -> Result<()> {
+    if workspace.is_empty() {
+        return Err(invalid("Workspace name cannot be empty"));
+    }
+    if !workspace
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(invalid(format!(
+            "Workspace name '{workspace}' may only contain letters, digits, '-', '_' and '.'"
+        )));
+    }
+    Ok(())
+}
+
+fn invalid(reason: impl Into<String>) -> PlannerError {
+    PlannerError::invalid_input("url").with_reason(reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_rejected(input: &str, expected_reason_contains: &str) {
+        match input.parse::<BeaconUri>() {
+            Err(PlannerError::InvalidInput { field, reason }) => {
+                assert_eq!(field, "url");
+                assert!(
+                    reason.contains(expected_reason_contains),
+                    "Expected reason to contain '{expected_reason_contains}', got: {reason}"
+                );
+            }
+            other => panic!("Expected InvalidInput error for '{input}', got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_check_workspace() {
+        let database = Path::new("/data/work.db");
+        assert_eq!(workspace_of(database), Some("work"));
+
+        assert!(BeaconUri::plan(1).check_workspace(database).is_ok());
+        let uri = BeaconUri::plan(1).with_workspace("work").unwrap();
+        assert!(uri.check_workspace(database).is_ok());
+
+        let uri = BeaconUri::step(2).with_workspace("home").unwrap();
+        match uri.check_workspace(database) {
+            Err(PlannerError::InvalidInput { field, reason }) => {
+                assert_eq!(field, "url");
+                assert!(reason.contains("belongs to workspace 'home'"), "{reason}");
+                assert!(reason.contains("is workspace 'work'"), "{reason}");
+            }
+            other => panic!("Expected InvalidInput error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for uri in [BeaconUri::plan(12), BeaconUri::step(7), BeaconUri::plans()] {
+            let formatted = uri.to_string();
+            assert_eq!(formatted.parse::<BeaconUri>().unwrap(), uri);
+        }
+        assert_eq!(BeaconUri::plan(12).to_string(), "beacon://open/plan/12");
+        assert_eq!(BeaconUri::step(7).to_string(), "beacon://open/step/7");
+        assert_eq!(BeaconUri::plans().to_string(), "beacon://open/plans");
+    }
+
+    #[test]
+    fn test_workspace_qualified_round_trip() {
+        let uri = BeaconUri::step(3).with_workspace("work-2").unwrap();
+        assert_eq!(uri.to_string(), "beacon://open/step/3?workspace=work-2");
+        assert_eq!(uri.to_string().parse::<BeaconUri>().unwrap(), uri);
+
+        let parsed: BeaconUri = "beacon://open/plan/5?workspace=home".parse().unwrap();
+        assert_eq!(parsed.target, UriTarget::Plan(5));
+        assert_eq!(parsed.workspace.as_deref(), Some("home"));
+
+        let parsed: BeaconUri = "beacon://open/plans?workspace=home".parse().unwrap();
+        assert_eq!(parsed.target, UriTarget::Plans);
+        assert_eq!(parsed.workspace.as_deref(), Some("home"));
+    }
+
+    #[test]
+    fn test_trailing_slash_and_whitespace_accepted() {
+        let parsed: BeaconUri = "  beacon://open/plan/9/ ".parse().unwrap();
+        assert_eq!(parsed, BeaconUri::plan(9));
+    }
+
+    #[test]
+    fn test_rejects_junk() {
+        assert_rejected("", "does not start with");
+        assert_rejected("http://open/plan/1", "does not start with");
+        assert_rejected("beacon:/open/plan/1", "does not start with");
+        assert_rejected("beacon://open/plan", "must have the form");
+        assert_rejected("beacon://open/plan/1/extra", "must have the form");
+        assert_rejected("beacon://open/plans/1", "Unknown entity");
+        assert_rejected("beacon://plans", "must have the form");
+        assert_rejected("beacon://edit/plan/1", "Unknown action");
+        assert_rejected("beacon://open/task/1", "Unknown entity");
+        assert_rejected("beacon://open/plan/abc", "not a valid plan ID");
+        assert_rejected("beacon://open/step/0", "not a valid step ID");
+        assert_rejected("beacon://open/plan/-1", "not a valid plan ID");
+        assert_rejected("beacon://open/plan/1?workspace", "Malformed query");
+        assert_rejected("beacon://open/plan/1?ws=home", "Unknown query parameter");
+        assert_rejected("beacon://open/plan/1?workspace=", "cannot be empty");
+        assert_rejected("beacon://open/plan/1?workspace=a/b", "may only contain");
+        assert!(BeaconUri::plan(1).with_workspace("bad name").is_err());
+    }
+}
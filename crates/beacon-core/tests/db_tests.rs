@@ -1,5 +1,12 @@
-use beacon_core::{Database, PlannerError, StepStatus, UpdateStepRequest};
-use tempfile::NamedTempFile;
+use std::path::Path;
+
+use beacon_core::{
+    CompletionFilter, ConflictPolicy, Database, EventEntity, EventKind, ImportOutcome,
+    MergeOptions, Momentum, PlanFilter, PlanStatus, PlannerError, StepStatus, StepStatusCounts,
+    TemplateStep, UpdatePlanRequest, UpdateStepRequest, db::migrations::SCHEMA_VERSION,
+    models::CreatePlanRequest,
+};
+use tempfile::{NamedTempFile, TempDir};
 
 /// Helper function to create a temporary database for testing
 fn create_test_db() -> (NamedTempFile, Database) {
@@ -200,6 +207,64 @@ fn test_get_steps() {
     assert_eq!(steps[2].order, 2);
 }
 
+#[test]
+fn test_step_status_counts() {
+    let (_temp_file, mut db) = create_test_db();
+
+    let plan = db
+        .create_plan("Counted Title", None, None)
+        .expect("Failed to create plan");
+    let counts = db.step_status_counts(plan.id).expect("Failed to count");
+    assert_eq!(counts, StepStatusCounts::default());
+
+    let statuses = [
+        StepStatus::Done,
+        StepStatus::InProgress,
+        StepStatus::Todo,
+        StepStatus::Todo,
+        StepStatus::Blocked,
+        StepStatus::Todo,
+    ];
+    let mut ids = Vec::new();
+    for (index, status) in statuses.into_iter().enumerate() {
+        let step = db
+            .add_step(plan.id, &format!("Step {index}"), None, None, Vec::new())
+            .expect("Failed to add step");
+        if status != StepStatus::Todo {
+            db.update_step(
+                step.id,
+                UpdateStepRequest {
+                    status: Some(status),
+                    result: (status == StepStatus::Done).then(|| "Finished".to_string()),
+                    ..Default::default()
+                },
+            )
+            .expect("Failed to update step");
+        }
+        ids.push(step.id);
+    }
+    // Removed steps aren't counted
+    db.remove_step(ids[5]).expect("Failed to remove step");
+
+    let counts = db.step_status_counts(plan.id).expect("Failed to count");
+    assert_eq!(
+        counts,
+        StepStatusCounts {
+            done: 1,
+            in_progress: 1,
+            todo: 2,
+            blocked: 1,
+        }
+    );
+    assert_eq!(counts.total(), 5);
+    assert_eq!(counts.count(StepStatus::Todo), 2);
+
+    assert!(matches!(
+        db.step_status_counts(9999),
+        Err(PlannerError::PlanNotFound { id: 9999 })
+    ));
+}
+
 #[test]
 fn test_remove_step() {
     let (_temp_file, mut db) = create_test_db();
@@ -227,6 +292,429 @@ fn test_remove_step() {
     assert!(steps.iter().any(|s| s.id == step3.id));
 }
 
+#[test]
+fn test_next_step_skips_started_finished_and_blocked_steps() {
+    let (_temp_file, mut db) = create_test_db();
+
+    let plan = db.create_plan("Next", None, None).unwrap();
+    let ids: Vec<u64> = ["Done", "Started", "Blocked", "Ready", "Later"]
+        .into_iter()
+        .map(|title| db.add_step(plan.id, title, None, None, vec![]).unwrap().id)
+        .collect();
+    let set_status = |db: &mut Database, id: u64, status: StepStatus| {
+        db.update_step(
+            id,
+            UpdateStepRequest {
+                status: Some(status),
+                result: Some("Finished".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    };
+    set_status(&mut db, ids[0], StepStatus::Done);
+    set_status(&mut db, ids[1], StepStatus::InProgress);
+    db.add_step_dependency(ids[2], ids[1]).unwrap();
+
+    let next = db.next_step(plan.id).unwrap().unwrap();
+    assert_eq!(next.id, ids[3]);
+    assert_eq!(next.title, "Ready");
+
+    // Finishing the dependency unblocks the earlier step
+    set_status(&mut db, ids[1], StepStatus::Done);
+    assert_eq!(db.next_step(plan.id).unwrap().unwrap().id, ids[2]);
+
+    for &id in &ids[2..] {
+        set_status(&mut db, id, StepStatus::Done);
+    }
+    assert_eq!(db.next_step(plan.id).unwrap(), None);
+
+    let empty = db.create_plan("Empty", None, None).unwrap();
+    assert_eq!(db.next_step(empty.id).unwrap(), None);
+    assert!(matches!(
+        db.next_step(9999),
+        Err(PlannerError::PlanNotFound { id: 9999 })
+    ));
+}
+
+#[test]
+fn test_claim_next_step_claims_steps_in_order() {
+    let (_temp_file, mut db) = create_test_db();
+
+    let plan = db.create_plan("Queue", None, None).unwrap();
+    let ids: Vec<u64> = ["First", "Second", "Third"]
+        .into_iter()
+        .map(|title| db.add_step(plan.id, title, None, None, vec![]).unwrap().id)
+        .collect();
+    db.add_step_dependency(ids[1], ids[0]).unwrap();
+
+    let claimed = db.claim_next_step(plan.id).unwrap().unwrap();
+    assert_eq!(claimed.id, ids[0]);
+    assert_eq!(claimed.status, StepStatus::InProgress);
+    assert!(claimed.started_at.is_some());
+
+    // The second step waits on the first, which is now in progress
+    assert_eq!(db.claim_next_step(plan.id).unwrap().unwrap().id, ids[2]);
+    assert_eq!(db.claim_next_step(plan.id).unwrap(), None);
+
+    assert!(matches!(
+        db.claim_next_step(9999),
+        Err(PlannerError::PlanNotFound { id: 9999 })
+    ));
+}
+
+#[test]
+fn test_mutations_record_events() {
+    let (_temp_file, mut db) = create_test_db();
+
+    let plan = db.create_plan("Events", None, None).unwrap();
+    let first = db.add_step(plan.id, "First", None, None, vec![]).unwrap();
+    let second = db.add_step(plan.id, "Second", None, None, vec![]).unwrap();
+    db.claim_step(first.id).unwrap().unwrap();
+    db.update_step(
+        first.id,
+        UpdateStepRequest {
+            status: Some(StepStatus::Done),
+            result: Some("Done it".into()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    db.swap_steps(first.id, second.id).unwrap();
+    db.remove_step(second.id).unwrap();
+    db.archive_plan(plan.id, false, false).unwrap();
+    db.delete_plan(plan.id, false).unwrap();
+
+    let events = db.list_events(None, None).unwrap();
+    let kinds: Vec<_> = events
+        .iter()
+        .map(|event| (event.entity_type, event.entity_id, event.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (EventEntity::Plan, plan.id, EventKind::Created),
+            (EventEntity::Step, first.id, EventKind::Added),
+            (EventEntity::Step, second.id, EventKind::Added),
+            (EventEntity::Step, first.id, EventKind::Claimed),
+            (EventEntity::Step, first.id, EventKind::Updated),
+            (EventEntity::Step, first.id, EventKind::Swapped),
+            (EventEntity::Step, second.id, EventKind::Swapped),
+            (EventEntity::Step, second.id, EventKind::Removed),
+            (EventEntity::Plan, plan.id, EventKind::Archived),
+            (EventEntity::Plan, plan.id, EventKind::Deleted),
+        ]
+    );
+
+    // Payloads are the serialized models
+    assert_eq!(events[4].payload["status"], "done");
+    assert_eq!(events[4].payload["result"], "Done it");
+    assert_eq!(events[9].payload["title"], "Events");
+
+    let limited = db.list_events(None, Some(2)).unwrap();
+    assert_eq!(limited, events[..2]);
+    let since = events[9].created_at;
+    assert!(
+        db.list_events(Some(since), None)
+            .unwrap()
+            .contains(&events[9])
+    );
+    let later = since
+        .checked_add(jiff::SignedDuration::from_secs(60))
+        .unwrap();
+    assert!(db.list_events(Some(later), None).unwrap().is_empty());
+}
+
+/// Returns the entity, ID and kind of the events recorded after the first
+/// `skip`.
+fn recorded_events(db: &Database, skip: usize) -> Vec<(EventEntity, u64, EventKind)> {
+    db.list_events(None, None)
+        .unwrap()
+        .into_iter()
+        .skip(skip)
+        .map(|event| (event.entity_type, event.entity_id, event.kind))
+        .collect()
+}
+
+#[test]
+fn test_plan_updates_record_events() {
+    let (_temp_file, mut db) = create_test_db();
+    let root = TempDir::new().unwrap();
+    let old = root.path().join("old");
+    let new = root.path().join("new");
+    let plan = db
+        .create_plan("Edited", None, Some(old.to_str().unwrap()))
+        .unwrap();
+    let skip = db.list_events(None, None).unwrap().len();
+
+    let request = UpdatePlanRequest {
+        title: Some("Renamed".into()),
+        ..Default::default()
+    };
+    db.update_plan(plan.id, &request).unwrap();
+    db.append_plan_note(plan.id, "Kicked off").unwrap();
+    db.set_plan_notes(plan.id, "Rewritten").unwrap();
+    assert_eq!(
+        db.reassign_directory(old.to_str().unwrap(), new.to_str().unwrap())
+            .unwrap(),
+        1
+    );
+
+    assert_eq!(
+        recorded_events(&db, skip),
+        [(EventEntity::Plan, plan.id, EventKind::Updated); 4]
+    );
+    let events = db.list_events(None, None).unwrap();
+    assert_eq!(events[skip].payload["title"], "Renamed");
+    assert_eq!(events[skip + 2].payload["notes"], "Rewritten");
+}
+
+#[test]
+fn test_merging_plans_records_events() {
+    let (_temp_file, mut db) = create_test_db();
+    let source = db.create_plan("Source", None, None).unwrap();
+    let target = db.create_plan("Target", None, None).unwrap();
+    let step = db.add_step(source.id, "Moved", None, None, vec![]).unwrap();
+    let skip = db.list_events(None, None).unwrap().len();
+
+    db.merge_plans(source.id, target.id, MergeOptions::default())
+        .unwrap();
+
+    assert_eq!(
+        recorded_events(&db, skip),
+        [
+            (EventEntity::Step, step.id, EventKind::Updated),
+            (EventEntity::Plan, target.id, EventKind::Updated),
+            (EventEntity::Plan, source.id, EventKind::Merged),
+        ]
+    );
+    let events = db.list_events(None, None).unwrap();
+    assert_eq!(events[skip + 2].payload["merged_into_plan_id"], target.id);
+}
+
+#[test]
+fn test_step_workflow_records_events() {
+    let (_temp_file, mut db) = create_test_db();
+    let plan = db.create_plan("Workflow", None, None).unwrap();
+    let step = db
+        .add_step(plan.id, "Reviewed", None, None, vec![])
+        .unwrap();
+    db.claim_step(step.id).unwrap().unwrap();
+    let skip = db.list_events(None, None).unwrap().len();
+
+    assert!(db.release_step(step.id).unwrap());
+    // Releasing a step that isn't in progress changes nothing
+    assert!(!db.release_step(step.id).unwrap());
+    db.update_step(
+        step.id,
+        UpdateStepRequest {
+            status: Some(StepStatus::Done),
+            result: Some("Done".into()),
+            verification_required: Some(true),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    db.verify_step(step.id, "reviewer", None).unwrap();
+
+    assert_eq!(
+        recorded_events(&db, skip),
+        [
+            (EventEntity::Step, step.id, EventKind::Released),
+            (EventEntity::Step, step.id, EventKind::Updated),
+            (EventEntity::Step, step.id, EventKind::Verified),
+        ]
+    );
+}
+
+#[test]
+fn test_organizing_steps_records_events() {
+    let (_temp_file, mut db) = create_test_db();
+    let plan = db.create_plan("Organized", None, None).unwrap();
+    let first = db.add_step(plan.id, "First", None, None, vec![]).unwrap();
+    let second = db.add_step(plan.id, "Second", None, None, vec![]).unwrap();
+    let skip = db.list_events(None, None).unwrap().len();
+
+    db.move_step(second.id, 0).unwrap();
+    // Moving a step to where it is changes nothing
+    db.move_step(second.id, 0).unwrap();
+    assert!(db.set_step_phase(first.id, Some("Build")).unwrap());
+    assert!(db.set_step_estimate(first.id, Some(30)).unwrap());
+    assert_eq!(db.renumber_steps(plan.id).unwrap(), 2);
+
+    assert_eq!(
+        recorded_events(&db, skip),
+        [
+            (EventEntity::Step, second.id, EventKind::Moved),
+            (EventEntity::Step, first.id, EventKind::Updated),
+            (EventEntity::Step, first.id, EventKind::Updated),
+            (EventEntity::Plan, plan.id, EventKind::Updated),
+        ]
+    );
+}
+
+#[test]
+fn test_trash_records_events() {
+    let (_temp_file, mut db) = create_test_db();
+    let plan = db.create_plan("Trashy", None, None).unwrap();
+    let kept = db.add_step(plan.id, "Kept", None, None, vec![]).unwrap();
+    let purged = db.add_step(plan.id, "Purged", None, None, vec![]).unwrap();
+    db.remove_step(kept.id).unwrap();
+    db.remove_step(purged.id).unwrap();
+    let skip = db.list_events(None, None).unwrap().len();
+
+    db.restore_step(kept.id).unwrap();
+    let later = jiff::Timestamp::now() + jiff::SignedDuration::from_secs(1);
+    assert_eq!(db.purge_trashed_steps(later).unwrap(), 1);
+
+    assert_eq!(
+        recorded_events(&db, skip),
+        [
+            (EventEntity::Step, kept.id, EventKind::Restored),
+            (EventEntity::Step, purged.id, EventKind::Purged),
+        ]
+    );
+    let events = db.list_events(None, None).unwrap();
+    assert_eq!(events[skip + 1].payload["title"], "Purged");
+}
+
+#[test]
+fn test_step_dependencies_and_notes_record_events() {
+    let (_temp_file, mut db) = create_test_db();
+    let plan = db.create_plan("Linked", None, None).unwrap();
+    let first = db.add_step(plan.id, "First", None, None, vec![]).unwrap();
+    let second = db.add_step(plan.id, "Second", None, None, vec![]).unwrap();
+    let skip = db.list_events(None, None).unwrap().len();
+
+    assert!(db.add_step_dependency(second.id, first.id).unwrap());
+    // Adding it again or removing a missing one changes nothing
+    assert!(!db.add_step_dependency(second.id, first.id).unwrap());
+    assert!(db.remove_step_dependency(second.id, first.id).unwrap());
+    assert!(!db.remove_step_dependency(second.id, first.id).unwrap());
+    db.add_step_note(first.id, "Looked into it").unwrap();
+
+    assert_eq!(
+        recorded_events(&db, skip),
+        [
+            (EventEntity::Step, second.id, EventKind::Updated),
+            (EventEntity::Step, second.id, EventKind::Updated),
+            (EventEntity::Step, first.id, EventKind::Updated),
+        ]
+    );
+    let events = db.list_events(None, None).unwrap();
+    assert_eq!(
+        events[skip].payload["depends_on"],
+        serde_json::json!([first.id])
+    );
+    assert_eq!(
+        events[skip + 2].payload["notes"][0]["body"],
+        "Looked into it"
+    );
+}
+
+#[test]
+fn test_failed_mutations_record_no_events() {
+    let (temp_file, mut db) = create_test_db();
+
+    let plan = db.create_plan("Atomic", None, None).unwrap();
+    let step = db.add_step(plan.id, "Pending", None, None, vec![]).unwrap();
+    let recorded = db.list_events(None, None).unwrap().len();
+
+    assert!(
+        db.update_step(
+            step.id,
+            UpdateStepRequest {
+                status: Some(StepStatus::Done),
+                ..Default::default()
+            },
+        )
+        .is_err()
+    );
+    assert!(db.remove_step(9999).is_err());
+
+    // The deletion is recorded before the plan is deleted, so failing the
+    // delete itself must roll the event back too
+    rusqlite::Connection::open(temp_file.path())
+        .unwrap()
+        .execute_batch(
+            "CREATE TRIGGER refuse_plan_delete BEFORE DELETE ON plans
+             BEGIN SELECT RAISE(ABORT, 'refused'); END;",
+        )
+        .unwrap();
+    assert!(db.delete_plan(plan.id, true).is_err());
+
+    assert_eq!(db.list_events(None, None).unwrap().len(), recorded);
+}
+
+#[test]
+fn test_trashed_steps_restore_and_purge() {
+    let (_temp_file, mut db) = create_test_db();
+
+    let plan = db.create_plan("Trash", None, None).unwrap();
+    let ids: Vec<u64> = ["First", "Second", "Third"]
+        .into_iter()
+        .map(|title| db.add_step(plan.id, title, None, None, vec![]).unwrap().id)
+        .collect();
+    db.add_step_dependency(ids[2], ids[1]).unwrap();
+
+    db.remove_step(ids[1]).unwrap();
+
+    // Trashed steps are hidden from the plan, its counts and lookups
+    let steps = db.get_steps(plan.id).unwrap();
+    let orders: Vec<_> = steps.iter().map(|s| (s.id, s.order)).collect();
+    assert_eq!(orders, vec![(ids[0], 0), (ids[2], 1)]);
+    assert!(steps[1].depends_on.is_empty());
+    assert_eq!(db.get_step(ids[1]).unwrap(), None);
+    assert_eq!(db.list_plan_summaries(None).unwrap()[0].total_steps, 2);
+    assert!(matches!(
+        db.remove_step(ids[1]),
+        Err(PlannerError::StepNotFound { .. })
+    ));
+    assert!(matches!(
+        db.add_step_note(ids[1], "Too late"),
+        Err(PlannerError::StepNotFound { .. })
+    ));
+
+    let trashed = db.get_trashed_steps(plan.id).unwrap();
+    assert_eq!(trashed.len(), 1);
+    assert_eq!(trashed[0].title, "Second");
+    assert_eq!(trashed[0].status, StepStatus::Todo);
+    assert!(matches!(
+        db.get_trashed_steps(9999),
+        Err(PlannerError::PlanNotFound { id: 9999 })
+    ));
+
+    // Restoring puts the step at the end with its dependencies intact
+    assert!(matches!(
+        db.restore_step(ids[0]),
+        Err(PlannerError::InvalidInput { .. })
+    ));
+    let restored = db.restore_step(ids[1]).unwrap();
+    assert_eq!(restored.order, 2);
+    assert!(db.get_trashed_steps(plan.id).unwrap().is_empty());
+    let third = db.get_step(ids[2]).unwrap().unwrap();
+    assert_eq!(third.depends_on, vec![ids[1]]);
+
+    // Purging only deletes steps trashed before the cutoff
+    db.remove_step(ids[1]).unwrap();
+    let now = jiff::Timestamp::now();
+    assert_eq!(
+        db.purge_trashed_steps(now - jiff::SignedDuration::from_hours(24))
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        db.purge_trashed_steps(now + jiff::SignedDuration::from_secs(1))
+            .unwrap(),
+        1
+    );
+    assert!(db.get_trashed_steps(plan.id).unwrap().is_empty());
+    assert!(matches!(
+        db.restore_step(ids[1]),
+        Err(PlannerError::StepNotFound { .. })
+    ));
+}
+
 #[test]
 fn test_insert_step_at_position() {
     let (_temp_file, mut db) = create_test_db();
@@ -407,7 +895,7 @@ fn test_directory_path_conversion() {
         .expect("Failed to create plan with relative path");
 
     // Directory should be converted to absolute path
-    assert!(relative_plan.directory.as_ref().unwrap().starts_with('/'));
+    assert!(Path::new(relative_plan.directory.as_ref().unwrap()).is_absolute());
     assert!(
         relative_plan
             .directory
@@ -433,7 +921,7 @@ fn test_directory_path_conversion() {
 
     // Should have a directory and it should be absolute
     assert!(default_plan.directory.is_some());
-    assert!(default_plan.directory.as_ref().unwrap().starts_with('/'));
+    assert!(Path::new(default_plan.directory.as_ref().unwrap()).is_absolute());
 }
 
 #[test]
@@ -447,7 +935,7 @@ fn test_empty_relative_path_conversion() {
 
     // Empty path should be converted to current working directory
     assert!(empty_plan.directory.is_some());
-    assert!(empty_plan.directory.as_ref().unwrap().starts_with('/'));
+    assert!(Path::new(empty_plan.directory.as_ref().unwrap()).is_absolute());
 
     // Test dot directory (current directory)
     let dot_plan = db
@@ -465,10 +953,438 @@ fn test_empty_relative_path_conversion() {
 
     // Dot should be converted to current working directory
     assert!(dot_plan.directory.is_some());
-    assert!(dot_plan.directory.as_ref().unwrap().starts_with('/'));
+    assert!(Path::new(dot_plan.directory.as_ref().unwrap()).is_absolute());
     assert_eq!(dot_plan.directory.as_ref().unwrap(), &expected_cwd);
 }
 
+#[test]
+fn test_find_git_root() {
+    let repo = TempDir::new().expect("Failed to create temp dir");
+    let nested = repo.path().join("crates/core/src");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::create_dir(repo.path().join(".git")).unwrap();
+
+    assert_eq!(
+        Database::find_git_root(&nested).as_deref(),
+        Some(repo.path())
+    );
+    assert_eq!(
+        Database::find_git_root(repo.path()).as_deref(),
+        Some(repo.path())
+    );
+
+    // A `.git` file (linked worktree) marks a root as well
+    let worktree = TempDir::new().expect("Failed to create temp dir");
+    std::fs::write(worktree.path().join(".git"), "gitdir: /elsewhere").unwrap();
+    assert_eq!(
+        Database::find_git_root(worktree.path()).as_deref(),
+        Some(worktree.path())
+    );
+
+    let plain = TempDir::new().expect("Failed to create temp dir");
+    assert_eq!(Database::find_git_root(plain.path()), None);
+}
+
+#[test]
+fn test_create_plan_infers_git_root() {
+    let (_temp_file, mut db) = create_test_db();
+    let repo = TempDir::new().expect("Failed to create temp dir");
+    let nested = repo.path().join("crates/core");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::create_dir(repo.path().join(".git")).unwrap();
+    let repo_root = repo.path().to_str().unwrap().to_string();
+
+    let plan = db
+        .create_plan_with(&CreatePlanRequest {
+            title: "Nested".to_string(),
+            directory: Some(nested.to_str().unwrap().to_string()),
+            infer_git_root: true,
+            ..Default::default()
+        })
+        .expect("Failed to create plan");
+    assert_eq!(plan.directory.as_ref(), Some(&repo_root));
+    assert_eq!(plan.subpath.as_deref(), Some("crates/core"));
+
+    // Round-trips through the database
+    let stored = db.get_plan(plan.id).unwrap().unwrap();
+    assert_eq!(stored.directory.as_ref(), Some(&repo_root));
+    assert_eq!(stored.subpath.as_deref(), Some("crates/core"));
+    let listed = db.list_plans(None).unwrap();
+    assert_eq!(listed[0].subpath.as_deref(), Some("crates/core"));
+
+    // Creating at the root itself records no subpath
+    let at_root = db
+        .create_plan_with(&CreatePlanRequest {
+            title: "Root".to_string(),
+            directory: Some(repo_root.clone()),
+            infer_git_root: true,
+            ..Default::default()
+        })
+        .expect("Failed to create plan");
+    assert_eq!(at_root.directory.as_ref(), Some(&repo_root));
+    assert_eq!(at_root.subpath, None);
+
+    // Without the flag the subdirectory is stored as-is
+    let unchanged = db
+        .create_plan("Plain", None, nested.to_str())
+        .expect("Failed to create plan");
+    assert_eq!(unchanged.directory.as_deref(), nested.to_str());
+    assert_eq!(unchanged.subpath, None);
+}
+
+#[test]
+fn test_update_plan_directory() {
+    let (_temp_file, mut db) = create_test_db();
+    let repo = TempDir::new().expect("Failed to create temp dir");
+    let nested = repo.path().join("crates/core");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::create_dir(repo.path().join(".git")).unwrap();
+
+    let plan = db
+        .create_plan("Typo'd", Some("Keep me"), Some("/tmp/old"))
+        .expect("Failed to create plan");
+    db.add_step(plan.id, "Existing step", None, None, vec![])
+        .unwrap();
+
+    // A relative directory is made absolute; other fields are kept
+    let updated = db
+        .update_plan(
+            plan.id,
+            &UpdatePlanRequest {
+                directory: Some("some/relative".to_string()),
+                ..Default::default()
+            },
+        )
+        .expect("Failed to update plan");
+    let directory = updated.directory.as_deref().unwrap();
+    assert!(Path::new(directory).is_absolute(), "{directory}");
+    assert!(directory.ends_with("some/relative"), "{directory}");
+    assert_eq!(updated.title, "Typo'd");
+    assert_eq!(updated.description.as_deref(), Some("Keep me"));
+    assert_eq!(updated.steps.len(), 1);
+
+    // The git root is inferred as when creating a plan
+    let updated = db
+        .update_plan(
+            plan.id,
+            &UpdatePlanRequest {
+                title: Some("Fixed".to_string()),
+                directory: Some(nested.to_str().unwrap().to_string()),
+                infer_git_root: true,
+                ..Default::default()
+            },
+        )
+        .expect("Failed to update plan");
+    assert_eq!(updated.directory.as_deref(), repo.path().to_str());
+    assert_eq!(updated.subpath.as_deref(), Some("crates/core"));
+
+    // A new directory outside the repository replaces the subpath
+    let updated = db
+        .update_plan(
+            plan.id,
+            &UpdatePlanRequest {
+                directory: Some("/srv/app".to_string()),
+                ..Default::default()
+            },
+        )
+        .expect("Failed to update plan");
+    assert_eq!(updated.directory.as_deref(), Some("/srv/app"));
+    assert_eq!(updated.subpath, None);
+
+    // An empty update changes nothing, not even the update time
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    let unchanged = db
+        .update_plan(plan.id, &UpdatePlanRequest::default())
+        .expect("Empty update should succeed");
+    assert_eq!(unchanged, updated);
+
+    assert!(matches!(
+        db.update_plan(9999, &UpdatePlanRequest::default()),
+        Err(PlannerError::PlanNotFound { id: 9999 })
+    ));
+}
+
+#[test]
+fn test_create_plan_git_root_outside_repository() {
+    let (_temp_file, mut db) = create_test_db();
+    let plain = TempDir::new().expect("Failed to create temp dir");
+    let nested = plain.path().join("not/a/repo");
+    std::fs::create_dir_all(&nested).unwrap();
+
+    let plan = db
+        .create_plan_with(&CreatePlanRequest {
+            title: "No Repo".to_string(),
+            directory: Some(nested.to_str().unwrap().to_string()),
+            infer_git_root: true,
+            ..Default::default()
+        })
+        .expect("Failed to create plan");
+    assert_eq!(plan.directory.as_deref(), nested.to_str());
+    assert_eq!(plan.subpath, None);
+}
+
+#[test]
+fn test_plan_summaries_oldest_in_progress_age() {
+    let (temp_file, mut db) = create_test_db();
+
+    let stale = db.create_plan("Stale", None, None).unwrap();
+    let fresh = db.create_plan("Fresh", None, None).unwrap();
+    let idle = db.create_plan("Idle", None, None).unwrap();
+
+    let old_step = db.add_step(stale.id, "Old", None, None, vec![]).unwrap();
+    let newer_step = db.add_step(stale.id, "Newer", None, None, vec![]).unwrap();
+    let done_step = db.add_step(stale.id, "Done", None, None, vec![]).unwrap();
+    let fresh_step = db.add_step(fresh.id, "Fresh", None, None, vec![]).unwrap();
+    db.add_step(idle.id, "Todo", None, None, vec![]).unwrap();
+
+    for id in [old_step.id, newer_step.id, fresh_step.id] {
+        db.claim_step(id).unwrap();
+    }
+    db.update_step(
+        done_step.id,
+        UpdateStepRequest {
+            status: Some(StepStatus::Done),
+            result: Some("Done".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    // Backdate steps directly, as if they had been claimed long ago
+    let now = jiff::Timestamp::now();
+    let backdate = |id: u64, days: i64| {
+        let conn = rusqlite::Connection::open(temp_file.path()).unwrap();
+        let at = now - jiff::SignedDuration::from_hours(24 * days);
+        conn.execute(
+            "UPDATE steps SET updated_at = ?1 WHERE id = ?2",
+            rusqlite::params![at.to_string(), id as i64],
+        )
+        .unwrap();
+    };
+    backdate(old_step.id, 21);
+    backdate(newer_step.id, 3);
+    // Done steps never count, however old
+    backdate(done_step.id, 60);
+
+    let summaries = db.list_plan_summaries(None).unwrap();
+    let age_of = |id: u64| {
+        summaries
+            .iter()
+            .find(|s| s.id == id)
+            .unwrap()
+            .oldest_in_progress_age
+    };
+
+    let day = std::time::Duration::from_secs(24 * 60 * 60);
+    let stale_age = age_of(stale.id).expect("Stale plan should have an age");
+    assert!(stale_age >= 21 * day && stale_age < 22 * day);
+    let fresh_age = age_of(fresh.id).expect("Fresh plan should have an age");
+    assert!(fresh_age < day);
+    assert_eq!(age_of(idle.id), None);
+
+    // Counts come from the view as well
+    let stale_summary = summaries.iter().find(|s| s.id == stale.id).unwrap();
+    assert_eq!(stale_summary.total_steps, 3);
+    assert_eq!(stale_summary.completed_steps, 1);
+    assert!(format!("{stale_summary}").contains("⚠ step in progress 21d"));
+}
+
+#[test]
+fn test_get_plan_summary() {
+    let (_temp_file, mut db) = create_test_db();
+
+    let plan = db.create_plan("Summarized", None, None).unwrap();
+    for title in ["First", "Second", "Third"] {
+        db.add_step(plan.id, title, None, None, vec![]).unwrap();
+    }
+    let first = db.get_steps(plan.id).unwrap()[0].id;
+    db.update_step(
+        first,
+        UpdateStepRequest {
+            status: Some(StepStatus::Done),
+            result: Some("Done".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let summary = db.get_plan_summary(plan.id).unwrap().unwrap();
+    assert_eq!(summary.title, "Summarized");
+    assert_eq!((summary.completed_steps, summary.total_steps), (1, 3));
+    assert_eq!(db.list_plan_summaries(None).unwrap(), vec![summary]);
+
+    // Archived plans are summarized too
+    db.archive_plan(plan.id, false, false).unwrap();
+    let summary = db.get_plan_summary(plan.id).unwrap().unwrap();
+    assert_eq!(summary.status, PlanStatus::Archived);
+    assert_eq!(summary.total_steps, 3);
+
+    assert_eq!(db.get_plan_summary(999).unwrap(), None);
+}
+
+#[test]
+fn test_plan_summaries_match_step_counts() {
+    let (_temp_file, mut db) = create_test_db();
+
+    // Plans with zero to four steps, some of them done
+    for i in 0..50_u64 {
+        let plan = db.create_plan(&format!("Plan {i}"), None, None).unwrap();
+        let total = i % 5;
+        for j in 0..total {
+            let step = db
+                .add_step(plan.id, &format!("Step {j}"), None, None, vec![])
+                .unwrap();
+            if j < i % 3 {
+                db.update_step(
+                    step.id,
+                    UpdateStepRequest {
+                        status: Some(StepStatus::Done),
+                        result: Some("Done".to_string()),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    let summaries = db.list_plan_summaries(None).unwrap();
+    assert_eq!(summaries.len(), 50);
+    for summary in &summaries {
+        let steps = db.get_steps(summary.id).unwrap();
+        let completed = steps
+            .iter()
+            .filter(|step| step.status == StepStatus::Done)
+            .count();
+        assert_eq!(summary.total_steps as usize, steps.len());
+        assert_eq!(summary.completed_steps as usize, completed);
+    }
+
+    // Completion filters select by the counts from the view
+    let matching = |completion| {
+        let filter = PlanFilter::builder()
+            .completion(completion)
+            .build()
+            .expect("Valid filter");
+        db.list_plan_summaries(Some(&filter)).unwrap()
+    };
+    let complete = matching(CompletionFilter::Complete);
+    assert!(
+        complete
+            .iter()
+            .all(|s| s.total_steps > 0 && s.completed_steps == s.total_steps)
+    );
+    let incomplete = matching(CompletionFilter::Incomplete);
+    assert!(incomplete.iter().all(|s| s.completed_steps < s.total_steps));
+    let empty = matching(CompletionFilter::Empty);
+    assert!(empty.iter().all(|s| s.total_steps == 0));
+    assert_eq!(empty.len(), 10);
+    assert_eq!(complete.len() + incomplete.len() + empty.len(), 50);
+}
+
+#[test]
+fn test_count_completions_in_range() {
+    let (temp_file, mut db) = create_test_db();
+
+    let plan = db.create_plan("Metrics", None, None).unwrap();
+    let mut step_ids = Vec::new();
+    for title in ["Before", "Start", "Inside", "End", "After", "Open"] {
+        step_ids.push(db.add_step(plan.id, title, None, None, vec![]).unwrap().id);
+    }
+    for &id in &step_ids[..5] {
+        db.update_step(
+            id,
+            UpdateStepRequest {
+                status: Some(StepStatus::Done),
+                result: Some("Done".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    }
+
+    let start: jiff::Timestamp = "2025-03-03T00:00:00Z".parse().unwrap();
+    let end: jiff::Timestamp = "2025-03-10T00:00:00Z".parse().unwrap();
+
+    // Set completion times directly, including sub-second precision that
+    // would compare incorrectly as plain strings
+    let conn = rusqlite::Connection::open(temp_file.path()).unwrap();
+    let completed_at = [
+        "2025-03-02T23:59:59.999Z", // just before the window
+        "2025-03-03T00:00:00Z",     // window start is inclusive
+        "2025-03-05T12:30:00.5Z",
+        "2025-03-10T00:00:00Z", // window end is exclusive
+        "2025-03-11T08:00:00Z",
+        "2025-03-05T12:00:00Z", // inside, but the step is not done
+    ];
+    for (id, at) in step_ids.iter().zip(completed_at) {
+        conn.execute(
+            "UPDATE steps SET completed_at = ?1 WHERE id = ?2",
+            rusqlite::params![at, *id as i64],
+        )
+        .unwrap();
+    }
+
+    assert_eq!(db.count_completions_in_range(start, end).unwrap(), 2);
+    assert_eq!(db.count_completions_in_range(end, start).unwrap(), 0);
+
+    let wide_end: jiff::Timestamp = "2025-04-01T00:00:00Z".parse().unwrap();
+    assert_eq!(db.count_completions_in_range(start, wide_end).unwrap(), 4);
+}
+
+#[test]
+fn test_editing_done_step_keeps_completion_time() {
+    let (temp_file, mut db) = create_test_db();
+
+    let plan = db.create_plan("Metrics", None, None).unwrap();
+    let step = db
+        .add_step(plan.id, "Old work", None, None, vec![])
+        .unwrap();
+    db.update_step(
+        step.id,
+        UpdateStepRequest {
+            status: Some(StepStatus::Done),
+            result: Some("Done".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    // Completed a month ago
+    let completed_at: jiff::Timestamp = "2025-03-05T12:00:00Z".parse().unwrap();
+    rusqlite::Connection::open(temp_file.path())
+        .unwrap()
+        .execute(
+            "UPDATE steps SET completed_at = ?1, updated_at = ?1 WHERE id = ?2",
+            rusqlite::params![completed_at.to_string(), step.id as i64],
+        )
+        .unwrap();
+
+    let week_start = jiff::Timestamp::now() - jiff::SignedDuration::from_hours(7 * 24);
+    let week_end = jiff::Timestamp::now() + jiff::SignedDuration::from_hours(1);
+    let metrics = |db: &Database| {
+        (
+            db.count_completions_in_range(week_start, week_end).unwrap(),
+            db.completion_times().unwrap(),
+        )
+    };
+    let before = metrics(&db);
+    assert_eq!(before, (0, vec![(plan.id, completed_at)]));
+
+    // Editing the step bumps its updated_at, but it was not completed again
+    db.update_step(
+        step.id,
+        UpdateStepRequest {
+            title: Some("Old work, renamed".to_string()),
+            description: Some("Described later".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(db.get_step(step.id).unwrap().unwrap().updated_at > completed_at);
+    assert_eq!(metrics(&db), before);
+}
+
 #[test]
 fn test_swap_steps_same_plan() {
     let (_temp_file, mut db) = create_test_db();
@@ -575,16 +1491,132 @@ fn test_swap_steps_with_self() {
 }
 
 #[test]
-fn test_swap_nonexistent_steps() {
+fn test_move_step() {
     let (_temp_file, mut db) = create_test_db();
-
     let plan = db
-        .create_plan("Test Plan", None, None)
+        .create_plan("Move Test Plan", None, None)
         .expect("Failed to create plan");
+    let mut expected: Vec<u64> = (1..=5)
+        .map(|i| {
+            db.add_step(plan.id, &format!("Step {i}"), None, None, Vec::new())
+                .expect("Failed to add step")
+                .id
+        })
+        .collect();
 
-    let step = db
-        .add_step(plan.id, "Existing Step", None, None, Vec::new())
-        .expect("Failed to add step");
+    // Forward, backward, to the ends, and to the current position
+    for (from, to) in [(4, 1), (0, 3), (2, 0), (1, 4), (3, 3)] {
+        let step_id = expected[from];
+        db.move_step(step_id, to as u32)
+            .expect("Failed to move step");
+        expected.remove(from);
+        expected.insert(to, step_id);
+        assert_positions(&db, plan.id, &expected);
+    }
+
+    // Repeatedly moving to the front uses up the gaps and respaces the plan
+    for _ in 0..20 {
+        let step_id = expected.pop().unwrap();
+        db.move_step(step_id, 0).expect("Failed to move step");
+        expected.insert(0, step_id);
+    }
+    assert_positions(&db, plan.id, &expected);
+
+    match db.move_step(expected[0], 5).unwrap_err() {
+        PlannerError::InvalidInput { field, reason } => {
+            assert_eq!(field, "new_position");
+            assert!(reason.contains("valid positions are 0 to 4"), "{reason}");
+        }
+        other => panic!("Expected InvalidInput error, got {other:?}"),
+    }
+    assert!(matches!(
+        db.move_step(99999, 0),
+        Err(PlannerError::StepNotFound { id: 99999 })
+    ));
+    assert_positions(&db, plan.id, &expected);
+}
+
+#[test]
+fn test_move_step_to_plan() {
+    let (_temp_file, mut db) = create_test_db();
+    let source = db.create_plan("Source", None, None).unwrap();
+    let target = db.create_plan("Target", None, None).unwrap();
+    let add = |db: &mut Database, plan_id: u64, count: usize| -> Vec<u64> {
+        (0..count)
+            .map(|i| {
+                db.add_step(plan_id, &format!("Step {i}"), None, None, Vec::new())
+                    .unwrap()
+                    .id
+            })
+            .collect()
+    };
+    let mut source_ids = add(&mut db, source.id, 3);
+    let mut target_ids = add(&mut db, target.id, 2);
+    let moved = source_ids[1];
+    db.claim_step(moved).unwrap();
+    let (source_before, _) = updated_ats(&db, source.id, source_ids[0]);
+    let (target_before, _) = updated_ats(&db, target.id, target_ids[0]);
+    std::thread::sleep(std::time::Duration::from_millis(5));
+
+    // The step keeps its status, the gap closes and both plans are updated
+    let step = db.move_step_to_plan(moved, target.id, Some(1)).unwrap();
+    assert_eq!((step.plan_id, step.order), (target.id, 1));
+    assert_eq!(step.status, StepStatus::InProgress);
+    source_ids.remove(1);
+    target_ids.insert(1, moved);
+    assert_positions(&db, source.id, &source_ids);
+    assert_positions(&db, target.id, &target_ids);
+    assert!(updated_ats(&db, source.id, source_ids[0]).0 > source_before);
+    assert!(updated_ats(&db, target.id, target_ids[0]).0 > target_before);
+
+    // Without a position the step goes to the end
+    let step = db
+        .move_step_to_plan(source_ids[0], target.id, None)
+        .unwrap();
+    assert_eq!(step.order, 3);
+    target_ids.push(source_ids.remove(0));
+    assert_positions(&db, target.id, &target_ids);
+
+    // Within its own plan the step is repositioned
+    db.move_step_to_plan(moved, target.id, Some(0)).unwrap();
+    target_ids.retain(|&id| id != moved);
+    target_ids.insert(0, moved);
+    assert_positions(&db, target.id, &target_ids);
+
+    match db.move_step_to_plan(source_ids[0], target.id, Some(9)) {
+        Err(PlannerError::InvalidInput { field, .. }) => assert_eq!(field, "position"),
+        other => panic!("Expected InvalidInput error, got {other:?}"),
+    }
+    assert!(matches!(
+        db.move_step_to_plan(source_ids[0], 99999, None),
+        Err(PlannerError::PlanNotFound { id: 99999 })
+    ));
+    assert!(matches!(
+        db.move_step_to_plan(99999, target.id, None),
+        Err(PlannerError::StepNotFound { id: 99999 })
+    ));
+    db.archive_plan(target.id, true, false).unwrap();
+    match db.move_step_to_plan(source_ids[0], target.id, None) {
+        Err(PlannerError::InvalidInput { field, reason }) => {
+            assert_eq!(field, "plan_id");
+            assert!(reason.contains("archived"), "{reason}");
+        }
+        other => panic!("Expected InvalidInput error, got {other:?}"),
+    }
+    assert_positions(&db, source.id, &source_ids);
+}
+
+#[test]
+fn test_swap_nonexistent_steps() {
+    let (_temp_file, mut db) = create_test_db();
+
+    let plan = db
+        .create_plan("Test Plan", None, None)
+        .expect("Failed to create plan");
+
+    let step = db
+        .add_step(plan.id, "Existing Step", None, None, Vec::new())
+        .expect("Failed to add step");
 
     // Try to swap with a non-existent step
     let result = db.swap_steps(step.id, 99999);
@@ -703,7 +1735,7 @@ fn test_update_step_result_ignored_for_non_done_status() {
         Some("Completed successfully".to_string())
     );
 
-    // Change back to todo (result should be cleared)
+    // Change back to todo (the given result is ignored, the stored one kept)
     db.update_step(
         step.id,
         UpdateStepRequest {
@@ -719,7 +1751,83 @@ fn test_update_step_result_ignored_for_non_done_status() {
         .expect("Failed to get step")
         .expect("Step should exist");
     assert_eq!(updated_step.status, StepStatus::Todo);
-    assert_eq!(updated_step.result, None); // Result should be cleared
+    assert_eq!(
+        updated_step.result,
+        Some("Completed successfully".to_string())
+    );
+}
+
+#[test]
+fn test_reopened_step_keeps_result() {
+    let (_temp_file, mut db) = create_test_db();
+    let plan = db.create_plan("Test Plan", None, None).unwrap();
+    let step = db
+        .add_step(plan.id, "Test Step", None, None, Vec::new())
+        .unwrap();
+    let complete = |result: Option<&str>| UpdateStepRequest {
+        status: Some(StepStatus::Done),
+        result: result.map(String::from),
+        ..Default::default()
+    };
+    let reopen = |clear_result| UpdateStepRequest {
+        status: Some(StepStatus::InProgress),
+        clear_result,
+        ..Default::default()
+    };
+
+    db.update_step(step.id, complete(Some("First report")))
+        .unwrap();
+    db.update_step(step.id, reopen(false)).unwrap();
+    let reopened = db.get_step(step.id).unwrap().unwrap();
+    assert_eq!(reopened.status, StepStatus::InProgress);
+    assert_eq!(reopened.result.as_deref(), Some("First report"));
+    // The kept result is hidden while the step isn't done
+    assert!(!reopened.to_string().contains("First report"));
+
+    // Re-completing without a result reuses the kept one
+    db.update_step(step.id, complete(None))
+        .expect("Re-completing should reuse the kept result");
+    let step_after = db.get_step(step.id).unwrap().unwrap();
+    assert_eq!(step_after.status, StepStatus::Done);
+    assert_eq!(step_after.result.as_deref(), Some("First report"));
+
+    // Completing it again is refused, but a new result replaces it
+    assert!(
+        db.update_step(step.id, complete(Some("Second report")))
+            .is_err()
+    );
+    db.update_step(
+        step.id,
+        UpdateStepRequest {
+            result: Some("Second report".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        db.get_step(step.id).unwrap().unwrap().result.as_deref(),
+        Some("Second report")
+    );
+
+    // A done step can't drop its result
+    match db.update_step(
+        step.id,
+        UpdateStepRequest {
+            clear_result: true,
+            ..Default::default()
+        },
+    ) {
+        Err(PlannerError::InvalidInput { field, .. }) => assert_eq!(field, "clear_result"),
+        other => panic!("Expected InvalidInput error, got {other:?}"),
+    }
+
+    // Reopening with clear_result drops it, so a new one is needed again
+    db.update_step(step.id, reopen(true)).unwrap();
+    assert!(db.get_step(step.id).unwrap().unwrap().result.is_none());
+    match db.update_step(step.id, complete(None)) {
+        Err(PlannerError::InvalidInput { field, .. }) => assert_eq!(field, "result"),
+        other => panic!("Expected InvalidInput error, got {other:?}"),
+    }
 }
 
 #[test]
@@ -744,7 +1852,8 @@ fn test_delete_plan() {
     assert!(db.get_step(step2.id).expect("Failed to get step").is_some());
 
     // Delete the plan
-    db.delete_plan(plan.id).expect("Failed to delete plan");
+    db.delete_plan(plan.id, true)
+        .expect("Failed to delete plan");
 
     // Verify plan and steps are deleted
     assert!(db.get_plan(plan.id).expect("Failed to get plan").is_none());
@@ -757,7 +1866,7 @@ fn test_delete_nonexistent_plan() {
     let (_temp_file, mut db) = create_test_db();
 
     // Try to delete a plan that doesn't exist
-    let result = db.delete_plan(999);
+    let result = db.delete_plan(999, true);
     assert!(result.is_err());
 
     match result.unwrap_err() {
@@ -895,3 +2004,1107 @@ fn test_performance_eager_loading() {
         duration
     );
 }
+
+/// Asserts that the plan's steps are `expected` in order, with contiguous
+/// positions everywhere a step's position is exposed.
+fn assert_positions(db: &Database, plan_id: u64, expected: &[u64]) {
+    let steps = db.get_steps(plan_id).expect("Failed to get steps");
+    let ids: Vec<u64> = steps.iter().map(|step| step.id).collect();
+    assert_eq!(ids, expected);
+
+    let outline = db.get_plan_outline(plan_id).expect("Failed to get outline");
+    for (position, (step, entry)) in steps.iter().zip(&outline).enumerate() {
+        assert_eq!(step.order, position as u32);
+        assert_eq!(entry.id, step.id);
+        assert_eq!(entry.order, position as u32);
+        let single = db.get_step(step.id).unwrap().expect("Step not found");
+        assert_eq!(single.order, position as u32);
+    }
+}
+
+#[test]
+fn test_step_positions_stay_contiguous_across_mutations() {
+    let (_temp_file, mut db) = create_test_db();
+    let plan = db
+        .create_plan("Positions", None, None)
+        .expect("Failed to create plan");
+
+    let mut expected = Vec::new();
+    for title in ["A", "B", "C"] {
+        let step = db.add_step(plan.id, title, None, None, Vec::new()).unwrap();
+        assert_eq!(step.order, expected.len() as u32);
+        expected.push(step.id);
+    }
+    assert_positions(&db, plan.id, &expected);
+
+    for (position, title) in [(0, "front"), (2, "middle"), (5, "end")] {
+        let step = db
+            .insert_step(plan.id, position, title, None, None, Vec::new())
+            .unwrap();
+        assert_eq!(step.order, position);
+        expected.insert(position as usize, step.id);
+        assert_positions(&db, plan.id, &expected);
+    }
+
+    db.swap_steps(expected[0], expected[4]).unwrap();
+    expected.swap(0, 4);
+    assert_positions(&db, plan.id, &expected);
+
+    db.move_step(expected[1], 4).unwrap();
+    let moved = expected.remove(1);
+    expected.insert(4, moved);
+    assert_positions(&db, plan.id, &expected);
+
+    db.remove_step(expected[2]).unwrap();
+    expected.remove(2);
+    assert_positions(&db, plan.id, &expected);
+
+    // Removing leaves a gap that later inserts and appends reuse correctly
+    let step = db
+        .insert_step(plan.id, 2, "refill", None, None, Vec::new())
+        .unwrap();
+    expected.insert(2, step.id);
+    let step = db
+        .add_step(plan.id, "last", None, None, Vec::new())
+        .unwrap();
+    assert_eq!(step.order, expected.len() as u32);
+    expected.push(step.id);
+    assert_positions(&db, plan.id, &expected);
+
+    db.update_step(
+        expected[1],
+        UpdateStepRequest {
+            status: Some(StepStatus::InProgress),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_positions(&db, plan.id, &expected);
+}
+
+/// Creates a plan in each of the named subdirectories of `root`.
+fn create_plans_in(db: &mut Database, root: &TempDir, names: &[&str]) -> Vec<u64> {
+    names
+        .iter()
+        .map(|name| {
+            let directory = root.path().join(name);
+            std::fs::create_dir_all(&directory).unwrap();
+            db.create_plan(name, None, directory.to_str())
+                .expect("Failed to create plan")
+                .id
+        })
+        .collect()
+}
+
+fn listed_ids(db: &Database, filter: &PlanFilter) -> Vec<u64> {
+    let mut ids: Vec<u64> = db
+        .list_plans(Some(filter))
+        .expect("Failed to list plans")
+        .iter()
+        .map(|plan| plan.id)
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+#[test]
+fn test_list_plans_matching_any_directory() {
+    let (_temp_file, mut db) = create_test_db();
+    let root = TempDir::new().unwrap();
+    let ids = create_plans_in(&mut db, &root, &["api", "api/nested", "web", "docs"]);
+    let dir = |name: &str| root.path().join(name).to_str().unwrap().to_string();
+
+    let filter = PlanFilter::builder()
+        .directory_any([dir("api"), dir("web")])
+        .build()
+        .unwrap();
+    assert_eq!(listed_ids(&db, &filter), vec![ids[0], ids[1], ids[2]]);
+
+    // The OR group is combined with the other conditions
+    let filter = PlanFilter::builder()
+        .directory_any([dir("api"), dir("docs")])
+        .title_contains("nested")
+        .build()
+        .unwrap();
+    assert_eq!(listed_ids(&db, &filter), vec![ids[1]]);
+
+    let filter = PlanFilter::builder()
+        .directory_any([dir("missing")])
+        .build()
+        .unwrap();
+    assert!(listed_ids(&db, &filter).is_empty());
+}
+
+#[test]
+fn test_list_plans_pages_in_listing_order() {
+    let (_temp_file, mut db) = create_test_db();
+    let root = TempDir::new().unwrap();
+    let ids = create_plans_in(&mut db, &root, &["a", "b", "c", "d", "e"]);
+    let page = |filter: PlanFilter| {
+        db.list_plans(Some(&filter))
+            .unwrap()
+            .iter()
+            .map(|plan| plan.id)
+            .collect::<Vec<_>>()
+    };
+
+    // Newest first, so the second page of two starts at the third newest
+    let filter = PlanFilter::builder().limit(2).offset(2).build().unwrap();
+    assert_eq!(page(filter), vec![ids[2], ids[1]]);
+    let filter = PlanFilter::builder().offset(3).build().unwrap();
+    assert_eq!(page(filter), vec![ids[1], ids[0]]);
+    let filter = PlanFilter::builder().limit(2).build().unwrap();
+    assert_eq!(page(filter), vec![ids[4], ids[3]]);
+
+    // Past the end is an empty page that still counts every plan
+    let filter = PlanFilter::builder().limit(2).offset(10).build().unwrap();
+    let summaries = db.list_plan_summaries_page(Some(&filter)).unwrap();
+    assert!(summaries.items.is_empty());
+    assert_eq!((summaries.total, summaries.offset), (5, 10));
+}
+
+#[test]
+fn test_list_plans_pages_after_completion_filter() {
+    let (_temp_file, mut db) = create_test_db();
+    let root = TempDir::new().unwrap();
+    let ids = create_plans_in(&mut db, &root, &["a", "b", "c", "d"]);
+    for &id in &ids[1..] {
+        db.add_step(id, "Step", None, None, vec![]).unwrap();
+    }
+
+    let filter = PlanFilter::builder()
+        .completion(CompletionFilter::Incomplete)
+        .limit(1)
+        .offset(1)
+        .build()
+        .unwrap();
+    let summaries = db.list_plan_summaries_page(Some(&filter)).unwrap();
+    let listed: Vec<u64> = summaries.items.iter().map(|summary| summary.id).collect();
+    assert_eq!(listed, vec![ids[2]]);
+    assert_eq!(summaries.total, 3);
+    assert_eq!(db.count_plans(Some(&filter)).unwrap(), 3);
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_list_plans_single_directory_filters_stay_compatible() {
+    let (_temp_file, mut db) = create_test_db();
+    let root = TempDir::new().unwrap();
+    let ids = create_plans_in(&mut db, &root, &["api", "api/nested", "web"]);
+    let api = root.path().join("api").to_str().unwrap().to_string();
+
+    let literal = PlanFilter {
+        directory: Some(api.clone()),
+        ..Default::default()
+    };
+    let built = PlanFilter::builder()
+        .directory(api.clone())
+        .build()
+        .unwrap();
+    let searched = PlanFilter::for_directory(api, false);
+
+    for filter in [literal, built, searched] {
+        assert_eq!(listed_ids(&db, &filter), vec![ids[0], ids[1]]);
+    }
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_plan_filter_fields_stay_public_while_deprecated() {
+    let (_temp_file, mut db) = create_test_db();
+    let root = TempDir::new().unwrap();
+    let ids = create_plans_in(&mut db, &root, &["api", "api/nested", "web"]);
+    db.archive_plan(ids[1], true, false).unwrap();
+    let api = root.path().join("api").to_str().unwrap().to_string();
+
+    let mut literal = PlanFilter {
+        title_contains: Some("api".to_string()),
+        directory: Some(api.clone()),
+        created_after: None,
+        created_before: None,
+        completion_status: None,
+        status: Some(PlanStatus::Archived),
+        include_archived: true,
+        ..PlanFilter::new()
+    };
+    let built = PlanFilter::builder()
+        .title_contains("api")
+        .directory(api.clone())
+        .status(PlanStatus::Archived)
+        .include_archived(true)
+        .build()
+        .unwrap();
+
+    assert_eq!(literal.title_contains(), Some("api"));
+    assert_eq!(literal.directories(), vec![api.as_str()]);
+    assert_eq!(literal.status(), Some(PlanStatus::Archived));
+    assert_eq!(listed_ids(&db, &literal), vec![ids[1]]);
+    assert_eq!(listed_ids(&db, &built), vec![ids[1]]);
+
+    literal.status = None;
+    assert_eq!(listed_ids(&db, &literal), vec![ids[0], ids[1]]);
+}
+
+#[test]
+fn test_list_plans_matches_directories_at_separators() {
+    let (_temp_file, mut db) = create_test_db();
+    let root = TempDir::new().unwrap();
+    let ids = create_plans_in(
+        &mut db,
+        &root,
+        &[
+            "proj",
+            "proj/nested",
+            "project-x",
+            "a_b",
+            "axb",
+            "100%",
+            "1000",
+        ],
+    );
+    let dir = |name: &str| root.path().join(name).to_str().unwrap().to_string();
+    let filter = |name: &str, include_subdirectories: bool| {
+        PlanFilter::builder()
+            .directory(dir(name))
+            .include_subdirectories(include_subdirectories)
+            .build()
+            .unwrap()
+    };
+
+    // The directory and its subdirectories, but not a sibling sharing a prefix
+    assert_eq!(listed_ids(&db, &filter("proj", true)), vec![ids[0], ids[1]]);
+    // Only the exact directory
+    assert_eq!(listed_ids(&db, &filter("proj", false)), vec![ids[0]]);
+    // LIKE wildcards in the path match only themselves
+    assert_eq!(listed_ids(&db, &filter("a_b", true)), vec![ids[3]]);
+    assert_eq!(listed_ids(&db, &filter("100%", true)), vec![ids[5]]);
+}
+
+/// Reads the stored `updated_at` of a plan and of one of its steps.
+fn updated_ats(db: &Database, plan_id: u64, step_id: u64) -> (jiff::Timestamp, jiff::Timestamp) {
+    let plan = db.get_plan(plan_id).unwrap().unwrap();
+    let step = db.get_step(step_id).unwrap().unwrap();
+    (plan.updated_at, step.updated_at)
+}
+
+#[test]
+fn test_updated_at_maintained_on_every_mutation() {
+    let (temp_file, mut db) = create_test_db();
+    let plan = db.create_plan("Plan", None, None).unwrap();
+    let step = db.add_step(plan.id, "Step", None, None, vec![]).unwrap();
+    let other = db.add_step(plan.id, "Other", None, None, vec![]).unwrap();
+
+    // Each mutation must bump the plan, and the step when it changes it
+    let check =
+        |db: &mut Database, what: &str, bumps_step: bool, mutate: &dyn Fn(&mut Database)| {
+            let (plan_before, step_before) = updated_ats(db, plan.id, step.id);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            mutate(db);
+            let (plan_after, step_after) = updated_ats(db, plan.id, step.id);
+            assert!(plan_after > plan_before, "{what} should update the plan");
+            if bumps_step {
+                assert!(step_after > step_before, "{what} should update the step");
+            } else {
+                assert_eq!(step_after, step_before, "{what} should leave the step");
+            }
+        };
+
+    check(&mut db, "update_plan", false, &|db| {
+        let request = UpdatePlanRequest {
+            title: Some("Renamed".into()),
+            ..Default::default()
+        };
+        db.update_plan(plan.id, &request).unwrap();
+    });
+    check(&mut db, "add_step", false, &|db| {
+        db.add_step(plan.id, "Added", None, None, vec![]).unwrap();
+    });
+    check(&mut db, "insert_step", false, &|db| {
+        db.insert_step(plan.id, 0, "Inserted", None, None, vec![])
+            .unwrap();
+    });
+    check(&mut db, "swap_steps", false, &|db| {
+        db.swap_steps(step.id, other.id).unwrap();
+    });
+    check(&mut db, "move_step", false, &|db| {
+        db.move_step(other.id, 0).unwrap();
+    });
+    check(&mut db, "set_step_phase", true, &|db| {
+        assert!(db.set_step_phase(step.id, Some("Build")).unwrap());
+    });
+    check(&mut db, "set_step_estimate", true, &|db| {
+        assert!(db.set_step_estimate(step.id, Some(30)).unwrap());
+    });
+    check(&mut db, "claim_step", true, &|db| {
+        db.claim_step(step.id).unwrap().unwrap();
+    });
+    check(&mut db, "update_step", true, &|db| {
+        let request = UpdateStepRequest {
+            status: Some(StepStatus::Done),
+            result: Some("Done".into()),
+            verification_required: Some(true),
+            ..Default::default()
+        };
+        db.update_step(step.id, request).unwrap();
+    });
+    check(&mut db, "verify_step", true, &|db| {
+        // A verified step was updated when it was verified
+        let step = db.verify_step(step.id, "reviewer", None).unwrap();
+        assert_eq!(
+            Some(step.updated_at),
+            step.verification.map(|v| v.verified_at)
+        );
+    });
+    check(&mut db, "remove_step", false, &|db| {
+        db.remove_step(other.id).unwrap();
+    });
+    check(&mut db, "archive_plan", false, &|db| {
+        db.archive_plan(plan.id, false, false).unwrap().unwrap();
+    });
+    check(&mut db, "unarchive_plan", false, &|db| {
+        db.unarchive_plan(plan.id).unwrap().unwrap();
+    });
+    // Writers outside this crate are covered too
+    check(&mut db, "a direct SQL update", true, &|_| {
+        let conn = rusqlite::Connection::open(temp_file.path()).unwrap();
+        conn.execute(
+            "UPDATE steps SET title = 'Edited by hand' WHERE id = ?1",
+            [step.id as i64],
+        )
+        .unwrap();
+    });
+    check(&mut db, "changing the blocked reason", true, &|_| {
+        let conn = rusqlite::Connection::open(temp_file.path()).unwrap();
+        conn.execute(
+            "UPDATE steps SET blocked_reason = 'Waiting on review' WHERE id = ?1",
+            [step.id as i64],
+        )
+        .unwrap();
+    });
+}
+
+#[test]
+fn test_historical_timestamps_are_preserved() {
+    let (temp_file, mut db) = create_test_db();
+    let plan = db.create_plan("Imported", None, None).unwrap();
+    let step = db
+        .add_step(plan.id, "Old work", None, None, vec![])
+        .unwrap();
+
+    let created: jiff::Timestamp = "2023-03-01T09:00:00Z".parse().unwrap();
+    let updated: jiff::Timestamp = "2023-03-02T17:30:00.250Z".parse().unwrap();
+    assert!(db.set_step_timestamps(step.id, created, updated).unwrap());
+    assert!(db.set_plan_timestamps(plan.id, created, updated).unwrap());
+    assert!(
+        !db.set_plan_timestamps(plan.id + 1, created, updated)
+            .unwrap()
+    );
+
+    let plan = db.get_plan(plan.id).unwrap().unwrap();
+    assert_eq!((plan.created_at, plan.updated_at), (created, updated));
+    assert_eq!(
+        (plan.steps[0].created_at, plan.steps[0].updated_at),
+        (created, updated)
+    );
+
+    // An update that sets updated_at itself keeps the value it sets
+    let conn = rusqlite::Connection::open(temp_file.path()).unwrap();
+    conn.execute(
+        "UPDATE steps SET status = 'done', result = 'Shipped', updated_at = ?1 WHERE id = ?2",
+        rusqlite::params!["2023-03-03T08:00:00.000Z", step.id as i64],
+    )
+    .unwrap();
+    let step = db.get_step(step.id).unwrap().unwrap();
+    assert_eq!(step.updated_at, "2023-03-03T08:00:00Z".parse().unwrap());
+}
+
+/// Creates a plan titled `title` in `directory` with steps of the given
+/// titles and statuses, done steps getting a result, and reads it back as
+/// an export would.
+fn exported_plan(
+    db: &mut Database,
+    title: &str,
+    directory: &str,
+    steps: &[(&str, StepStatus)],
+) -> beacon_core::Plan {
+    let plan = db.create_plan(title, None, Some(directory)).unwrap();
+    for (step_title, status) in steps {
+        let step = db
+            .add_step(plan.id, step_title, None, None, vec![])
+            .unwrap();
+        if *status == StepStatus::Todo {
+            continue;
+        }
+        db.update_step(
+            step.id,
+            UpdateStepRequest {
+                status: Some(*status),
+                result: Some(format!("{step_title} finished")),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    }
+    db.get_plan(plan.id).unwrap().unwrap()
+}
+
+#[test]
+fn test_import_plan_without_conflict_keeps_steps() {
+    let (_source_file, mut source) = create_test_db();
+    let (_temp_file, mut db) = create_test_db();
+    let root = TempDir::new().unwrap();
+    let directory = root.path().to_str().unwrap();
+    let exported = exported_plan(
+        &mut source,
+        "Release",
+        directory,
+        &[("Build", StepStatus::Done), ("Ship", StepStatus::Todo)],
+    );
+
+    let outcome = db
+        .import_plan(&exported, ConflictPolicy::default())
+        .unwrap();
+    let ImportOutcome::Created(plan) = outcome else {
+        panic!("Expected a new plan, got {outcome:?}");
+    };
+    assert_eq!(plan.title, "Release");
+    assert_eq!(plan.directory.as_deref(), Some(directory));
+    let steps: Vec<_> = plan
+        .steps
+        .iter()
+        .map(|step| (step.title.as_str(), step.status, step.result.as_deref()))
+        .collect();
+    assert_eq!(
+        steps,
+        [
+            ("Build", StepStatus::Done, Some("Build finished")),
+            ("Ship", StepStatus::Todo, None)
+        ]
+    );
+}
+
+#[test]
+fn test_import_plan_conflicts_are_skipped_by_default() {
+    let (_temp_file, mut db) = create_test_db();
+    let root = TempDir::new().unwrap();
+    let directory = root.path().to_str().unwrap();
+    let existing = exported_plan(
+        &mut db,
+        "Release",
+        directory,
+        &[("Build", StepStatus::Todo)],
+    );
+    let mut exported = existing.clone();
+    exported.steps.clear();
+
+    assert_eq!(
+        db.import_plan(&exported, ConflictPolicy::default())
+            .unwrap(),
+        ImportOutcome::Skipped {
+            existing_id: existing.id
+        }
+    );
+    assert_eq!(db.list_plans(None).unwrap().len(), 1);
+    assert_eq!(db.get_plan(existing.id).unwrap().unwrap(), existing);
+
+    // Archived plans don't conflict
+    db.archive_plan(existing.id, true, false).unwrap();
+    let outcome = db
+        .import_plan(&exported, ConflictPolicy::default())
+        .unwrap();
+    assert!(matches!(outcome, ImportOutcome::Created(_)), "{outcome:?}");
+}
+
+#[test]
+fn test_import_plan_duplicate_with_suffix() {
+    let (_temp_file, mut db) = create_test_db();
+    let root = TempDir::new().unwrap();
+    let directory = root.path().to_str().unwrap();
+    let existing = exported_plan(
+        &mut db,
+        "Release",
+        directory,
+        &[("Build", StepStatus::Done)],
+    );
+
+    let outcome = db
+        .import_plan(&existing, ConflictPolicy::DuplicateWithSuffix)
+        .unwrap();
+    let ImportOutcome::Duplicated(plan) = outcome else {
+        panic!("Expected a duplicate, got {outcome:?}");
+    };
+    let today = jiff::Zoned::now().date();
+    assert_eq!(plan.title, format!("Release (imported {today})"));
+    assert_ne!(plan.id, existing.id);
+    assert_eq!(plan.steps.len(), 1);
+    assert_eq!(db.get_plan(existing.id).unwrap().unwrap().steps.len(), 1);
+
+    // Importing it again the same day numbers the suffix
+    let titles: Vec<_> = (0..2)
+        .map(|_| {
+            match db
+                .import_plan(&existing, ConflictPolicy::DuplicateWithSuffix)
+                .unwrap()
+            {
+                ImportOutcome::Duplicated(plan) => plan.title,
+                outcome => panic!("Expected a duplicate, got {outcome:?}"),
+            }
+        })
+        .collect();
+    assert_eq!(
+        titles,
+        [
+            format!("Release (imported {today} #2)"),
+            format!("Release (imported {today} #3)")
+        ]
+    );
+}
+
+#[test]
+fn test_import_plan_keeps_historical_timestamps() {
+    let (_source_file, mut source) = create_test_db();
+    let (_temp_file, mut db) = create_test_db();
+    let root = TempDir::new().unwrap();
+    let directory = root.path().to_str().unwrap();
+    let mut exported = exported_plan(
+        &mut source,
+        "Last year",
+        directory,
+        &[
+            ("Build", StepStatus::Done),
+            ("Ship", StepStatus::InProgress),
+        ],
+    );
+    let created: jiff::Timestamp = "2023-03-01T09:00:00Z".parse().unwrap();
+    let started: jiff::Timestamp = "2023-03-02T10:00:00Z".parse().unwrap();
+    let completed: jiff::Timestamp = "2023-03-03T17:30:00Z".parse().unwrap();
+    exported.created_at = created;
+    exported.updated_at = completed;
+    for step in &mut exported.steps {
+        step.created_at = created;
+        step.updated_at = completed;
+        step.started_at = Some(started);
+    }
+    exported.steps[0].completed_at = Some(completed);
+    // Older exports may lack the completion time
+    let mut merged = exported.clone();
+    merged.steps[0].title = "Tag".to_string();
+    merged.steps[0].completed_at = None;
+
+    let now = jiff::Zoned::now();
+    let week = (
+        now.timestamp() - jiff::SignedDuration::from_hours(7 * 24),
+        now.timestamp(),
+    );
+    let momentum = |db: &Database| {
+        let completions = db.completion_times().unwrap();
+        Momentum::from_completions(completions.into_iter().map(|(_, at)| at), &now)
+    };
+    let stats_before = db.stats(Some(week)).unwrap();
+    let momentum_before = momentum(&db);
+
+    let outcome = db
+        .import_plan(&exported, ConflictPolicy::default())
+        .unwrap();
+    let ImportOutcome::Created(plan) = outcome else {
+        panic!("Expected a new plan, got {outcome:?}");
+    };
+    assert_eq!((plan.created_at, plan.updated_at), (created, completed));
+    let times: Vec<_> = plan
+        .steps
+        .iter()
+        .map(|step| {
+            (
+                step.created_at,
+                step.updated_at,
+                step.started_at,
+                step.completed_at,
+            )
+        })
+        .collect();
+    assert_eq!(
+        times,
+        [
+            (created, completed, Some(started), Some(completed)),
+            (created, completed, Some(started), None)
+        ]
+    );
+
+    // Merged steps keep theirs too
+    let outcome = db.import_plan(&merged, ConflictPolicy::Merge).unwrap();
+    let ImportOutcome::Merged { plan, added, .. } = outcome else {
+        panic!("Expected a merge, got {outcome:?}");
+    };
+    assert_eq!(added, ["Tag"]);
+    assert_eq!(plan.steps[2].completed_at, Some(completed));
+
+    assert_eq!(db.stats(Some(week)).unwrap(), stats_before);
+    assert_eq!(momentum(&db), momentum_before);
+}
+
+#[test]
+fn test_import_plan_merge_appends_missing_steps() {
+    let (_source_file, mut source) = create_test_db();
+    let (_temp_file, mut db) = create_test_db();
+    let root = TempDir::new().unwrap();
+    let directory = root.path().to_str().unwrap();
+    let existing = exported_plan(
+        &mut db,
+        "Release",
+        directory,
+        &[
+            ("Build", StepStatus::Done),
+            ("Write notes", StepStatus::Todo),
+        ],
+    );
+    let exported = exported_plan(
+        &mut source,
+        "Release",
+        directory,
+        &[
+            ("Tag", StepStatus::Done),
+            ("write  NOTES!", StepStatus::InProgress),
+            ("Announce", StepStatus::InProgress),
+            ("Build", StepStatus::Todo),
+            ("tag", StepStatus::Todo),
+        ],
+    );
+
+    let outcome = db.import_plan(&exported, ConflictPolicy::Merge).unwrap();
+    let ImportOutcome::Merged {
+        plan,
+        added,
+        skipped,
+    } = outcome
+    else {
+        panic!("Expected a merge, got {outcome:?}");
+    };
+    assert_eq!(plan.id, existing.id);
+    assert_eq!(added, ["Tag", "Announce"]);
+    assert_eq!(skipped, ["write  NOTES!", "Build", "tag"]);
+
+    // The existing steps are untouched and the new ones follow them in the
+    // imported order, keeping their statuses and results
+    let steps: Vec<_> = plan
+        .steps
+        .iter()
+        .map(|step| (step.title.as_str(), step.status, step.result.as_deref()))
+        .collect();
+    assert_eq!(
+        steps,
+        [
+            ("Build", StepStatus::Done, Some("Build finished")),
+            ("Write notes", StepStatus::Todo, None),
+            ("Tag", StepStatus::Done, Some("Tag finished")),
+            ("Announce", StepStatus::InProgress, None),
+        ]
+    );
+    let orders: Vec<_> = plan.steps.iter().map(|step| step.order).collect();
+    assert_eq!(orders, [0, 1, 2, 3]);
+
+    let notes = plan.notes.unwrap();
+    assert!(
+        notes.contains("Merged imported plan 'Release', appending 2 step(s):\n\n- Tag\n- Announce"),
+        "{notes}"
+    );
+    assert!(
+        notes.contains("3 step(s) were already in this plan and were skipped."),
+        "{notes}"
+    );
+    assert_eq!(db.list_plans(None).unwrap().len(), 1);
+
+    // Merging again adds nothing but is still recorded
+    let outcome = db.import_plan(&exported, ConflictPolicy::Merge).unwrap();
+    let ImportOutcome::Merged { plan, added, .. } = outcome else {
+        panic!("Expected a merge, got {outcome:?}");
+    };
+    assert!(added.is_empty());
+    assert_eq!(plan.steps.len(), 4);
+    assert!(
+        plan.notes
+            .unwrap()
+            .contains("Merged imported plan 'Release', adding no steps.")
+    );
+}
+
+#[test]
+fn test_step_notes() {
+    let (temp_file, mut db) = create_test_db();
+    let plan = db.create_plan("Noted", None, None).unwrap();
+    let step = db
+        .add_step(plan.id, "Load test", None, None, Vec::new())
+        .unwrap();
+    let other = db
+        .add_step(plan.id, "Other", None, None, Vec::new())
+        .unwrap();
+
+    db.add_step_note(step.id, "  The API returns 429 under load ")
+        .unwrap();
+    let second = db.add_step_note(step.id, "Backoff fixes it").unwrap();
+    assert_eq!(second.body, "Backoff fixes it");
+    db.add_step_note(other.id, "Unrelated").unwrap();
+
+    let bodies = |notes: &[beacon_core::StepNote]| {
+        notes
+            .iter()
+            .map(|note| note.body.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        bodies(&db.get_step_notes(step.id).unwrap()),
+        ["The API returns 429 under load", "Backoff fixes it"]
+    );
+    let steps = db.get_steps(plan.id).unwrap();
+    assert_eq!(bodies(&steps[0].notes).len(), 2);
+    assert_eq!(bodies(&steps[1].notes), ["Unrelated"]);
+
+    let shown = db.get_step(step.id).unwrap().unwrap().to_string();
+    let notes_at = shown.find("#### Notes").expect("Notes section");
+    assert!(shown[notes_at..].contains("The API returns 429 under load"));
+    assert!(shown.find("Backoff fixes it").unwrap() > shown.find("429").unwrap());
+
+    assert!(matches!(
+        db.add_step_note(9999, "Lost"),
+        Err(PlannerError::StepNotFound { id: 9999 })
+    ));
+    assert!(matches!(
+        db.get_step_notes(9999),
+        Err(PlannerError::StepNotFound { id: 9999 })
+    ));
+    assert!(matches!(
+        db.add_step_note(step.id, "  "),
+        Err(PlannerError::InvalidInput { .. })
+    ));
+
+    // Notes stay with a trashed step, go once it is purged, and go with
+    // their plan
+    let count_notes = || {
+        rusqlite::Connection::open(temp_file.path())
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM step_notes", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .unwrap()
+    };
+    db.remove_step(step.id).unwrap();
+    assert_eq!(count_notes(), 3);
+    db.purge_trashed_steps(jiff::Timestamp::now() + jiff::SignedDuration::from_secs(1))
+        .unwrap();
+    assert_eq!(count_notes(), 1);
+    db.delete_plan(plan.id, true).unwrap();
+    assert_eq!(count_notes(), 0);
+}
+
+/// Creates a database with the layout of the first release, before steps
+/// had a result, holding one plan with one step.
+fn create_legacy_db(path: &Path) {
+    let conn = rusqlite::Connection::open(path).expect("Failed to create legacy database");
+    conn.execute_batch(
+        "CREATE TABLE plans (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            description TEXT,
+            status TEXT NOT NULL DEFAULT 'active' CHECK(status IN ('active', 'archived')),
+            directory TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE TABLE steps (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            plan_id INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            description TEXT,
+            acceptance_criteria TEXT,
+            step_references TEXT,
+            status TEXT NOT NULL DEFAULT 'todo' CHECK(status IN ('todo', 'inprogress', 'done')),
+            step_order INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (plan_id) REFERENCES plans(id) ON DELETE CASCADE
+        );
+        INSERT INTO plans (title, status, created_at, updated_at)
+            VALUES ('Legacy plan', 'active', '2024-01-15T10:30:00Z', '2024-01-15T10:30:00Z');
+        INSERT INTO steps (plan_id, title, step_references, status, step_order, created_at, updated_at)
+            VALUES (1, 'Legacy step', 'src/lib.rs,https://example.com', 'todo', 0, '2024-01-15T10:30:00Z', '2024-01-15T10:30:00Z');",
+    )
+    .expect("Failed to create legacy schema");
+}
+
+/// Versions recorded in the schema_version table, in order.
+fn recorded_versions(path: &Path) -> Vec<i64> {
+    let conn = rusqlite::Connection::open(path).unwrap();
+    let mut stmt = conn
+        .prepare("SELECT version FROM schema_version ORDER BY version")
+        .unwrap();
+    stmt.query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap()
+}
+
+#[test]
+fn test_unique_plans_are_enforced_by_the_database() {
+    let (temp_file, mut db) = create_test_db();
+    let plan = db
+        .create_plan_unique("Deploy", None, Some("/tmp/project"))
+        .unwrap();
+
+    // A writer skipping the lookup still cannot add a second such plan
+    let conn = rusqlite::Connection::open(temp_file.path()).unwrap();
+    let insert = |status: &str, unique: bool| {
+        conn.execute(
+            "INSERT INTO plans (title, directory, status, unique_title, created_at, updated_at) \
+             VALUES ('Deploy', '/tmp/project', ?1, ?2, '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z')",
+            rusqlite::params![status, unique],
+        )
+    };
+    let error = insert("active", true).expect_err("Duplicate plan was inserted");
+    assert_eq!(
+        error.sqlite_error_code(),
+        Some(rusqlite::ErrorCode::ConstraintViolation)
+    );
+
+    // Archived plans and plans created without deduplication may share it
+    insert("archived", true).expect("Failed to insert archived plan");
+    insert("active", false).expect("Failed to insert plan");
+    assert_eq!(
+        db.create_plan_unique("Deploy", None, Some("/tmp/project"))
+            .unwrap()
+            .id,
+        plan.id
+    );
+}
+
+#[test]
+fn test_unique_plan_clashes_are_reported_as_invalid_input() {
+    let (_temp_file, mut db) = create_test_db();
+    let archived = db
+        .create_plan_unique("Deploy", None, Some("/tmp/project"))
+        .unwrap();
+    db.archive_plan(archived.id, false, false).unwrap();
+    let active = db
+        .create_plan_unique("Deploy", None, Some("/tmp/project"))
+        .unwrap();
+    assert_ne!(active.id, archived.id);
+    let other = db
+        .create_plan_unique("Release", None, Some("/tmp/project"))
+        .unwrap();
+
+    // Restoring the archived plan would make two active plans named Deploy
+    match db.unarchive_plan(archived.id).unwrap_err() {
+        PlannerError::InvalidInput { field, reason } => {
+            assert_eq!(field, "title");
+            assert_eq!(
+                reason,
+                "An active plan titled 'Deploy' already exists in '/tmp/project'"
+            );
+        }
+        error => panic!("Expected InvalidInput error, got {error:?}"),
+    }
+    assert_eq!(
+        db.get_plan(archived.id).unwrap().unwrap().status,
+        PlanStatus::Archived
+    );
+
+    // So would renaming another plan to it
+    let rename = UpdatePlanRequest {
+        title: Some("Deploy".into()),
+        ..Default::default()
+    };
+    match db.update_plan(other.id, &rename).unwrap_err() {
+        PlannerError::InvalidInput { field, reason } => {
+            assert_eq!(field, "title");
+            assert!(reason.contains("'Deploy' already exists"));
+        }
+        error => panic!("Expected InvalidInput error, got {error:?}"),
+    }
+    assert_eq!(db.get_plan(other.id).unwrap().unwrap().title, "Release");
+
+    // Once the active one is archived, the other can be restored
+    db.archive_plan(active.id, false, false).unwrap();
+    assert!(db.unarchive_plan(archived.id).unwrap().is_some());
+}
+
+#[test]
+fn test_legacy_database_migrates_to_latest_version() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("legacy.db");
+    create_legacy_db(&path);
+
+    let mut db = Database::new(&path).expect("Failed to migrate legacy database");
+    assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+    assert_eq!(
+        recorded_versions(&path),
+        (1..=SCHEMA_VERSION).collect::<Vec<_>>()
+    );
+
+    // The existing rows read back with the columns added since
+    let plan = db.get_plan(1).unwrap().expect("Legacy plan not found");
+    assert_eq!(plan.title, "Legacy plan");
+
+    // Deduplicated plans can be created once the index exists
+    let unique = db
+        .create_plan_unique("Deduplicated", None, Some("/tmp/legacy"))
+        .unwrap();
+    assert_eq!(
+        db.create_plan_unique("Deduplicated", None, Some("/tmp/legacy"))
+            .unwrap()
+            .id,
+        unique.id
+    );
+    assert!(plan.due_date.is_none());
+    assert!(plan.notes.is_none());
+    let steps = db.get_steps(1).unwrap();
+    assert_eq!(steps.len(), 1);
+    assert!(steps[0].result.is_none());
+    assert!(steps[0].started_at.is_none());
+    assert_eq!(
+        steps[0].references,
+        vec!["src/lib.rs".into(), "https://example.com".into()]
+    );
+
+    // Columns added by migrations are usable, and the status check allows
+    // the statuses added since
+    db.update_step(
+        steps[0].id,
+        UpdateStepRequest {
+            status: Some(StepStatus::Blocked),
+            blocked_reason: Some("Waiting on review".to_string()),
+            ..Default::default()
+        },
+    )
+    .expect("Failed to block legacy step");
+    let step = db.get_step(steps[0].id).unwrap().unwrap();
+    assert_eq!(step.status, StepStatus::Blocked);
+    assert_eq!(step.blocked_reason.as_deref(), Some("Waiting on review"));
+    assert_eq!(step.references.len(), 2);
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let integrity: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(integrity, "ok");
+    assert!(
+        conn.execute("UPDATE steps SET status = 'stuck' WHERE id = 1", [])
+            .is_err()
+    );
+    drop(conn);
+
+    db.update_step(
+        steps[0].id,
+        UpdateStepRequest {
+            status: Some(StepStatus::Done),
+            result: Some("Migrated".to_string()),
+            ..Default::default()
+        },
+    )
+    .expect("Failed to complete legacy step");
+    let step = db.get_step(steps[0].id).unwrap().unwrap();
+    assert_eq!(step.result.as_deref(), Some("Migrated"));
+    assert!(step.completed_at.is_some());
+}
+
+#[test]
+fn test_reopening_database_is_idempotent() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("test.db");
+
+    let mut db = Database::new(&path).expect("Failed to create database");
+    let plan = db.create_plan("Kept", None, None).unwrap();
+    drop(db);
+    assert_eq!(recorded_versions(&path), [SCHEMA_VERSION]);
+
+    for _ in 0..2 {
+        let db = Database::new(&path).expect("Failed to reopen database");
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+        assert_eq!(db.get_plan(plan.id).unwrap().unwrap().title, "Kept");
+    }
+    assert_eq!(recorded_versions(&path), [SCHEMA_VERSION]);
+
+    // Databases that kept their version in user_version record it once
+    let legacy = temp_dir.path().join("versioned.db");
+    drop(Database::new(&legacy).unwrap());
+    let conn = rusqlite::Connection::open(&legacy).unwrap();
+    conn.execute_batch(&format!(
+        "DROP TABLE schema_version; PRAGMA user_version = {SCHEMA_VERSION};"
+    ))
+    .unwrap();
+    drop(conn);
+    Database::new(&legacy).expect("Failed to reopen database");
+    Database::new(&legacy).expect("Failed to reopen database");
+    assert_eq!(recorded_versions(&legacy), [SCHEMA_VERSION]);
+}
+
+#[test]
+fn test_newer_schema_version_is_refused() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("future.db");
+    drop(Database::new(&path).unwrap());
+
+    let newer = SCHEMA_VERSION + 1;
+    rusqlite::Connection::open(&path)
+        .unwrap()
+        .execute("INSERT INTO schema_version (version) VALUES (?1)", [newer])
+        .unwrap();
+
+    for result in [Database::new(&path), Database::open_read_only(&path)] {
+        match result {
+            Err(PlannerError::SchemaVersion { found, supported }) => {
+                assert_eq!(found, newer);
+                assert_eq!(supported, SCHEMA_VERSION);
+            }
+            Err(other) => panic!("Expected a schema version error, got {other:?}"),
+            Ok(_) => panic!("Expected a schema version error"),
+        }
+    }
+}
+
+#[test]
+fn test_apply_template() {
+    let (_temp_file, mut db) = create_test_db();
+
+    let steps = vec![
+        TemplateStep {
+            title: "Bump version".to_string(),
+            ..Default::default()
+        },
+        TemplateStep {
+            title: "Tag release".to_string(),
+            description: Some("Push the tag".to_string()),
+            acceptance_criteria: Some("Tag is on the remote".to_string()),
+        },
+    ];
+    let template = db.create_template("Release", &steps).unwrap();
+    assert_eq!(template.steps, steps);
+    assert!(matches!(
+        db.create_template("release", &steps),
+        Err(PlannerError::InvalidInput { .. })
+    ));
+
+    let plan = db.create_plan("Release 2.0", None, None).unwrap();
+    db.add_step(plan.id, "Write notes", None, None, vec![])
+        .unwrap();
+    let added = db.apply_template(plan.id, "RELEASE").unwrap();
+    assert_eq!(added.len(), 2);
+    assert_eq!(added[1].description.as_deref(), Some("Push the tag"));
+
+    let plan = db.get_plan(plan.id).unwrap().unwrap();
+    let titles: Vec<_> = plan.steps.iter().map(|step| step.title.as_str()).collect();
+    assert_eq!(titles, ["Write notes", "Bump version", "Tag release"]);
+    assert_eq!(db.list_templates().unwrap(), vec![template]);
+
+    assert!(matches!(
+        db.apply_template(plan.id, "Missing"),
+        Err(PlannerError::TemplateNotFound { .. })
+    ));
+    assert!(matches!(
+        db.apply_template(9999, "Release"),
+        Err(PlannerError::PlanNotFound { id: 9999 })
+    ));
+
+    db.archive_plan(plan.id, false, false).unwrap();
+    assert!(matches!(
+        db.apply_template(plan.id, "Release"),
+        Err(PlannerError::InvalidInput { .. })
+    ));
+    assert_eq!(db.get_plan(plan.id).unwrap().unwrap().steps.len(), 3);
+}
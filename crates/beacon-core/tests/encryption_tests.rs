@@ -0,0 +1,95 @@
+//! Integration tests for SQLCipher database encryption.
+
+#![cfg(feature = "encryption")]
+
+use beacon_core::{
+    PlannerBuilder, PlannerError,
+    params::{CreatePlan, Id},
+};
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_encrypted_database_reopen() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("encrypted.db");
+
+    // Create an encrypted database with a plan in it, then drop the planner
+    let plan_id = {
+        let planner = PlannerBuilder::new()
+            .with_database_path(Some(&db_path))
+            .with_encryption_key("correct horse")
+            .build()
+            .await
+            .expect("Failed to create encrypted planner");
+
+        planner
+            .create_plan(&CreatePlan {
+                title: "Secret Plan".to_string(),
+                ..Default::default()
+            })
+            .await
+            .expect("Failed to create plan")
+            .id
+    };
+
+    // The file must not be readable as plain SQLite
+    let contents = std::fs::read(&db_path).expect("Failed to read database file");
+    assert!(!contents.starts_with(b"SQLite format 3"));
+
+    // Reopening with the correct key sees the data
+    let planner = PlannerBuilder::new()
+        .with_database_path(Some(&db_path))
+        .with_encryption_key("correct horse")
+        .build()
+        .await
+        .expect("Failed to reopen with correct key");
+    let plan = planner
+        .get_plan(&Id { id: plan_id })
+        .await
+        .expect("Failed to get plan")
+        .expect("Plan should exist");
+    assert_eq!(plan.title, "Secret Plan");
+
+    // A wrong key is reported as such
+    let result = PlannerBuilder::new()
+        .with_database_path(Some(&db_path))
+        .with_encryption_key("battery staple")
+        .build()
+        .await;
+    match result {
+        Err(PlannerError::Encryption { message }) => {
+            assert!(message.contains("Wrong encryption key"), "got: {message}");
+        }
+        Err(e) => panic!("Expected encryption error, got {e:?}"),
+        Ok(_) => panic!("Expected encryption error, got a planner"),
+    }
+
+    // So is a missing key
+    let result = PlannerBuilder::new()
+        .with_database_path(Some(&db_path))
+        .build()
+        .await;
+    match result {
+        Err(PlannerError::Encryption { message }) => {
+            assert!(message.contains("key is required"), "got: {message}");
+        }
+        Err(e) => panic!("Expected encryption error, got {e:?}"),
+        Ok(_) => panic!("Expected encryption error, got a planner"),
+    }
+}
+
+#[tokio::test]
+async fn test_empty_encryption_key_rejected() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+
+    let result = PlannerBuilder::new()
+        .with_database_path(Some(temp_dir.path().join("empty.db")))
+        .with_encryption_key("")
+        .build()
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(PlannerError::InvalidInput { ref field, .. }) if field == "encryption_key"
+    ));
+}
@@ -1,13 +1,35 @@
 //! Integration tests for the planner module.
 
+use std::{
+    fmt,
+    path::Path,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
 use beacon_core::{
-    PlannerBuilder,
+    ClaimOutcome, CreateStepRequest, Database, DuplicateScope, IdHint, ListedSteps,
+    MAX_DESCRIPTION_LEN, MAX_TITLE_LEN, MergeOptions, PlanFilter, PlanStatus, PlanSummary,
+    PlannerBuilder, PlannerError, PromptBadge, ReferenceKind, SettingKey, SettingSource,
+    StatusLabels, StepChange, StepOutline, StepReference, StepStatus, StepTransition, StepsByPlan,
+    Trend, UpdateStepRequest,
     params::{
-        CreatePlan, DeletePlan, Id, InsertStep, ListPlans, SearchPlans, StepCreate, SwapSteps,
-        UpdateStep,
+        ArchivePlan, CreatePlan, DeletePlan, Id, InsertStep, ListPlans, ListSteps, MergePlans,
+        SearchPlans, SetConfig, SetStepPhase, StepCreate, StepDependency, SwapSteps, UpdatePlan,
+        UpdateStep, VerifyStep,
     },
+    quick::{quick_badge, quick_momentum, quick_summaries},
 };
+use jiff::Zoned;
 use tempfile::TempDir;
+use tracing::{
+    Event, Level, Metadata, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id as SpanId, Record},
+};
 
 #[tokio::test]
 async fn test_list_plans_summary_active() {
@@ -19,6 +41,7 @@ async fn test_list_plans_summary_active() {
             title: "Test Plan".to_string(),
             description: Some("Test Description".to_string()),
             directory: None,
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
@@ -30,25 +53,31 @@ async fn test_list_plans_summary_active() {
             title: "Test Step".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: vec![],
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
 
     // Test list_plans_summary for active plans
     let summaries = planner
-        .list_plans_summary(&ListPlans { archived: false })
+        .list_plans_summary(&ListPlans {
+            archived: false,
+            ..Default::default()
+        })
         .await
         .expect("Failed to list plan summaries");
 
-    assert_eq!(summaries.0.len(), 1);
-    assert_eq!(summaries.0[0].title, "Test Plan");
+    assert_eq!(summaries.items.len(), 1);
+    assert_eq!(summaries.items[0].title, "Test Plan");
     assert_eq!(
-        summaries.0[0].description,
+        summaries.items[0].description,
         Some("Test Description".to_string())
     );
-    assert_eq!(summaries.0[0].total_steps, 1);
-    assert_eq!(summaries.0[0].completed_steps, 0);
+    assert_eq!(summaries.items[0].total_steps, 1);
+    assert_eq!(summaries.items[0].completed_steps, 0);
 }
 
 #[tokio::test]
@@ -61,12 +90,16 @@ async fn test_list_plans_summary_archived() {
             title: "Archived Plan".to_string(),
             description: None,
             directory: None,
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
 
     let archived_plan = planner
-        .archive_plan(&Id { id: plan.id })
+        .archive_plan(&ArchivePlan {
+            id: plan.id,
+            force: false,
+        })
         .await
         .expect("Failed to archive plan")
         .expect("Plan should exist");
@@ -74,19 +107,116 @@ async fn test_list_plans_summary_archived() {
 
     // Test list_plans_summary for archived plans
     let summaries = planner
-        .list_plans_summary(&ListPlans { archived: true })
+        .list_plans_summary(&ListPlans {
+            archived: true,
+            ..Default::default()
+        })
         .await
         .expect("Failed to list archived plan summaries");
 
-    assert_eq!(summaries.0.len(), 1);
-    assert_eq!(summaries.0[0].title, "Archived Plan");
+    assert_eq!(summaries.items.len(), 1);
+    assert_eq!(summaries.items[0].title, "Archived Plan");
 
     // Verify active plans is empty
     let active_summaries = planner
-        .list_plans_summary(&ListPlans { archived: false })
+        .list_plans_summary(&ListPlans {
+            archived: false,
+            ..Default::default()
+        })
         .await
         .expect("Failed to list active plans");
-    assert_eq!(active_summaries.0.len(), 0);
+    assert_eq!(active_summaries.items.len(), 0);
+}
+
+#[tokio::test]
+async fn test_list_plans_summary_by_completion() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let complete = create_plan_with_steps(&planner, "Complete", None, 2, 0).await;
+    let incomplete = create_plan_with_steps(&planner, "Incomplete", None, 1, 1).await;
+    let empty = create_plan_with_steps(&planner, "Empty", None, 0, 0).await;
+    let archived = create_plan_with_steps(&planner, "Archived", None, 0, 1).await;
+    planner
+        .archive_plan(&ArchivePlan {
+            id: archived,
+            force: false,
+        })
+        .await
+        .expect("Failed to archive plan");
+
+    let list = |completion: &str, archived: bool| {
+        let params = ListPlans {
+            archived,
+            completion: Some(completion.to_string()),
+            ..Default::default()
+        };
+        let planner = &planner;
+        async move {
+            let summaries = planner.list_plans_summary(&params).await?;
+            Ok::<_, PlannerError>(summaries.items.iter().map(|s| s.id).collect::<Vec<_>>())
+        }
+    };
+
+    assert_eq!(list("complete", false).await.unwrap(), vec![complete]);
+    assert_eq!(list("Incomplete", false).await.unwrap(), vec![incomplete]);
+    assert_eq!(list("empty", false).await.unwrap(), vec![empty]);
+    assert_eq!(list("incomplete", true).await.unwrap(), vec![archived]);
+    assert!(list("complete", true).await.unwrap().is_empty());
+
+    match list("finished", false).await {
+        Err(PlannerError::InvalidInput { field, reason }) => {
+            assert_eq!(field, "completion");
+            assert!(reason.contains("'complete', 'incomplete', or 'empty'"));
+        }
+        other => panic!("Expected an invalid completion to be rejected, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_list_plans_summary_pages() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let mut ids = Vec::new();
+    for title in ["First", "Second", "Third"] {
+        ids.push(create_plan_with_steps(&planner, title, None, 1, 0).await);
+    }
+
+    let page = |limit: Option<u32>, offset: Option<u32>| ListPlans {
+        limit,
+        offset,
+        ..Default::default()
+    };
+
+    let listed = planner
+        .list_plans_summary(&page(Some(2), None))
+        .await
+        .unwrap();
+    let listed_ids: Vec<u64> = listed.items.iter().map(|s| s.id).collect();
+    assert_eq!(listed_ids, vec![ids[2], ids[1]]);
+    assert_eq!((listed.total, listed.offset), (3, 0));
+    assert!(listed.is_partial());
+    assert!(listed.to_string().contains("Showing 2 of 3 plans."));
+
+    let (explained, explanation) = planner
+        .explain_list_plans(&page(Some(2), Some(2)))
+        .await
+        .unwrap();
+    let explained_ids: Vec<u64> = explained.items.iter().map(|s| s.id).collect();
+    assert_eq!(explained_ids, vec![ids[0]]);
+    assert_eq!(explained.total, 3);
+    assert_eq!(explanation.stages.last().unwrap().plans, 3);
+
+    // An offset past the end is an empty page rather than an error
+    let beyond = planner
+        .list_plans_summary(&page(None, Some(5)))
+        .await
+        .unwrap();
+    assert!(beyond.items.is_empty());
+    assert_eq!((beyond.total, beyond.offset), (3, 5));
+
+    // Without paging every plan is listed and no count is shown
+    let all = planner.list_plans_summary(&page(None, None)).await.unwrap();
+    assert_eq!(all.items.len(), 3);
+    assert!(!all.to_string().contains("Showing"));
 }
 
 #[tokio::test]
@@ -99,6 +229,7 @@ async fn test_show_plan_with_steps() {
             title: "Plan with Steps".to_string(),
             description: None,
             directory: None,
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
@@ -109,7 +240,10 @@ async fn test_show_plan_with_steps() {
             title: "Step 1".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: vec![],
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
@@ -120,7 +254,10 @@ async fn test_show_plan_with_steps() {
             title: "Step 2".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: vec![],
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
@@ -161,13 +298,17 @@ async fn test_archive_plan() {
             title: "To Archive".to_string(),
             description: None,
             directory: None,
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
 
     // Test archive_plan
     let archived_plan = planner
-        .archive_plan(&Id { id: plan.id })
+        .archive_plan(&ArchivePlan {
+            id: plan.id,
+            force: false,
+        })
         .await
         .expect("Failed to archive plan")
         .expect("Plan should exist");
@@ -189,13 +330,75 @@ async fn test_archive_plan_not_found() {
 
     // Test non-existent plan
     let result = planner
-        .archive_plan(&Id { id: 999 })
+        .archive_plan(&ArchivePlan {
+            id: 999,
+            force: false,
+        })
         .await
         .expect("Should not fail on non-existent plan");
 
     assert!(result.is_none());
 }
 
+#[tokio::test]
+async fn test_archive_plan_checked() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let archive = |id: u64, force: bool| {
+        let planner = planner.clone();
+        async move {
+            planner
+                .archive_plan_checked(&ArchivePlan { id, force }, true)
+                .await
+        }
+    };
+
+    // Fully done and empty plans are archived
+    let done = create_plan_with_steps(&planner, "Done", None, 2, 0).await;
+    let empty = create_plan_with_steps(&planner, "Empty", None, 0, 0).await;
+    for id in [done, empty] {
+        let plan = archive(id, false).await.unwrap().unwrap();
+        assert_eq!(plan.status, PlanStatus::Archived);
+    }
+
+    // A partially done plan is refused with its counts
+    let partial = create_plan_with_steps(&planner, "Partial", None, 1, 3).await;
+    let steps = planner.get_steps(&Id { id: partial }).await.unwrap();
+    planner.claim_step(&Id { id: steps[1].id }).await.unwrap();
+    assert!(matches!(
+        archive(partial, false).await,
+        Err(PlannerError::PlanHasActiveSteps {
+            id,
+            in_progress: 1,
+            todo: 2,
+        }) if id == partial
+    ));
+    let plan = planner
+        .get_plan(&Id { id: partial })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(plan.status, PlanStatus::Active);
+
+    // Forcing archives it anyway, and archiving without the check never
+    // refuses
+    let plan = archive(partial, true).await.unwrap().unwrap();
+    assert_eq!(plan.status, PlanStatus::Archived);
+    let unchecked = create_plan_with_steps(&planner, "Unchecked", None, 0, 1).await;
+    let plan = planner
+        .archive_plan(&ArchivePlan {
+            id: unchecked,
+            force: false,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(plan.status, PlanStatus::Archived);
+
+    // Archiving an archived plan again isn't refused
+    assert!(archive(unchecked, false).await.unwrap().is_some());
+    assert!(archive(999, false).await.unwrap().is_none());
+}
+
 #[tokio::test]
 async fn test_unarchive_plan() {
     let (_temp_dir, planner) = create_test_planner().await;
@@ -206,12 +409,16 @@ async fn test_unarchive_plan() {
             title: "To Unarchive".to_string(),
             description: None,
             directory: None,
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
 
     let archived_plan = planner
-        .archive_plan(&Id { id: plan.id })
+        .archive_plan(&ArchivePlan {
+            id: plan.id,
+            force: false,
+        })
         .await
         .expect("Failed to archive plan")
         .expect("Plan should exist");
@@ -245,6 +452,7 @@ async fn test_delete_plan_with_confirmation() {
             title: "To Delete".to_string(),
             description: None,
             directory: None,
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
@@ -255,16 +463,44 @@ async fn test_delete_plan_with_confirmation() {
             title: "Step to Delete".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: vec![],
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
 
+    // A plan with a step that is not done is only deleted when forced
+    let error = planner
+        .delete_plan(&DeletePlan {
+            id: plan.id,
+            confirmed: true,
+            force: false,
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        PlannerError::PlanNotEmpty {
+            id,
+            pending_steps: 1,
+        } if id == plan.id
+    ));
+    assert!(
+        planner
+            .get_plan(&Id { id: plan.id })
+            .await
+            .unwrap()
+            .is_some()
+    );
+
     // Test delete_plan
     let deleted_plan = planner
         .delete_plan(&DeletePlan {
             id: plan.id,
             confirmed: true,
+            force: true,
         })
         .await
         .expect("Failed to delete plan")
@@ -297,6 +533,7 @@ async fn test_delete_plan_confirmation_required() {
             title: "Test Plan".to_string(),
             description: Some("Test description".to_string()),
             directory: None,
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
@@ -306,6 +543,7 @@ async fn test_delete_plan_confirmation_required() {
         .delete_plan(&DeletePlan {
             id: plan.id,
             confirmed: false,
+            force: false,
         })
         .await;
 
@@ -333,6 +571,7 @@ async fn test_search_plans_summary() {
             title: "Plan in Test Dir".to_string(),
             description: None,
             directory: Some(test_dir.to_string()),
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
@@ -342,6 +581,7 @@ async fn test_search_plans_summary() {
             title: "Plan in Other Dir".to_string(),
             description: None,
             directory: Some("/other/directory".to_string()),
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
@@ -353,7 +593,10 @@ async fn test_search_plans_summary() {
             title: "Test Step".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: vec![],
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
@@ -363,6 +606,7 @@ async fn test_search_plans_summary() {
         .search_plans_summary(&SearchPlans {
             directory: test_dir.to_string(),
             archived: false,
+            ..Default::default()
         })
         .await
         .expect("Failed to search plans");
@@ -383,12 +627,16 @@ async fn test_search_plans_summary_archived() {
             title: "Archived Plan in Dir".to_string(),
             description: None,
             directory: Some(test_dir.to_string()),
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
 
     let archived_plan = planner
-        .archive_plan(&Id { id: plan.id })
+        .archive_plan(&ArchivePlan {
+            id: plan.id,
+            force: false,
+        })
         .await
         .expect("Failed to archive plan")
         .expect("Plan should exist");
@@ -399,6 +647,7 @@ async fn test_search_plans_summary_archived() {
         .search_plans_summary(&SearchPlans {
             directory: test_dir.to_string(),
             archived: true,
+            ..Default::default()
         })
         .await
         .expect("Failed to search archived plans");
@@ -411,12 +660,262 @@ async fn test_search_plans_summary_archived() {
         .search_plans_summary(&SearchPlans {
             directory: test_dir.to_string(),
             archived: false,
+            ..Default::default()
         })
         .await
         .expect("Failed to search active plans");
     assert_eq!(active_summaries.0.len(), 0);
 }
 
+#[tokio::test]
+async fn test_search_plans_by_directory_boundaries() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    for directory in [
+        "/home/user/proj",
+        "/home/user/proj/sub",
+        "/home/user/project-x",
+    ] {
+        planner
+            .create_plan(&CreatePlan {
+                title: directory.to_string(),
+                directory: Some(directory.to_string()),
+                ..Default::default()
+            })
+            .await
+            .expect("Failed to create plan");
+    }
+
+    let search = |directory: &str, include_subdirectories: bool| {
+        let params = SearchPlans {
+            directory: directory.to_string(),
+            include_subdirectories,
+            ..Default::default()
+        };
+        let planner = &planner;
+        async move {
+            let mut titles: Vec<String> = planner
+                .search_plans_by_directory(&params)
+                .await
+                .expect("Failed to search plans")
+                .into_iter()
+                .map(|plan| plan.title)
+                .collect();
+            titles.sort();
+            titles
+        }
+    };
+
+    // Exact directory and subdirectories, not a sibling sharing the prefix
+    assert_eq!(
+        search("/home/user/proj", true).await,
+        ["/home/user/proj", "/home/user/proj/sub"]
+    );
+    // A trailing separator or "." component makes no difference
+    assert_eq!(
+        search("/home/user/./proj/", true).await,
+        ["/home/user/proj", "/home/user/proj/sub"]
+    );
+    // Only the exact directory
+    assert_eq!(search("/home/user/proj", false).await, ["/home/user/proj"]);
+    // A wildcard in the path is matched literally
+    assert!(search("/home/user/pro_", true).await.is_empty());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_search_plans_through_symlinks() {
+    let (temp_dir, planner) = create_test_planner().await;
+    let real = temp_dir.path().join("code/app");
+    std::fs::create_dir_all(&real).unwrap();
+    let real = real.canonicalize().unwrap();
+    let link = temp_dir.path().join("app-link");
+    std::os::unix::fs::symlink(&real, &link).unwrap();
+    let real = real.to_str().unwrap().to_string();
+    let link = link.to_str().unwrap().to_string();
+
+    let create = |planner: &beacon_core::Planner, title: &str, directory: &str| {
+        let params = CreatePlan {
+            title: title.to_string(),
+            directory: Some(directory.to_string()),
+            ..Default::default()
+        };
+        let planner = planner.clone();
+        async move { planner.create_plan(&params).await.unwrap() }
+    };
+    let search = |planner: &beacon_core::Planner, directory: &str| {
+        let params = SearchPlans {
+            directory: directory.to_string(),
+            ..Default::default()
+        };
+        let planner = planner.clone();
+        async move {
+            let mut titles: Vec<String> = planner
+                .search_plans_by_directory(&params)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|plan| plan.title)
+                .collect();
+            titles.sort();
+            titles
+        }
+    };
+
+    // A plan created through the symlink is stored under the real path
+    let via_link = create(&planner, "Via link", &link).await;
+    assert_eq!(via_link.directory.as_deref(), Some(real.as_str()));
+    create(&planner, "Via real path", &real).await;
+    assert_eq!(search(&planner, &real).await, ["Via link", "Via real path"]);
+    assert_eq!(search(&planner, &link).await, ["Via link", "Via real path"]);
+
+    // Raw paths are kept as given when canonicalization is disabled
+    let raw = PlannerBuilder::new()
+        .with_database_path(Some(temp_dir.path().join("raw.db")))
+        .with_path_canonicalization(false)
+        .build()
+        .await
+        .unwrap();
+    let via_link = create(&raw, "Via link", &link).await;
+    assert_eq!(via_link.directory.as_deref(), Some(link.as_str()));
+    assert!(search(&raw, &real).await.is_empty());
+    assert_eq!(search(&raw, &link).await, ["Via link"]);
+}
+
+#[tokio::test]
+async fn test_reassign_directory() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let mut ids = Vec::new();
+    for directory in [
+        "/old/repo",
+        "/old/repo/crates/core",
+        "/old/repo-docs",
+        "/elsewhere",
+    ] {
+        let plan = planner
+            .create_plan(&CreatePlan {
+                title: directory.to_string(),
+                directory: Some(directory.to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        ids.push(plan.id);
+    }
+    planner
+        .archive_plan(&ArchivePlan {
+            id: ids[1],
+            force: false,
+        })
+        .await
+        .unwrap();
+    let directory = async |id: u64| {
+        planner
+            .get_plan(&Id { id })
+            .await
+            .unwrap()
+            .unwrap()
+            .directory
+            .unwrap()
+    };
+
+    // The directory and those below it move, archived plans included, but
+    // not siblings sharing a prefix
+    let moved = planner
+        .reassign_directory("/old/repo/", "/new/place/repo")
+        .await
+        .unwrap();
+    assert_eq!(moved, 2);
+    assert_eq!(directory(ids[0]).await, "/new/place/repo");
+    assert_eq!(directory(ids[1]).await, "/new/place/repo/crates/core");
+    assert_eq!(directory(ids[2]).await, "/old/repo-docs");
+    assert_eq!(directory(ids[3]).await, "/elsewhere");
+
+    // Nothing left to move is not an error
+    assert_eq!(
+        planner
+            .reassign_directory("/old/repo", "/new/place/repo")
+            .await
+            .unwrap(),
+        0
+    );
+
+    // Overlapping directories are refused
+    for (from, to) in [
+        ("/new/place/repo", "/new/place/repo"),
+        ("/new/place/repo", "/new/place/repo/nested"),
+        ("/new/place/repo", "/new"),
+        ("/new/place/repo", " "),
+    ] {
+        assert!(
+            matches!(
+                planner.reassign_directory(from, to).await,
+                Err(PlannerError::InvalidInput { ref field, .. }) if field == "to"
+            ),
+            "{from} -> {to}"
+        );
+    }
+    assert_eq!(directory(ids[0]).await, "/new/place/repo");
+}
+
+#[tokio::test]
+async fn test_plan_notes() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Notes".to_string(),
+            description: Some("The goal".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let id = Id { id: plan.id };
+    assert!(plan.notes.is_none());
+
+    planner
+        .append_plan_note(&id, "Chose SQLite over files")
+        .await
+        .unwrap();
+    let plan = planner
+        .append_plan_note(&id, "  Open question: migrations?  ")
+        .await
+        .unwrap();
+    let notes = plan.notes.as_deref().unwrap();
+    assert!(notes.starts_with("### "));
+    assert_eq!(notes.matches("### ").count(), 2);
+    assert!(
+        notes.find("Chose SQLite over files").unwrap()
+            < notes.find("Open question: migrations?").unwrap()
+    );
+
+    // Notes follow the description in the plan, but not in summaries
+    let shown = plan.to_string();
+    assert!(shown.find("The goal").unwrap() < shown.find("## Notes").unwrap());
+    let summaries = planner.list_plans(None).await.unwrap();
+    assert!(
+        !serde_json::to_string(&summaries)
+            .unwrap()
+            .contains("SQLite")
+    );
+
+    let plan = planner.set_plan_notes(&id, "Start over").await.unwrap();
+    assert_eq!(plan.notes.as_deref(), Some("Start over"));
+    let plan = planner.set_plan_notes(&id, "").await.unwrap();
+    assert!(plan.notes.is_none());
+
+    assert!(matches!(
+        planner.append_plan_note(&id, " ").await,
+        Err(PlannerError::InvalidInput { ref field, .. }) if field == "text"
+    ));
+    assert!(matches!(
+        planner.append_plan_note(&Id { id: 999 }, "Lost").await,
+        Err(PlannerError::PlanNotFound { id: 999 })
+    ));
+    assert!(matches!(
+        planner.set_plan_notes(&Id { id: 999 }, "Lost").await,
+        Err(PlannerError::PlanNotFound { id: 999 })
+    ));
+}
+
 #[tokio::test]
 async fn test_update_step_validated() {
     let (_temp_dir, planner) = create_test_planner().await;
@@ -427,6 +926,7 @@ async fn test_update_step_validated() {
             title: "Update Test".to_string(),
             description: None,
             directory: None,
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
@@ -437,7 +937,10 @@ async fn test_update_step_validated() {
             title: "Step to Update".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: vec![],
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
@@ -450,8 +953,12 @@ async fn test_update_step_validated() {
             title: Some("Updated Step Title".to_string()),
             description: Some("Updated description".to_string()),
             acceptance_criteria: None,
+            expected_output: None,
             references: None,
             result: Some("Step completed successfully".to_string()),
+            clear_result: false,
+            blocked_reason: None,
+            verification_required: None,
         })
         .await
         .expect("Failed to update step")
@@ -468,6 +975,141 @@ async fn test_update_step_validated() {
     );
 }
 
+#[tokio::test]
+async fn test_update_step_with_changes() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan_id = create_plan_with_steps(&planner, "Changes", None, 0, 1).await;
+    let step = planner
+        .get_steps(&Id { id: plan_id })
+        .await
+        .expect("Failed to get steps")
+        .into_inner()
+        .remove(0);
+
+    let update = UpdateStep {
+        id: step.id,
+        status: Some("done".to_string()),
+        title: Some(step.title.clone()),
+        description: Some("Now described".to_string()),
+        result: Some("Finished".to_string()),
+        ..Default::default()
+    };
+    let result = planner
+        .update_step_with_changes(&update)
+        .await
+        .expect("Failed to update step")
+        .expect("Step should exist");
+
+    // The title was set to the value it already had, so it didn't change
+    assert_eq!(
+        result.changes,
+        vec![
+            StepChange::StatusChanged {
+                from: StepStatus::Todo,
+                to: StepStatus::Done,
+            },
+            StepChange::DescriptionChanged {
+                from: None,
+                to: Some("Now described".to_string()),
+            },
+            StepChange::ResultChanged {
+                from: None,
+                to: Some("Finished".to_string()),
+            },
+        ]
+    );
+    let output = result.to_string();
+    assert!(output.contains("- status: Todo → Done"));
+    assert!(output.contains("- description: (none) → 'Now described'"));
+
+    // Completing the step again is refused, repeating the rest changes
+    // nothing
+    let Err(error) = planner.update_step_with_changes(&update).await else {
+        panic!("A done step should not be completed again");
+    };
+    assert!(error.to_string().contains("is already done"));
+    let update = UpdateStep {
+        status: None,
+        ..update
+    };
+    let result = planner
+        .update_step_with_changes(&update)
+        .await
+        .expect("Failed to update step")
+        .expect("Step should exist");
+    assert!(result.changes.is_empty());
+    assert!(
+        result
+            .to_string()
+            .starts_with(&format!("Nothing changed in step with ID: {}", step.id))
+    );
+}
+
+#[tokio::test]
+async fn test_step_time_tracking() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan_id = create_plan_with_steps(&planner, "Timed", None, 1, 1).await;
+    let steps = planner
+        .get_steps(&Id { id: plan_id })
+        .await
+        .expect("Failed to get steps")
+        .into_inner();
+
+    // Steps created done are completed but were never started
+    assert!(steps[0].started_at.is_none());
+    assert!(steps[0].completed_at.is_some());
+    assert!(steps[0].duration().is_none());
+
+    let step = &steps[1];
+    assert!(step.started_at.is_none());
+    assert!(step.completed_at.is_none());
+
+    let claimed = planner
+        .claim_step(&Id { id: step.id })
+        .await
+        .expect("Failed to claim step")
+        .expect("Step should be claimed");
+    let started_at = claimed.started_at.expect("Claiming should start the step");
+    assert!(claimed.completed_at.is_none());
+
+    let done = UpdateStep {
+        id: step.id,
+        status: Some("done".to_string()),
+        result: Some("Finished".to_string()),
+        ..Default::default()
+    };
+    let completed = planner
+        .update_step_validated(&done)
+        .await
+        .expect("Failed to complete step")
+        .expect("Step should exist");
+    assert_eq!(completed.started_at, Some(started_at));
+    let completed_at = completed.completed_at.expect("Step should be completed");
+    assert!(completed_at >= started_at);
+    assert!(completed.duration().is_some());
+
+    // Reopening clears the completion but keeps the first start
+    let reopen = UpdateStep {
+        id: step.id,
+        status: Some("todo".to_string()),
+        ..Default::default()
+    };
+    let reopened = planner
+        .update_step_validated(&reopen)
+        .await
+        .expect("Failed to reopen step")
+        .expect("Step should exist");
+    assert!(reopened.completed_at.is_none());
+    assert_eq!(reopened.started_at, Some(started_at));
+
+    let reclaimed = planner
+        .claim_step(&Id { id: step.id })
+        .await
+        .expect("Failed to claim step")
+        .expect("Step should be claimed");
+    assert_eq!(reclaimed.started_at, Some(started_at));
+}
+
 #[tokio::test]
 async fn test_update_step_validated_not_found() {
     let (_temp_dir, planner) = create_test_planner().await;
@@ -480,8 +1122,12 @@ async fn test_update_step_validated_not_found() {
             title: None,
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: None,
             result: Some("Test result".to_string()),
+            clear_result: false,
+            blocked_reason: None,
+            verification_required: None,
         })
         .await
         .expect("Should not fail on non-existent step");
@@ -499,6 +1145,7 @@ async fn test_claim_step_atomically() {
             title: "Claim Test".to_string(),
             description: None,
             directory: None,
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
@@ -509,18 +1156,23 @@ async fn test_claim_step_atomically() {
             title: "Step to Claim".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: vec![],
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
 
-    // Test claim_step_atomically
+    // Claiming returns the step with its new status
     let claimed = planner
         .claim_step(&Id { id: step.id })
         .await
-        .expect("Failed to claim step");
-
-    assert!(claimed.is_some(), "Step should be successfully claimed");
+        .expect("Failed to claim step")
+        .expect("Step should be successfully claimed");
+    assert_eq!(claimed.id, step.id);
+    assert_eq!(claimed.title, "Step to Claim");
+    assert_eq!(claimed.status, StepStatus::InProgress);
 
     // Verify step is in progress
     let retrieved_step = planner
@@ -542,228 +1194,4364 @@ async fn test_claim_step_atomically() {
 }
 
 #[tokio::test]
-async fn test_add_step_to_plan() {
+async fn test_try_claim_step_returns_context_or_reason() {
     let (_temp_dir, planner) = create_test_planner().await;
-
-    // Create a plan
     let plan = planner
         .create_plan(&CreatePlan {
-            title: "Add Step Test".to_string(),
-            description: None,
-            directory: None,
+            title: "Claim Context".to_string(),
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
-
-    // Test add_step_to_plan
     let step = planner
         .add_step(&StepCreate {
             plan_id: plan.id,
-            title: "New Step".to_string(),
-            description: Some("Step description".to_string()),
-            acceptance_criteria: Some("Must be completed".to_string()),
-            references: vec!["file1.rs".to_string(), "file2.rs".to_string()],
+            title: "Claimable".to_string(),
+            description: Some("Full description".to_string()),
+            acceptance_criteria: Some("Tests pass".to_string()),
+            expected_output: None,
+            references: vec!["src/lib.rs".into()],
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
-        .expect("Failed to add step to plan");
+        .expect("Failed to add step");
 
-    assert_eq!(step.title, "New Step");
-    assert_eq!(step.description, Some("Step description".to_string()));
-    assert_eq!(
-        step.acceptance_criteria,
-        Some("Must be completed".to_string())
-    );
-    assert_eq!(
-        step.references,
-        vec!["file1.rs".to_string(), "file2.rs".to_string()]
-    );
+    let ClaimOutcome::Claimed(claimed) = planner
+        .try_claim_step(&Id { id: step.id })
+        .await
+        .expect("Failed to claim step")
+    else {
+        panic!("Step should be claimed");
+    };
+    assert_eq!(claimed.status, StepStatus::InProgress);
+    assert_eq!(claimed.description.as_deref(), Some("Full description"));
+    assert_eq!(claimed.acceptance_criteria.as_deref(), Some("Tests pass"));
+    assert_eq!(claimed.references, vec!["src/lib.rs".into()]);
+
+    // A failed claim reports only the reason
+    let outcome = planner
+        .try_claim_step(&Id { id: step.id })
+        .await
+        .expect("Failed to attempt claiming again");
+    assert_eq!(outcome, ClaimOutcome::NotClaimable(StepStatus::InProgress));
+
+    let outcome = planner
+        .try_claim_step(&Id { id: 9999 })
+        .await
+        .expect("Failed to attempt claiming missing step");
+    assert_eq!(outcome, ClaimOutcome::NotFound);
 }
 
 #[tokio::test]
-async fn test_insert_step_to_plan() {
+async fn test_release_step() {
     let (_temp_dir, planner) = create_test_planner().await;
+    let plan_id = create_plan_with_steps(&planner, "Abandoned", None, 1, 1).await;
+    let steps = planner.get_steps(&Id { id: plan_id }).await.unwrap();
+    let (done, todo) = (Id { id: steps[0].id }, Id { id: steps[1].id });
 
-    // Create a plan with existing steps
-    let plan = planner
-        .create_plan(&CreatePlan {
-            title: "Insert Step Test".to_string(),
-            description: None,
-            directory: None,
-        })
-        .await
-        .expect("Failed to create plan");
+    // Only steps in progress are released
+    assert!(!planner.release_step(&todo).await.unwrap());
+    assert!(!planner.release_step(&done).await.unwrap());
 
     planner
-        .add_step(&StepCreate {
-            plan_id: plan.id,
-            title: "First Step".to_string(),
-            description: None,
-            acceptance_criteria: None,
-            references: vec![],
-        })
+        .claim_step(&todo)
         .await
-        .expect("Failed to add first step");
+        .unwrap()
+        .expect("Step claimed");
+    let claimed_at = plan_updated_at(&planner, plan_id).await;
+    std::thread::sleep(Duration::from_millis(5));
+    assert!(planner.release_step(&todo).await.unwrap());
 
-    planner
-        .add_step(&StepCreate {
-            plan_id: plan.id,
-            title: "Third Step".to_string(),
-            description: None,
-            acceptance_criteria: None,
-            references: vec![],
+    let step = planner.get_step(&todo).await.unwrap().unwrap();
+    assert_eq!(step.status, StepStatus::Todo);
+    assert!(plan_updated_at(&planner, plan_id).await > claimed_at);
+
+    // A released step can be claimed again
+    assert!(planner.claim_step(&todo).await.unwrap().is_some());
+
+    assert!(matches!(
+        planner.release_step(&Id { id: 9999 }).await,
+        Err(PlannerError::StepNotFound { id: 9999 })
+    ));
+}
+
+#[tokio::test]
+async fn test_blocked_status() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan_id = create_plan_with_steps(&planner, "Blocked", None, 0, 2).await;
+    let steps = planner.get_steps(&Id { id: plan_id }).await.unwrap();
+    let (blocked, todo) = (steps[0].id, steps[1].id);
+
+    // Blocking needs no result, and records the reason
+    let step = planner
+        .update_step_validated(&UpdateStep {
+            id: blocked,
+            status: Some("blocked".to_string()),
+            blocked_reason: Some("  Waiting on API keys ".to_string()),
+            ..Default::default()
         })
         .await
-        .expect("Failed to add third step");
+        .expect("Failed to block step")
+        .expect("Step not found");
+    assert_eq!(step.status, StepStatus::Blocked);
+    assert_eq!(step.blocked_reason.as_deref(), Some("Waiting on API keys"));
+    assert!(step.to_string().contains("(⛔ Blocked)"));
+    assert!(step.to_string().contains("- Blocked: Waiting on API keys"));
 
-    // Test insert_step_to_plan at position 1 (between first and third)
-    let inserted_step = planner
-        .insert_step(&InsertStep {
-            step: StepCreate {
-                plan_id: plan.id,
-                title: "Second Step".to_string(),
-                description: None,
-                acceptance_criteria: None,
-                references: vec![],
-            },
-            position: 1,
+    // Blocked steps can't be claimed and are skipped for the next step, but
+    // count as pending
+    let outcome = planner.try_claim_step(&Id { id: blocked }).await.unwrap();
+    assert_eq!(outcome, ClaimOutcome::NotClaimable(StepStatus::Blocked));
+    let next = planner.next_step(&Id { id: plan_id }).await.unwrap();
+    assert_eq!(next.map(|step| step.id), Some(todo));
+    let summary = planner
+        .get_plan_summary(&Id { id: plan_id })
+        .await
+        .unwrap()
+        .expect("Plan not found");
+    assert_eq!(summary.pending_steps, 2);
+    let listed = planner
+        .list_steps(&ListSteps {
+            plan_id,
+            status: Some("blocked".to_string()),
         })
         .await
-        .expect("Failed to insert step");
+        .unwrap();
+    assert_eq!(listed.len(), 1);
 
-    assert_eq!(inserted_step.title, "Second Step");
+    // A reason only goes with the blocked status
+    let result = planner
+        .update_step_validated(&UpdateStep {
+            id: todo,
+            blocked_reason: Some("No reason".to_string()),
+            ..Default::default()
+        })
+        .await;
+    assert!(matches!(
+        result,
+        Err(PlannerError::InvalidInput { ref field, .. }) if field == "blocked_reason"
+    ));
 
-    // Verify order
-    let steps = planner
-        .get_steps(&Id { id: plan.id })
+    // Unblocking drops the reason
+    let step = planner
+        .update_step_validated(&UpdateStep {
+            id: blocked,
+            status: Some("todo".to_string()),
+            ..Default::default()
+        })
         .await
-        .expect("Failed to get steps");
-
-    assert_eq!(steps.len(), 3);
-    assert_eq!(steps[0].title, "First Step");
-    assert_eq!(steps[1].title, "Second Step");
-    assert_eq!(steps[2].title, "Third Step");
+        .unwrap()
+        .unwrap();
+    assert_eq!(step.status, StepStatus::Todo);
+    assert!(step.blocked_reason.is_none());
 }
 
 #[tokio::test]
-async fn test_show_step_details() {
+async fn test_step_dependencies_block_claims() {
     let (_temp_dir, planner) = create_test_planner().await;
+    let plan_id = create_plan_with_steps(&planner, "Pipeline", None, 0, 3).await;
+    let steps = planner.get_steps(&Id { id: plan_id }).await.unwrap();
+    let (first, second, third) = (steps[0].id, steps[1].id, steps[2].id);
+    let dependency = |step_id, depends_on| StepDependency {
+        step_id,
+        depends_on,
+    };
 
-    // Create a plan and step
+    assert!(
+        planner
+            .add_step_dependency(&dependency(third, first))
+            .await
+            .unwrap()
+    );
+    assert!(
+        planner
+            .add_step_dependency(&dependency(third, second))
+            .await
+            .unwrap()
+    );
+    assert!(
+        !planner
+            .add_step_dependency(&dependency(third, first))
+            .await
+            .unwrap()
+    );
+    planner
+        .add_step_dependency(&dependency(second, first))
+        .await
+        .unwrap();
+
+    let step = planner
+        .get_step(&Id { id: third })
+        .await
+        .unwrap()
+        .expect("Step not found");
+    assert_eq!(step.depends_on, vec![first, second]);
+    assert!(
+        step.to_string()
+            .contains(&format!("- Depends on: {first}, {second}"))
+    );
+    let steps = planner.get_steps(&Id { id: plan_id }).await.unwrap();
+    let depends_on: Vec<_> = steps.iter().map(|step| step.depends_on.len()).collect();
+    assert_eq!(depends_on, vec![0, 1, 2]);
+
+    // Cycles, direct or through other steps, are rejected
+    for (step_id, depends_on) in [(first, first), (first, second), (first, third)] {
+        match planner
+            .add_step_dependency(&dependency(step_id, depends_on))
+            .await
+        {
+            Err(PlannerError::InvalidInput { field, .. }) => assert_eq!(field, "depends_on"),
+            other => panic!("Expected the cycle to be rejected, got {other:?}"),
+        }
+    }
+    assert!(matches!(
+        planner.add_step_dependency(&dependency(third, 9999)).await,
+        Err(PlannerError::StepNotFound { id: 9999 })
+    ));
+
+    // The last step waits for both others
+    let outcome = planner.try_claim_step(&Id { id: third }).await.unwrap();
+    assert_eq!(outcome, ClaimOutcome::Blocked(vec![first, second]));
+    assert!(
+        planner
+            .claim_step(&Id { id: third })
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    complete_next_step(&planner, plan_id).await;
+    complete_next_step(&planner, plan_id).await;
+    let claimed = planner
+        .claim_step(&Id { id: third })
+        .await
+        .unwrap()
+        .expect("Step should be claimable once its dependencies are done");
+    assert_eq!(claimed.status, StepStatus::InProgress);
+
+    // Removed dependencies no longer show
+    assert!(
+        planner
+            .remove_step_dependency(&dependency(third, second))
+            .await
+            .unwrap()
+    );
+    assert!(
+        !planner
+            .remove_step_dependency(&dependency(third, second))
+            .await
+            .unwrap()
+    );
+    let step = planner.get_step(&Id { id: third }).await.unwrap().unwrap();
+    assert_eq!(step.depends_on, vec![first]);
+}
+
+#[tokio::test]
+async fn test_set_step_phase() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan_id = create_plan_with_steps(&planner, "Phased", None, 0, 2).await;
+    let plan = planner
+        .get_plan(&Id { id: plan_id })
+        .await
+        .expect("Failed to get plan")
+        .expect("Plan not found");
+    let step_id = plan.steps[1].id;
+
+    let step = planner
+        .set_step_phase(&SetStepPhase {
+            id: step_id,
+            phase: Some(" Testing ".to_string()),
+        })
+        .await
+        .expect("Failed to set phase")
+        .expect("Step not found");
+    assert_eq!(step.phase.as_deref(), Some("Testing"));
+
+    let plan = planner
+        .get_plan(&Id { id: plan_id })
+        .await
+        .expect("Failed to get plan")
+        .expect("Plan not found");
+    assert_eq!(plan.steps[0].phase, None);
+    assert_eq!(plan.steps[1].phase.as_deref(), Some("Testing"));
+    assert!(plan.to_string().contains("## Phase: Testing"));
+
+    let result = planner
+        .set_step_phase(&SetStepPhase {
+            id: step_id,
+            phase: Some("  ".to_string()),
+        })
+        .await;
+    assert!(matches!(result, Err(PlannerError::InvalidInput { .. })));
+
+    let step = planner
+        .set_step_phase(&SetStepPhase {
+            id: step_id,
+            phase: None,
+        })
+        .await
+        .expect("Failed to clear phase")
+        .expect("Step not found");
+    assert_eq!(step.phase, None);
+
+    let missing = planner
+        .set_step_phase(&SetStepPhase {
+            id: 9999,
+            phase: Some("Design".to_string()),
+        })
+        .await
+        .expect("Failed to set phase of missing step");
+    assert!(missing.is_none());
+}
+
+#[tokio::test]
+async fn test_add_step_to_plan() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    // Create a plan
     let plan = planner
         .create_plan(&CreatePlan {
-            title: "Step Details Test".to_string(),
+            title: "Add Step Test".to_string(),
             description: None,
             directory: None,
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
 
+    // Test add_step_to_plan
     let step = planner
         .add_step(&StepCreate {
             plan_id: plan.id,
-            title: "Detailed Step".to_string(),
-            description: Some("Detailed description".to_string()),
-            acceptance_criteria: Some("Must pass all tests".to_string()),
-            references: vec!["test.rs".to_string()],
+            title: "New Step".to_string(),
+            description: Some("Step description".to_string()),
+            acceptance_criteria: Some("Must be completed".to_string()),
+            expected_output: None,
+            references: vec!["file1.rs".into(), "file2.rs".into()],
+            verification_required: false,
+            estimate_minutes: None,
+        })
+        .await
+        .expect("Failed to add step to plan");
+
+    assert_eq!(step.title, "New Step");
+    assert_eq!(step.description, Some("Step description".to_string()));
+    assert_eq!(
+        step.acceptance_criteria,
+        Some("Must be completed".to_string())
+    );
+    assert_eq!(step.references, vec!["file1.rs".into(), "file2.rs".into()]);
+}
+
+#[tokio::test]
+async fn test_expected_output_round_trips() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Expected Output Test".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+
+    let added = planner
+        .add_step(&StepCreate {
+            plan_id: plan.id,
+            title: "Add token endpoint".to_string(),
+            description: None,
+            acceptance_criteria: Some("Tokens verify".to_string()),
+            expected_output: Some("A new endpoint POST /v1/tokens".to_string()),
+            references: vec![],
+            verification_required: true,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
+    assert_eq!(
+        added.expected_output.as_deref(),
+        Some("A new endpoint POST /v1/tokens")
+    );
+    assert!(added.verification_required);
 
-    // Test show_step_details
-    let retrieved_step = planner
-        .get_step(&Id { id: step.id })
+    let stored = planner
+        .get_step(&Id { id: added.id })
         .await
-        .expect("Failed to show step details")
-        .expect("Step should exist");
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.expected_output, added.expected_output);
 
-    assert_eq!(retrieved_step.title, "Detailed Step");
+    let updated = planner
+        .update_step_validated(&UpdateStep {
+            id: added.id,
+            expected_output: Some("POST /v1/tokens and its docs".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .unwrap();
     assert_eq!(
-        retrieved_step.description,
-        Some("Detailed description".to_string())
+        updated.expected_output.as_deref(),
+        Some("POST /v1/tokens and its docs")
     );
     assert_eq!(
-        retrieved_step.acceptance_criteria,
-        Some("Must pass all tests".to_string())
+        updated.acceptance_criteria.as_deref(),
+        Some("Tokens verify")
     );
-    assert_eq!(retrieved_step.references, vec!["test.rs".to_string()]);
+
+    // Serialized steps carry it too, as MCP clients receive them
+    let json = serde_json::to_string(&updated).unwrap();
+    let parsed: beacon_core::Step = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, updated);
 }
 
 #[tokio::test]
-async fn test_swap_step_positions() {
+async fn test_titles_and_text_are_validated() {
     let (_temp_dir, planner) = create_test_planner().await;
+    let assert_invalid = |result: beacon_core::Result<_>, expected: &str| match result {
+        Err(PlannerError::InvalidInput { field, .. }) => assert_eq!(field, expected),
+        Err(e) => panic!("Expected InvalidInput error, got {e:?}"),
+        Ok(_) => panic!("Expected InvalidInput error"),
+    };
 
-    // Create a plan with multiple steps
     let plan = planner
         .create_plan(&CreatePlan {
-            title: "Swap Test".to_string(),
-            description: None,
-            directory: None,
+            title: "  Validated plan  ".to_string(),
+            description: Some("\nContext\n".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(plan.title, "Validated plan");
+    assert_eq!(plan.description.as_deref(), Some("Context"));
+
+    assert_invalid(
+        planner
+            .create_plan(&CreatePlan {
+                title: " \t ".to_string(),
+                ..Default::default()
+            })
+            .await
+            .map(|_| ()),
+        "title",
+    );
+    assert_invalid(
+        planner
+            .create_plan(&CreatePlan {
+                title: "Huge".to_string(),
+                description: Some("x".repeat(MAX_DESCRIPTION_LEN + 1)),
+                ..Default::default()
+            })
+            .await
+            .map(|_| ()),
+        "description",
+    );
+
+    let step = planner
+        .add_step(&StepCreate {
+            plan_id: plan.id,
+            title: " Étape 🚀 ".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(step.title, "Étape 🚀");
+    assert_invalid(
+        planner
+            .insert_step(&InsertStep {
+                step: StepCreate {
+                    plan_id: plan.id,
+                    title: "t".repeat(MAX_TITLE_LEN + 1),
+                    ..Default::default()
+                },
+                position: 0,
+            })
+            .await
+            .map(|_| ()),
+        "title",
+    );
+
+    assert_invalid(
+        planner
+            .update_plan(&UpdatePlan {
+                id: plan.id,
+                title: Some(String::new()),
+                ..Default::default()
+            })
+            .await
+            .map(|_| ()),
+        "title",
+    );
+    assert_invalid(
+        planner
+            .update_step_validated(&UpdateStep {
+                id: step.id,
+                title: Some("   ".to_string()),
+                ..Default::default()
+            })
+            .await
+            .map(|_| ()),
+        "title",
+    );
+    let updated = planner
+        .update_step_validated(&UpdateStep {
+            id: step.id,
+            title: Some(format!(" {} ", "t".repeat(MAX_TITLE_LEN))),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated.title.chars().count(), MAX_TITLE_LEN);
+}
+
+#[tokio::test]
+async fn test_references_keep_commas_and_labels() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "References Test".to_string(),
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
 
-    let step1 = planner
+    // References arrive as plain strings or labelled objects
+    let params: StepCreate = serde_json::from_value(serde_json::json!({
+        "plan_id": plan.id,
+        "title": "Read the docs",
+        "references": [
+            "https://example.com/search?q=a,b",
+            {"url": "https://example.com/guide", "label": "Guide"}
+        ]
+    }))
+    .unwrap();
+    let added = planner.add_step(&params).await.unwrap();
+    let expected = vec![
+        StepReference::from("https://example.com/search?q=a,b"),
+        StepReference::labelled("https://example.com/guide", "Guide"),
+    ];
+    assert_eq!(added.references, expected);
+
+    let stored = planner
+        .get_step(&Id { id: added.id })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.references, expected);
+    let plan_steps = planner
+        .get_plan(&Id { id: plan.id })
+        .await
+        .unwrap()
+        .unwrap()
+        .steps;
+    assert_eq!(plan_steps[0].references, expected);
+
+    let output = stored.to_string();
+    assert!(output.contains("- https://example.com/search?q=a,b"));
+    assert!(output.contains("- [Guide](https://example.com/guide)"));
+
+    // Unlabelled references serialize back as plain strings
+    assert_eq!(
+        serde_json::to_value(&stored.references).unwrap(),
+        serde_json::json!([
+            "https://example.com/search?q=a,b",
+            {"url": "https://example.com/guide", "label": "Guide"}
+        ])
+    );
+
+    let updated = planner
+        .update_step_validated(&UpdateStep {
+            id: added.id,
+            references: Some(vec!["docs/a,b.md".into()]),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated.references, vec!["docs/a,b.md".into()]);
+
+    let clone = planner.clone_plan(&Id { id: plan.id }, None).await.unwrap();
+    assert_eq!(clone.steps[0].references, vec!["docs/a,b.md".into()]);
+}
+
+#[tokio::test]
+async fn test_strict_references() {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join("notes.md"), "").unwrap();
+    let planner = PlannerBuilder::new()
+        .with_database_path(Some(temp_dir.path().join("test.db")))
+        .strict_references(true)
+        .build()
+        .await
+        .unwrap();
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Strict".to_string(),
+            directory: Some(temp_dir.path().display().to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let step = planner
         .add_step(&StepCreate {
             plan_id: plan.id,
-            title: "First Step".to_string(),
+            title: "Read up".to_string(),
+            references: vec![
+                " HTTPS://Example.COM/Guide ".into(),
+                "https://example.com/Guide".into(),
+                "notes.md".into(),
+                "missing.md".into(),
+                "see docs".into(),
+            ],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let kinds = |step: &beacon_core::Step| -> Vec<(String, Option<ReferenceKind>)> {
+        step.references
+            .iter()
+            .map(|reference| (reference.url.clone(), reference.kind))
+            .collect()
+    };
+    let expected = vec![
+        (
+            "https://example.com/Guide".to_string(),
+            Some(ReferenceKind::Url),
+        ),
+        ("notes.md".to_string(), Some(ReferenceKind::File)),
+        ("missing.md".to_string(), Some(ReferenceKind::MissingFile)),
+        ("see docs".to_string(), Some(ReferenceKind::Text)),
+    ];
+    assert_eq!(kinds(&step), expected);
+
+    // The classification is stored with the references
+    let stored = planner
+        .get_step(&Id { id: step.id })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(kinds(&stored), expected);
+    let output = stored.to_string();
+    assert!(output.contains("- <https://example.com/Guide>"), "{output}");
+    assert!(output.contains("- notes.md\n"), "{output}");
+    assert!(output.contains("- missing.md (not found)"), "{output}");
+    assert!(output.contains("- see docs\n"), "{output}");
+
+    // Malformed URLs are rejected, when adding and when updating
+    let result = planner
+        .add_step(&StepCreate {
+            plan_id: plan.id,
+            title: "Broken".to_string(),
+            references: vec!["https://".into()],
+            ..Default::default()
+        })
+        .await;
+    assert!(
+        matches!(result, Err(PlannerError::InvalidInput { ref field, .. }) if field == "references")
+    );
+    let result = planner
+        .update_step_validated(&UpdateStep {
+            id: step.id,
+            references: Some(vec!["http://exa mple.com".into()]),
+            ..Default::default()
+        })
+        .await;
+    assert!(matches!(result, Err(PlannerError::InvalidInput { .. })));
+
+    let updated = planner
+        .update_step_validated(&UpdateStep {
+            id: step.id,
+            references: Some(vec![StepReference::labelled("HTTP://Docs.RS/", "Docs")]),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated.references[0].url, "http://docs.rs/");
+    assert_eq!(updated.references[0].kind, Some(ReferenceKind::Url));
+}
+
+#[tokio::test]
+async fn test_strict_references_can_be_turned_off() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.db");
+    let planner = PlannerBuilder::new()
+        .with_database_path(Some(&db_path))
+        .build()
+        .await
+        .unwrap();
+    planner
+        .set_setting("strict_references", "true")
+        .await
+        .unwrap();
+    let plan_id = create_plan_with_steps(&planner, "Lenient", None, 0, 0).await;
+    let add = |planner: beacon_core::Planner| async move {
+        planner
+            .add_step(&StepCreate {
+                plan_id,
+                title: "Read up".to_string(),
+                references: vec![" HTTPS://Example.COM ".into(), "https://".into()],
+                ..Default::default()
+            })
+            .await
+    };
+    assert!(add(planner).await.is_err());
+
+    // The builder overrides the workspace setting, storing references as
+    // given like earlier versions did
+    let lenient = PlannerBuilder::new()
+        .with_database_path(Some(&db_path))
+        .strict_references(false)
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(
+        add(lenient).await.unwrap().references,
+        vec![" HTTPS://Example.COM ".into(), "https://".into()]
+    );
+}
+
+#[tokio::test]
+async fn test_insert_step_to_plan() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    // Create a plan with existing steps
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Insert Step Test".to_string(),
             description: None,
-            acceptance_criteria: None,
-            references: vec![],
+            directory: None,
+            ..Default::default()
         })
         .await
-        .expect("Failed to add step 1");
+        .expect("Failed to create plan");
 
-    let _step2 = planner
+    planner
         .add_step(&StepCreate {
             plan_id: plan.id,
-            title: "Second Step".to_string(),
+            title: "First Step".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: vec![],
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
-        .expect("Failed to add step 2");
+        .expect("Failed to add first step");
 
-    let step3 = planner
+    planner
         .add_step(&StepCreate {
             plan_id: plan.id,
             title: "Third Step".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: vec![],
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
-        .expect("Failed to add step 3");
+        .expect("Failed to add third step");
 
-    // Test swap_step_positions
-    planner
-        .swap_steps(&SwapSteps {
-            step1_id: step1.id,
-            step2_id: step3.id,
+    // Test insert_step_to_plan at position 1 (between first and third)
+    let inserted_step = planner
+        .insert_step(&InsertStep {
+            step: StepCreate {
+                plan_id: plan.id,
+                title: "Second Step".to_string(),
+                description: None,
+                acceptance_criteria: None,
+                expected_output: None,
+                references: vec![],
+                verification_required: false,
+                estimate_minutes: None,
+            },
+            position: 1,
         })
         .await
-        .expect("Failed to swap steps");
+        .expect("Failed to insert step");
 
-    // Verify new order
+    assert_eq!(inserted_step.title, "Second Step");
+
+    // Verify order
     let steps = planner
         .get_steps(&Id { id: plan.id })
         .await
         .expect("Failed to get steps");
 
     assert_eq!(steps.len(), 3);
-    assert_eq!(steps[0].title, "Third Step"); // step3 is now first
-    assert_eq!(steps[1].title, "Second Step"); // step2 stays in middle
-    assert_eq!(steps[2].title, "First Step"); // step1 is now last
+    assert_eq!(steps[0].title, "First Step");
+    assert_eq!(steps[1].title, "Second Step");
+    assert_eq!(steps[2].title, "Third Step");
 }
 
-/// Helper function to create a test planner
-pub async fn create_test_planner() -> (TempDir, beacon_core::Planner) {
-    let temp_dir = TempDir::new().expect("Failed to create temp dir");
-    let db_path = temp_dir.path().join("test.db");
-    let planner = PlannerBuilder::new()
-        .with_database_path(Some(db_path))
-        .build()
-        .await
-        .expect("Failed to create planner");
-    (temp_dir, planner)
+#[tokio::test]
+async fn test_show_step_details() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    // Create a plan and step
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Step Details Test".to_string(),
+            description: None,
+            directory: None,
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+
+    let step = planner
+        .add_step(&StepCreate {
+            plan_id: plan.id,
+            title: "Detailed Step".to_string(),
+            description: Some("Detailed description".to_string()),
+            acceptance_criteria: Some("Must pass all tests".to_string()),
+            expected_output: None,
+            references: vec!["test.rs".into()],
+            verification_required: false,
+            estimate_minutes: None,
+        })
+        .await
+        .expect("Failed to add step");
+
+    // Test show_step_details
+    let retrieved_step = planner
+        .get_step(&Id { id: step.id })
+        .await
+        .expect("Failed to show step details")
+        .expect("Step should exist");
+
+    assert_eq!(retrieved_step.title, "Detailed Step");
+    assert_eq!(
+        retrieved_step.description,
+        Some("Detailed description".to_string())
+    );
+    assert_eq!(
+        retrieved_step.acceptance_criteria,
+        Some("Must pass all tests".to_string())
+    );
+    assert_eq!(retrieved_step.references, vec!["test.rs".into()]);
+}
+
+#[tokio::test]
+async fn test_not_found_errors_hint_at_other_kind_of_item() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    // Plans 1 to 4, with steps 1 to 3 in plan 2
+    let mut plan_ids = Vec::new();
+    for title in ["First", "Second", "Third", "Fourth"] {
+        let plan = planner
+            .create_plan(&CreatePlan {
+                title: title.to_string(),
+                ..Default::default()
+            })
+            .await
+            .expect("Failed to create plan");
+        plan_ids.push(plan.id);
+    }
+    assert_eq!(plan_ids, vec![1, 2, 3, 4]);
+    for title in ["Step A", "Step B", "Step C"] {
+        planner
+            .add_step(&StepCreate {
+                plan_id: 2,
+                title: title.to_string(),
+                description: None,
+                acceptance_criteria: None,
+                expected_output: None,
+                references: vec![],
+                verification_required: false,
+                estimate_minutes: None,
+            })
+            .await
+            .expect("Failed to add step");
+    }
+    planner
+        .remove_step(&Id { id: 3 })
+        .await
+        .expect("Failed to remove step");
+    planner
+        .delete_plan_by_id(&Id { id: 3 })
+        .await
+        .expect("Failed to delete plan");
+
+    // Neither: plan 3 and step 3 are both gone
+    let error = planner.plan_not_found(3).await;
+    assert!(matches!(error, PlannerError::PlanNotFound { id: 3 }));
+    assert_eq!(error.id_hint(), None);
+
+    // Plan lookup hitting a step
+    planner
+        .delete_plan_by_id(&Id { id: 1 })
+        .await
+        .expect("Failed to delete plan");
+    let error = planner
+        .get_plan_outline(1)
+        .await
+        .expect_err("Plan 1 was deleted");
+    assert!(error.is_not_found());
+    assert_eq!(
+        error.id_hint(),
+        Some(IdHint::StepExists {
+            step_id: 1,
+            plan_id: 2
+        })
+    );
+    assert_eq!(
+        error.to_string(),
+        "Plan with ID 1 not found, but step 1 exists in plan 2"
+    );
+
+    // Step lookup hitting a plan
+    let error = planner
+        .remove_step(&Id { id: 4 })
+        .await
+        .expect_err("Step 4 doesn't exist");
+    assert!(error.is_not_found());
+    assert_eq!(error.id_hint(), Some(IdHint::PlanExists { plan_id: 4 }));
+    assert_eq!(
+        error.to_string(),
+        "Step with ID 4 not found, but plan 4 exists"
+    );
+
+    // Both exist, so neither lookup fails
+    assert!(planner.get_plan(&Id { id: 2 }).await.unwrap().is_some());
+    assert!(planner.get_step(&Id { id: 2 }).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_swap_step_positions() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    // Create a plan with multiple steps
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Swap Test".to_string(),
+            description: None,
+            directory: None,
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+
+    let step1 = planner
+        .add_step(&StepCreate {
+            plan_id: plan.id,
+            title: "First Step".to_string(),
+            description: None,
+            acceptance_criteria: None,
+            expected_output: None,
+            references: vec![],
+            verification_required: false,
+            estimate_minutes: None,
+        })
+        .await
+        .expect("Failed to add step 1");
+
+    let _step2 = planner
+        .add_step(&StepCreate {
+            plan_id: plan.id,
+            title: "Second Step".to_string(),
+            description: None,
+            acceptance_criteria: None,
+            expected_output: None,
+            references: vec![],
+            verification_required: false,
+            estimate_minutes: None,
+        })
+        .await
+        .expect("Failed to add step 2");
+
+    let step3 = planner
+        .add_step(&StepCreate {
+            plan_id: plan.id,
+            title: "Third Step".to_string(),
+            description: None,
+            acceptance_criteria: None,
+            expected_output: None,
+            references: vec![],
+            verification_required: false,
+            estimate_minutes: None,
+        })
+        .await
+        .expect("Failed to add step 3");
+
+    // Test swap_step_positions
+    planner
+        .swap_steps(&SwapSteps {
+            step1_id: step1.id,
+            step2_id: step3.id,
+        })
+        .await
+        .expect("Failed to swap steps");
+
+    // Verify new order
+    let steps = planner
+        .get_steps(&Id { id: plan.id })
+        .await
+        .expect("Failed to get steps");
+
+    assert_eq!(steps.len(), 3);
+    assert_eq!(steps[0].title, "Third Step"); // step3 is now first
+    assert_eq!(steps[1].title, "Second Step"); // step2 stays in middle
+    assert_eq!(steps[2].title, "First Step"); // step1 is now last
+}
+
+#[tokio::test]
+async fn test_create_plan_unique_per_directory_returns_existing() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let planner = PlannerBuilder::new()
+        .with_database_path(Some(temp_dir.path().join("test.db")))
+        .unique_plan_per_directory(true)
+        .build()
+        .await
+        .expect("Failed to create planner");
+
+    let params = CreatePlan {
+        title: "Retry Me".to_string(),
+        description: Some("First attempt".to_string()),
+        directory: Some("/tmp/project".to_string()),
+        ..Default::default()
+    };
+    let first = planner
+        .create_plan(&params)
+        .await
+        .expect("Failed to create plan");
+    planner
+        .add_step(&StepCreate {
+            plan_id: first.id,
+            title: "Existing step".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to add step");
+
+    // Retrying returns the existing plan, steps included
+    let retried = planner
+        .create_plan(&CreatePlan {
+            description: Some("Second attempt".to_string()),
+            ..params.clone()
+        })
+        .await
+        .expect("Failed to retry plan creation");
+    assert_eq!(retried.id, first.id);
+    assert_eq!(retried.description, Some("First attempt".to_string()));
+    assert_eq!(retried.steps.len(), 1);
+
+    // Same title in another directory is still a new plan
+    let other_dir = planner
+        .create_plan(&CreatePlan {
+            directory: Some("/tmp/other".to_string()),
+            ..params.clone()
+        })
+        .await
+        .expect("Failed to create plan");
+    assert_ne!(other_dir.id, first.id);
+
+    // Different title in the same directory is a new plan
+    let other_title = planner
+        .create_plan(&CreatePlan {
+            title: "Something Else".to_string(),
+            ..params
+        })
+        .await
+        .expect("Failed to create plan");
+    assert_ne!(other_title.id, first.id);
+}
+
+#[tokio::test]
+async fn test_create_plan_unique_per_directory_ignores_archived_plans() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let planner = PlannerBuilder::new()
+        .with_database_path(Some(temp_dir.path().join("test.db")))
+        .unique_plan_per_directory(true)
+        .build()
+        .await
+        .expect("Failed to create planner");
+
+    let params = CreatePlan {
+        title: "Weekly".to_string(),
+        directory: Some("/tmp/project".to_string()),
+        ..Default::default()
+    };
+    let archived = planner
+        .create_plan(&params)
+        .await
+        .expect("Failed to create plan");
+    planner
+        .archive_plan(&ArchivePlan {
+            id: archived.id,
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to archive plan");
+
+    // An archived plan is not handed back as the existing one
+    let created = planner
+        .create_plan(&params)
+        .await
+        .expect("Failed to create plan");
+    assert_ne!(created.id, archived.id);
+    assert_eq!(created.status, PlanStatus::Active);
+
+    // Nor can it be brought back next to its replacement
+    let error = planner
+        .unarchive_plan(&Id { id: archived.id })
+        .await
+        .expect_err("Duplicate plan was unarchived");
+    assert!(
+        matches!(&error, PlannerError::InvalidInput { field, .. } if field == "title"),
+        "{error:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_create_plan_duplicates_allowed_by_default() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let params = CreatePlan {
+        title: "Duplicate".to_string(),
+        description: None,
+        directory: Some("/tmp/project".to_string()),
+        ..Default::default()
+    };
+    let first = planner
+        .create_plan(&params)
+        .await
+        .expect("Failed to create plan");
+    let second = planner
+        .create_plan(&params)
+        .await
+        .expect("Failed to create plan");
+
+    assert_ne!(first.id, second.id);
+}
+
+#[tokio::test]
+async fn test_plan_report() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan_id = create_plan_with_steps(&planner, "Standup", None, 2, 5).await;
+    let id = Id { id: plan_id };
+    let steps = planner.get_steps(&id).await.unwrap();
+    planner.claim_step(&Id { id: steps[2].id }).await.unwrap();
+
+    let report = planner.plan_report(&id, None).await.unwrap();
+    assert!(report.has_activity());
+    let titles = |steps: &[beacon_core::Step]| -> Vec<String> {
+        steps.iter().map(|step| step.title.clone()).collect()
+    };
+    assert_eq!(
+        titles(&report.completed),
+        ["Standup step 0", "Standup step 1"]
+    );
+    assert_eq!(titles(&report.in_progress), ["Standup step 2"]);
+    // Only the first todo steps are listed
+    assert_eq!(
+        titles(&report.upcoming),
+        ["Standup step 3", "Standup step 4", "Standup step 5"]
+    );
+    assert_eq!(report.upcoming[0].order, 3);
+
+    let shown = report.to_string();
+    assert!(shown.starts_with("# Standup: Standup"), "{shown}");
+    assert!(
+        shown.contains("## Completed\n\n- Standup step 0"),
+        "{shown}"
+    );
+    assert!(shown.contains("  Done"), "{shown}");
+    assert!(shown.contains("## In Progress"), "{shown}");
+    assert!(shown.contains("## Up Next"), "{shown}");
+
+    // Steps completed before the cutoff are left out
+    let later = jiff::Timestamp::now() + Duration::from_secs(60);
+    let report = planner
+        .plan_report(&Id { id: plan_id }, Some(later))
+        .await
+        .unwrap();
+    assert!(report.completed.is_empty());
+    assert_eq!(report.in_progress.len(), 1);
+
+    // A quiet plan says so rather than showing empty sections
+    let quiet = create_plan_with_steps(&planner, "Quiet", None, 0, 1).await;
+    let shown = planner
+        .plan_report(&Id { id: quiet }, None)
+        .await
+        .unwrap()
+        .to_string();
+    assert!(shown.contains("No activity since"), "{shown}");
+    assert!(!shown.contains("## Completed"), "{shown}");
+    assert!(!shown.contains("## In Progress"), "{shown}");
+    assert!(shown.contains("## Up Next\n\n- Quiet step 0"), "{shown}");
+
+    assert!(matches!(
+        planner.plan_report(&Id { id: 999 }, None).await,
+        Err(PlannerError::PlanNotFound { id: 999 })
+    ));
+}
+
+#[tokio::test]
+async fn test_handoff_summary_sections() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Handoff Plan".to_string(),
+            description: Some("Ship the feature".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+
+    let mut ids = Vec::new();
+    for title in ["Done Step", "Active Step", "Later Step", "Also Done"] {
+        let step = planner
+            .add_step(&StepCreate {
+                plan_id: plan.id,
+                title: title.to_string(),
+                ..Default::default()
+            })
+            .await
+            .expect("Failed to add step");
+        ids.push(step.id);
+    }
+
+    for (id, result) in [(ids[0], "Wrote the code"), (ids[3], "Line one\nLine two")] {
+        planner
+            .update_step_validated(&UpdateStep {
+                id,
+                status: Some("done".to_string()),
+                result: Some(result.to_string()),
+                ..Default::default()
+            })
+            .await
+            .expect("Failed to complete step");
+    }
+    planner
+        .claim_step(&Id { id: ids[1] })
+        .await
+        .expect("Failed to claim step");
+
+    let brief = planner
+        .handoff_summary(&Id { id: plan.id })
+        .await
+        .expect("Failed to build handoff summary")
+        .to_string();
+
+    let section = |heading: &str| -> String {
+        let start = brief
+            .find(&format!("## {heading}\n"))
+            .unwrap_or_else(|| panic!("Missing section {heading}: {brief}"));
+        let rest = &brief[start + heading.len() + 4..];
+        rest[..rest.find("## ").unwrap_or(rest.len())].to_string()
+    };
+
+    assert!(brief.starts_with("# Handoff: Handoff Plan"));
+    assert!(section("Goal").contains("Ship the feature"));
+
+    let done = section("Done");
+    assert!(done.contains("Done Step"));
+    assert!(done.contains("Wrote the code"));
+    assert!(done.contains("Also Done"));
+    assert!(done.contains("  Line one\n  Line two"));
+    assert!(!done.contains("Active Step"));
+    assert!(!done.contains("Later Step"));
+
+    let in_progress = section("In Progress");
+    assert!(in_progress.contains("Active Step"));
+    assert!(!in_progress.contains("Done Step"));
+    assert!(!in_progress.contains("Later Step"));
+
+    let remaining = section("Remaining");
+    assert!(remaining.contains("Later Step"));
+    assert!(!remaining.contains("Active Step"));
+    assert!(!remaining.contains("Done Step"));
+
+    // Unknown plans are reported as errors
+    assert!(planner.handoff_summary(&Id { id: 9999 }).await.is_err());
+}
+
+#[tokio::test]
+async fn test_momentum_stats_count_todays_completions() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let steps = |count: usize| {
+        (0..count)
+            .map(|index| CreateStepRequest {
+                title: format!("Step {index}"),
+                description: None,
+                acceptance_criteria: None,
+                result: None,
+                references: Vec::new(),
+                status: StepStatus::Todo,
+            })
+            .collect()
+    };
+    let busy = planner
+        .create_plan_with_steps(
+            &CreatePlan {
+                title: "Busy Plan".to_string(),
+                ..Default::default()
+            },
+            steps(3),
+        )
+        .await
+        .unwrap();
+    let idle = planner
+        .create_plan_with_steps(
+            &CreatePlan {
+                title: "Idle Plan".to_string(),
+                ..Default::default()
+            },
+            steps(1),
+        )
+        .await
+        .unwrap();
+
+    for step in &busy.steps[..2] {
+        planner
+            .update_step(
+                step.id,
+                UpdateStepRequest {
+                    status: Some(StepStatus::Done),
+                    result: Some("Done".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    let now = Zoned::now();
+    let momentum = planner.momentum_stats(&now).await.unwrap();
+    assert_eq!(momentum.streak_days, 1);
+    assert!(momentum.completed_today);
+    assert_eq!(
+        momentum.trend,
+        Trend {
+            recent: 2,
+            prior: 0
+        }
+    );
+
+    let by_plan = planner.momentum_stats_by_plan(&now).await.unwrap();
+    assert_eq!(by_plan[&busy.id].trend.recent, 2);
+    assert!(!by_plan.contains_key(&idle.id));
+
+    let mut summary = PlanSummary::from(&busy);
+    summary.momentum = Some(by_plan[&busy.id].trend);
+    assert!(summary.to_string().contains("- **Momentum**: ▲ 2 vs 0"));
+}
+
+#[tokio::test]
+async fn test_handoff_summary_crosses_tasks() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Spawned Plan".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+
+    // The summary owns its plan, so it can be built in one task and rendered
+    // in another
+    let brief = tokio::spawn(async move { planner.handoff_summary(&Id { id: plan.id }).await })
+        .await
+        .expect("Task panicked")
+        .expect("Failed to build handoff summary");
+
+    assert_eq!(brief.0.id, plan.id);
+    assert!(brief.to_string().starts_with("# Handoff: Spawned Plan"));
+}
+
+/// Creates a plan, optionally under a parent, with `done` completed steps
+/// followed by `todo` open ones.
+async fn create_plan_with_steps(
+    planner: &beacon_core::Planner,
+    title: &str,
+    parent_plan_id: Option<u64>,
+    done: usize,
+    todo: usize,
+) -> u64 {
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: title.to_string(),
+            parent_plan_id,
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+
+    for i in 0..done + todo {
+        let step = planner
+            .add_step(&StepCreate {
+                plan_id: plan.id,
+                title: format!("{title} step {i}"),
+                ..Default::default()
+            })
+            .await
+            .expect("Failed to add step");
+        if i < done {
+            planner
+                .update_step_validated(&UpdateStep {
+                    id: step.id,
+                    status: Some("done".to_string()),
+                    result: Some("Done".to_string()),
+                    ..Default::default()
+                })
+                .await
+                .expect("Failed to complete step");
+        }
+    }
+
+    plan.id
+}
+
+#[tokio::test]
+async fn test_child_plan_rollup() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let parent = create_plan_with_steps(&planner, "Parent", None, 1, 1).await;
+    let backend = create_plan_with_steps(&planner, "Backend", Some(parent), 2, 1).await;
+    let frontend = create_plan_with_steps(&planner, "Frontend", Some(parent), 1, 3).await;
+    let unrelated = create_plan_with_steps(&planner, "Unrelated", None, 1, 0).await;
+
+    // Archived children still count towards the rollup
+    planner
+        .archive_plan(&ArchivePlan {
+            id: frontend,
+            force: false,
+        })
+        .await
+        .expect("Failed to archive child");
+
+    let summaries = planner
+        .list_plans_summary(&ListPlans::default())
+        .await
+        .expect("Failed to list plans");
+    let summary = |id: u64| summaries.items.iter().find(|s| s.id == id).unwrap();
+
+    let parent_summary = summary(parent);
+    assert_eq!(parent_summary.total_steps, 2);
+    assert_eq!(parent_summary.completed_steps, 1);
+    assert_eq!(parent_summary.child_plans, 2);
+    assert_eq!(parent_summary.child_total_steps, 7);
+    assert_eq!(parent_summary.child_completed_steps, 3);
+    assert!(format!("{parent_summary}").contains("- **Child plans**: 2 (3/7 steps done)"));
+
+    let backend_summary = summary(backend);
+    assert_eq!(backend_summary.parent_plan_id, Some(parent));
+    assert_eq!(backend_summary.child_plans, 0);
+    assert_eq!(summary(unrelated).child_total_steps, 0);
+
+    // Showing the parent lists child plans with their progress after its steps
+    let plan = planner
+        .get_plan(&Id { id: parent })
+        .await
+        .expect("Failed to get plan")
+        .expect("Plan should exist");
+    assert_eq!(
+        plan.child_plans.iter().map(|c| c.id).collect::<Vec<_>>(),
+        vec![backend, frontend]
+    );
+    let shown = plan.to_string();
+    let children_at = shown.find("## Child Plans").expect("Missing child plans");
+    assert!(children_at > shown.find("## Steps").unwrap());
+    assert!(shown.contains(&format!("- {backend}. Backend (2/3)\n")));
+    assert!(shown.contains(&format!("- {frontend}. Frontend (1/4) [archived]\n")));
+    assert_eq!(PlanSummary::from(&plan).child_total_steps, 7);
+}
+
+#[tokio::test]
+async fn test_plan_summary_progress() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let empty = create_plan_with_steps(&planner, "Empty", None, 0, 0).await;
+    let finished = create_plan_with_steps(&planner, "Finished", None, 2, 0).await;
+    let mixed = create_plan_with_steps(&planner, "Mixed", None, 1, 3).await;
+    let plan = planner
+        .get_plan(&Id { id: mixed })
+        .await
+        .expect("Failed to get plan")
+        .expect("Plan should exist");
+    planner
+        .claim_step(&Id {
+            id: plan.steps[1].id,
+        })
+        .await
+        .expect("Failed to claim step");
+
+    let summaries = planner
+        .list_plans_summary(&ListPlans::default())
+        .await
+        .expect("Failed to list plans");
+    let summary = |id: u64| summaries.items.iter().find(|s| s.id == id).unwrap();
+
+    assert_eq!(summary(empty).progress_percent(), 0.0);
+    assert_eq!(summary(finished).progress_percent(), 100.0);
+
+    let mixed_summary = summary(mixed);
+    assert_eq!(mixed_summary.completed_steps, 1);
+    assert_eq!(mixed_summary.in_progress_steps, 1);
+    assert_eq!(mixed_summary.pending_steps, 3);
+    assert_eq!(mixed_summary.progress_percent(), 25.0);
+    assert!(format!("{mixed_summary}").contains("(1/4, 1 in progress)"));
+
+    // Counting loaded steps gives the same result as the summary view
+    let plan = planner
+        .get_plan(&Id { id: mixed })
+        .await
+        .expect("Failed to get plan")
+        .expect("Plan should exist");
+    assert_eq!(PlanSummary::from(&plan).in_progress_steps, 1);
+}
+
+#[tokio::test]
+async fn test_child_plan_nesting_limited_to_one_level() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let parent = create_plan_with_steps(&planner, "Parent", None, 0, 0).await;
+    let child = create_plan_with_steps(&planner, "Child", Some(parent), 0, 0).await;
+    let other = create_plan_with_steps(&planner, "Other", None, 0, 0).await;
+
+    let assert_parent_rejected =
+        |result: beacon_core::Result<beacon_core::Plan>, reason: &str| match result {
+            Err(PlannerError::InvalidInput { field, reason: r }) => {
+                assert_eq!(field, "parent_plan_id");
+                assert!(r.contains(reason), "Expected '{reason}' in '{r}'");
+            }
+            other => panic!("Expected parent_plan_id error, got {other:?}"),
+        };
+
+    // A child cannot become a parent, at creation or via update
+    assert_parent_rejected(
+        planner
+            .create_plan(&CreatePlan {
+                title: "Grandchild".to_string(),
+                parent_plan_id: Some(child),
+                ..Default::default()
+            })
+            .await,
+        "one level deep",
+    );
+    assert_parent_rejected(
+        planner
+            .update_plan(&UpdatePlan {
+                id: other,
+                parent_plan_id: Some(child),
+                ..Default::default()
+            })
+            .await,
+        "one level deep",
+    );
+
+    // A parent cannot become a child, nor can a plan parent itself
+    assert_parent_rejected(
+        planner
+            .update_plan(&UpdatePlan {
+                id: parent,
+                parent_plan_id: Some(other),
+                ..Default::default()
+            })
+            .await,
+        "has child plans",
+    );
+    assert_parent_rejected(
+        planner
+            .update_plan(&UpdatePlan {
+                id: other,
+                parent_plan_id: Some(other),
+                ..Default::default()
+            })
+            .await,
+        "its own parent",
+    );
+    assert_parent_rejected(
+        planner
+            .create_plan(&CreatePlan {
+                title: "Orphan".to_string(),
+                parent_plan_id: Some(9999),
+                ..Default::default()
+            })
+            .await,
+        "does not exist",
+    );
+
+    // Moving and detaching plans works within the constraint
+    let moved = planner
+        .update_plan(&UpdatePlan {
+            id: other,
+            parent_plan_id: Some(parent),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to move plan under parent");
+    assert_eq!(moved.parent_plan_id, Some(parent));
+    assert_eq!(moved.title, "Other");
+
+    let detached = planner
+        .update_plan(&UpdatePlan {
+            id: child,
+            title: Some("Renamed".to_string()),
+            clear_parent: true,
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to detach plan");
+    assert_eq!(detached.parent_plan_id, None);
+    assert_eq!(detached.title, "Renamed");
+
+    assert!(matches!(
+        planner
+            .update_plan(&UpdatePlan {
+                id: 9999,
+                title: Some("Missing".to_string()),
+                ..Default::default()
+            })
+            .await,
+        Err(PlannerError::PlanNotFound { id: 9999 })
+    ));
+}
+
+#[tokio::test]
+async fn test_archive_parent_with_active_children() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let parent = create_plan_with_steps(&planner, "Parent", None, 0, 0).await;
+    let child = create_plan_with_steps(&planner, "Child", Some(parent), 0, 1).await;
+
+    // Refused without force while the child is active
+    match planner
+        .archive_plan(&ArchivePlan {
+            id: parent,
+            force: false,
+        })
+        .await
+    {
+        Err(PlannerError::InvalidInput { field, reason }) => {
+            assert_eq!(field, "force");
+            assert!(reason.contains(&format!("({child})")));
+        }
+        other => panic!("Expected refusal, got {other:?}"),
+    }
+    let plan = planner.get_plan(&Id { id: parent }).await.unwrap().unwrap();
+    assert_eq!(plan.status, PlanStatus::Active);
+
+    // Forcing archives the parent and leaves the child alone
+    let archived = planner
+        .archive_plan(&ArchivePlan {
+            id: parent,
+            force: true,
+        })
+        .await
+        .expect("Failed to force archive")
+        .expect("Plan should exist");
+    assert_eq!(archived.status, PlanStatus::Archived);
+    assert_eq!(archived.child_plans[0].status, PlanStatus::Active);
+
+    // Once the children are archived, no force is needed
+    planner.unarchive_plan(&Id { id: parent }).await.unwrap();
+    planner
+        .archive_plan(&ArchivePlan {
+            id: child,
+            force: false,
+        })
+        .await
+        .expect("Failed to archive child");
+    planner
+        .archive_plan(&ArchivePlan {
+            id: parent,
+            force: false,
+        })
+        .await
+        .expect("Failed to archive parent without active children");
+}
+
+#[tokio::test]
+async fn test_plan_due_dates() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let create = |title: &str, due_date: Option<&str>| CreatePlan {
+        title: title.to_string(),
+        due_date: due_date.map(str::to_string),
+        ..Default::default()
+    };
+
+    let early = planner
+        .create_plan(&create("Early", Some("2025-01-15")))
+        .await
+        .expect("Failed to create plan");
+    assert_eq!(early.due_date, Some(jiff::civil::date(2025, 1, 15)));
+    let late = planner
+        .create_plan(&create("Late", Some("2025-03-01")))
+        .await
+        .expect("Failed to create plan");
+    planner
+        .create_plan(&create("Undated", None))
+        .await
+        .expect("Failed to create plan");
+
+    let error = planner
+        .create_plan(&create("Bad", Some("2025-13-01")))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, PlannerError::InvalidInput { ref field, .. } if field == "due_date"));
+
+    // Plans without a due date never match; the date itself is included
+    let due_by = |date: &str| ListPlans {
+        due_before: Some(date.to_string()),
+        ..Default::default()
+    };
+    // Sorted, as plans created within the same millisecond list in any order
+    let titles = |summaries: Vec<PlanSummary>| {
+        let mut titles = summaries
+            .into_iter()
+            .map(|summary| summary.title)
+            .collect::<Vec<_>>();
+        titles.sort();
+        titles
+    };
+    let listed = planner
+        .list_plans_summary(&due_by("2025-01-15"))
+        .await
+        .unwrap();
+    assert_eq!(titles(listed.items.to_vec()), ["Early"]);
+    assert_eq!(
+        listed.items[0].due_date,
+        Some(jiff::civil::date(2025, 1, 15))
+    );
+    let listed = planner
+        .list_plans_summary(&due_by("2025-12-31"))
+        .await
+        .unwrap();
+    assert_eq!(titles(listed.items.to_vec()), ["Early", "Late"]);
+
+    // The due date can be moved and cleared
+    let moved = planner
+        .update_plan(&UpdatePlan {
+            id: late.id,
+            due_date: Some("2025-01-01".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(moved.due_date, Some(jiff::civil::date(2025, 1, 1)));
+    let cleared = planner
+        .update_plan(&UpdatePlan {
+            id: early.id,
+            clear_due_date: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(cleared.due_date, None);
+    let listed = planner
+        .list_plans_summary(&due_by("2025-12-31"))
+        .await
+        .unwrap();
+    assert_eq!(titles(listed.items.to_vec()), ["Late"]);
+}
+
+#[tokio::test]
+async fn test_archive_completed_plans() {
+    let (temp_dir, planner) = create_test_planner().await;
+
+    let done = create_plan_with_steps(&planner, "Done", None, 2, 0).await;
+    let partial = create_plan_with_steps(&planner, "Partial", None, 1, 1).await;
+    let empty = create_plan_with_steps(&planner, "Empty", None, 0, 0).await;
+    let blocked_parent = create_plan_with_steps(&planner, "Blocked parent", None, 1, 0).await;
+    let open_child =
+        create_plan_with_steps(&planner, "Open child", Some(blocked_parent), 0, 1).await;
+    let done_parent = create_plan_with_steps(&planner, "Done parent", None, 1, 0).await;
+    let done_child = create_plan_with_steps(&planner, "Done child", Some(done_parent), 1, 0).await;
+    let elsewhere = planner
+        .create_plan(&CreatePlan {
+            title: "Elsewhere".to_string(),
+            directory: Some(temp_dir.path().to_string_lossy().into_owned()),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .id;
+    planner
+        .add_step(&StepCreate {
+            plan_id: elsewhere,
+            title: "Only step".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    complete_next_step(&planner, elsewhere).await;
+
+    let status = |id| {
+        let planner = &planner;
+        async move { planner.get_plan(&Id { id }).await.unwrap().unwrap().status }
+    };
+
+    // Limited to a directory, only the plan there is archived
+    let archived = planner
+        .archive_completed_plans(Some(temp_dir.path().to_string_lossy().into_owned()))
+        .await
+        .unwrap();
+    assert_eq!(
+        archived.iter().map(|s| s.id).collect::<Vec<_>>(),
+        vec![elsewhere]
+    );
+    assert_eq!(archived[0].status, PlanStatus::Archived);
+    assert_eq!(status(done).await, PlanStatus::Active);
+
+    let mut archived: Vec<u64> = planner
+        .archive_completed_plans(None)
+        .await
+        .unwrap()
+        .iter()
+        .map(|s| s.id)
+        .collect();
+    archived.sort();
+    assert_eq!(archived, vec![done, done_parent, done_child]);
+    for id in [done, done_parent, done_child, elsewhere] {
+        assert_eq!(status(id).await, PlanStatus::Archived);
+    }
+    // Empty plans are not complete, and a parent waits for its children
+    for id in [partial, empty, blocked_parent, open_child] {
+        assert_eq!(status(id).await, PlanStatus::Active);
+    }
+
+    assert!(
+        planner
+            .archive_completed_plans(None)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_claim_next_step_claims_each_step_once() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("shared.db");
+    let build = || {
+        PlannerBuilder::new()
+            .with_database_path(Some(&db_path))
+            .with_busy_timeout(Duration::from_secs(10))
+            .build()
+    };
+    let planners = [build().await.unwrap(), build().await.unwrap()];
+
+    let plan = create_plan_with_steps(&planners[0], "Shared", None, 0, 20).await;
+
+    let mut tasks = Vec::new();
+    for worker in 0..8 {
+        let planner = planners[worker % 2].clone();
+        tasks.push(tokio::spawn(async move {
+            let mut claimed = Vec::new();
+            while let Some(step) = planner
+                .claim_next_step(&Id { id: plan })
+                .await
+                .unwrap_or_else(|e| panic!("claim_next_step failed: {e}"))
+            {
+                assert_eq!(step.status, StepStatus::InProgress);
+                claimed.push(step.id);
+            }
+            claimed
+        }));
+    }
+
+    let mut claimed = Vec::new();
+    for task in tasks {
+        claimed.extend(task.await.unwrap());
+    }
+    let mut unique = claimed.clone();
+    unique.sort_unstable();
+    unique.dedup();
+    assert_eq!(unique.len(), claimed.len(), "a step was claimed twice");
+
+    // No todo step was left behind either
+    let steps = planners[0].get_steps(&Id { id: plan }).await.unwrap();
+    let mut all: Vec<u64> = steps.iter().map(|step| step.id).collect();
+    all.sort_unstable();
+    assert_eq!(unique, all);
+    assert!(
+        steps
+            .iter()
+            .all(|step| step.status == StepStatus::InProgress)
+    );
+}
+
+#[tokio::test]
+async fn test_reset_plan_steps() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan = create_plan_with_steps(&planner, "Weekly", None, 2, 2).await;
+    let id = Id { id: plan };
+    let steps = planner.get_steps(&id).await.unwrap();
+    planner.claim_step(&Id { id: steps[2].id }).await.unwrap();
+    let before = planner.get_plan(&id).await.unwrap().unwrap().updated_at;
+    tokio::time::sleep(Duration::from_millis(5)).await;
+
+    // Results are kept when asked to
+    assert_eq!(planner.reset_plan_steps(&id, false).await.unwrap(), 4);
+    let steps = planner.get_steps(&id).await.unwrap();
+    assert!(steps.iter().all(|step| step.status == StepStatus::Todo
+        && step.started_at.is_none()
+        && step.completed_at.is_none()));
+    assert_eq!(steps[0].result.as_deref(), Some("Done"));
+    assert!(steps.iter().all(|step| step.updated_at > before));
+    assert!(planner.get_plan(&id).await.unwrap().unwrap().updated_at > before);
+
+    complete_next_step(&planner, plan).await;
+    assert_eq!(planner.reset_plan_steps(&id, true).await.unwrap(), 4);
+    let steps = planner.get_steps(&id).await.unwrap();
+    assert!(steps.iter().all(|step| step.result.is_none()));
+
+    planner
+        .archive_plan(&ArchivePlan {
+            id: plan,
+            force: true,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(
+        planner.reset_plan_steps(&id, true).await,
+        Err(PlannerError::InvalidInput { ref field, .. }) if field == "id"
+    ));
+    assert!(matches!(
+        planner.reset_plan_steps(&Id { id: 999 }, true).await,
+        Err(PlannerError::PlanNotFound { id: 999 })
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_claims_during_reset() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("shared.db");
+    let build = || {
+        PlannerBuilder::new()
+            .with_database_path(Some(&db_path))
+            .with_busy_timeout(Duration::from_secs(10))
+            .build()
+    };
+    let planners = [build().await.unwrap(), build().await.unwrap()];
+
+    let plan = create_plan_with_steps(&planners[0], "Recurring", None, 0, 20).await;
+
+    let claimer = planners[0].clone();
+    let claims = tokio::spawn(async move {
+        while claimer
+            .claim_next_step(&Id { id: plan })
+            .await
+            .unwrap_or_else(|e| panic!("claim_next_step failed: {e}"))
+            .is_some()
+        {}
+    });
+    for _ in 0..5 {
+        assert_eq!(
+            planners[1]
+                .reset_plan_steps(&Id { id: plan }, true)
+                .await
+                .unwrap(),
+            20
+        );
+    }
+    claims.await.unwrap();
+
+    // Every step is either claimed after the last reset or reset after its
+    // claim, never a mix of the two
+    let steps = planners[1].get_steps(&Id { id: plan }).await.unwrap();
+    for step in steps.iter() {
+        match step.status {
+            StepStatus::InProgress => assert!(step.started_at.is_some(), "{step:?}"),
+            StepStatus::Todo => assert!(step.started_at.is_none(), "{step:?}"),
+            status => panic!("step {} is {status:?}", step.id),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_writers_on_one_file() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("shared.db");
+    // Separate planners stand in for the CLI and an MCP server
+    let build = || {
+        PlannerBuilder::new()
+            .with_database_path(Some(&db_path))
+            .with_busy_timeout(Duration::from_secs(10))
+            .build()
+    };
+    let planners = [build().await.unwrap(), build().await.unwrap()];
+
+    let plan = create_plan_with_steps(&planners[0], "Shared", None, 0, 20).await;
+    let steps: Vec<u64> = planners[0]
+        .get_steps(&Id { id: plan })
+        .await
+        .unwrap()
+        .iter()
+        .map(|step| step.id)
+        .collect();
+
+    let mut tasks = Vec::new();
+    for worker in 0..8 {
+        let planner = planners[worker % 2].clone();
+        let steps = steps.clone();
+        tasks.push(tokio::spawn(async move {
+            let mut claimed = 0;
+            for i in 0..10 {
+                if worker % 2 == 0 {
+                    planner
+                        .add_step(&StepCreate {
+                            plan_id: plan,
+                            title: format!("Worker {worker} step {i}"),
+                            ..Default::default()
+                        })
+                        .await
+                        .unwrap_or_else(|e| panic!("add_step failed: {e}"));
+                } else {
+                    for &step in &steps {
+                        let outcome = planner
+                            .claim_step(&Id { id: step })
+                            .await
+                            .unwrap_or_else(|e| panic!("claim_step failed: {e}"));
+                        claimed += usize::from(outcome.is_some());
+                    }
+                }
+            }
+            claimed
+        }));
+    }
+
+    let mut claimed = 0;
+    for task in tasks {
+        claimed += task.await.unwrap();
+    }
+    // Every step was claimed exactly once, and no add was lost
+    assert_eq!(claimed, steps.len());
+    let all = planners[1].get_steps(&Id { id: plan }).await.unwrap();
+    assert_eq!(all.len(), 20 + 4 * 10);
+
+    assert_eq!(
+        Database::new(&db_path).unwrap().journal_mode().unwrap(),
+        "wal"
+    );
+}
+
+#[tokio::test]
+async fn test_list_plans_children_of() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let parent = create_plan_with_steps(&planner, "Parent", None, 0, 0).await;
+    let first = create_plan_with_steps(&planner, "First", Some(parent), 0, 0).await;
+    let second = create_plan_with_steps(&planner, "Second", Some(parent), 0, 0).await;
+    let other_parent = create_plan_with_steps(&planner, "Other Parent", None, 0, 0).await;
+    create_plan_with_steps(&planner, "Other Child", Some(other_parent), 0, 0).await;
+    planner
+        .archive_plan(&ArchivePlan {
+            id: second,
+            force: false,
+        })
+        .await
+        .unwrap();
+
+    let ids = |summaries: &[PlanSummary]| {
+        let mut ids: Vec<u64> = summaries.iter().map(|s| s.id).collect();
+        ids.sort();
+        ids
+    };
+
+    let active = planner
+        .list_plans_summary(&ListPlans {
+            children_of: Some(parent),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(ids(&active.items), vec![first]);
+
+    let archived = planner
+        .list_plans_summary(&ListPlans {
+            archived: true,
+            children_of: Some(parent),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(ids(&archived.items), vec![second]);
+
+    // Deleting the parent turns its children into top-level plans
+    planner
+        .delete_plan(&DeletePlan {
+            id: other_parent,
+            confirmed: true,
+            force: false,
+        })
+        .await
+        .unwrap();
+    let all = planner
+        .list_plans_summary(&ListPlans::default())
+        .await
+        .unwrap();
+    assert!(
+        all.items
+            .iter()
+            .all(|s| s.parent_plan_id != Some(other_parent))
+    );
+    assert!(all.items.iter().any(|s| s.title == "Other Child"));
+}
+
+#[tokio::test]
+async fn test_replaced_database_file_is_picked_up() {
+    let (temp_dir, planner) = create_test_planner().await;
+    planner
+        .create_plan(&CreatePlan {
+            title: "Original".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+
+    // Prepare a different database and swap it in atomically, as backup
+    // tooling restoring a snapshot would
+    let replacement_path = temp_dir.path().join("replacement.db");
+    let replacement = PlannerBuilder::new()
+        .with_database_path(Some(replacement_path.clone()))
+        .build()
+        .await
+        .expect("Failed to create replacement planner");
+    replacement
+        .create_plan(&CreatePlan {
+            title: "Restored".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+    // Closing the last connection checkpoints the write-ahead log into the
+    // file, so that it is complete on its own
+    drop(replacement);
+    std::fs::rename(&replacement_path, temp_dir.path().join("test.db"))
+        .expect("Failed to replace database file");
+
+    let plan = planner
+        .get_plan(&Id { id: 1 })
+        .await
+        .expect("Failed to get plan")
+        .expect("Plan not found");
+    assert_eq!(plan.title, "Restored");
+
+    // The pooled connection to the old file was replaced by a new one
+    assert_eq!(planner.connections_opened(), 2);
+    assert_eq!(planner.reconnects(), 1);
+}
+
+#[tokio::test]
+async fn test_deleted_database_file_is_unavailable() {
+    let (temp_dir, planner) = create_test_planner().await;
+    planner
+        .create_plan(&CreatePlan {
+            title: "Original".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+
+    let db_path = temp_dir.path().join("test.db");
+    let moved_path = temp_dir.path().join("moved.db");
+    std::fs::rename(&db_path, &moved_path).expect("Failed to move database file");
+
+    // Nothing is served from, or created in place of, the missing file
+    let error = planner
+        .get_plan(&Id { id: 1 })
+        .await
+        .expect_err("Missing database was read");
+    assert!(matches!(error, PlannerError::DatabaseUnavailable { .. }));
+    assert!(error.is_unavailable() && error.is_recoverable());
+    assert!(!db_path.exists());
+
+    // Restoring the file brings the planner back
+    std::fs::rename(&moved_path, &db_path).expect("Failed to restore database file");
+    let plan = planner
+        .get_plan(&Id { id: 1 })
+        .await
+        .expect("Failed to get plan")
+        .expect("Plan not found");
+    assert_eq!(plan.title, "Original");
+}
+
+#[tokio::test]
+async fn test_operations_reuse_connections() {
+    let (temp_dir, planner) = create_test_planner().await;
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Pooled".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+
+    // Sequential operations keep using the connection opened by build()
+    for _ in 0..100 {
+        planner
+            .get_plan(&Id { id: plan.id })
+            .await
+            .expect("Failed to get plan")
+            .expect("Plan not found");
+    }
+    assert_eq!(planner.connections_opened(), 1);
+
+    // Clones share the pool
+    let clone = planner.clone();
+    clone.get_plan(&Id { id: plan.id }).await.unwrap();
+    assert_eq!(planner.connections_opened(), 1);
+
+    // Without a pool, every operation opens a connection
+    let unpooled = PlannerBuilder::new()
+        .with_database_path(Some(temp_dir.path().join("test.db")))
+        .with_connection_pool_size(0)
+        .build()
+        .await
+        .expect("Failed to create planner");
+    let opened = unpooled.connections_opened();
+    for _ in 0..3 {
+        unpooled.get_plan(&Id { id: plan.id }).await.unwrap();
+    }
+    assert_eq!(unpooled.connections_opened(), opened + 3);
+}
+
+#[tokio::test]
+async fn test_read_only_planner_reads_but_rejects_writes() {
+    let (temp_dir, planner) = create_test_planner().await;
+    let db_path = temp_dir.path().join("test.db");
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Frozen".to_string(),
+            directory: Some("/srv/frozen".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+    let step = planner
+        .add_step(&StepCreate {
+            plan_id: plan.id,
+            title: "Look, don't touch".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to add step");
+
+    let read_only = PlannerBuilder::new()
+        .with_database_path(Some(&db_path))
+        .read_only(true)
+        .build()
+        .await
+        .expect("Failed to create read-only planner");
+    assert!(read_only.is_read_only());
+    assert!(!planner.is_read_only());
+
+    // Reads work as usual
+    let shown = read_only
+        .get_plan(&Id { id: plan.id })
+        .await
+        .expect("Failed to get plan")
+        .expect("Plan not found");
+    assert_eq!(shown.steps.len(), 1);
+    assert_eq!(read_only.list_plans(None).await.unwrap().len(), 1);
+    let found = read_only
+        .search_plans_by_directory(&SearchPlans {
+            directory: "/srv".to_string(),
+            archived: false,
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to search plans");
+    assert_eq!(found.len(), 1);
+
+    // Writes fail before touching the database
+    let rejected = |result: Result<(), PlannerError>, operation: &str| match result {
+        Err(PlannerError::ReadOnly { operation: op }) => assert_eq!(op, operation),
+        other => panic!("Expected {operation} to be refused, got {other:?}"),
+    };
+    rejected(
+        read_only
+            .create_plan(&CreatePlan {
+                title: "New".to_string(),
+                ..Default::default()
+            })
+            .await
+            .map(drop),
+        "create_plan_with_steps",
+    );
+    rejected(
+        read_only
+            .add_step(&StepCreate {
+                plan_id: plan.id,
+                title: "New".to_string(),
+                ..Default::default()
+            })
+            .await
+            .map(drop),
+        "add_step",
+    );
+    rejected(
+        read_only
+            .update_step_validated(&UpdateStep {
+                id: step.id,
+                status: Some("inprogress".to_string()),
+                ..Default::default()
+            })
+            .await
+            .map(drop),
+        "update_step",
+    );
+    rejected(
+        read_only.claim_step(&Id { id: step.id }).await.map(drop),
+        "claim_step",
+    );
+    rejected(
+        read_only
+            .set_setting("auto_archive", "true")
+            .await
+            .map(drop),
+        "set_setting",
+    );
+
+    // Nothing changed
+    let after = planner
+        .get_plan(&Id { id: plan.id })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(after, shown);
+    assert_eq!(after.steps[0].status, StepStatus::Todo);
+}
+
+#[tokio::test]
+async fn test_read_only_planner_requires_existing_database() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let missing = temp_dir.path().join("missing/none.db");
+    let result = PlannerBuilder::new()
+        .with_database_path(Some(&missing))
+        .read_only(true)
+        .build()
+        .await;
+    assert!(result.is_err());
+    assert!(!missing.parent().unwrap().exists());
+}
+
+#[tokio::test]
+async fn test_in_memory_planner_keeps_data_across_operations() {
+    let builder = PlannerBuilder::new()
+        .in_memory()
+        .with_connection_pool_size(0);
+    assert!(builder.is_in_memory());
+    assert_eq!(builder.resolved_path().unwrap(), Path::new(":memory:"));
+    let planner = builder.build().await.expect("Failed to create planner");
+
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Ephemeral".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    planner
+        .add_step(&StepCreate {
+            plan_id: plan.id,
+            title: "Step".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    // Every operation opened and closed a connection of its own
+    let shown = planner
+        .get_plan(&Id { id: plan.id })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(shown.title, "Ephemeral");
+    assert_eq!(shown.steps.len(), 1);
+
+    // Clones share the database, and concurrent operations take turns
+    let writes = (0..8).map(|index| {
+        let planner = planner.clone();
+        tokio::spawn(async move {
+            planner
+                .add_step(&StepCreate {
+                    plan_id: plan.id,
+                    title: format!("Concurrent {index}"),
+                    ..Default::default()
+                })
+                .await
+        })
+    });
+    for write in writes.collect::<Vec<_>>() {
+        write.await.unwrap().unwrap();
+    }
+    let shown = planner
+        .get_plan(&Id { id: plan.id })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(shown.steps.len(), 9);
+}
+
+#[tokio::test]
+async fn test_in_memory_planners_do_not_share_data() {
+    let first = PlannerBuilder::new().in_memory().build().await.unwrap();
+    let second = PlannerBuilder::new()
+        .with_database_path(Some(":memory:"))
+        .build()
+        .await
+        .unwrap();
+
+    first
+        .create_plan(&CreatePlan {
+            title: "Only in the first".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let listed = |planner: beacon_core::Planner| async move {
+        planner
+            .list_plans_summary(&ListPlans::default())
+            .await
+            .unwrap()
+            .items
+            .len()
+    };
+    assert_eq!(listed(first).await, 1);
+    assert_eq!(listed(second).await, 0);
+
+    // There is no file to open without writing to
+    let result = PlannerBuilder::new()
+        .in_memory()
+        .read_only(true)
+        .build()
+        .await;
+    assert!(matches!(result, Err(PlannerError::Configuration { .. })));
+}
+
+/// Subscriber recording the spans created while it is the default, as
+/// their name followed by their `operation` field if any, and the warnings.
+#[derive(Clone, Default)]
+struct TraceRecorder {
+    spans: Arc<Mutex<Vec<String>>>,
+    warnings: Arc<Mutex<Vec<String>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+/// Appends the `operation` field of a span, or the message of an event.
+struct FieldRecorder<'a>(&'a mut String);
+
+impl Visit for FieldRecorder<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "operation" {
+            self.0.push_str(&format!(" {value}"));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0.push_str(&format!("{value:?}"));
+        }
+    }
+}
+
+impl Subscriber for TraceRecorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> SpanId {
+        let mut name = span.metadata().name().to_string();
+        span.record(&mut FieldRecorder(&mut name));
+        self.spans.lock().unwrap().push(name);
+        SpanId::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _: &SpanId, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &SpanId, _: &SpanId) {}
+
+    fn event(&self, event: &Event<'_>) {
+        if *event.metadata().level() == Level::WARN {
+            let mut message = String::new();
+            event.record(&mut FieldRecorder(&mut message));
+            self.warnings.lock().unwrap().push(message);
+        }
+    }
+
+    fn enter(&self, _: &SpanId) {}
+
+    fn exit(&self, _: &SpanId) {}
+}
+
+#[tokio::test]
+async fn test_planner_operations_emit_tracing_spans() {
+    let recorder = TraceRecorder::default();
+    let _default = tracing::subscriber::set_default(recorder.clone());
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let planner = PlannerBuilder::new()
+        .with_database_path(Some(temp_dir.path().join("test.db")))
+        .with_slow_query_threshold(Duration::ZERO)
+        .build()
+        .await
+        .expect("Failed to create planner");
+
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Traced".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    planner
+        .get_plan(&Id { id: plan.id })
+        .await
+        .unwrap()
+        .unwrap();
+    planner
+        .list_plans_summary(&ListPlans::default())
+        .await
+        .unwrap();
+
+    // Planner methods wrap the database operations they run, on the
+    // blocking threads as well
+    let spans = recorder.spans.lock().unwrap().clone();
+    for expected in [
+        "create_plan",
+        "database create_plan_with_steps",
+        "get_plan",
+        "database get_plan",
+        "list_plans_summary",
+        "database list_plan_summaries_page",
+    ] {
+        assert!(
+            spans.iter().any(|span| span == expected),
+            "{expected} in {spans:?}"
+        );
+    }
+    let create = spans.iter().position(|span| span == "create_plan");
+    let inserted = spans
+        .iter()
+        .position(|span| span == "database create_plan_with_steps");
+    assert!(create < inserted, "{spans:?}");
+
+    // Every operation was slow, but only the first one is reported: reading
+    // the settings the new plan is created with
+    let warnings = recorder.warnings.lock().unwrap().clone();
+    assert_eq!(warnings, ["Slow database operation load_settings"]);
+}
+
+/// Helper function to create a test planner
+pub async fn create_test_planner() -> (TempDir, beacon_core::Planner) {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("test.db");
+    let planner = PlannerBuilder::new()
+        .with_database_path(Some(db_path))
+        .build()
+        .await
+        .expect("Failed to create planner");
+    (temp_dir, planner)
+}
+
+/// Marks the first step of a plan that is still open as done.
+async fn complete_next_step(planner: &beacon_core::Planner, plan_id: u64) {
+    let plan = planner
+        .get_plan(&Id { id: plan_id })
+        .await
+        .expect("Failed to get plan")
+        .expect("Plan not found");
+    let step = plan
+        .steps
+        .iter()
+        .find(|step| step.status != StepStatus::Done)
+        .expect("No open step left");
+    planner
+        .update_step_validated(&UpdateStep {
+            id: step.id,
+            status: Some("done".to_string()),
+            result: Some("Done".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to complete step");
+}
+
+async fn plan_status(planner: &beacon_core::Planner, plan_id: u64) -> PlanStatus {
+    planner
+        .get_plan(&Id { id: plan_id })
+        .await
+        .expect("Failed to get plan")
+        .expect("Plan not found")
+        .status
+}
+
+async fn plan_updated_at(planner: &beacon_core::Planner, plan_id: u64) -> jiff::Timestamp {
+    planner
+        .get_plan(&Id { id: plan_id })
+        .await
+        .expect("Failed to get plan")
+        .expect("Plan not found")
+        .updated_at
+}
+
+#[tokio::test]
+async fn test_auto_archive_on_final_completion() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let auto = planner
+        .create_plan(&CreatePlan {
+            title: "Auto".to_string(),
+            auto_archive: Some(true),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+    assert!(auto.auto_archive);
+    for i in 0..2 {
+        planner
+            .add_step(&StepCreate {
+                plan_id: auto.id,
+                title: format!("Step {i}"),
+                ..Default::default()
+            })
+            .await
+            .expect("Failed to add step");
+    }
+    let manual = create_plan_with_steps(&planner, "Manual", None, 0, 2).await;
+
+    // Completing all but the last step leaves the plan active
+    complete_next_step(&planner, auto.id).await;
+    assert_eq!(plan_status(&planner, auto.id).await, PlanStatus::Active);
+
+    complete_next_step(&planner, auto.id).await;
+    assert_eq!(plan_status(&planner, auto.id).await, PlanStatus::Archived);
+
+    // Plans without the flag stay active when completed
+    complete_next_step(&planner, manual).await;
+    complete_next_step(&planner, manual).await;
+    assert_eq!(plan_status(&planner, manual).await, PlanStatus::Active);
+}
+
+#[tokio::test]
+async fn test_auto_archive_toggled_by_update() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan_id = create_plan_with_steps(&planner, "Toggled", None, 0, 1).await;
+
+    let plan = planner
+        .update_plan(&UpdatePlan {
+            id: plan_id,
+            auto_archive: Some(true),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to update plan");
+    assert!(plan.auto_archive);
+    assert!(plan.to_string().contains("Auto-archive"));
+
+    // Other updates leave the flag untouched
+    let plan = planner
+        .update_plan(&UpdatePlan {
+            id: plan_id,
+            title: Some("Renamed".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to update plan");
+    assert!(plan.auto_archive);
+
+    complete_next_step(&planner, plan_id).await;
+    assert_eq!(plan_status(&planner, plan_id).await, PlanStatus::Archived);
+}
+
+#[tokio::test]
+async fn test_status_labels_set_and_cleared_by_update() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan_id = create_plan_with_steps(&planner, "Labelled", None, 0, 1).await;
+
+    let mut labels = StatusLabels::default();
+    labels.set(StepStatus::Done, Some("Shipped".to_string()));
+    let plan = planner
+        .update_plan(&UpdatePlan {
+            id: plan_id,
+            status_labels: Some(labels.clone()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to update plan");
+    assert_eq!(plan.status_labels, labels);
+
+    complete_next_step(&planner, plan_id).await;
+    let plan = planner
+        .get_plan(&Id { id: plan_id })
+        .await
+        .expect("Failed to get plan")
+        .expect("Plan not found");
+    assert_eq!(plan.status_labels, labels);
+    assert_eq!(plan.steps[0].status, StepStatus::Done);
+    assert!(plan.to_string().contains("✓ Shipped"));
+
+    let step_labels = planner
+        .get_step_status_labels(&Id {
+            id: plan.steps[0].id,
+        })
+        .await
+        .expect("Failed to get status labels");
+    assert_eq!(step_labels, labels);
+
+    // Invalid labels are rejected
+    let mut invalid = StatusLabels::default();
+    invalid.set(StepStatus::Todo, Some(String::new()));
+    let result = planner
+        .update_plan(&UpdatePlan {
+            id: plan_id,
+            status_labels: Some(invalid),
+            ..Default::default()
+        })
+        .await;
+    assert!(matches!(result, Err(PlannerError::InvalidInput { .. })));
+
+    // Empty labels clear the mapping
+    let plan = planner
+        .update_plan(&UpdatePlan {
+            id: plan_id,
+            status_labels: Some(StatusLabels::default()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to update plan");
+    assert!(plan.status_labels.is_empty());
+    assert!(plan.to_string().contains("✓ Done"));
+}
+
+#[tokio::test]
+async fn test_auto_archive_waits_for_active_child_plans() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let parent_id = create_plan_with_steps(&planner, "Parent", None, 0, 1).await;
+    create_plan_with_steps(&planner, "Child", Some(parent_id), 0, 1).await;
+
+    planner
+        .update_plan(&UpdatePlan {
+            id: parent_id,
+            auto_archive: Some(true),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to update plan");
+
+    complete_next_step(&planner, parent_id).await;
+    assert_eq!(plan_status(&planner, parent_id).await, PlanStatus::Active);
+}
+
+fn step_request(title: &str, status: StepStatus, result: Option<&str>) -> CreateStepRequest {
+    CreateStepRequest {
+        title: title.to_string(),
+        description: None,
+        acceptance_criteria: None,
+        references: vec![format!("{title}-ref").into()],
+        status,
+        result: result.map(String::from),
+    }
+}
+
+#[tokio::test]
+async fn test_create_plan_with_steps() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let plan = planner
+        .create_plan_with_steps(
+            &CreatePlan {
+                title: "Imported".to_string(),
+                ..Default::default()
+            },
+            vec![
+                step_request("first", StepStatus::Done, Some("did it")),
+                step_request("second", StepStatus::Todo, Some("ignored")),
+            ],
+        )
+        .await
+        .expect("Failed to create plan with steps");
+
+    let steps: Vec<_> = plan
+        .steps
+        .iter()
+        .map(|s| (s.title.as_str(), s.status, s.result.as_deref(), s.order))
+        .collect();
+    assert_eq!(
+        steps,
+        vec![
+            ("first", StepStatus::Done, Some("did it"), 0),
+            ("second", StepStatus::Todo, None, 1),
+        ]
+    );
+    assert_eq!(plan.steps[0].references, vec!["first-ref".into()]);
+}
+
+#[tokio::test]
+async fn test_create_plan_with_steps_is_atomic() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let result = planner
+        .create_plan_with_steps(
+            &CreatePlan {
+                title: "Broken".to_string(),
+                ..Default::default()
+            },
+            vec![
+                step_request("fine", StepStatus::Done, Some("ok")),
+                step_request("missing result", StepStatus::Done, None),
+            ],
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(PlannerError::InvalidInput { ref field, .. }) if field == "result"
+    ));
+
+    let plans = planner
+        .list_plans(None)
+        .await
+        .expect("Failed to list plans");
+    assert!(plans.is_empty());
+}
+
+#[test]
+fn test_builder_resolved_path() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("nested").join("test.db");
+
+    let resolved = PlannerBuilder::new()
+        .with_database_path(Some(&db_path))
+        .resolved_path()
+        .expect("Failed to resolve path");
+    assert_eq!(resolved, db_path);
+    // Resolving has no side effects on the file system
+    assert!(!temp_dir.path().join("nested").exists());
+
+    let relative = PlannerBuilder::new()
+        .with_database_path(Some("relative.db"))
+        .resolved_path()
+        .expect("Failed to resolve path");
+    assert!(relative.is_absolute());
+    assert!(relative.ends_with("relative.db"));
+}
+
+#[tokio::test]
+async fn test_get_plan_outline() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan_id = create_plan_with_steps(&planner, "Outlined", None, 1, 2).await;
+
+    let outline = planner
+        .get_plan_outline(plan_id)
+        .await
+        .expect("Failed to get outline");
+    let plan = planner
+        .get_plan(&Id { id: plan_id })
+        .await
+        .expect("Failed to get plan")
+        .expect("Plan not found");
+
+    // Same steps, in the same order, as the full plan
+    let expected: Vec<_> = plan
+        .steps
+        .iter()
+        .map(|s| (s.id, s.order, s.title.clone(), s.status))
+        .collect();
+    let actual: Vec<_> = outline
+        .iter()
+        .map(|s| (s.id, s.order, s.title.clone(), s.status))
+        .collect();
+    assert_eq!(actual, expected);
+    assert_eq!(outline[0].status, StepStatus::Done);
+
+    let empty_id = create_plan_with_steps(&planner, "Empty", None, 0, 0).await;
+    assert!(planner.get_plan_outline(empty_id).await.unwrap().is_empty());
+
+    assert!(matches!(
+        planner.get_plan_outline(9999).await,
+        Err(PlannerError::PlanNotFound { id: 9999 })
+    ));
+}
+
+#[tokio::test]
+async fn test_list_steps_by_status() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    // Steps 0 and 1 are done, 2 to 4 are todo
+    let plan_id = create_plan_with_steps(&planner, "Listed", None, 2, 3).await;
+    let outline = planner.get_plan_outline(plan_id).await.unwrap();
+    planner
+        .update_step_validated(&UpdateStep {
+            id: outline[3].id,
+            status: Some("inprogress".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to start step");
+
+    let list = async |status: Option<&str>| {
+        planner
+            .list_steps(&ListSteps {
+                plan_id,
+                status: status.map(String::from),
+            })
+            .await
+    };
+    let ids_and_orders = |steps: Vec<StepOutline>| -> Vec<(u64, u32)> {
+        steps.iter().map(|step| (step.id, step.order)).collect()
+    };
+
+    // Filtered steps keep their position in the whole plan
+    let todo = list(Some("todo")).await.expect("Failed to list steps");
+    assert!(todo.iter().all(|step| step.status == StepStatus::Todo));
+    assert_eq!(
+        ids_and_orders(todo),
+        [(outline[2].id, 2), (outline[4].id, 4)]
+    );
+    assert_eq!(
+        ids_and_orders(list(Some("inprogress")).await.unwrap()),
+        [(outline[3].id, 3)]
+    );
+    assert_eq!(list(Some("done")).await.unwrap().len(), 2);
+    assert_eq!(
+        list(None).await.unwrap(),
+        planner.get_plan_outline(plan_id).await.unwrap()
+    );
+
+    let listed = ListedSteps(list(Some("inprogress")).await.unwrap()).to_string();
+    assert!(listed.contains(&format!("{}. Listed step 3", outline[3].id)));
+    assert!(listed.ends_with("· position 4\n"));
+
+    // Plans without matching steps give empty lists
+    let empty_id = create_plan_with_steps(&planner, "Empty", None, 0, 0).await;
+    for status in [None, Some("todo".to_string())] {
+        let steps = planner
+            .list_steps(&ListSteps {
+                plan_id: empty_id,
+                status,
+            })
+            .await
+            .expect("Failed to list steps of empty plan");
+        assert!(steps.is_empty());
+    }
+    assert_eq!(
+        ListedSteps(Vec::new()).to_string(),
+        "No matching steps found.\n"
+    );
+
+    assert!(matches!(
+        list(Some("finished")).await,
+        Err(PlannerError::InvalidInput { field, reason })
+            if field == "status" && reason.contains("Must be 'todo', 'inprogress', 'done', or 'blocked'")
+    ));
+    assert!(matches!(
+        planner
+            .list_steps(&ListSteps {
+                plan_id: 9999,
+                status: Some("todo".to_string()),
+            })
+            .await,
+        Err(PlannerError::PlanNotFound { id: 9999 })
+    ));
+}
+
+#[tokio::test]
+async fn test_find_steps() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Searched".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let mut ids = Vec::new();
+    for (title, description) in [
+        ("Set up CI", None),
+        ("Add OAuth login", Some("Store the token")),
+        ("Document authentication", None),
+        ("Show 100% coverage", Some("Read the AUTH logs")),
+    ] {
+        let step = planner
+            .add_step(&StepCreate {
+                plan_id: plan.id,
+                title: title.to_string(),
+                description: description.map(String::from),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        ids.push(step.id);
+    }
+    planner
+        .update_step_validated(&UpdateStep {
+            id: ids[2],
+            status: Some("inprogress".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let find = async |query: &str, status: Option<StepStatus>| {
+        planner
+            .find_steps(plan.id, query, status)
+            .await
+            .map(|steps| steps.iter().map(|step| step.id).collect::<Vec<_>>())
+    };
+
+    // Titles and descriptions match ignoring case, in plan order
+    assert_eq!(find("auth", None).await.unwrap(), [ids[1], ids[2], ids[3]]);
+    assert_eq!(find(" TOKEN ", None).await.unwrap(), [ids[1]]);
+    assert_eq!(
+        find("auth", Some(StepStatus::InProgress)).await.unwrap(),
+        [ids[2]]
+    );
+    // Wildcards are matched literally
+    assert_eq!(find("100%", None).await.unwrap(), [ids[3]]);
+    assert!(find("_", None).await.unwrap().is_empty());
+    // Matching steps keep their position in the whole plan
+    let steps = planner.find_steps(plan.id, "document", None).await.unwrap();
+    assert_eq!(steps[0].order, 2);
+
+    assert!(matches!(
+        find("  ", None).await,
+        Err(PlannerError::InvalidInput { field, .. }) if field == "query"
+    ));
+    assert!(matches!(
+        planner.find_steps(9999, "auth", None).await,
+        Err(PlannerError::PlanNotFound { id: 9999 })
+    ));
+}
+
+#[tokio::test]
+async fn test_list_steps_by_status_across_plans() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let mut plan_ids = Vec::new();
+    let mut step_ids = Vec::new();
+    for title in ["Backend", "Frontend", "Shelved"] {
+        let plan = planner
+            .create_plan(&CreatePlan {
+                title: title.to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        plan_ids.push(plan.id);
+        for step in ["first", "second"] {
+            let step = planner
+                .add_step(&StepCreate {
+                    plan_id: plan.id,
+                    title: format!("{title} {step}"),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            step_ids.push(step.id);
+        }
+    }
+    // Started one after another, so later ones were updated more recently
+    for id in [step_ids[1], step_ids[2], step_ids[0], step_ids[4]] {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        planner
+            .update_step_validated(&UpdateStep {
+                id,
+                status: Some("inprogress".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+    }
+    planner
+        .archive_plan(&ArchivePlan {
+            id: plan_ids[2],
+            force: true,
+        })
+        .await
+        .unwrap();
+
+    let list = async |include_archived_plans: bool| {
+        planner
+            .list_steps_by_status(StepStatus::InProgress, include_archived_plans)
+            .await
+            .unwrap()
+    };
+
+    // Most recently updated first, leaving out archived plans
+    let steps = list(false).await;
+    let ids = steps.iter().map(|(_, step)| step.id).collect::<Vec<_>>();
+    assert_eq!(ids, [step_ids[0], step_ids[2], step_ids[1]]);
+    let (plan, step) = &steps[1];
+    assert_eq!(plan.id, plan_ids[1]);
+    assert_eq!(plan.title, "Frontend");
+    assert_eq!(step.order, 0);
+    assert_eq!(steps[2].1.order, 1);
+
+    let ids = list(true)
+        .await
+        .iter()
+        .map(|(_, step)| step.id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, [step_ids[4], step_ids[0], step_ids[2], step_ids[1]]);
+
+    // Steps are grouped under their plans
+    let text = StepsByPlan::owned(steps).to_string();
+    let backend = text.find("## Backend (ID: 1)").unwrap();
+    let frontend = text.find("## Frontend (ID: 2)").unwrap();
+    assert!(backend < frontend);
+    assert!(text.find("Backend second").unwrap() < frontend);
+    assert!(text.find("Frontend first").unwrap() > frontend);
+    assert!(!text.contains("Shelved"));
+
+    let done = planner
+        .list_steps_by_status(StepStatus::Done, false)
+        .await
+        .unwrap();
+    assert!(done.is_empty());
+    assert_eq!(StepsByPlan::owned(done).to_string(), "No steps found.\n");
+}
+
+/// Summary of a plan as listed, whether it is active or archived.
+async fn plan_summary(planner: &beacon_core::Planner, plan_id: u64) -> PlanSummary {
+    for archived in [false, true] {
+        let summaries = planner
+            .list_plans_summary(&ListPlans {
+                archived,
+                ..Default::default()
+            })
+            .await
+            .expect("Failed to list plans");
+        if let Some(summary) = summaries.items.iter().find(|summary| summary.id == plan_id) {
+            return summary.clone();
+        }
+    }
+    panic!("Plan {plan_id} not listed");
+}
+
+#[tokio::test]
+async fn test_step_verification_state_machine() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Migration".to_string(),
+            auto_archive: Some(true),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+    let step = planner
+        .add_step(&StepCreate {
+            plan_id: plan.id,
+            title: "Run production migration".to_string(),
+            verification_required: true,
+            estimate_minutes: None,
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to add step");
+    assert!(step.verification_required);
+    let verify = |verifier: &str| VerifyStep {
+        id: step.id,
+        verifier: verifier.to_string(),
+        comment: Some("Checked row counts".to_string()),
+    };
+
+    // Verification is only possible once the step is done
+    let result = planner.verify_step(&verify("reviewer")).await;
+    assert!(matches!(result, Err(PlannerError::InvalidInput { .. })));
+
+    complete_next_step(&planner, plan.id).await;
+    let done = planner
+        .get_step(&Id { id: step.id })
+        .await
+        .expect("Failed to get step")
+        .expect("Step not found");
+    assert!(done.awaiting_verification());
+    assert!(done.to_string().contains("✓ Done (awaiting verification)"));
+
+    // Awaiting steps don't count as completed, so the plan stays active
+    let summary = plan_summary(&planner, plan.id).await;
+    assert_eq!(summary.completed_steps, 0);
+    assert_eq!(summary.awaiting_verification_steps, 1);
+    assert_eq!(plan_status(&planner, plan.id).await, PlanStatus::Active);
+
+    // A verifier must be named
+    let result = planner.verify_step(&verify("  ")).await;
+    assert!(matches!(result, Err(PlannerError::InvalidInput { .. })));
+
+    let verified = planner
+        .verify_step(&verify("reviewer"))
+        .await
+        .expect("Failed to verify step");
+    let verification = verified.verification.as_ref().expect("Not verified");
+    assert_eq!(verification.verifier, "reviewer");
+    assert_eq!(verification.comment.as_deref(), Some("Checked row counts"));
+    assert!(!verified.awaiting_verification());
+
+    // Verifying completes the plan
+    let summary = plan_summary(&planner, plan.id).await;
+    assert_eq!(summary.completed_steps, 1);
+    assert_eq!(summary.awaiting_verification_steps, 0);
+    assert_eq!(plan_status(&planner, plan.id).await, PlanStatus::Archived);
+
+    // A step is verified once per completion
+    let result = planner.verify_step(&verify("someone else")).await;
+    assert!(matches!(result, Err(PlannerError::InvalidInput { .. })));
+
+    // Reopening the step drops its verification
+    let reopened = planner
+        .update_step_validated(&UpdateStep {
+            id: step.id,
+            status: Some("todo".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to reopen step")
+        .expect("Step not found");
+    assert!(reopened.verification.is_none());
+    assert!(reopened.verification_required);
+}
+
+#[tokio::test]
+async fn test_verify_step_requires_opt_in() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let plan_id = create_plan_with_steps(&planner, "Plain", None, 1, 0).await;
+    let plan = planner
+        .get_plan(&Id { id: plan_id })
+        .await
+        .expect("Failed to get plan")
+        .expect("Plan not found");
+
+    let result = planner
+        .verify_step(&VerifyStep {
+            id: plan.steps[0].id,
+            verifier: "reviewer".to_string(),
+            comment: None,
+        })
+        .await;
+    assert!(matches!(result, Err(PlannerError::InvalidInput { .. })));
+
+    let result = planner
+        .verify_step(&VerifyStep {
+            id: 9999,
+            verifier: "reviewer".to_string(),
+            comment: None,
+        })
+        .await;
+    assert!(matches!(result, Err(PlannerError::StepNotFound { .. })));
+
+    // Opting in after the fact leaves the done step awaiting verification
+    let step = planner
+        .update_step_validated(&UpdateStep {
+            id: plan.steps[0].id,
+            verification_required: Some(true),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to update step")
+        .expect("Step not found");
+    assert!(step.awaiting_verification());
+    assert_eq!(plan_summary(&planner, plan_id).await.completed_steps, 0);
+}
+
+#[tokio::test]
+async fn test_merge_plans_appends_steps_in_order() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let target = create_plan_with_steps(&planner, "Target", None, 1, 1).await;
+    let source = create_plan_with_steps(&planner, "Source", None, 1, 2).await;
+    planner
+        .update_plan(&UpdatePlan {
+            id: source,
+            description: Some("Source notes".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to describe source");
+
+    // Reorder the source so that its order differs from creation order
+    let source_steps = planner.get_steps(&Id { id: source }).await.unwrap();
+    planner
+        .swap_steps(&SwapSteps {
+            step1_id: source_steps[1].id,
+            step2_id: source_steps[2].id,
+        })
+        .await
+        .expect("Failed to swap steps");
+    let done_before = planner.get_steps(&Id { id: source }).await.unwrap()[0].clone();
+
+    let merged = planner
+        .merge_plans(
+            source,
+            target,
+            MergeOptions {
+                prefix_titles: true,
+            },
+        )
+        .await
+        .expect("Failed to merge plans");
+
+    let titles: Vec<&str> = merged.steps.iter().map(|s| s.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        [
+            "Target step 0",
+            "Target step 1",
+            "Source: Source step 0",
+            "Source: Source step 2",
+            "Source: Source step 1",
+        ]
+    );
+
+    // Moved steps keep their identity, status, result and completion time
+    let moved = &merged.steps[2];
+    assert_eq!(moved.id, done_before.id);
+    assert_eq!(moved.status, StepStatus::Done);
+    assert_eq!(moved.result.as_deref(), Some("Done"));
+    assert_eq!(moved.updated_at, done_before.updated_at);
+    assert_eq!(merged.steps[3].status, StepStatus::Todo);
+
+    let description = merged.description.expect("Merged plan has a description");
+    assert!(description.starts_with("Source notes\n\n---\n\nMerged plan"));
+    assert!(description.contains(&format!("Merged plan {source} (Source)")));
+}
+
+#[tokio::test]
+async fn test_merge_plans_archives_source_with_pointer() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let target = create_plan_with_steps(&planner, "Target", None, 0, 1).await;
+    let source = create_plan_with_steps(&planner, "Source", None, 0, 1).await;
+    let child = create_plan_with_steps(&planner, "Child", Some(source), 0, 1).await;
+
+    let merged = planner
+        .merge_plans(source, target, MergeOptions::default())
+        .await
+        .expect("Failed to merge plans");
+    assert_eq!(merged.steps[1].title, "Source step 0");
+    assert_eq!(merged.merged_into_plan_id, None);
+    assert_eq!(merged.child_plans.len(), 1);
+
+    let source_plan = planner
+        .get_plan(&Id { id: source })
+        .await
+        .unwrap()
+        .expect("Merged source is kept");
+    assert_eq!(source_plan.status, PlanStatus::Archived);
+    assert_eq!(source_plan.merged_into_plan_id, Some(target));
+    assert!(source_plan.steps.is_empty());
+    assert!(
+        source_plan
+            .description
+            .unwrap()
+            .contains(&format!("Merged into plan {target} (Target)"))
+    );
+
+    let child_plan = planner.get_plan(&Id { id: child }).await.unwrap().unwrap();
+    assert_eq!(child_plan.parent_plan_id, Some(target));
+}
+
+#[tokio::test]
+async fn test_merge_plans_rejects_merged_source() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let first = create_plan_with_steps(&planner, "First", None, 0, 1).await;
+    let second = create_plan_with_steps(&planner, "Second", None, 0, 1).await;
+    let source = create_plan_with_steps(&planner, "Source", None, 0, 1).await;
+
+    let unconfirmed = planner
+        .merge_plans_confirmed(&MergePlans {
+            source_id: source,
+            target_id: first,
+            ..Default::default()
+        })
+        .await;
+    assert!(matches!(
+        unconfirmed,
+        Err(PlannerError::InvalidInput { ref field, .. }) if field == "confirmed"
+    ));
+
+    planner
+        .merge_plans_confirmed(&MergePlans {
+            source_id: source,
+            target_id: first,
+            prefix_titles: false,
+            confirmed: true,
+        })
+        .await
+        .expect("Failed to merge plans");
+
+    // Merging the same source again, anywhere, is refused
+    for target in [first, second] {
+        match planner
+            .merge_plans(source, target, MergeOptions::default())
+            .await
+        {
+            Err(PlannerError::InvalidInput { field, reason }) => {
+                assert_eq!(field, "source_id");
+                assert!(reason.contains(&format!("already merged into plan {first}")));
+            }
+            other => panic!("Expected a merged source to be refused, got {other:?}"),
+        }
+    }
+    let second_plan = planner.get_plan(&Id { id: second }).await.unwrap().unwrap();
+    assert_eq!(second_plan.steps.len(), 1);
+
+    let itself = planner
+        .merge_plans(first, first, MergeOptions::default())
+        .await;
+    assert!(matches!(itself, Err(PlannerError::InvalidInput { .. })));
+}
+
+#[tokio::test]
+async fn test_clone_plan_resets_steps() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let source = create_plan_with_steps(&planner, "Release checklist", None, 2, 1).await;
+    planner
+        .update_plan(&UpdatePlan {
+            id: source,
+            description: Some("Every sprint".to_string()),
+            directory: Some("/work/release".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to describe source");
+    let steps = planner.get_steps(&Id { id: source }).await.unwrap();
+    planner
+        .add_step_dependency(&StepDependency {
+            step_id: steps[2].id,
+            depends_on: steps[1].id,
+        })
+        .await
+        .expect("Failed to add dependency");
+    planner
+        .archive_plan(&ArchivePlan {
+            id: source,
+            force: false,
+        })
+        .await
+        .expect("Failed to archive source");
+
+    std::thread::sleep(Duration::from_millis(5));
+    let clone = planner
+        .clone_plan(&Id { id: source }, None)
+        .await
+        .expect("Failed to clone plan");
+
+    assert_ne!(clone.id, source);
+    assert_eq!(clone.title, "Release checklist");
+    assert_eq!(clone.description.as_deref(), Some("Every sprint"));
+    assert_eq!(clone.directory.as_deref(), Some("/work/release"));
+    assert_eq!(clone.status, PlanStatus::Active);
+
+    let titles: Vec<&str> = clone.steps.iter().map(|s| s.title.as_str()).collect();
+    let original: Vec<&str> = steps.iter().map(|s| s.title.as_str()).collect();
+    assert_eq!(titles, original);
+    for (copy, step) in clone.steps.iter().zip(steps.iter()) {
+        assert_ne!(copy.id, step.id);
+        assert_eq!(copy.status, StepStatus::Todo);
+        assert_eq!(copy.result, None);
+        assert!(copy.created_at > step.created_at);
+    }
+
+    // Dependencies point at the copied steps, not the originals
+    assert_eq!(clone.steps[2].depends_on, vec![clone.steps[1].id]);
+
+    // The original is left as it was
+    let source_plan = planner.get_plan(&Id { id: source }).await.unwrap().unwrap();
+    assert_eq!(source_plan.status, PlanStatus::Archived);
+    assert_eq!(source_plan.steps[0].status, StepStatus::Done);
+}
+
+#[tokio::test]
+async fn test_clone_plan_title_and_errors() {
+    let (_temp_dir, planner) = create_test_planner().await;
+    let source = create_plan_with_steps(&planner, "Template", None, 0, 1).await;
+
+    let clone = planner
+        .clone_plan(&Id { id: source }, Some("Sprint 12".to_string()))
+        .await
+        .expect("Failed to clone plan");
+    assert_eq!(clone.title, "Sprint 12");
+    assert_eq!(clone.steps.len(), 1);
+
+    assert!(matches!(
+        planner.clone_plan(&Id { id: 9999 }, None).await,
+        Err(PlannerError::PlanNotFound { id: 9999 })
+    ));
+    assert!(matches!(
+        planner.clone_plan(&Id { id: source }, Some("  ".to_string())).await,
+        Err(PlannerError::InvalidInput { ref field, .. }) if field == "title"
+    ));
+}
+
+#[tokio::test]
+async fn test_find_duplicate_steps() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let mut plan_ids = Vec::new();
+    for (title, directory, steps) in [
+        ("API", "/work/api", ["Upgrade to Rust 1.80", "Write docs"]),
+        (
+            "Web",
+            "/work/web",
+            ["upgrade to rust 1.80.", "Write the docs"],
+        ),
+        (
+            "Tools",
+            "/other/tools",
+            ["Upgrade to Rust 1.80!", "Write docs"],
+        ),
+        ("Old", "/work/old", ["Upgrade to Rust 1.80", "Write docs"]),
+    ] {
+        let plan = planner
+            .create_plan(&CreatePlan {
+                title: title.to_string(),
+                directory: Some(directory.to_string()),
+                ..Default::default()
+            })
+            .await
+            .expect("Failed to create plan");
+        for step in steps {
+            planner
+                .add_step(&StepCreate {
+                    plan_id: plan.id,
+                    title: step.to_string(),
+                    ..Default::default()
+                })
+                .await
+                .expect("Failed to add step");
+        }
+        plan_ids.push(plan.id);
+    }
+    let [api, web, tools, old] = plan_ids[..] else {
+        unreachable!()
+    };
+
+    // Archived plans and done steps are settled, so they are not duplicates
+    planner
+        .archive_plan(&ArchivePlan {
+            id: old,
+            force: false,
+        })
+        .await
+        .expect("Failed to archive plan");
+    let tools_steps = planner.get_steps(&Id { id: tools }).await.unwrap();
+    planner
+        .update_step_validated(&UpdateStep {
+            id: tools_steps[1].id,
+            status: Some("done".to_string()),
+            result: Some("Written".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to complete step");
+
+    let groups = planner
+        .find_duplicate_steps(DuplicateScope::AllPlans)
+        .await
+        .expect("Failed to find duplicates");
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].key, "upgrade to rust 1 80");
+    let plans: Vec<u64> = groups[0].steps.iter().map(|step| step.plan_id).collect();
+    assert_eq!(plans, [api, web, tools]);
+    assert_eq!(groups[0].steps[1].title, "upgrade to rust 1.80.");
+    assert_eq!(groups[0].steps[1].plan_title, "Web");
+
+    // Within /work only the API and web plans are compared
+    let groups = planner
+        .find_duplicate_steps(DuplicateScope::Directory("/work".to_string()))
+        .await
+        .expect("Failed to find duplicates");
+    let plans: Vec<u64> = groups[0].steps.iter().map(|step| step.plan_id).collect();
+    assert_eq!(plans, [api, web]);
+
+    let groups = planner
+        .find_duplicate_steps(DuplicateScope::Directory("/work/api".to_string()))
+        .await
+        .expect("Failed to find duplicates");
+    assert!(groups.is_empty());
+}
+
+/// Sets up a step in the given state, in a plan of its own. A `waiting`
+/// step depends on another step of the plan that is not done yet.
+async fn step_in_state(
+    planner: &beacon_core::Planner,
+    status: StepStatus,
+    verification_required: bool,
+    verified: bool,
+    plan_archived: bool,
+    waiting: bool,
+) -> u64 {
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Transitions".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+    let step = planner
+        .add_step(&StepCreate {
+            plan_id: plan.id,
+            title: "Step".to_string(),
+            verification_required,
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to add step");
+    if status != StepStatus::Todo {
+        planner
+            .update_step(
+                step.id,
+                UpdateStepRequest {
+                    status: Some(status),
+                    result: Some("Done".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .expect("Failed to set status");
+    }
+    if waiting {
+        let dependency = planner
+            .add_step(&StepCreate {
+                plan_id: plan.id,
+                title: "Dependency".to_string(),
+                ..Default::default()
+            })
+            .await
+            .expect("Failed to add step");
+        planner
+            .add_step_dependency(&StepDependency {
+                step_id: step.id,
+                depends_on: dependency.id,
+            })
+            .await
+            .expect("Failed to add dependency");
+    }
+    if verified {
+        planner
+            .verify_step(&VerifyStep {
+                id: step.id,
+                verifier: "reviewer".to_string(),
+                comment: None,
+            })
+            .await
+            .expect("Failed to verify step");
+    }
+    if plan_archived {
+        planner
+            .archive_plan(&ArchivePlan {
+                id: plan.id,
+                force: false,
+            })
+            .await
+            .expect("Failed to archive plan");
+    }
+    step.id
+}
+
+/// Attempts a transition through the code path that makes it, returning
+/// whether it happened.
+async fn attempt_transition(
+    planner: &beacon_core::Planner,
+    step_id: u64,
+    transition: StepTransition,
+) -> bool {
+    let id = Id { id: step_id };
+    match transition {
+        StepTransition::Claim => matches!(
+            planner.try_claim_step(&id).await,
+            Ok(ClaimOutcome::Claimed(_))
+        ),
+        StepTransition::Release => matches!(planner.release_step(&id).await, Ok(true)),
+        StepTransition::Verify => planner
+            .verify_step(&VerifyStep {
+                id: step_id,
+                verifier: "second reviewer".to_string(),
+                comment: None,
+            })
+            .await
+            .is_ok(),
+        _ => {
+            let status = transition.target().expect("Status transition");
+            planner
+                .update_step(
+                    step_id,
+                    UpdateStepRequest {
+                        status: Some(status),
+                        result: Some("Done again".to_string()),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .is_ok()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_code_paths_follow_step_state_machine() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    // (status, verification required, verified, plan archived, waiting);
+    // only done steps requiring verification can be verified
+    let mut states = Vec::new();
+    for status in StepStatus::ALL {
+        for verification_required in [false, true] {
+            for verified in [false, true] {
+                if verified && !(verification_required && status == StepStatus::Done) {
+                    continue;
+                }
+                for plan_archived in [false, true] {
+                    for waiting in [false, true] {
+                        states.push((
+                            status,
+                            verification_required,
+                            verified,
+                            plan_archived,
+                            waiting,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    for (status, verification_required, verified, plan_archived, waiting) in states {
+        let state = format!(
+            "{status} (verification required: {verification_required}, verified: {verified}, \
+             plan archived: {plan_archived}, waiting: {waiting})"
+        );
+        // Denied transitions leave the step as it was, so one step serves
+        // for all of them; allowed ones each need a fresh step
+        let mut step_id = None;
+        for transition in StepTransition::ALL {
+            let id = match step_id {
+                Some(id) => id,
+                None => {
+                    step_in_state(
+                        &planner,
+                        status,
+                        verification_required,
+                        verified,
+                        plan_archived,
+                        waiting,
+                    )
+                    .await
+                }
+            };
+            let allowed = planner
+                .allowed_transitions(&Id { id })
+                .await
+                .expect("Failed to check transitions")
+                .expect("Step not found")
+                .allows(transition);
+
+            let happened = attempt_transition(&planner, id, transition).await;
+            assert_eq!(happened, allowed, "{transition} from {state}");
+            step_id = (!happened).then_some(id);
+        }
+
+        // Resetting the plan reopens its steps, so it may only do so for
+        // steps that can be reopened
+        let id = step_in_state(
+            &planner,
+            status,
+            verification_required,
+            verified,
+            plan_archived,
+            waiting,
+        )
+        .await;
+        let step = planner.get_step(&Id { id }).await.unwrap().unwrap();
+        let reopenable = planner
+            .allowed_transitions(&Id { id })
+            .await
+            .expect("Failed to check transitions")
+            .expect("Step not found")
+            .allows(StepTransition::Reopen);
+        let reset = planner
+            .reset_plan_steps(&Id { id: step.plan_id }, false)
+            .await
+            .is_ok();
+        let reopened = planner.get_step(&Id { id }).await.unwrap().unwrap().status
+            == StepStatus::Todo
+            && status != StepStatus::Todo;
+        assert!(!reopened || reopenable, "reset from {state}");
+        assert_eq!(reset, !plan_archived, "reset from {state}");
+    }
+
+    let missing = planner
+        .allowed_transitions(&Id { id: 999_999 })
+        .await
+        .expect("Failed to check transitions");
+    assert!(missing.is_none());
+}
+
+#[tokio::test]
+async fn test_setting_precedence() {
+    let (temp_dir, planner) = create_test_planner().await;
+    let db_path = temp_dir.path().join("test.db");
+    let threshold = async |planner: &beacon_core::Planner| {
+        planner
+            .get_setting("in_progress_warning_threshold")
+            .await
+            .expect("Failed to read setting")
+    };
+
+    // Defaults apply until a setting is stored
+    let setting = threshold(&planner).await;
+    assert_eq!(setting.value, "7d");
+    assert_eq!(setting.source, SettingSource::Default);
+
+    // Stored settings take effect right away, in canonical form
+    let setting = planner
+        .set_setting("in_progress_warning_threshold", "48h")
+        .await
+        .expect("Failed to change setting");
+    assert_eq!(setting.value, "2d");
+    assert_eq!(setting.source, SettingSource::Workspace);
+    assert_eq!(
+        planner
+            .settings()
+            .await
+            .expect("Failed to read settings")
+            .in_progress_warning_threshold,
+        Duration::from_secs(2 * 24 * 60 * 60)
+    );
+
+    // Other planners on the same database see them
+    let reopened = PlannerBuilder::new()
+        .with_database_path(Some(&db_path))
+        .build()
+        .await
+        .expect("Failed to build planner");
+    assert_eq!(threshold(&reopened).await, setting);
+
+    // Builder flags override stored settings, even ones stored later
+    let overridden = PlannerBuilder::new()
+        .with_database_path(Some(&db_path))
+        .in_progress_warning_threshold(Duration::from_secs(60 * 60))
+        .build()
+        .await
+        .expect("Failed to build planner");
+    let setting = threshold(&overridden).await;
+    assert_eq!(setting.value, "1h");
+    assert_eq!(setting.source, SettingSource::Override);
+    let setting = overridden
+        .set_setting("in_progress_warning_threshold", "3d")
+        .await
+        .expect("Failed to change setting");
+    assert_eq!(setting.value, "1h");
+    assert_eq!(setting.source, SettingSource::Override);
+
+    // The override doesn't leak into the stored setting, which planners
+    // built before it was changed pick up without being rebuilt
+    assert_eq!(threshold(&reopened).await.value, "3d");
+    assert_eq!(
+        planner
+            .settings()
+            .await
+            .expect("Failed to read settings")
+            .in_progress_warning_threshold,
+        Duration::from_secs(3 * 24 * 60 * 60)
+    );
+    assert_eq!(
+        reopened.list_settings().await.unwrap().len(),
+        SettingKey::ALL.len()
+    );
+}
+
+#[tokio::test]
+async fn test_settings_changed_elsewhere_apply_to_running_planners() {
+    let (temp_dir, planner) = create_test_planner().await;
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Serve".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+    let step = planner
+        .add_step(&StepCreate {
+            plan_id: plan.id,
+            title: "Report back".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to add step");
+
+    // As `b config set` would while `b serve` keeps its planner open
+    let other = PlannerBuilder::new()
+        .with_database_path(Some(&temp_dir.path().join("test.db")))
+        .build()
+        .await
+        .expect("Failed to build planner");
+    other
+        .set_setting("max_result_length", "10")
+        .await
+        .expect("Failed to change setting");
+
+    let result = planner
+        .update_step(
+            step.id,
+            UpdateStepRequest {
+                status: Some(StepStatus::Done),
+                result: Some("Far too long a result".to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+    assert!(
+        matches!(&result, Err(PlannerError::InvalidInput { field, .. }) if field == "result"),
+        "{result:?}"
+    );
+    assert_eq!(
+        planner
+            .get_setting("max_result_length")
+            .await
+            .expect("Failed to read setting")
+            .source,
+        SettingSource::Workspace
+    );
+}
+
+#[tokio::test]
+async fn test_settings_drive_plan_and_step_behavior() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    planner
+        .set_setting("auto_archive", "true")
+        .await
+        .expect("Failed to change setting");
+    planner
+        .set_setting("max_result_length", "10")
+        .await
+        .expect("Failed to change setting");
+
+    // New plans archive themselves unless they say otherwise
+    let plan = planner
+        .create_plan(&CreatePlan {
+            title: "Defaults to the setting".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+    assert!(plan.auto_archive);
+    let explicit = planner
+        .create_plan(&CreatePlan {
+            title: "Opts out".to_string(),
+            auto_archive: Some(false),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+    assert!(!explicit.auto_archive);
+
+    let step = planner
+        .add_step(&StepCreate {
+            plan_id: plan.id,
+            title: "Write it up".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to add step");
+    let complete = |result: &str| UpdateStepRequest {
+        status: Some(StepStatus::Done),
+        result: Some(result.to_string()),
+        ..Default::default()
+    };
+    match planner
+        .update_step(step.id, complete("Far too long a result"))
+        .await
+    {
+        Err(PlannerError::InvalidInput { field, reason }) => {
+            assert_eq!(field, "result");
+            assert_eq!(reason, "Result must be at most 10 characters, got 21");
+        }
+        other => panic!("Expected the result to be too long, got {other:?}"),
+    }
+    planner
+        .update_step(step.id, complete("Done"))
+        .await
+        .expect("Failed to complete step");
+    assert_eq!(plan_status(&planner, plan.id).await, PlanStatus::Archived);
+}
+
+#[tokio::test]
+async fn test_setting_validation_and_agent_whitelist() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    let result = planner.set_setting("colour", "blue").await;
+    assert!(matches!(result, Err(PlannerError::InvalidInput { field, .. }) if field == "key"));
+    let result = planner.set_setting("auto_archive", "sometimes").await;
+    assert!(matches!(result, Err(PlannerError::InvalidInput { field, .. }) if field == "value"));
+    let result = planner.set_setting("max_result_length", "0").await;
+    assert!(matches!(result, Err(PlannerError::InvalidInput { field, .. }) if field == "value"));
+    assert!(
+        planner
+            .list_settings()
+            .await
+            .unwrap()
+            .iter()
+            .all(|setting| setting.source == SettingSource::Default)
+    );
+
+    // Agents may only change agent-safe settings
+    let setting = planner
+        .set_agent_setting(&SetConfig {
+            key: "auto_archive".to_string(),
+            value: "yes".to_string(),
+        })
+        .await
+        .expect("Failed to change setting");
+    assert_eq!(setting.value, "true");
+    for key in [
+        "max_result_length",
+        "unique_plan_per_directory",
+        "infer_git_root",
+    ] {
+        let result = planner
+            .set_agent_setting(&SetConfig {
+                key: key.to_string(),
+                value: "true".to_string(),
+            })
+            .await;
+        match result {
+            Err(PlannerError::InvalidInput { field, reason }) => {
+                assert_eq!(field, "key");
+                assert!(reason.contains("b config set"), "{reason}");
+            }
+            other => panic!("Expected {key} to be refused, got {other:?}"),
+        }
+        assert_eq!(
+            planner.get_setting(key).await.unwrap().source,
+            SettingSource::Default
+        );
+    }
+}
+
+/// Creates plans covering what summaries report: done, todo and
+/// in-progress steps, a child plan, an archived plan and a stored setting.
+async fn create_listing_fixture(planner: &beacon_core::Planner, directory: &str) {
+    planner
+        .set_setting("in_progress_warning_threshold", "1h")
+        .await
+        .expect("Failed to store setting");
+
+    let parent = planner
+        .create_plan_with_steps(
+            &CreatePlan {
+                title: "Parent".to_string(),
+                directory: Some(format!("{directory}/app")),
+                ..Default::default()
+            },
+            vec![
+                step_request("Done", StepStatus::Done, Some("Done")),
+                step_request("Started", StepStatus::InProgress, None),
+                step_request("Todo", StepStatus::Todo, None),
+            ],
+        )
+        .await
+        .expect("Failed to create plan");
+
+    let child = planner
+        .create_plan(&CreatePlan {
+            title: "Child".to_string(),
+            directory: Some(directory.to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+    planner
+        .update_plan(&UpdatePlan {
+            id: child.id,
+            parent_plan_id: Some(parent.id),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to set parent");
+
+    let archived = planner
+        .create_plan(&CreatePlan {
+            title: "Archived".to_string(),
+            directory: Some(directory.to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+    planner
+        .archive_plan(&ArchivePlan {
+            id: archived.id,
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to archive plan");
+
+    planner
+        .create_plan(&CreatePlan {
+            title: "Elsewhere".to_string(),
+            directory: Some("/elsewhere".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("Failed to create plan");
+}
+
+/// Clears the age of in-progress steps, which is measured when listing and
+/// so differs between two listings.
+fn without_ages(mut summaries: Vec<PlanSummary>) -> Vec<PlanSummary> {
+    for summary in &mut summaries {
+        summary.oldest_in_progress_age = None;
+    }
+    summaries
+}
+
+#[tokio::test]
+async fn test_quick_reads_match_planner() {
+    let (temp_dir, planner) = create_test_planner().await;
+    let db_path = temp_dir.path().join("test.db");
+    let directory = temp_dir.path().join("work").display().to_string();
+    create_listing_fixture(&planner, &directory).await;
+
+    for params in [
+        ListPlans::default(),
+        ListPlans {
+            archived: true,
+            ..Default::default()
+        },
+    ] {
+        let filter = PlanFilter::try_from(&params).expect("Valid filter");
+        let full = planner
+            .list_plan_summaries(Some(filter.clone()))
+            .await
+            .expect("Failed to list plans");
+        let quick = quick_summaries(&db_path, Some(&filter)).expect("Quick listing failed");
+        assert!(!quick.is_empty());
+        assert_eq!(quick.len(), full.len());
+        assert_eq!(
+            quick
+                .iter()
+                .map(|s| s.in_progress_warning_threshold)
+                .collect::<Vec<_>>(),
+            vec![Some(Duration::from_secs(3600)); quick.len()]
+        );
+        assert_eq!(without_ages(quick), without_ages(full));
+    }
+
+    let now = Zoned::now();
+    let (momentum, by_plan) = quick_momentum(&db_path, &now).expect("Quick momentum failed");
+    assert_eq!(momentum, planner.momentum_stats(&now).await.unwrap());
+    assert_eq!(by_plan, planner.momentum_stats_by_plan(&now).await.unwrap());
+
+    // The badge counts the active plans in the directory and below it
+    let badge = quick_badge(&db_path, &directory).expect("Quick badge failed");
+    let filter = PlanFilter::builder()
+        .directory(directory.clone())
+        .build()
+        .unwrap();
+    let full = planner.list_plan_summaries(Some(filter)).await.unwrap();
+    assert_eq!(badge, PromptBadge::from_summaries(&full));
+    assert_eq!(
+        badge,
+        PromptBadge {
+            plans: 2,
+            pending_steps: 2,
+            stale_plans: 0,
+        }
+    );
+    assert_eq!(badge.to_string(), "2 plans · 2 pending");
+    assert!(
+        quick_badge(&db_path, "/nowhere")
+            .expect("Quick badge failed")
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_quick_reads_require_current_schema() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+
+    // A missing database is not created
+    let missing = temp_dir.path().join("missing.db");
+    assert!(quick_summaries(&missing, None).is_err());
+    assert!(!missing.exists());
+
+    // A database no planner initialized is not read
+    let blank = temp_dir.path().join("blank.db");
+    rusqlite::Connection::open(&blank)
+        .and_then(|conn| conn.execute_batch("CREATE TABLE notes (id INTEGER PRIMARY KEY)"))
+        .expect("Failed to create database");
+    assert!(matches!(
+        quick_summaries(&blank, None),
+        Err(PlannerError::Configuration { .. })
+    ));
+
+    // Opening it through the planner brings it up to date
+    Database::new(&blank).expect("Failed to initialize database");
+    assert_eq!(quick_summaries(&blank, None).unwrap(), vec![]);
+}
+
+#[tokio::test]
+async fn test_quick_summaries_skip_schema_initialization() {
+    let (temp_dir, planner) = create_test_planner().await;
+    let db_path = temp_dir.path().join("test.db");
+    create_listing_fixture(&planner, &temp_dir.path().display().to_string()).await;
+    drop(planner);
+
+    // Initializing the schema recreates every trigger, so one dropped from a
+    // database at the current version only stays gone if it is skipped
+    let has_trigger = || {
+        rusqlite::Connection::open(&db_path)
+            .and_then(|conn| {
+                conn.query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type = 'trigger' AND name = 'plans_touch_on_update'",
+                    [],
+                    |row| row.get::<_, i64>(0),
+                )
+            })
+            .expect("Failed to query triggers")
+            > 0
+    };
+    rusqlite::Connection::open(&db_path)
+        .and_then(|conn| conn.execute_batch("DROP TRIGGER plans_touch_on_update"))
+        .expect("Failed to drop trigger");
+    let contents = std::fs::read(&db_path).expect("Failed to read database");
+
+    let summaries = quick_summaries(&db_path, None).expect("Quick listing failed");
+    assert!(!summaries.is_empty());
+    assert_eq!(
+        std::fs::read(&db_path).expect("Failed to read database"),
+        contents
+    );
+    assert!(!has_trigger());
+
+    // The planner does initialize the schema, bringing the trigger back
+    PlannerBuilder::new()
+        .with_database_path(Some(db_path.clone()))
+        .build()
+        .await
+        .expect("Failed to create planner");
+    assert!(has_trigger());
+}
+
+#[tokio::test]
+async fn test_maintenance_reports_reclaimed_space() {
+    let (_temp_dir, planner) = create_test_planner().await;
+
+    for i in 0..10 {
+        let plan = planner
+            .create_plan(&CreatePlan {
+                title: format!("Bulky {i}"),
+                description: Some("x".repeat(20_000)),
+                ..Default::default()
+            })
+            .await
+            .expect("Failed to create plan");
+        planner
+            .delete_plan(&DeletePlan {
+                id: plan.id,
+                confirmed: true,
+                force: true,
+            })
+            .await
+            .expect("Failed to delete plan");
+    }
+
+    let report = planner
+        .maintenance()
+        .await
+        .expect("Failed to run maintenance");
+    assert!(report.size_after > 0);
+    assert!(report.size_before > report.size_after);
+    assert_eq!(
+        report.bytes_reclaimed(),
+        report.size_before - report.size_after
+    );
+    assert!(report.is_intact());
+    assert!(report.to_string().contains("- Integrity: ok"));
+
+    // The database is still usable afterwards
+    assert!(planner.list_plans(None).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_backup_and_restore() {
+    let (temp_dir, planner) = create_test_planner().await;
+    let backup_path = temp_dir.path().join("backup.db");
+
+    create_plan_with_steps(&planner, "Backed up", None, 0, 1).await;
+    planner
+        .backup_to(&backup_path)
+        .await
+        .expect("Failed to back up database");
+
+    // Changes made after the backup, including a step in progress
+    let later = create_plan_with_steps(&planner, "Later", None, 0, 1).await;
+    let step = planner
+        .get_steps(&Id { id: later })
+        .await
+        .unwrap()
+        .into_inner()
+        .remove(0);
+    planner.claim_step(&Id { id: step.id }).await.unwrap();
+    planner.set_setting("auto_archive", "true").await.unwrap();
+
+    let error = planner.restore_from(&backup_path, false).await.unwrap_err();
+    assert!(matches!(error, PlannerError::InvalidInput { ref field, .. } if field == "confirmed"));
+    assert_eq!(planner.list_plans(None).await.unwrap().len(), 2);
+
+    planner
+        .restore_from(&backup_path, true)
+        .await
+        .expect("Failed to restore database");
+    let titles = planner
+        .list_plans(None)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|plan| plan.title)
+        .collect::<Vec<_>>();
+    assert_eq!(titles, ["Backed up"]);
+    assert_eq!(
+        planner.get_setting("auto_archive").await.unwrap().source,
+        SettingSource::Default
+    );
+
+    // Nothing is in progress any more, so restoring needs no confirmation
+    planner
+        .restore_from(&backup_path, false)
+        .await
+        .expect("Failed to restore database");
+}
+
+#[tokio::test]
+async fn test_backup_and_restore_errors() {
+    let (temp_dir, planner) = create_test_planner().await;
+
+    let unwritable = temp_dir.path().join("missing").join("backup.db");
+    match planner.backup_to(&unwritable).await {
+        Err(PlannerError::FileSystem { path, .. }) => assert_eq!(path, unwritable),
+        other => panic!("Expected a file system error, got {other:?}"),
+    }
+
+    let missing = temp_dir.path().join("missing.db");
+    match planner.restore_from(&missing, true).await {
+        Err(PlannerError::FileSystem { path, .. }) => assert_eq!(path, missing),
+        other => panic!("Expected a file system error, got {other:?}"),
+    }
+    assert!(!missing.exists());
+
+    let not_beacon = temp_dir.path().join("notes.txt");
+    std::fs::write(&not_beacon, "not a database").unwrap();
+    let error = planner.restore_from(&not_beacon, true).await.unwrap_err();
+    assert!(matches!(error, PlannerError::InvalidInput { ref field, .. } if field == "file"));
+
+    let other_sqlite = temp_dir.path().join("other.db");
+    rusqlite::Connection::open(&other_sqlite)
+        .unwrap()
+        .execute_batch("CREATE TABLE things (id INTEGER)")
+        .unwrap();
+    let error = planner.restore_from(&other_sqlite, true).await.unwrap_err();
+    assert!(matches!(error, PlannerError::InvalidInput { ref field, .. } if field == "file"));
 }
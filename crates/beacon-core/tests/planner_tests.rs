@@ -1,6 +1,9 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
-use beacon_core::{CompletionFilter, PlanFilter, PlannerBuilder, StepStatus, UpdateStepRequest};
+use beacon_core::{
+    CompletionFilter, PlanFilter, PlannerBuilder, PlannerError, PlannerStats, StepStatus,
+    UpdateStepRequest,
+};
 use tempfile::TempDir;
 
 /// Helper function to create a temporary directory and database path
@@ -27,6 +30,7 @@ async fn test_complete_plan_workflow() {
             title: "Integration Test".to_string(),
             description: Some("Testing complete workflow".to_string()),
             directory: None,
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
@@ -38,7 +42,10 @@ async fn test_complete_plan_workflow() {
             title: "First step".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: Vec::new(),
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
@@ -48,7 +55,10 @@ async fn test_complete_plan_workflow() {
             title: "Second step".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: Vec::new(),
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
@@ -58,7 +68,10 @@ async fn test_complete_plan_workflow() {
             title: "Third step".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: Vec::new(),
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
@@ -121,10 +134,10 @@ async fn test_complete_plan_workflow() {
     assert_eq!(updated_steps[2].status, StepStatus::Done);
 
     // Test filtering
-    let incomplete_filter = PlanFilter {
-        completion_status: Some(CompletionFilter::Incomplete),
-        ..Default::default()
-    };
+    let incomplete_filter = PlanFilter::builder()
+        .completion(CompletionFilter::Incomplete)
+        .build()
+        .expect("Valid filter");
 
     let filtered_plans = planner
         .list_plans(Some(incomplete_filter))
@@ -151,6 +164,7 @@ async fn test_database_persistence_across_connections() {
                 title: "Test Plan".to_string(),
                 description: None,
                 directory: None,
+                ..Default::default()
             })
             .await
             .expect("Failed to create plan");
@@ -161,7 +175,10 @@ async fn test_database_persistence_across_connections() {
                 title: "Test step".to_string(),
                 description: None,
                 acceptance_criteria: None,
+                expected_output: None,
                 references: Vec::new(),
+                verification_required: false,
+                estimate_minutes: None,
             })
             .await
             .expect("Failed to add step");
@@ -216,13 +233,19 @@ async fn test_error_handling_invalid_operations() {
             title: "Invalid step".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: Vec::new(),
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await;
     assert!(result.is_err());
 
     let result = planner
-        .archive_plan(&beacon_core::params::Id { id: 999 })
+        .archive_plan(&beacon_core::params::ArchivePlan {
+            id: 999,
+            force: false,
+        })
         .await
         .expect("archive_plan should not error even for non-existent plans");
     assert!(result.is_none(), "Should return None for non-existent plan");
@@ -262,6 +285,7 @@ async fn test_plan_with_steps_retrieval() {
             title: "Test Plan".to_string(),
             description: Some("Testing step retrieval".to_string()),
             directory: None,
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
@@ -272,7 +296,10 @@ async fn test_plan_with_steps_retrieval() {
             title: "Step 1".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: Vec::new(),
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step 1");
@@ -282,7 +309,10 @@ async fn test_plan_with_steps_retrieval() {
             title: "Step 2".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: Vec::new(),
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step 2");
@@ -314,6 +344,7 @@ async fn test_step_removal() {
             title: "Step Test".to_string(),
             description: None,
             directory: None,
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
@@ -324,7 +355,10 @@ async fn test_step_removal() {
             title: "Step to keep".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: Vec::new(),
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
@@ -334,7 +368,10 @@ async fn test_step_removal() {
             title: "Step to remove".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: Vec::new(),
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
@@ -344,7 +381,10 @@ async fn test_step_removal() {
             title: "Another step to keep".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: Vec::new(),
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
@@ -380,6 +420,7 @@ async fn test_plan_archiving() {
             title: "Archive Test".to_string(),
             description: None,
             directory: None,
+            ..Default::default()
         })
         .await
         .expect("Failed to create plan");
@@ -391,7 +432,10 @@ async fn test_plan_archiving() {
             title: "Step 1".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: Vec::new(),
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
@@ -401,14 +445,20 @@ async fn test_plan_archiving() {
             title: "Step 2".to_string(),
             description: None,
             acceptance_criteria: None,
+            expected_output: None,
             references: Vec::new(),
+            verification_required: false,
+            estimate_minutes: None,
         })
         .await
         .expect("Failed to add step");
 
     // Archive the plan
     let archived_plan = planner
-        .archive_plan(&beacon_core::params::Id { id: plan.id })
+        .archive_plan(&beacon_core::params::ArchivePlan {
+            id: plan.id,
+            force: false,
+        })
         .await
         .expect("Failed to archive plan")
         .expect("Plan should exist");
@@ -422,10 +472,10 @@ async fn test_plan_archiving() {
     assert!(!active_plans.iter().any(|p| p.id == plan.id));
 
     // Verify plan is visible when including archived
-    let filter = PlanFilter {
-        include_archived: true,
-        ..Default::default()
-    };
+    let filter = PlanFilter::builder()
+        .include_archived(true)
+        .build()
+        .expect("Valid filter");
     let all_plans = planner
         .list_plans(Some(filter))
         .await
@@ -439,3 +489,142 @@ async fn test_plan_archiving() {
         .expect("Query should succeed");
     assert_eq!(steps.len(), 2);
 }
+
+#[tokio::test]
+async fn test_wait_for_change() {
+    let (_temp_dir, db_path) = create_test_environment();
+    let planner = PlannerBuilder::new()
+        .with_database_path(Some(db_path))
+        .build()
+        .await
+        .expect("Failed to create planner");
+
+    let plan = planner
+        .create_plan(&beacon_core::params::CreatePlan {
+            title: "Watched".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let id = beacon_core::params::Id { id: plan.id };
+    let short = Duration::from_millis(50);
+
+    assert_eq!(
+        planner
+            .wait_for_change_every(&id, plan.updated_at, short, Duration::from_millis(10))
+            .await
+            .unwrap(),
+        None
+    );
+
+    let (changed, added) = tokio::join!(
+        planner.wait_for_change_every(
+            &id,
+            plan.updated_at,
+            Duration::from_secs(10),
+            Duration::from_millis(10)
+        ),
+        async {
+            tokio::time::sleep(short).await;
+            planner
+                .add_step(&beacon_core::params::StepCreate {
+                    plan_id: plan.id,
+                    title: "Seen".to_string(),
+                    ..Default::default()
+                })
+                .await
+                .unwrap()
+        }
+    );
+    let changed = changed.unwrap().expect("the added step changes the plan");
+    assert!(changed > plan.updated_at);
+    assert_eq!(changed, added.created_at);
+
+    planner.delete_plan_by_id(&id).await.unwrap();
+    assert!(matches!(
+        planner.wait_for_change(&id, changed, short).await,
+        Err(PlannerError::PlanNotFound { id }) if id == plan.id
+    ));
+}
+
+#[tokio::test]
+async fn test_stats() {
+    let (_temp_dir, db_path) = create_test_environment();
+    let planner = PlannerBuilder::new()
+        .with_database_path(Some(db_path))
+        .build()
+        .await
+        .expect("Failed to create planner");
+
+    // An empty database gives a zeroed report
+    assert_eq!(planner.stats(None).await.unwrap(), PlannerStats::default());
+
+    let plan = planner
+        .create_plan(&beacon_core::params::CreatePlan {
+            title: "Reviewed".to_string(),
+            directory: Some("/work/reviewed".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    for title in ["First", "Second"] {
+        planner
+            .add_step(&beacon_core::params::StepCreate {
+                plan_id: plan.id,
+                title: title.to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+    }
+    let first = planner
+        .claim_next_step(&beacon_core::params::Id { id: plan.id })
+        .await
+        .unwrap()
+        .unwrap();
+    planner
+        .update_step(
+            first.id,
+            UpdateStepRequest {
+                status: Some(StepStatus::Done),
+                result: Some("Reviewed it".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let stats = planner
+        .stats_since(&beacon_core::params::GetStats {
+            since: Some("7d".to_string()),
+        })
+        .await
+        .unwrap();
+    assert!(stats.since.is_some());
+    assert_eq!(stats.plans_created, 1);
+    assert_eq!(stats.plans_touched, 1);
+    assert_eq!(stats.steps_created, 2);
+    assert_eq!(stats.steps_completed, 1);
+    assert!((stats.completion_rate - 0.5).abs() < f64::EPSILON);
+    assert!(stats.average_seconds_to_done.is_some());
+    assert_eq!(stats.busiest_directories.len(), 1);
+    assert_eq!(stats.busiest_directories[0].steps_completed, 1);
+
+    // A range ending before anything happened counts nothing
+    let start = jiff::Timestamp::UNIX_EPOCH;
+    let empty = planner.stats(Some((start, start))).await.unwrap();
+    assert_eq!(empty.steps_created, 0);
+    assert!(empty.busiest_directories.is_empty());
+
+    for since in ["lately", "-3d", "2999-01-01"] {
+        let result = planner
+            .stats_since(&beacon_core::params::GetStats {
+                since: Some(since.to_string()),
+            })
+            .await;
+        assert!(
+            matches!(result, Err(PlannerError::InvalidInput { ref field, .. }) if field == "since"),
+            "{since}: {result:?}"
+        );
+    }
+}